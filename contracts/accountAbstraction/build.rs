@@ -0,0 +1,45 @@
+// Generates the `contractmeta!` entries identifying this build: crate
+// version, git commit and enabled cargo features. `contractmeta!` only takes
+// literals, so they're written to a file that lib.rs includes.
+
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn main() {
+    let dirty =
+        git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+    let commit = match git(&["rev-parse", "HEAD"]) {
+        Some(commit) if dirty => format!("{commit}-dirty"),
+        Some(commit) => commit,
+        None => "unknown".to_string(),
+    };
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|f| f.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+
+    let meta = format!(
+        "soroban_sdk::contractmeta!(key = \"version\", val = \"{}\");\n\
+         soroban_sdk::contractmeta!(key = \"commit\", val = \"{commit}\");\n\
+         soroban_sdk::contractmeta!(key = \"features\", val = \"{}\");\n",
+        std::env::var("CARGO_PKG_VERSION").unwrap(),
+        features.join(","),
+    );
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("build_meta.rs");
+    std::fs::write(out, meta).unwrap();
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/index");
+}
@@ -0,0 +1,204 @@
+// ============================================================================
+// ALLOWANCES
+//
+// Pull payments for subscriptions and other recurring charges: the owner
+// approves a dApp contract to take up to an amount of one asset before an
+// expiry, and the dApp then calls `pull` as often as it bills, authorizing
+// as itself instead of needing a fresh owner signature each time. The
+// wallet makes the transfer, so the reserve still applies and a freeze
+// stops pulls like everything else.
+//
+// An allowance is a running total, not a rate: each pull spends from it and
+// the owner tops it up with another `approve`, which replaces it. Approvals
+// are bounded in time and live in temporary storage, so one the owner
+// forgets about lapses on its own.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol};
+
+use crate::fragments::ledgers_for;
+use crate::freeze::ensure_not_frozen;
+use crate::history::record_op;
+use crate::reserve::ReserveGuard;
+use crate::*;
+
+/// Furthest ahead an allowance can expire (90 days)
+pub const MAX_ALLOWANCE_WINDOW: u64 = 90 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Allowance {
+    /// What the spender can still pull
+    pub amount: i128,
+    /// Ledger timestamp after which pulls are refused
+    pub expiry: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AllowanceKey {
+    /// Allowance of (spender, asset)
+    Allowance(Address, Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowanceApprovedEvent {
+    pub spender: Address,
+    pub asset: Address,
+    pub amount: i128,
+    pub expiry: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowancePulledEvent {
+    pub spender: Address,
+    pub asset: Address,
+    pub to: Address,
+    pub amount: i128,
+    /// Allowance left after the pull
+    pub remaining: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Let `spender` pull up to `amount` of `asset` until `expiry`,
+    /// replacing any allowance it had. Owner-signed over
+    /// (spender, asset, amount, expiry).
+    pub fn approve(
+        env: Env,
+        spender: Address,
+        asset: Address,
+        amount: i128,
+        expiry: u64,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let now = now(&env);
+        if expiry <= now {
+            return Err(Error::Expired);
+        }
+        if expiry - now > MAX_ALLOWANCE_WINDOW {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (spender.clone(), asset.clone(), amount, expiry).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_high_risk_signature(&env, "approve", payload, signature)?;
+
+        let allowance = Allowance { amount, expiry };
+        let key = AllowanceKey::Allowance(spender.clone(), asset.clone());
+        let storage = env.storage().temporary();
+        storage.set(&key, &allowance);
+        let ledgers = ledgers_for(expiry - now);
+        storage.extend_ttl(&key, ledgers, ledgers);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, events::ALLOWANCE_APPROVED),
+                spender.clone(),
+            ),
+            AllowanceApprovedEvent {
+                spender,
+                asset,
+                amount,
+                expiry,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Withdraw `spender`'s allowance for `asset`. Owner-signed over
+    /// (spender, asset).
+    pub fn revoke_allowance(
+        env: Env,
+        spender: Address,
+        asset: Address,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::get_allowance(env.clone(), spender.clone(), asset.clone()).is_none() {
+            return Err(Error::NotFound);
+        }
+
+        let payload = (spender.clone(), asset.clone()).to_xdr(&env);
+        Self::require_owner_signature(&env, "revoke_allowance", payload, signature)?;
+
+        env.storage()
+            .temporary()
+            .remove(&AllowanceKey::Allowance(spender.clone(), asset.clone()));
+        env.events().publish(
+            (Symbol::new(&env, events::ALLOWANCE_REVOKED), spender),
+            asset,
+        );
+
+        Ok(())
+    }
+
+    /// Live allowance of `spender` for `asset`, if any
+    pub fn get_allowance(env: Env, spender: Address, asset: Address) -> Option<Allowance> {
+        env.storage()
+            .temporary()
+            .get::<_, Allowance>(&AllowanceKey::Allowance(spender, asset))
+            .filter(|allowance| now(&env) <= allowance.expiry)
+    }
+
+    /// Transfer `amount` of `asset` to `to` out of `spender`'s allowance.
+    /// Authorized by `spender`. Returns the allowance left.
+    pub fn pull(
+        env: Env,
+        spender: Address,
+        asset: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        spender.require_auth();
+        ensure_not_frozen(&env)?;
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let mut allowance = Self::get_allowance(env.clone(), spender.clone(), asset.clone())
+            .ok_or(Error::NotFound)?;
+        if amount > allowance.amount {
+            return Err(Error::LimitExceeded);
+        }
+        large_transfers::check_transfer(&env, &asset, amount)?;
+        contacts::check_destination(&env, &to)?;
+        fiat_limits::check_transfer(&env, &asset, amount)?;
+
+        allowance.amount -= amount;
+        let key = AllowanceKey::Allowance(spender.clone(), asset.clone());
+        if allowance.amount == 0 {
+            env.storage().temporary().remove(&key);
+        } else {
+            env.storage().temporary().set(&key, &allowance);
+        }
+
+        let reserve = ReserveGuard::new(&env);
+        token::Client::new(&env, &asset).transfer(&env.current_contract_address(), &to, &amount);
+        reserve.check(&env)?;
+
+        record_op(
+            &env,
+            OpKind::AllowancePull,
+            &asset,
+            -amount,
+            Some(to.clone()),
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::ALLOWANCE_PULLED), spender.clone()),
+            AllowancePulledEvent {
+                spender,
+                asset,
+                to,
+                amount,
+                remaining: allowance.amount,
+            },
+        );
+
+        Ok(allowance.amount)
+    }
+}
@@ -0,0 +1,220 @@
+// ============================================================================
+// NEW KEY APPROVALS
+//
+// Account takeovers almost always act through the newest key. With a
+// new-key window set, an owner key installed less than that long ago can't
+// change high-risk settings on its own: it first proposes the change, and
+// the change only goes through once a signer older than the window
+// co-approves it or the window has passed since the proposal.
+// An entrypoint is high-risk when it verifies the owner through
+// `require_high_risk_signature` rather than `require_owner_signature`;
+// anything else the owner signs is unaffected. Keys added inside the window
+// (signers, devices, the passkey, guardians) can't approve, nor co-sign a
+// high-risk action, either.
+//
+// A change is identified by sha256(action || payload), so the approval
+// covers exactly the call that was proposed. Proposals live in temporary
+// storage and lapse a week after they become executable.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol};
+
+use crate::fragments::ledgers_for;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Longest new-key window the owner can set (30 days)
+pub const MAX_NEW_KEY_WINDOW: u64 = 30 * 24 * 60 * 60;
+/// How long an executable proposal is kept (7 days)
+pub const PENDING_CHANGE_LIFETIME: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingChange {
+    /// When the change can go through without co-approval
+    pub eta: u64,
+    /// Whether a signer older than the window co-approved
+    pub approved: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ApprovalKey {
+    NewKeyWindow,
+    /// When the current owner key was installed
+    OwnerSince,
+    /// When a key was last added as a signer, device, passkey or guardian
+    SignerSince(Signer),
+    PendingChange(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeProposedEvent {
+    pub change: BytesN<32>,
+    pub eta: u64,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set how long a new owner key needs approval for high-risk changes;
+    /// 0 turns the check off
+    pub fn set_new_key_window(env: Env, window: u64, signature: BytesN<64>) -> Result<(), Error> {
+        if window > MAX_NEW_KEY_WINDOW {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = window.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_new_key_window", payload, signature)?;
+
+        env.storage()
+            .instance()
+            .set(&ApprovalKey::NewKeyWindow, &window);
+
+        Ok(())
+    }
+
+    pub fn get_new_key_window(env: Env) -> u64 {
+        new_key_window(&env)
+    }
+
+    /// Propose the high-risk change sha256(action || payload). Signed by
+    /// the owner.
+    pub fn propose_change(
+        env: Env,
+        change: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = Bytes::from_array(&env, &change.to_array());
+        Self::require_owner_signature(&env, "propose_change", payload, signature)?;
+
+        let eta = now(&env).saturating_add(new_key_window(&env));
+        save_pending(
+            &env,
+            &change,
+            &PendingChange {
+                eta,
+                approved: false,
+            },
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::CHANGE_PROPOSED),),
+            ChangeProposedEvent { change, eta },
+        );
+
+        Ok(())
+    }
+
+    /// Co-approve a proposed change. Signed by a weighted signer added
+    /// before the new-key window, over the hashed action message.
+    pub fn approve_change(env: Env, change: BytesN<32>, proof: SignerProof) -> Result<(), Error> {
+        let mut pending =
+            Self::get_pending_change(env.clone(), change.clone()).ok_or(Error::NotFound)?;
+        let signer = proof.signer();
+        if !Self::get_signers(env.clone()).contains_key(signer.clone())
+            || is_new_signer(&env, &signer)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let payload = Bytes::from_array(&env, &change.to_array());
+        let message = Self::action_message(&env, "approve_change", payload)?;
+        let digest = Bytes::from_array(&env, &env.crypto().sha256(&message).to_array());
+        verify_proof(&env, &proof, &digest)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        pending.approved = true;
+        save_pending(&env, &change, &pending);
+        env.events()
+            .publish((Symbol::new(&env, events::CHANGE_APPROVED), change), signer);
+
+        Ok(())
+    }
+
+    pub fn get_pending_change(env: Env, change: BytesN<32>) -> Option<PendingChange> {
+        env.storage()
+            .temporary()
+            .get(&ApprovalKey::PendingChange(change))
+    }
+}
+
+/// Fail with `Timelocked` when a new owner key attempts the high-risk
+/// `action` without it being proposed and then approved or waited out
+pub(crate) fn check_new_key(env: &Env, action: &str, payload: &Bytes) -> Result<(), Error> {
+    if !is_new(env, owner_since(env)) {
+        return Ok(());
+    }
+
+    let change = change_id(env, action, payload);
+    let pending =
+        WalletContract::get_pending_change(env.clone(), change.clone()).ok_or(Error::Timelocked)?;
+    if !pending.approved && now(env) < pending.eta {
+        return Err(Error::Timelocked);
+    }
+    env.storage()
+        .temporary()
+        .remove(&ApprovalKey::PendingChange(change));
+    Ok(())
+}
+
+/// sha256(action || payload), what `propose_change` takes
+pub(crate) fn change_id(env: &Env, action: &str, payload: &Bytes) -> BytesN<32> {
+    let mut preimage = Bytes::new(env);
+    accesly_messages::change_preimage(&mut preimage, action, payload);
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Start the new-key window for a freshly installed owner key
+pub(crate) fn record_owner_change(env: &Env) {
+    env.storage()
+        .instance()
+        .set(&ApprovalKey::OwnerSince, &now(env));
+}
+
+/// Start the new-key window for a key given any role. Kept when the role
+/// is taken away, since the same key may still hold another one.
+pub(crate) fn record_signer_added(env: &Env, signer: &Signer) {
+    env.storage()
+        .persistent()
+        .set(&ApprovalKey::SignerSince(signer.clone()), &now(env));
+}
+
+/// Whether `signer` was added less than the new-key window ago
+pub(crate) fn is_new_signer(env: &Env, signer: &Signer) -> bool {
+    is_new(env, signer_since(env, signer))
+}
+
+fn new_key_window(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&ApprovalKey::NewKeyWindow)
+        .unwrap_or(0)
+}
+
+/// Keys installed before ages were tracked count as old
+fn owner_since(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&ApprovalKey::OwnerSince)
+        .unwrap_or(0)
+}
+
+fn signer_since(env: &Env, signer: &Signer) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&ApprovalKey::SignerSince(signer.clone()))
+        .unwrap_or(0)
+}
+
+fn is_new(env: &Env, since: u64) -> bool {
+    let window = new_key_window(env);
+    window > 0 && now(env) < since.saturating_add(window)
+}
+
+fn save_pending(env: &Env, change: &BytesN<32>, pending: &PendingChange) {
+    let key = ApprovalKey::PendingChange(change.clone());
+    let storage = env.storage().temporary();
+    storage.set(&key, pending);
+    let ledgers = ledgers_for(pending.eta.saturating_sub(now(env)) + PENDING_CHANGE_LIFETIME);
+    storage.extend_ttl(&key, ledgers, ledgers);
+}
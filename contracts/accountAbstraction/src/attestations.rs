@@ -0,0 +1,112 @@
+// ============================================================================
+// ATTESTATION SLOTS
+//
+// Named slots third parties can write a credential hash into, so verifiable
+// credentials (a credit score, proof of employment) anchor to the wallet
+// itself. The owner grants one address write access per slot and can revoke
+// it at any time; an attestor can only ever write its own slot, nothing else
+// on the wallet. A revoked attestor's last hash stays, with its address, so
+// verifiers decide whether to trust it.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Symbol};
+
+use crate::*;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub attestor: Address,
+    pub hash: BytesN<32>,
+    pub attested_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AttestationKey {
+    /// Address allowed to write the slot
+    Attestor(Symbol),
+    /// Latest attestation in the slot
+    Attestation(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationRecordedEvent {
+    pub attestor: Address,
+    pub hash: BytesN<32>,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Let `attestor` write `slot`, replacing any previous attestor
+    pub fn grant_attestor(
+        env: Env,
+        slot: Symbol,
+        attestor: Address,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = (slot.clone(), attestor.clone()).to_xdr(&env);
+        Self::require_owner_signature(&env, "grant_attestor", payload, signature)?;
+
+        env.storage()
+            .persistent()
+            .set(&AttestationKey::Attestor(slot.clone()), &attestor);
+        env.events().publish(
+            (Symbol::new(&env, events::ATTESTOR_GRANTED), slot),
+            attestor,
+        );
+
+        Ok(())
+    }
+
+    pub fn revoke_attestor(env: Env, slot: Symbol, signature: BytesN<64>) -> Result<(), Error> {
+        let attestor = Self::get_attestor(env.clone(), slot.clone()).ok_or(Error::NotFound)?;
+
+        let payload = slot.clone().to_xdr(&env);
+        Self::require_owner_signature(&env, "revoke_attestor", payload, signature)?;
+
+        env.storage()
+            .persistent()
+            .remove(&AttestationKey::Attestor(slot.clone()));
+        env.events().publish(
+            (Symbol::new(&env, events::ATTESTOR_REVOKED), slot),
+            attestor,
+        );
+
+        Ok(())
+    }
+
+    /// Write `hash` into `slot`. Authorized by the slot's attestor.
+    pub fn attest(env: Env, slot: Symbol, hash: BytesN<32>) -> Result<(), Error> {
+        let attestor = Self::get_attestor(env.clone(), slot.clone()).ok_or(Error::Unauthorized)?;
+        attestor.require_auth();
+
+        env.storage().persistent().set(
+            &AttestationKey::Attestation(slot.clone()),
+            &Attestation {
+                attestor: attestor.clone(),
+                hash: hash.clone(),
+                attested_at: env.ledger().timestamp(),
+            },
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::ATTESTATION_RECORDED), slot),
+            AttestationRecordedEvent { attestor, hash },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_attestor(env: Env, slot: Symbol) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&AttestationKey::Attestor(slot))
+    }
+
+    pub fn get_attestation(env: Env, slot: Symbol) -> Option<Attestation> {
+        env.storage()
+            .persistent()
+            .get(&AttestationKey::Attestation(slot))
+    }
+}
@@ -0,0 +1,273 @@
+// ============================================================================
+// AUTH POLICY MATRIX
+//
+// Every owner-signed entrypoint takes the owner key alone by default, the
+// same as a payment. The owner can raise that per action (rotating the key,
+// changing batch limits, linking a device, upgrading):
+//
+//   Single            the owner key
+//   Devices(n)        the owner key plus n - 1 other linked devices,
+//                     registered signers or the passkey
+//   OwnerAndGuardian  the owner key plus a guardian
+//
+// Co-signers sign first, each calling `cosign_action` on the change id
+// sha256(action || payload) (as `propose_change` takes it), and the owner's
+// call then goes through `require_owner_signature`, which uses the
+// co-signatures up. Co-signers are checked again at that point, so a device
+// unlinked in between no longer counts.
+//
+// The matrix itself can only be edited at its highest level: whatever every
+// row asks for combined, so a stolen owner key can't lower a row on its own.
+// A few actions exist to get past a safeguard and always need a second
+// device, whatever their row says.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Map, Symbol, Vec};
+
+use crate::approvals::{change_id, PENDING_CHANGE_LIFETIME};
+use crate::devices::is_device;
+use crate::fragments::ledgers_for;
+use crate::rotation::is_registered;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Most actions the matrix can raise
+pub const MAX_AUTH_POLICIES: u32 = 32;
+
+/// Actions that need at least `Devices(2)`, set in the matrix or not
+const MULTI_DEVICE_ACTIONS: [&str; 1] = ["grant_velocity_bypass"];
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuthLevel {
+    Single,
+    /// Distinct devices, the owner key included
+    Devices(u32),
+    OwnerAndGuardian,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum AuthPolicyKey {
+    /// Map<Symbol, AuthLevel> of actions above `Single`
+    Policies,
+    /// Signers that co-signed a change id
+    Cosigners(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthPolicySetEvent {
+    pub action: Symbol,
+    pub level: AuthLevel,
+}
+
+/// Co-signatures an action needs on top of the owner's
+#[derive(Clone, Copy, Default)]
+struct Requirement {
+    devices: u32,
+    guardian: bool,
+}
+
+impl Requirement {
+    fn of(level: &AuthLevel) -> Self {
+        match level {
+            AuthLevel::Single => Self::default(),
+            AuthLevel::Devices(n) => Self {
+                devices: n.saturating_sub(1),
+                guardian: false,
+            },
+            AuthLevel::OwnerAndGuardian => Self {
+                devices: 0,
+                guardian: true,
+            },
+        }
+    }
+
+    fn join(self, other: Self) -> Self {
+        Self {
+            devices: self.devices.max(other.devices),
+            guardian: self.guardian || other.guardian,
+        }
+    }
+
+    fn is_none(&self) -> bool {
+        self.devices == 0 && !self.guardian
+    }
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Require `level` for the owner-signed `action`; `Single` drops the row.
+    /// Needs the highest level in the matrix.
+    pub fn set_auth_policy(
+        env: Env,
+        action: Symbol,
+        level: AuthLevel,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut policies = policies(&env);
+        match level {
+            AuthLevel::Single => {}
+            AuthLevel::Devices(n) if n < 2 => return Err(Error::InvalidAmount),
+            AuthLevel::Devices(n) if n > MAX_DEVICES => return Err(Error::LimitExceeded),
+            AuthLevel::Devices(_) => {}
+            AuthLevel::OwnerAndGuardian => {
+                if Self::get_guardians(env.clone()).is_empty() {
+                    return Err(Error::NotFound);
+                }
+            }
+        }
+        if level != AuthLevel::Single
+            && !policies.contains_key(action.clone())
+            && policies.len() >= MAX_AUTH_POLICIES
+        {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (action.clone(), level.clone()).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_auth_policy", payload, signature)?;
+
+        if level == AuthLevel::Single {
+            policies.remove(action.clone());
+        } else {
+            policies.set(action.clone(), level.clone());
+        }
+        env.storage()
+            .instance()
+            .set(&AuthPolicyKey::Policies, &policies);
+        env.events().publish(
+            (Symbol::new(&env, events::AUTH_POLICY_SET),),
+            AuthPolicySetEvent { action, level },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_auth_policy(env: Env, action: Symbol) -> AuthLevel {
+        policies(&env).get(action).unwrap_or(AuthLevel::Single)
+    }
+
+    /// Every action above `Single`
+    pub fn get_auth_policies(env: Env) -> Map<Symbol, AuthLevel> {
+        policies(&env)
+    }
+
+    /// Co-sign the change sha256(action || payload) for the owner to carry
+    /// out. Signed by a linked device, registered signer, the passkey or a
+    /// guardian, over the hashed action message.
+    pub fn cosign_action(env: Env, change: BytesN<32>, proof: SignerProof) -> Result<(), Error> {
+        let signer = proof.signer();
+        if !is_cosigner_device(&env, &signer)?
+            && !Self::get_guardians(env.clone()).contains(&signer)
+        {
+            return Err(Error::Unauthorized);
+        }
+        let mut cosigners = Self::get_cosigners(env.clone(), change.clone());
+        if cosigners.contains(&signer) {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = Bytes::from_array(&env, &change.to_array());
+        let message = Self::action_message(&env, "cosign_action", payload)?;
+        let digest = Bytes::from_array(&env, &env.crypto().sha256(&message).to_array());
+        verify_proof(&env, &proof, &digest)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        cosigners.push_back(signer.clone());
+        let key = AuthPolicyKey::Cosigners(change.clone());
+        let storage = env.storage().temporary();
+        storage.set(&key, &cosigners);
+        let ledgers = ledgers_for(PENDING_CHANGE_LIFETIME);
+        storage.extend_ttl(&key, ledgers, ledgers);
+        env.events()
+            .publish((Symbol::new(&env, events::ACTION_COSIGNED), change), signer);
+
+        Ok(())
+    }
+
+    pub fn get_cosigners(env: Env, change: BytesN<32>) -> Vec<Signer> {
+        env.storage()
+            .temporary()
+            .get(&AuthPolicyKey::Cosigners(change))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+/// Fail with `Unauthorized` unless the owner-signed `action` was co-signed
+/// as its level requires, using the co-signatures up when it was. For a
+/// `high_risk` action, keys added inside the new-key window don't count.
+pub(crate) fn check_cosigned(
+    env: &Env,
+    action: &str,
+    payload: &Bytes,
+    high_risk: bool,
+) -> Result<(), Error> {
+    let floor = if MULTI_DEVICE_ACTIONS.contains(&action) {
+        Requirement::of(&AuthLevel::Devices(2))
+    } else {
+        Requirement::default()
+    };
+    let policies = policies(env);
+    if policies.is_empty() && floor.is_none() {
+        return Ok(());
+    }
+    let required = if action == "set_auth_policy" {
+        policies
+            .values()
+            .iter()
+            .fold(Requirement::default(), |acc, level| {
+                acc.join(Requirement::of(&level))
+            })
+    } else {
+        policies
+            .get(Symbol::new(env, action))
+            .map(|level| Requirement::of(&level))
+            .unwrap_or_default()
+    }
+    .join(floor);
+    if required.is_none() {
+        return Ok(());
+    }
+
+    let change = change_id(env, action, payload);
+    let cosigners = WalletContract::get_cosigners(env.clone(), change.clone());
+    let guardians = WalletContract::get_guardians(env.clone());
+    let mut devices = 0;
+    let mut guardian = false;
+    for signer in cosigners.iter() {
+        if high_risk && approvals::is_new_signer(env, &signer) {
+            continue;
+        }
+        if is_cosigner_device(env, &signer)? {
+            devices += 1;
+        }
+        guardian |= guardians.contains(&signer);
+    }
+    if devices < required.devices || (required.guardian && !guardian) {
+        return Err(Error::Unauthorized);
+    }
+
+    env.storage()
+        .temporary()
+        .remove(&AuthPolicyKey::Cosigners(change));
+    Ok(())
+}
+
+fn policies(env: &Env) -> Map<Symbol, AuthLevel> {
+    env.storage()
+        .instance()
+        .get(&AuthPolicyKey::Policies)
+        .unwrap_or(Map::new(env))
+}
+
+/// A device other than the owner key, which already signs the action itself
+fn is_cosigner_device(env: &Env, signer: &Signer) -> Result<bool, Error> {
+    if *signer == Signer::Ed25519(WalletContract::get_owner(env.clone())?) {
+        return Ok(false);
+    }
+    Ok(
+        is_registered(env, signer)?
+            || matches!(signer, Signer::Ed25519(key) if is_device(env, key)),
+    )
+}
@@ -0,0 +1,410 @@
+// ============================================================================
+// BATCH EXECUTION
+//
+// One owner signature for a whole user action: a swap followed by a payment,
+// an approve followed by a deposit. The wallet makes each call itself, in
+// order, so callees see the wallet as the direct invoker and its
+// `require_auth` passes without further signatures. Calls that go one level
+// deeper (a router pulling tokens from the wallet) need the wallet's
+// authorization for that nested call, which each invocation carries and the
+// owner signs along with the rest. Those nested calls move the wallet's
+// funds as surely as the top-level ones, so every check on a batch (the
+// contract policy, thresholds, contacts, fees, the fiat limit) walks the
+// whole authorization tree, not just the calls the wallet makes itself.
+//
+// Any failing call reverts the whole batch. Trustlines are classic
+// operations and can't be created from a contract; they go in the same
+// transaction as separate operations.
+//
+// Independent calls (a payroll of transfers) can instead run best effort:
+// a failing call is rolled back on its own and reported, and the rest go
+// ahead, so one recipient without a trustline doesn't mean re-signing the
+// whole payroll. The owner bounds how many failures to tolerate before the
+// remaining calls are skipped.
+//
+// The relayer estimates fees before submitting, so the worst case a batch
+// can cost is bounded on-chain: the number of calls, how deep the nested
+// calls each one authorizes go, and a complexity score summing a weight per
+// function called, nested calls included. The owner can tighten the limits
+// and weigh known-heavy functions (a swap through several pools) above the
+// default; batches over any limit are rejected before anything runs.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::InvokerContractAuthEntry, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env,
+    InvokeError, Map, Symbol, Val, Vec,
+};
+
+use crate::policy::check_invocable;
+use crate::reserve::ReserveGuard;
+use crate::*;
+
+/// Default and ceiling for `BatchLimits::max_ops`
+pub const MAX_BATCH_OPS: u32 = 20;
+/// Default and ceiling for `BatchLimits::max_depth`
+pub const MAX_BATCH_DEPTH: u32 = 4;
+/// Default and ceiling for `BatchLimits::max_complexity`
+pub const MAX_BATCH_COMPLEXITY: u32 = 100;
+/// Weight of a function without one set
+pub const DEFAULT_OP_WEIGHT: u32 = 1;
+pub const MAX_OP_WEIGHTS: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchLimits {
+    pub max_ops: u32,
+    /// A call with no nested authorizations has depth 1
+    pub max_depth: u32,
+    /// Bound on the summed weights of every call in the batch
+    pub max_complexity: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum BatchKey {
+    Limits,
+    /// Map<Symbol, u32> of function name to weight
+    OpWeights,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Invocation {
+    pub contract: Address,
+    pub fn_name: Symbol,
+    pub args: Vec<Val>,
+    /// Nested calls the wallet authorizes for this invocation
+    pub auth: Vec<InvokerContractAuthEntry>,
+}
+
+/// What happened to one call of a best-effort batch
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchOpOutcome {
+    Ok,
+    /// The callee returned this contract error code
+    Failed(u32),
+    /// The callee panicked or a host function failed
+    Aborted,
+    /// Not attempted, the failure bound was already reached
+    Skipped,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchExecutedEvent {
+    pub ops: u32,
+    /// Calls that failed or were skipped; always 0 for atomic batches
+    pub failed: u32,
+    pub nonce: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchOpEvent {
+    pub index: u32,
+    pub outcome: BatchOpOutcome,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Run `ops` in order, all or nothing, and return each call's result
+    pub fn execute_batch(
+        env: Env,
+        ops: Vec<Invocation>,
+        signature: BytesN<64>,
+    ) -> Result<Vec<Val>, Error> {
+        check_ops(&env, &ops)?;
+
+        let nonce = Self::get_nonce(env.clone())?;
+        let payload = ops.clone().to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "execute_batch", payload, signature)?;
+
+        let reserve = ReserveGuard::new(&env);
+        let mut results = Vec::new(&env);
+        for op in ops.iter() {
+            results.push_back(invoke(&env, &op));
+        }
+        reserve.check(&env)?;
+
+        env.events().publish(
+            (Symbol::new(&env, events::BATCH_EXECUTED),),
+            BatchExecutedEvent {
+                ops: ops.len(),
+                failed: 0,
+                nonce,
+            },
+        );
+
+        Ok(results)
+    }
+
+    /// Run `ops` in order, rolling back and reporting each call that fails
+    /// instead of the whole batch. Once more than `max_failures` calls have
+    /// failed the rest are skipped. Only outcomes are returned, not the
+    /// calls' return values.
+    pub fn execute_batch_best_effort(
+        env: Env,
+        ops: Vec<Invocation>,
+        max_failures: u32,
+        signature: BytesN<64>,
+    ) -> Result<Vec<BatchOpOutcome>, Error> {
+        check_ops(&env, &ops)?;
+
+        let nonce = Self::get_nonce(env.clone())?;
+        let payload = (ops.clone(), max_failures).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "execute_batch_best_effort", payload, signature)?;
+
+        let reserve = ReserveGuard::new(&env);
+        let mut outcomes = Vec::new(&env);
+        let mut failed = 0u32;
+        for (index, op) in ops.iter().enumerate() {
+            let outcome = if failed > max_failures {
+                BatchOpOutcome::Skipped
+            } else {
+                try_invoke(&env, &op)
+            };
+            if outcome != BatchOpOutcome::Ok {
+                failed += 1;
+            }
+            env.events().publish(
+                (Symbol::new(&env, events::BATCH_OP),),
+                BatchOpEvent {
+                    index: index as u32,
+                    outcome,
+                },
+            );
+            outcomes.push_back(outcome);
+        }
+        reserve.check(&env)?;
+
+        env.events().publish(
+            (Symbol::new(&env, events::BATCH_EXECUTED),),
+            BatchExecutedEvent {
+                ops: ops.len(),
+                failed,
+                nonce,
+            },
+        );
+
+        Ok(outcomes)
+    }
+
+    /// Set the batch limits, each between 1 and its ceiling
+    pub fn set_batch_limits(
+        env: Env,
+        limits: BatchLimits,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if limits.max_ops == 0 || limits.max_depth == 0 || limits.max_complexity == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if limits.max_ops > MAX_BATCH_OPS
+            || limits.max_depth > MAX_BATCH_DEPTH
+            || limits.max_complexity > MAX_BATCH_COMPLEXITY
+        {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = limits.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_batch_limits", payload, signature)?;
+
+        env.storage().instance().set(&BatchKey::Limits, &limits);
+        env.events()
+            .publish((Symbol::new(&env, events::BATCH_LIMITS),), limits);
+
+        Ok(())
+    }
+
+    pub fn get_batch_limits(env: Env) -> BatchLimits {
+        batch_limits(&env)
+    }
+
+    /// Weigh calls to `fn_name` in the complexity score. 0 goes back to
+    /// the default weight.
+    pub fn set_op_weight(
+        env: Env,
+        fn_name: Symbol,
+        weight: u32,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if weight > MAX_BATCH_COMPLEXITY {
+            return Err(Error::LimitExceeded);
+        }
+        let mut weights = op_weights(&env);
+        if weight != 0 && !weights.contains_key(fn_name.clone()) && weights.len() >= MAX_OP_WEIGHTS
+        {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (fn_name.clone(), weight).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_op_weight", payload, signature)?;
+
+        if weight == 0 {
+            weights.remove(fn_name.clone());
+        } else {
+            weights.set(fn_name.clone(), weight);
+        }
+        env.storage().instance().set(&BatchKey::OpWeights, &weights);
+        env.events()
+            .publish((Symbol::new(&env, events::OP_WEIGHT), fn_name), weight);
+
+        Ok(())
+    }
+
+    /// Weights set with `set_op_weight`; other functions weigh
+    /// `DEFAULT_OP_WEIGHT`
+    pub fn get_op_weights(env: Env) -> Map<Symbol, u32> {
+        op_weights(&env)
+    }
+}
+
+pub(crate) fn batch_limits(env: &Env) -> BatchLimits {
+    env.storage()
+        .instance()
+        .get(&BatchKey::Limits)
+        .unwrap_or(BatchLimits {
+            max_ops: MAX_BATCH_OPS,
+            max_depth: MAX_BATCH_DEPTH,
+            max_complexity: MAX_BATCH_COMPLEXITY,
+        })
+}
+
+fn op_weights(env: &Env) -> Map<Symbol, u32> {
+    env.storage()
+        .instance()
+        .get(&BatchKey::OpWeights)
+        .unwrap_or(Map::new(env))
+}
+
+fn op_weight(weights: &Map<Symbol, u32>, fn_name: &Symbol) -> u32 {
+    weights.get(fn_name.clone()).unwrap_or(DEFAULT_OP_WEIGHT)
+}
+
+/// Depth and summed weight of the nested calls in `auth`
+fn auth_cost(weights: &Map<Symbol, u32>, auth: &Vec<InvokerContractAuthEntry>) -> (u32, u32) {
+    let (mut depth, mut complexity) = (0u32, 0u32);
+    for entry in auth.iter() {
+        let (entry_depth, entry_complexity) = match entry {
+            InvokerContractAuthEntry::Contract(call) => {
+                let (sub_depth, sub_complexity) = auth_cost(weights, &call.sub_invocations);
+                (
+                    sub_depth + 1,
+                    sub_complexity.saturating_add(op_weight(weights, &call.context.fn_name)),
+                )
+            }
+            // Contract creation
+            _ => (1, DEFAULT_OP_WEIGHT),
+        };
+        depth = depth.max(entry_depth);
+        complexity = complexity.saturating_add(entry_complexity);
+    }
+    (depth, complexity)
+}
+
+/// `check_calls`, then count what the batch spends against the fiat limit
+/// and tag its transfers to own accounts, for batches about to run
+pub(crate) fn check_ops(env: &Env, ops: &Vec<Invocation>) -> Result<(), Error> {
+    let calls = check_calls(env, ops)?;
+    fiat_limits::check_batch(env, &calls)?;
+    own_accounts::tag_batch(env, &calls);
+    Ok(())
+}
+
+/// Reject empty batches and batches over the limits, calls the contract
+/// policy forbids, calls back into the wallet, which the host would refuse
+/// as re-entry anyway, transfers over a large-transfer threshold or to
+/// non-contacts in contacts-only mode, and relayer fees over the cap.
+/// Nested calls the ops authorize are checked like the ops themselves.
+/// Returns every call, as `calls` does.
+pub(crate) fn check_calls(env: &Env, ops: &Vec<Invocation>) -> Result<Vec<Invocation>, Error> {
+    if ops.is_empty() {
+        return Err(Error::InvalidAmount);
+    }
+    let limits = batch_limits(env);
+    if ops.len() > limits.max_ops {
+        return Err(Error::LimitExceeded);
+    }
+    let wallet = env.current_contract_address();
+    if ops.iter().any(|op| op.contract == wallet) {
+        return Err(Error::Unauthorized);
+    }
+
+    let weights = op_weights(env);
+    let mut complexity = 0u32;
+    for op in ops.iter() {
+        let (depth, nested) = auth_cost(&weights, &op.auth);
+        if depth + 1 > limits.max_depth {
+            return Err(Error::LimitExceeded);
+        }
+        complexity = complexity
+            .saturating_add(op_weight(&weights, &op.fn_name))
+            .saturating_add(nested);
+    }
+    if complexity > limits.max_complexity {
+        return Err(Error::LimitExceeded);
+    }
+
+    // Walked only once the depth is known to be bounded
+    let calls = calls(env, ops);
+    for call in calls.iter() {
+        check_invocable(env, &call.contract, &call.fn_name)?;
+    }
+    large_transfers::check_batch(env, &calls)?;
+    contacts::check_batch(env, &calls)?;
+    fees::check_batch(env, &calls)?;
+    Ok(calls)
+}
+
+/// Every contract call `ops` make as the wallet: each op, followed by the
+/// nested calls its `auth` tree authorizes, depth first. The nested ones
+/// carry no `auth` of their own.
+pub(crate) fn calls(env: &Env, ops: &Vec<Invocation>) -> Vec<Invocation> {
+    let mut calls = Vec::new(env);
+    for op in ops.iter() {
+        calls.push_back(Invocation {
+            contract: op.contract,
+            fn_name: op.fn_name,
+            args: op.args,
+            auth: Vec::new(env),
+        });
+        push_nested(env, &mut calls, &op.auth);
+    }
+    calls
+}
+
+fn push_nested(env: &Env, calls: &mut Vec<Invocation>, auth: &Vec<InvokerContractAuthEntry>) {
+    for entry in auth.iter() {
+        // Contract creation moves no funds
+        if let InvokerContractAuthEntry::Contract(call) = entry {
+            calls.push_back(Invocation {
+                contract: call.context.contract,
+                fn_name: call.context.fn_name,
+                args: call.context.args,
+                auth: Vec::new(env),
+            });
+            push_nested(env, calls, &call.sub_invocations);
+        }
+    }
+}
+
+pub(crate) fn invoke(env: &Env, op: &Invocation) -> Val {
+    if !op.auth.is_empty() {
+        env.authorize_as_current_contract(op.auth.clone());
+    }
+    env.invoke_contract(&op.contract, &op.fn_name, op.args.clone())
+}
+
+/// Like `invoke`, but a failing call only rolls back its own changes
+fn try_invoke(env: &Env, op: &Invocation) -> BatchOpOutcome {
+    if !op.auth.is_empty() {
+        env.authorize_as_current_contract(op.auth.clone());
+    }
+    match env.try_invoke_contract::<Val, InvokeError>(&op.contract, &op.fn_name, op.args.clone()) {
+        Ok(Ok(_)) => BatchOpOutcome::Ok,
+        Err(Ok(InvokeError::Contract(code))) => BatchOpOutcome::Failed(code),
+        _ => BatchOpOutcome::Aborted,
+    }
+}
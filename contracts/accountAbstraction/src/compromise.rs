@@ -0,0 +1,176 @@
+// ============================================================================
+// COMPROMISED KEYS
+//
+// A freeze stops the wallet, but the leaked key is still trusted for what a
+// freeze leaves open (vetoing a recovery, lifting the freeze later) and by
+// relayers that keep submitting what it signs. `report_compromise` lets the
+// owner, a weighted signer, the passkey or a linked device report a key by
+// its fingerprint, with the hash of whatever evidence the app collected. In
+// one call the key is quarantined, the wallet is frozen (see `freeze`) and
+// `key_compromised` is published with the fingerprint as its second topic,
+// so relayers following the wallet's events drop the key fleet-wide.
+//
+// A quarantined key fails every signature check in the wallet with
+// `Quarantined`, whatever role it signs in: owner, signer, passkey, device,
+// session, guardian, relayer or quoter. Quarantine outlives the freeze. A
+// quarantined owner key is replaced through recovery; any other key stays
+// out until the owner releases it with `release_quarantine`. Since only the
+// owner can release a quarantine or lift a freeze, the owner key can't be
+// reported while no guardians are set up to recover the wallet: one stolen
+// device would otherwise lock it for good.
+//
+// A key's fingerprint is sha256 of its raw public key (32 bytes for
+// ed25519, 65 for P-256).
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Map, Symbol};
+
+use crate::devices::is_device;
+use crate::freeze::freeze_by;
+use crate::recovery::guardian_threshold;
+use crate::rotation::is_registered;
+use crate::signers::verify_proof;
+use crate::*;
+
+pub const MAX_QUARANTINED_KEYS: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuarantinedKey {
+    /// Hash of the evidence behind the report, kept off-chain
+    pub evidence_hash: BytesN<32>,
+    pub reported_by: Signer,
+    pub reported_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CompromiseKey {
+    /// Map<BytesN<32>, QuarantinedKey> by fingerprint
+    Quarantined,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Quarantine the key with `signer_fingerprint` and freeze the wallet.
+    /// Signed by the owner, a registered signer, the passkey or a linked
+    /// device, over the hashed action message. The owner key can only be
+    /// reported while guardian recovery is set up.
+    pub fn report_compromise(
+        env: Env,
+        signer_fingerprint: BytesN<32>,
+        evidence_hash: BytesN<32>,
+        proof: SignerProof,
+    ) -> Result<(), Error> {
+        let signer = proof.signer();
+        let trusted = is_registered(&env, &signer)?
+            || matches!(&signer, Signer::Ed25519(key) if is_device(&env, key));
+        if !trusted {
+            return Err(Error::Unauthorized);
+        }
+        let mut quarantined = quarantined(&env);
+        if quarantined.contains_key(signer_fingerprint.clone()) {
+            return Err(Error::AlreadyExists);
+        }
+        if quarantined.len() >= MAX_QUARANTINED_KEYS {
+            return Err(Error::LimitExceeded);
+        }
+        let owner = Bytes::from_array(&env, &Self::get_owner(env.clone())?.to_array());
+        let owner_fingerprint: BytesN<32> = env.crypto().sha256(&owner).into();
+        if signer_fingerprint == owner_fingerprint && guardian_threshold(&env) == 0 {
+            return Err(Error::Unauthorized);
+        }
+
+        let payload = (signer_fingerprint.clone(), evidence_hash.clone()).to_xdr(&env);
+        let message = Self::action_message(&env, "report_compromise", payload)?;
+        let digest = Bytes::from_array(&env, &env.crypto().sha256(&message).to_array());
+        verify_proof(&env, &proof, &digest)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        let record = QuarantinedKey {
+            evidence_hash,
+            reported_by: signer.clone(),
+            reported_at: now(&env),
+        };
+        quarantined.set(signer_fingerprint.clone(), record.clone());
+        save_quarantined(&env, &quarantined);
+        freeze_by(&env, signer);
+        env.events().publish(
+            (
+                Symbol::new(&env, events::KEY_COMPROMISED),
+                signer_fingerprint,
+            ),
+            record,
+        );
+
+        Ok(())
+    }
+
+    /// Let a quarantined key sign again. Owner-signed over the fingerprint.
+    pub fn release_quarantine(
+        env: Env,
+        signer_fingerprint: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut quarantined = quarantined(&env);
+        if !quarantined.contains_key(signer_fingerprint.clone()) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &signer_fingerprint.to_array());
+        Self::require_high_risk_signature(&env, "release_quarantine", payload, signature)?;
+
+        quarantined.remove(signer_fingerprint.clone());
+        save_quarantined(&env, &quarantined);
+        env.events().publish(
+            (
+                Symbol::new(&env, events::QUARANTINE_RELEASED),
+                signer_fingerprint,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_quarantined(env: Env, signer_fingerprint: BytesN<32>) -> Option<QuarantinedKey> {
+        quarantined(&env).get(signer_fingerprint)
+    }
+
+    pub fn list_quarantined(env: Env) -> Map<BytesN<32>, QuarantinedKey> {
+        quarantined(&env)
+    }
+}
+
+fn quarantined(env: &Env) -> Map<BytesN<32>, QuarantinedKey> {
+    env.storage()
+        .instance()
+        .get(&CompromiseKey::Quarantined)
+        .unwrap_or(Map::new(env))
+}
+
+fn save_quarantined(env: &Env, quarantined: &Map<BytesN<32>, QuarantinedKey>) {
+    if quarantined.is_empty() {
+        env.storage().instance().remove(&CompromiseKey::Quarantined);
+    } else {
+        env.storage()
+            .instance()
+            .set(&CompromiseKey::Quarantined, quarantined);
+    }
+}
+
+/// Fail with `Quarantined` when the public key `key` was reported
+/// compromised
+pub(crate) fn ensure_key_trusted(env: &Env, key: &Bytes) -> Result<(), Error> {
+    ensure_not_quarantined(env, env.crypto().sha256(key).into())
+}
+
+fn ensure_not_quarantined(env: &Env, fingerprint: BytesN<32>) -> Result<(), Error> {
+    if !env.storage().instance().has(&CompromiseKey::Quarantined) {
+        return Ok(());
+    }
+    if quarantined(env).contains_key(fingerprint) {
+        return Err(Error::Quarantined);
+    }
+    Ok(())
+}
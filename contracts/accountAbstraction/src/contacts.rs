@@ -0,0 +1,223 @@
+// ============================================================================
+// CONTACTS
+//
+// An address book on the wallet, and a contacts-only mode that restricts
+// outgoing transfers to it. With the mode on, a transfer from the wallet to
+// anyone but a saved contact is refused on every path that sends: the
+// authorizations in `__check_auth`, batches (and so meta-transactions and
+// scheduled calls) down to the nested transfers a batch op authorizes,
+// allowance pulls, subscription collections, queued large transfers, share
+// distributions and quote payments. Approving a spender
+// counts as paying it. Relayer fees paid by the wallet itself aren't
+// transfers the owner asked for and stay outside it.
+//
+// A thief holding the owner key would just add their own address, so a new
+// contact can only be sent to after `CONTACT_COOLING_OFF`, which gives the
+// owner's other devices a day to notice the `contact_added` event. Turning
+// the mode off waits out the same delay; turning it on applies at once.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Map, String,
+    Symbol, Val, Vec,
+};
+
+use crate::outflows::{outflow, Outflow};
+use crate::*;
+
+/// Time before a new contact can receive transfers (24 hours)
+pub const CONTACT_COOLING_OFF: u64 = 24 * 60 * 60;
+pub const MAX_CONTACTS: u32 = 50;
+pub const MAX_CONTACT_LABEL_LEN: u32 = 32;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contact {
+    pub address: Address,
+    pub label: String,
+    /// Ledger timestamp from which transfers to it are allowed
+    pub spendable_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContactsOnly {
+    pub enabled: bool,
+    /// When a switch-off takes effect; the mode holds until then
+    pub effective_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ContactsKey {
+    /// Map<Address, Contact> by address
+    Contacts,
+    ContactsOnly,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Save `address` as a contact, or relabel it. A new contact can receive
+    /// transfers after `CONTACT_COOLING_OFF`. Owner-signed over
+    /// (address, label).
+    pub fn add_contact(
+        env: Env,
+        address: Address,
+        label: String,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if label.is_empty() || label.len() > MAX_CONTACT_LABEL_LEN {
+            return Err(Error::InvalidAmount);
+        }
+        if address == env.current_contract_address() {
+            return Err(Error::InvalidOwner);
+        }
+        let mut contacts = contacts(&env);
+        let existing = contacts.get(address.clone());
+        if existing.is_none() && contacts.len() >= MAX_CONTACTS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (address.clone(), label.clone()).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "add_contact", payload, signature)?;
+
+        // Relabelling doesn't restart the cooling-off
+        let spendable_at = match existing {
+            Some(contact) => contact.spendable_at,
+            None => now(&env).saturating_add(CONTACT_COOLING_OFF),
+        };
+        let contact = Contact {
+            address: address.clone(),
+            label,
+            spendable_at,
+        };
+        contacts.set(address.clone(), contact.clone());
+        save_contacts(&env, &contacts);
+        env.events()
+            .publish((Symbol::new(&env, events::CONTACT_ADDED), address), contact);
+
+        Ok(())
+    }
+
+    /// Delete a contact; transfers to it stop being allowed right away.
+    /// Owner-signed over the address.
+    pub fn remove_contact(env: Env, address: Address, signature: BytesN<64>) -> Result<(), Error> {
+        let mut contacts = contacts(&env);
+        if !contacts.contains_key(address.clone()) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = address.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "remove_contact", payload, signature)?;
+
+        contacts.remove(address.clone());
+        save_contacts(&env, &contacts);
+        env.events()
+            .publish((Symbol::new(&env, events::CONTACT_REMOVED), address), ());
+
+        Ok(())
+    }
+
+    pub fn get_contact(env: Env, address: Address) -> Option<Contact> {
+        contacts(&env).get(address)
+    }
+
+    pub fn list_contacts(env: Env) -> Vec<Contact> {
+        contacts(&env).values()
+    }
+
+    /// Turn contacts-only mode on, at once, or off, after
+    /// `CONTACT_COOLING_OFF`. Owner-signed over `enabled`.
+    pub fn set_contacts_only(env: Env, enabled: bool, signature: BytesN<64>) -> Result<(), Error> {
+        let payload = enabled.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_contacts_only", payload, signature)?;
+
+        let now = now(&env);
+        let mode = ContactsOnly {
+            enabled,
+            effective_at: if !enabled && contacts_only(&env) {
+                now.saturating_add(CONTACT_COOLING_OFF)
+            } else {
+                now
+            },
+        };
+        env.storage()
+            .instance()
+            .set(&ContactsKey::ContactsOnly, &mode);
+        env.events()
+            .publish((Symbol::new(&env, events::CONTACTS_ONLY),), mode);
+
+        Ok(())
+    }
+
+    /// Whether outgoing transfers are restricted to contacts now
+    pub fn is_contacts_only(env: Env) -> bool {
+        contacts_only(&env)
+    }
+}
+
+fn contacts(env: &Env) -> Map<Address, Contact> {
+    env.storage()
+        .instance()
+        .get(&ContactsKey::Contacts)
+        .unwrap_or(Map::new(env))
+}
+
+fn save_contacts(env: &Env, contacts: &Map<Address, Contact>) {
+    env.storage()
+        .instance()
+        .set(&ContactsKey::Contacts, contacts);
+}
+
+fn contacts_only(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get::<_, ContactsOnly>(&ContactsKey::ContactsOnly)
+        .is_some_and(|mode| mode.enabled || now(env) < mode.effective_at)
+}
+
+/// Fail with `Unauthorized` if contacts-only mode is on and `to` isn't a
+/// contact past its cooling-off
+pub(crate) fn check_destination(env: &Env, to: &Address) -> Result<(), Error> {
+    if !contacts_only(env) {
+        return Ok(());
+    }
+    match contacts(env).get(to.clone()) {
+        Some(contact) if contact.spendable_at <= now(env) => Ok(()),
+        _ => Err(Error::Unauthorized),
+    }
+}
+
+/// Check who the call lets take funds out of the wallet, if anyone
+fn check_call(env: &Env, fn_name: &Symbol, args: &Vec<Val>) -> Result<(), Error> {
+    match outflow(env, fn_name, args) {
+        Some(Outflow { to: Some(to), .. }) => check_destination(env, &to),
+        _ => Ok(()),
+    }
+}
+
+/// Fail with `Unauthorized` if a call in `contexts` transfers to or approves
+/// anyone but a contact while contacts-only mode is on
+pub(crate) fn check_auth_contexts(env: &Env, contexts: &Vec<Context>) -> Result<(), Error> {
+    if !contacts_only(env) {
+        return Ok(());
+    }
+    for context in contexts.iter() {
+        if let Context::Contract(call) = context {
+            check_call(env, &call.fn_name, &call.args)?;
+        }
+    }
+    Ok(())
+}
+
+/// Fail with `Unauthorized` if a call of a batch, nested or not, transfers
+/// to or approves anyone but a contact while contacts-only mode is on
+pub(crate) fn check_batch(env: &Env, ops: &Vec<Invocation>) -> Result<(), Error> {
+    if !contacts_only(env) {
+        return Ok(());
+    }
+    for op in ops.iter() {
+        check_call(env, &op.fn_name, &op.args)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,312 @@
+// ============================================================================
+// AUTO-CONVERSION
+//
+// Owner-configured rules such as "convert incoming EURC to USDC", one per
+// incoming asset. The rule names the relayer allowed to trigger it, the
+// router, the worst slippage accepted and how much may be converted per
+// day; within those bounds the relayer converts without asking the owner
+// each time.
+//
+// Slippage is the price impact of the trade: what actually reached the
+// wallet against the router's quote for a trade a hundredth the size,
+// scaled up. With an oracle set (see `fiat_limits`) and fresh prices for
+// both tokens, it is measured against the oracle's rate instead, which a
+// pool pushed around in the same transaction can't move. Nothing the router
+// reports about the trade is trusted: the wallet's own balances are read
+// before and after the swap. The daily cap bounds what a badly timed
+// relayer can lose.
+//
+// What a conversion sells counts like a payment: against the large-transfer
+// thresholds, the fiat limit and a post-recovery cooldown, and the router
+// and its pair are held to the contract policy. Contacts-only mode doesn't
+// apply, the proceeds come back to the wallet.
+//
+// A rule is a standing authorization to sell, so as with the large-transfer
+// thresholds, removing or tightening it applies at once and loosening waits
+// out `LARGE_TRANSFER_DELAY`: a new rule, another router, executor or
+// target token, a higher cap or more slippage.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, token, vec, xdr::ToXdr, Address, BytesN, Env, Symbol,
+};
+
+use crate::history::record_op;
+use crate::large_transfers::LARGE_TRANSFER_DELAY;
+use crate::oracle::token_value;
+use crate::reserve::ReserveGuard;
+use crate::swap::{sell_exact, SwapRouterClient};
+use crate::*;
+
+/// Most slippage a rule can allow, in basis points
+pub const MAX_SLIPPAGE_BPS: u32 = 10_000;
+/// Size of the reference trade slippage is measured against, as a fraction
+/// of the converted amount
+const PROBE_DIVISOR: i128 = 100;
+const DAY: u64 = 24 * 60 * 60;
+/// Ledgers in a day at 5s per ledger, how long a day's counter must live
+const DAY_LEDGERS: u32 = (DAY / 5) as u32;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConversionRule {
+    pub to_token: Address,
+    pub router: Address,
+    /// Address allowed to trigger `convert`, normally the relayer
+    pub executor: Address,
+    pub max_slippage_bps: u32,
+    /// Most of the incoming asset converted per UTC day
+    pub daily_cap: i128,
+}
+
+/// A loosening rule change and when it applies
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConversionRuleChange {
+    pub rule: ConversionRule,
+    pub effective_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ConversionKey {
+    /// Rule for an incoming asset
+    Rule(Address),
+    /// Loosening change to an asset's rule waiting out the delay
+    PendingRule(Address),
+    /// Amount of an asset converted on a day (days since epoch)
+    Converted(Address, u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConversionEvent {
+    pub to_token: Address,
+    pub sold: i128,
+    pub bought: i128,
+    pub slippage_bps: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set or (with `None`) remove the rule for `from_token`. Removing or
+    /// tightening applies at once, loosening after `LARGE_TRANSFER_DELAY`.
+    /// Owner-signed over (from_token, rule).
+    pub fn set_conversion_rule(
+        env: Env,
+        from_token: Address,
+        rule: Option<ConversionRule>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if let Some(rule) = &rule {
+            if rule.to_token == from_token {
+                return Err(Error::InvalidAmount);
+            }
+            if rule.max_slippage_bps > MAX_SLIPPAGE_BPS || rule.daily_cap <= 0 {
+                return Err(Error::LimitExceeded);
+            }
+        }
+
+        let payload = (from_token.clone(), rule.clone()).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_conversion_rule", payload, signature)?;
+
+        let now = now(&env);
+        let current = Self::get_conversion_rule(env.clone(), from_token.clone());
+        let storage = env.storage().persistent();
+        let key = ConversionKey::Rule(from_token.clone());
+        let pending_key = ConversionKey::PendingRule(from_token.clone());
+        let effective_at = match &rule {
+            Some(loosened) if loosens(current.as_ref(), loosened) => {
+                // The rule in force keeps applying until the change does
+                match &current {
+                    Some(current) => storage.set(&key, current),
+                    None => storage.remove(&key),
+                }
+                let change = ConversionRuleChange {
+                    rule: loosened.clone(),
+                    effective_at: now.saturating_add(LARGE_TRANSFER_DELAY),
+                };
+                storage.set(&pending_key, &change);
+                change.effective_at
+            }
+            _ => {
+                match &rule {
+                    Some(rule) => storage.set(&key, rule),
+                    None => storage.remove(&key),
+                }
+                storage.remove(&pending_key);
+                now
+            }
+        };
+        env.events().publish(
+            (Symbol::new(&env, events::CONVERSION_RULE), from_token),
+            (rule, effective_at),
+        );
+
+        Ok(())
+    }
+
+    /// Rule in force now for `from_token`
+    pub fn get_conversion_rule(env: Env, from_token: Address) -> Option<ConversionRule> {
+        let storage = env.storage().persistent();
+        match storage
+            .get::<_, ConversionRuleChange>(&ConversionKey::PendingRule(from_token.clone()))
+        {
+            Some(change) if now(&env) >= change.effective_at => Some(change.rule),
+            _ => storage.get(&ConversionKey::Rule(from_token)),
+        }
+    }
+
+    /// Loosening change to the rule for `from_token` waiting out the delay,
+    /// if any
+    pub fn get_pending_conversion_rule(
+        env: Env,
+        from_token: Address,
+    ) -> Option<ConversionRuleChange> {
+        env.storage()
+            .persistent()
+            .get::<_, ConversionRuleChange>(&ConversionKey::PendingRule(from_token))
+            .filter(|change| now(&env) < change.effective_at)
+    }
+
+    /// Convert `amount` of `from_token` under its rule and return how much
+    /// was received. Authorized by the rule's executor.
+    pub fn convert(env: Env, from_token: Address, amount: i128) -> Result<i128, Error> {
+        let rule =
+            Self::get_conversion_rule(env.clone(), from_token.clone()).ok_or(Error::NotFound)?;
+        rule.executor.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let day_key = ConversionKey::Converted(from_token.clone(), now(&env) / DAY);
+        let converted: i128 = env.storage().temporary().get(&day_key).unwrap_or(0);
+        let converted = converted
+            .checked_add(amount)
+            .filter(|&total| total <= rule.daily_cap)
+            .ok_or(Error::LimitExceeded)?;
+
+        // What is sold leaves the wallet like a payment
+        large_transfers::check_transfer(&env, &from_token, amount)?;
+        fiat_limits::check_transfer(&env, &from_token, amount)?;
+
+        // The quote only turns a bad trade down early; the swap is checked
+        // against what arrives
+        let reference = reference_out(&env, &rule, &from_token, amount)?;
+        let quoted = quote_out(&env, &rule, &from_token, amount)?;
+        if impact_bps(reference, quoted) > rule.max_slippage_bps {
+            return Err(Error::LimitExceeded);
+        }
+        let min_out = reference
+            .saturating_sub(reference.saturating_mul(rule.max_slippage_bps as i128) / 10_000)
+            .max(1);
+
+        let wallet = env.current_contract_address();
+        let from_client = token::Client::new(&env, &from_token);
+        let to_client = token::Client::new(&env, &rule.to_token);
+        let from_before = from_client.balance(&wallet);
+        let to_before = to_client.balance(&wallet);
+
+        let reserve = ReserveGuard::new(&env);
+        sell_exact(
+            &env,
+            &rule.router,
+            &from_token,
+            &rule.to_token,
+            amount,
+            min_out,
+            env.ledger().timestamp(),
+        )?;
+        reserve.check(&env)?;
+
+        let sold = from_before.saturating_sub(from_client.balance(&wallet));
+        let bought = to_client.balance(&wallet).saturating_sub(to_before);
+        if sold > amount {
+            return Err(Error::InvalidAmount);
+        }
+        let slippage_bps = impact_bps(reference, bought);
+        if slippage_bps > rule.max_slippage_bps {
+            return Err(Error::LimitExceeded);
+        }
+
+        env.storage().temporary().set(&day_key, &converted);
+        env.storage()
+            .temporary()
+            .extend_ttl(&day_key, DAY_LEDGERS, DAY_LEDGERS);
+        record_op(
+            &env,
+            OpKind::Conversion,
+            &from_token,
+            -sold,
+            Some(rule.router),
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::CONVERSION), from_token),
+            ConversionEvent {
+                to_token: rule.to_token,
+                sold,
+                bought,
+                slippage_bps,
+            },
+        );
+
+        Ok(bought)
+    }
+}
+
+/// Whether `rule` lets the executor sell anything `current` wouldn't
+fn loosens(current: Option<&ConversionRule>, rule: &ConversionRule) -> bool {
+    current.is_none_or(|current| {
+        rule.to_token != current.to_token
+            || rule.router != current.router
+            || rule.executor != current.executor
+            || rule.max_slippage_bps > current.max_slippage_bps
+            || rule.daily_cap > current.daily_cap
+    })
+}
+
+fn quote_out(
+    env: &Env,
+    rule: &ConversionRule,
+    from_token: &Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    let path = vec![env, from_token.clone(), rule.to_token.clone()];
+    SwapRouterClient::new(env, &rule.router)
+        .router_get_amounts_out(&amount, &path)
+        .last()
+        .filter(|&out| out > 0)
+        .ok_or(Error::InvalidAmount)
+}
+
+/// What selling `amount` should fetch without price impact: the oracle's
+/// rate when there is one, else a small trade's quote scaled up
+fn reference_out(
+    env: &Env,
+    rule: &ConversionRule,
+    from_token: &Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    let oracle_rate = WalletContract::get_oracle(env.clone())
+        .and_then(|oracle| token_value(env, &oracle, from_token, amount, &rule.to_token));
+    match oracle_rate {
+        Some(reference) if reference > 0 => Ok(reference),
+        Some(_) => Err(Error::InvalidAmount),
+        None => {
+            let probe = amount / PROBE_DIVISOR;
+            if probe == 0 {
+                return Err(Error::InvalidAmount);
+            }
+            Ok(quote_out(env, rule, from_token, probe)?.saturating_mul(PROBE_DIVISOR))
+        }
+    }
+}
+
+/// How far `out` falls short of `reference`, in basis points
+fn impact_bps(reference: i128, out: i128) -> u32 {
+    if out >= reference {
+        return 0;
+    }
+    let bps = (reference - out).saturating_mul(10_000) / reference;
+    bps.min(MAX_SLIPPAGE_BPS as i128) as u32
+}
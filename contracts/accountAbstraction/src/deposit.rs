@@ -0,0 +1,172 @@
+// ============================================================================
+// ANCHOR DEPOSIT BINDINGS (SEP-24 / SEP-6)
+//
+// The owner registers, per anchor, the memo and claim route an on-ramp
+// deposit is expected to arrive with. The indexer checks incoming payments
+// against these bindings to recognize deposits and label the counterparty.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, symbol_short, xdr::ToXdr, Address, BytesN, Env, String, Symbol, Vec,
+};
+
+use crate::paging::{index_page, index_push, index_swap_remove};
+use crate::*;
+
+const DEPOSIT_INDEX: Symbol = symbol_short!("deposits");
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositBinding {
+    /// sha256 of the memo the anchor attaches to deposits
+    pub memo_hash: BytesN<32>,
+    /// Account or contract the anchor pays from
+    pub route: Address,
+    /// Counterparty label shown in history (e.g. "Etherfuse MXN")
+    pub label: String,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DepositKey {
+    /// Keyed by sha256 of the anchor's home domain
+    DepositBinding(BytesN<32>),
+    /// Position of the anchor in the listing index
+    DepositPosition(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositBindingEvent {
+    pub anchor_domain_hash: BytesN<32>,
+    pub route: Address,
+    pub label: String,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Register (or replace) the deposit binding for an anchor
+    pub fn set_deposit_binding(
+        env: Env,
+        anchor_domain_hash: BytesN<32>,
+        memo_hash: BytesN<32>,
+        route: Address,
+        label: String,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = (
+            anchor_domain_hash.clone(),
+            memo_hash.clone(),
+            route.clone(),
+            label.clone(),
+        )
+            .to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_deposit_binding", payload, signature)?;
+
+        let binding = DepositBinding {
+            memo_hash,
+            route: route.clone(),
+            label: label.clone(),
+        };
+        let key = DepositKey::DepositBinding(anchor_domain_hash.clone());
+        if !env.storage().persistent().has(&key) {
+            let position = index_push(&env, &DEPOSIT_INDEX, &anchor_domain_hash);
+            env.storage().persistent().set(
+                &DepositKey::DepositPosition(anchor_domain_hash.clone()),
+                &position,
+            );
+        }
+        env.storage().persistent().set(&key, &binding);
+
+        env.events().publish(
+            (Symbol::new(&env, events::DEPOSIT_BINDING_SET),),
+            DepositBindingEvent {
+                anchor_domain_hash,
+                route,
+                label,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Remove the deposit binding for an anchor
+    pub fn remove_deposit_binding(
+        env: Env,
+        anchor_domain_hash: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let key = DepositKey::DepositBinding(anchor_domain_hash.clone());
+        let binding: DepositBinding = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NotFound)?;
+
+        let payload = anchor_domain_hash.clone().to_xdr(&env);
+        Self::require_owner_signature(&env, "remove_deposit_binding", payload, signature)?;
+
+        env.storage().persistent().remove(&key);
+
+        let position_key = DepositKey::DepositPosition(anchor_domain_hash.clone());
+        let position: u32 = env.storage().persistent().get(&position_key).ok_or(Error::NotFound)?;
+        if let Some(moved) = index_swap_remove::<BytesN<32>>(&env, &DEPOSIT_INDEX, position) {
+            env.storage()
+                .persistent()
+                .set(&DepositKey::DepositPosition(moved), &position);
+        }
+        env.storage().persistent().remove(&position_key);
+
+        env.events().publish(
+            (Symbol::new(&env, events::DEPOSIT_BINDING_REMOVED),),
+            DepositBindingEvent {
+                anchor_domain_hash,
+                route: binding.route,
+                label: binding.label,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the deposit binding registered for an anchor
+    pub fn get_deposit_binding(
+        env: Env,
+        anchor_domain_hash: BytesN<32>,
+    ) -> Result<DepositBinding, Error> {
+        env.storage()
+            .persistent()
+            .get(&DepositKey::DepositBinding(anchor_domain_hash))
+            .ok_or(Error::NotFound)
+    }
+
+    /// List deposit bindings, one page at a time
+    pub fn list_deposit_bindings(
+        env: Env,
+        cursor: u32,
+        limit: u32,
+    ) -> (Vec<(BytesN<32>, DepositBinding)>, Option<u32>) {
+        let (anchors, next_cursor) = index_page::<BytesN<32>>(&env, &DEPOSIT_INDEX, cursor, limit);
+
+        let mut items = Vec::new(&env);
+        for anchor in anchors.iter() {
+            if let Ok(binding) = Self::get_deposit_binding(env.clone(), anchor.clone()) {
+                items.push_back((anchor, binding));
+            }
+        }
+        (items, next_cursor)
+    }
+
+    /// Check whether a deposit matches the binding registered for its anchor
+    pub fn verify_deposit(
+        env: Env,
+        anchor_domain_hash: BytesN<32>,
+        memo_hash: BytesN<32>,
+        route: Address,
+    ) -> bool {
+        match Self::get_deposit_binding(env, anchor_domain_hash) {
+            Ok(binding) => binding.memo_hash == memo_hash && binding.route == route,
+            Err(_) => false,
+        }
+    }
+}
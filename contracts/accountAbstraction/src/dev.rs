@@ -0,0 +1,87 @@
+// ============================================================================
+// DEV MODE (feature = "dev-mode")
+//
+// Test hooks for QA on testnet: force the nonce after a desynced client run,
+// and move the wallet's clock forward to get past session and quote expiry
+// without waiting. Only compiled into builds with the `dev-mode` feature,
+// which `init` refuses to initialize on mainnet, and every hook checks the
+// network again in case such a build ever ends up behind a mainnet wallet.
+// The feature is recorded in the contract's build metadata, so
+// `cargo xtask verify-build` shows when a deployment carries it.
+//
+// The hooks are still owner-gated: a dev build is test tooling, not a way
+// around the wallet's authorization.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol};
+
+use crate::*;
+
+const MAINNET_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DevKey {
+    /// Seconds added to the ledger timestamp by `dev_fast_forward`
+    TimeOffset,
+}
+
+#[contractimpl]
+impl WalletContract {
+    pub fn dev_set_nonce(env: Env, nonce: u64, signature: BytesN<64>) -> Result<(), Error> {
+        ensure_not_mainnet(&env)?;
+
+        let payload = nonce.to_xdr(&env);
+        Self::require_owner_signature(&env, "dev_set_nonce", payload, signature)?;
+
+        env.storage().instance().set(&DataKey::Nonce, &nonce);
+        env.events()
+            .publish((Symbol::new(&env, events::DEV_SET_NONCE),), nonce);
+
+        Ok(())
+    }
+
+    /// Move the wallet's clock `seconds` ahead of the ledger
+    pub fn dev_fast_forward(env: Env, seconds: u64, signature: BytesN<64>) -> Result<(), Error> {
+        ensure_not_mainnet(&env)?;
+
+        let payload = seconds.to_xdr(&env);
+        Self::require_owner_signature(&env, "dev_fast_forward", payload, signature)?;
+
+        let offset = time_offset(&env)
+            .checked_add(seconds)
+            .ok_or(Error::LimitExceeded)?;
+        env.storage().instance().set(&DevKey::TimeOffset, &offset);
+        env.events()
+            .publish((Symbol::new(&env, events::DEV_FAST_FORWARD),), offset);
+
+        Ok(())
+    }
+
+    /// Ledger timestamp as the wallet currently sees it
+    pub fn dev_now(env: Env) -> u64 {
+        now(&env)
+    }
+}
+
+/// Refuse to run on mainnet
+pub(crate) fn ensure_not_mainnet(env: &Env) -> Result<(), Error> {
+    let mainnet = env
+        .crypto()
+        .sha256(&Bytes::from_slice(env, MAINNET_PASSPHRASE.as_bytes()));
+    if env.ledger().network_id() == mainnet.to_bytes() {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+pub(crate) fn now(env: &Env) -> u64 {
+    env.ledger().timestamp().saturating_add(time_offset(env))
+}
+
+fn time_offset(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DevKey::TimeOffset)
+        .unwrap_or(0)
+}
@@ -0,0 +1,248 @@
+// ============================================================================
+// DEVICES
+//
+// Each device the user signs in from (phone, laptop, tablet) can hold its
+// own ed25519 key instead of sharing the owner key. The owner links a device
+// with a label, and `__check_auth` then accepts that device's signatures
+// like the owner's. The wallet keeps when each device was linked and last
+// used, in ledger sequence, so the device list in settings needs nothing
+// off-chain.
+//
+// Unlinking takes more than the owner key, so one compromised session can't
+// quietly cut the user's real phone off. `remove_device` also needs a fresh
+// signature from the device being removed, and cuts it off at once. When the
+// device is lost, `queue_device_removal` unlinks it after
+// `DEVICE_REMOVAL_DELAY` instead, and until then the device itself can
+// cancel. A thief holding a lost phone can keep cancelling; rotating the
+// owner key or freezing the wallet is the way out then.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Map, String, Symbol, Vec,
+};
+
+use crate::signature::verify_ed25519;
+use crate::*;
+
+pub const MAX_DEVICES: u32 = 10;
+pub const MAX_DEVICE_LABEL_LEN: u32 = 32;
+/// Time before a queued device removal can be executed (24 hours)
+pub const DEVICE_REMOVAL_DELAY: u64 = 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Device {
+    pub public_key: BytesN<32>,
+    /// Name shown in settings ("Pixel 8", "Work laptop")
+    pub label: String,
+    /// Ledger sequence the device was linked in
+    pub added_at: u32,
+    /// Ledger sequence of the device's last authorization, 0 if none yet
+    pub last_used: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DeviceKey {
+    Devices,
+    /// When a queued removal of the device can be executed
+    DeviceRemoval(BytesN<32>),
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Link a device key, labelled as it should show in settings
+    pub fn add_device(
+        env: Env,
+        public_key: BytesN<32>,
+        label: String,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::is_zero_bytes(&public_key) {
+            return Err(Error::InvalidOwner);
+        }
+        if label.is_empty() || label.len() > MAX_DEVICE_LABEL_LEN {
+            return Err(Error::InvalidAmount);
+        }
+        let mut devices = devices(&env);
+        if devices.contains_key(public_key.clone()) || public_key == Self::get_owner(env.clone())? {
+            return Err(Error::AlreadyExists);
+        }
+        if devices.len() >= MAX_DEVICES {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (public_key.clone(), label.clone()).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "add_device", payload, signature)?;
+
+        let device = Device {
+            public_key: public_key.clone(),
+            label,
+            added_at: env.ledger().sequence(),
+            last_used: 0,
+        };
+        devices.set(public_key.clone(), device.clone());
+        save_devices(&env, &devices);
+        approvals::record_signer_added(&env, &Signer::Ed25519(public_key.clone()));
+        env.events().publish(
+            (Symbol::new(&env, events::DEVICE_ADDED), public_key),
+            device,
+        );
+
+        Ok(())
+    }
+
+    /// Unlink a device; its signatures stop being accepted right away.
+    /// `device_signature` is the device's own, over the
+    /// `confirm_device_removal` action message for the public key, at the
+    /// same nonce as the owner's.
+    pub fn remove_device(
+        env: Env,
+        public_key: BytesN<32>,
+        device_signature: BytesN<64>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut devices = devices(&env);
+        if !devices.contains_key(public_key.clone()) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &public_key.to_array());
+        let message = Self::action_message(&env, "confirm_device_removal", payload.clone())?;
+        verify_ed25519(&env, &public_key, &message, &device_signature)?;
+        Self::require_high_risk_signature(&env, "remove_device", payload, signature)?;
+
+        devices.remove(public_key.clone());
+        save_devices(&env, &devices);
+        env.storage()
+            .instance()
+            .remove(&DeviceKey::DeviceRemoval(public_key.clone()));
+        env.events()
+            .publish((Symbol::new(&env, events::DEVICE_REMOVED), public_key), ());
+
+        Ok(())
+    }
+
+    /// Unlink a device that can't confirm, after `DEVICE_REMOVAL_DELAY`.
+    /// Owner-signed over the public key. Returns when it can be executed.
+    pub fn queue_device_removal(
+        env: Env,
+        public_key: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<u64, Error> {
+        if !devices(&env).contains_key(public_key.clone()) {
+            return Err(Error::NotFound);
+        }
+        let key = DeviceKey::DeviceRemoval(public_key.clone());
+        if env.storage().instance().has(&key) {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = Bytes::from_array(&env, &public_key.to_array());
+        Self::require_high_risk_signature(&env, "queue_device_removal", payload, signature)?;
+
+        let eta = now(&env).saturating_add(DEVICE_REMOVAL_DELAY);
+        env.storage().instance().set(&key, &eta);
+        env.events().publish(
+            (Symbol::new(&env, events::DEVICE_REMOVAL_QUEUED), public_key),
+            eta,
+        );
+
+        Ok(eta)
+    }
+
+    /// Unlink a device whose queued removal is due. Anyone can call.
+    pub fn execute_device_removal(env: Env, public_key: BytesN<32>) -> Result<(), Error> {
+        let key = DeviceKey::DeviceRemoval(public_key.clone());
+        let eta: u64 = env.storage().instance().get(&key).ok_or(Error::NotFound)?;
+        if now(&env) < eta {
+            return Err(Error::Timelocked);
+        }
+
+        let mut devices = devices(&env);
+        devices.remove(public_key.clone());
+        save_devices(&env, &devices);
+        env.storage().instance().remove(&key);
+        env.events().publish(
+            (
+                Symbol::new(&env, events::DEVICE_REMOVAL_EXECUTED),
+                public_key,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    /// Cancel a queued removal. Signed by the device being removed, over the
+    /// `cancel_device_removal` action message for its public key.
+    pub fn cancel_device_removal(
+        env: Env,
+        public_key: BytesN<32>,
+        device_signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let key = DeviceKey::DeviceRemoval(public_key.clone());
+        if !env.storage().instance().has(&key) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &public_key.to_array());
+        let message = Self::action_message(&env, "cancel_device_removal", payload)?;
+        verify_ed25519(&env, &public_key, &message, &device_signature)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        env.storage().instance().remove(&key);
+        env.events().publish(
+            (
+                Symbol::new(&env, events::DEVICE_REMOVAL_CANCELLED),
+                public_key,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    /// When the queued removal of a device can be executed, if one is queued
+    pub fn get_device_removal(env: Env, public_key: BytesN<32>) -> Option<u64> {
+        env.storage()
+            .instance()
+            .get(&DeviceKey::DeviceRemoval(public_key))
+    }
+
+    pub fn list_devices(env: Env) -> Vec<Device> {
+        devices(&env).values()
+    }
+}
+
+/// Verify a device signature over `message` and mark the device used
+pub(crate) fn verify_device(
+    env: &Env,
+    public_key: &BytesN<32>,
+    message: &Bytes,
+    signature: &BytesN<64>,
+) -> Result<(), Error> {
+    let mut devices = devices(env);
+    let mut device = devices.get(public_key.clone()).ok_or(Error::Unauthorized)?;
+    verify_ed25519(env, public_key, message, signature)?;
+
+    device.last_used = env.ledger().sequence();
+    devices.set(public_key.clone(), device);
+    save_devices(env, &devices);
+    Ok(())
+}
+
+pub(crate) fn is_device(env: &Env, public_key: &BytesN<32>) -> bool {
+    devices(env).contains_key(public_key.clone())
+}
+
+fn devices(env: &Env) -> Map<BytesN<32>, Device> {
+    env.storage()
+        .instance()
+        .get(&DeviceKey::Devices)
+        .unwrap_or(Map::new(env))
+}
+
+fn save_devices(env: &Env, devices: &Map<BytesN<32>, Device>) {
+    env.storage().instance().set(&DeviceKey::Devices, devices);
+}
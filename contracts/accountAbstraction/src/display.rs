@@ -0,0 +1,82 @@
+// ============================================================================
+// ASSET DISPLAY
+//
+// Owner-set overrides for how assets are shown: a symbol alias and the
+// number of decimal places to display. Business wallets use them to make
+// internal tokens (CETES receipts, say) read the same on every device of
+// the team, since all of them read the overrides from the wallet. Purely
+// cosmetic: amounts on-chain are never rescaled.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Map, String, Symbol,
+};
+
+use crate::*;
+
+pub const MAX_DISPLAY_OVERRIDES: u32 = 20;
+pub const MAX_DISPLAY_SYMBOL_LEN: u32 = 12;
+pub const MAX_DISPLAY_DECIMALS: u32 = 18;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetDisplay {
+    pub symbol: String,
+    /// Decimal places to show
+    pub decimals: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DisplayKey {
+    AssetDisplays,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set or (with `None`) remove the display override for `asset`
+    pub fn set_asset_display(
+        env: Env,
+        asset: Address,
+        display: Option<AssetDisplay>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut displays = Self::get_asset_displays(env.clone());
+        if let Some(display) = &display {
+            if display.symbol.is_empty()
+                || display.symbol.len() > MAX_DISPLAY_SYMBOL_LEN
+                || display.decimals > MAX_DISPLAY_DECIMALS
+            {
+                return Err(Error::InvalidAmount);
+            }
+            if !displays.contains_key(asset.clone()) && displays.len() >= MAX_DISPLAY_OVERRIDES {
+                return Err(Error::LimitExceeded);
+            }
+        }
+
+        let payload = (asset.clone(), display.clone()).to_xdr(&env);
+        Self::require_owner_signature(&env, "set_asset_display", payload, signature)?;
+
+        match &display {
+            Some(display) => displays.set(asset.clone(), display.clone()),
+            None => {
+                displays.remove(asset.clone());
+            }
+        }
+        env.storage()
+            .instance()
+            .set(&DisplayKey::AssetDisplays, &displays);
+        env.events()
+            .publish((Symbol::new(&env, events::ASSET_DISPLAY), asset), display);
+
+        Ok(())
+    }
+
+    /// Every display override, for the SDK to apply in one read
+    pub fn get_asset_displays(env: Env) -> Map<Address, AssetDisplay> {
+        env.storage()
+            .instance()
+            .get(&DisplayKey::AssetDisplays)
+            .unwrap_or(Map::new(&env))
+    }
+}
@@ -0,0 +1,261 @@
+// ============================================================================
+// ZK EMAIL RECOVERY
+//
+// Recovery by proving control of the wallet's email address. The user sends
+// an email committing to the new key; a stellar-zk-email verifier contract
+// checks the zero-knowledge proof of that email's DKIM signature and returns
+// the hash of the proven sender, which must match the wallet's email hash.
+//
+// The command the email commits to is
+// sha256("recover_with_email_proof" || new_owner || nonce), so a proof
+// rotates to one key, once. With a rotation delay set the new key only
+// becomes pending, like `propose_owner_rotation`: a stolen mailbox gets the
+// same cancellation window as a stolen key. Either way the recovered key
+// starts out with capped spending (see `recovery_cooldown`).
+//
+// Users change email addresses, so the owner can move the wallet to a new
+// email hash. Proofs are checked against the current hash, so the old
+// address stops recovering the wallet at once, and a rotation it started
+// that is still in its delay is dropped along with it. Changing the email
+// is high risk: a freshly installed key can't also take over the mailbox
+// that could recover the wallet from it.
+//
+// The factory binds the wallets it deploys to its registry, and a bound
+// wallet moves its own registry entry when its email changes, so lookups
+// by email hash keep finding it. The registry admin vouches for the new
+// address in the same call, so an owner can't list the wallet under
+// someone else's email. Only the contract that deployed the
+// wallet can bind it: the wallet checks its own address is the one that
+// contract would deploy at with the salt it names.
+// ============================================================================
+
+use soroban_sdk::{
+    contractclient, contractimpl, contracttype, vec, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    IntoVal, Symbol,
+};
+
+use crate::approvals::record_owner_change;
+use crate::fragments::{clear_recovery_proof, record_recovery_proof, recovery_proof_key};
+use crate::recovery_cooldown;
+use crate::rotation::rotation_delay;
+use crate::*;
+
+/// Interface of the zk-email verifier contract
+#[contractclient(name = "EmailVerifierClient")]
+pub trait EmailVerifier {
+    /// Verify `proof` of an email committing to `command` and return the
+    /// hash of its sender address. Traps on an invalid proof.
+    fn verify(env: Env, proof: Bytes, command: BytesN<32>) -> BytesN<32>;
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum EmailRecoveryKey {
+    Verifier,
+    /// WalletRegistry the wallet is listed in by email hash
+    Registry,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmailRecoveryEvent {
+    pub new_owner: BytesN<32>,
+    pub verifier: Address,
+    /// 0 when the key changed immediately, otherwise when it can be
+    /// confirmed
+    pub eta: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmailRotatedEvent {
+    pub old_email_hash: BytesN<32>,
+    pub new_email_hash: BytesN<32>,
+    pub nonce: u64,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set or (with `None`) remove the zk-email verifier
+    pub fn set_verifier(
+        env: Env,
+        verifier: Option<Address>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = verifier.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_verifier", payload, signature)?;
+
+        match &verifier {
+            Some(verifier) => env
+                .storage()
+                .instance()
+                .set(&EmailRecoveryKey::Verifier, verifier),
+            None => env.storage().instance().remove(&EmailRecoveryKey::Verifier),
+        }
+        env.events()
+            .publish((Symbol::new(&env, events::VERIFIER_SET),), verifier);
+
+        Ok(())
+    }
+
+    pub fn get_verifier(env: Env) -> Option<Address> {
+        env.storage().instance().get(&EmailRecoveryKey::Verifier)
+    }
+
+    /// Keep the wallet's entry in `registry` in step with its email hash.
+    /// Authorized by `deployer`, which must have deployed the wallet with
+    /// `salt`.
+    pub fn bind_registry(
+        env: Env,
+        deployer: Address,
+        salt: BytesN<32>,
+        registry: Address,
+    ) -> Result<(), Error> {
+        deployer.require_auth();
+        let deployed = env
+            .deployer()
+            .with_address(deployer, salt)
+            .deployed_address();
+        if deployed != env.current_contract_address() {
+            return Err(Error::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .set(&EmailRecoveryKey::Registry, &registry);
+        Ok(())
+    }
+
+    pub fn get_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&EmailRecoveryKey::Registry)
+    }
+
+    /// Move the wallet to the email address hashing to `new_hash`, and its
+    /// registry entry with it when bound to one; the registry admin has to
+    /// authorize that move too
+    pub fn update_email_hash(
+        env: Env,
+        new_hash: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::is_zero_bytes(&new_hash) {
+            return Err(Error::InvalidEmailHash);
+        }
+        let old_hash = Self::get_email_hash(env.clone())?;
+        if old_hash == new_hash {
+            return Err(Error::AlreadyExists);
+        }
+
+        let nonce = Self::get_nonce(env.clone())?;
+        let payload = Bytes::from_array(&env, &new_hash.to_array());
+        Self::require_high_risk_signature(&env, "update_email_hash", payload, signature)?;
+
+        env.storage().instance().set(&DataKey::EmailHash, &new_hash);
+        if let Some(registry) = Self::get_registry(env.clone()) {
+            env.invoke_contract::<()>(
+                &registry,
+                &Symbol::new(&env, "sync_email"),
+                vec![
+                    &env,
+                    env.current_contract_address().into_val(&env),
+                    old_hash.into_val(&env),
+                    new_hash.into_val(&env),
+                ],
+            );
+        }
+
+        // A pending rotation whose key matches the recorded recovery proof
+        // came from the old address
+        if let Some(pending) = Self::get_pending_rotation(env.clone()) {
+            if recovery_proof_key(&env) == Some(pending.new_owner.clone()) {
+                env.storage()
+                    .instance()
+                    .remove(&RotationKey::PendingRotation);
+                clear_recovery_proof(&env);
+                env.events().publish(
+                    (Symbol::new(&env, events::ROTATION_CANCELLED),),
+                    RotationCancelledEvent {
+                        new_owner: pending.new_owner,
+                        cancelled_by: Signer::Ed25519(Self::get_owner(env.clone())?),
+                    },
+                );
+            }
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, events::EMAIL_ROTATED),),
+            EmailRotatedEvent {
+                old_email_hash: old_hash,
+                new_email_hash: new_hash,
+                nonce,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Rotate to `new_owner` with a proof of an email from the wallet's
+    /// address
+    pub fn recover_with_email_proof(
+        env: Env,
+        proof: Bytes,
+        new_owner: BytesN<32>,
+    ) -> Result<(), Error> {
+        let verifier = Self::get_verifier(env.clone()).ok_or(Error::Unauthorized)?;
+        if Self::is_zero_bytes(&new_owner) {
+            return Err(Error::InvalidOwner);
+        }
+        let old_owner = Self::get_owner(env.clone())?;
+        if old_owner == new_owner {
+            return Err(Error::SameOwner);
+        }
+
+        let payload = Bytes::from_array(&env, &new_owner.to_array());
+        let message = Self::action_message(&env, "recover_with_email_proof", payload)?;
+        let command: BytesN<32> = env.crypto().sha256(&message).into();
+        let proven = EmailVerifierClient::new(&env, &verifier).verify(&proof, &command);
+        if proven != Self::get_email_hash(env.clone())? {
+            return Err(Error::Unauthorized);
+        }
+        let nonce = Self::get_and_increment_nonce(env.clone())?;
+
+        let delay = rotation_delay(&env);
+        let eta = if delay > 0 {
+            let eta = now(&env).saturating_add(delay);
+            env.storage().instance().set(
+                &RotationKey::PendingRotation,
+                &PendingRotation {
+                    new_owner: new_owner.clone(),
+                    eta,
+                },
+            );
+            recovery_cooldown::start_on_confirm(&env, &new_owner);
+            eta
+        } else {
+            env.storage().instance().set(&DataKey::Owner, &new_owner);
+            record_owner_change(&env);
+            recovery_cooldown::start(&env);
+            env.events().publish(
+                (Symbol::new(&env, events::KEY_ROTATED),),
+                KeyRotatedEvent {
+                    old_owner,
+                    new_owner: new_owner.clone(),
+                    nonce,
+                },
+            );
+            0
+        };
+        record_recovery_proof(&env, &new_owner, eta);
+
+        env.events().publish(
+            (Symbol::new(&env, events::EMAIL_RECOVERY),),
+            EmailRecoveryEvent {
+                new_owner,
+                verifier,
+                eta,
+            },
+        );
+
+        Ok(())
+    }
+}
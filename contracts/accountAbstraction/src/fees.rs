@@ -0,0 +1,159 @@
+// ============================================================================
+// FEE REIMBURSEMENT
+//
+// The relayer keeps paying the XLM network fees, and in fee mode the wallet
+// pays it back in another asset (USDC): a SAC transfer to the relayer inside
+// the same batch, or the same authorization, as the calls it paid for, so
+// the fee only moves if they land. The relayer is the wallet's sponsor.
+//
+// The relayer quotes the fee and builds the transfer, and the owner's
+// passkey signs whatever the SDK hands it, so the owner sets a cap and the
+// wallet refuses anything that pays the relayer more than that in the fee
+// asset: batches up front in `check_ops`, everything else in `__check_auth`.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol,
+    TryFromVal, Val, Vec,
+};
+
+use crate::*;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    /// SAC the relayer is paid in, e.g. USDC
+    pub asset: Address,
+    /// Most one batch or authorization can pay the relayer in `asset`
+    pub max_fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum FeeKey {
+    FeeConfig,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Pay the sponsor's fees in `asset`, up to `max_fee` per batch or
+    /// authorization. A `max_fee` of 0 turns fee mode off.
+    pub fn set_fee_config(
+        env: Env,
+        asset: Address,
+        max_fee: i128,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if max_fee < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        // Without a sponsor there is no relayer to pay
+        if max_fee > 0 {
+            Self::get_sponsorship(env.clone())?;
+        }
+
+        let payload = (asset.clone(), max_fee).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_fee_config", payload, signature)?;
+
+        if max_fee == 0 {
+            env.storage().instance().remove(&FeeKey::FeeConfig);
+        } else {
+            env.storage()
+                .instance()
+                .set(&FeeKey::FeeConfig, &FeeConfig { asset, max_fee });
+        }
+
+        Ok(())
+    }
+
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        env.storage().instance().get(&FeeKey::FeeConfig)
+    }
+}
+
+/// Who fees go to and the most they can take
+struct FeeCap {
+    config: FeeConfig,
+    relayer: Address,
+    wallet: Address,
+    transfer: Symbol,
+    paid: i128,
+}
+
+impl FeeCap {
+    fn load(env: &Env) -> Option<Self> {
+        let config = WalletContract::get_fee_config(env.clone())?;
+        let relayer = WalletContract::get_sponsorship(env.clone()).ok()?.sponsor;
+        Some(Self {
+            config,
+            relayer,
+            wallet: env.current_contract_address(),
+            transfer: Symbol::new(env, "transfer"),
+            paid: 0,
+        })
+    }
+
+    /// Count the call if it moves the fee asset from the wallet to the relayer
+    fn add(&mut self, env: &Env, contract: &Address, fn_name: &Symbol, args: &Vec<Val>) {
+        if *contract != self.config.asset || *fn_name != self.transfer {
+            return;
+        }
+        let from = args
+            .get(0)
+            .and_then(|v| Address::try_from_val(env, &v).ok());
+        let to = args
+            .get(1)
+            .and_then(|v| Address::try_from_val(env, &v).ok());
+        let amount = args.get(2).and_then(|v| i128::try_from_val(env, &v).ok());
+        if let (Some(from), Some(to), Some(amount)) = (from, to, amount) {
+            if from == self.wallet && to == self.relayer {
+                self.paid = self.paid.saturating_add(amount);
+            }
+        }
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.paid > self.config.max_fee {
+            return Err(Error::LimitExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// Fail if the calls in `contexts` pay the relayer more than the cap
+pub(crate) fn check_auth_contexts(env: &Env, contexts: &Vec<Context>) -> Result<(), Error> {
+    let Some(mut cap) = FeeCap::load(env) else {
+        return Ok(());
+    };
+    for context in contexts.iter() {
+        if let Context::Contract(call) = context {
+            cap.add(env, &call.contract, &call.fn_name, &call.args);
+        }
+    }
+    cap.check()
+}
+
+/// Fail if the calls of a batch, nested ones included, pay the relayer more
+/// than the cap
+pub(crate) fn check_batch(env: &Env, ops: &Vec<Invocation>) -> Result<(), Error> {
+    let Some(mut cap) = FeeCap::load(env) else {
+        return Ok(());
+    };
+    for op in ops.iter() {
+        cap.add(env, &op.contract, &op.fn_name, &op.args);
+    }
+    cap.check()
+}
+
+/// Pay the relayer `fee` on top of what `ops` already pay it, within the cap
+pub(crate) fn pay_relayer(env: &Env, ops: &Vec<Invocation>, fee: i128) -> Result<(), Error> {
+    let mut cap = FeeCap::load(env).ok_or(Error::NotFound)?;
+    for op in ops.iter() {
+        cap.add(env, &op.contract, &op.fn_name, &op.args);
+    }
+    cap.paid = cap.paid.saturating_add(fee);
+    cap.check()?;
+
+    token::Client::new(env, &cap.config.asset).transfer(&cap.wallet, &cap.relayer, &fee);
+    Ok(())
+}
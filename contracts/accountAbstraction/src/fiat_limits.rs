@@ -0,0 +1,361 @@
+// ============================================================================
+// FIAT SPENDING LIMITS
+//
+// A daily spending limit in a fiat currency ("$200 a day", "5,000 MXN a
+// day") instead of per-asset units, which mean nothing to most users and
+// drift with the price. Everything that moves funds out of the wallet is
+// valued through the owner's price oracle (see `oracle`) and added to the
+// day's total, and refused once the total would pass the limit: transfers,
+// approvals and burns `__check_auth` authorizes, batches (and so
+// meta-transactions, generic invokes and scheduled calls, when they run)
+// with every nested call their ops authorize, allowance pulls, subscription
+// collections, share distributions and quote payments. An approval counts
+// in full when granted, as with the large-transfer thresholds. Queued large
+// transfers are left out: they already wait out a delay any device can
+// cancel in. So are transfers to the owner's own accounts (see
+// `own_accounts`), which aren't spending. The same paths count against the
+// cap after an email recovery (see `recovery_cooldown`), own accounts
+// included: the recovered key could have linked them itself.
+//
+// An asset the oracle has no fresh price for follows the limit's fallback:
+// `Refuse` fails the payment, so a dead feed can't open the limit up;
+// `Skip` lets it through uncounted, with only the per-asset large-transfer
+// thresholds to hold it.
+//
+// As with the large-transfer thresholds, tightening applies at once and
+// loosening waits out `LARGE_TRANSFER_DELAY`, so a stolen owner key can't
+// lift the limit and drain in one go. Raising or removing the limit,
+// switching its currency or going from `Refuse` to `Skip` loosens it, and
+// so does swapping the oracle it is priced through for another.
+// ============================================================================
+
+use accesly_policy::{add_fiat, check_fiat};
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Map, Symbol, Val,
+    Vec,
+};
+
+use crate::large_transfers::LARGE_TRANSFER_DELAY;
+use crate::oracle::fiat_value;
+use crate::outflows::outflow;
+use crate::own_accounts::is_internal_call;
+use crate::recovery_cooldown;
+use crate::*;
+
+const DAY: u64 = 24 * 60 * 60;
+/// Ledgers in a day at 5s per ledger, how long a day's total must live
+const DAY_LEDGERS: u32 = (DAY / 5) as u32;
+
+/// What a transfer of an asset without a fresh price does
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StalePrice {
+    Refuse,
+    Skip,
+}
+
+impl From<StalePrice> for accesly_policy::StalePrice {
+    fn from(on_stale: StalePrice) -> Self {
+        match on_stale {
+            StalePrice::Refuse => accesly_policy::StalePrice::Refuse,
+            StalePrice::Skip => accesly_policy::StalePrice::Skip,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiatLimit {
+    /// Currency code as the oracle names it, e.g. USD or MXN
+    pub currency: Symbol,
+    /// Most the wallet can send per day, in cents of `currency`
+    pub daily_limit: i128,
+    pub on_stale: StalePrice,
+}
+
+/// A fiat limit change and when it applies. A `daily_limit` of 0 removes
+/// the limit.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiatLimitChange {
+    pub limit: FiatLimit,
+    pub effective_at: u64,
+}
+
+/// An oracle swap and when it applies
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleChange {
+    pub oracle: Address,
+    pub effective_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum FiatLimitKey {
+    Oracle,
+    FiatLimit,
+    /// Loosening change waiting out the delay
+    PendingFiatLimit,
+    /// Oracle swap waiting out the delay
+    PendingOracle,
+    /// Cents spent on a day, by day index
+    FiatSpent(u64),
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Price fiat limits and conversions through `oracle`, an oracle
+    /// adapter. The first oracle applies at once, a swap after
+    /// `LARGE_TRANSFER_DELAY`. Owner-signed over the address.
+    pub fn set_oracle(env: Env, oracle: Address, signature: BytesN<64>) -> Result<(), Error> {
+        // The cooldown cap is priced through the oracle
+        if recovery_cooldown::is_active(&env) {
+            return Err(Error::Timelocked);
+        }
+        let payload = oracle.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_oracle", payload, signature)?;
+
+        let now = now(&env);
+        let storage = env.storage().instance();
+        let change = match Self::get_oracle(env.clone()) {
+            Some(current) if current != oracle => {
+                let change = OracleChange {
+                    oracle,
+                    effective_at: now.saturating_add(LARGE_TRANSFER_DELAY),
+                };
+                storage.set(&FiatLimitKey::Oracle, &current);
+                storage.set(&FiatLimitKey::PendingOracle, &change);
+                change
+            }
+            _ => {
+                storage.set(&FiatLimitKey::Oracle, &oracle);
+                storage.remove(&FiatLimitKey::PendingOracle);
+                OracleChange {
+                    oracle,
+                    effective_at: now,
+                }
+            }
+        };
+        env.events()
+            .publish((Symbol::new(&env, events::ORACLE_SET),), change);
+
+        Ok(())
+    }
+
+    /// Oracle in force now
+    pub fn get_oracle(env: Env) -> Option<Address> {
+        let storage = env.storage().instance();
+        match storage.get::<_, OracleChange>(&FiatLimitKey::PendingOracle) {
+            Some(change) if now(&env) >= change.effective_at => Some(change.oracle),
+            _ => storage.get(&FiatLimitKey::Oracle),
+        }
+    }
+
+    /// Oracle swap waiting out the delay, if any
+    pub fn get_pending_oracle(env: Env) -> Option<OracleChange> {
+        env.storage()
+            .instance()
+            .get::<_, OracleChange>(&FiatLimitKey::PendingOracle)
+            .filter(|change| now(&env) < change.effective_at)
+    }
+
+    /// Limit what the wallet spends per day to `daily_limit` cents of
+    /// `currency`; 0 removes the limit. Needs an oracle. Tightening applies
+    /// at once, loosening after `LARGE_TRANSFER_DELAY`. Owner-signed over
+    /// (currency, daily_limit, on_stale).
+    pub fn set_fiat_limit(
+        env: Env,
+        currency: Symbol,
+        daily_limit: i128,
+        on_stale: StalePrice,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if daily_limit < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if daily_limit > 0 && Self::get_oracle(env.clone()).is_none() {
+            return Err(Error::NotFound);
+        }
+
+        let payload = (currency.clone(), daily_limit, on_stale).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_fiat_limit", payload, signature)?;
+
+        let limit = FiatLimit {
+            currency,
+            daily_limit,
+            on_stale,
+        };
+        let now = now(&env);
+        let storage = env.storage().instance();
+        let change = match Self::get_fiat_limit(env.clone()) {
+            Some(current) if loosens(&current, &limit) => {
+                let change = FiatLimitChange {
+                    limit,
+                    effective_at: now.saturating_add(LARGE_TRANSFER_DELAY),
+                };
+                storage.set(&FiatLimitKey::FiatLimit, &current);
+                storage.set(&FiatLimitKey::PendingFiatLimit, &change);
+                change
+            }
+            _ => {
+                if daily_limit == 0 {
+                    storage.remove(&FiatLimitKey::FiatLimit);
+                } else {
+                    storage.set(&FiatLimitKey::FiatLimit, &limit);
+                }
+                storage.remove(&FiatLimitKey::PendingFiatLimit);
+                FiatLimitChange {
+                    limit,
+                    effective_at: now,
+                }
+            }
+        };
+        env.events()
+            .publish((Symbol::new(&env, events::FIAT_LIMIT),), change);
+
+        Ok(())
+    }
+
+    /// Limit in force now
+    pub fn get_fiat_limit(env: Env) -> Option<FiatLimit> {
+        let storage = env.storage().instance();
+        match storage.get::<_, FiatLimitChange>(&FiatLimitKey::PendingFiatLimit) {
+            Some(change) if now(&env) >= change.effective_at => {
+                Some(change.limit).filter(|limit| limit.daily_limit > 0)
+            }
+            _ => storage.get(&FiatLimitKey::FiatLimit),
+        }
+    }
+
+    /// Loosening change waiting out the delay, if any
+    pub fn get_pending_fiat_limit(env: Env) -> Option<FiatLimitChange> {
+        env.storage()
+            .instance()
+            .get::<_, FiatLimitChange>(&FiatLimitKey::PendingFiatLimit)
+            .filter(|change| now(&env) < change.effective_at)
+    }
+
+    /// Cents counted against the limit today
+    pub fn get_fiat_spent_today(env: Env) -> i128 {
+        env.storage()
+            .temporary()
+            .get(&FiatLimitKey::FiatSpent(now(&env) / DAY))
+            .unwrap_or(0)
+    }
+}
+
+/// Add what the calls in `contexts` move out of the wallet to the day's
+/// total, failing with `LimitExceeded` past the limit or `Expired` on a
+/// stale price the limit refuses
+pub(crate) fn check_auth_contexts(env: &Env, contexts: &Vec<Context>) -> Result<(), Error> {
+    if !is_limited(env) {
+        return Ok(());
+    }
+    let mut outflows = Outflows::new(env);
+    for context in contexts.iter() {
+        if let Context::Contract(call) = context {
+            outflows.add(env, &call.contract, &call.fn_name, &call.args);
+        }
+    }
+    outflows.spend(env)
+}
+
+/// `check_auth_contexts` for the calls of a batch, as `batch::calls` lists
+/// them
+pub(crate) fn check_batch(env: &Env, ops: &Vec<Invocation>) -> Result<(), Error> {
+    if !is_limited(env) {
+        return Ok(());
+    }
+    let mut outflows = Outflows::new(env);
+    for op in ops.iter() {
+        outflows.add(env, &op.contract, &op.fn_name, &op.args);
+    }
+    outflows.spend(env)
+}
+
+/// `check_auth_contexts` for the wallet's own transfer of `amount` of
+/// `asset`
+pub(crate) fn check_transfer(env: &Env, asset: &Address, amount: i128) -> Result<(), Error> {
+    if !is_limited(env) {
+        return Ok(());
+    }
+    let sent = Map::from_array(env, [(asset.clone(), amount)]);
+    spend(env, &sent)?;
+    recovery_cooldown::spend(env, &sent)
+}
+
+/// Whether `limit` lets through anything `current` would refuse
+fn loosens(current: &FiatLimit, limit: &FiatLimit) -> bool {
+    limit.daily_limit == 0
+        || limit.daily_limit > current.daily_limit
+        || limit.currency != current.currency
+        || (limit.on_stale == StalePrice::Skip && current.on_stale == StalePrice::Refuse)
+}
+
+/// Whether a fiat limit or a post-recovery cooldown caps spending
+fn is_limited(env: &Env) -> bool {
+    WalletContract::get_fiat_limit(env.clone()).is_some() || recovery_cooldown::is_active(env)
+}
+
+/// Outflows summed per asset, so each is priced once
+struct Outflows {
+    /// Counted against the fiat limit
+    sent: Map<Address, i128>,
+    /// Counted against a post-recovery cooldown, own accounts included
+    moved: Map<Address, i128>,
+}
+
+impl Outflows {
+    fn new(env: &Env) -> Self {
+        Self {
+            sent: Map::new(env),
+            moved: Map::new(env),
+        }
+    }
+
+    fn add(&mut self, env: &Env, contract: &Address, fn_name: &Symbol, args: &Vec<Val>) {
+        let Some(flow) = outflow(env, fn_name, args) else {
+            return;
+        };
+        add_to(&mut self.moved, contract, flow.amount);
+        // Moving funds to the owner's own accounts isn't spending
+        if !is_internal_call(env, fn_name, args) {
+            add_to(&mut self.sent, contract, flow.amount);
+        }
+    }
+
+    fn spend(&self, env: &Env) -> Result<(), Error> {
+        spend(env, &self.sent)?;
+        recovery_cooldown::spend(env, &self.moved)
+    }
+}
+
+fn add_to(totals: &mut Map<Address, i128>, asset: &Address, amount: i128) {
+    let total = totals.get(asset.clone()).unwrap_or(0);
+    totals.set(asset.clone(), total.saturating_add(amount));
+}
+
+fn spend(env: &Env, sent: &Map<Address, i128>) -> Result<(), Error> {
+    let Some(limit) = WalletContract::get_fiat_limit(env.clone()) else {
+        return Ok(());
+    };
+    if sent.is_empty() {
+        return Ok(());
+    }
+    let oracle = WalletContract::get_oracle(env.clone()).ok_or(Error::NotFound)?;
+
+    let mut spent = WalletContract::get_fiat_spent_today(env.clone());
+    for (asset, amount) in sent.iter() {
+        let value = fiat_value(env, &oracle, &asset, amount, &limit.currency);
+        spent = add_fiat(spent, value, limit.on_stale.into())?;
+    }
+    check_fiat(spent, limit.daily_limit)?;
+
+    let key = FiatLimitKey::FiatSpent(now(env) / DAY);
+    env.storage().temporary().set(&key, &spent);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, DAY_LEDGERS, DAY_LEDGERS);
+    Ok(())
+}
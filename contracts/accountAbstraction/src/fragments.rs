@@ -0,0 +1,120 @@
+// ============================================================================
+// FRAGMENT RELEASE
+//
+// After a recovery the new device needs the key fragments the backend holds
+// (F2, F3) to rebuild the user's key. The backend only releases them when
+// the wallet says so: a successful recovery proof (zk-email or guardians)
+// records the recovered key, that key calls `authorize_fragment_release`,
+// and the sep30 handler checks `is_fragment_release_authorized` for the
+// requesting device key. The handler never learns who the user is, only
+// that this key may have its fragments.
+//
+// The recovered key must actually own the wallet before it can authorize,
+// so a recovery still in its rotation delay (and possibly cancelled) can't
+// pull the fragments early. Both the proof and the authorization are
+// short-lived and live in temporary storage, so they expire without cleanup.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, Bytes, BytesN, Env, Symbol};
+
+use crate::signature::verify_ed25519;
+use crate::*;
+
+/// How long after a recovery takes effect the release can be authorized
+/// (1 hour)
+pub const RECOVERY_PROOF_WINDOW: u64 = 60 * 60;
+/// How long an authorized release stays valid (15 minutes)
+pub const FRAGMENT_RELEASE_WINDOW: u64 = 15 * 60;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum FragmentKey {
+    /// Key a recovery proof was accepted for, with the proof's expiry
+    RecoveryProof,
+    /// Expiry of the release authorized for a device key
+    Release(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FragmentReleaseEvent {
+    pub device_key: BytesN<32>,
+    pub expires_at: u64,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Authorize the backend to release fragments to the recovered key.
+    /// Signed by that key over "authorize_fragment_release" || nonce.
+    pub fn authorize_fragment_release(env: Env, signature: BytesN<64>) -> Result<(), Error> {
+        let (device_key, proof_expiry): (BytesN<32>, u64) = env
+            .storage()
+            .temporary()
+            .get(&FragmentKey::RecoveryProof)
+            .ok_or(Error::NotFound)?;
+        if now(&env) > proof_expiry {
+            return Err(Error::Expired);
+        }
+        if Self::get_owner(env.clone())? != device_key {
+            return Err(Error::Timelocked);
+        }
+
+        let message = Self::action_message(&env, "authorize_fragment_release", Bytes::new(&env))?;
+        verify_ed25519(&env, &device_key, &message, &signature)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        let expires_at = now(&env).saturating_add(FRAGMENT_RELEASE_WINDOW);
+        let storage = env.storage().temporary();
+        storage.remove(&FragmentKey::RecoveryProof);
+        let key = FragmentKey::Release(device_key.clone());
+        storage.set(&key, &expires_at);
+        let ledgers = ledgers_for(FRAGMENT_RELEASE_WINDOW);
+        storage.extend_ttl(&key, ledgers, ledgers);
+
+        env.events().publish(
+            (Symbol::new(&env, events::FRAGMENT_RELEASE),),
+            FragmentReleaseEvent {
+                device_key,
+                expires_at,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_fragment_release_authorized(env: Env, new_device_key: BytesN<32>) -> bool {
+        env.storage()
+            .temporary()
+            .get::<_, u64>(&FragmentKey::Release(new_device_key))
+            .is_some_and(|expires_at| now(&env) <= expires_at)
+    }
+}
+
+/// Note that a recovery proof for `new_owner` was accepted, taking effect
+/// at `eta` (now, or the end of the rotation delay)
+pub(crate) fn record_recovery_proof(env: &Env, new_owner: &BytesN<32>, eta: u64) {
+    let from = eta.max(now(env));
+    let expiry = from.saturating_add(RECOVERY_PROOF_WINDOW);
+    let storage = env.storage().temporary();
+    storage.set(&FragmentKey::RecoveryProof, &(new_owner.clone(), expiry));
+    let ledgers = ledgers_for(expiry - now(env));
+    storage.extend_ttl(&FragmentKey::RecoveryProof, ledgers, ledgers);
+}
+
+/// Key the last recovery proof was accepted for, while it's live
+pub(crate) fn recovery_proof_key(env: &Env) -> Option<BytesN<32>> {
+    let proof: Option<(BytesN<32>, u64)> =
+        env.storage().temporary().get(&FragmentKey::RecoveryProof);
+    proof.map(|(key, _)| key)
+}
+
+pub(crate) fn clear_recovery_proof(env: &Env) {
+    env.storage()
+        .temporary()
+        .remove(&FragmentKey::RecoveryProof);
+}
+
+/// Ledgers covering `seconds` at 5s per ledger
+pub(crate) fn ledgers_for(seconds: u64) -> u32 {
+    (seconds / 5) as u32
+}
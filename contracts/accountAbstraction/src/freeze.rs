@@ -0,0 +1,114 @@
+// ============================================================================
+// EMERGENCY FREEZE
+//
+// A user who thinks their phone was stolen needs to stop the wallet now, not
+// after a rotation delay. `freeze` can be signed by anyone the wallet trusts
+// with a key: the owner, a weighted signer, the passkey, a linked device or
+// a guardian, and reporting a key with `report_compromise` freezes it too.
+// While frozen, `__check_auth` refuses everything, sessions included, and so
+// do the owner-signed entrypoints, upgrades and rotations.
+// Recovery stays open, and the owner can still veto a recovery, so guardians
+// can move the wallet to a new key while it is frozen.
+//
+// `unfreeze` takes the owner key, which may be the stolen one, so it only
+// works `UNFREEZE_DELAY` after the last freeze: as long as the recovery
+// delay, giving guardians time to recover first. Freezing again restarts
+// the delay.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, Bytes, BytesN, Env, Symbol};
+
+use crate::devices::is_device;
+use crate::rotation::is_registered;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Time after a freeze before the owner can lift it (3 days)
+pub const UNFREEZE_DELAY: u64 = RECOVERY_DELAY;
+
+/// Owner-signed actions a freeze doesn't block
+const FREEZE_EXEMPT_ACTIONS: [&str; 2] = ["unfreeze", "veto_recovery"];
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Freeze {
+    pub frozen_at: u64,
+    pub frozen_by: Signer,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum FreezeKey {
+    Freeze,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Freeze the wallet, or restart the unfreeze delay of a frozen one.
+    /// Signed by the owner, a registered signer, the passkey, a linked
+    /// device or a guardian.
+    pub fn freeze(env: Env, proof: SignerProof) -> Result<(), Error> {
+        let signer = proof.signer();
+        let trusted = is_registered(&env, &signer)?
+            || Self::get_guardians(env.clone()).contains(&signer)
+            || matches!(&signer, Signer::Ed25519(key) if is_device(&env, key));
+        if !trusted {
+            return Err(Error::Unauthorized);
+        }
+
+        let message = Self::action_message(&env, "freeze", Bytes::new(&env))?;
+        verify_proof(&env, &proof, &message)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        freeze_by(&env, signer);
+
+        Ok(())
+    }
+
+    /// Lift the freeze, once `UNFREEZE_DELAY` has passed since it was last
+    /// frozen
+    pub fn unfreeze(env: Env, signature: BytesN<64>) -> Result<(), Error> {
+        let freeze = Self::get_freeze(env.clone()).ok_or(Error::NotFound)?;
+        if now(&env) < freeze.frozen_at.saturating_add(UNFREEZE_DELAY) {
+            return Err(Error::Timelocked);
+        }
+
+        Self::require_owner_signature(&env, "unfreeze", Bytes::new(&env), signature)?;
+
+        env.storage().instance().remove(&FreezeKey::Freeze);
+        env.events()
+            .publish((Symbol::new(&env, events::WALLET_UNFROZEN),), ());
+
+        Ok(())
+    }
+
+    pub fn get_freeze(env: Env) -> Option<Freeze> {
+        env.storage().instance().get(&FreezeKey::Freeze)
+    }
+}
+
+/// Freeze the wallet on `signer`'s word, restarting the unfreeze delay
+pub(crate) fn freeze_by(env: &Env, signer: Signer) {
+    let freeze = Freeze {
+        frozen_at: now(env),
+        frozen_by: signer,
+    };
+    env.storage().instance().set(&FreezeKey::Freeze, &freeze);
+    env.events()
+        .publish((Symbol::new(env, events::WALLET_FROZEN),), freeze);
+}
+
+pub(crate) fn ensure_not_frozen(env: &Env) -> Result<(), Error> {
+    if env.storage().instance().has(&FreezeKey::Freeze) {
+        return Err(Error::Frozen);
+    }
+    Ok(())
+}
+
+/// `ensure_not_frozen` for an owner-signed action
+pub(crate) fn ensure_action_allowed(env: &Env, action: &str) -> Result<(), Error> {
+    if FREEZE_EXEMPT_ACTIONS.contains(&action) {
+        return Ok(());
+    }
+    ensure_not_frozen(env)
+}
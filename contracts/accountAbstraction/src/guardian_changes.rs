@@ -0,0 +1,177 @@
+// ============================================================================
+// GUARDIAN CHANGE DELAY
+//
+// Guardians are the last line of defense after a full key compromise: with
+// the owner key, devices and passkey in a thief's hands, they are what gets
+// the wallet back. A thief who could swap them out at once would cut that
+// off. So once recovery is on (a guardian threshold above 0), adding or
+// removing a guardian, changing the threshold and lowering this delay all
+// wait out the guardian delay, which is set apart from the other timelocks
+// and never shorter than `RECOVERY_DELAY`, so the real guardians always have
+// time to recover the wallet first.
+//
+// The owner proposes the change by id, sha256(action || payload) as
+// `propose_change` takes it, and makes the call once the delay is over.
+// Until then any guardian, linked device, registered signer or the passkey
+// can cancel it. Setting guardians up while recovery is off applies at once,
+// as does raising the delay.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol};
+
+use crate::approvals::{change_id, PENDING_CHANGE_LIFETIME};
+use crate::devices::is_device;
+use crate::fragments::ledgers_for;
+use crate::rotation::is_registered;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Delay until the owner sets one (7 days)
+pub const DEFAULT_GUARDIAN_DELAY: u64 = 7 * 24 * 60 * 60;
+pub const MAX_GUARDIAN_DELAY: u64 = 30 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum GuardianChangeKey {
+    GuardianDelay,
+    /// When a proposed guardian change can be made
+    GuardianChange(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianChangeProposedEvent {
+    pub change: BytesN<32>,
+    pub eta: u64,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set how long guardian changes wait once recovery is on, between
+    /// `RECOVERY_DELAY` and `MAX_GUARDIAN_DELAY`. Lowering it waits out the
+    /// current delay. Owner-signed over the delay.
+    pub fn set_guardian_delay(env: Env, delay: u64, signature: BytesN<64>) -> Result<(), Error> {
+        if delay > MAX_GUARDIAN_DELAY {
+            return Err(Error::LimitExceeded);
+        }
+        if delay < RECOVERY_DELAY {
+            return Err(Error::InvalidAmount);
+        }
+
+        let payload = delay.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_guardian_delay", payload.clone(), signature)?;
+        if delay < guardian_delay(&env) {
+            check_guardian_change(&env, "set_guardian_delay", &payload)?;
+        }
+
+        env.storage()
+            .instance()
+            .set(&GuardianChangeKey::GuardianDelay, &delay);
+        env.events()
+            .publish((Symbol::new(&env, events::GUARDIAN_DELAY),), delay);
+
+        Ok(())
+    }
+
+    pub fn get_guardian_delay(env: Env) -> u64 {
+        guardian_delay(&env)
+    }
+
+    /// Start the guardian delay for the change sha256(action || payload).
+    /// Owner-signed over the change id. Returns when it can be made.
+    pub fn propose_guardian_change(
+        env: Env,
+        change: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<u64, Error> {
+        if Self::get_guardian_change(env.clone(), change.clone()).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = Bytes::from_array(&env, &change.to_array());
+        Self::require_high_risk_signature(&env, "propose_guardian_change", payload, signature)?;
+
+        let delay = guardian_delay(&env);
+        let eta = now(&env).saturating_add(delay);
+        let key = GuardianChangeKey::GuardianChange(change.clone());
+        let storage = env.storage().temporary();
+        storage.set(&key, &eta);
+        let ledgers = ledgers_for(delay + PENDING_CHANGE_LIFETIME);
+        storage.extend_ttl(&key, ledgers, ledgers);
+        env.events().publish(
+            (Symbol::new(&env, events::GUARDIAN_CHANGE_PROPOSED),),
+            GuardianChangeProposedEvent { change, eta },
+        );
+
+        Ok(eta)
+    }
+
+    /// Cancel a proposed guardian change. Signed by a guardian, a linked
+    /// device, a registered signer, the passkey or the owner, over the
+    /// hashed action message.
+    pub fn cancel_guardian_change(
+        env: Env,
+        change: BytesN<32>,
+        proof: SignerProof,
+    ) -> Result<(), Error> {
+        if Self::get_guardian_change(env.clone(), change.clone()).is_none() {
+            return Err(Error::NotFound);
+        }
+        let signer = proof.signer();
+        let trusted = Self::get_guardians(env.clone()).contains(&signer)
+            || is_registered(&env, &signer)?
+            || matches!(&signer, Signer::Ed25519(key) if is_device(&env, key));
+        if !trusted {
+            return Err(Error::Unauthorized);
+        }
+
+        let payload = Bytes::from_array(&env, &change.to_array());
+        let message = Self::action_message(&env, "cancel_guardian_change", payload)?;
+        let digest = Bytes::from_array(&env, &env.crypto().sha256(&message).to_array());
+        verify_proof(&env, &proof, &digest)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        env.storage()
+            .temporary()
+            .remove(&GuardianChangeKey::GuardianChange(change.clone()));
+        env.events().publish(
+            (Symbol::new(&env, events::GUARDIAN_CHANGE_CANCELLED), change),
+            signer,
+        );
+
+        Ok(())
+    }
+
+    /// When a proposed guardian change can be made, if one is proposed
+    pub fn get_guardian_change(env: Env, change: BytesN<32>) -> Option<u64> {
+        env.storage()
+            .temporary()
+            .get(&GuardianChangeKey::GuardianChange(change))
+    }
+}
+
+/// Fail with `Timelocked` when recovery is on and the guardian change
+/// `action` wasn't proposed and waited out, using the proposal up when it was
+pub(crate) fn check_guardian_change(env: &Env, action: &str, payload: &Bytes) -> Result<(), Error> {
+    if WalletContract::get_guardian_threshold(env.clone()) == 0 {
+        return Ok(());
+    }
+
+    let change = change_id(env, action, payload);
+    let eta = WalletContract::get_guardian_change(env.clone(), change.clone())
+        .ok_or(Error::Timelocked)?;
+    if now(env) < eta {
+        return Err(Error::Timelocked);
+    }
+    env.storage()
+        .temporary()
+        .remove(&GuardianChangeKey::GuardianChange(change));
+    Ok(())
+}
+
+fn guardian_delay(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&GuardianChangeKey::GuardianDelay)
+        .unwrap_or(DEFAULT_GUARDIAN_DELAY)
+}
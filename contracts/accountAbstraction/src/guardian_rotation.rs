@@ -0,0 +1,99 @@
+// ============================================================================
+// GUARDIAN KEY ROTATION
+//
+// A guardian can replace its own key without the owner, so an institutional
+// guardian (the app operator, a custodian) can run scheduled key ceremonies
+// across every wallet it guards. The old key signs over the new one, which
+// proves the new key is the same guardian's, and the new key takes the old
+// one's place. The number of guardians and the threshold don't change, so
+// this skips the guardian delay that guards owner changes to the set (see
+// `guardian_changes`). Approvals the old key gave a pending recovery don't
+// carry over.
+//
+// A key rotated in can't rotate again for `GUARDIAN_ROTATION_INTERVAL`, so a
+// leaked guardian key can't be walked through fresh keys faster than the
+// owner can notice `guardian_rotated` and remove the guardian.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Env, Symbol};
+
+use crate::fragments::ledgers_for;
+use crate::recovery::verify_guardian;
+use crate::*;
+
+/// Time before a rotated-in guardian key can rotate again (7 days)
+pub const GUARDIAN_ROTATION_INTERVAL: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum GuardianRotationKey {
+    /// When a guardian key was rotated in, while it can't rotate again
+    RotatedIn(Signer),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianRotatedEvent {
+    pub old_guardian: Signer,
+    pub new_guardian: Signer,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Replace the guardian key `old_pk` with `new_pk`. Signed by `old_pk`
+    /// over the hashed action message, with `new_pk` as the payload.
+    pub fn guardian_rotate(
+        env: Env,
+        old_pk: Signer,
+        new_pk: Signer,
+        signature_old: SignerProof,
+    ) -> Result<(), Error> {
+        if signature_old.signer() != old_pk {
+            return Err(Error::Unauthorized);
+        }
+        // Same rules as `add_guardian`
+        if new_pk == Signer::Ed25519(Self::get_owner(env.clone())?) {
+            return Err(Error::InvalidOwner);
+        }
+        let mut guardians = Self::get_guardians(env.clone());
+        if guardians.contains(&new_pk) {
+            return Err(Error::AlreadyExists);
+        }
+        if Self::get_guardian_rotated_at(env.clone(), old_pk.clone()).is_some() {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = new_pk.clone().to_xdr(&env);
+        verify_guardian(&env, "guardian_rotate", payload, &signature_old)?;
+
+        let position = guardians.first_index_of(&old_pk).ok_or(Error::NotFound)?;
+        guardians.set(position, new_pk.clone());
+        env.storage()
+            .instance()
+            .set(&RecoveryKey::Guardians, &guardians);
+
+        let key = GuardianRotationKey::RotatedIn(new_pk.clone());
+        let storage = env.storage().temporary();
+        storage.set(&key, &now(&env));
+        let ledgers = ledgers_for(GUARDIAN_ROTATION_INTERVAL);
+        storage.extend_ttl(&key, ledgers, ledgers);
+
+        env.events().publish(
+            (Symbol::new(&env, events::GUARDIAN_ROTATED),),
+            GuardianRotatedEvent {
+                old_guardian: old_pk,
+                new_guardian: new_pk,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// When `guardian` was rotated in, while it can't rotate again
+    pub fn get_guardian_rotated_at(env: Env, guardian: Signer) -> Option<u64> {
+        env.storage()
+            .temporary()
+            .get::<_, u64>(&GuardianRotationKey::RotatedIn(guardian))
+            .filter(|at| now(&env) < at.saturating_add(GUARDIAN_ROTATION_INTERVAL))
+    }
+}
@@ -0,0 +1,128 @@
+// ============================================================================
+// HEALTH CHECK
+//
+// `self_check` inspects the wallet's own state and reports what looks wrong,
+// so the backend can sweep the fleet and repair wallets before a user's
+// transaction fails on them. It is read-only and needs no signature.
+//
+// Two things the report can't cover from inside the contract: entry TTLs
+// (not readable by contracts; take `liveUntilLedgerSeq` from
+// getLedgerEntries) and the code hash, so the wallet reports its crate
+// version for the backend to compare with the factory's current release.
+// Sessions aren't enumerable either; the caller passes the keys it knows of
+// from `session_created` events.
+//
+// `ping` is the cheap counterpart for uptime monitoring: simulating it
+// proves the RPC can reach and execute the deployed code. It also reports
+// how many nonce channels the wallet has opened, for relayers to size
+// their parallel submissions. Simulations are free and independent, but a
+// ping submitted on-chain only loads the wallet for nothing, so it answers
+// once per ledger.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, BytesN, Env, String, Symbol, Vec};
+
+use crate::migration::storage_version;
+use crate::nonces::channels_in_use;
+use crate::recovery::guardian_threshold;
+use crate::reserve::ReserveKey;
+use crate::rotation::rotation_delay;
+use crate::sessions::session_epoch;
+use crate::signers::{ensure_reachable, threshold};
+use crate::*;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum HealthKey {
+    /// Ledger sequence `ping` last ran in
+    LastPing,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HealthReport {
+    /// Crate version of the wallet code
+    pub version: String,
+    /// Problems found, as short codes; empty for a healthy wallet
+    pub issues: Vec<Symbol>,
+    /// Session entries still in storage that can never authorize again
+    pub orphaned_sessions: Vec<BytesN<32>>,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Check storage and configuration, and which of `sessions` are dead
+    /// entries
+    pub fn self_check(env: Env, sessions: Vec<BytesN<32>>) -> Result<HealthReport, Error> {
+        if sessions.len() > MAX_PAGE_LIMIT {
+            return Err(Error::LimitExceeded);
+        }
+
+        let mut issues = Vec::new(&env);
+        let mut flag = |code: &str| issues.push_back(Symbol::new(&env, code));
+        let storage = env.storage().instance();
+
+        if !storage.has(&DataKey::Owner) {
+            flag("missing_owner");
+        }
+        if !storage.has(&DataKey::EmailHash) {
+            flag("missing_email_hash");
+        }
+        if !storage.has(&DataKey::Nonce) {
+            flag("missing_nonce");
+        }
+        if storage_version(&env) < STORAGE_VERSION {
+            flag("storage_not_migrated");
+        }
+        if ensure_reachable(&Self::get_signers(env.clone()), threshold(&env)).is_err() {
+            flag("threshold_unreachable");
+        }
+        if guardian_threshold(&env) > Self::get_guardians(env.clone()).len() {
+            flag("guardian_threshold_unreachable");
+        }
+        if rotation_delay(&env) > MAX_ROTATION_DELAY {
+            flag("rotation_delay_too_long");
+        }
+        let reserve: Option<ReserveConfig> = storage.get(&ReserveKey::ReserveConfig);
+        if reserve.is_some_and(|r| r.min_reserve < 0) {
+            flag("negative_reserve");
+        }
+
+        let epoch = session_epoch(&env);
+        let now = now(&env);
+        let mut orphaned_sessions = Vec::new(&env);
+        for key in sessions.iter() {
+            let session: Option<Session> = env
+                .storage()
+                .persistent()
+                .get(&SessionKey::Session(key.clone()));
+            if session.is_some_and(|s| s.epoch != epoch || now > s.expires_at) {
+                orphaned_sessions.push_back(key);
+            }
+        }
+
+        Ok(HealthReport {
+            version: version(&env),
+            issues,
+            orphaned_sessions,
+        })
+    }
+
+    /// Crate version, current ledger and the number of nonce channels in
+    /// use besides the wallet nonce. Fails with `LimitExceeded` if it
+    /// already ran in this ledger.
+    pub fn ping(env: Env) -> Result<(String, u32, u32), Error> {
+        let ledger = env.ledger().sequence();
+        let storage = env.storage().temporary();
+        if storage.get(&HealthKey::LastPing) == Some(ledger) {
+            return Err(Error::LimitExceeded);
+        }
+        storage.set(&HealthKey::LastPing, &ledger);
+
+        Ok((version(&env), ledger, channels_in_use(&env)))
+    }
+}
+
+fn version(env: &Env) -> String {
+    String::from_str(env, env!("CARGO_PKG_VERSION"))
+}
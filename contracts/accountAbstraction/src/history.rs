@@ -0,0 +1,94 @@
+// ============================================================================
+// RECENT OPERATIONS
+//
+// A fixed-size ring buffer of the wallet's latest value-moving operations.
+// RPC nodes only retain events for a limited window, so lightweight clients
+// read recent activity from here instead of needing an indexer.
+// ============================================================================
+
+use acceslyinterface::clamp_limit;
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::privacy::privacy_salt;
+use crate::*;
+
+/// Operations kept before the oldest is overwritten
+pub const RECENT_OPS_CAPACITY: u32 = 32;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OpKind {
+    VirtualPayment,
+    SponsorRepayment,
+    Distribution,
+    QuotePayment,
+    Lending,
+    Conversion,
+    AllowancePull,
+    Subscription,
+    LargeTransfer,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpSummary {
+    pub kind: OpKind,
+    pub asset: Address,
+    /// Positive for funds received, negative for funds sent
+    pub amount: i128,
+    /// None when the operation paid several parties
+    pub counterparty: Option<Address>,
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum HistoryKey {
+    /// Operations recorded since the wallet was created
+    OpCount,
+    RecentOp(u32),
+}
+
+/// Append an operation, overwriting the oldest once the buffer is full
+pub(crate) fn record_op(
+    env: &Env,
+    kind: OpKind,
+    asset: &Address,
+    amount: i128,
+    counterparty: Option<Address>,
+) {
+    let count: u32 = env.storage().instance().get(&HistoryKey::OpCount).unwrap_or(0);
+    let summary = OpSummary {
+        kind,
+        asset: asset.clone(),
+        amount,
+        // Privacy mode keeps counterparties out of readable storage too
+        counterparty: counterparty.filter(|_| privacy_salt(env).is_none()),
+        ledger: env.ledger().sequence(),
+    };
+    env.storage()
+        .persistent()
+        .set(&HistoryKey::RecentOp(count % RECENT_OPS_CAPACITY), &summary);
+    env.storage()
+        .instance()
+        .set(&HistoryKey::OpCount, &count.wrapping_add(1));
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Most recent operations, newest first. `limit` follows the shared
+    /// page size rules (0 for the default, capped at `MAX_PAGE_LIMIT`).
+    pub fn get_recent_ops(env: Env, limit: u32) -> Vec<OpSummary> {
+        let count: u32 = env.storage().instance().get(&HistoryKey::OpCount).unwrap_or(0);
+        let take = clamp_limit(limit).min(count).min(RECENT_OPS_CAPACITY);
+
+        let mut ops = Vec::new(&env);
+        for back in 1..=take {
+            let slot = count.wrapping_sub(back) % RECENT_OPS_CAPACITY;
+            if let Some(op) = env.storage().persistent().get(&HistoryKey::RecentOp(slot)) {
+                ops.push_back(op);
+            }
+        }
+        ops
+    }
+}
@@ -0,0 +1,348 @@
+// ============================================================================
+// LARGE TRANSFER TIMELOCK
+//
+// Anti-drain defense for a leaked key. The owner sets a threshold per asset,
+// and outgoing funds of more than that are refused on every immediate path:
+// transfers, approvals and burns authorized in `__check_auth`, batches (and
+// so meta-transactions and scheduled calls) including the nested calls their
+// ops authorize, allowance pulls, subscription collections, share
+// distributions and quote payments. An approval counts
+// in full, since the spender can take it all later without the wallet.
+// Amounts are summed per asset over one authorization or batch, so
+// splitting a transfer into several calls doesn't get it under.
+//
+// Large transfers go through a queue instead: the owner queues one, the
+// wallet publishes `large_transfer_queued`, and after `LARGE_TRANSFER_DELAY`
+// anyone can execute it. Until then the owner or any other registered
+// device can cancel, as with a key rotation. A queued transfer still counts
+// against a post-recovery cooldown when it is queued, so the queue's delay
+// is no way to outwait one longer than it. Raising or removing a
+// threshold waits out the same delay before it applies, so a stolen owner
+// key can't lift the limit and drain at once.
+// ============================================================================
+
+use accesly_policy::{check_threshold, effective_threshold};
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Map,
+    Symbol, Val, Vec,
+};
+
+use crate::approvals::PENDING_CHANGE_LIFETIME;
+use crate::devices::is_device;
+use crate::fragments::ledgers_for;
+use crate::freeze::ensure_not_frozen;
+use crate::history::record_op;
+use crate::outflows::outflow;
+use crate::reserve::ReserveGuard;
+use crate::rotation::is_registered;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Time between queueing a large transfer and executing it (24 hours)
+pub const LARGE_TRANSFER_DELAY: u64 = 24 * 60 * 60;
+pub const MAX_LARGE_TRANSFER_ASSETS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransferThreshold {
+    /// Most one authorization or batch can send at once; 0 for no limit
+    pub threshold: i128,
+    /// Threshold that applies until `effective_at`, while a raise waits
+    pub previous: i128,
+    pub effective_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueuedTransfer {
+    pub asset: Address,
+    pub to: Address,
+    pub amount: i128,
+    /// Earliest ledger timestamp it can be executed at
+    pub eta: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum LargeTransferKey {
+    /// Map<Address, TransferThreshold> by asset
+    Thresholds,
+    Queued(u32),
+    NextQueuedId,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LargeTransferQueuedEvent {
+    pub id: u32,
+    pub transfer: QueuedTransfer,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LargeTransferCancelledEvent {
+    pub id: u32,
+    /// Device that cancelled
+    pub cancelled_by: Signer,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Hold transfers of more than `threshold` of `asset` for the delay; 0
+    /// removes the limit. Lowering applies at once, raising or removing
+    /// after `LARGE_TRANSFER_DELAY`. Owner-signed over (asset, threshold).
+    pub fn set_large_transfer_threshold(
+        env: Env,
+        asset: Address,
+        threshold: i128,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if threshold < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let mut thresholds = thresholds(&env);
+        if !thresholds.contains_key(asset.clone()) && thresholds.len() >= MAX_LARGE_TRANSFER_ASSETS
+        {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (asset.clone(), threshold).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_large_transfer_threshold", payload, signature)?;
+
+        let now = now(&env);
+        let current = effective(thresholds.get(asset.clone()), now);
+        let loosens = current > 0 && (threshold == 0 || threshold > current);
+        let config = TransferThreshold {
+            threshold,
+            previous: if loosens { current } else { threshold },
+            effective_at: if loosens {
+                now.saturating_add(LARGE_TRANSFER_DELAY)
+            } else {
+                now
+            },
+        };
+        if threshold == 0 && !loosens {
+            thresholds.remove(asset.clone());
+        } else {
+            thresholds.set(asset.clone(), config.clone());
+        }
+        env.storage()
+            .instance()
+            .set(&LargeTransferKey::Thresholds, &thresholds);
+        env.events().publish(
+            (Symbol::new(&env, events::LARGE_TRANSFER_THRESHOLD), asset),
+            config,
+        );
+
+        Ok(())
+    }
+
+    /// Threshold in force for `asset` now, 0 when there is none
+    pub fn get_large_transfer_threshold(env: Env, asset: Address) -> i128 {
+        effective(thresholds(&env).get(asset), now(&env))
+    }
+
+    /// Queue a transfer of `amount` of `asset` to `to`, to run after the
+    /// delay. Owner-signed over (asset, to, amount). Returns its id.
+    pub fn queue_transfer(
+        env: Env,
+        asset: Address,
+        to: Address,
+        amount: i128,
+        signature: BytesN<64>,
+    ) -> Result<u32, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        contacts::check_destination(&env, &to)?;
+
+        let payload = (asset.clone(), to.clone(), amount).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "queue_transfer", payload, signature)?;
+        recovery_cooldown::spend(&env, &Map::from_array(&env, [(asset.clone(), amount)]))?;
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&LargeTransferKey::NextQueuedId)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&LargeTransferKey::NextQueuedId, &(id + 1));
+
+        let transfer = QueuedTransfer {
+            asset,
+            to,
+            amount,
+            eta: now(&env).saturating_add(LARGE_TRANSFER_DELAY),
+        };
+        let key = LargeTransferKey::Queued(id);
+        let storage = env.storage().persistent();
+        storage.set(&key, &transfer);
+        let ledgers = ledgers_for(LARGE_TRANSFER_DELAY + PENDING_CHANGE_LIFETIME);
+        storage.extend_ttl(&key, ledgers, ledgers);
+        env.events().publish(
+            (Symbol::new(&env, events::LARGE_TRANSFER_QUEUED), id),
+            LargeTransferQueuedEvent { id, transfer },
+        );
+
+        Ok(id)
+    }
+
+    /// Send a queued transfer once its delay has passed. Anyone can call.
+    pub fn execute_queued_transfer(env: Env, id: u32) -> Result<(), Error> {
+        let transfer = Self::get_queued_transfer(env.clone(), id).ok_or(Error::NotFound)?;
+        ensure_not_frozen(&env)?;
+        if now(&env) < transfer.eta {
+            return Err(Error::Timelocked);
+        }
+        contacts::check_destination(&env, &transfer.to)?;
+
+        env.storage()
+            .persistent()
+            .remove(&LargeTransferKey::Queued(id));
+
+        let reserve = ReserveGuard::new(&env);
+        token::Client::new(&env, &transfer.asset).transfer(
+            &env.current_contract_address(),
+            &transfer.to,
+            &transfer.amount,
+        );
+        reserve.check(&env)?;
+
+        record_op(
+            &env,
+            OpKind::LargeTransfer,
+            &transfer.asset,
+            -transfer.amount,
+            Some(transfer.to),
+        );
+        env.events()
+            .publish((Symbol::new(&env, events::LARGE_TRANSFER_EXECUTED), id), ());
+
+        Ok(())
+    }
+
+    /// Cancel a queued transfer. Signed by the owner, a registered signer,
+    /// the passkey or a linked device.
+    pub fn cancel_queued_transfer(env: Env, id: u32, proof: SignerProof) -> Result<(), Error> {
+        if Self::get_queued_transfer(env.clone(), id).is_none() {
+            return Err(Error::NotFound);
+        }
+        let device = proof.signer();
+        let trusted = is_registered(&env, &device)?
+            || matches!(&device, Signer::Ed25519(key) if is_device(&env, key));
+        if !trusted {
+            return Err(Error::Unauthorized);
+        }
+
+        let payload = Bytes::from_array(&env, &id.to_be_bytes());
+        let message = Self::action_message(&env, "cancel_queued_transfer", payload)?;
+        verify_proof(&env, &proof, &message)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        env.storage()
+            .persistent()
+            .remove(&LargeTransferKey::Queued(id));
+        env.events().publish(
+            (Symbol::new(&env, events::LARGE_TRANSFER_CANCELLED), id),
+            LargeTransferCancelledEvent {
+                id,
+                cancelled_by: device,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_queued_transfer(env: Env, id: u32) -> Option<QueuedTransfer> {
+        env.storage()
+            .persistent()
+            .get(&LargeTransferKey::Queued(id))
+    }
+}
+
+fn thresholds(env: &Env) -> Map<Address, TransferThreshold> {
+    env.storage()
+        .instance()
+        .get(&LargeTransferKey::Thresholds)
+        .unwrap_or(Map::new(env))
+}
+
+fn effective(config: Option<TransferThreshold>, now: u64) -> i128 {
+    config.map_or(0, |config| {
+        effective_threshold(config.threshold, config.previous, config.effective_at, now)
+    })
+}
+
+/// Outgoing amounts per asset, checked against the thresholds
+struct Outgoing {
+    thresholds: Map<Address, TransferThreshold>,
+    sent: Map<Address, i128>,
+}
+
+impl Outgoing {
+    fn load(env: &Env) -> Option<Self> {
+        let thresholds = thresholds(env);
+        if thresholds.is_empty() {
+            return None;
+        }
+        Some(Self {
+            thresholds,
+            sent: Map::new(env),
+        })
+    }
+
+    /// Count the call if it moves a limited asset out of the wallet
+    fn add(&mut self, env: &Env, contract: &Address, fn_name: &Symbol, args: &Vec<Val>) {
+        if !self.thresholds.contains_key(contract.clone()) {
+            return;
+        }
+        if let Some(flow) = outflow(env, fn_name, args) {
+            let sent = self.sent.get(contract.clone()).unwrap_or(0);
+            self.sent
+                .set(contract.clone(), sent.saturating_add(flow.amount));
+        }
+    }
+
+    fn check(&self, env: &Env) -> Result<(), Error> {
+        let now = now(env);
+        for (asset, sent) in self.sent.iter() {
+            check_threshold(sent, effective(self.thresholds.get(asset), now))?;
+        }
+        Ok(())
+    }
+}
+
+/// Fail with `Timelocked` if the calls in `contexts` send or approve more
+/// than a threshold
+pub(crate) fn check_auth_contexts(env: &Env, contexts: &Vec<Context>) -> Result<(), Error> {
+    let Some(mut outgoing) = Outgoing::load(env) else {
+        return Ok(());
+    };
+    for context in contexts.iter() {
+        if let Context::Contract(call) = context {
+            outgoing.add(env, &call.contract, &call.fn_name, &call.args);
+        }
+    }
+    outgoing.check(env)
+}
+
+/// Fail with `Timelocked` if the calls of a batch, nested ones included (see
+/// `batch::calls`), send or approve more than a threshold
+pub(crate) fn check_batch(env: &Env, ops: &Vec<Invocation>) -> Result<(), Error> {
+    let Some(mut outgoing) = Outgoing::load(env) else {
+        return Ok(());
+    };
+    for op in ops.iter() {
+        outgoing.add(env, &op.contract, &op.fn_name, &op.args);
+    }
+    outgoing.check(env)
+}
+
+/// Fail with `Timelocked` if the wallet's own transfer of `amount` of
+/// `asset` is over its threshold
+pub(crate) fn check_transfer(env: &Env, asset: &Address, amount: i128) -> Result<(), Error> {
+    let threshold = effective(thresholds(env).get(asset.clone()), now(env));
+    Ok(check_threshold(amount, threshold)?)
+}
@@ -0,0 +1,207 @@
+// ============================================================================
+// LENDING ADAPTER (BLEND)
+//
+// Narrow, policy-gated access to Blend-style lending pools. The owner
+// whitelists pools, and the wallet only ever builds supply, withdraw and
+// repay requests itself; there is no generic invoke. Supplying collateral
+// (the step that makes borrowing, and liquidation, possible) additionally
+// needs its own owner approval capping the amount per pool and asset.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contractclient, contractimpl, contracttype, vec, xdr::ToXdr, Address, BytesN, Env, IntoVal,
+    Symbol, Val, Vec,
+};
+
+use crate::history::record_op;
+use crate::reserve::ReserveGuard;
+use crate::*;
+
+/// Request as defined by the Blend pool `submit` entrypoint
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LendingRequest {
+    pub request_type: u32,
+    pub address: Address,
+    pub amount: i128,
+}
+
+#[contractclient(name = "LendingPoolClient")]
+pub trait LendingPool {
+    /// Returns the account's positions, which the wallet doesn't read
+    fn submit(env: Env, from: Address, spender: Address, to: Address, requests: Vec<LendingRequest>) -> Val;
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LendAction {
+    Supply,
+    Withdraw,
+    Repay,
+    SupplyCollateral,
+}
+
+impl LendAction {
+    /// Blend request type
+    fn request_type(self) -> u32 {
+        match self {
+            LendAction::Supply => 0,
+            LendAction::Withdraw => 1,
+            LendAction::SupplyCollateral => 2,
+            LendAction::Repay => 5,
+        }
+    }
+
+    /// Whether the pool pulls `asset` from the wallet
+    fn pays_in(self) -> bool {
+        !matches!(self, LendAction::Withdraw)
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum LendingKey {
+    LendingPool(Address),
+    /// Collateral the owner approved per (pool, asset)
+    CollateralAllowance(Address, Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LendingEvent {
+    pub pool: Address,
+    pub action: LendAction,
+    pub asset: Address,
+    pub amount: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Allow or disallow a lending pool
+    pub fn set_lending_pool(
+        env: Env,
+        pool: Address,
+        allowed: bool,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = (pool.clone(), allowed).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_lending_pool", payload, signature)?;
+
+        let key = LendingKey::LendingPool(pool);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_lending_pool(env: Env, pool: Address) -> bool {
+        env.storage().persistent().has(&LendingKey::LendingPool(pool))
+    }
+
+    /// Approve supplying up to `amount` of `asset` as collateral to `pool`.
+    /// Replaces any previous approval.
+    pub fn approve_collateral(
+        env: Env,
+        pool: Address,
+        asset: Address,
+        amount: i128,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let payload = (pool.clone(), asset.clone(), amount).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "approve_collateral", payload, signature)?;
+
+        env.storage()
+            .persistent()
+            .set(&LendingKey::CollateralAllowance(pool, asset), &amount);
+
+        Ok(())
+    }
+
+    pub fn get_collateral_allowance(env: Env, pool: Address, asset: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&LendingKey::CollateralAllowance(pool, asset))
+            .unwrap_or(0)
+    }
+
+    /// Supply, withdraw, repay or supply collateral on a whitelisted pool
+    pub fn lend(
+        env: Env,
+        pool: Address,
+        action: LendAction,
+        asset: Address,
+        amount: i128,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if !Self::is_lending_pool(env.clone(), pool.clone()) {
+            return Err(Error::Unauthorized);
+        }
+
+        let payload = (pool.clone(), action, asset.clone(), amount).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "lend", payload, signature)?;
+
+        if action == LendAction::SupplyCollateral {
+            let allowance = Self::get_collateral_allowance(env.clone(), pool.clone(), asset.clone());
+            if amount > allowance {
+                return Err(Error::Unauthorized);
+            }
+            env.storage().persistent().set(
+                &LendingKey::CollateralAllowance(pool.clone(), asset.clone()),
+                &(allowance - amount),
+            );
+        }
+
+        let reserve = ReserveGuard::new(&env);
+        let wallet = env.current_contract_address();
+        if action.pays_in() {
+            // The pool pulls the asset from the wallet inside `submit`
+            env.authorize_as_current_contract(vec![
+                &env,
+                InvokerContractAuthEntry::Contract(SubContractInvocation {
+                    context: ContractContext {
+                        contract: asset.clone(),
+                        fn_name: Symbol::new(&env, "transfer"),
+                        args: (wallet.clone(), pool.clone(), amount).into_val(&env),
+                    },
+                    sub_invocations: Vec::new(&env),
+                }),
+            ]);
+        }
+
+        let request = LendingRequest {
+            request_type: action.request_type(),
+            address: asset.clone(),
+            amount,
+        };
+        LendingPoolClient::new(&env, &pool).submit(&wallet, &wallet, &wallet, &vec![&env, request]);
+
+        let signed_amount = if action.pays_in() { -amount } else { amount };
+        record_op(&env, OpKind::Lending, &asset, signed_amount, Some(pool.clone()));
+        reserve.check(&env)?;
+
+        env.events().publish(
+            (Symbol::new(&env, events::LENDING), pool.clone()),
+            LendingEvent {
+                pool,
+                action,
+                asset,
+                amount,
+            },
+        );
+
+        Ok(())
+    }
+}
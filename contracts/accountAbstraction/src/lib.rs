@@ -1,9 +1,194 @@
 #![no_std]
+#![allow(non_snake_case)]
 
 use soroban_sdk::{
     contract, contracterror, contractimpl, contracttype, 
     Bytes, BytesN, Env, Symbol,
 };
+use acceslyinterface::events;
+
+mod allowances;
+mod approvals;
+mod attestations;
+mod auth_policy;
+mod batch;
+mod compromise;
+mod contacts;
+mod conversion;
+mod deposit;
+#[cfg(feature = "dev-mode")]
+mod dev;
+mod devices;
+mod display;
+mod email_recovery;
+mod fees;
+mod fiat_limits;
+mod fragments;
+mod freeze;
+mod guardian_changes;
+mod guardian_rotation;
+mod health;
+mod history;
+mod large_transfers;
+mod lending;
+mod meta_tx;
+mod migration;
+mod nonces;
+mod notifications;
+mod oracle;
+mod outflows;
+mod own_accounts;
+mod paging;
+mod policy;
+mod preauth;
+mod presets;
+mod privacy;
+mod quote;
+mod receipts;
+mod recovery;
+mod recovery_cooldown;
+mod relayers;
+mod reserve;
+mod rotation;
+mod scheduled;
+mod sessions;
+mod shares;
+mod signature;
+mod signers;
+mod snapshots;
+mod sponsor;
+mod subaddress;
+mod subscriptions;
+mod support;
+mod swap;
+mod ttl;
+mod upgrade;
+mod velocity;
+mod webauthn;
+
+pub use acceslyinterface::{PolicyPreset, MAX_PAGE_LIMIT};
+pub use allowances::{
+    Allowance, AllowanceApprovedEvent, AllowanceKey, AllowancePulledEvent, MAX_ALLOWANCE_WINDOW,
+};
+pub use approvals::{
+    ApprovalKey, ChangeProposedEvent, PendingChange, MAX_NEW_KEY_WINDOW, PENDING_CHANGE_LIFETIME,
+};
+pub use attestations::{Attestation, AttestationKey, AttestationRecordedEvent};
+pub use auth_policy::{AuthLevel, AuthPolicyKey, AuthPolicySetEvent, MAX_AUTH_POLICIES};
+pub use batch::{
+    BatchExecutedEvent, BatchKey, BatchLimits, BatchOpEvent, BatchOpOutcome, Invocation,
+    DEFAULT_OP_WEIGHT, MAX_BATCH_COMPLEXITY, MAX_BATCH_DEPTH, MAX_BATCH_OPS, MAX_OP_WEIGHTS,
+};
+pub use compromise::{CompromiseKey, QuarantinedKey, MAX_QUARANTINED_KEYS};
+pub use contacts::{
+    Contact, ContactsKey, ContactsOnly, CONTACT_COOLING_OFF, MAX_CONTACTS, MAX_CONTACT_LABEL_LEN,
+};
+pub use conversion::{
+    ConversionEvent, ConversionKey, ConversionRule, ConversionRuleChange, MAX_SLIPPAGE_BPS,
+};
+pub use deposit::{DepositBinding, DepositBindingEvent, DepositKey};
+#[cfg(feature = "dev-mode")]
+pub use dev::DevKey;
+pub use devices::{Device, DeviceKey, DEVICE_REMOVAL_DELAY, MAX_DEVICES, MAX_DEVICE_LABEL_LEN};
+pub use display::{
+    AssetDisplay, DisplayKey, MAX_DISPLAY_DECIMALS, MAX_DISPLAY_OVERRIDES, MAX_DISPLAY_SYMBOL_LEN,
+};
+pub use email_recovery::{
+    EmailRecoveryEvent, EmailRecoveryKey, EmailRotatedEvent, EmailVerifier, EmailVerifierClient,
+};
+pub use fees::{FeeConfig, FeeKey};
+pub use fiat_limits::{FiatLimit, FiatLimitChange, FiatLimitKey, OracleChange, StalePrice};
+pub use fragments::{
+    FragmentKey, FragmentReleaseEvent, FRAGMENT_RELEASE_WINDOW, RECOVERY_PROOF_WINDOW,
+};
+pub use freeze::{Freeze, FreezeKey, UNFREEZE_DELAY};
+pub use guardian_changes::{
+    GuardianChangeKey, GuardianChangeProposedEvent, DEFAULT_GUARDIAN_DELAY, MAX_GUARDIAN_DELAY,
+};
+pub use guardian_rotation::{
+    GuardianRotatedEvent, GuardianRotationKey, GUARDIAN_ROTATION_INTERVAL,
+};
+pub use health::{HealthKey, HealthReport};
+pub use history::{HistoryKey, OpKind, OpSummary, RECENT_OPS_CAPACITY};
+pub use large_transfers::{
+    LargeTransferCancelledEvent, LargeTransferKey, LargeTransferQueuedEvent, QueuedTransfer,
+    TransferThreshold, LARGE_TRANSFER_DELAY, MAX_LARGE_TRANSFER_ASSETS,
+};
+pub use lending::{
+    LendAction, LendingEvent, LendingKey, LendingPool, LendingPoolClient, LendingRequest,
+};
+pub use meta_tx::{MetaTx, MetaTxExecutedEvent, MetaTxKey, MAX_META_TX_WINDOW};
+pub use migration::{StorageMigratedEvent, STORAGE_VERSION};
+pub use nonces::{ChannelSignature, ExpiringSignature, NonceKey};
+pub use notifications::{
+    NotificationFilter, NotificationFilterEvent, NotificationKey, MAX_NOTIFICATION_CHANNELS,
+};
+pub use oracle::{OracleAsset, PriceOracle, PriceOracleClient, FIAT_DECIMALS, MAX_PRICE_AGE};
+pub use own_accounts::{
+    InternalTransferEvent, OwnAccount, OwnAccountsKey, MAX_OWN_ACCOUNTS, OWN_ACCOUNT_COOLING_OFF,
+};
+pub use paging::IndexKey;
+pub use policy::{ContractBlockedEvent, ContractInvokedEvent, ContractListing, PolicyKey};
+pub use preauth::{PreAuthGrantedEvent, PreAuthKey, MAX_PREAUTH_WINDOW};
+pub use privacy::{amount_bucket, PrivacyKey, PrivateTransferEvent};
+pub use quote::{Quote, QuoteAcceptedEvent, QuoteKey, QuoteReceipt, SignedQuote};
+pub use receipts::{ReceiptsCommitment, ReceiptsCommittedEvent, ReceiptsKey};
+pub use recovery::{
+    GuardianChangedEvent, RecoveryApprovedEvent, RecoveryInitiatedEvent, RecoveryKey,
+    RecoveryRequest, MAX_GUARDIANS, RECOVERY_DELAY,
+};
+pub use recovery_cooldown::{
+    CooldownConfig, CooldownKey, RecoveryCooldown, MAX_COOLDOWN_ASSETS, MAX_RECOVERY_COOLDOWN,
+    RECOVERY_COOLDOWN, RECOVERY_SPEND_CAP,
+};
+pub use relayers::{RelayedSignature, RelayerKey, MAX_RELAYERS};
+pub use reserve::{ReserveConfig, ReserveKey};
+pub use rotation::{
+    PendingRotation, RotationCancelledEvent, RotationKey, RotationProposedEvent,
+    MAX_ROTATION_DELAY,
+};
+pub use scheduled::{
+    ScheduledEvent, ScheduledKey, ScheduledOp, MAX_SCHEDULE_DELAY, SCHEDULE_EXECUTION_WINDOW,
+};
+pub use sessions::{
+    Session, SessionAuthEvent, SessionCall, SessionCreatedEvent, SessionDerivedEvent, SessionKey,
+    SessionScope, MAX_SESSION_CALLS, MAX_SESSION_CHILDREN, MAX_SESSION_DEPTH,
+    MAX_SESSION_DURATION,
+};
+pub use shares::{DistributionEvent, ShareHolder, SharesKey, SharesSetEvent, MAX_SHARE_HOLDERS};
+pub use signers::{
+    Signer, SignerChangedEvent, SignerProof, SignersKey, ThresholdChangedEvent, MAX_SIGNERS,
+};
+pub use snapshots::{
+    AssetBalance, BalanceSnapshot, SnapshotCommitment, SnapshotKey, MAX_SNAPSHOT_ASSETS,
+    SNAPSHOT_EPOCH_LEDGERS,
+};
+pub use sponsor::{SponsorKey, SponsorRepaidEvent, Sponsorship, SponsorshipRecordedEvent};
+pub use subaddress::{
+    SubaddressKey, VirtualAccount, VirtualIdRegisteredEvent, VirtualPaymentEvent,
+};
+pub use subscriptions::{
+    Subscription, SubscriptionCollectedEvent, SubscriptionKey, SubscriptionMissedEvent,
+    MAX_SUBSCRIPTIONS, MIN_SUBSCRIPTION_INTERVAL,
+};
+pub use support::{ReadGrant, SupportKey, MAX_READ_GRANT_DURATION};
+pub use swap::{SwapRouter, SwapRouterClient};
+pub use ttl::INSTANCE_BUMP_LEDGERS;
+pub use upgrade::{
+    PendingUpgrade, UpgradeCancelledEvent, UpgradeKey, WalletFactory, WalletFactoryClient,
+    UPGRADE_DELAY,
+};
+pub use velocity::{
+    VelocityBypass, VelocityKey, VelocityLimit, MAX_VELOCITY_OPS, MAX_VELOCITY_WINDOW,
+    MIN_VELOCITY_WINDOW,
+};
+pub use webauthn::{
+    AuthSignature, PasskeyChangedEvent, PasskeyKey, WebAuthnSignature, MAX_CLIENT_DATA_LEN,
+};
+
+// Build identity (version, git commit, cargo features) in contractmetav0,
+// generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/build_meta.rs"));
 
 // ============================================================================
 // ERROR CODES - Ahora usa contracterror! macro
@@ -22,6 +207,28 @@ pub enum Error {
     SameOwner = 7,
     Unauthorized = 8,
     ReplayAttack = 9,
+    NotFound = 10,
+    AlreadyExists = 11,
+    InvalidAmount = 12,
+    InvalidEpoch = 13,
+    LimitExceeded = 14,
+    Expired = 15,
+    Timelocked = 16,
+    UnsupportedVersion = 17,
+    Frozen = 18,
+    BreakingUpgrade = 19,
+    Quarantined = 20,
+}
+
+impl From<accesly_policy::Denial> for Error {
+    fn from(denial: accesly_policy::Denial) -> Self {
+        match denial {
+            accesly_policy::Denial::Unauthorized => Error::Unauthorized,
+            accesly_policy::Denial::Timelocked => Error::Timelocked,
+            accesly_policy::Denial::LimitExceeded => Error::LimitExceeded,
+            accesly_policy::Denial::Expired => Error::Expired,
+        }
+    }
 }
 
 // ============================================================================
@@ -34,6 +241,8 @@ pub enum DataKey {
     Owner,
     EmailHash,
     Nonce,
+    /// Storage layout version, absent for v1
+    StorageVersion,
 }
 
 // ============================================================================
@@ -52,6 +261,7 @@ pub struct WalletCreatedEvent {
 pub struct AuthSuccessEvent {
     pub owner: BytesN<32>,
     pub nonce: u64,
+    pub channel: u32,
 }
 
 #[contracttype]
@@ -73,6 +283,10 @@ pub struct WalletContract;
 impl WalletContract {
     /// Initialize the wallet contract
     pub fn init(env: Env, owner: BytesN<32>, email_hash: BytesN<32>) -> Result<(), Error> {
+        // Test hooks never go live on mainnet
+        #[cfg(feature = "dev-mode")]
+        dev::ensure_not_mainnet(&env)?;
+
         // Check if already initialized
         if env.storage().instance().has(&DataKey::Owner) {
             return Err(Error::AlreadyInitialized);
@@ -97,9 +311,11 @@ impl WalletContract {
         // Initialize nonce to 0
         env.storage().instance().set(&DataKey::Nonce, &0u64);
 
+        migration::init_layout(&env);
+
         // Emit event
         env.events().publish(
-            (Symbol::new(&env, "wallet_created"),),
+            (Symbol::new(&env, events::WALLET_CREATED),),
             WalletCreatedEvent {
                 owner: owner.clone(),
                 email_hash: email_hash.clone(),
@@ -149,21 +365,6 @@ impl WalletContract {
         Ok(current_nonce)
     }
 
-    /// Verify Ed25519 signature (helper function)
-    /// 
-    /// IMPORTANT: En SDK 22.x, ed25519_verify NO retorna bool
-    /// Si la verificación falla, causa un PANIC automáticamente
-    fn verify_ed25519_signature(
-        env: &Env,
-        public_key: BytesN<32>,  // Sin referencia &
-        message: Bytes,          // Sin referencia &
-        signature: BytesN<64>,   // Sin referencia &
-    ) {
-        // En SDK 22.x, esto causa panic si falla
-        // No retorna nada si tiene éxito
-        env.crypto().ed25519_verify(&public_key, &message, &signature);
-    }
-
     /// Update the owner public key (key rotation)
     pub fn update_owner(
         env: Env, 
@@ -186,28 +387,24 @@ impl WalletContract {
             return Err(Error::SameOwner);
         }
 
-        // Get current nonce
-        let nonce = Self::get_nonce(env.clone())?;
-
-        // Build message to verify: "update_owner" || new_owner || nonce
-        let mut message = Bytes::new(&env);
-        message.extend_from_array(b"update_owner");
-        message.extend_from_slice(&new_owner.to_array());
-        message.extend_from_array(&nonce.to_be_bytes());
-
-        // Verify signature from current owner
-        // En SDK 22.x, esto causa panic si falla
-        Self::verify_ed25519_signature(&env, current_owner.clone(), message, signature);
+        // With a rotation delay set, keys only change through
+        // propose_owner_rotation / confirm_rotation
+        if rotation::rotation_delay(&env) > 0 {
+            return Err(Error::Timelocked);
+        }
 
-        // Increment nonce
-        Self::get_and_increment_nonce(env.clone())?;
+        // Verify "update_owner" || new_owner || nonce and consume the nonce
+        let nonce = Self::get_nonce(env.clone())?;
+        let payload = Bytes::from_array(&env, &new_owner.to_array());
+        Self::require_high_risk_signature(&env, "update_owner", payload, signature)?;
 
         // Update owner
         env.storage().instance().set(&DataKey::Owner, &new_owner);
+        approvals::record_owner_change(&env);
 
         // Emit event
         env.events().publish(
-            (Symbol::new(&env, "key_rotated"),),
+            (Symbol::new(&env, events::KEY_ROTATED),),
             KeyRotatedEvent {
                 old_owner: current_owner,
                 new_owner: new_owner.clone(),
@@ -218,54 +415,200 @@ impl WalletContract {
         Ok(())
     }
 
-    /// Main authorization function (__check_auth)
+    /// Main authorization function (__check_auth). Accepts an ed25519
+    /// signature by the owner key or a linked device, or a WebAuthn
+    /// assertion by the passkey, or weighted signatures once a signer
+    /// threshold is set.
     pub fn __check_auth(
         env: Env,
         signature_payload: BytesN<32>,
-        signature: BytesN<64>,
-        _auth_context: soroban_sdk::Vec<soroban_sdk::Val>,
+        signature: AuthSignature,
+        auth_contexts: soroban_sdk::Vec<soroban_sdk::auth::Context>,
     ) -> Result<(), Error> {
         // Get current owner
         let owner: BytesN<32> = env.storage()
             .instance()
             .get(&DataKey::Owner)
             .ok_or(Error::NotInitialized)?;
+        freeze::ensure_not_frozen(&env)?;
+        relayers::check_required(&env, &signature)?;
+
+        // Signatures wrapped in a channel use that channel's nonce, and
+        // expiring ones are refused past their last ledger. Relayed ones
+        // carry either, once the relayer's co-signature checks out.
+        let (channel, valid_until_ledger, signature) = match signature {
+            AuthSignature::Channel(channel, signature) => (channel, None, signature.into()),
+            AuthSignature::Expiring(expiring) => (
+                expiring.channel,
+                Some(expiring.valid_until_ledger),
+                expiring.signature.into(),
+            ),
+            AuthSignature::Relayed(relayed) => {
+                relayers::verify_relayer(&env, &signature_payload, &relayed)?;
+                (
+                    relayed.channel,
+                    relayed.valid_until_ledger,
+                    relayed.signature.into(),
+                )
+            }
+            signature => (0, None, signature),
+        };
+        nonces::check_not_expired(&env, valid_until_ledger)?;
+        let expected_nonce = nonces::channel_nonce(&env, channel)?;
+
+        // Build message: signature_payload || [channel ||] nonce [|| valid_until_ledger]
+        let message = nonces::auth_message(
+            &env,
+            &signature_payload,
+            channel,
+            expected_nonce,
+            valid_until_ledger,
+        );
 
-        // Get current nonce
-        let expected_nonce = Self::get_nonce(env.clone())?;
-
-        // Build message: signature_payload || nonce
-        let mut message = Bytes::new(&env);
-        message.extend_from_slice(&signature_payload.to_array());
-        message.extend_from_array(&expected_nonce.to_be_bytes());
+        // Session keys are checked on their own; none can be created with a
+        // threshold set. With one, every other signature form goes through
+        // the weighted signer set
+        let single_signer = signers::threshold(&env) == 0;
+        match signature {
+            AuthSignature::Session(public_key, signature) => {
+                sessions::verify_session(
+                    &env,
+                    public_key,
+                    &message,
+                    &signature,
+                    &auth_contexts,
+                    expected_nonce,
+                )?
+            }
+            AuthSignature::Ed25519(signature) if single_signer => {
+                signature::verify_ed25519(&env, &owner, &message, &signature)?
+            }
+            AuthSignature::Device(public_key, signature) if single_signer => {
+                devices::verify_device(&env, &public_key, &message, &signature)?
+            }
+            AuthSignature::Secp256r1(assertion) if single_signer => {
+                let passkey = webauthn::passkey(&env)?;
+                webauthn::verify_webauthn(&env, &passkey, &message, &assertion)?
+            }
+            AuthSignature::PreAuthorized if single_signer => {
+                preauth::consume_preauth(&env, &signature_payload)?
+            }
+            signature => signers::verify_weighted(&env, &owner, &message, signature)?,
+        }
 
-        // Verify signature (causes panic if fails in SDK 22.x)
-        Self::verify_ed25519_signature(&env, owner.clone(), message, signature);
+        // Calls authorized here are held to the contract policy
+        policy::check_auth_contexts(&env, &auth_contexts)?;
+        // Native transfers authorized here must leave the XLM reserve intact
+        reserve::check_auth_contexts(&env, &auth_contexts)?;
+        // In fee mode, transfers to the relayer must stay under the fee cap
+        fees::check_auth_contexts(&env, &auth_contexts)?;
+        // Transfers over a large-transfer threshold go through the queue
+        large_transfers::check_auth_contexts(&env, &auth_contexts)?;
+        // In contacts-only mode, transfers can only go to saved contacts
+        contacts::check_auth_contexts(&env, &auth_contexts)?;
+        // Transfers count against the fiat spending limit, unless they go
+        // to the owner's own accounts, which are tagged instead
+        fiat_limits::check_auth_contexts(&env, &auth_contexts)?;
+        own_accounts::tag_auth_contexts(&env, &auth_contexts);
+        // Count the authorization against the velocity limit
+        velocity::record_authorization(&env)?;
 
         // Increment nonce
-        Self::get_and_increment_nonce(env.clone())?;
+        nonces::consume_channel_nonce(&env, channel)?;
+
+        // Keep a wallet in use far from archival
+        ttl::extend_instance(&env);
 
         // Emit event
         env.events().publish(
-            (Symbol::new(&env, "auth_success"),),
+            (Symbol::new(&env, events::AUTH_SUCCESS),),
             AuthSuccessEvent {
                 owner: owner.clone(),
                 nonce: expected_nonce,
+                channel,
             },
         );
 
         Ok(())
     }
 
+    /// Helper: Verify an owner signature over `action || payload || nonce`
+    /// and consume the nonce. Every owner-gated entrypoint goes through here
+    /// or through `require_high_risk_signature`.
+    pub(crate) fn require_owner_signature(
+        env: &Env,
+        action: &str,
+        payload: Bytes,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        Self::verify_owner_action(env, action, payload, signature, false)
+    }
+
+    /// Helper: `require_owner_signature` for a high-risk action, which a
+    /// recently installed owner key needs approval for (see `approvals`)
+    pub(crate) fn require_high_risk_signature(
+        env: &Env,
+        action: &str,
+        payload: Bytes,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        Self::verify_owner_action(env, action, payload, signature, true)
+    }
+
+    fn verify_owner_action(
+        env: &Env,
+        action: &str,
+        payload: Bytes,
+        signature: BytesN<64>,
+        high_risk: bool,
+    ) -> Result<(), Error> {
+        let owner: BytesN<32> = env.storage()
+            .instance()
+            .get(&DataKey::Owner)
+            .ok_or(Error::NotInitialized)?;
+        freeze::ensure_action_allowed(env, action)?;
+        let message = Self::action_message(env, action, payload.clone())?;
+
+        signature::verify_ed25519(env, &owner, &message, &signature)?;
+        if high_risk {
+            approvals::check_new_key(env, action, &payload)?;
+        }
+        // Actions raised in the auth policy matrix need their co-signers
+        auth_policy::check_cosigned(env, action, &payload, high_risk)?;
+
+        Self::get_and_increment_nonce(env.clone())?;
+        Ok(())
+    }
+
+    /// Helper: `action || payload || nonce`, the message every signed
+    /// entrypoint verifies
+    pub(crate) fn action_message(env: &Env, action: &str, payload: Bytes) -> Result<Bytes, Error> {
+        let nonce = Self::get_nonce(env.clone())?;
+
+        let mut message = Bytes::new(env);
+        accesly_messages::action_message(&mut message, action, &payload, nonce);
+        Ok(message)
+    }
+
     /// Helper: Check if BytesN<32> is all zeros
-    fn is_zero_bytes(bytes: &BytesN<32>) -> bool {
+    pub(crate) fn is_zero_bytes(bytes: &BytesN<32>) -> bool {
         bytes.to_array().iter().all(|&b| b == 0)
     }
 }
 
+/// Current time for expiry windows. Dev builds can run it ahead of the
+/// ledger with `dev_fast_forward`.
+#[cfg(not(feature = "dev-mode"))]
+pub(crate) fn now(env: &Env) -> u64 {
+    env.ledger().timestamp()
+}
+#[cfg(feature = "dev-mode")]
+pub(crate) use dev::now;
+
 // ============================================================================
 // TESTS
 // ============================================================================
 
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod test;
\ No newline at end of file
@@ -0,0 +1,133 @@
+// ============================================================================
+// META-TRANSACTIONS
+//
+// A signed envelope the relayer submits for the owner, who never needs XLM:
+// one call, like a single-op batch, plus the most the relayer may charge
+// for it and when the offer lapses. The relayer names its fee as it submits,
+// once it knows what the transaction cost, and it's paid in the fee asset
+// (see `set_fee_config`) to the sponsor.
+//
+// Envelopes carry their own nonce instead of taking the wallet nonce, so an
+// app can hand out several at once without them racing each other or
+// stalling owner actions. The owner signs
+// "execute_meta_tx" || network_id || wallet || envelope, so an envelope
+// can't be replayed on another wallet the same key owns, or on another
+// network. Any unused nonce works, and a used one is remembered until the
+// envelope expires, after which the expiry alone refuses it. That bounds
+// storage to envelopes that could still be replayed.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Val, Vec,
+};
+
+use crate::batch::{calls, check_ops, invoke};
+use crate::fragments::ledgers_for;
+use crate::reserve::ReserveGuard;
+use crate::*;
+
+/// Furthest ahead an envelope can expire (1 day)
+pub const MAX_META_TX_WINDOW: u64 = 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone)]
+pub struct MetaTx {
+    pub call: Invocation,
+    /// Any value not used by an earlier envelope
+    pub nonce: u64,
+    /// Most the relayer can charge for submitting, in the fee asset
+    pub max_fee: i128,
+    /// Timestamp after which the envelope is refused
+    pub expiry: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum MetaTxKey {
+    /// Expiry of the envelope that used a nonce
+    Used(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetaTxExecutedEvent {
+    pub nonce: u64,
+    pub contract: Address,
+    pub fn_name: Symbol,
+    pub fee: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Run the call in `envelope` on the owner's signature, charging `fee`
+    /// (at most `envelope.max_fee`) for the relayer. Returns the call's result.
+    pub fn execute_meta_tx(
+        env: Env,
+        envelope: MetaTx,
+        signature: BytesN<64>,
+        fee: i128,
+    ) -> Result<Val, Error> {
+        let now = now(&env);
+        if envelope.expiry < now {
+            return Err(Error::Expired);
+        }
+        if envelope.expiry - now > MAX_META_TX_WINDOW {
+            return Err(Error::LimitExceeded);
+        }
+        if fee < 0 || fee > envelope.max_fee {
+            return Err(Error::InvalidAmount);
+        }
+        if Self::is_meta_tx_used(env.clone(), envelope.nonce) {
+            return Err(Error::ReplayAttack);
+        }
+        let ops = Vec::from_array(&env, [envelope.call.clone()]);
+        check_ops(&env, &ops)?;
+
+        let owner = Self::get_owner(env.clone())?;
+        signers::ensure_single_signer(&env)?;
+        freeze::ensure_action_allowed(&env, accesly_messages::META_TX)?;
+        let mut message = Bytes::new(&env);
+        accesly_messages::meta_tx_message(
+            &mut message,
+            &env.ledger().network_id().to_array(),
+            &env.current_contract_address().to_xdr(&env),
+            &envelope.clone().to_xdr(&env),
+        );
+        signature::verify_ed25519(&env, &owner, &message, &signature)?;
+
+        // Kept a ledger past the expiry, so it can't lapse while the
+        // envelope is still accepted
+        let storage = env.storage().temporary();
+        let key = MetaTxKey::Used(envelope.nonce);
+        storage.set(&key, &envelope.expiry);
+        let ledgers = ledgers_for(envelope.expiry - now) + 1;
+        storage.extend_ttl(&key, ledgers, ledgers);
+
+        let reserve = ReserveGuard::new(&env);
+        let result = invoke(&env, &envelope.call);
+        if fee > 0 {
+            fees::pay_relayer(&env, &calls(&env, &ops), fee)?;
+        }
+        reserve.check(&env)?;
+
+        env.events().publish(
+            (Symbol::new(&env, events::META_TX_EXECUTED),),
+            MetaTxExecutedEvent {
+                nonce: envelope.nonce,
+                contract: envelope.call.contract,
+                fn_name: envelope.call.fn_name,
+                fee,
+            },
+        );
+
+        Ok(result)
+    }
+
+    /// Whether an envelope that hasn't expired yet used `nonce`
+    pub fn is_meta_tx_used(env: Env, nonce: u64) -> bool {
+        env.storage()
+            .temporary()
+            .get::<_, u64>(&MetaTxKey::Used(nonce))
+            .is_some_and(|expiry| now(&env) <= expiry)
+    }
+}
@@ -0,0 +1,106 @@
+// ============================================================================
+// STORAGE MIGRATIONS
+//
+// `DataKey::StorageVersion` records which storage layout a wallet is in. The
+// first wallets held nothing but the owner key, email hash and nonce, and
+// have no version entry: that is v1. v2 writes out the configuration later
+// code reads, the signer map and threshold, the session epoch and the batch
+// limits, with the values a v1 wallet behaves as having.
+//
+// New code can't run anything while it's being swapped in, so after
+// `apply_upgrade` the relayer calls `migrate`, which steps the layout up one
+// version at a time to the one this code uses. Anyone may call it: each step
+// is fixed by the code, and a wallet already on the current layout is left
+// alone. `init` runs the same steps, so fresh and migrated wallets end up
+// with the same layout.
+//
+// A layout change adds a `migrate_vN` step below and bumps `STORAGE_VERSION`.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, Env, Map, Symbol};
+
+use crate::batch::batch_limits;
+use crate::sessions::session_epoch;
+use crate::signers::threshold;
+use crate::*;
+
+/// Layout this code reads and writes
+pub const STORAGE_VERSION: u32 = 2;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageMigratedEvent {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Bring storage up to `STORAGE_VERSION`, returning the version
+    pub fn migrate(env: Env) -> Result<u32, Error> {
+        if !env.storage().instance().has(&DataKey::Owner) {
+            return Err(Error::NotInitialized);
+        }
+        let from = storage_version(&env);
+        // Code older than the layout can't know what it's reading
+        if from > STORAGE_VERSION {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        for version in from..STORAGE_VERSION {
+            migrate_step(&env, version);
+            env.events().publish(
+                (Symbol::new(&env, events::STORAGE_MIGRATED),),
+                StorageMigratedEvent {
+                    from: version,
+                    to: version + 1,
+                },
+            );
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &STORAGE_VERSION);
+
+        Ok(STORAGE_VERSION)
+    }
+
+    pub fn get_storage_version(env: Env) -> u32 {
+        storage_version(&env)
+    }
+}
+
+pub(crate) fn storage_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::StorageVersion)
+        .unwrap_or(1)
+}
+
+/// Take a freshly initialized v1 layout to `STORAGE_VERSION`
+pub(crate) fn init_layout(env: &Env) {
+    for version in 1..STORAGE_VERSION {
+        migrate_step(env, version);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::StorageVersion, &STORAGE_VERSION);
+}
+
+fn migrate_step(env: &Env, from: u32) {
+    match from {
+        1 => migrate_v1(env),
+        _ => panic!("no migration from this version"),
+    }
+}
+
+/// v1 -> v2: write out the signer set, session epoch and batch limits.
+/// Entries the wallet already has are kept.
+fn migrate_v1(env: &Env) {
+    let storage = env.storage().instance();
+    if !storage.has(&SignersKey::Signers) {
+        storage.set(&SignersKey::Signers, &Map::<Signer, u32>::new(env));
+    }
+    storage.set(&SignersKey::Threshold, &threshold(env));
+    storage.set(&SessionKey::SessionEpoch, &session_epoch(env));
+    storage.set(&BatchKey::Limits, &batch_limits(env));
+}
@@ -0,0 +1,144 @@
+// ============================================================================
+// NONCE CHANNELS
+//
+// `__check_auth` signatures commit to the wallet nonce, so two transactions
+// signed at once (a payment and a swap) race for it and one always fails.
+// Channels are independent nonce sequences: a signature wrapped in
+// `AuthSignature::Channel(channel, ..)` commits to
+// payload || channel || that channel's nonce, and only moves that channel.
+// Independent flows each take their own channel and go through together.
+//
+// Channel 0 is the wallet nonce every other signature uses, and owner-gated
+// entrypoints stay on it. Other channels live in persistent storage, not
+// temporary: an expired counter must fail to load rather than read as 0,
+// which would make old signatures valid again. The wallet counts the
+// channels it has opened, which `ping` reports, since the entries
+// themselves can't be listed.
+//
+// A nonce only goes stale once something else moves it, so a signature
+// handed to a relayer stays usable for as long as the wallet sits idle.
+// `AuthSignature::Expiring` bounds that: it commits to
+// payload || channel || nonce || valid_until_ledger, and `__check_auth`
+// refuses it once the ledger sequence is past `valid_until_ledger`. The
+// channel is always included there, so no two message forms share a length.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, Bytes, BytesN, Env, Vec};
+
+use crate::*;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum NonceKey {
+    Channel(u32),
+    /// Channels other than 0 used at least once
+    ChannelsInUse,
+}
+
+/// The signature forms `__check_auth` accepts, for use on a channel
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChannelSignature {
+    Ed25519(BytesN<64>),
+    Secp256r1(WebAuthnSignature),
+    Multisig(Vec<SignerProof>),
+    Session(BytesN<32>, BytesN<64>),
+    Device(BytesN<32>, BytesN<64>),
+}
+
+/// A signature that stops being accepted after `valid_until_ledger`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpiringSignature {
+    /// Nonce channel, 0 for the wallet nonce
+    pub channel: u32,
+    /// Last ledger sequence the signature is valid in
+    pub valid_until_ledger: u32,
+    pub signature: ChannelSignature,
+}
+
+impl From<ChannelSignature> for AuthSignature {
+    fn from(signature: ChannelSignature) -> Self {
+        match signature {
+            ChannelSignature::Ed25519(signature) => AuthSignature::Ed25519(signature),
+            ChannelSignature::Secp256r1(assertion) => AuthSignature::Secp256r1(assertion),
+            ChannelSignature::Multisig(proofs) => AuthSignature::Multisig(proofs),
+            ChannelSignature::Session(key, signature) => AuthSignature::Session(key, signature),
+            ChannelSignature::Device(key, signature) => AuthSignature::Device(key, signature),
+        }
+    }
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Next nonce on `channel`; channel 0 is `get_nonce`
+    pub fn get_channel_nonce(env: Env, channel: u32) -> Result<u64, Error> {
+        channel_nonce(&env, channel)
+    }
+}
+
+pub(crate) fn channel_nonce(env: &Env, channel: u32) -> Result<u64, Error> {
+    let nonce = WalletContract::get_nonce(env.clone())?;
+    if channel == 0 {
+        return Ok(nonce);
+    }
+    Ok(env
+        .storage()
+        .persistent()
+        .get(&NonceKey::Channel(channel))
+        .unwrap_or(0))
+}
+
+/// Move `channel` past its current nonce and return that nonce
+pub(crate) fn consume_channel_nonce(env: &Env, channel: u32) -> Result<u64, Error> {
+    if channel == 0 {
+        return WalletContract::get_and_increment_nonce(env.clone());
+    }
+    let nonce = channel_nonce(env, channel)?;
+    let next = nonce.checked_add(1).ok_or(Error::InvalidNonce)?;
+    let storage = env.storage().persistent();
+    if !storage.has(&NonceKey::Channel(channel)) {
+        env.storage()
+            .instance()
+            .set(&NonceKey::ChannelsInUse, &(channels_in_use(env) + 1));
+    }
+    storage.set(&NonceKey::Channel(channel), &next);
+    Ok(nonce)
+}
+
+/// How many channels other than 0 have been used
+pub(crate) fn channels_in_use(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&NonceKey::ChannelsInUse)
+        .unwrap_or(0)
+}
+
+/// What a `__check_auth` signature signs: payload || nonce on channel 0,
+/// payload || channel || nonce on any other, and
+/// payload || channel || nonce || valid_until_ledger when it expires
+pub(crate) fn auth_message(
+    env: &Env,
+    payload: &BytesN<32>,
+    channel: u32,
+    nonce: u64,
+    valid_until_ledger: Option<u32>,
+) -> Bytes {
+    let mut message = Bytes::new(env);
+    accesly_messages::auth_message(
+        &mut message,
+        &payload.to_array(),
+        channel,
+        nonce,
+        valid_until_ledger,
+    );
+    message
+}
+
+/// Fail with `Expired` once the ledger is past `valid_until_ledger`
+pub(crate) fn check_not_expired(env: &Env, valid_until_ledger: Option<u32>) -> Result<(), Error> {
+    match valid_until_ledger {
+        Some(ledger) if env.ledger().sequence() > ledger => Err(Error::Expired),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,102 @@
+// ============================================================================
+// NOTIFICATION FILTERS
+//
+// What the owner wants to be alerted about, per delivery channel ("push",
+// "email", ...). The wallet doesn't act on these; the indexer reads them
+// (or follows the `notify_filter` event) to decide which operations to
+// webhook. Keeping them on-chain means the preference follows the wallet
+// across devices instead of living in one app's settings.
+//
+// A `notify_filter` event with an empty body means the channel was removed.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, BytesN, Env, Map, Symbol, Vec};
+
+use crate::*;
+
+pub const MAX_NOTIFICATION_CHANNELS: u32 = 5;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotificationFilter {
+    /// Smallest absolute amount worth a notification
+    pub min_amount: i128,
+    /// Operation kinds to notify about; empty for all
+    pub kinds: Vec<OpKind>,
+}
+
+impl NotificationFilter {
+    pub fn matches(&self, kind: OpKind, amount: i128) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(kind))
+            && amount.unsigned_abs() >= self.min_amount.unsigned_abs()
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum NotificationKey {
+    /// Map<Symbol, NotificationFilter> keyed by channel
+    NotificationFilters,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotificationFilterEvent {
+    pub channel: Symbol,
+    pub filter: NotificationFilter,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set or (with `None`) remove the filter for `channel`
+    pub fn set_notification_filter(
+        env: Env,
+        channel: Symbol,
+        filter: Option<NotificationFilter>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut filters = Self::get_notification_filters(env.clone());
+        match &filter {
+            Some(filter) => {
+                if filter.min_amount < 0 {
+                    return Err(Error::InvalidAmount);
+                }
+                if !filters.contains_key(channel.clone())
+                    && filters.len() >= MAX_NOTIFICATION_CHANNELS
+                {
+                    return Err(Error::LimitExceeded);
+                }
+            }
+            None if !filters.contains_key(channel.clone()) => return Err(Error::NotFound),
+            None => {}
+        }
+
+        let payload = (channel.clone(), filter.clone()).to_xdr(&env);
+        Self::require_owner_signature(&env, "set_notification_filter", payload, signature)?;
+
+        let topics = (Symbol::new(&env, events::NOTIFY_FILTER), channel.clone());
+        match filter {
+            Some(filter) => {
+                filters.set(channel.clone(), filter.clone());
+                env.events()
+                    .publish(topics, NotificationFilterEvent { channel, filter });
+            }
+            None => {
+                filters.remove(channel.clone());
+                env.events().publish(topics, ());
+            }
+        }
+        env.storage()
+            .instance()
+            .set(&NotificationKey::NotificationFilters, &filters);
+
+        Ok(())
+    }
+
+    pub fn get_notification_filters(env: Env) -> Map<Symbol, NotificationFilter> {
+        env.storage()
+            .instance()
+            .get(&NotificationKey::NotificationFilters)
+            .unwrap_or(Map::new(&env))
+    }
+}
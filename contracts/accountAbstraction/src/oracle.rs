@@ -0,0 +1,88 @@
+// ============================================================================
+// PRICE ORACLE
+//
+// Client for the oracle adapter (`contracts/oracleAdapter`), which prices
+// assets and fiat currencies alike in one base currency, usually USD, over
+// Reflector feeds. Fiat currencies are priced like any other asset, as
+// `Other("MXN")`, so a value in MXN is the value in the base divided by the
+// MXN price, and both prices share the adapter's decimals.
+//
+// The adapter refuses prices older than each feed's threshold; the wallet
+// also refuses any older than `MAX_PRICE_AGE`, so a loosely configured
+// adapter can't hand it a stale price. No price is the caller's fallback.
+// ============================================================================
+
+use soroban_sdk::{contractclient, contracttype, token, Address, Env, Symbol};
+
+use crate::now;
+
+/// Oldest price accepted, in seconds (15 minutes)
+pub const MAX_PRICE_AGE: u64 = 15 * 60;
+/// Decimals of fiat amounts, which are in cents
+pub const FIAT_DECIMALS: u32 = 2;
+
+/// Asset as the oracle names it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// Subset of the oracle adapter interface
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Price in the base currency and when it was published
+    fn price(env: Env, asset: OracleAsset) -> (i128, u64);
+}
+
+/// Value of `amount` of `asset` in cents of `currency`, `None` when either
+/// has no fresh price
+pub(crate) fn fiat_value(
+    env: &Env,
+    oracle: &Address,
+    asset: &Address,
+    amount: i128,
+    currency: &Symbol,
+) -> Option<i128> {
+    let asset_price = fresh_price(env, oracle, OracleAsset::Stellar(asset.clone()))?;
+    let currency_price = fresh_price(env, oracle, OracleAsset::Other(currency.clone()))?;
+    let asset_unit = 10i128.checked_pow(token::Client::new(env, asset).decimals())?;
+
+    // Too large to represent is over any limit
+    let value = amount
+        .checked_mul(asset_price)
+        .and_then(|v| v.checked_mul(10i128.pow(FIAT_DECIMALS)))
+        .map(|v| v / asset_unit / currency_price)
+        .unwrap_or(i128::MAX);
+    Some(value)
+}
+
+/// What `amount` of `from` is worth in `to` at oracle prices, `None` when
+/// either has no fresh price
+pub(crate) fn token_value(
+    env: &Env,
+    oracle: &Address,
+    from: &Address,
+    amount: i128,
+    to: &Address,
+) -> Option<i128> {
+    let from_price = fresh_price(env, oracle, OracleAsset::Stellar(from.clone()))?;
+    let to_price = fresh_price(env, oracle, OracleAsset::Stellar(to.clone()))?;
+    let from_unit = 10i128.checked_pow(token::Client::new(env, from).decimals())?;
+    let to_unit = 10i128.checked_pow(token::Client::new(env, to).decimals())?;
+
+    amount
+        .checked_mul(from_price)?
+        .checked_mul(to_unit)
+        .map(|v| v / from_unit / to_price)
+}
+
+fn fresh_price(env: &Env, oracle: &Address, asset: OracleAsset) -> Option<i128> {
+    let (price, timestamp) = PriceOracleClient::new(env, oracle)
+        .try_price(&asset)
+        .ok()?
+        .ok()?;
+    let fresh = now(env).saturating_sub(timestamp) <= MAX_PRICE_AGE;
+    (fresh && price > 0).then_some(price)
+}
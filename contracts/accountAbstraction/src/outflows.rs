@@ -0,0 +1,41 @@
+// ============================================================================
+// OUTFLOWS
+//
+// Which token calls the wallet authorizes move value out of it, for the
+// checks that limit outgoing funds. Transfers and burns spend at once. An
+// approval spends too: once granted, the spender can `transfer_from` or
+// `burn_from` the amount without asking the wallet again, so it is counted
+// when the wallet signs it rather than when the spender uses it. Which
+// calls those are is shared with off-chain tooling in `accesly_policy`.
+// ============================================================================
+
+use accesly_policy::OUTFLOW_CALLS;
+use soroban_sdk::{Address, Env, Symbol, TryFromVal, Val, Vec};
+
+/// Value a call moves out of the wallet
+pub(crate) struct Outflow {
+    pub amount: i128,
+    /// Who can end up with it: the recipient, or the spender of an
+    /// approval. `None` for burns.
+    pub to: Option<Address>,
+}
+
+/// The outflow of calling `fn_name` on a token with `args`, if it spends
+/// from the wallet
+pub(crate) fn outflow(env: &Env, fn_name: &Symbol, args: &Vec<Val>) -> Option<Outflow> {
+    let (_, positions) = OUTFLOW_CALLS
+        .iter()
+        .find(|(name, _)| *fn_name == Symbol::new(env, name))?;
+
+    let arg = |index: u32| args.get(index);
+    let from = Address::try_from_val(env, &arg(positions.from)?).ok()?;
+    let amount = i128::try_from_val(env, &arg(positions.amount)?).ok()?;
+    if from != env.current_contract_address() || amount <= 0 {
+        return None;
+    }
+    let to = match positions.to {
+        Some(to) => Some(Address::try_from_val(env, &arg(to)?).ok()?),
+        None => None,
+    };
+    Some(Outflow { amount, to })
+}
@@ -0,0 +1,196 @@
+// ============================================================================
+// OWN ACCOUNTS
+//
+// The owner's other accounts, such as a savings vault or a sub-account,
+// linked to the wallet so moving money to them is told apart from paying
+// someone. A transfer from the wallet to a linked account is published as
+// `internal_transfer` and doesn't count against the fiat spending limit.
+// Approvals to one are still approvals and count as usual, as do burns.
+//
+// Anything linked escapes the limit, so a thief holding the owner key would
+// link their own address first. A new account only counts as internal after
+// `OWN_ACCOUNT_COOLING_OFF`, which gives the owner's other devices a day to
+// notice the `own_account_added` event, and linking is one of the changes a
+// new owner key has to wait out (see `approvals`). Unlinking applies at once.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Map, Symbol, Val,
+    Vec,
+};
+
+use crate::outflows::{outflow, Outflow};
+use crate::privacy::publish_transfer;
+use crate::*;
+
+/// Time before transfers to a new own account stop counting (24 hours)
+pub const OWN_ACCOUNT_COOLING_OFF: u64 = 24 * 60 * 60;
+pub const MAX_OWN_ACCOUNTS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnAccount {
+    pub address: Address,
+    /// Ledger timestamp from which transfers to it are internal
+    pub internal_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum OwnAccountsKey {
+    /// Map<Address, OwnAccount> by address
+    OwnAccounts,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InternalTransferEvent {
+    pub asset: Address,
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Link `address` as one of the owner's own accounts. Transfers to it
+    /// are internal after `OWN_ACCOUNT_COOLING_OFF`. Owner-signed over the
+    /// address.
+    pub fn add_own_account(env: Env, address: Address, signature: BytesN<64>) -> Result<(), Error> {
+        if address == env.current_contract_address() {
+            return Err(Error::InvalidOwner);
+        }
+        let mut accounts = own_accounts(&env);
+        if accounts.contains_key(address.clone()) {
+            return Err(Error::AlreadyExists);
+        }
+        if accounts.len() >= MAX_OWN_ACCOUNTS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = address.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "add_own_account", payload, signature)?;
+
+        let account = OwnAccount {
+            address: address.clone(),
+            internal_at: now(&env).saturating_add(OWN_ACCOUNT_COOLING_OFF),
+        };
+        accounts.set(address.clone(), account.clone());
+        save_own_accounts(&env, &accounts);
+        env.events().publish(
+            (Symbol::new(&env, events::OWN_ACCOUNT_ADDED), address),
+            account,
+        );
+
+        Ok(())
+    }
+
+    /// Unlink an own account; transfers to it count again right away.
+    /// Owner-signed over the address.
+    pub fn remove_own_account(
+        env: Env,
+        address: Address,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut accounts = own_accounts(&env);
+        if !accounts.contains_key(address.clone()) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = address.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "remove_own_account", payload, signature)?;
+
+        accounts.remove(address.clone());
+        save_own_accounts(&env, &accounts);
+        env.events().publish(
+            (Symbol::new(&env, events::OWN_ACCOUNT_REMOVED), address),
+            (),
+        );
+
+        Ok(())
+    }
+
+    pub fn list_own_accounts(env: Env) -> Vec<OwnAccount> {
+        own_accounts(&env).values()
+    }
+
+    /// Whether transfers to `address` are internal now
+    pub fn is_own_account(env: Env, address: Address) -> bool {
+        is_internal(&env, &address)
+    }
+}
+
+fn own_accounts(env: &Env) -> Map<Address, OwnAccount> {
+    env.storage()
+        .instance()
+        .get(&OwnAccountsKey::OwnAccounts)
+        .unwrap_or(Map::new(env))
+}
+
+fn save_own_accounts(env: &Env, accounts: &Map<Address, OwnAccount>) {
+    env.storage()
+        .instance()
+        .set(&OwnAccountsKey::OwnAccounts, accounts);
+}
+
+/// Whether `to` is an own account past its cooling-off
+fn is_internal(env: &Env, to: &Address) -> bool {
+    own_accounts(env)
+        .get(to.clone())
+        .is_some_and(|account| account.internal_at <= now(env))
+}
+
+/// The transfer from the wallet to one of its own accounts that calling
+/// `fn_name` on a token with `args` makes, if it makes one
+fn internal_outflow(env: &Env, fn_name: &Symbol, args: &Vec<Val>) -> Option<Outflow> {
+    if *fn_name != Symbol::new(env, "transfer") && *fn_name != Symbol::new(env, "transfer_from") {
+        return None;
+    }
+    let flow = outflow(env, fn_name, args)?;
+    match &flow.to {
+        Some(to) if is_internal(env, to) => Some(flow),
+        _ => None,
+    }
+}
+
+/// Whether the call transfers from the wallet to one of its own accounts
+pub(crate) fn is_internal_call(env: &Env, fn_name: &Symbol, args: &Vec<Val>) -> bool {
+    internal_outflow(env, fn_name, args).is_some()
+}
+
+/// Publish `internal_transfer` for a call that moves funds to an own account
+fn tag_call(env: &Env, asset: &Address, fn_name: &Symbol, args: &Vec<Val>) {
+    if let Some(Outflow {
+        amount,
+        to: Some(to),
+    }) = internal_outflow(env, fn_name, args)
+    {
+        publish_transfer(
+            env,
+            (Symbol::new(env, events::INTERNAL_TRANSFER),),
+            InternalTransferEvent {
+                asset: asset.clone(),
+                to: to.clone(),
+                amount,
+            },
+            Some(&to),
+            asset,
+            amount,
+        );
+    }
+}
+
+/// Tag the transfers to own accounts `contexts` authorizes
+pub(crate) fn tag_auth_contexts(env: &Env, contexts: &Vec<Context>) {
+    for context in contexts.iter() {
+        if let Context::Contract(call) = context {
+            tag_call(env, &call.contract, &call.fn_name, &call.args);
+        }
+    }
+}
+
+/// Tag the transfers to own accounts in a batch, nested ones included
+pub(crate) fn tag_batch(env: &Env, ops: &Vec<Invocation>) {
+    for op in ops.iter() {
+        tag_call(env, &op.contract, &op.fn_name, &op.args);
+    }
+}
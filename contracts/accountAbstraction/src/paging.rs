@@ -0,0 +1,88 @@
+// ============================================================================
+// PAGINATED INDEXES
+//
+// Persistent maps can't be iterated, so every listable collection keeps a
+// dense index (`IndexItem(name, 0..len)`) next to its entries. Listing views
+// read one page of that index starting at a cursor; page sizes follow
+// `acceslyinterface::clamp_limit`, so no input can push a view simulation
+// past the RPC resource limits.
+// ============================================================================
+
+use acceslyinterface::clamp_limit;
+use soroban_sdk::{contracttype, Env, IntoVal, Symbol, TryFromVal, Val, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum IndexKey {
+    IndexLen(Symbol),
+    IndexItem(Symbol, u32),
+}
+
+pub(crate) fn index_len(env: &Env, name: &Symbol) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&IndexKey::IndexLen(name.clone()))
+        .unwrap_or(0)
+}
+
+/// Append `item` and return its position
+pub(crate) fn index_push<T: IntoVal<Env, Val>>(env: &Env, name: &Symbol, item: &T) -> u32 {
+    let position = index_len(env, name);
+    env.storage()
+        .persistent()
+        .set(&IndexKey::IndexItem(name.clone(), position), item);
+    env.storage()
+        .persistent()
+        .set(&IndexKey::IndexLen(name.clone()), &(position + 1));
+    position
+}
+
+/// Swap-remove the item at `position`. Returns the item that was moved into
+/// the hole (if any) so the caller can update its stored position.
+pub(crate) fn index_swap_remove<T>(env: &Env, name: &Symbol, position: u32) -> Option<T>
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    let last = index_len(env, name).checked_sub(1)?;
+    let last_key = IndexKey::IndexItem(name.clone(), last);
+
+    let moved = if position != last {
+        let item: T = env.storage().persistent().get(&last_key)?;
+        env.storage()
+            .persistent()
+            .set(&IndexKey::IndexItem(name.clone(), position), &item);
+        Some(item)
+    } else {
+        None
+    };
+
+    env.storage().persistent().remove(&last_key);
+    env.storage()
+        .persistent()
+        .set(&IndexKey::IndexLen(name.clone()), &last);
+    moved
+}
+
+/// Read one page of up to `clamp_limit(limit)` items starting at `cursor`.
+/// Returns the items and the cursor of the next page, if there is one.
+pub(crate) fn index_page<T>(env: &Env, name: &Symbol, cursor: u32, limit: u32) -> (Vec<T>, Option<u32>)
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    let len = index_len(env, name);
+    let end = cursor.saturating_add(clamp_limit(limit)).min(len);
+
+    let mut items = Vec::new(env);
+    for position in cursor..end {
+        if let Some(item) = env
+            .storage()
+            .persistent()
+            .get(&IndexKey::IndexItem(name.clone(), position))
+        {
+            items.push_back(item);
+        }
+    }
+
+    let next_cursor = if end < len { Some(end) } else { None };
+    (items, next_cursor)
+}
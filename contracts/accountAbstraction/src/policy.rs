@@ -0,0 +1,207 @@
+// ============================================================================
+// CONTRACT POLICY
+//
+// `invoke` lets the owner call any contract from the wallet, which is what
+// the SDK's generic contract calls go through. What it may reach is the
+// owner's call: by default anything not on the denylist, or with allowlist
+// mode on, only contracts explicitly allowed. A denied contract stays denied
+// in either mode. Batches are held to the same policy, so they aren't a way
+// around it, and so is every contract call `__check_auth` authorizes, so a
+// dApp can't walk the wallet into a known drainer either.
+//
+// A blocked `invoke` doesn't fail: once the owner's signature checks out it
+// publishes `contract_blocked`, uses up the nonce and returns no result, so
+// the block lands on chain for indexers and the app's activity feed.
+// Batches and `__check_auth` can't skip a call and carry on, so there a
+// blocked call fails the whole transaction, and the `contract_blocked` they
+// publish first is rolled back with it: it only shows in the simulation the
+// relayer and the SDK run before submitting.
+//
+// Whether a contract is invocable is decided in `accesly_policy`, which the
+// relayer and the indexer use to re-evaluate calls off-chain.
+// ============================================================================
+
+use accesly_policy::Listing;
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+use crate::batch::{check_ops, invoke, Invocation};
+use crate::*;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContractListing {
+    Allowed,
+    Denied,
+}
+
+impl From<ContractListing> for Listing {
+    fn from(listing: ContractListing) -> Self {
+        match listing {
+            ContractListing::Allowed => Listing::Allowed,
+            ContractListing::Denied => Listing::Denied,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PolicyKey {
+    /// Only allowed contracts can be invoked
+    AllowlistMode,
+    Listing(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractInvokedEvent {
+    pub contract: Address,
+    pub fn_name: Symbol,
+}
+
+/// On chain for a blocked `invoke`; for batches and `__check_auth`, only
+/// in simulation, rolled back with the call it explains
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractBlockedEvent {
+    pub contract: Address,
+    pub fn_name: Symbol,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Switch between allowlist mode and (the default) denylist mode
+    pub fn set_allowlist_mode(env: Env, enabled: bool, signature: BytesN<64>) -> Result<(), Error> {
+        let payload = enabled.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_allowlist_mode", payload, signature)?;
+
+        env.storage()
+            .instance()
+            .set(&PolicyKey::AllowlistMode, &enabled);
+        env.events()
+            .publish((Symbol::new(&env, events::ALLOWLIST_MODE),), enabled);
+
+        Ok(())
+    }
+
+    pub fn is_allowlist_mode(env: Env) -> bool {
+        allowlist_mode(&env)
+    }
+
+    /// Allow, deny or (with `None`) unlist `contract`. An `invoke` the
+    /// policy blocks returns `None` and publishes `contract_blocked`;
+    /// batches and authorizations calling it fail with `Unauthorized`.
+    pub fn set_contract_listing(
+        env: Env,
+        contract: Address,
+        listing: Option<ContractListing>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = (contract.clone(), listing).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_contract_listing", payload, signature)?;
+
+        let key = PolicyKey::Listing(contract.clone());
+        match listing {
+            Some(listing) => env.storage().persistent().set(&key, &listing),
+            None => env.storage().persistent().remove(&key),
+        }
+        env.events().publish(
+            (Symbol::new(&env, events::CONTRACT_LISTING), contract),
+            listing,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_contract_listing(env: Env, contract: Address) -> Option<ContractListing> {
+        env.storage()
+            .persistent()
+            .get(&PolicyKey::Listing(contract))
+    }
+
+    /// Call `fn_name` on `contract` as the wallet and return its result, if
+    /// the policy allows it. A blocked call still succeeds, returning
+    /// `None`, so the `contract_blocked` event it publishes stays on chain.
+    pub fn invoke(
+        env: Env,
+        contract: Address,
+        fn_name: Symbol,
+        args: Vec<Val>,
+        signature: BytesN<64>,
+    ) -> Result<Option<Val>, Error> {
+        let op = Invocation {
+            contract: contract.clone(),
+            fn_name: fn_name.clone(),
+            args,
+            auth: Vec::new(&env),
+        };
+        let blocked = !is_invocable(&env, &contract);
+        if !blocked {
+            check_ops(&env, &Vec::from_array(&env, [op.clone()]))?;
+        }
+
+        let payload = (contract.clone(), fn_name.clone(), op.args.clone()).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "invoke", payload, signature)?;
+
+        if blocked {
+            publish_blocked(&env, &contract, &fn_name);
+            return Ok(None);
+        }
+        let result = invoke(&env, &op);
+        env.events().publish(
+            (Symbol::new(&env, events::CONTRACT_INVOKED),),
+            ContractInvokedEvent { contract, fn_name },
+        );
+
+        Ok(Some(result))
+    }
+}
+
+fn allowlist_mode(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&PolicyKey::AllowlistMode)
+        .unwrap_or(false)
+}
+
+/// Whether the policy lets the wallet call `contract`
+fn is_invocable(env: &Env, contract: &Address) -> bool {
+    let listing = WalletContract::get_contract_listing(env.clone(), contract.clone());
+    accesly_policy::is_invocable(listing.map(Listing::from), allowlist_mode(env))
+}
+
+/// Fail with `Unauthorized` if the policy doesn't let the wallet call
+/// `contract`, publishing `contract_blocked` for simulations to see
+pub(crate) fn check_invocable(
+    env: &Env,
+    contract: &Address,
+    fn_name: &Symbol,
+) -> Result<(), Error> {
+    if is_invocable(env, contract) {
+        return Ok(());
+    }
+    publish_blocked(env, contract, fn_name);
+    Err(Error::Unauthorized)
+}
+
+fn publish_blocked(env: &Env, contract: &Address, fn_name: &Symbol) {
+    env.events().publish(
+        (Symbol::new(env, events::CONTRACT_BLOCKED), contract.clone()),
+        ContractBlockedEvent {
+            contract: contract.clone(),
+            fn_name: fn_name.clone(),
+        },
+    );
+}
+
+/// Fail if a call in `contexts` is to a contract the policy forbids
+pub(crate) fn check_auth_contexts(env: &Env, contexts: &Vec<Context>) -> Result<(), Error> {
+    for context in contexts.iter() {
+        if let Context::Contract(call) = context {
+            check_invocable(env, &call.contract, &call.fn_name)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+// ============================================================================
+// PRE-AUTHORIZED PAYLOADS
+//
+// "Confirm on phone, submit from desktop": the owner approves one exact
+// transaction ahead of time by its `signature_payload`, the hash the host
+// hands `__check_auth`, and the transaction can then be submitted from
+// anywhere with `AuthSignature::PreAuthorized` and no signature. That hash
+// commits to the network, the invocation tree and the auth entry's nonce
+// and expiration ledger, so nothing else can be passed off under it.
+//
+// An approval is used up by the first auth it passes, and lapses at its
+// expiry or on `revoke_preauth`. It stands in for an owner signature, so it
+// isn't accepted once a signer threshold is set. Approvals are short-lived
+// and live in temporary storage.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol};
+
+use crate::fragments::ledgers_for;
+use crate::signers::threshold;
+use crate::*;
+
+/// Furthest ahead a pre-authorization can expire (7 days)
+pub const MAX_PREAUTH_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PreAuthKey {
+    /// Expiry of the approval for a signature payload
+    PreAuth(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreAuthGrantedEvent {
+    pub payload_hash: BytesN<32>,
+    pub expiry: u64,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Approve the transaction whose auth payload is `payload_hash` until
+    /// `expiry`. Owner-signed over (payload_hash, expiry).
+    pub fn pre_authorize(
+        env: Env,
+        payload_hash: BytesN<32>,
+        expiry: u64,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let now = now(&env);
+        if expiry <= now {
+            return Err(Error::Expired);
+        }
+        if expiry - now > MAX_PREAUTH_WINDOW {
+            return Err(Error::LimitExceeded);
+        }
+        // Approvals can't be used under a threshold, so don't take one
+        if threshold(&env) > 0 {
+            return Err(Error::Unauthorized);
+        }
+        if Self::get_preauth(env.clone(), payload_hash.clone()).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = (payload_hash.clone(), expiry).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "pre_authorize", payload, signature)?;
+
+        let storage = env.storage().temporary();
+        let key = PreAuthKey::PreAuth(payload_hash.clone());
+        storage.set(&key, &expiry);
+        let ledgers = ledgers_for(expiry - now);
+        storage.extend_ttl(&key, ledgers, ledgers);
+
+        env.events().publish(
+            (Symbol::new(&env, events::PREAUTH_GRANTED),),
+            PreAuthGrantedEvent {
+                payload_hash,
+                expiry,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Withdraw an unused approval. Owner-signed over the payload hash.
+    pub fn revoke_preauth(
+        env: Env,
+        payload_hash: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::get_preauth(env.clone(), payload_hash.clone()).is_none() {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &payload_hash.to_array());
+        Self::require_owner_signature(&env, "revoke_preauth", payload, signature)?;
+
+        env.storage()
+            .temporary()
+            .remove(&PreAuthKey::PreAuth(payload_hash.clone()));
+        env.events()
+            .publish((Symbol::new(&env, events::PREAUTH_REVOKED),), payload_hash);
+
+        Ok(())
+    }
+
+    /// Expiry of the live approval for `payload_hash`, if any
+    pub fn get_preauth(env: Env, payload_hash: BytesN<32>) -> Option<u64> {
+        env.storage()
+            .temporary()
+            .get::<_, u64>(&PreAuthKey::PreAuth(payload_hash))
+            .filter(|expiry| now(&env) <= *expiry)
+    }
+}
+
+/// Accept `signature_payload` on its approval and use the approval up
+pub(crate) fn consume_preauth(env: &Env, signature_payload: &BytesN<32>) -> Result<(), Error> {
+    let key = PreAuthKey::PreAuth(signature_payload.clone());
+    let expiry: u64 = env
+        .storage()
+        .temporary()
+        .get(&key)
+        .ok_or(Error::Unauthorized)?;
+    if now(env) > expiry {
+        return Err(Error::Expired);
+    }
+    env.storage().temporary().remove(&key);
+    Ok(())
+}
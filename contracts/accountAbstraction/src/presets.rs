@@ -0,0 +1,80 @@
+// ============================================================================
+// POLICY PRESETS
+//
+// `init_with_preset` initializes the wallet like `init` and applies one of
+// the policy bundles in `acceslyinterface::presets` in the same call, so a
+// wallet deployed through the factory's `deploy_with_preset` starts with
+// audited limits instead of whatever the integrator set up. The preset only
+// sets the starting values; each stays under the owner's control through
+// its usual setter.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, BytesN, Env, Symbol};
+
+use crate::*;
+
+#[contractimpl]
+impl WalletContract {
+    /// `init`, then apply `preset`
+    pub fn init_with_preset(
+        env: Env,
+        owner: BytesN<32>,
+        email_hash: BytesN<32>,
+        preset: PolicyPreset,
+    ) -> Result<(), Error> {
+        check_preset(&preset)?;
+        Self::init(env.clone(), owner, email_hash)?;
+
+        let storage = env.storage().instance();
+        if preset.velocity_max_ops > 0 {
+            storage.set(
+                &VelocityKey::Limit,
+                &VelocityLimit {
+                    max_ops: preset.velocity_max_ops,
+                    window: preset.velocity_window,
+                },
+            );
+        }
+        storage.set(&RotationKey::RotationDelay, &preset.rotation_delay);
+        storage.set(&ApprovalKey::NewKeyWindow, &preset.new_key_window);
+        storage.set(
+            &BatchKey::Limits,
+            &BatchLimits {
+                max_ops: preset.max_batch_ops,
+                max_depth: MAX_BATCH_DEPTH,
+                max_complexity: MAX_BATCH_COMPLEXITY,
+            },
+        );
+        if preset.contacts_only {
+            storage.set(
+                &ContactsKey::ContactsOnly,
+                &ContactsOnly {
+                    enabled: true,
+                    effective_at: now(&env),
+                },
+            );
+        }
+        env.events()
+            .publish((Symbol::new(&env, events::PRESET_APPLIED),), preset);
+
+        Ok(())
+    }
+}
+
+/// Hold a preset to the same bounds as the setters it stands in for
+fn check_preset(preset: &PolicyPreset) -> Result<(), Error> {
+    if preset.velocity_max_ops > MAX_VELOCITY_OPS
+        || preset.velocity_window > MAX_VELOCITY_WINDOW
+        || preset.rotation_delay > MAX_ROTATION_DELAY
+        || preset.new_key_window > MAX_NEW_KEY_WINDOW
+        || preset.max_batch_ops > MAX_BATCH_OPS
+    {
+        return Err(Error::LimitExceeded);
+    }
+    if (preset.velocity_max_ops > 0 && preset.velocity_window < MIN_VELOCITY_WINDOW)
+        || preset.max_batch_ops == 0
+    {
+        return Err(Error::InvalidAmount);
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+// ============================================================================
+// EVENT PRIVACY
+//
+// With privacy mode on, value-moving events carry
+// sha256(salt || counterparty_xdr) and an order-of-magnitude amount bucket
+// instead of the raw address and amount, and the recent-ops buffer drops the
+// counterparty. The owner derives the salt off-chain and can recompute the
+// hashes to index their own history.
+//
+// This keeps the wallet out of generic spending-graph indexers; it is not
+// secrecy. The salt is contract state like any other, so someone targeting
+// this wallet can still test candidate addresses. Rotating the salt breaks
+// linkage between periods.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, Topics, Val,
+};
+
+use crate::*;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PrivacyKey {
+    PrivacySalt,
+}
+
+/// Emitted in place of a transfer event while privacy mode is on
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateTransferEvent {
+    /// None when the transfer paid several parties
+    pub counterparty_hash: Option<BytesN<32>>,
+    pub asset: Address,
+    pub amount_bucket: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Turn privacy mode on with `salt`, rotate the salt, or turn it off
+    /// with `None`
+    pub fn set_event_privacy(
+        env: Env,
+        salt: Option<BytesN<32>>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = salt.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_event_privacy", payload, signature)?;
+
+        match salt {
+            Some(salt) => env.storage().instance().set(&PrivacyKey::PrivacySalt, &salt),
+            None => env.storage().instance().remove(&PrivacyKey::PrivacySalt),
+        }
+
+        Ok(())
+    }
+
+    pub fn is_event_privacy_enabled(env: Env) -> bool {
+        env.storage().instance().has(&PrivacyKey::PrivacySalt)
+    }
+}
+
+pub(crate) fn privacy_salt(env: &Env) -> Option<BytesN<32>> {
+    env.storage().instance().get(&PrivacyKey::PrivacySalt)
+}
+
+pub(crate) fn hash_counterparty(env: &Env, salt: &BytesN<32>, counterparty: &Address) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &salt.to_array());
+    preimage.append(&counterparty.clone().to_xdr(env));
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Number of decimal digits in |amount| (0 for zero), so 1-9 share a
+/// bucket, 10-99 the next one, and so on
+pub fn amount_bucket(amount: i128) -> u32 {
+    amount.unsigned_abs().checked_ilog10().map_or(0, |log| log + 1)
+}
+
+/// Publish a transfer under `topics`: `event` as given when privacy mode is
+/// off, otherwise a `PrivateTransferEvent` built from the same transfer
+pub(crate) fn publish_transfer<T, E>(
+    env: &Env,
+    topics: T,
+    event: E,
+    counterparty: Option<&Address>,
+    asset: &Address,
+    amount: i128,
+) where
+    T: Topics,
+    E: IntoVal<Env, Val>,
+{
+    match privacy_salt(env) {
+        None => env.events().publish(topics, event),
+        Some(salt) => env.events().publish(
+            topics,
+            PrivateTransferEvent {
+                counterparty_hash: counterparty.map(|address| hash_counterparty(env, &salt, address)),
+                asset: asset.clone(),
+                amount_bucket: amount_bucket(amount),
+            },
+        ),
+    }
+}
@@ -0,0 +1,211 @@
+// ============================================================================
+// SIGNED QUOTES (FIAT CORRIDORS)
+//
+// Anchors and market makers sign the price and expiry they offer for a
+// corridor. `accept_quote` checks that signature against the signers the
+// owner trusts, executes the payment on those exact terms and keeps a
+// receipt holding the hash of the signed quote, so compliance can later
+// prove which quote the user accepted.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, token, xdr::FromXdr, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    Symbol,
+};
+
+use crate::history::record_op;
+use crate::privacy::publish_transfer;
+use crate::reserve::ReserveGuard;
+use crate::signature::verify_ed25519;
+use crate::swap::buy_exact;
+use crate::*;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quote {
+    pub quote_id: BytesN<32>,
+    /// ed25519 key of the anchor / market maker that issued the quote
+    pub signer: BytesN<32>,
+    pub sell_asset: Address,
+    /// Most the wallet pays in `sell_asset`
+    pub sell_amount: i128,
+    pub buy_asset: Address,
+    /// Exact amount `destination` receives in `buy_asset`
+    pub buy_amount: i128,
+    pub destination: Address,
+    /// Router that executes the path payment when the assets differ
+    pub router: Address,
+    /// Ledger timestamp after which the quote can't be accepted
+    pub expires_at: u64,
+}
+
+/// The blob passed to `accept_quote`, XDR encoded. `signature` is the
+/// signer's ed25519 signature over the XDR of `quote`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedQuote {
+    pub quote: Quote,
+    pub signature: BytesN<64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuoteReceipt {
+    /// sha256 of the signed quote blob
+    pub quote_hash: BytesN<32>,
+    pub signer: BytesN<32>,
+    pub sold: i128,
+    pub bought: i128,
+    pub destination: Address,
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum QuoteKey {
+    QuoteSigner(BytesN<32>),
+    QuoteReceipt(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuoteAcceptedEvent {
+    pub quote_id: BytesN<32>,
+    pub quote_hash: BytesN<32>,
+    pub signer: BytesN<32>,
+    pub sold: i128,
+    pub bought: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Trust (or stop trusting) a quote signing key
+    pub fn set_quote_signer(
+        env: Env,
+        signer: BytesN<32>,
+        trusted: bool,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = (signer.clone(), trusted).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_quote_signer", payload, signature)?;
+
+        let key = QuoteKey::QuoteSigner(signer);
+        if trusted {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        Ok(())
+    }
+
+    pub fn is_quote_signer(env: Env, signer: BytesN<32>) -> bool {
+        env.storage().persistent().has(&QuoteKey::QuoteSigner(signer))
+    }
+
+    /// Verify a signed quote and pay `destination` on its terms. Each quote
+    /// can be accepted once, and not for more than a large-transfer
+    /// threshold.
+    pub fn accept_quote(
+        env: Env,
+        quote_id: BytesN<32>,
+        signed_quote_blob: Bytes,
+        signature: BytesN<64>,
+    ) -> Result<QuoteReceipt, Error> {
+        let signed = SignedQuote::from_xdr(&env, &signed_quote_blob).map_err(|_| Error::InvalidSignature)?;
+        let quote = signed.quote;
+        if quote.quote_id != quote_id {
+            return Err(Error::NotFound);
+        }
+        let receipt_key = QuoteKey::QuoteReceipt(quote_id.clone());
+        if env.storage().persistent().has(&receipt_key) {
+            return Err(Error::AlreadyExists);
+        }
+        if now(&env) > quote.expires_at {
+            return Err(Error::Expired);
+        }
+        if quote.sell_amount <= 0 || quote.buy_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if !Self::is_quote_signer(env.clone(), quote.signer.clone()) {
+            return Err(Error::Unauthorized);
+        }
+
+        // Traps if the quote was not signed by its signer
+        verify_ed25519(&env, &quote.signer, &quote.clone().to_xdr(&env), &signed.signature)?;
+        large_transfers::check_transfer(&env, &quote.buy_asset, quote.buy_amount)?;
+        if quote.sell_asset != quote.buy_asset {
+            large_transfers::check_transfer(&env, &quote.sell_asset, quote.sell_amount)?;
+        }
+        contacts::check_destination(&env, &quote.destination)?;
+        fiat_limits::check_transfer(&env, &quote.buy_asset, quote.buy_amount)?;
+
+        let payload = (quote_id.clone(), signed_quote_blob.clone()).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "accept_quote", payload, signature)?;
+
+        let reserve = ReserveGuard::new(&env);
+        let sold = if quote.sell_asset == quote.buy_asset {
+            quote.buy_amount
+        } else {
+            buy_exact(
+                &env,
+                &quote.router,
+                &quote.sell_asset,
+                &quote.buy_asset,
+                quote.buy_amount,
+                quote.sell_amount,
+                quote.expires_at,
+            )?
+        };
+
+        token::Client::new(&env, &quote.buy_asset).transfer(
+            &env.current_contract_address(),
+            &quote.destination,
+            &quote.buy_amount,
+        );
+
+        let receipt = QuoteReceipt {
+            quote_hash: env.crypto().sha256(&signed_quote_blob).into(),
+            signer: quote.signer.clone(),
+            sold,
+            bought: quote.buy_amount,
+            destination: quote.destination.clone(),
+            ledger: env.ledger().sequence(),
+        };
+        env.storage().persistent().set(&receipt_key, &receipt);
+        record_op(
+            &env,
+            OpKind::QuotePayment,
+            &quote.buy_asset,
+            -quote.buy_amount,
+            Some(quote.destination.clone()),
+        );
+        reserve.check(&env)?;
+
+        publish_transfer(
+            &env,
+            (Symbol::new(&env, events::QUOTE_ACCEPTED), quote_id.clone()),
+            QuoteAcceptedEvent {
+                quote_id,
+                quote_hash: receipt.quote_hash.clone(),
+                signer: quote.signer,
+                sold,
+                bought: receipt.bought,
+            },
+            Some(&quote.destination),
+            &quote.buy_asset,
+            quote.buy_amount,
+        );
+
+        Ok(receipt)
+    }
+
+    /// Receipt of an accepted quote
+    pub fn get_quote_receipt(env: Env, quote_id: BytesN<32>) -> Result<QuoteReceipt, Error> {
+        env.storage()
+            .persistent()
+            .get(&QuoteKey::QuoteReceipt(quote_id))
+            .ok_or(Error::NotFound)
+    }
+}
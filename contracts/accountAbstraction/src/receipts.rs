@@ -0,0 +1,141 @@
+// ============================================================================
+// RECEIPT COMMITMENTS
+//
+// Once per epoch the relayer commits the Merkle root of the wallet's operation
+// receipts. Roots are write-once, so a statement exported later can be proven
+// against chain state with the verifier in `accesly-client::receipts`.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, symbol_short, xdr::ToXdr, Address, BytesN, Env, Symbol, Vec,
+};
+
+use crate::paging::{index_page, index_push};
+use crate::*;
+
+const RECEIPTS_INDEX: Symbol = symbol_short!("receipts");
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptsCommitment {
+    pub root: BytesN<32>,
+    pub receipt_count: u32,
+    /// Ledger in which the root was committed
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ReceiptsKey {
+    ReceiptsCommitter,
+    LatestReceiptsEpoch,
+    ReceiptsRoot(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptsCommittedEvent {
+    pub epoch: u64,
+    pub root: BytesN<32>,
+    pub receipt_count: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set the address (normally the relayer) allowed to commit receipt roots
+    pub fn set_receipts_committer(
+        env: Env,
+        committer: Address,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let payload = committer.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_receipts_committer", payload, signature)?;
+
+        env.storage()
+            .instance()
+            .set(&ReceiptsKey::ReceiptsCommitter, &committer);
+
+        Ok(())
+    }
+
+    /// Commit the receipts root for `epoch`. Epochs must strictly increase.
+    pub fn commit_receipts_root(
+        env: Env,
+        epoch: u64,
+        root: BytesN<32>,
+        receipt_count: u32,
+    ) -> Result<(), Error> {
+        receipts_committer(&env)?.require_auth();
+
+        let latest: Option<u64> = env.storage().instance().get(&ReceiptsKey::LatestReceiptsEpoch);
+        if latest.is_some_and(|latest| epoch <= latest) {
+            return Err(Error::InvalidEpoch);
+        }
+
+        let commitment = ReceiptsCommitment {
+            root: root.clone(),
+            receipt_count,
+            ledger: env.ledger().sequence(),
+        };
+        env.storage()
+            .persistent()
+            .set(&ReceiptsKey::ReceiptsRoot(epoch), &commitment);
+        env.storage()
+            .instance()
+            .set(&ReceiptsKey::LatestReceiptsEpoch, &epoch);
+        index_push(&env, &RECEIPTS_INDEX, &epoch);
+
+        env.events().publish(
+            (Symbol::new(&env, events::RECEIPTS_COMMITTED), epoch),
+            ReceiptsCommittedEvent {
+                epoch,
+                root,
+                receipt_count,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the receipts commitment for `epoch`
+    pub fn get_receipts_root(env: Env, epoch: u64) -> Result<ReceiptsCommitment, Error> {
+        env.storage()
+            .persistent()
+            .get(&ReceiptsKey::ReceiptsRoot(epoch))
+            .ok_or(Error::NotFound)
+    }
+
+    /// List committed epochs in commit order, one page at a time
+    pub fn list_receipts_roots(
+        env: Env,
+        cursor: u32,
+        limit: u32,
+    ) -> (Vec<(u64, ReceiptsCommitment)>, Option<u32>) {
+        let (epochs, next_cursor) = index_page::<u64>(&env, &RECEIPTS_INDEX, cursor, limit);
+
+        let mut items = Vec::new(&env);
+        for epoch in epochs.iter() {
+            if let Ok(commitment) = Self::get_receipts_root(env.clone(), epoch) {
+                items.push_back((epoch, commitment));
+            }
+        }
+        (items, next_cursor)
+    }
+
+    /// Get the most recently committed epoch
+    pub fn get_latest_receipts_epoch(env: Env) -> Result<u64, Error> {
+        env.storage()
+            .instance()
+            .get(&ReceiptsKey::LatestReceiptsEpoch)
+            .ok_or(Error::NotFound)
+    }
+}
+
+/// The address set with `set_receipts_committer`, which also commits
+/// balance snapshots
+pub(crate) fn receipts_committer(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .instance()
+        .get(&ReceiptsKey::ReceiptsCommitter)
+        .ok_or(Error::NotFound)
+}
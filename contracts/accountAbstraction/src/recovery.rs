@@ -0,0 +1,320 @@
+// ============================================================================
+// SOCIAL RECOVERY
+//
+// The owner names guardians (keys or passkeys the user doesn't carry: family,
+// a second phone, a custodian) and how many of them it takes to recover the
+// wallet. If the owner key is lost, one guardian proposes a new key, the
+// others approve, and once enough have and the recovery delay has passed
+// anyone can execute it. The current owner can veto until then, so
+// colluding guardians can't take a wallet whose owner is still around.
+// Once recovery is on, changing the guardians waits out the guardian delay
+// (see `guardian_changes`); a guardian replacing its own key doesn't (see
+// `guardian_rotation`).
+//
+// Guardians sign sha256(action || payload || nonce) rather than the message
+// itself: the payload is a whole key and a passkey challenge is capped at
+// 40 bytes.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol, Vec};
+
+use crate::approvals::record_owner_change;
+use crate::fragments::record_recovery_proof;
+use crate::guardian_changes::check_guardian_change;
+use crate::signers::verify_proof;
+use crate::*;
+
+pub const MAX_GUARDIANS: u32 = 10;
+/// Time the owner has to veto a recovery (3 days)
+pub const RECOVERY_DELAY: u64 = 3 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryRequest {
+    pub new_owner: BytesN<32>,
+    /// Earliest ledger timestamp `execute_recovery` is accepted at
+    pub eta: u64,
+    /// Guardians that approved, the initiator included
+    pub approvals: Vec<Signer>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum RecoveryKey {
+    Guardians,
+    /// Approvals needed to recover; 0 when recovery is off
+    GuardianThreshold,
+    Recovery,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianChangedEvent {
+    pub guardian: Signer,
+    pub added: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryInitiatedEvent {
+    pub new_owner: BytesN<32>,
+    pub eta: u64,
+    pub initiated_by: Signer,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryApprovedEvent {
+    pub approved_by: Signer,
+    pub approvals: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    pub fn add_guardian(env: Env, guardian: Signer, signature: BytesN<64>) -> Result<(), Error> {
+        // The owner guarding itself would let a stolen key recover to itself
+        if guardian == Signer::Ed25519(Self::get_owner(env.clone())?) {
+            return Err(Error::InvalidOwner);
+        }
+        // Recovery kits hold guardians by key, so they can sign offline
+        if matches!(guardian, Signer::Address(_)) {
+            return Err(Error::InvalidOwner);
+        }
+        let mut guardians = Self::get_guardians(env.clone());
+        if guardians.contains(&guardian) {
+            return Err(Error::AlreadyExists);
+        }
+        if guardians.len() >= MAX_GUARDIANS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = guardian.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "add_guardian", payload.clone(), signature)?;
+        check_guardian_change(&env, "add_guardian", &payload)?;
+
+        guardians.push_back(guardian.clone());
+        env.storage()
+            .instance()
+            .set(&RecoveryKey::Guardians, &guardians);
+        approvals::record_signer_added(&env, &guardian);
+        publish_guardian_changed(&env, guardian, true);
+
+        Ok(())
+    }
+
+    pub fn remove_guardian(env: Env, guardian: Signer, signature: BytesN<64>) -> Result<(), Error> {
+        let mut guardians = Self::get_guardians(env.clone());
+        let position = guardians.first_index_of(&guardian).ok_or(Error::NotFound)?;
+        if guardians.len() - 1 < guardian_threshold(&env) {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = guardian.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "remove_guardian", payload.clone(), signature)?;
+        check_guardian_change(&env, "remove_guardian", &payload)?;
+
+        guardians.remove(position);
+        env.storage()
+            .instance()
+            .set(&RecoveryKey::Guardians, &guardians);
+        publish_guardian_changed(&env, guardian, false);
+
+        Ok(())
+    }
+
+    /// Require `threshold` guardian approvals to recover; 0 turns recovery off
+    pub fn set_guardian_threshold(
+        env: Env,
+        threshold: u32,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if threshold > Self::get_guardians(env.clone()).len() {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = threshold.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_guardian_threshold", payload.clone(), signature)?;
+        check_guardian_change(&env, "set_guardian_threshold", &payload)?;
+
+        env.storage()
+            .instance()
+            .set(&RecoveryKey::GuardianThreshold, &threshold);
+        env.events()
+            .publish((Symbol::new(&env, events::GUARDIAN_THRESHOLD),), threshold);
+
+        Ok(())
+    }
+
+    pub fn get_guardians(env: Env) -> Vec<Signer> {
+        env.storage()
+            .instance()
+            .get(&RecoveryKey::Guardians)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_guardian_threshold(env: Env) -> u32 {
+        guardian_threshold(&env)
+    }
+
+    /// Start recovering the wallet to `new_owner`. Signed by a guardian,
+    /// whose approval counts.
+    pub fn initiate_recovery(
+        env: Env,
+        new_owner: BytesN<32>,
+        proof: SignerProof,
+    ) -> Result<(), Error> {
+        if guardian_threshold(&env) == 0 {
+            return Err(Error::Unauthorized);
+        }
+        if Self::is_zero_bytes(&new_owner) {
+            return Err(Error::InvalidOwner);
+        }
+        if Self::get_owner(env.clone())? == new_owner {
+            return Err(Error::SameOwner);
+        }
+        if Self::get_recovery(env.clone()).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = new_owner.clone().to_xdr(&env);
+        let guardian = verify_guardian(&env, "initiate_recovery", payload, &proof)?;
+
+        let eta = now(&env).saturating_add(RECOVERY_DELAY);
+        env.storage().instance().set(
+            &RecoveryKey::Recovery,
+            &RecoveryRequest {
+                new_owner: new_owner.clone(),
+                eta,
+                approvals: Vec::from_array(&env, [guardian.clone()]),
+            },
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::RECOVERY_INITIATED),),
+            RecoveryInitiatedEvent {
+                new_owner,
+                eta,
+                initiated_by: guardian,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Approve the pending recovery. Signed by a guardian.
+    pub fn approve_recovery(env: Env, proof: SignerProof) -> Result<(), Error> {
+        let mut recovery = Self::get_recovery(env.clone()).ok_or(Error::NotFound)?;
+        if recovery.approvals.contains(proof.signer()) {
+            return Err(Error::AlreadyExists);
+        }
+
+        let guardian = verify_guardian(&env, "approve_recovery", Bytes::new(&env), &proof)?;
+
+        recovery.approvals.push_back(guardian.clone());
+        env.storage()
+            .instance()
+            .set(&RecoveryKey::Recovery, &recovery);
+        env.events().publish(
+            (Symbol::new(&env, events::RECOVERY_APPROVED),),
+            RecoveryApprovedEvent {
+                approved_by: guardian,
+                approvals: recovery.approvals.len(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Cancel the pending recovery. Signed by the current owner.
+    pub fn veto_recovery(env: Env, signature: BytesN<64>) -> Result<(), Error> {
+        let recovery = Self::get_recovery(env.clone()).ok_or(Error::NotFound)?;
+
+        Self::require_owner_signature(&env, "veto_recovery", Bytes::new(&env), signature)?;
+
+        env.storage().instance().remove(&RecoveryKey::Recovery);
+        env.events().publish(
+            (Symbol::new(&env, events::RECOVERY_VETOED),),
+            recovery.new_owner,
+        );
+
+        Ok(())
+    }
+
+    /// Rotate to the recovered key once enough guardians approved and the
+    /// delay has passed. Anyone can call.
+    pub fn execute_recovery(env: Env) -> Result<(), Error> {
+        let recovery = Self::get_recovery(env.clone()).ok_or(Error::NotFound)?;
+        if now(&env) < recovery.eta {
+            return Err(Error::Timelocked);
+        }
+        // Approvals from guardians removed since don't count
+        let guardians = Self::get_guardians(env.clone());
+        let approvals = recovery
+            .approvals
+            .iter()
+            .filter(|g| guardians.contains(g))
+            .count() as u32;
+        let threshold = guardian_threshold(&env);
+        if threshold == 0 || approvals < threshold {
+            return Err(Error::Unauthorized);
+        }
+
+        let old_owner = Self::get_owner(env.clone())?;
+        let nonce = Self::get_and_increment_nonce(env.clone())?;
+        let storage = env.storage().instance();
+        storage.set(&DataKey::Owner, &recovery.new_owner);
+        record_owner_change(&env);
+        storage.remove(&RecoveryKey::Recovery);
+        // A rotation the lost key had proposed is void
+        storage.remove(&RotationKey::PendingRotation);
+        record_recovery_proof(&env, &recovery.new_owner, 0);
+        env.events().publish(
+            (Symbol::new(&env, events::KEY_ROTATED),),
+            KeyRotatedEvent {
+                old_owner,
+                new_owner: recovery.new_owner,
+                nonce,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_recovery(env: Env) -> Option<RecoveryRequest> {
+        env.storage().instance().get(&RecoveryKey::Recovery)
+    }
+}
+
+pub(crate) fn guardian_threshold(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&RecoveryKey::GuardianThreshold)
+        .unwrap_or(0)
+}
+
+/// Check `proof` is a guardian's signature over the hashed action message
+/// and consume the nonce. Returns the guardian.
+pub(crate) fn verify_guardian(
+    env: &Env,
+    action: &str,
+    payload: Bytes,
+    proof: &SignerProof,
+) -> Result<Signer, Error> {
+    let guardian = proof.signer();
+    if !WalletContract::get_guardians(env.clone()).contains(&guardian) {
+        return Err(Error::Unauthorized);
+    }
+
+    let message = WalletContract::action_message(env, action, payload)?;
+    let digest = Bytes::from_array(env, &env.crypto().sha256(&message).to_array());
+    verify_proof(env, proof, &digest)?;
+    WalletContract::get_and_increment_nonce(env.clone())?;
+
+    Ok(guardian)
+}
+
+fn publish_guardian_changed(env: &Env, guardian: Signer, added: bool) {
+    env.events().publish(
+        (Symbol::new(env, events::GUARDIAN_CHANGED),),
+        GuardianChangedEvent { guardian, added },
+    );
+}
@@ -0,0 +1,307 @@
+// ============================================================================
+// POST-RECOVERY COOLDOWN
+//
+// Recovering through the email address hands the wallet to whoever holds the
+// mailbox, which makes a phished inbox the shortest path to a takeover. So a
+// key installed by email recovery (at once, or when `confirm_rotation`
+// completes the rotation it started) can only spend up to a small cap for a
+// while, `RECOVERY_SPEND_CAP` cents for `RECOVERY_COOLDOWN` unless the owner
+// set others with `set_recovery_cooldown`. It is counted on every path the
+// fiat limit counts (see `fiat_limits`) and on top of it, and also on the two
+// the limit leaves out: transfers to own accounts, which the recovered key
+// could link for itself, and queued large transfers, counted when queued so
+// the queue's delay can't outwait the cooldown. Spending is priced in the
+// fiat limit's currency, or USD without one; without a fresh price nothing
+// can be shown to be under the cap, so nothing goes out. A wallet with no
+// oracle at all counts each asset against its own cap in `asset_caps`, in the
+// asset's units, and can't send assets it has none for. Guardian recovery
+// already waits out the recovery delay and gets no cooldown.
+//
+// This only caps spending: the key is the owner, unlike a quarantined one
+// (see `compromise`). A guardian who has confirmed the user's identity can
+// lift the cooldown early with `lift_recovery_cooldown`.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, Val,
+};
+
+use crate::fragments::ledgers_for;
+use crate::oracle::fiat_value;
+use crate::recovery::verify_guardian;
+use crate::*;
+
+/// How long spending stays capped after an email recovery (24 hours)
+pub const RECOVERY_COOLDOWN: u64 = 24 * 60 * 60;
+/// Most the recovered key can spend during the cooldown, in cents (100.00)
+pub const RECOVERY_SPEND_CAP: i128 = 10_000;
+/// Longest cooldown the owner can set (30 days)
+pub const MAX_RECOVERY_COOLDOWN: u64 = 30 * 24 * 60 * 60;
+pub const MAX_COOLDOWN_ASSETS: u32 = 10;
+
+/// Cooldown an email recovery starts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CooldownConfig {
+    pub duration: u64,
+    /// In cents of the fiat limit's currency, or USD
+    pub cap: i128,
+    /// Most of each asset the key can send without an oracle, in the
+    /// asset's own units
+    pub asset_caps: Map<Address, i128>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryCooldown {
+    pub until: u64,
+    /// Currency the cap is priced in
+    pub currency: Symbol,
+    /// In cents of `currency`
+    pub cap: i128,
+    /// Caps by asset, used without an oracle
+    pub asset_caps: Map<Address, i128>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CooldownKey {
+    RecoveryCooldown,
+    /// CooldownConfig, absent for the defaults
+    Config,
+    /// Cents spent during the cooldown
+    CooldownSpent,
+    /// Map<Address, i128> of each asset spent during the cooldown, without
+    /// an oracle
+    CooldownAssetsSpent,
+    /// Key a delayed email recovery rotates to, which starts a cooldown
+    /// when confirmed
+    CooldownOnConfirm,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Cap spending after an email recovery to `cap` cents for `duration`
+    /// seconds, or without an oracle to `asset_caps` of each asset. Refused
+    /// during a cooldown. Owner-signed over (duration, cap, asset_caps).
+    pub fn set_recovery_cooldown(
+        env: Env,
+        duration: u64,
+        cap: i128,
+        asset_caps: Map<Address, i128>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if duration == 0 || duration > MAX_RECOVERY_COOLDOWN {
+            return Err(Error::LimitExceeded);
+        }
+        if asset_caps.len() > MAX_COOLDOWN_ASSETS {
+            return Err(Error::LimitExceeded);
+        }
+        if cap < 0 || asset_caps.values().iter().any(|cap| cap < 0) {
+            return Err(Error::InvalidAmount);
+        }
+        // The recovered key can't loosen its own cap
+        if is_active(&env) {
+            return Err(Error::Timelocked);
+        }
+
+        let payload = (duration, cap, asset_caps.clone()).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_recovery_cooldown", payload, signature)?;
+
+        let config = CooldownConfig {
+            duration,
+            cap,
+            asset_caps,
+        };
+        env.storage().instance().set(&CooldownKey::Config, &config);
+        env.events().publish(
+            (Symbol::new(&env, events::RECOVERY_COOLDOWN_CONFIG),),
+            config,
+        );
+
+        Ok(())
+    }
+
+    /// Cooldown the next email recovery starts
+    pub fn get_recovery_cooldown_config(env: Env) -> CooldownConfig {
+        env.storage()
+            .instance()
+            .get(&CooldownKey::Config)
+            .unwrap_or(CooldownConfig {
+                duration: RECOVERY_COOLDOWN,
+                cap: RECOVERY_SPEND_CAP,
+                asset_caps: Map::new(&env),
+            })
+    }
+
+    /// End the post-recovery cooldown now. Signed by a guardian over the
+    /// hashed action message.
+    pub fn lift_recovery_cooldown(env: Env, proof: SignerProof) -> Result<(), Error> {
+        if Self::get_recovery_cooldown(env.clone()).is_none() {
+            return Err(Error::NotFound);
+        }
+        let guardian = verify_guardian(&env, "lift_recovery_cooldown", Bytes::new(&env), &proof)?;
+
+        end(&env);
+        env.events().publish(
+            (Symbol::new(&env, events::RECOVERY_COOLDOWN_LIFTED),),
+            guardian,
+        );
+
+        Ok(())
+    }
+
+    /// The cooldown in force, if any
+    pub fn get_recovery_cooldown(env: Env) -> Option<RecoveryCooldown> {
+        let cooldown: RecoveryCooldown = env
+            .storage()
+            .instance()
+            .get(&CooldownKey::RecoveryCooldown)?;
+        (now(&env) < cooldown.until).then_some(cooldown)
+    }
+
+    /// Cents spent so far during the cooldown
+    pub fn get_cooldown_spent(env: Env) -> i128 {
+        env.storage()
+            .temporary()
+            .get(&CooldownKey::CooldownSpent)
+            .unwrap_or(0)
+    }
+
+    /// How much of `asset` was spent so far during the cooldown, without
+    /// an oracle
+    pub fn get_cooldown_asset_spent(env: Env, asset: Address) -> i128 {
+        assets_spent(&env).get(asset).unwrap_or(0)
+    }
+}
+
+/// Cap spending from now on, for a key installed by email recovery
+pub(crate) fn start(env: &Env) {
+    let currency = WalletContract::get_fiat_limit(env.clone())
+        .map(|limit| limit.currency)
+        .unwrap_or(Symbol::new(env, "USD"));
+    let config = WalletContract::get_recovery_cooldown_config(env.clone());
+    let cooldown = RecoveryCooldown {
+        until: now(env).saturating_add(config.duration),
+        currency,
+        cap: config.cap,
+        asset_caps: config.asset_caps,
+    };
+    env.storage()
+        .instance()
+        .set(&CooldownKey::RecoveryCooldown, &cooldown);
+    clear_spent(env);
+    env.events()
+        .publish((Symbol::new(env, events::RECOVERY_COOLDOWN),), cooldown);
+}
+
+/// Start a cooldown when the delayed email recovery to `new_owner` is
+/// confirmed
+pub(crate) fn start_on_confirm(env: &Env, new_owner: &BytesN<32>) {
+    env.storage()
+        .instance()
+        .set(&CooldownKey::CooldownOnConfirm, new_owner);
+}
+
+/// `start` if `new_owner` comes from a delayed email recovery
+pub(crate) fn confirm(env: &Env, new_owner: &BytesN<32>) {
+    let pending: Option<BytesN<32>> = env
+        .storage()
+        .instance()
+        .get(&CooldownKey::CooldownOnConfirm);
+    if pending.is_some() {
+        env.storage()
+            .instance()
+            .remove(&CooldownKey::CooldownOnConfirm);
+    }
+    if pending.as_ref() == Some(new_owner) {
+        start(env);
+    }
+}
+
+pub(crate) fn is_active(env: &Env) -> bool {
+    WalletContract::get_recovery_cooldown(env.clone()).is_some()
+}
+
+/// Add `sent`, per asset, to what was spent during the cooldown, failing
+/// with `LimitExceeded` past the cap or when it can't be priced
+pub(crate) fn spend(env: &Env, sent: &Map<Address, i128>) -> Result<(), Error> {
+    let Some(cooldown) = WalletContract::get_recovery_cooldown(env.clone()) else {
+        return Ok(());
+    };
+    if sent.is_empty() {
+        return Ok(());
+    }
+    let Some(oracle) = WalletContract::get_oracle(env.clone()) else {
+        return spend_assets(env, &cooldown, sent);
+    };
+
+    let mut spent = WalletContract::get_cooldown_spent(env.clone());
+    for (asset, amount) in sent.iter() {
+        let value = fiat_value(env, &oracle, &asset, amount, &cooldown.currency)
+            .ok_or(Error::LimitExceeded)?;
+        spent = spent.saturating_add(value);
+    }
+    if spent > cooldown.cap {
+        return Err(Error::LimitExceeded);
+    }
+
+    save_spent(env, &cooldown, &CooldownKey::CooldownSpent, &spent);
+    Ok(())
+}
+
+/// `spend` without an oracle, each asset against its own cap
+fn spend_assets(
+    env: &Env,
+    cooldown: &RecoveryCooldown,
+    sent: &Map<Address, i128>,
+) -> Result<(), Error> {
+    let mut spent = assets_spent(env);
+    for (asset, amount) in sent.iter() {
+        let cap = cooldown
+            .asset_caps
+            .get(asset.clone())
+            .ok_or(Error::LimitExceeded)?;
+        let total = spent.get(asset.clone()).unwrap_or(0).saturating_add(amount);
+        if total > cap {
+            return Err(Error::LimitExceeded);
+        }
+        spent.set(asset, total);
+    }
+
+    save_spent(env, cooldown, &CooldownKey::CooldownAssetsSpent, &spent);
+    Ok(())
+}
+
+fn assets_spent(env: &Env) -> Map<Address, i128> {
+    env.storage()
+        .temporary()
+        .get(&CooldownKey::CooldownAssetsSpent)
+        .unwrap_or(Map::new(env))
+}
+
+/// Keep `spent` under `key` until the cooldown ends
+fn save_spent<V: IntoVal<Env, Val>>(
+    env: &Env,
+    cooldown: &RecoveryCooldown,
+    key: &CooldownKey,
+    spent: &V,
+) {
+    let storage = env.storage().temporary();
+    storage.set(key, spent);
+    let ledgers = ledgers_for(cooldown.until.saturating_sub(now(env))) + 1;
+    storage.extend_ttl(key, ledgers, ledgers);
+}
+
+fn clear_spent(env: &Env) {
+    let storage = env.storage().temporary();
+    storage.remove(&CooldownKey::CooldownSpent);
+    storage.remove(&CooldownKey::CooldownAssetsSpent);
+}
+
+fn end(env: &Env) {
+    env.storage()
+        .instance()
+        .remove(&CooldownKey::RecoveryCooldown);
+    clear_spent(env);
+}
@@ -0,0 +1,114 @@
+// ============================================================================
+// RELAYER ALLOWLIST
+//
+// A signed authorization is good for whoever holds it: anyone who sees the
+// auth entry on its way to the relayer (a logging proxy, a compromised app
+// backend) can submit it first, in a transaction of their choosing. With
+// relayers set, `__check_auth` only accepts `AuthSignature::Relayed`, which
+// carries the owner's signature plus one by an allowlisted relayer key over
+// "relayed" || signature_payload. The relayer signs as it submits, so an
+// entry that took any other path has no co-signature and is refused.
+//
+// Owner-gated entrypoints verify their own signatures and aren't covered;
+// neither is `AuthSignature::PreAuthorized`, which carries no signature to
+// wrap, so pre-authorizing stops working while the allowlist is on.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol, Vec};
+
+use crate::*;
+
+pub const MAX_RELAYERS: u32 = 10;
+
+/// An authorization co-signed by the relayer submitting it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelayedSignature {
+    /// Nonce channel, 0 for the wallet nonce
+    pub channel: u32,
+    pub relayer: BytesN<32>,
+    /// The relayer's ed25519 signature over "relayed" || signature_payload
+    pub relayer_signature: BytesN<64>,
+    pub signature: ChannelSignature,
+    /// Last ledger sequence the owner's signature is valid in, if it expires
+    pub valid_until_ledger: Option<u32>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum RelayerKey {
+    Relayers,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Only accept authorizations co-signed by one of `relayers`; an empty
+    /// list accepts them from anyone again
+    pub fn set_relayers(
+        env: Env,
+        relayers: Vec<BytesN<32>>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if relayers.len() > MAX_RELAYERS {
+            return Err(Error::LimitExceeded);
+        }
+        for (i, relayer) in relayers.iter().enumerate() {
+            if Self::is_zero_bytes(&relayer) {
+                return Err(Error::InvalidOwner);
+            }
+            if relayers.first_index_of(&relayer) != Some(i as u32) {
+                return Err(Error::AlreadyExists);
+            }
+        }
+
+        let payload = relayers.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_relayers", payload, signature)?;
+
+        if relayers.is_empty() {
+            env.storage().instance().remove(&RelayerKey::Relayers);
+        } else {
+            env.storage()
+                .instance()
+                .set(&RelayerKey::Relayers, &relayers);
+        }
+        env.events()
+            .publish((Symbol::new(&env, events::RELAYERS_SET),), relayers);
+
+        Ok(())
+    }
+
+    pub fn get_relayers(env: Env) -> Vec<BytesN<32>> {
+        relayers(&env)
+    }
+}
+
+fn relayers(env: &Env) -> Vec<BytesN<32>> {
+    env.storage()
+        .instance()
+        .get(&RelayerKey::Relayers)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Fail unless `signature` is relayed, when relayers are set
+pub(crate) fn check_required(env: &Env, signature: &AuthSignature) -> Result<(), Error> {
+    if matches!(signature, AuthSignature::Relayed(_)) || relayers(env).is_empty() {
+        return Ok(());
+    }
+    Err(Error::Unauthorized)
+}
+
+/// Verify the relayer's co-signature over `signature_payload`. Relayed
+/// signatures are refused from relayers not on the list, and while there's
+/// no list at all.
+pub(crate) fn verify_relayer(
+    env: &Env,
+    signature_payload: &BytesN<32>,
+    relayed: &RelayedSignature,
+) -> Result<(), Error> {
+    if !relayers(env).contains(&relayed.relayer) {
+        return Err(Error::Unauthorized);
+    }
+    let mut message = Bytes::new(env);
+    accesly_messages::relayer_message(&mut message, &signature_payload.to_array());
+    signature::verify_ed25519(env, &relayed.relayer, &message, &relayed.relayer_signature)
+}
@@ -0,0 +1,142 @@
+// ============================================================================
+// XLM RESERVE
+//
+// Self-sponsored wallets pay their own fees and TTL extensions, so spending
+// their last XLM strands them: the next rent bump can't be paid. The owner
+// sets how much native balance to hold back, and every outgoing path is
+// checked against it: the wallet's own value-moving entrypoints at their
+// end, and native transfers the wallet authorizes as an account in
+// `__check_auth`. `top_up_needed` tells the SDK how far below the reserve
+// the wallet currently is.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol,
+    TryFromVal, Vec,
+};
+
+use crate::*;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveConfig {
+    /// Native XLM token contract
+    pub native_token: Address,
+    /// Balance the wallet's own operations can't spend below
+    pub min_reserve: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ReserveKey {
+    ReserveConfig,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set or (with `None`) clear the reserve
+    pub fn set_reserve(
+        env: Env,
+        config: Option<ReserveConfig>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if config.as_ref().is_some_and(|c| c.min_reserve < 0) {
+            return Err(Error::InvalidAmount);
+        }
+
+        let payload = config.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_reserve", payload, signature)?;
+
+        match config {
+            Some(config) => env
+                .storage()
+                .instance()
+                .set(&ReserveKey::ReserveConfig, &config),
+            None => env.storage().instance().remove(&ReserveKey::ReserveConfig),
+        }
+
+        Ok(())
+    }
+
+    pub fn get_reserve(env: Env) -> Option<ReserveConfig> {
+        env.storage().instance().get(&ReserveKey::ReserveConfig)
+    }
+
+    /// XLM missing to get back to the reserve, 0 when there is enough
+    pub fn top_up_needed(env: Env) -> i128 {
+        match Self::get_reserve(env.clone()) {
+            None => 0,
+            Some(config) => (config.min_reserve - native_balance(&env, &config)).max(0),
+        }
+    }
+}
+
+fn native_balance(env: &Env, config: &ReserveConfig) -> i128 {
+    token::Client::new(env, &config.native_token).balance(&env.current_contract_address())
+}
+
+/// Snapshot of the native balance before an operation that moves funds
+/// out. The operation fails (and reverts) if it spent XLM and left the
+/// wallet below its reserve; operations that don't touch XLM still work
+/// on an underfunded wallet.
+pub(crate) struct ReserveGuard {
+    config: Option<ReserveConfig>,
+    before: i128,
+}
+
+impl ReserveGuard {
+    pub(crate) fn new(env: &Env) -> Self {
+        let config = WalletContract::get_reserve(env.clone());
+        let before = config.as_ref().map_or(0, |c| native_balance(env, c));
+        Self { config, before }
+    }
+
+    pub(crate) fn check(&self, env: &Env) -> Result<(), Error> {
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+        let after = native_balance(env, config);
+        if after < self.before && after < config.min_reserve {
+            return Err(Error::LimitExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// Fail if native transfers out of the wallet in `contexts` would take it
+/// below its reserve
+pub(crate) fn check_auth_contexts(env: &Env, contexts: &Vec<Context>) -> Result<(), Error> {
+    let Some(config) = WalletContract::get_reserve(env.clone()) else {
+        return Ok(());
+    };
+    let wallet = env.current_contract_address();
+    let transfer = Symbol::new(env, "transfer");
+
+    let mut outgoing: i128 = 0;
+    for context in contexts.iter() {
+        let Context::Contract(call) = context else {
+            continue;
+        };
+        if call.contract != config.native_token || call.fn_name != transfer {
+            continue;
+        }
+        let from = call
+            .args
+            .get(0)
+            .and_then(|v| Address::try_from_val(env, &v).ok());
+        let amount = call
+            .args
+            .get(2)
+            .and_then(|v| i128::try_from_val(env, &v).ok());
+        if let (Some(from), Some(amount)) = (from, amount) {
+            if from == wallet {
+                outgoing = outgoing.saturating_add(amount);
+            }
+        }
+    }
+
+    if outgoing > 0 && native_balance(env, &config).saturating_sub(outgoing) < config.min_reserve {
+        return Err(Error::LimitExceeded);
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+// ============================================================================
+// TIMELOCKED OWNER ROTATION
+//
+// Once the owner sets a rotation delay, `update_owner` is closed and the key
+// can only change in two steps: the owner proposes a new key, and after the
+// delay the new key confirms by signing with itself. Until then the owner
+// or any other registered device (a weighted signer, the passkey or a
+// linked device) can cancel, so a stolen owner key can't quietly take the
+// wallet over. The delay can be raised but never lowered, for the same
+// reason.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, BytesN, Env, Symbol};
+
+use crate::approvals::record_owner_change;
+use crate::devices::is_device;
+use crate::freeze::ensure_not_frozen;
+use crate::recovery_cooldown;
+use crate::signature::verify_ed25519;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Longest delay that can be configured (30 days)
+pub const MAX_ROTATION_DELAY: u64 = 30 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingRotation {
+    pub new_owner: BytesN<32>,
+    /// Earliest ledger timestamp `confirm_rotation` is accepted at
+    pub eta: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum RotationKey {
+    /// Seconds between proposal and confirmation; 0 when not configured
+    RotationDelay,
+    PendingRotation,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RotationProposedEvent {
+    pub new_owner: BytesN<32>,
+    pub eta: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RotationCancelledEvent {
+    pub new_owner: BytesN<32>,
+    /// Device that cancelled
+    pub cancelled_by: Signer,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Require `delay` seconds between proposing and confirming a rotation
+    pub fn set_rotation_delay(env: Env, delay: u64, signature: BytesN<64>) -> Result<(), Error> {
+        if delay > MAX_ROTATION_DELAY {
+            return Err(Error::LimitExceeded);
+        }
+        if delay < rotation_delay(&env) {
+            return Err(Error::Timelocked);
+        }
+
+        let payload = delay.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_rotation_delay", payload, signature)?;
+
+        env.storage()
+            .instance()
+            .set(&RotationKey::RotationDelay, &delay);
+        env.events()
+            .publish((Symbol::new(&env, events::ROTATION_DELAY),), delay);
+
+        Ok(())
+    }
+
+    pub fn get_rotation_delay(env: Env) -> u64 {
+        rotation_delay(&env)
+    }
+
+    pub fn propose_owner_rotation(
+        env: Env,
+        new_owner: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::is_zero_bytes(&new_owner) {
+            return Err(Error::InvalidOwner);
+        }
+        if Self::get_owner(env.clone())? == new_owner {
+            return Err(Error::SameOwner);
+        }
+        if Self::get_pending_rotation(env.clone()).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = new_owner.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "propose_owner_rotation", payload, signature)?;
+
+        let eta = now(&env).saturating_add(rotation_delay(&env));
+        env.storage().instance().set(
+            &RotationKey::PendingRotation,
+            &PendingRotation {
+                new_owner: new_owner.clone(),
+                eta,
+            },
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::ROTATION_PROPOSED),),
+            RotationProposedEvent { new_owner, eta },
+        );
+
+        Ok(())
+    }
+
+    /// Complete a rotation once its delay has passed. Signed by the new key.
+    pub fn confirm_rotation(env: Env, signature: BytesN<64>) -> Result<(), Error> {
+        let pending = Self::get_pending_rotation(env.clone()).ok_or(Error::NotFound)?;
+        ensure_not_frozen(&env)?;
+        if now(&env) < pending.eta {
+            return Err(Error::Timelocked);
+        }
+
+        let old_owner = Self::get_owner(env.clone())?;
+        let nonce = Self::get_nonce(env.clone())?;
+        let message = Self::action_message(&env, "confirm_rotation", Bytes::new(&env))?;
+        verify_ed25519(&env, &pending.new_owner, &message, &signature)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Owner, &pending.new_owner);
+        record_owner_change(&env);
+        recovery_cooldown::confirm(&env, &pending.new_owner);
+        env.storage()
+            .instance()
+            .remove(&RotationKey::PendingRotation);
+        env.events().publish(
+            (Symbol::new(&env, events::KEY_ROTATED),),
+            KeyRotatedEvent {
+                old_owner,
+                new_owner: pending.new_owner,
+                nonce,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Cancel the pending rotation. Signed by the owner, a registered
+    /// signer, the passkey or a linked device.
+    pub fn cancel_rotation(env: Env, proof: SignerProof) -> Result<(), Error> {
+        let pending = Self::get_pending_rotation(env.clone()).ok_or(Error::NotFound)?;
+
+        let device = proof.signer();
+        let trusted = is_registered(&env, &device)?
+            || matches!(&device, Signer::Ed25519(key) if is_device(&env, key));
+        if !trusted {
+            return Err(Error::Unauthorized);
+        }
+
+        // Short enough to fit a passkey challenge; the nonce pins it to
+        // this proposal
+        let message = Self::action_message(&env, "cancel_rotation", Bytes::new(&env))?;
+        verify_proof(&env, &proof, &message)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        env.storage()
+            .instance()
+            .remove(&RotationKey::PendingRotation);
+        env.events().publish(
+            (Symbol::new(&env, events::ROTATION_CANCELLED),),
+            RotationCancelledEvent {
+                new_owner: pending.new_owner,
+                cancelled_by: device,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_pending_rotation(env: Env) -> Option<PendingRotation> {
+        env.storage().instance().get(&RotationKey::PendingRotation)
+    }
+}
+
+/// Whether `device` is the owner key, the passkey or a weighted signer
+pub(crate) fn is_registered(env: &Env, device: &Signer) -> Result<bool, Error> {
+    let signers = WalletContract::get_signers(env.clone());
+    Ok(match device {
+        Signer::Ed25519(key) => {
+            *key == WalletContract::get_owner(env.clone())? || signers.contains_key(device.clone())
+        }
+        Signer::Secp256r1(key) => {
+            WalletContract::get_passkey(env.clone()).as_ref() == Some(key)
+                || signers.contains_key(device.clone())
+        }
+        Signer::Address(_) => signers.contains_key(device.clone()),
+    })
+}
+
+pub(crate) fn rotation_delay(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&RotationKey::RotationDelay)
+        .unwrap_or(0)
+}
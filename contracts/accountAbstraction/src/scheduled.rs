@@ -0,0 +1,165 @@
+// ============================================================================
+// SCHEDULED TRANSACTIONS
+//
+// "Send the rent on the 1st" without the owner's key being online then: the
+// owner signs one call and the ledger it may run after, the wallet stores
+// it, and once that ledger has passed any keeper or relayer can execute it.
+// The owner can cancel until then.
+//
+// The call is checked like a single-op batch when it is scheduled, and
+// again when it runs, since the contract policy or batch limits may have
+// changed in between. It runs at most once, and only within
+// `SCHEDULE_EXECUTION_WINDOW` ledgers of its time, so a keeper that shows
+// up weeks late can't send last month's rent. Scheduled calls live in
+// persistent storage, kept alive a ledger past the window so a late keeper
+// gets `Expired`. The wallet instance is extended as long, so a keeper can
+// still run them on a wallet nobody has touched since.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Val, Vec,
+};
+
+use crate::batch::{check_calls, check_ops, invoke};
+use crate::freeze::ensure_not_frozen;
+use crate::reserve::ReserveGuard;
+use crate::*;
+
+/// Furthest ahead a call can be scheduled, in ledgers (90 days)
+pub const MAX_SCHEDULE_DELAY: u32 = 90 * 17_280;
+/// Ledgers after its time a scheduled call can still run (7 days)
+pub const SCHEDULE_EXECUTION_WINDOW: u32 = 7 * 17_280;
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ScheduledOp {
+    pub op: Invocation,
+    /// The call runs in any later ledger, within the execution window
+    pub execute_after_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ScheduledKey {
+    Scheduled(u32),
+    NextScheduledId,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduledEvent {
+    pub id: u32,
+    pub contract: Address,
+    pub fn_name: Symbol,
+    pub execute_after_ledger: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Store `op` for anyone to execute after `execute_after_ledger`.
+    /// Owner-signed over (op, execute_after_ledger). Returns its id.
+    pub fn schedule(
+        env: Env,
+        op: Invocation,
+        execute_after_ledger: u32,
+        signature: BytesN<64>,
+    ) -> Result<u32, Error> {
+        let current = env.ledger().sequence();
+        if execute_after_ledger <= current {
+            return Err(Error::Expired);
+        }
+        if execute_after_ledger - current > MAX_SCHEDULE_DELAY {
+            return Err(Error::LimitExceeded);
+        }
+        // Spending counts against the fiat limit when the call runs
+        check_calls(&env, &Vec::from_array(&env, [op.clone()]))?;
+
+        let payload = (op.clone(), execute_after_ledger).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "schedule", payload, signature)?;
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&ScheduledKey::NextScheduledId)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ScheduledKey::NextScheduledId, &(id + 1));
+
+        let key = ScheduledKey::Scheduled(id);
+        let storage = env.storage().persistent();
+        storage.set(
+            &key,
+            &ScheduledOp {
+                op: op.clone(),
+                execute_after_ledger,
+            },
+        );
+        let ledgers = execute_after_ledger - current + SCHEDULE_EXECUTION_WINDOW + 1;
+        storage.extend_ttl(&key, ledgers, ledgers);
+        env.storage().instance().extend_ttl(ledgers, ledgers);
+
+        env.events().publish(
+            (Symbol::new(&env, events::TX_SCHEDULED), id),
+            ScheduledEvent {
+                id,
+                contract: op.contract,
+                fn_name: op.fn_name,
+                execute_after_ledger,
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Run a scheduled call whose time has come. Anyone can call. Returns
+    /// the call's result.
+    pub fn execute_scheduled(env: Env, id: u32) -> Result<Val, Error> {
+        let scheduled = Self::get_scheduled(env.clone(), id).ok_or(Error::NotFound)?;
+        ensure_not_frozen(&env)?;
+        let current = env.ledger().sequence();
+        if current <= scheduled.execute_after_ledger {
+            return Err(Error::Timelocked);
+        }
+        if current - scheduled.execute_after_ledger > SCHEDULE_EXECUTION_WINDOW {
+            return Err(Error::Expired);
+        }
+        check_ops(&env, &Vec::from_array(&env, [scheduled.op.clone()]))?;
+
+        env.storage()
+            .persistent()
+            .remove(&ScheduledKey::Scheduled(id));
+
+        let reserve = ReserveGuard::new(&env);
+        let result = invoke(&env, &scheduled.op);
+        reserve.check(&env)?;
+
+        env.events()
+            .publish((Symbol::new(&env, events::SCHEDULED_EXECUTED), id), ());
+
+        Ok(result)
+    }
+
+    /// Drop a scheduled call before it runs. Owner-signed over the id.
+    pub fn cancel_scheduled(env: Env, id: u32, signature: BytesN<64>) -> Result<(), Error> {
+        if Self::get_scheduled(env.clone(), id).is_none() {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &id.to_be_bytes());
+        Self::require_owner_signature(&env, "cancel_scheduled", payload, signature)?;
+
+        env.storage()
+            .persistent()
+            .remove(&ScheduledKey::Scheduled(id));
+        env.events()
+            .publish((Symbol::new(&env, events::SCHEDULED_CANCELLED), id), ());
+
+        Ok(())
+    }
+
+    pub fn get_scheduled(env: Env, id: u32) -> Option<ScheduledOp> {
+        env.storage().persistent().get(&ScheduledKey::Scheduled(id))
+    }
+}
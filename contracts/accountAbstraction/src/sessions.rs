@@ -0,0 +1,444 @@
+// ============================================================================
+// SESSION KEYS
+//
+// Temporary ed25519 signers handed to a dApp. The owner creates a session
+// for a key with an expiry and the (contract, function) pairs it may
+// authorize. `__check_auth` accepts that key's signatures until it expires
+// or is revoked, and only when every invocation in the auth context is on
+// the allowlist. Each use is counted on the session and announced in an
+// event, so the owner can see which session authorized what.
+// `revoke_all_sessions` bumps an epoch instead of touching every session
+// entry.
+//
+// A session for a contract the app doesn't know can carry the hash of the
+// risk summary the owner was shown before approving (see
+// `accesly_client::session_risk`). It is signed with the session and kept
+// beside it, so the consent can be checked against the summary later.
+//
+// A session can be re-issued to another app, a mini-app inside a super-app
+// say, with `derive_session`: a new key limited to some of the parent's
+// calls and ending no later, narrower in at least one of the two. Revoking
+// a session revokes everything derived from it, down the whole tree.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::Context, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec,
+};
+
+use crate::signature::verify_ed25519;
+use crate::*;
+
+/// Longest a session can be granted for (30 days)
+pub const MAX_SESSION_DURATION: u64 = 30 * 24 * 60 * 60;
+/// Most (contract, function) pairs one session can be scoped to
+pub const MAX_SESSION_CALLS: u32 = 10;
+/// Most sessions derived straight from one session
+pub const MAX_SESSION_CHILDREN: u32 = 5;
+/// Most sessions in a chain of derivations, the root included
+pub const MAX_SESSION_DEPTH: u32 = 3;
+
+/// An invocation a session key may authorize
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionCall {
+    pub contract: Address,
+    pub function: Symbol,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub public_key: BytesN<32>,
+    /// What the session was granted for, as shown to the owner
+    pub scope: Symbol,
+    /// Invocations the key may authorize; anything else is rejected
+    pub allowed: Vec<SessionCall>,
+    /// Ledger timestamp after which the key is no longer accepted
+    pub expires_at: u64,
+    /// Calls authorized so far
+    pub uses: u32,
+    /// Revocation epoch the session was created in
+    pub epoch: u32,
+}
+
+/// What a derived session may do
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionScope {
+    pub scope: Symbol,
+    /// Some of the parent's calls
+    pub allowed: Vec<SessionCall>,
+    /// No later than the parent's
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SessionKey {
+    Session(BytesN<32>),
+    /// Hash of the risk summary a session was approved with
+    RiskSummary(BytesN<32>),
+    /// Sessions created under an older epoch are revoked
+    SessionEpoch,
+    /// Session a session was derived from
+    ParentSession(BytesN<32>),
+    /// Vec<BytesN<32>> of sessions derived from a session
+    ChildSessions(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionCreatedEvent {
+    pub public_key: BytesN<32>,
+    pub scope: Symbol,
+    pub allowed: Vec<SessionCall>,
+    pub expires_at: u64,
+    pub risk_summary: Option<BytesN<32>>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionDerivedEvent {
+    pub parent: BytesN<32>,
+    pub public_key: BytesN<32>,
+    pub scope: SessionScope,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionAuthEvent {
+    pub public_key: BytesN<32>,
+    pub nonce: u64,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Grant `public_key` the calls in `allowed` until `expires_at`, with
+    /// the hash of the risk summary shown to the owner, if any. Owner-signed
+    /// over (public_key, scope, allowed, expires_at, risk_summary).
+    pub fn create_session(
+        env: Env,
+        public_key: BytesN<32>,
+        scope: Symbol,
+        allowed: Vec<SessionCall>,
+        expires_at: u64,
+        risk_summary: Option<BytesN<32>>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::is_zero_bytes(&public_key) {
+            return Err(Error::InvalidOwner);
+        }
+        // A session that may call nothing is a mistake, not a restriction
+        if allowed.is_empty() || allowed.len() > MAX_SESSION_CALLS {
+            return Err(Error::LimitExceeded);
+        }
+        let now = now(&env);
+        if expires_at <= now {
+            return Err(Error::Expired);
+        }
+        if expires_at - now > MAX_SESSION_DURATION {
+            return Err(Error::LimitExceeded);
+        }
+        if Self::get_session(env.clone(), public_key.clone()).is_ok() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = (
+            public_key.clone(),
+            scope.clone(),
+            allowed.clone(),
+            expires_at,
+            risk_summary.clone(),
+        )
+            .to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_high_risk_signature(&env, "create_session", payload, signature)?;
+
+        let session = Session {
+            public_key: public_key.clone(),
+            scope: scope.clone(),
+            allowed: allowed.clone(),
+            expires_at,
+            uses: 0,
+            epoch: session_epoch(&env),
+        };
+        // An expired session under this key may have been derived
+        detach(&env, &public_key);
+        env.storage()
+            .persistent()
+            .set(&SessionKey::Session(public_key.clone()), &session);
+        if let Some(hash) = &risk_summary {
+            env.storage()
+                .persistent()
+                .set(&SessionKey::RiskSummary(public_key.clone()), hash);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, events::SESSION_CREATED), public_key.clone()),
+            SessionCreatedEvent {
+                public_key,
+                scope,
+                allowed,
+                expires_at,
+                risk_summary,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Re-issue the live session `parent_session_id` to `public_key`,
+    /// limited to `narrowed_scope`. Owner-signed over (parent_session_id,
+    /// public_key, narrowed_scope).
+    pub fn derive_session(
+        env: Env,
+        parent_session_id: BytesN<32>,
+        public_key: BytesN<32>,
+        narrowed_scope: SessionScope,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let parent = Self::get_session(env.clone(), parent_session_id.clone())?;
+        if Self::is_zero_bytes(&public_key) {
+            return Err(Error::InvalidOwner);
+        }
+        if Self::get_session(env.clone(), public_key.clone()).is_ok() {
+            return Err(Error::AlreadyExists);
+        }
+        let allowed = &narrowed_scope.allowed;
+        if allowed.is_empty() || allowed.iter().any(|call| !parent.allowed.contains(&call)) {
+            return Err(Error::LimitExceeded);
+        }
+        let narrower =
+            allowed.len() < parent.allowed.len() || narrowed_scope.expires_at < parent.expires_at;
+        if narrowed_scope.expires_at > parent.expires_at || !narrower {
+            return Err(Error::LimitExceeded);
+        }
+        if narrowed_scope.expires_at <= now(&env) {
+            return Err(Error::Expired);
+        }
+        // Expired children don't take up a slot
+        let mut children = Vec::new(&env);
+        for child in Self::get_child_sessions(env.clone(), parent_session_id.clone()).iter() {
+            if Self::get_session(env.clone(), child.clone()).is_ok() {
+                children.push_back(child);
+            }
+        }
+        if children.len() >= MAX_SESSION_CHILDREN
+            || depth(&env, &parent_session_id) >= MAX_SESSION_DEPTH
+        {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (
+            parent_session_id.clone(),
+            public_key.clone(),
+            narrowed_scope.clone(),
+        )
+            .to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_high_risk_signature(&env, "derive_session", payload, signature)?;
+
+        let session = Session {
+            public_key: public_key.clone(),
+            scope: narrowed_scope.scope.clone(),
+            allowed: narrowed_scope.allowed.clone(),
+            expires_at: narrowed_scope.expires_at,
+            uses: 0,
+            epoch: session_epoch(&env),
+        };
+        detach(&env, &public_key);
+        let storage = env.storage().persistent();
+        storage.set(&SessionKey::Session(public_key.clone()), &session);
+        storage.set(
+            &SessionKey::ParentSession(public_key.clone()),
+            &parent_session_id,
+        );
+        children.push_back(public_key.clone());
+        storage.set(
+            &SessionKey::ChildSessions(parent_session_id.clone()),
+            &children,
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, events::SESSION_DERIVED), public_key.clone()),
+            SessionDerivedEvent {
+                parent: parent_session_id,
+                public_key,
+                scope: narrowed_scope,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Revoke a session and every session derived from it
+    pub fn revoke_session(
+        env: Env,
+        public_key: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let key = SessionKey::Session(public_key.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = public_key.clone().to_xdr(&env);
+        Self::require_owner_signature(&env, "revoke_session", payload, signature)?;
+
+        detach(&env, &public_key);
+        remove_session_tree(&env, public_key);
+
+        Ok(())
+    }
+
+    /// Session `public_key` was derived from, if any
+    pub fn get_parent_session(env: Env, public_key: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&SessionKey::ParentSession(public_key))
+    }
+
+    /// Sessions derived straight from `public_key`
+    pub fn get_child_sessions(env: Env, public_key: BytesN<32>) -> Vec<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&SessionKey::ChildSessions(public_key))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Revoke every session created so far
+    pub fn revoke_all_sessions(env: Env, signature: BytesN<64>) -> Result<(), Error> {
+        Self::require_owner_signature(&env, "revoke_all_sessions", Bytes::new(&env), signature)?;
+
+        let epoch = session_epoch(&env)
+            .checked_add(1)
+            .ok_or(Error::LimitExceeded)?;
+        env.storage()
+            .instance()
+            .set(&SessionKey::SessionEpoch, &epoch);
+        env.events()
+            .publish((Symbol::new(&env, events::SESSIONS_REVOKED),), epoch);
+
+        Ok(())
+    }
+
+    /// A live session: created, not revoked and not expired
+    pub fn get_session(env: Env, public_key: BytesN<32>) -> Result<Session, Error> {
+        let session: Session = env
+            .storage()
+            .persistent()
+            .get(&SessionKey::Session(public_key))
+            .ok_or(Error::NotFound)?;
+        if session.epoch != session_epoch(&env) {
+            return Err(Error::NotFound);
+        }
+        if now(&env) > session.expires_at {
+            return Err(Error::Expired);
+        }
+        Ok(session)
+    }
+
+    /// Risk summary hash a live session was approved with
+    pub fn get_session_risk(env: Env, public_key: BytesN<32>) -> Option<BytesN<32>> {
+        Self::get_session(env.clone(), public_key.clone()).ok()?;
+        env.storage()
+            .persistent()
+            .get(&SessionKey::RiskSummary(public_key))
+    }
+}
+
+/// Sessions in the chain ending at `public_key`, the root included
+fn depth(env: &Env, public_key: &BytesN<32>) -> u32 {
+    let mut depth = 1;
+    let mut current = public_key.clone();
+    while let Some(parent) = WalletContract::get_parent_session(env.clone(), current) {
+        depth += 1;
+        current = parent;
+    }
+    depth
+}
+
+/// Drop `public_key` from the children of the session it was derived from
+fn detach(env: &Env, public_key: &BytesN<32>) {
+    let storage = env.storage().persistent();
+    let parent_key = SessionKey::ParentSession(public_key.clone());
+    let Some(parent) = storage.get::<_, BytesN<32>>(&parent_key) else {
+        return;
+    };
+    storage.remove(&parent_key);
+
+    let mut siblings = WalletContract::get_child_sessions(env.clone(), parent.clone());
+    if let Some(index) = siblings.first_index_of(public_key) {
+        siblings.remove(index);
+    }
+    let siblings_key = SessionKey::ChildSessions(parent);
+    if siblings.is_empty() {
+        storage.remove(&siblings_key);
+    } else {
+        storage.set(&siblings_key, &siblings);
+    }
+}
+
+/// Remove `public_key`'s session and everything derived from it
+fn remove_session_tree(env: &Env, public_key: BytesN<32>) {
+    let storage = env.storage().persistent();
+    for child in WalletContract::get_child_sessions(env.clone(), public_key.clone()).iter() {
+        remove_session_tree(env, child);
+    }
+    storage.remove(&SessionKey::Session(public_key.clone()));
+    storage.remove(&SessionKey::RiskSummary(public_key.clone()));
+    storage.remove(&SessionKey::ParentSession(public_key.clone()));
+    storage.remove(&SessionKey::ChildSessions(public_key.clone()));
+    env.events()
+        .publish((Symbol::new(env, events::SESSION_REVOKED), public_key), ());
+}
+
+pub(crate) fn session_epoch(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&SessionKey::SessionEpoch)
+        .unwrap_or(0)
+}
+
+/// Verify a session key's signature over `message`, check `contexts`
+/// against the session's allowlist and record the use
+pub(crate) fn verify_session(
+    env: &Env,
+    public_key: BytesN<32>,
+    message: &Bytes,
+    signature: &BytesN<64>,
+    contexts: &Vec<Context>,
+    nonce: u64,
+) -> Result<(), Error> {
+    let mut session = match WalletContract::get_session(env.clone(), public_key.clone()) {
+        Err(Error::NotFound) => return Err(Error::Unauthorized),
+        other => other?,
+    };
+
+    // Contract deployments are never in scope
+    for context in contexts.iter() {
+        let Context::Contract(call) = context else {
+            return Err(Error::Unauthorized);
+        };
+        let call = SessionCall {
+            contract: call.contract,
+            function: call.fn_name,
+        };
+        if !session.allowed.contains(&call) {
+            return Err(Error::Unauthorized);
+        }
+    }
+
+    verify_ed25519(env, &public_key, message, signature)?;
+
+    session.uses = session.uses.saturating_add(1);
+    env.storage()
+        .persistent()
+        .set(&SessionKey::Session(public_key.clone()), &session);
+    env.events().publish(
+        (Symbol::new(env, events::SESSION_AUTH), public_key.clone()),
+        SessionAuthEvent { public_key, nonce },
+    );
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// ============================================================================
+// TREASURY SHARES
+//
+// Business wallets co-owned by several people record each partner's share of
+// the treasury. Shares are bookkeeping, not a token: they can't be
+// transferred, only replaced as a whole by the owner. `distribute` pays an
+// amount out pro-rata to every holder in one call.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol, Vec};
+
+use crate::history::record_op;
+use crate::reserve::ReserveGuard;
+use crate::privacy::publish_transfer;
+use crate::*;
+
+/// Holders paid by one `distribute` call; bounded so the batch of transfers
+/// stays within a transaction's footprint.
+pub const MAX_SHARE_HOLDERS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareHolder {
+    pub holder: Address,
+    pub shares: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SharesKey {
+    ShareHolders,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SharesSetEvent {
+    pub holders: u32,
+    pub total_shares: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DistributionEvent {
+    pub asset: Address,
+    pub amount: i128,
+    /// Rounding remainder left in the wallet
+    pub remainder: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Replace the share records. An empty list removes them.
+    pub fn set_shares(env: Env, holders: Vec<ShareHolder>, signature: BytesN<64>) -> Result<(), Error> {
+        if holders.len() > MAX_SHARE_HOLDERS {
+            return Err(Error::LimitExceeded);
+        }
+        for (i, entry) in holders.iter().enumerate() {
+            if entry.shares == 0 {
+                return Err(Error::InvalidAmount);
+            }
+            if holders.iter().skip(i + 1).any(|other| other.holder == entry.holder) {
+                return Err(Error::AlreadyExists);
+            }
+        }
+
+        let payload = holders.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_shares", payload, signature)?;
+
+        env.storage().instance().set(&SharesKey::ShareHolders, &holders);
+
+        env.events().publish(
+            (Symbol::new(&env, events::SHARES_SET),),
+            SharesSetEvent {
+                holders: holders.len(),
+                total_shares: Self::total_shares(env.clone()),
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_shares(env: Env) -> Vec<ShareHolder> {
+        env.storage()
+            .instance()
+            .get(&SharesKey::ShareHolders)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Shares recorded for `holder` (0 if none)
+    pub fn share_balance(env: Env, holder: Address) -> u32 {
+        Self::get_shares(env)
+            .iter()
+            .find(|entry| entry.holder == holder)
+            .map_or(0, |entry| entry.shares)
+    }
+
+    pub fn total_shares(env: Env) -> u64 {
+        Self::get_shares(env)
+            .iter()
+            .map(|entry| entry.shares as u64)
+            .sum()
+    }
+
+    /// Pay `amount` of `asset` out to the holders in proportion to their
+    /// shares. Amounts round down; the remainder stays in the wallet. More
+    /// than the large-transfer threshold has to go through the queue.
+    pub fn distribute(env: Env, asset: Address, amount: i128, signature: BytesN<64>) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let holders = Self::get_shares(env.clone());
+        if holders.is_empty() {
+            return Err(Error::NotFound);
+        }
+
+        large_transfers::check_transfer(&env, &asset, amount)?;
+        for entry in holders.iter() {
+            contacts::check_destination(&env, &entry.holder)?;
+        }
+        fiat_limits::check_transfer(&env, &asset, amount)?;
+
+        let payload = (asset.clone(), amount).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "distribute", payload, signature)?;
+
+        let reserve = ReserveGuard::new(&env);
+        let total = Self::total_shares(env.clone()) as i128;
+        let wallet = env.current_contract_address();
+        let token = token::Client::new(&env, &asset);
+        let mut paid = 0i128;
+        for entry in holders.iter() {
+            let part = amount
+                .checked_mul(entry.shares as i128)
+                .ok_or(Error::InvalidAmount)?
+                / total;
+            if part > 0 {
+                token.transfer(&wallet, &entry.holder, &part);
+                paid += part;
+            }
+        }
+
+        record_op(&env, OpKind::Distribution, &asset, -paid, None);
+        reserve.check(&env)?;
+
+        publish_transfer(
+            &env,
+            (Symbol::new(&env, events::DISTRIBUTION), asset.clone()),
+            DistributionEvent {
+                asset: asset.clone(),
+                amount,
+                remainder: amount - paid,
+            },
+            None,
+            &asset,
+            amount,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+// ============================================================================
+// SIGNATURE CHECKS
+//
+// `ed25519_verify` and `secp256r1_verify` don't return a result: the host
+// traps on a bad signature, and callers see `Error(Crypto, InvalidInput)`
+// instead of one of our error codes. Every auth path goes through the
+// helpers below, which first reject what the host would reject without
+// doing any curve arithmetic (non-canonical or high-s scalars, small-order
+// points) as `Error::InvalidSignature`. That covers truncated, zeroed and
+// garbage signatures. A well-formed signature that simply doesn't match the
+// message still traps in the host, since Soroban has no non-trapping
+// verifier.
+//
+// Both helpers also refuse keys quarantined by `report_compromise`, so a
+// reported key is cut off in every role it signs in.
+// ============================================================================
+
+use soroban_sdk::{crypto::Hash, Bytes, BytesN, Env};
+
+use crate::compromise::ensure_key_trusted;
+use crate::Error;
+
+/// Ed25519 group order L, little-endian
+const ED25519_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+/// Encodings of the identity and the order-4 point with y = 0, the only
+/// small-order points a zeroed or default-initialized key decodes to
+const ED25519_IDENTITY: [u8; 32] = {
+    let mut point = [0u8; 32];
+    point[0] = 1;
+    point
+};
+const ED25519_ZERO_Y: [u8; 32] = [0u8; 32];
+
+/// P-256 group order n, big-endian
+const P256_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+/// n / 2, the largest `s` the host accepts
+const P256_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xde, 0x73, 0x7d, 0x56, 0xd3, 0x8b, 0xcf, 0x42, 0x79, 0xdc, 0xe5, 0x61, 0x7e, 0x31, 0x92, 0xa8,
+];
+
+/// Verify an ed25519 signature, with malformed input reported as
+/// `InvalidSignature`
+pub(crate) fn verify_ed25519(
+    env: &Env,
+    public_key: &BytesN<32>,
+    message: &Bytes,
+    signature: &BytesN<64>,
+) -> Result<(), Error> {
+    let key = public_key.to_array();
+    let signature_bytes = signature.to_array();
+    let (r, s) = signature_bytes.split_at(32);
+
+    let small_order = |point: &[u8]| point == ED25519_IDENTITY || point == ED25519_ZERO_Y;
+    if small_order(&key) || small_order(r) {
+        return Err(Error::InvalidSignature);
+    }
+    ensure_key_trusted(env, &Bytes::from_array(env, &key))?;
+    // S is little-endian and must be reduced mod L
+    if !s.iter().rev().lt(ED25519_ORDER.iter().rev()) {
+        return Err(Error::InvalidSignature);
+    }
+
+    env.crypto().ed25519_verify(public_key, message, signature);
+    Ok(())
+}
+
+/// Verify a secp256r1 signature over a prehashed digest, with malformed
+/// input reported as `InvalidSignature`
+pub(crate) fn verify_secp256r1(
+    env: &Env,
+    public_key: &BytesN<65>,
+    digest: &Hash<32>,
+    signature: &BytesN<64>,
+) -> Result<(), Error> {
+    if public_key.get(0) != Some(0x04) {
+        return Err(Error::InvalidSignature);
+    }
+    let signature_bytes = signature.to_array();
+    let (r, s) = signature_bytes.split_at(32);
+
+    let in_range = |scalar: &[u8]| scalar.iter().any(|&b| b != 0) && scalar < &P256_ORDER[..];
+    if !in_range(r) || !in_range(s) || s > &P256_HALF_ORDER[..] {
+        return Err(Error::InvalidSignature);
+    }
+    ensure_key_trusted(env, &Bytes::from_array(env, &public_key.to_array()))?;
+
+    env.crypto().secp256r1_verify(public_key, digest, signature);
+    Ok(())
+}
@@ -0,0 +1,301 @@
+// ============================================================================
+// WEIGHTED SIGNERS
+//
+// Optional multi-signer mode. The owner registers ed25519 keys and passkeys
+// with weights and sets a threshold; while the threshold is non-zero,
+// `__check_auth` only passes when the signatures it receives come from
+// distinct registered signers whose weights add up to the threshold. The
+// owner key and the passkey are ordinary members of the set in this mode:
+// their single-signature forms count with whatever weight they were given.
+//
+// Managing the set stays gated by the owner signature, like every other
+// configuration entrypoint. Spending doesn't: the entrypoints that move funds
+// or hand out spending power on the owner's signature alone (batches, meta
+// transactions, sessions, allowances, subscriptions, `invoke` and the like)
+// are refused in this mode, so payments go through `__check_auth`.
+//
+// A signer can also be a contract `Address` (another Accesly wallet, an
+// organisation's multisig). It has no key to sign with, so its proof is
+// `require_auth_for_args(message)` on that address, which runs its own
+// `__check_auth`. Given the whole threshold, such a signer is the wallet's
+// effective owner for payments: an org account controlled by a smart
+// account.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Map, Symbol, Vec};
+
+use crate::approvals::record_signer_added;
+use crate::signature::verify_ed25519;
+use crate::webauthn::{passkey, verify_webauthn};
+use crate::*;
+
+pub const MAX_SIGNERS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Signer {
+    Ed25519(BytesN<32>),
+    /// Uncompressed SEC1 P-256 public key
+    Secp256r1(BytesN<65>),
+    /// An account or contract, authenticated with `require_auth`
+    Address(Address),
+}
+
+/// One signer's signature inside `AuthSignature::Multisig`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignerProof {
+    Ed25519(BytesN<32>, BytesN<64>),
+    Secp256r1(BytesN<65>, WebAuthnSignature),
+    /// Authorized by the address itself, in the same transaction
+    Address(Address),
+}
+
+impl SignerProof {
+    pub(crate) fn signer(&self) -> Signer {
+        match self {
+            SignerProof::Ed25519(key, _) => Signer::Ed25519(key.clone()),
+            SignerProof::Secp256r1(key, _) => Signer::Secp256r1(key.clone()),
+            SignerProof::Address(address) => Signer::Address(address.clone()),
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SignersKey {
+    /// Map<Signer, u32> of weights
+    Signers,
+    Threshold,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignerChangedEvent {
+    pub signer: Signer,
+    /// 0 when the signer was removed
+    pub weight: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ThresholdChangedEvent {
+    pub threshold: u32,
+}
+
+#[contractimpl]
+impl WalletContract {
+    pub fn add_signer(
+        env: Env,
+        signer: Signer,
+        weight: u32,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if weight == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        match &signer {
+            Signer::Secp256r1(key) if key.get(0) != Some(0x04) => {
+                return Err(Error::InvalidOwner);
+            }
+            // Its own authorization would run this wallet's `__check_auth`
+            // from inside itself
+            Signer::Address(address) if *address == env.current_contract_address() => {
+                return Err(Error::InvalidOwner);
+            }
+            _ => {}
+        }
+
+        let mut signers = Self::get_signers(env.clone());
+        if signers.contains_key(signer.clone()) {
+            return Err(Error::AlreadyExists);
+        }
+        if signers.len() >= MAX_SIGNERS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (signer.clone(), weight).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "add_signer", payload, signature)?;
+
+        signers.set(signer.clone(), weight);
+        save_signers(&env, &signers)?;
+        record_signer_added(&env, &signer);
+        publish_signer_changed(&env, signer, weight);
+
+        Ok(())
+    }
+
+    /// Remove a signer. Fails if the rest can no longer reach the threshold.
+    pub fn remove_signer(env: Env, signer: Signer, signature: BytesN<64>) -> Result<(), Error> {
+        let mut signers = Self::get_signers(env.clone());
+        if !signers.contains_key(signer.clone()) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = signer.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "remove_signer", payload, signature)?;
+
+        signers.remove(signer.clone());
+        save_signers(&env, &signers)?;
+        publish_signer_changed(&env, signer, 0);
+
+        Ok(())
+    }
+
+    pub fn update_signer_weight(
+        env: Env,
+        signer: Signer,
+        weight: u32,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if weight == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let mut signers = Self::get_signers(env.clone());
+        if !signers.contains_key(signer.clone()) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = (signer.clone(), weight).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "update_signer_weight", payload, signature)?;
+
+        signers.set(signer.clone(), weight);
+        save_signers(&env, &signers)?;
+        publish_signer_changed(&env, signer, weight);
+
+        Ok(())
+    }
+
+    /// Set the weight `__check_auth` requires. 0 turns multi-signer mode
+    /// off and restores single owner / passkey signatures.
+    pub fn set_threshold(env: Env, threshold: u32, signature: BytesN<64>) -> Result<(), Error> {
+        ensure_reachable(&Self::get_signers(env.clone()), threshold)?;
+
+        let payload = threshold.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_threshold", payload, signature)?;
+
+        env.storage()
+            .instance()
+            .set(&SignersKey::Threshold, &threshold);
+        env.events().publish(
+            (Symbol::new(&env, events::THRESHOLD_CHANGED),),
+            ThresholdChangedEvent { threshold },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_signers(env: Env) -> Map<Signer, u32> {
+        env.storage()
+            .instance()
+            .get(&SignersKey::Signers)
+            .unwrap_or(Map::new(&env))
+    }
+
+    pub fn get_threshold(env: Env) -> u32 {
+        threshold(&env)
+    }
+}
+
+pub(crate) fn threshold(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&SignersKey::Threshold)
+        .unwrap_or(0)
+}
+
+/// Fail with `Unauthorized` in multi-signer mode, where the owner key alone
+/// can't spend
+pub(crate) fn ensure_single_signer(env: &Env) -> Result<(), Error> {
+    if threshold(env) > 0 {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Store the signer set after checking it can still reach the threshold
+fn save_signers(env: &Env, signers: &Map<Signer, u32>) -> Result<(), Error> {
+    ensure_reachable(signers, threshold(env))?;
+    env.storage().instance().set(&SignersKey::Signers, signers);
+    Ok(())
+}
+
+/// Refuse configurations that would lock `__check_auth`
+pub(crate) fn ensure_reachable(signers: &Map<Signer, u32>, threshold: u32) -> Result<(), Error> {
+    let total: u64 = signers.values().iter().map(u64::from).sum();
+    if total < threshold as u64 {
+        return Err(Error::LimitExceeded);
+    }
+    Ok(())
+}
+
+fn publish_signer_changed(env: &Env, signer: Signer, weight: u32) {
+    env.events().publish(
+        (Symbol::new(env, events::SIGNER_CHANGED),),
+        SignerChangedEvent { signer, weight },
+    );
+}
+
+/// Check one signer's signature over `message`
+pub(crate) fn verify_proof(env: &Env, proof: &SignerProof, message: &Bytes) -> Result<(), Error> {
+    match proof {
+        SignerProof::Ed25519(key, signature) => verify_ed25519(env, key, message, signature),
+        SignerProof::Secp256r1(key, assertion) => verify_webauthn(env, key, message, assertion),
+        SignerProof::Address(address) => {
+            address.require_auth_for_args(Vec::from_array(env, [message.to_val()]));
+            Ok(())
+        }
+    }
+}
+
+/// Check `signature` over `message` against the weighted signer set
+pub(crate) fn verify_weighted(
+    env: &Env,
+    owner: &BytesN<32>,
+    message: &Bytes,
+    signature: AuthSignature,
+) -> Result<(), Error> {
+    let threshold = threshold(env);
+    if threshold == 0 {
+        return Err(Error::Unauthorized);
+    }
+
+    let proofs = match signature {
+        AuthSignature::Ed25519(signature) => {
+            Vec::from_array(env, [SignerProof::Ed25519(owner.clone(), signature)])
+        }
+        AuthSignature::Secp256r1(assertion) => {
+            Vec::from_array(env, [SignerProof::Secp256r1(passkey(env)?, assertion)])
+        }
+        AuthSignature::Multisig(proofs) => proofs,
+        AuthSignature::Session(..)
+        | AuthSignature::Device(..)
+        | AuthSignature::Channel(..)
+        | AuthSignature::Expiring(..)
+        | AuthSignature::Relayed(..)
+        | AuthSignature::PreAuthorized => return Err(Error::Unauthorized),
+    };
+    if proofs.len() > MAX_SIGNERS {
+        return Err(Error::LimitExceeded);
+    }
+
+    let signers = WalletContract::get_signers(env.clone());
+    let mut seen: Vec<Signer> = Vec::new(env);
+    let mut total: u64 = 0;
+    for proof in proofs.iter() {
+        let signer = proof.signer();
+        if seen.contains(&signer) {
+            return Err(Error::InvalidSignature);
+        }
+        let weight = signers.get(signer.clone()).ok_or(Error::Unauthorized)?;
+        verify_proof(env, &proof, message)?;
+
+        seen.push_back(signer);
+        total += weight as u64;
+    }
+
+    if total < threshold as u64 {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+// ============================================================================
+// BALANCE SNAPSHOTS
+//
+// Proof-of-reserve style attestations. The owner registers the assets to
+// track, and once per epoch (about a day of ledgers) the relayer commits a
+// snapshot: the wallet's balance of each asset read from its token contract,
+// hashed together with the epoch and ledger. Only the hash is stored; the
+// balances go out in the snapshot event, and whoever keeps them can later
+// show an auditor what the wallet held at that ledger by matching them
+// against the committed hash.
+//
+// The relayer is the receipts committer, see `set_receipts_committer`. Each
+// commit tops the instance up like `__check_auth` does, so a wallet that is
+// only snapshotted stays live between the relayer's `bump` sweeps.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol, Vec,
+};
+
+use crate::receipts::receipts_committer;
+use crate::*;
+
+pub const MAX_SNAPSHOT_ASSETS: u32 = 10;
+/// Ledgers per snapshot epoch (~1 day at 5s ledgers)
+pub const SNAPSHOT_EPOCH_LEDGERS: u32 = 17_280;
+
+/// One asset's balance at the snapshot ledger
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetBalance {
+    pub asset: Address,
+    pub balance: i128,
+}
+
+/// What the hash commits to: sha256 of its XDR
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceSnapshot {
+    pub epoch: u32,
+    pub ledger: u32,
+    pub balances: Vec<AssetBalance>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SnapshotCommitment {
+    pub hash: BytesN<32>,
+    /// Ledger the balances were read in
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SnapshotKey {
+    SnapshotAssets,
+    LatestSnapshotEpoch,
+    Snapshot(u32),
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Set the assets balance snapshots cover
+    pub fn set_snapshot_assets(
+        env: Env,
+        assets: Vec<Address>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if assets.len() > MAX_SNAPSHOT_ASSETS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = assets.clone().to_xdr(&env);
+        Self::require_owner_signature(&env, "set_snapshot_assets", payload, signature)?;
+
+        env.storage()
+            .instance()
+            .set(&SnapshotKey::SnapshotAssets, &assets);
+        env.events()
+            .publish((Symbol::new(&env, events::SNAPSHOT_ASSETS),), assets);
+
+        Ok(())
+    }
+
+    pub fn get_snapshot_assets(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&SnapshotKey::SnapshotAssets)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Read the registered assets' balances and commit their hash for the
+    /// current epoch. Called by the relayer, once per epoch.
+    pub fn commit_balance_snapshot(env: Env) -> Result<SnapshotCommitment, Error> {
+        receipts_committer(&env)?.require_auth();
+        ttl::extend_instance(&env);
+
+        let assets = Self::get_snapshot_assets(env.clone());
+        if assets.is_empty() {
+            return Err(Error::NotFound);
+        }
+        let ledger = env.ledger().sequence();
+        let epoch = ledger / SNAPSHOT_EPOCH_LEDGERS;
+        if env
+            .storage()
+            .persistent()
+            .has(&SnapshotKey::Snapshot(epoch))
+        {
+            return Err(Error::AlreadyExists);
+        }
+
+        let wallet = env.current_contract_address();
+        let mut balances = Vec::new(&env);
+        for asset in assets.iter() {
+            let balance = token::Client::new(&env, &asset).balance(&wallet);
+            balances.push_back(AssetBalance { asset, balance });
+        }
+        let snapshot = BalanceSnapshot {
+            epoch,
+            ledger,
+            balances,
+        };
+        let commitment = SnapshotCommitment {
+            hash: env.crypto().sha256(&snapshot.clone().to_xdr(&env)).into(),
+            ledger,
+        };
+
+        env.storage()
+            .persistent()
+            .set(&SnapshotKey::Snapshot(epoch), &commitment);
+        env.storage()
+            .instance()
+            .set(&SnapshotKey::LatestSnapshotEpoch, &epoch);
+        env.events().publish(
+            (Symbol::new(&env, events::BALANCE_SNAPSHOT), epoch),
+            snapshot,
+        );
+
+        Ok(commitment)
+    }
+
+    pub fn get_balance_snapshot(env: Env, epoch: u32) -> Result<SnapshotCommitment, Error> {
+        env.storage()
+            .persistent()
+            .get(&SnapshotKey::Snapshot(epoch))
+            .ok_or(Error::NotFound)
+    }
+
+    pub fn get_latest_snapshot_epoch(env: Env) -> Result<u32, Error> {
+        env.storage()
+            .instance()
+            .get(&SnapshotKey::LatestSnapshotEpoch)
+            .ok_or(Error::NotFound)
+    }
+}
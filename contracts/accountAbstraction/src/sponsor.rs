@@ -0,0 +1,152 @@
+// ============================================================================
+// SPONSORED ACTIVATION
+//
+// The relayer pays the XLM that activates a wallet and keeps its entries
+// alive. It records that spend here so the wallet carries its own debt, and
+// apps that want users to eventually cover their rent can let the owner
+// settle it with `repay_sponsor`, swapping from a held asset (e.g. USDC)
+// through a Soroswap-compatible router when the wallet has no XLM to spare.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol};
+
+use crate::history::record_op;
+use crate::reserve::ReserveGuard;
+use crate::swap::buy_exact;
+use crate::*;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sponsorship {
+    pub sponsor: Address,
+    /// Total XLM (stroops) the sponsor has spent on this wallet
+    pub spent: i128,
+    /// Total XLM (stroops) paid back so far
+    pub repaid: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SponsorKey {
+    Sponsorship,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SponsorshipRecordedEvent {
+    pub sponsor: Address,
+    pub amount: i128,
+    pub spent: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SponsorRepaidEvent {
+    pub sponsor: Address,
+    pub pay_token: Address,
+    pub amount_in: i128,
+    pub xlm_repaid: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Record XLM the sponsor spent activating or maintaining this wallet
+    pub fn record_sponsorship(env: Env, sponsor: Address, amount: i128) -> Result<(), Error> {
+        sponsor.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut sponsorship = match Self::get_sponsorship(env.clone()) {
+            Ok(existing) if existing.sponsor != sponsor => return Err(Error::Unauthorized),
+            Ok(existing) => existing,
+            Err(_) => Sponsorship {
+                sponsor: sponsor.clone(),
+                spent: 0,
+                repaid: 0,
+            },
+        };
+        sponsorship.spent = sponsorship.spent.saturating_add(amount);
+        env.storage()
+            .instance()
+            .set(&SponsorKey::Sponsorship, &sponsorship);
+
+        env.events().publish(
+            (Symbol::new(&env, events::SPONSORSHIP_RECORDED), sponsor.clone()),
+            SponsorshipRecordedEvent {
+                sponsor,
+                amount,
+                spent: sponsorship.spent,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the sponsor and what the wallet owes it
+    pub fn get_sponsorship(env: Env) -> Result<Sponsorship, Error> {
+        env.storage()
+            .instance()
+            .get(&SponsorKey::Sponsorship)
+            .ok_or(Error::NotFound)
+    }
+
+    /// Pay back the outstanding sponsorship in XLM. When `pay_token` isn't
+    /// `native_token`, exactly the outstanding XLM is bought through `router`,
+    /// spending at most `max_in` of `pay_token`.
+    pub fn repay_sponsor(
+        env: Env,
+        router: Address,
+        native_token: Address,
+        pay_token: Address,
+        max_in: i128,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut sponsorship = Self::get_sponsorship(env.clone())?;
+        let outstanding = sponsorship.spent - sponsorship.repaid;
+        if outstanding <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let payload = (router.clone(), native_token.clone(), pay_token.clone(), max_in).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_owner_signature(&env, "repay_sponsor", payload, signature)?;
+
+        let reserve = ReserveGuard::new(&env);
+        let wallet = env.current_contract_address();
+        let amount_in = if pay_token == native_token {
+            outstanding
+        } else {
+            let deadline = env.ledger().timestamp();
+            buy_exact(&env, &router, &pay_token, &native_token, outstanding, max_in, deadline)?
+        };
+
+        token::Client::new(&env, &native_token).transfer(&wallet, &sponsorship.sponsor, &outstanding);
+
+        sponsorship.repaid = sponsorship.spent;
+        env.storage()
+            .instance()
+            .set(&SponsorKey::Sponsorship, &sponsorship);
+        record_op(
+            &env,
+            OpKind::SponsorRepayment,
+            &native_token,
+            -outstanding,
+            Some(sponsorship.sponsor.clone()),
+        );
+        reserve.check(&env)?;
+
+        env.events().publish(
+            (Symbol::new(&env, events::SPONSOR_REPAID), sponsorship.sponsor.clone()),
+            SponsorRepaidEvent {
+                sponsor: sponsorship.sponsor,
+                pay_token,
+                amount_in,
+                xlm_repaid: outstanding,
+            },
+        );
+
+        Ok(())
+    }
+}
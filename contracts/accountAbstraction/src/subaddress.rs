@@ -0,0 +1,169 @@
+// ============================================================================
+// VIRTUAL SUB-ADDRESSES
+//
+// Soroban equivalent of classic muxed accounts: a business wallet hands each
+// payer or invoice its own 64-bit virtual id. Payers pay through
+// `pay_virtual`, so the id is recorded on-chain next to the transfer and
+// several payers to one wallet can be told apart without off-chain memos.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, symbol_short, token, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    String, Symbol, Vec,
+};
+
+use crate::history::record_op;
+use crate::paging::{index_page, index_push};
+use crate::privacy::publish_transfer;
+use crate::*;
+
+const VIRTUAL_INDEX: Symbol = symbol_short!("virtual");
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VirtualAccount {
+    /// Payer or invoice reference the id was derived from
+    pub reference: BytesN<32>,
+    pub label: String,
+    pub payment_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SubaddressKey {
+    VirtualAccount(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VirtualIdRegisteredEvent {
+    pub virtual_id: u64,
+    pub reference: BytesN<32>,
+    pub label: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VirtualPaymentEvent {
+    pub virtual_id: u64,
+    pub from: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Derive the virtual id for a reference: first 8 bytes of
+    /// sha256(wallet_address_xdr || reference). Payers can compute it offline.
+    pub fn derive_virtual_id(env: Env, reference: BytesN<32>) -> u64 {
+        let mut preimage = Bytes::new(&env);
+        preimage.append(&env.current_contract_address().to_xdr(&env));
+        preimage.extend_from_array(&reference.to_array());
+
+        let digest = env.crypto().sha256(&preimage).to_array();
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&digest[..8]);
+        u64::from_be_bytes(id)
+    }
+
+    /// Register a virtual id for a payer/invoice reference and return it
+    pub fn register_virtual_id(
+        env: Env,
+        reference: BytesN<32>,
+        label: String,
+        signature: BytesN<64>,
+    ) -> Result<u64, Error> {
+        let virtual_id = Self::derive_virtual_id(env.clone(), reference.clone());
+        let key = SubaddressKey::VirtualAccount(virtual_id);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = (reference.clone(), label.clone()).to_xdr(&env);
+        Self::require_owner_signature(&env, "register_virtual_id", payload, signature)?;
+
+        let account = VirtualAccount {
+            reference: reference.clone(),
+            label: label.clone(),
+            payment_count: 0,
+        };
+        env.storage().persistent().set(&key, &account);
+        index_push(&env, &VIRTUAL_INDEX, &virtual_id);
+
+        env.events().publish(
+            (Symbol::new(&env, events::VIRTUAL_ID_REGISTERED),),
+            VirtualIdRegisteredEvent {
+                virtual_id,
+                reference,
+                label,
+            },
+        );
+
+        Ok(virtual_id)
+    }
+
+    /// Resolve a virtual id to its reference and label
+    pub fn get_virtual_id(env: Env, virtual_id: u64) -> Result<VirtualAccount, Error> {
+        env.storage()
+            .persistent()
+            .get(&SubaddressKey::VirtualAccount(virtual_id))
+            .ok_or(Error::NotFound)
+    }
+
+    /// List registered virtual ids, one page at a time
+    pub fn list_virtual_ids(env: Env, cursor: u32, limit: u32) -> (Vec<(u64, VirtualAccount)>, Option<u32>) {
+        let (ids, next_cursor) = index_page::<u64>(&env, &VIRTUAL_INDEX, cursor, limit);
+
+        let mut items = Vec::new(&env);
+        for id in ids.iter() {
+            if let Ok(account) = Self::get_virtual_id(env.clone(), id) {
+                items.push_back((id, account));
+            }
+        }
+        (items, next_cursor)
+    }
+
+    /// Pay `amount` of `token` into this wallet, attributed to `virtual_id`
+    pub fn pay_virtual(
+        env: Env,
+        from: Address,
+        virtual_id: u64,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        from.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let key = SubaddressKey::VirtualAccount(virtual_id);
+        let mut account: VirtualAccount = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NotFound)?;
+
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+
+        account.payment_count = account.payment_count.saturating_add(1);
+        env.storage().persistent().set(&key, &account);
+        record_op(&env, OpKind::VirtualPayment, &token, amount, Some(from.clone()));
+
+        publish_transfer(
+            &env,
+            (Symbol::new(&env, events::VIRTUAL_PAYMENT), virtual_id),
+            VirtualPaymentEvent {
+                virtual_id,
+                from: from.clone(),
+                token: token.clone(),
+                amount,
+            },
+            Some(&from),
+            &token,
+            amount,
+        );
+
+        Ok(())
+    }
+}
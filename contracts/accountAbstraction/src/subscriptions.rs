@@ -0,0 +1,262 @@
+// ============================================================================
+// SUBSCRIPTIONS
+//
+// Recurring payments the wallet makes on its own schedule: the owner signs
+// a subscription once (merchant, asset, amount per interval), and from then
+// on anyone, normally the merchant's backend or the relayer, can `collect`
+// each payment as it falls due. The first is due right away.
+//
+// A collection pays one interval's amount and nothing more, whatever the
+// caller asks, so the amount is the cap. Intervals nobody collected are
+// skipped rather than charged in a lump, and a payment the wallet can't
+// cover is skipped too; both publish a missed-payment event so the merchant
+// and the owner's app can follow up. Paused subscriptions don't fall due,
+// and pick up from the resume.
+// ============================================================================
+
+use soroban_sdk::{
+    contractimpl, contracttype, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, Symbol, Vec,
+};
+
+use crate::freeze::ensure_not_frozen;
+use crate::history::record_op;
+use crate::reserve::ReserveGuard;
+use crate::*;
+
+pub const MAX_SUBSCRIPTIONS: u32 = 20;
+/// Shortest interval a subscription can charge at (1 day)
+pub const MIN_SUBSCRIPTION_INTERVAL: u64 = 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Subscription {
+    pub id: u32,
+    pub merchant: Address,
+    pub asset: Address,
+    /// Paid each interval
+    pub amount: i128,
+    /// Seconds between payments
+    pub interval: u64,
+    /// Ledger timestamp the next payment can be collected at
+    pub next_due: u64,
+    pub paused: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SubscriptionKey {
+    /// Map<u32, Subscription> by id
+    Subscriptions,
+    NextSubscriptionId,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionCollectedEvent {
+    pub id: u32,
+    pub merchant: Address,
+    pub asset: Address,
+    pub amount: i128,
+    pub next_due: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionMissedEvent {
+    pub id: u32,
+    pub merchant: Address,
+    /// Intervals that went unpaid
+    pub missed: u64,
+    /// Whether the latest one was missed because the wallet couldn't pay
+    pub insufficient_funds: bool,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Pay `merchant` `amount` of `asset` every `interval` seconds, starting
+    /// now. Owner-signed over (merchant, asset, amount, interval). Returns
+    /// the subscription id.
+    pub fn create_subscription(
+        env: Env,
+        merchant: Address,
+        asset: Address,
+        amount: i128,
+        interval: u64,
+        signature: BytesN<64>,
+    ) -> Result<u32, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if interval < MIN_SUBSCRIPTION_INTERVAL {
+            return Err(Error::LimitExceeded);
+        }
+        let mut subscriptions = subscriptions(&env);
+        if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (merchant.clone(), asset.clone(), amount, interval).to_xdr(&env);
+        signers::ensure_single_signer(&env)?;
+        Self::require_high_risk_signature(&env, "create_subscription", payload, signature)?;
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&SubscriptionKey::NextSubscriptionId)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&SubscriptionKey::NextSubscriptionId, &(id + 1));
+        let subscription = Subscription {
+            id,
+            merchant,
+            asset,
+            amount,
+            interval,
+            next_due: now(&env),
+            paused: false,
+        };
+        subscriptions.set(id, subscription.clone());
+        save_subscriptions(&env, &subscriptions);
+        env.events().publish(
+            (Symbol::new(&env, events::SUBSCRIPTION_CREATED), id),
+            subscription,
+        );
+
+        Ok(id)
+    }
+
+    /// Pause or resume a subscription. Owner-signed over (id, paused).
+    pub fn set_subscription_paused(
+        env: Env,
+        id: u32,
+        paused: bool,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let mut subscriptions = subscriptions(&env);
+        let mut subscription = subscriptions.get(id).ok_or(Error::NotFound)?;
+
+        let payload = (id, paused).to_xdr(&env);
+        Self::require_owner_signature(&env, "set_subscription_paused", payload, signature)?;
+
+        // Time spent paused doesn't count as missed
+        if subscription.paused && !paused {
+            subscription.next_due = subscription.next_due.max(now(&env));
+        }
+        subscription.paused = paused;
+        subscriptions.set(id, subscription);
+        save_subscriptions(&env, &subscriptions);
+        env.events()
+            .publish((Symbol::new(&env, events::SUBSCRIPTION_PAUSED), id), paused);
+
+        Ok(())
+    }
+
+    /// End a subscription. Owner-signed over the id.
+    pub fn cancel_subscription(env: Env, id: u32, signature: BytesN<64>) -> Result<(), Error> {
+        let mut subscriptions = subscriptions(&env);
+        if !subscriptions.contains_key(id) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &id.to_be_bytes());
+        Self::require_owner_signature(&env, "cancel_subscription", payload, signature)?;
+
+        subscriptions.remove(id);
+        save_subscriptions(&env, &subscriptions);
+        env.events()
+            .publish((Symbol::new(&env, events::SUBSCRIPTION_CANCELLED), id), ());
+
+        Ok(())
+    }
+
+    /// Pay the subscription's amount for the current interval, once it is
+    /// due. Anyone can call. Returns whether it was paid; `false` when the
+    /// wallet couldn't cover it and the interval was skipped.
+    pub fn collect(env: Env, id: u32) -> Result<bool, Error> {
+        ensure_not_frozen(&env)?;
+        let mut subscriptions = subscriptions(&env);
+        let mut subscription = subscriptions.get(id).ok_or(Error::NotFound)?;
+        if subscription.paused {
+            return Err(Error::Unauthorized);
+        }
+        let now = now(&env);
+        if now < subscription.next_due {
+            return Err(Error::Timelocked);
+        }
+        large_transfers::check_transfer(&env, &subscription.asset, subscription.amount)?;
+        contacts::check_destination(&env, &subscription.merchant)?;
+
+        // Only the interval `now` falls in is paid
+        let skipped = (now - subscription.next_due) / subscription.interval;
+        subscription.next_due = subscription
+            .next_due
+            .saturating_add((skipped + 1).saturating_mul(subscription.interval));
+        subscriptions.set(id, subscription.clone());
+        save_subscriptions(&env, &subscriptions);
+
+        let wallet = env.current_contract_address();
+        let asset = token::Client::new(&env, &subscription.asset);
+        let funded = asset.balance(&wallet) >= subscription.amount;
+        if skipped > 0 || !funded {
+            env.events().publish(
+                (Symbol::new(&env, events::SUBSCRIPTION_MISSED), id),
+                SubscriptionMissedEvent {
+                    id,
+                    merchant: subscription.merchant.clone(),
+                    missed: skipped + u64::from(!funded),
+                    insufficient_funds: !funded,
+                },
+            );
+        }
+        if !funded {
+            return Ok(false);
+        }
+        fiat_limits::check_transfer(&env, &subscription.asset, subscription.amount)?;
+
+        let reserve = ReserveGuard::new(&env);
+        asset.transfer(&wallet, &subscription.merchant, &subscription.amount);
+        reserve.check(&env)?;
+
+        record_op(
+            &env,
+            OpKind::Subscription,
+            &subscription.asset,
+            -subscription.amount,
+            Some(subscription.merchant.clone()),
+        );
+        env.events().publish(
+            (Symbol::new(&env, events::SUBSCRIPTION_COLLECTED), id),
+            SubscriptionCollectedEvent {
+                id,
+                merchant: subscription.merchant,
+                asset: subscription.asset,
+                amount: subscription.amount,
+                next_due: subscription.next_due,
+            },
+        );
+
+        Ok(true)
+    }
+
+    pub fn get_subscription(env: Env, id: u32) -> Option<Subscription> {
+        subscriptions(&env).get(id)
+    }
+
+    pub fn list_subscriptions(env: Env) -> Vec<Subscription> {
+        subscriptions(&env).values()
+    }
+}
+
+fn subscriptions(env: &Env) -> Map<u32, Subscription> {
+    env.storage()
+        .instance()
+        .get(&SubscriptionKey::Subscriptions)
+        .unwrap_or(Map::new(env))
+}
+
+fn save_subscriptions(env: &Env, subscriptions: &Map<u32, Subscription>) {
+    env.storage()
+        .instance()
+        .set(&SubscriptionKey::Subscriptions, subscriptions);
+}
@@ -0,0 +1,98 @@
+// ============================================================================
+// SUPPORT READ ACCESS
+//
+// When troubleshooting, a user can hand Accesly support a read token instead
+// of screen-sharing their settings. The token is a random secret kept
+// off-chain; the wallet only holds its hash, with an expiry. The support
+// backend hashes the token it was given and shows the wallet configuration
+// only while that hash has an unexpired grant here (see
+// `accesly-client::support`). Grants confer no authority on-chain; revoking
+// one removes it, which cuts access off on the backend's next check.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol};
+
+use crate::*;
+
+/// Longest read access can be granted for (7 days)
+pub const MAX_READ_GRANT_DURATION: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReadGrant {
+    pub granted_at: u64,
+    /// Ledger timestamp after which the token is refused
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SupportKey {
+    ReadGrant(BytesN<32>),
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Grant read access to whoever holds the token hashing to `token_hash`
+    pub fn grant_read_access(
+        env: Env,
+        token_hash: BytesN<32>,
+        expires_at: u64,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let now = now(&env);
+        if expires_at <= now {
+            return Err(Error::Expired);
+        }
+        if expires_at - now > MAX_READ_GRANT_DURATION {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = (token_hash.clone(), expires_at).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "grant_read_access", payload, signature)?;
+
+        let grant = ReadGrant {
+            granted_at: now,
+            expires_at,
+        };
+        env.storage()
+            .persistent()
+            .set(&SupportKey::ReadGrant(token_hash.clone()), &grant);
+        env.events().publish(
+            (Symbol::new(&env, events::READ_ACCESS_GRANTED), token_hash),
+            grant,
+        );
+
+        Ok(())
+    }
+
+    pub fn revoke_read_access(
+        env: Env,
+        token_hash: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let key = SupportKey::ReadGrant(token_hash.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::NotFound);
+        }
+
+        let payload = Bytes::from_array(&env, &token_hash.to_array());
+        Self::require_owner_signature(&env, "revoke_read_access", payload, signature)?;
+
+        env.storage().persistent().remove(&key);
+        env.events().publish(
+            (Symbol::new(&env, events::READ_ACCESS_REVOKED), token_hash),
+            (),
+        );
+
+        Ok(())
+    }
+
+    /// The grant for `token_hash`, if it exists and hasn't expired
+    pub fn get_read_grant(env: Env, token_hash: BytesN<32>) -> Option<ReadGrant> {
+        env.storage()
+            .persistent()
+            .get(&SupportKey::ReadGrant(token_hash))
+            .filter(|grant: &ReadGrant| now(&env) <= grant.expires_at)
+    }
+}
@@ -0,0 +1,133 @@
+// ============================================================================
+// SWAP ROUTER
+//
+// Client for a Soroswap-compatible router, the exact-output swap the wallet
+// uses whenever it has to pay in an asset it doesn't hold, and the
+// exact-input swap behind auto-conversion.
+// ============================================================================
+
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contractclient, vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+use crate::policy::check_invocable;
+use crate::Error;
+
+/// Subset of the Soroswap router interface
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    fn router_pair_for(env: Env, token_a: Address, token_b: Address) -> Address;
+    fn router_get_amounts_in(env: Env, amount_out: i128, path: Vec<Address>) -> Vec<i128>;
+    fn router_get_amounts_out(env: Env, amount_in: i128, path: Vec<Address>) -> Vec<i128>;
+    fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128>;
+    fn swap_tokens_for_exact_tokens(
+        env: Env,
+        amount_out: i128,
+        amount_in_max: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128>;
+}
+
+/// Swap `pay_token` into exactly `amount_out` of `out_token`, delivered to
+/// the wallet, and return how much `pay_token` it cost (at most `max_in`)
+pub(crate) fn buy_exact(
+    env: &Env,
+    router: &Address,
+    pay_token: &Address,
+    out_token: &Address,
+    amount_out: i128,
+    max_in: i128,
+    deadline: u64,
+) -> Result<i128, Error> {
+    let router_client = SwapRouterClient::new(env, router);
+    let path = vec![env, pay_token.clone(), out_token.clone()];
+
+    let amount_in = router_client
+        .router_get_amounts_in(&amount_out, &path)
+        .first()
+        .ok_or(Error::InvalidAmount)?;
+    if amount_in <= 0 || amount_in > max_in {
+        return Err(Error::InvalidAmount);
+    }
+
+    let wallet = env.current_contract_address();
+    authorize_pair_transfer(
+        env,
+        &router_client,
+        "swap_tokens_for_exact_tokens",
+        pay_token,
+        out_token,
+        amount_in,
+    )?;
+    router_client.swap_tokens_for_exact_tokens(&amount_out, &max_in, &path, &wallet, &deadline);
+
+    Ok(amount_in)
+}
+
+/// Swap exactly `amount_in` of `pay_token` into `out_token`, delivered to
+/// the wallet, asking the router for at least `min_out`. What the router
+/// reports back isn't trusted; callers measure what arrived.
+pub(crate) fn sell_exact(
+    env: &Env,
+    router: &Address,
+    pay_token: &Address,
+    out_token: &Address,
+    amount_in: i128,
+    min_out: i128,
+    deadline: u64,
+) -> Result<(), Error> {
+    let router_client = SwapRouterClient::new(env, router);
+    let path = vec![env, pay_token.clone(), out_token.clone()];
+
+    let wallet = env.current_contract_address();
+    authorize_pair_transfer(
+        env,
+        &router_client,
+        "swap_exact_tokens_for_tokens",
+        pay_token,
+        out_token,
+        amount_in,
+    )?;
+    router_client.swap_exact_tokens_for_tokens(&amount_in, &min_out, &path, &wallet, &deadline);
+    Ok(())
+}
+
+/// The router moves `pay_token` from the wallet into the pair; that nested
+/// transfer needs the wallet's authorization for this exact amount. The
+/// router and the pair are held to the contract policy first, like any
+/// contract the wallet calls.
+fn authorize_pair_transfer(
+    env: &Env,
+    router_client: &SwapRouterClient,
+    swap_fn: &str,
+    pay_token: &Address,
+    out_token: &Address,
+    amount_in: i128,
+) -> Result<(), Error> {
+    let wallet = env.current_contract_address();
+    let pair = router_client.router_pair_for(pay_token, out_token);
+    check_invocable(env, &router_client.address, &Symbol::new(env, swap_fn))?;
+    check_invocable(env, &pair, &Symbol::new(env, "swap"))?;
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: pay_token.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (wallet, pair, amount_in).into_val(env),
+            },
+            sub_invocations: Vec::new(env),
+        }),
+    ]);
+    Ok(())
+}
@@ -1,7 +1,14 @@
 // src/test.rs
 
+extern crate std;
+
 use super::*;
-use soroban_sdk::{testutils::Events, Address, Env, BytesN};
+use acceslyinterface::{Changelog, VersionInfo};
+use ed25519_dalek::{Signer as _, SigningKey};
+use soroban_sdk::{
+    testutils::Address as _, testutils::Events, testutils::Ledger, token, vec, xdr::ToXdr, Address, Env, BytesN, Map, String,
+    Vec,
+};
 
 // ============================================================================
 // HELPER FUNCTIONS
@@ -15,6 +22,38 @@ fn create_contract(env: &Env) -> Address {
     env.register(WalletContract, ())
 }
 
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn public_key(env: &Env, key: &SigningKey) -> BytesN<32> {
+    BytesN::from_array(env, &key.verifying_key().to_bytes())
+}
+
+fn sign_raw(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+    let mut buf = [0u8; 4096];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut buf[..len]);
+    BytesN::from_array(env, &key.sign(&buf[..len]).to_bytes())
+}
+
+/// Sign `action || payload || nonce` the way `require_owner_signature` expects
+fn sign_action(env: &Env, key: &SigningKey, action: &str, payload: &Bytes, nonce: u64) -> BytesN<64> {
+    let mut message = Bytes::new(env);
+    message.extend_from_slice(action.as_bytes());
+    message.append(payload);
+    message.extend_from_array(&nonce.to_be_bytes());
+    sign_raw(env, key, &message)
+}
+
+/// Register and initialize a wallet owned by `key`
+fn setup_wallet<'a>(env: &'a Env, key: &SigningKey) -> WalletContractClient<'a> {
+    let contract_id = create_contract(env);
+    let client = WalletContractClient::new(env, &contract_id);
+    client.init(&public_key(env, key), &BytesN::from_array(env, &[2u8; 32]));
+    client
+}
+
 // ============================================================================
 // INITIALIZATION TESTS
 // ============================================================================
@@ -98,6 +137,37 @@ fn test_init_emits_event() {
     assert!(events.len() > 0);
 }
 
+#[test]
+fn test_init_with_preset_applies_it() {
+    use acceslyinterface::presets::{BUSINESS, HIGH_SECURITY, STARTER};
+
+    let env = create_test_env();
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    for preset in [STARTER, BUSINESS, HIGH_SECURITY] {
+        let client = WalletContractClient::new(&env, &create_contract(&env));
+        client.init_with_preset(&owner, &email_hash, &preset);
+
+        assert_eq!(client.get_owner(), owner);
+        assert_eq!(
+            client.get_velocity_limit(),
+            Some(VelocityLimit { max_ops: preset.velocity_max_ops, window: preset.velocity_window })
+        );
+        assert_eq!(client.get_rotation_delay(), preset.rotation_delay);
+        assert_eq!(client.get_new_key_window(), preset.new_key_window);
+        assert_eq!(client.get_batch_limits().max_ops, preset.max_batch_ops);
+        assert_eq!(client.is_contacts_only(), preset.contacts_only);
+    }
+
+    // Held to the setters' bounds
+    let client = WalletContractClient::new(&env, &create_contract(&env));
+    let too_slow = PolicyPreset { rotation_delay: MAX_ROTATION_DELAY + 1, ..STARTER };
+    assert_eq!(client.try_init_with_preset(&owner, &email_hash, &too_slow), Err(Ok(Error::LimitExceeded)));
+    let no_batches = PolicyPreset { max_batch_ops: 0, ..STARTER };
+    assert_eq!(client.try_init_with_preset(&owner, &email_hash, &no_batches), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(client.try_get_owner(), Err(Ok(Error::NotInitialized)));
+}
+
 // ============================================================================
 // GETTER TESTS
 // ============================================================================
@@ -280,6 +350,5203 @@ fn test_update_owner_not_initialized() {
     client.update_owner(&new_owner, &signature);
 }
 
+#[test]
+fn test_update_owner_success() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let new_owner = BytesN::from_array(&env, &[5u8; 32]);
+    let payload = Bytes::from_array(&env, &new_owner.to_array());
+    let signature = sign_action(&env, &key, "update_owner", &payload, 0);
+
+    client.update_owner(&new_owner, &signature);
+
+    assert_eq!(client.get_owner(), new_owner);
+    assert_eq!(client.get_nonce(), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_update_owner_wrong_signer() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let new_owner = BytesN::from_array(&env, &[5u8; 32]);
+    let payload = Bytes::from_array(&env, &new_owner.to_array());
+    let signature = sign_action(&env, &signing_key(9), "update_owner", &payload, 0);
+
+    client.update_owner(&new_owner, &signature);
+}
+
+// ============================================================================
+// DEPOSIT BINDING TESTS
+// ============================================================================
+
+fn set_binding(env: &Env, client: &WalletContractClient, key: &SigningKey, anchor: &BytesN<32>, route: &Address) {
+    let memo_hash = BytesN::from_array(env, &[8u8; 32]);
+    let label = String::from_str(env, "Etherfuse MXN");
+    let payload = (anchor.clone(), memo_hash.clone(), route.clone(), label.clone()).to_xdr(env);
+    let signature = sign_action(env, key, "set_deposit_binding", &payload, client.get_nonce());
+    client.set_deposit_binding(anchor, &memo_hash, route, &label, &signature);
+}
+
+#[test]
+fn test_set_deposit_binding_and_verify() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let anchor = BytesN::from_array(&env, &[4u8; 32]);
+    let route = Address::generate(&env);
+    set_binding(&env, &client, &key, &anchor, &route);
+
+    let binding = client.get_deposit_binding(&anchor);
+    assert_eq!(binding.route, route);
+    assert!(client.verify_deposit(&anchor, &BytesN::from_array(&env, &[8u8; 32]), &route));
+    assert!(!client.verify_deposit(&anchor, &BytesN::from_array(&env, &[9u8; 32]), &route));
+    assert!(!client.verify_deposit(&anchor, &binding.memo_hash, &Address::generate(&env)));
+}
+
+#[test]
+fn test_remove_deposit_binding() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let anchor = BytesN::from_array(&env, &[4u8; 32]);
+    let route = Address::generate(&env);
+    set_binding(&env, &client, &key, &anchor, &route);
+
+    let payload = anchor.clone().to_xdr(&env);
+    let signature = sign_action(&env, &key, "remove_deposit_binding", &payload, client.get_nonce());
+    client.remove_deposit_binding(&anchor, &signature);
+
+    assert_eq!(client.try_get_deposit_binding(&anchor), Err(Ok(Error::NotFound)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_remove_deposit_binding_not_found() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let anchor = BytesN::from_array(&env, &[4u8; 32]);
+    let signature = BytesN::from_array(&env, &[3u8; 64]);
+    client.remove_deposit_binding(&anchor, &signature);
+}
+
+// ============================================================================
+// VIRTUAL SUB-ADDRESS TESTS
+// ============================================================================
+
+fn register_virtual(env: &Env, client: &WalletContractClient, key: &SigningKey, seed: u8) -> u64 {
+    let reference = BytesN::from_array(env, &[seed; 32]);
+    let label = String::from_str(env, "Invoice 42");
+    let payload = (reference.clone(), label.clone()).to_xdr(env);
+    let signature = sign_action(env, key, "register_virtual_id", &payload, client.get_nonce());
+    client.register_virtual_id(&reference, &label, &signature)
+}
+
+#[test]
+fn test_register_virtual_id() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let virtual_id = register_virtual(&env, &client, &key, 6);
+
+    let reference = BytesN::from_array(&env, &[6u8; 32]);
+    assert_eq!(virtual_id, client.derive_virtual_id(&reference));
+    assert_eq!(client.get_virtual_id(&virtual_id).reference, reference);
+}
+
+#[test]
+fn test_virtual_ids_differ_per_wallet() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client_1 = setup_wallet(&env, &key);
+    let client_2 = setup_wallet(&env, &key);
+
+    let reference = BytesN::from_array(&env, &[6u8; 32]);
+    assert_ne!(client_1.derive_virtual_id(&reference), client_2.derive_virtual_id(&reference));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_register_virtual_id_twice() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    register_virtual(&env, &client, &key, 6);
+    register_virtual(&env, &client, &key, 6);
+}
+
+#[test]
+fn test_pay_virtual() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let virtual_id = register_virtual(&env, &client, &key, 6);
+
+    let token_admin = Address::generate(&env);
+    let token_id = env.register_stellar_asset_contract_v2(token_admin).address();
+    let payer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_id).mint(&payer, &1_000);
+
+    client.pay_virtual(&payer, &virtual_id, &token_id, &400);
+
+    let token = token::Client::new(&env, &token_id);
+    assert_eq!(token.balance(&client.address), 400);
+    assert_eq!(token.balance(&payer), 600);
+    assert_eq!(client.get_virtual_id(&virtual_id).payment_count, 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_pay_virtual_unknown_id() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    client.pay_virtual(&Address::generate(&env), &42, &Address::generate(&env), &1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn test_pay_virtual_zero_amount() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let virtual_id = register_virtual(&env, &client, &key, 6);
+
+    client.pay_virtual(&Address::generate(&env), &virtual_id, &Address::generate(&env), &0);
+}
+
+// ============================================================================
+// RECEIPT COMMITMENT TESTS
+// ============================================================================
+
+fn set_committer(env: &Env, client: &WalletContractClient, key: &SigningKey) -> Address {
+    let committer = Address::generate(env);
+    let payload = committer.clone().to_xdr(env);
+    let signature = sign_action(env, key, "set_receipts_committer", &payload, client.get_nonce());
+    client.set_receipts_committer(&committer, &signature);
+    committer
+}
+
+#[test]
+fn test_commit_receipts_root() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    set_committer(&env, &client, &key);
+
+    let root = BytesN::from_array(&env, &[9u8; 32]);
+    client.commit_receipts_root(&1, &root, &12);
+
+    let commitment = client.get_receipts_root(&1);
+    assert_eq!(commitment.root, root);
+    assert_eq!(commitment.receipt_count, 12);
+    assert_eq!(client.get_latest_receipts_epoch(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_commit_receipts_root_cannot_rewrite_epoch() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    set_committer(&env, &client, &key);
+
+    client.commit_receipts_root(&2, &BytesN::from_array(&env, &[9u8; 32]), &1);
+    client.commit_receipts_root(&2, &BytesN::from_array(&env, &[8u8; 32]), &1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_commit_receipts_root_without_committer() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    client.commit_receipts_root(&1, &BytesN::from_array(&env, &[9u8; 32]), &1);
+}
+
+// ============================================================================
+// PAGINATION TESTS
+// ============================================================================
+
+#[test]
+fn test_list_virtual_ids_pages() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    for seed in 10..15 {
+        register_virtual(&env, &client, &key, seed);
+    }
+
+    let (first, next) = client.list_virtual_ids(&0, &3);
+    assert_eq!(first.len(), 3);
+    assert_eq!(next, Some(3));
+
+    let (second, next) = client.list_virtual_ids(&3, &3);
+    assert_eq!(second.len(), 2);
+    assert_eq!(next, None);
+
+    let (past_end, next) = client.list_virtual_ids(&9, &3);
+    assert_eq!(past_end.len(), 0);
+    assert_eq!(next, None);
+}
+
+#[test]
+fn test_list_is_capped_at_max_page_limit() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    set_committer(&env, &client, &key);
+    for epoch in 0..(MAX_PAGE_LIMIT as u64 + 5) {
+        client.commit_receipts_root(&epoch, &BytesN::from_array(&env, &[9u8; 32]), &1);
+    }
+
+    let (items, next) = client.list_receipts_roots(&0, &u32::MAX);
+    assert_eq!(items.len(), MAX_PAGE_LIMIT);
+    assert_eq!(next, Some(MAX_PAGE_LIMIT));
+
+    // A zero limit asks for the default page size
+    let (items, next) = client.list_receipts_roots(&0, &0);
+    assert_eq!(items.len(), acceslyinterface::DEFAULT_PAGE_LIMIT);
+    assert_eq!(next, Some(acceslyinterface::DEFAULT_PAGE_LIMIT));
+}
+
+#[test]
+fn test_list_deposit_bindings_after_remove() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let route = Address::generate(&env);
+    let anchors: [BytesN<32>; 3] = [
+        BytesN::from_array(&env, &[4u8; 32]),
+        BytesN::from_array(&env, &[5u8; 32]),
+        BytesN::from_array(&env, &[6u8; 32]),
+    ];
+    for anchor in anchors.iter() {
+        set_binding(&env, &client, &key, anchor, &route);
+    }
+
+    let payload = anchors[0].clone().to_xdr(&env);
+    let signature = sign_action(&env, &key, "remove_deposit_binding", &payload, client.get_nonce());
+    client.remove_deposit_binding(&anchors[0], &signature);
+
+    let (items, next) = client.list_deposit_bindings(&0, &10);
+    assert_eq!(next, None);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items.get(0).unwrap().0, anchors[2]);
+    assert_eq!(items.get(1).unwrap().0, anchors[1]);
+
+    // The moved entry can itself still be removed cleanly
+    let payload = anchors[2].clone().to_xdr(&env);
+    let signature = sign_action(&env, &key, "remove_deposit_binding", &payload, client.get_nonce());
+    client.remove_deposit_binding(&anchors[2], &signature);
+    let (items, _) = client.list_deposit_bindings(&0, &10);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items.get(0).unwrap().0, anchors[1]);
+}
+
+// ============================================================================
+// SPONSORED ACTIVATION TESTS
+// ============================================================================
+
+/// Router settling from its own reserves: exact-output swaps at a fixed 2:1
+/// price, exact-input swaps on a 2:1 constant-product curve of
+/// `MOCK_POOL_DEPTH`, so larger sales get a worse price
+#[contract]
+struct MockRouter;
+
+#[contractimpl]
+impl MockRouter {
+    pub fn router_pair_for(env: Env, _token_a: Address, _token_b: Address) -> Address {
+        env.current_contract_address()
+    }
+
+    pub fn router_get_amounts_in(env: Env, amount_out: i128, _path: Vec<Address>) -> Vec<i128> {
+        vec![&env, amount_out * 2, amount_out]
+    }
+
+    pub fn swap_tokens_for_exact_tokens(
+        env: Env,
+        amount_out: i128,
+        amount_in_max: i128,
+        path: Vec<Address>,
+        to: Address,
+        _deadline: u64,
+    ) -> Vec<i128> {
+        to.require_auth();
+        let amount_in = amount_out * 2;
+        assert!(amount_in <= amount_in_max);
+
+        let router = env.current_contract_address();
+        token::Client::new(&env, &path.get(0).unwrap()).transfer(&to, &router, &amount_in);
+        token::Client::new(&env, &path.get(1).unwrap()).transfer(&router, &to, &amount_out);
+        vec![&env, amount_in, amount_out]
+    }
+
+    pub fn router_get_amounts_out(env: Env, amount_in: i128, _path: Vec<Address>) -> Vec<i128> {
+        vec![&env, amount_in, mock_amount_out(amount_in)]
+    }
+
+    pub fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        _deadline: u64,
+    ) -> Vec<i128> {
+        to.require_auth();
+        let amount_out = mock_amount_out(amount_in);
+        assert!(amount_out >= amount_out_min);
+
+        let router = env.current_contract_address();
+        token::Client::new(&env, &path.get(0).unwrap()).transfer(&to, &router, &amount_in);
+        token::Client::new(&env, &path.get(1).unwrap()).transfer(&router, &to, &amount_out);
+        vec![&env, amount_in, amount_out]
+    }
+}
+
+const MOCK_POOL_DEPTH: i128 = 10_000_000_000;
+
+fn mock_amount_out(amount_in: i128) -> i128 {
+    amount_in * MOCK_POOL_DEPTH / (2 * MOCK_POOL_DEPTH + amount_in)
+}
+
+fn create_token(env: &Env) -> Address {
+    env.register_stellar_asset_contract_v2(Address::generate(env)).address()
+}
+
+fn sign_repay(
+    env: &Env,
+    client: &WalletContractClient,
+    key: &SigningKey,
+    router: &Address,
+    native: &Address,
+    pay_token: &Address,
+    max_in: i128,
+) -> BytesN<64> {
+    let payload = (router.clone(), native.clone(), pay_token.clone(), max_in).to_xdr(env);
+    sign_action(env, key, "repay_sponsor", &payload, client.get_nonce())
+}
+
+#[test]
+fn test_record_sponsorship_accumulates() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let sponsor = Address::generate(&env);
+
+    client.record_sponsorship(&sponsor, &15_000_000);
+    client.record_sponsorship(&sponsor, &5_000_000);
+
+    let sponsorship = client.get_sponsorship();
+    assert_eq!(sponsorship.sponsor, sponsor);
+    assert_eq!(sponsorship.spent, 20_000_000);
+    assert_eq!(sponsorship.repaid, 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_record_sponsorship_other_sponsor() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    client.record_sponsorship(&Address::generate(&env), &10);
+    client.record_sponsorship(&Address::generate(&env), &10);
+}
+
+#[test]
+fn test_repay_sponsor_in_xlm() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let sponsor = Address::generate(&env);
+    let xlm = create_token(&env);
+    token::StellarAssetClient::new(&env, &xlm).mint(&client.address, &50_000_000);
+    client.record_sponsorship(&sponsor, &20_000_000);
+
+    let router = Address::generate(&env);
+    let signature = sign_repay(&env, &client, &key, &router, &xlm, &xlm, 0);
+    client.repay_sponsor(&router, &xlm, &xlm, &0, &signature);
+
+    let token = token::Client::new(&env, &xlm);
+    assert_eq!(token.balance(&sponsor), 20_000_000);
+    assert_eq!(token.balance(&client.address), 30_000_000);
+    assert_eq!(client.get_sponsorship().repaid, 20_000_000);
+}
+
+#[test]
+fn test_repay_sponsor_through_router() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let sponsor = Address::generate(&env);
+    let xlm = create_token(&env);
+    let usdc = create_token(&env);
+    let router = env.register(MockRouter, ());
+    token::StellarAssetClient::new(&env, &xlm).mint(&router, &100_000_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &50_000_000);
+    client.record_sponsorship(&sponsor, &20_000_000);
+
+    let signature = sign_repay(&env, &client, &key, &router, &xlm, &usdc, 40_000_000);
+    client.repay_sponsor(&router, &xlm, &usdc, &40_000_000, &signature);
+
+    assert_eq!(token::Client::new(&env, &xlm).balance(&sponsor), 20_000_000);
+    assert_eq!(token::Client::new(&env, &xlm).balance(&client.address), 0);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&client.address), 10_000_000);
+    assert_eq!(client.get_sponsorship().repaid, 20_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn test_repay_sponsor_above_max_in() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let xlm = create_token(&env);
+    let usdc = create_token(&env);
+    let router = env.register(MockRouter, ());
+    client.record_sponsorship(&Address::generate(&env), &20_000_000);
+
+    let signature = sign_repay(&env, &client, &key, &router, &xlm, &usdc, 39_999_999);
+    client.repay_sponsor(&router, &xlm, &usdc, &39_999_999, &signature);
+}
+
+// ============================================================================
+// TREASURY SHARES TESTS
+// ============================================================================
+
+fn set_shares(env: &Env, client: &WalletContractClient, key: &SigningKey, holders: &Vec<ShareHolder>) {
+    let payload = holders.clone().to_xdr(env);
+    let signature = sign_action(env, key, "set_shares", &payload, client.get_nonce());
+    client.set_shares(holders, &signature);
+}
+
+fn holder(env: &Env, shares: u32) -> ShareHolder {
+    ShareHolder {
+        holder: Address::generate(env),
+        shares,
+    }
+}
+
+#[test]
+fn test_set_shares() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let holders = vec![&env, holder(&env, 60), holder(&env, 40)];
+
+    set_shares(&env, &client, &key, &holders);
+
+    assert_eq!(client.get_shares(), holders);
+    assert_eq!(client.total_shares(), 100);
+    assert_eq!(client.share_balance(&holders.get(0).unwrap().holder), 60);
+    assert_eq!(client.share_balance(&Address::generate(&env)), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_set_shares_duplicate_holder() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let partner = holder(&env, 50);
+
+    set_shares(&env, &client, &key, &vec![&env, partner.clone(), partner]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #14)")]
+fn test_set_shares_too_many_holders() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let mut holders = Vec::new(&env);
+    for _ in 0..=MAX_SHARE_HOLDERS {
+        holders.push_back(holder(&env, 1));
+    }
+    set_shares(&env, &client, &key, &holders);
+}
+
+#[test]
+fn test_distribute_pro_rata() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let holders = vec![&env, holder(&env, 1), holder(&env, 1), holder(&env, 1)];
+    set_shares(&env, &client, &key, &holders);
+
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+
+    let payload = (usdc.clone(), 1_000i128).to_xdr(&env);
+    let signature = sign_action(&env, &key, "distribute", &payload, client.get_nonce());
+    client.distribute(&usdc, &1_000, &signature);
+
+    let token = token::Client::new(&env, &usdc);
+    for entry in holders.iter() {
+        assert_eq!(token.balance(&entry.holder), 333);
+    }
+    assert_eq!(token.balance(&client.address), 1);
+}
+
+#[test]
+fn test_distribute_holds_to_large_transfer_threshold() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    set_shares(&env, &client, &key, &vec![&env, holder(&env, 1), holder(&env, 1)]);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    set_large_transfer_threshold(&env, &client, &key, &usdc, 500);
+
+    let distribute = |amount: i128| {
+        let payload = (usdc.clone(), amount).to_xdr(&env);
+        let signature = sign_action(&env, &key, "distribute", &payload, client.get_nonce());
+        client.try_distribute(&usdc, &amount, &signature)
+    };
+    assert_eq!(distribute(1_000), Err(Ok(Error::Timelocked)));
+    assert_eq!(distribute(500), Ok(Ok(())));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_distribute_without_shares() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let asset = Address::generate(&env);
+
+    let payload = (asset.clone(), 10i128).to_xdr(&env);
+    let signature = sign_action(&env, &key, "distribute", &payload, client.get_nonce());
+    client.distribute(&asset, &10, &signature);
+}
+
+// ============================================================================
+// RECENT OPERATIONS TESTS
+// ============================================================================
+
+#[test]
+fn test_recent_ops_newest_first() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let virtual_id = register_virtual(&env, &client, &key, 6);
+    let token_id = create_token(&env);
+    let payer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_id).mint(&payer, &1_000);
+
+    assert!(client.get_recent_ops(&10).is_empty());
+
+    client.pay_virtual(&payer, &virtual_id, &token_id, &100);
+    client.pay_virtual(&payer, &virtual_id, &token_id, &200);
+
+    let ops = client.get_recent_ops(&10);
+    assert_eq!(ops.len(), 2);
+    let newest = ops.get(0).unwrap();
+    assert_eq!(newest.kind, OpKind::VirtualPayment);
+    assert_eq!(newest.amount, 200);
+    assert_eq!(newest.counterparty, Some(payer));
+    assert_eq!(ops.get(1).unwrap().amount, 100);
+    assert_eq!(client.get_recent_ops(&1).len(), 1);
+}
+
+#[test]
+fn test_recent_ops_ring_buffer_wraps() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let virtual_id = register_virtual(&env, &client, &key, 6);
+    let token_id = create_token(&env);
+    let payer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_id).mint(&payer, &1_000_000);
+
+    for amount in 1..=(RECENT_OPS_CAPACITY as i128 + 5) {
+        client.pay_virtual(&payer, &virtual_id, &token_id, &amount);
+    }
+
+    let ops = client.get_recent_ops(&100);
+    assert_eq!(ops.len(), MAX_PAGE_LIMIT);
+    for (i, op) in ops.iter().enumerate() {
+        assert_eq!(op.amount, RECENT_OPS_CAPACITY as i128 + 5 - i as i128);
+    }
+}
+
+// ============================================================================
+// SIGNED QUOTE TESTS
+// ============================================================================
+
+struct QuoteSetup<'a> {
+    client: WalletContractClient<'a>,
+    owner: SigningKey,
+    maker: SigningKey,
+    usdc: Address,
+    mxn: Address,
+    router: Address,
+}
+
+fn setup_quotes(env: &Env) -> QuoteSetup<'_> {
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let maker = signing_key(7);
+    let client = setup_wallet(env, &owner);
+
+    let maker_key = public_key(env, &maker);
+    let payload = (maker_key.clone(), true).to_xdr(env);
+    let signature = sign_action(env, &owner, "set_quote_signer", &payload, client.get_nonce());
+    client.set_quote_signer(&maker_key, &true, &signature);
+
+    let usdc = create_token(env);
+    let mxn = create_token(env);
+    let router = env.register(MockRouter, ());
+    token::StellarAssetClient::new(env, &usdc).mint(&client.address, &1_000);
+    token::StellarAssetClient::new(env, &mxn).mint(&router, &10_000);
+
+    QuoteSetup {
+        client,
+        owner,
+        maker,
+        usdc,
+        mxn,
+        router,
+    }
+}
+
+fn quote(env: &Env, setup: &QuoteSetup, id: u8, destination: &Address) -> Quote {
+    Quote {
+        quote_id: BytesN::from_array(env, &[id; 32]),
+        signer: public_key(env, &setup.maker),
+        sell_asset: setup.usdc.clone(),
+        sell_amount: 500,
+        buy_asset: setup.mxn.clone(),
+        buy_amount: 200,
+        destination: destination.clone(),
+        router: setup.router.clone(),
+        expires_at: env.ledger().timestamp() + 60,
+    }
+}
+
+fn sign_quote(env: &Env, key: &SigningKey, quote: &Quote) -> Bytes {
+    let signature = sign_raw(env, key, &quote.clone().to_xdr(env));
+    SignedQuote {
+        quote: quote.clone(),
+        signature,
+    }
+    .to_xdr(env)
+}
+
+fn accept(env: &Env, setup: &QuoteSetup, quote_id: &BytesN<32>, blob: &Bytes) -> QuoteReceipt {
+    let payload = (quote_id.clone(), blob.clone()).to_xdr(env);
+    let signature = sign_action(env, &setup.owner, "accept_quote", &payload, setup.client.get_nonce());
+    setup.client.accept_quote(quote_id, blob, &signature)
+}
+
+#[test]
+fn test_accept_quote_pays_and_stores_receipt() {
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    let recipient = Address::generate(&env);
+    let quote = quote(&env, &setup, 1, &recipient);
+    let blob = sign_quote(&env, &setup.maker, &quote);
+
+    let receipt = accept(&env, &setup, &quote.quote_id, &blob);
+
+    assert_eq!(token::Client::new(&env, &setup.mxn).balance(&recipient), 200);
+    assert_eq!(token::Client::new(&env, &setup.usdc).balance(&setup.client.address), 600);
+    assert_eq!(receipt.sold, 400);
+    assert_eq!(receipt.quote_hash, BytesN::from(env.crypto().sha256(&blob)));
+    assert_eq!(setup.client.get_quote_receipt(&quote.quote_id), receipt);
+    assert_eq!(setup.client.get_recent_ops(&1).get(0).unwrap().kind, OpKind::QuotePayment);
+}
+
+#[test]
+fn test_accept_quote_holds_to_large_transfer_threshold() {
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    set_large_transfer_threshold(&env, &setup.client, &setup.owner, &setup.usdc, 400);
+    let quote = quote(&env, &setup, 1, &Address::generate(&env));
+    let blob = sign_quote(&env, &setup.maker, &quote);
+
+    // Up to 500 USDC could be sold for it
+    let payload = (quote.quote_id.clone(), blob.clone()).to_xdr(&env);
+    let signature = sign_action(&env, &setup.owner, "accept_quote", &payload, setup.client.get_nonce());
+    assert_eq!(setup.client.try_accept_quote(&quote.quote_id, &blob, &signature), Err(Ok(Error::Timelocked)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")]
+fn test_accept_quote_twice() {
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    let quote = quote(&env, &setup, 1, &Address::generate(&env));
+    let blob = sign_quote(&env, &setup.maker, &quote);
+
+    accept(&env, &setup, &quote.quote_id, &blob);
+    accept(&env, &setup, &quote.quote_id, &blob);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #15)")]
+fn test_accept_quote_expired() {
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    let quote = quote(&env, &setup, 1, &Address::generate(&env));
+    let blob = sign_quote(&env, &setup.maker, &quote);
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 61);
+    accept(&env, &setup, &quote.quote_id, &blob);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_accept_quote_untrusted_signer() {
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    let stranger = signing_key(9);
+    let mut quote = quote(&env, &setup, 1, &Address::generate(&env));
+    quote.signer = public_key(&env, &stranger);
+    let blob = sign_quote(&env, &stranger, &quote);
+
+    accept(&env, &setup, &quote.quote_id, &blob);
+}
+
+#[test]
+#[should_panic]
+fn test_accept_quote_forged_price() {
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    let mut quote = quote(&env, &setup, 1, &Address::generate(&env));
+    let signature = sign_raw(&env, &setup.maker, &quote.clone().to_xdr(&env));
+
+    quote.buy_amount = 100;
+    let blob = SignedQuote { quote: quote.clone(), signature }.to_xdr(&env);
+    accept(&env, &setup, &quote.quote_id, &blob);
+}
+
+// ============================================================================
+// EVENT PRIVACY TESTS
+// ============================================================================
+
+fn set_privacy(env: &Env, client: &WalletContractClient, key: &SigningKey, salt: Option<BytesN<32>>) {
+    let payload = salt.clone().to_xdr(env);
+    let signature = sign_action(env, key, "set_event_privacy", &payload, client.get_nonce());
+    client.set_event_privacy(&salt, &signature);
+}
+
+#[test]
+fn test_event_privacy_toggle() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    assert!(!client.is_event_privacy_enabled());
+
+    set_privacy(&env, &client, &key, Some(BytesN::from_array(&env, &[5u8; 32])));
+    assert!(client.is_event_privacy_enabled());
+
+    set_privacy(&env, &client, &key, None);
+    assert!(!client.is_event_privacy_enabled());
+}
+
+#[test]
+fn test_event_privacy_hides_counterparty_in_recent_ops() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let virtual_id = register_virtual(&env, &client, &key, 6);
+    let token_id = create_token(&env);
+    let payer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_id).mint(&payer, &1_000);
+
+    set_privacy(&env, &client, &key, Some(BytesN::from_array(&env, &[5u8; 32])));
+    client.pay_virtual(&payer, &virtual_id, &token_id, &250);
+
+    let op = client.get_recent_ops(&1).get(0).unwrap();
+    assert_eq!(op.amount, 250);
+    assert_eq!(op.counterparty, None);
+}
+
+#[test]
+fn test_amount_bucket() {
+    assert_eq!(amount_bucket(0), 0);
+    assert_eq!(amount_bucket(1), 1);
+    assert_eq!(amount_bucket(9), 1);
+    assert_eq!(amount_bucket(10), 2);
+    assert_eq!(amount_bucket(-10_000_000), 8);
+    assert_eq!(amount_bucket(i128::MIN), 39);
+}
+
+// ============================================================================
+// LENDING ADAPTER TESTS
+// ============================================================================
+
+/// Pool that settles supply/withdraw/repay requests 1:1 against its balance
+#[contract]
+struct MockLendingPool;
+
+#[contractimpl]
+impl MockLendingPool {
+    pub fn submit(env: Env, from: Address, spender: Address, to: Address, requests: Vec<LendingRequest>) {
+        from.require_auth();
+        let pool = env.current_contract_address();
+        for request in requests.iter() {
+            let token = token::Client::new(&env, &request.address);
+            match request.request_type {
+                1 | 3 => token.transfer(&pool, &to, &request.amount),
+                _ => token.transfer(&spender, &pool, &request.amount),
+            }
+        }
+    }
+}
+
+fn setup_lending(env: &Env) -> (WalletContractClient<'_>, SigningKey, Address, Address) {
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(env, &key);
+    let pool = env.register(MockLendingPool, ());
+    let usdc = create_token(env);
+    token::StellarAssetClient::new(env, &usdc).mint(&client.address, &1_000);
+
+    let payload = (pool.clone(), true).to_xdr(env);
+    let signature = sign_action(env, &key, "set_lending_pool", &payload, client.get_nonce());
+    client.set_lending_pool(&pool, &true, &signature);
+
+    (client, key, pool, usdc)
+}
+
+fn lend(
+    env: &Env,
+    client: &WalletContractClient,
+    key: &SigningKey,
+    pool: &Address,
+    action: LendAction,
+    asset: &Address,
+    amount: i128,
+) {
+    let payload = (pool.clone(), action, asset.clone(), amount).to_xdr(env);
+    let signature = sign_action(env, key, "lend", &payload, client.get_nonce());
+    client.lend(pool, &action, asset, &amount, &signature);
+}
+
+#[test]
+fn test_lend_supply_and_withdraw() {
+    let env = create_test_env();
+    let (client, key, pool, usdc) = setup_lending(&env);
+    let token = token::Client::new(&env, &usdc);
+
+    lend(&env, &client, &key, &pool, LendAction::Supply, &usdc, 600);
+    assert_eq!(token.balance(&pool), 600);
+
+    lend(&env, &client, &key, &pool, LendAction::Withdraw, &usdc, 200);
+    assert_eq!(token.balance(&client.address), 600);
+
+    let op = client.get_recent_ops(&1).get(0).unwrap();
+    assert_eq!(op.kind, OpKind::Lending);
+    assert_eq!(op.amount, 200);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_lend_unlisted_pool() {
+    let env = create_test_env();
+    let (client, key, _, usdc) = setup_lending(&env);
+    let other_pool = env.register(MockLendingPool, ());
+
+    lend(&env, &client, &key, &other_pool, LendAction::Supply, &usdc, 100);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #8)")]
+fn test_lend_collateral_needs_approval() {
+    let env = create_test_env();
+    let (client, key, pool, usdc) = setup_lending(&env);
+
+    lend(&env, &client, &key, &pool, LendAction::SupplyCollateral, &usdc, 100);
+}
+
+#[test]
+fn test_lend_collateral_consumes_approval() {
+    let env = create_test_env();
+    let (client, key, pool, usdc) = setup_lending(&env);
+
+    let payload = (pool.clone(), usdc.clone(), 300i128).to_xdr(&env);
+    let signature = sign_action(&env, &key, "approve_collateral", &payload, client.get_nonce());
+    client.approve_collateral(&pool, &usdc, &300, &signature);
+
+    lend(&env, &client, &key, &pool, LendAction::SupplyCollateral, &usdc, 250);
+    assert_eq!(client.get_collateral_allowance(&pool, &usdc), 50);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&pool), 250);
+
+    let payload = (pool.clone(), LendAction::SupplyCollateral, usdc.clone(), 100i128).to_xdr(&env);
+    let signature = sign_action(&env, &key, "lend", &payload, client.get_nonce());
+    let result = client.try_lend(&pool, &LendAction::SupplyCollateral, &usdc, &100, &signature);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+// ============================================================================
+// AUTH SIGNATURE TESTS
+// ============================================================================
+
+fn passkey() -> p256::ecdsa::SigningKey {
+    p256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap()
+}
+
+fn passkey_public(env: &Env, key: &p256::ecdsa::SigningKey) -> BytesN<65> {
+    let point = key.verifying_key().to_encoded_point(false);
+    BytesN::from_array(env, point.as_bytes().try_into().unwrap())
+}
+
+fn register_passkey(env: &Env, client: &WalletContractClient, owner: &SigningKey) {
+    let public_key = Some(passkey_public(env, &passkey()));
+    let sig = sign_action(env, owner, "set_passkey", &public_key.clone().to_xdr(env), client.get_nonce());
+    client.set_passkey(&public_key, &sig);
+}
+
+/// `signature_payload || nonce`, the message `__check_auth` verifies
+fn auth_message(payload: &BytesN<32>, nonce: u64) -> std::vec::Vec<u8> {
+    let mut message = payload.to_array().to_vec();
+    message.extend_from_slice(&nonce.to_be_bytes());
+    message
+}
+
+/// WebAuthn assertion as a browser would produce it for `challenge`
+fn assertion(env: &Env, challenge: &[u8], flags: u8) -> WebAuthnSignature {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use p256::ecdsa::signature::Signer as _;
+
+    let mut auth_data = [0x11u8; 37];
+    auth_data[32] = flags;
+    let client_data = std::format!(
+        "{{\"type\":\"webauthn.get\",\"challenge\":\"{}\",\"origin\":\"https://accesly.xyz\",\"crossOrigin\":false}}",
+        URL_SAFE_NO_PAD.encode(challenge)
+    );
+    let client_data_hash = env.crypto().sha256(&Bytes::from_slice(env, client_data.as_bytes()));
+
+    let mut signed = auth_data.to_vec();
+    signed.extend_from_slice(&client_data_hash.to_array());
+    let signature: p256::ecdsa::Signature = passkey().sign(&signed);
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    WebAuthnSignature {
+        authenticator_data: Bytes::from_slice(env, &auth_data),
+        client_data_json: Bytes::from_slice(env, client_data.as_bytes()),
+        signature: BytesN::from_array(env, &signature.to_bytes().into()),
+    }
+}
+
+fn check_auth(env: &Env, client: &WalletContractClient, payload: &BytesN<32>, signature: AuthSignature) -> bool {
+    use soroban_sdk::IntoVal;
+    env.try_invoke_contract_check_auth::<Error>(
+        &client.address,
+        payload,
+        signature.into_val(env),
+        &Vec::new(env),
+    )
+    .is_ok()
+}
+
+#[test]
+fn test_check_auth_ed25519() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+
+    let message = Bytes::from_slice(&env, &auth_message(&payload, 0));
+    let sig = sign_raw(&env, &owner, &message);
+    assert!(check_auth(&env, &client, &payload, AuthSignature::Ed25519(sig.clone())));
+    assert_eq!(client.get_nonce(), 1);
+
+    // The nonce moved on, so the same signature no longer verifies
+    assert!(!check_auth(&env, &client, &payload, AuthSignature::Ed25519(sig)));
+}
+
+#[test]
+fn test_check_auth_passkey() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+
+    use soroban_sdk::IntoVal;
+
+    // No passkey registered yet
+    let early = assertion(&env, &auth_message(&payload, 0), 0x05);
+    assert_eq!(
+        env.try_invoke_contract_check_auth::<Error>(
+            &client.address,
+            &payload,
+            AuthSignature::Secp256r1(early).into_val(&env),
+            &Vec::new(&env),
+        ),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    register_passkey(&env, &client, &owner);
+    assert_eq!(client.get_passkey(), Some(passkey_public(&env, &passkey())));
+
+    let nonce = client.get_nonce();
+    let valid = assertion(&env, &auth_message(&payload, nonce), 0x05);
+    assert!(check_auth(&env, &client, &payload, AuthSignature::Secp256r1(valid)));
+    assert_eq!(client.get_nonce(), nonce + 1);
+}
+
+#[test]
+fn test_check_auth_passkey_rejects_bad_assertions() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    register_passkey(&env, &client, &owner);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let message = auth_message(&payload, client.get_nonce());
+
+    // Challenge for another payload
+    let other = auth_message(&BytesN::from_array(&env, &[6u8; 32]), client.get_nonce());
+    assert!(!check_auth(&env, &client, &payload, AuthSignature::Secp256r1(assertion(&env, &other, 0x05))));
+
+    // User presence flag not set
+    assert!(!check_auth(&env, &client, &payload, AuthSignature::Secp256r1(assertion(&env, &message, 0x04))));
+
+    // Tampered authenticator data no longer matches the signature
+    let mut tampered = assertion(&env, &message, 0x05);
+    tampered.authenticator_data.set(0, 0x22);
+    assert!(!check_auth(&env, &client, &payload, AuthSignature::Secp256r1(tampered)));
+
+    // Registration clears with None, and only uncompressed keys are accepted
+    let nonce = client.get_nonce();
+    let sig = sign_action(&env, &owner, "set_passkey", &None::<BytesN<65>>.to_xdr(&env), nonce);
+    client.set_passkey(&None, &sig);
+    assert_eq!(client.get_passkey(), None);
+
+    let compressed = Some(BytesN::from_array(&env, &[2u8; 65]));
+    let sig = sign_action(&env, &owner, "set_passkey", &compressed.clone().to_xdr(&env), nonce + 1);
+    assert_eq!(client.try_set_passkey(&compressed, &sig), Err(Ok(Error::InvalidOwner)));
+
+    // The ed25519 owner key keeps working throughout
+    let message = Bytes::from_slice(&env, &auth_message(&payload, nonce + 1));
+    let sig = sign_raw(&env, &owner, &message);
+    assert!(check_auth(&env, &client, &payload, AuthSignature::Ed25519(sig)));
+}
+
+/// `signature_payload || channel || nonce`, the message on a nonce channel
+fn channel_auth_message(payload: &BytesN<32>, channel: u32, nonce: u64) -> std::vec::Vec<u8> {
+    let mut message = payload.to_array().to_vec();
+    message.extend_from_slice(&channel.to_be_bytes());
+    message.extend_from_slice(&nonce.to_be_bytes());
+    message
+}
+
+#[test]
+fn test_check_auth_nonce_channels_are_independent() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (payment, swap) = (BytesN::from_array(&env, &[5u8; 32]), BytesN::from_array(&env, &[6u8; 32]));
+
+    // Both signed against the same wallet state, as two devices would
+    let sign = |payload: &BytesN<32>, channel: u32| {
+        let message = Bytes::from_slice(&env, &channel_auth_message(payload, channel, 0));
+        AuthSignature::Channel(channel, ChannelSignature::Ed25519(sign_raw(&env, &owner, &message)))
+    };
+    let (on_1, on_2) = (sign(&payment, 1), sign(&swap, 2));
+    let plain = AuthSignature::Ed25519(sign_raw(&env, &owner, &Bytes::from_slice(&env, &auth_message(&payment, 0))));
+
+    assert!(check_auth(&env, &client, &swap, on_2.clone()));
+    assert!(check_auth(&env, &client, &payment, on_1.clone()));
+    assert!(check_auth(&env, &client, &payment, plain));
+    assert_eq!(client.get_channel_nonce(&1), 1);
+    assert_eq!(client.get_channel_nonce(&2), 1);
+    assert_eq!(client.get_channel_nonce(&0), client.get_nonce());
+    assert_eq!(client.get_nonce(), 1);
+
+    // Replays fail, and a signature is bound to its channel
+    assert!(!check_auth(&env, &client, &payment, on_1));
+    let AuthSignature::Channel(_, moved) = sign(&payment, 3) else { unreachable!() };
+    assert!(!check_auth(&env, &client, &payment, AuthSignature::Channel(4, moved)));
+}
+
+#[test]
+fn test_check_auth_passkey_on_channel() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    register_passkey(&env, &client, &owner);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+
+    let valid = assertion(&env, &channel_auth_message(&payload, 7, 0), 0x05);
+    assert!(check_auth(&env, &client, &payload, AuthSignature::Channel(7, ChannelSignature::Secp256r1(valid))));
+    assert_eq!(client.get_channel_nonce(&7), 1);
+}
+
+/// `signature_payload || channel || nonce || valid_until_ledger`, the
+/// message of an expiring signature
+fn expiring_auth_message(payload: &BytesN<32>, channel: u32, nonce: u64, valid_until_ledger: u32) -> std::vec::Vec<u8> {
+    let mut message = channel_auth_message(payload, channel, nonce);
+    message.extend_from_slice(&valid_until_ledger.to_be_bytes());
+    message
+}
+
+#[test]
+fn test_check_auth_expiring_signature_lapses() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let expiring = |channel: u32, nonce: u64, valid_until_ledger: u32| {
+        let message = Bytes::from_slice(&env, &expiring_auth_message(&payload, channel, nonce, valid_until_ledger));
+        AuthSignature::Expiring(ExpiringSignature {
+            channel,
+            valid_until_ledger,
+            signature: ChannelSignature::Ed25519(sign_raw(&env, &owner, &message)),
+        })
+    };
+
+    // Valid through its last ledger, on the wallet nonce or a channel
+    assert!(check_auth(&env, &client, &payload, expiring(0, 0, 100)));
+    assert!(check_auth(&env, &client, &payload, expiring(3, 0, 120)));
+    assert_eq!(client.get_nonce(), 1);
+    assert_eq!(client.get_channel_nonce(&3), 1);
+
+    // A relayer holding on to a signature can't use it past the bound,
+    // even though the nonce it was signed for is still current
+    let stale = expiring(0, 1, 150);
+    env.ledger().with_mut(|li| li.sequence_number = 151);
+    let result = env.try_invoke_contract_check_auth::<Error>(
+        &client.address,
+        &payload,
+        soroban_sdk::IntoVal::into_val(&stale, &env),
+        &Vec::new(&env),
+    );
+    assert_eq!(result.err(), Some(Ok(Error::Expired)));
+    assert_eq!(client.get_nonce(), 1);
+
+    // The bound is signed, so it can't be pushed out
+    let AuthSignature::Expiring(mut extended) = stale else { unreachable!() };
+    extended.valid_until_ledger = 1_000;
+    assert!(!check_auth(&env, &client, &payload, AuthSignature::Expiring(extended)));
+}
+
+// ============================================================================
+// WEIGHTED SIGNER TESTS
+// ============================================================================
+
+fn add_signer(env: &Env, client: &WalletContractClient, owner: &SigningKey, signer: &Signer, weight: u32) {
+    let sig = sign_action(env, owner, "add_signer", &(signer.clone(), weight).to_xdr(env), client.get_nonce());
+    client.add_signer(signer, &weight, &sig);
+}
+
+fn set_threshold(env: &Env, client: &WalletContractClient, owner: &SigningKey, threshold: u32) {
+    let sig = sign_action(env, owner, "set_threshold", &threshold.to_xdr(env), client.get_nonce());
+    client.set_threshold(&threshold, &sig);
+}
+
+fn ed25519_proof(env: &Env, key: &SigningKey, payload: &BytesN<32>, nonce: u64) -> SignerProof {
+    let message = Bytes::from_slice(env, &auth_message(payload, nonce));
+    SignerProof::Ed25519(public_key(env, key), sign_raw(env, key, &message))
+}
+
+fn try_check_auth(env: &Env, client: &WalletContractClient, payload: &BytesN<32>, signature: AuthSignature) -> Result<(), Error> {
+    use soroban_sdk::IntoVal;
+    env.try_invoke_contract_check_auth::<Error>(&client.address, payload, signature.into_val(env), &Vec::new(env))
+        .map_err(|e| e.ok().unwrap_or(Error::InvalidSignature))
+}
+
+#[test]
+fn test_check_auth_weighted_threshold() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let (alice, bob, carol) = (signing_key(2), signing_key(3), signing_key(4));
+    let client = setup_wallet(&env, &owner);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &owner)), 1);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &alice)), 1);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &bob)), 2);
+    set_threshold(&env, &client, &owner, 2);
+    assert_eq!(client.get_threshold(), 2);
+    assert_eq!(client.get_signers().len(), 3);
+
+    // The owner alone only carries weight 1 now
+    let nonce = client.get_nonce();
+    let message = Bytes::from_slice(&env, &auth_message(&payload, nonce));
+    let owner_only = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+    assert_eq!(try_check_auth(&env, &client, &payload, owner_only), Err(Error::Unauthorized));
+
+    // The same signer twice doesn't add up
+    let twice = vec![&env, ed25519_proof(&env, &alice, &payload, nonce), ed25519_proof(&env, &alice, &payload, nonce)];
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::Multisig(twice)), Err(Error::InvalidSignature));
+
+    // Unregistered keys don't count
+    let stranger = vec![&env, ed25519_proof(&env, &carol, &payload, nonce)];
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::Multisig(stranger)), Err(Error::Unauthorized));
+
+    let pair = vec![&env, ed25519_proof(&env, &owner, &payload, nonce), ed25519_proof(&env, &alice, &payload, nonce)];
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::Multisig(pair)), Ok(()));
+
+    // bob's weight meets the threshold on his own
+    let nonce = client.get_nonce();
+    let solo = vec![&env, ed25519_proof(&env, &bob, &payload, nonce)];
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::Multisig(solo)), Ok(()));
+}
+
+#[test]
+fn test_signer_set_management() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let alice = Signer::Ed25519(public_key(&env, &signing_key(2)));
+    let bob = Signer::Ed25519(public_key(&env, &signing_key(3)));
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+
+    let sig = sign_action(&env, &owner, "add_signer", &(alice.clone(), 0u32).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_add_signer(&alice, &0, &sig), Err(Ok(Error::InvalidAmount)));
+
+    add_signer(&env, &client, &owner, &alice, 1);
+    add_signer(&env, &client, &owner, &bob, 1);
+    let sig = sign_action(&env, &owner, "add_signer", &(bob.clone(), 1u32).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_add_signer(&bob, &1, &sig), Err(Ok(Error::AlreadyExists)));
+
+    // Thresholds the set can't reach are refused
+    let sig = sign_action(&env, &owner, "set_threshold", &3u32.to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_set_threshold(&3, &sig), Err(Ok(Error::LimitExceeded)));
+    set_threshold(&env, &client, &owner, 2);
+
+    // So are removals that would lock the wallet
+    let sig = sign_action(&env, &owner, "remove_signer", &bob.clone().to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_remove_signer(&bob, &sig), Err(Ok(Error::LimitExceeded)));
+
+    let sig = sign_action(&env, &owner, "update_signer_weight", &(alice.clone(), 2u32).to_xdr(&env), client.get_nonce());
+    client.update_signer_weight(&alice, &2, &sig);
+    let sig = sign_action(&env, &owner, "remove_signer", &bob.clone().to_xdr(&env), client.get_nonce());
+    client.remove_signer(&bob, &sig);
+    assert_eq!(client.get_signers().get(alice.clone()), Some(2));
+    assert_eq!(client.get_signers().get(bob.clone()), None);
+
+    let sig = sign_action(&env, &owner, "update_signer_weight", &(bob.clone(), 1u32).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_update_signer_weight(&bob, &1, &sig), Err(Ok(Error::NotFound)));
+
+    // A wallet can't be its own signer, nor a contract a guardian
+    let itself = Signer::Address(client.address.clone());
+    let sig = sign_action(&env, &owner, "add_signer", &(itself.clone(), 1u32).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_add_signer(&itself, &1, &sig), Err(Ok(Error::InvalidOwner)));
+    let org = Signer::Address(Address::generate(&env));
+    let sig = sign_action(&env, &owner, "add_guardian", &org.clone().to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_add_guardian(&org, &sig), Err(Ok(Error::InvalidOwner)));
+
+    // Threshold 0 goes back to plain owner signatures
+    set_threshold(&env, &client, &owner, 0);
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let sig = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+    assert_eq!(try_check_auth(&env, &client, &payload, sig), Ok(()));
+}
+
+#[test]
+fn test_contract_signer_authorizes_with_require_auth() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    // An org account that is itself an Accesly wallet
+    let org = setup_wallet(&env, &signing_key(7));
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &owner)), 1);
+    add_signer(&env, &client, &owner, &Signer::Address(org.address.clone()), 2);
+    set_threshold(&env, &client, &owner, 2);
+
+    // Without the org's own authorization the proof carries nothing
+    let proof = vec![&env, SignerProof::Address(org.address.clone())];
+    assert!(try_check_auth(&env, &client, &payload, AuthSignature::Multisig(proof.clone())).is_err());
+    assert_eq!(client.get_nonce(), 3);
+
+    env.mock_all_auths_allowing_non_root_auth();
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::Multisig(proof)), Ok(()));
+    assert_eq!(client.get_nonce(), 4);
+}
+
+#[test]
+fn test_owner_key_alone_cannot_spend_in_multi_signer_mode() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let (owner, alice) = (signing_key(1), signing_key(2));
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    let to = Address::generate(&env);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &owner)), 1);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &alice)), 1);
+    set_threshold(&env, &client, &owner, 2);
+
+    let ops = vec![&env, transfer_op(&env, &usdc, &client.address, &to, 100)];
+    let sig = sign_batch(&env, &client, &owner, &ops);
+    assert_eq!(client.try_execute_batch(&ops, &sig), Err(Ok(Error::Unauthorized)));
+    let sig = sign_action(&env, &owner, "execute_batch_best_effort", &(ops.clone(), 1u32).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_execute_batch_best_effort(&ops, &1, &sig), Err(Ok(Error::Unauthorized)));
+
+    let envelope = meta_tx(ops.get(0).unwrap(), 1, 0, env.ledger().timestamp() + 600);
+    let sig = sign_meta_tx(&env, &client, &owner, &envelope);
+    assert_eq!(client.try_execute_meta_tx(&envelope, &sig, &0).err(), Some(Ok(Error::Unauthorized)));
+
+    // Nor can it mint a session key that would skip the signer set
+    let key = public_key(&env, &signing_key(9));
+    let (scope, allowed, expires_at) = (Symbol::new(&env, "trading"), session_calls(&env, &to), env.ledger().timestamp() + 600);
+    let payload = (key.clone(), scope.clone(), allowed.clone(), expires_at, None::<BytesN<32>>).to_xdr(&env);
+    let sig = sign_action(&env, &owner, "create_session", &payload, client.get_nonce());
+    assert_eq!(client.try_create_session(&key, &scope, &allowed, &expires_at, &None, &sig), Err(Ok(Error::Unauthorized)));
+
+    assert_eq!(token::Client::new(&env, &usdc).balance(&client.address), 1_000);
+}
+
+// ============================================================================
+// SESSION KEY TESTS
+// ============================================================================
+
+fn session_calls(env: &Env, contract: &Address) -> Vec<SessionCall> {
+    vec![env, SessionCall { contract: contract.clone(), function: Symbol::new(env, "swap") }]
+}
+
+fn create_session(env: &Env, client: &WalletContractClient, owner: &SigningKey, session: &SigningKey, expires_at: u64) {
+    create_session_with_risk(env, client, owner, session, expires_at, None);
+}
+
+fn create_session_with_risk(
+    env: &Env,
+    client: &WalletContractClient,
+    owner: &SigningKey,
+    session: &SigningKey,
+    expires_at: u64,
+    risk_summary: Option<BytesN<32>>,
+) {
+    let key = public_key(env, session);
+    let scope = Symbol::new(env, "trading");
+    let allowed = session_calls(env, &client.address);
+    let payload = (key.clone(), scope.clone(), allowed.clone(), expires_at, risk_summary.clone()).to_xdr(env);
+    let sig = sign_action(env, owner, "create_session", &payload, client.get_nonce());
+    client.create_session(&key, &scope, &allowed, &expires_at, &risk_summary, &sig);
+}
+
+fn session_auth_for(env: &Env, client: &WalletContractClient, session: &SigningKey, contract: &Address, function: &str) -> Result<(), Error> {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let payload = BytesN::from_array(env, &[5u8; 32]);
+    let message = Bytes::from_slice(env, &auth_message(&payload, client.get_nonce()));
+    let signature = AuthSignature::Session(public_key(env, session), sign_raw(env, session, &message));
+    let context = Context::Contract(ContractContext {
+        contract: contract.clone(),
+        fn_name: Symbol::new(env, function),
+        args: Vec::new(env),
+    });
+    env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(env), &vec![env, context])
+        .map_err(|e| e.ok().unwrap_or(Error::InvalidSignature))
+}
+
+/// Session auth for the call every test session is scoped to
+fn session_auth(env: &Env, client: &WalletContractClient, session: &SigningKey) -> Result<(), Error> {
+    session_auth_for(env, client, session, &client.address, "swap")
+}
+
+#[test]
+fn test_session_key_auth() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let session = signing_key(8);
+    let client = setup_wallet(&env, &owner);
+
+    // Unknown keys are rejected
+    assert_eq!(session_auth(&env, &client, &session), Err(Error::Unauthorized));
+
+    create_session(&env, &client, &owner, &session, 2_000);
+    assert_eq!(session_auth(&env, &client, &session), Ok(()));
+    assert_eq!(session_auth(&env, &client, &session), Ok(()));
+    assert_eq!(client.get_session(&public_key(&env, &session)).uses, 2);
+
+    // A signature by another key under the session's name fails
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let forged = AuthSignature::Session(public_key(&env, &session), sign_raw(&env, &signing_key(9), &message));
+    assert!(try_check_auth(&env, &client, &payload, forged).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert_eq!(session_auth(&env, &client, &session), Err(Error::Expired));
+    assert_eq!(client.try_get_session(&public_key(&env, &session)), Err(Ok(Error::Expired)));
+}
+
+#[test]
+fn test_session_creation_rules() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let session = signing_key(8);
+    let client = setup_wallet(&env, &owner);
+    let key = public_key(&env, &session);
+    let scope = Symbol::new(&env, "trading");
+
+    let allowed = session_calls(&env, &client.address);
+    let try_create = |allowed: &Vec<SessionCall>, expires_at: u64| {
+        let payload = (key.clone(), scope.clone(), allowed.clone(), expires_at, None::<BytesN<32>>).to_xdr(&env);
+        let sig = sign_action(&env, &owner, "create_session", &payload, client.get_nonce());
+        client.try_create_session(&key, &scope, allowed, &expires_at, &None, &sig)
+    };
+
+    assert_eq!(try_create(&allowed, 1_000), Err(Ok(Error::Expired)));
+    assert_eq!(try_create(&allowed, 1_000 + MAX_SESSION_DURATION + 1), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(try_create(&Vec::new(&env), 5_000), Err(Ok(Error::LimitExceeded)));
+
+    create_session(&env, &client, &owner, &session, 5_000);
+    assert_eq!(try_create(&allowed, 6_000), Err(Ok(Error::AlreadyExists)));
+
+    let session = client.get_session(&key);
+    assert_eq!(session.scope, scope);
+    assert_eq!(session.allowed, allowed);
+    assert_eq!(session.expires_at, 5_000);
+    assert_eq!(client.get_session_risk(&key), None);
+}
+
+#[test]
+fn test_session_keeps_risk_summary() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let session = signing_key(8);
+    let client = setup_wallet(&env, &owner);
+    let key = public_key(&env, &session);
+    let scope = Symbol::new(&env, "trading");
+    let allowed = session_calls(&env, &client.address);
+    let risk = BytesN::from_array(&env, &[7u8; 32]);
+
+    // The hash is signed with the session, so it can't be added or swapped
+    let payload = (key.clone(), scope.clone(), allowed.clone(), 5_000u64, None::<BytesN<32>>).to_xdr(&env);
+    let sig = sign_action(&env, &owner, "create_session", &payload, client.get_nonce());
+    assert!(client
+        .try_create_session(&key, &scope, &allowed, &5_000, &Some(risk.clone()), &sig)
+        .is_err());
+
+    create_session_with_risk(&env, &client, &owner, &session, 5_000, Some(risk.clone()));
+    assert_eq!(client.get_session_risk(&key), Some(risk));
+
+    let sig = sign_action(&env, &owner, "revoke_session", &key.clone().to_xdr(&env), client.get_nonce());
+    client.revoke_session(&key, &sig);
+    assert_eq!(client.get_session_risk(&key), None);
+}
+
+#[test]
+fn test_session_scope_limits_calls() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let session = signing_key(8);
+    let client = setup_wallet(&env, &owner);
+    let other = Address::generate(&env);
+
+    create_session(&env, &client, &owner, &session, 5_000);
+
+    assert_eq!(session_auth_for(&env, &client, &session, &client.address, "swap"), Ok(()));
+    // Another function on the allowed contract
+    assert_eq!(session_auth_for(&env, &client, &session, &client.address, "update_owner"), Err(Error::Unauthorized));
+    // The allowed function name on another contract
+    assert_eq!(session_auth_for(&env, &client, &session, &other, "swap"), Err(Error::Unauthorized));
+    assert_eq!(client.get_session(&public_key(&env, &session)).uses, 1);
+}
+
+#[test]
+fn test_session_revocation() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let (first, second) = (signing_key(8), signing_key(9));
+    let client = setup_wallet(&env, &owner);
+
+    create_session(&env, &client, &owner, &first, 5_000);
+    create_session(&env, &client, &owner, &second, 5_000);
+
+    let key = public_key(&env, &first);
+    let sig = sign_action(&env, &owner, "revoke_session", &key.clone().to_xdr(&env), client.get_nonce());
+    client.revoke_session(&key, &sig);
+    assert_eq!(session_auth(&env, &client, &first), Err(Error::Unauthorized));
+    assert_eq!(session_auth(&env, &client, &second), Ok(()));
+
+    let sig = sign_action(&env, &owner, "revoke_session", &key.clone().to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_revoke_session(&key, &sig), Err(Ok(Error::NotFound)));
+
+    let sig = sign_action(&env, &owner, "revoke_all_sessions", &Bytes::new(&env), client.get_nonce());
+    client.revoke_all_sessions(&sig);
+    assert_eq!(session_auth(&env, &client, &second), Err(Error::Unauthorized));
+
+    // Sessions created after the bulk revocation work again
+    create_session(&env, &client, &owner, &second, 5_000);
+    assert_eq!(session_auth(&env, &client, &second), Ok(()));
+}
+
+fn derive_session(env: &Env, client: &WalletContractClient, owner: &SigningKey, parent: &SigningKey, session: &SigningKey, scope: &SessionScope) -> Result<(), Error> {
+    let (parent, key) = (public_key(env, parent), public_key(env, session));
+    let payload = (parent.clone(), key.clone(), scope.clone()).to_xdr(env);
+    let sig = sign_action(env, owner, "derive_session", &payload, client.get_nonce());
+    client.try_derive_session(&parent, &key, scope, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_derived_sessions() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let (app, mini_app, widget) = (signing_key(8), signing_key(9), signing_key(10));
+    let client = setup_wallet(&env, &owner);
+    let other = Address::generate(&env);
+
+    let key = public_key(&env, &app);
+    let mut allowed = session_calls(&env, &client.address);
+    allowed.push_back(SessionCall { contract: other.clone(), function: Symbol::new(&env, "swap") });
+    let scope = Symbol::new(&env, "trading");
+    let payload = (key.clone(), scope.clone(), allowed.clone(), 5_000u64, None::<BytesN<32>>).to_xdr(&env);
+    let sig = sign_action(&env, &owner, "create_session", &payload, client.get_nonce());
+    client.create_session(&key, &scope, &allowed, &5_000, &None, &sig);
+
+    // The same scope isn't narrower, and neither is a call the parent lacks
+    let same = SessionScope { scope: scope.clone(), allowed: allowed.clone(), expires_at: 5_000 };
+    assert_eq!(derive_session(&env, &client, &owner, &app, &mini_app, &same), Err(Error::LimitExceeded));
+    let wider = SessionScope {
+        scope: scope.clone(),
+        allowed: vec![&env, SessionCall { contract: other.clone(), function: Symbol::new(&env, "withdraw") }],
+        expires_at: 4_000,
+    };
+    assert_eq!(derive_session(&env, &client, &owner, &app, &mini_app, &wider), Err(Error::LimitExceeded));
+    let longer = SessionScope { scope: scope.clone(), allowed: session_calls(&env, &client.address), expires_at: 6_000 };
+    assert_eq!(derive_session(&env, &client, &owner, &app, &mini_app, &longer), Err(Error::LimitExceeded));
+
+    // Fewer calls is narrower; the child can make only those
+    let narrowed = SessionScope { scope: Symbol::new(&env, "mini_app"), allowed: session_calls(&env, &client.address), expires_at: 5_000 };
+    assert_eq!(derive_session(&env, &client, &owner, &app, &mini_app, &narrowed), Ok(()));
+    assert_eq!(derive_session(&env, &client, &owner, &app, &mini_app, &narrowed), Err(Error::AlreadyExists));
+    assert_eq!(session_auth(&env, &client, &mini_app), Ok(()));
+    assert_eq!(session_auth_for(&env, &client, &mini_app, &other, "swap"), Err(Error::Unauthorized));
+    assert_eq!(client.get_parent_session(&public_key(&env, &mini_app)), Some(key.clone()));
+    assert_eq!(client.get_child_sessions(&key), vec![&env, public_key(&env, &mini_app)]);
+
+    // An earlier expiry is narrower too
+    let shorter = SessionScope { expires_at: 3_000, ..narrowed.clone() };
+    assert_eq!(derive_session(&env, &client, &owner, &mini_app, &widget, &shorter), Ok(()));
+    assert_eq!(session_auth(&env, &client, &widget), Ok(()));
+
+    // Revoking the root revokes everything derived from it
+    let sig = sign_action(&env, &owner, "revoke_session", &key.clone().to_xdr(&env), client.get_nonce());
+    client.revoke_session(&key, &sig);
+    assert_eq!(session_auth(&env, &client, &mini_app), Err(Error::Unauthorized));
+    assert_eq!(session_auth(&env, &client, &widget), Err(Error::Unauthorized));
+    assert_eq!(client.get_child_sessions(&key), Vec::new(&env));
+    assert_eq!(client.get_parent_session(&public_key(&env, &widget)), None);
+    assert_eq!(derive_session(&env, &client, &owner, &app, &mini_app, &narrowed), Err(Error::NotFound));
+}
+
+// ============================================================================
+// NOTIFICATION FILTER TESTS
+// ============================================================================
+
+fn set_filter(env: &Env, client: &WalletContractClient, owner: &SigningKey, channel: &Symbol, filter: &Option<NotificationFilter>) -> Result<(), Error> {
+    let payload = (channel.clone(), filter.clone()).to_xdr(env);
+    let sig = sign_action(env, owner, "set_notification_filter", &payload, client.get_nonce());
+    client.try_set_notification_filter(channel, filter, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_notification_filters() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let push = Symbol::new(&env, "push");
+
+    let filter = NotificationFilter {
+        min_amount: 100,
+        kinds: vec![&env, OpKind::VirtualPayment, OpKind::QuotePayment],
+    };
+    assert_eq!(set_filter(&env, &client, &owner, &push, &Some(filter.clone())), Ok(()));
+    assert_eq!(client.get_notification_filters().get(push.clone()), Some(filter.clone()));
+
+    assert!(filter.matches(OpKind::VirtualPayment, -250));
+    assert!(!filter.matches(OpKind::VirtualPayment, 99));
+    assert!(!filter.matches(OpKind::Lending, 1_000));
+    let everything = NotificationFilter { min_amount: 0, kinds: Vec::new(&env) };
+    assert!(everything.matches(OpKind::Lending, 0));
+
+    let negative = NotificationFilter { min_amount: -1, kinds: Vec::new(&env) };
+    assert_eq!(set_filter(&env, &client, &owner, &push, &Some(negative)), Err(Error::InvalidAmount));
+
+    for i in 1..MAX_NOTIFICATION_CHANNELS {
+        let channel = Symbol::new(&env, ["email", "sms", "slack", "webhook"][i as usize - 1]);
+        assert_eq!(set_filter(&env, &client, &owner, &channel, &Some(everything.clone())), Ok(()));
+    }
+    let extra = Symbol::new(&env, "telegram");
+    assert_eq!(set_filter(&env, &client, &owner, &extra, &Some(everything.clone())), Err(Error::LimitExceeded));
+    // Updating an existing channel is still allowed at the cap
+    assert_eq!(set_filter(&env, &client, &owner, &push, &Some(everything)), Ok(()));
+
+    assert_eq!(set_filter(&env, &client, &owner, &push, &None), Ok(()));
+    assert_eq!(client.get_notification_filters().len(), MAX_NOTIFICATION_CHANNELS - 1);
+    assert_eq!(set_filter(&env, &client, &owner, &push, &None), Err(Error::NotFound));
+}
+
+// ============================================================================
+// XLM RESERVE TESTS
+// ============================================================================
+
+fn set_reserve(env: &Env, client: &WalletContractClient, key: &SigningKey, config: &Option<ReserveConfig>) {
+    let sig = sign_action(env, key, "set_reserve", &config.clone().to_xdr(env), client.get_nonce());
+    client.set_reserve(config, &sig);
+}
+
+fn sign_distribute(env: &Env, client: &WalletContractClient, key: &SigningKey, asset: &Address, amount: i128) -> BytesN<64> {
+    sign_action(env, key, "distribute", &(asset.clone(), amount).to_xdr(env), client.get_nonce())
+}
+
+#[test]
+fn test_reserve_blocks_spending_below_minimum() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let native = create_token(&env);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &native).mint(&client.address, &1_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    set_shares(&env, &client, &key, &vec![&env, holder(&env, 1)]);
+
+    assert_eq!(client.top_up_needed(), 0);
+    set_reserve(&env, &client, &key, &Some(ReserveConfig { native_token: native.clone(), min_reserve: 600 }));
+    assert_eq!(client.top_up_needed(), 0);
+
+    let sig = sign_distribute(&env, &client, &key, &native, 500);
+    assert_eq!(client.try_distribute(&native, &500, &sig), Err(Ok(Error::LimitExceeded)));
+    let sig = sign_distribute(&env, &client, &key, &native, 400);
+    client.distribute(&native, &400, &sig);
+    assert_eq!(token::Client::new(&env, &native).balance(&client.address), 600);
+
+    // Other assets can still be spent while XLM sits below the reserve
+    token::Client::new(&env, &native).burn(&client.address, &100);
+    assert_eq!(client.top_up_needed(), 100);
+    let sig = sign_distribute(&env, &client, &key, &usdc, 500);
+    client.distribute(&usdc, &500, &sig);
+
+    set_reserve(&env, &client, &key, &None);
+    assert_eq!(client.top_up_needed(), 0);
+}
+
+#[test]
+fn test_reserve_checked_in_check_auth() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let native = create_token(&env);
+    token::StellarAssetClient::new(&env, &native).mint(&client.address, &1_000);
+    set_reserve(&env, &client, &key, &Some(ReserveConfig { native_token: native.clone(), min_reserve: 600 }));
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let transfer = |amount: i128| {
+        let context = Context::Contract(ContractContext {
+            contract: native.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (client.address.clone(), Address::generate(&env), amount).into_val(&env),
+        });
+        let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+        let signature = AuthSignature::Ed25519(sign_raw(&env, &key, &message));
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context])
+    };
+
+    assert_eq!(transfer(401), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(transfer(400), Ok(()));
+}
+
+// ============================================================================
+// FEE REIMBURSEMENT TESTS
+// ============================================================================
+
+fn set_fee_config(env: &Env, client: &WalletContractClient, key: &SigningKey, asset: &Address, max_fee: i128) -> Result<(), Error> {
+    let sig = sign_action(env, key, "set_fee_config", &(asset.clone(), max_fee).to_xdr(env), client.get_nonce());
+    client.try_set_fee_config(asset, &max_fee, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_fee_config_needs_a_sponsor() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let usdc = create_token(&env);
+
+    assert_eq!(set_fee_config(&env, &client, &key, &usdc, 50), Err(Error::NotFound));
+    assert_eq!(set_fee_config(&env, &client, &key, &usdc, -1), Err(Error::InvalidAmount));
+
+    client.record_sponsorship(&Address::generate(&env), &1_000);
+    assert_eq!(set_fee_config(&env, &client, &key, &usdc, 50), Ok(()));
+    assert_eq!(client.get_fee_config(), Some(FeeConfig { asset: usdc.clone(), max_fee: 50 }));
+
+    assert_eq!(set_fee_config(&env, &client, &key, &usdc, 0), Ok(()));
+    assert_eq!(client.get_fee_config(), None);
+}
+
+#[test]
+fn test_batch_fee_capped() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    let (relayer, alice) = (Address::generate(&env), Address::generate(&env));
+    client.record_sponsorship(&relayer, &1_000);
+    set_fee_config(&env, &client, &key, &usdc, 50).unwrap();
+
+    // Two fee transfers adding up past the cap
+    let ops = vec![
+        &env,
+        transfer_op(&env, &usdc, &client.address, &relayer, 30),
+        transfer_op(&env, &usdc, &client.address, &alice, 500),
+        transfer_op(&env, &usdc, &client.address, &relayer, 21),
+    ];
+    let sig = sign_batch(&env, &client, &key, &ops);
+    assert_eq!(client.try_execute_batch(&ops, &sig), Err(Ok(Error::LimitExceeded)));
+
+    // Payments to anyone else don't count against it
+    let ops = vec![
+        &env,
+        transfer_op(&env, &usdc, &client.address, &alice, 500),
+        transfer_op(&env, &usdc, &client.address, &relayer, 50),
+    ];
+    client.execute_batch(&ops, &sign_batch(&env, &client, &key, &ops));
+    let usdc = token::Client::new(&env, &usdc);
+    assert_eq!(usdc.balance(&relayer), 50);
+    assert_eq!(usdc.balance(&alice), 500);
+}
+
+#[test]
+fn test_fee_cap_checked_in_check_auth() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let usdc = create_token(&env);
+    let relayer = Address::generate(&env);
+    client.record_sponsorship(&relayer, &1_000);
+    set_fee_config(&env, &client, &key, &usdc, 50).unwrap();
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let pay = |to: &Address, amount: i128| {
+        let context = Context::Contract(ContractContext {
+            contract: usdc.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (client.address.clone(), to.clone(), amount).into_val(&env),
+        });
+        let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+        let signature = AuthSignature::Ed25519(sign_raw(&env, &key, &message));
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context])
+    };
+
+    assert_eq!(pay(&relayer, 51), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(pay(&relayer, 50), Ok(()));
+    assert_eq!(pay(&Address::generate(&env), 500), Ok(()));
+}
+
+// ============================================================================
+// RELAYER ALLOWLIST TESTS
+// ============================================================================
+
+fn set_relayers(env: &Env, client: &WalletContractClient, key: &SigningKey, relayers: &Vec<BytesN<32>>) -> Result<(), Error> {
+    let sig = sign_action(env, key, "set_relayers", &relayers.clone().to_xdr(env), client.get_nonce());
+    client.try_set_relayers(relayers, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+/// The owner's signature on the wallet nonce, co-signed by `relayer`
+fn relayed(env: &Env, client: &WalletContractClient, owner: &SigningKey, relayer: &SigningKey, payload: &BytesN<32>) -> AuthSignature {
+    let message = Bytes::from_slice(env, &auth_message(payload, client.get_nonce()));
+    let mut cosigned = Bytes::from_slice(env, b"relayed");
+    cosigned.extend_from_array(&payload.to_array());
+    AuthSignature::Relayed(RelayedSignature {
+        channel: 0,
+        relayer: public_key(env, relayer),
+        relayer_signature: sign_raw(env, relayer, &cosigned),
+        signature: ChannelSignature::Ed25519(sign_raw(env, owner, &message)),
+        valid_until_ledger: None,
+    })
+}
+
+#[test]
+fn test_set_relayers_validates_list() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let relayer = public_key(&env, &signing_key(7));
+
+    let twice = vec![&env, relayer.clone(), relayer.clone()];
+    assert_eq!(set_relayers(&env, &client, &owner, &twice), Err(Error::AlreadyExists));
+    let mut too_many = Vec::new(&env);
+    for seed in 0..=MAX_RELAYERS as u8 {
+        too_many.push_back(BytesN::from_array(&env, &[seed + 10; 32]));
+    }
+    assert_eq!(set_relayers(&env, &client, &owner, &too_many), Err(Error::LimitExceeded));
+
+    assert_eq!(set_relayers(&env, &client, &owner, &vec![&env, relayer.clone()]), Ok(()));
+    assert_eq!(client.get_relayers(), vec![&env, relayer]);
+    assert_eq!(set_relayers(&env, &client, &owner, &Vec::new(&env)), Ok(()));
+    assert!(client.get_relayers().is_empty());
+}
+
+#[test]
+fn test_check_auth_requires_listed_relayer() {
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (relayer, stranger) = (signing_key(7), signing_key(8));
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let check = |signature: AuthSignature| {
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &Vec::new(&env))
+    };
+
+    // No list yet: relayed signatures have nobody to vouch for them
+    assert_eq!(check(relayed(&env, &client, &owner, &relayer, &payload)), Err(Ok(Error::Unauthorized)));
+
+    set_relayers(&env, &client, &owner, &vec![&env, public_key(&env, &relayer)]).unwrap();
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let direct = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+    assert_eq!(check(direct), Err(Ok(Error::Unauthorized)));
+    assert_eq!(check(relayed(&env, &client, &owner, &stranger, &payload)), Err(Ok(Error::Unauthorized)));
+
+    // A listed key that didn't sign this payload still fails in the host
+    let mut forged = relayed(&env, &client, &owner, &relayer, &payload);
+    if let AuthSignature::Relayed(inner) = &mut forged {
+        inner.relayer_signature = sign_raw(&env, &relayer, &Bytes::from_slice(&env, b"relayed"));
+    }
+    assert!(matches!(check(forged), Err(Err(_))));
+
+    assert_eq!(check(relayed(&env, &client, &owner, &relayer, &payload)), Ok(()));
+    assert_eq!(client.get_nonce(), 2);
+}
+
+// ============================================================================
+// META-TRANSACTION TESTS
+// ============================================================================
+
+fn meta_tx(call: Invocation, nonce: u64, max_fee: i128, expiry: u64) -> MetaTx {
+    MetaTx { call, nonce, max_fee, expiry }
+}
+
+fn sign_meta_tx(env: &Env, client: &WalletContractClient, key: &SigningKey, envelope: &MetaTx) -> BytesN<64> {
+    let mut message = Bytes::from_slice(env, b"execute_meta_tx");
+    message.extend_from_array(&env.ledger().network_id().to_array());
+    message.append(&client.address.clone().to_xdr(env));
+    message.append(&envelope.clone().to_xdr(env));
+    sign_raw(env, key, &message)
+}
+
+#[test]
+fn test_meta_tx_runs_call_and_pays_relayer() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    let (relayer, alice) = (Address::generate(&env), Address::generate(&env));
+    client.record_sponsorship(&relayer, &1_000);
+    set_fee_config(&env, &client, &key, &usdc, 50).unwrap();
+    let nonce = client.get_nonce();
+
+    // Envelope nonces need not be in order, and leave the wallet nonce alone
+    let expiry = env.ledger().timestamp() + 600;
+    for (nonce, amount) in [(9, 100), (3, 200)] {
+        let envelope = meta_tx(transfer_op(&env, &usdc, &client.address, &alice, amount), nonce, 40, expiry);
+        client.execute_meta_tx(&envelope, &sign_meta_tx(&env, &client, &key, &envelope), &25);
+        assert!(client.is_meta_tx_used(&nonce));
+    }
+
+    let usdc = token::Client::new(&env, &usdc);
+    assert_eq!(usdc.balance(&alice), 300);
+    assert_eq!(usdc.balance(&relayer), 50);
+    assert_eq!(client.get_nonce(), nonce);
+}
+
+#[test]
+fn test_meta_tx_replay_and_limits() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    let alice = Address::generate(&env);
+    let now = env.ledger().timestamp();
+    let call = transfer_op(&env, &usdc, &client.address, &alice, 100);
+
+    let envelope = meta_tx(call.clone(), 1, 10, now + 600);
+    let sig = sign_meta_tx(&env, &client, &key, &envelope);
+    // The relayer can't charge past the envelope's cap, or without fee mode on
+    assert_eq!(client.try_execute_meta_tx(&envelope, &sig, &11).err(), Some(Ok(Error::InvalidAmount)));
+    assert_eq!(client.try_execute_meta_tx(&envelope, &sig, &10).err(), Some(Ok(Error::NotFound)));
+    client.execute_meta_tx(&envelope, &sig, &0);
+    assert_eq!(client.try_execute_meta_tx(&envelope, &sig, &0).err(), Some(Ok(Error::ReplayAttack)));
+
+    let too_far = meta_tx(call.clone(), 2, 0, now + MAX_META_TX_WINDOW + 1);
+    let sig = sign_meta_tx(&env, &client, &key, &too_far);
+    assert_eq!(client.try_execute_meta_tx(&too_far, &sig, &0).err(), Some(Ok(Error::LimitExceeded)));
+
+    let envelope = meta_tx(call, 2, 0, now + 600);
+    let sig = sign_meta_tx(&env, &client, &key, &envelope);
+    env.ledger().with_mut(|ledger| ledger.timestamp = now + 601);
+    assert_eq!(client.try_execute_meta_tx(&envelope, &sig, &0).err(), Some(Ok(Error::Expired)));
+    assert!(!client.is_meta_tx_used(&1));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 100);
+}
+
+#[test]
+fn test_meta_tx_cannot_be_replayed_on_another_wallet_or_network() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let key = signing_key(1);
+    let (first, second) = (setup_wallet(&env, &key), setup_wallet(&env, &key));
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&second.address, &1_000);
+    let alice = Address::generate(&env);
+    let call = transfer_op(&env, &usdc, &second.address, &alice, 100);
+
+    // Same owner key, same nonce: a signature for the first wallet doesn't move the second's funds
+    let envelope = meta_tx(call, 1, 0, env.ledger().timestamp() + 600);
+    let sig = sign_meta_tx(&env, &first, &key, &envelope);
+    assert!(matches!(second.try_execute_meta_tx(&envelope, &sig, &0), Err(Err(_))));
+
+    // Nor does one signed for another network
+    let network = env.ledger().network_id().to_array();
+    env.ledger().with_mut(|ledger| ledger.network_id = [7; 32]);
+    let sig = sign_meta_tx(&env, &second, &key, &envelope);
+    env.ledger().with_mut(|ledger| ledger.network_id = network);
+    assert!(matches!(second.try_execute_meta_tx(&envelope, &sig, &0), Err(Err(_))));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 0);
+
+    let sig = sign_meta_tx(&env, &second, &key, &envelope);
+    second.execute_meta_tx(&envelope, &sig, &0);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 100);
+}
+
+// ============================================================================
+// AUTH POLICY TESTS
+// ============================================================================
+
+fn set_auth_policy(env: &Env, client: &WalletContractClient, owner: &SigningKey, action: &str, level: &AuthLevel) -> Result<(), Error> {
+    let action = Symbol::new(env, action);
+    let sig = sign_action(env, owner, "set_auth_policy", &(action.clone(), level.clone()).to_xdr(env), client.get_nonce());
+    client.try_set_auth_policy(&action, level, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+fn cosign(env: &Env, client: &WalletContractClient, cosigner: &SigningKey, action: &str, payload: &Bytes) -> Result<(), Error> {
+    let change = change_id(env, action, payload);
+    let proof = guardian_proof(env, client, cosigner, "cosign_action", &Bytes::from_array(env, &change.to_array()));
+    client.try_cosign_action(&change, &proof).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_auth_policy_needs_device_cosigners() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let phone = signing_key(2);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+
+    assert_eq!(set_auth_policy(&env, &client, &owner, "update_owner", &AuthLevel::Devices(1)), Err(Error::InvalidAmount));
+    assert_eq!(set_auth_policy(&env, &client, &owner, "update_owner", &AuthLevel::OwnerAndGuardian), Err(Error::NotFound));
+    set_auth_policy(&env, &client, &owner, "update_owner", &AuthLevel::Devices(2)).unwrap();
+    assert_eq!(client.get_auth_policy(&Symbol::new(&env, "update_owner")), AuthLevel::Devices(2));
+
+    let new_owner = public_key(&env, &signing_key(3));
+    let payload = Bytes::from_array(&env, &new_owner.to_array());
+    let sig = sign_action(&env, &owner, "update_owner", &payload, client.get_nonce());
+    assert_eq!(client.try_update_owner(&new_owner, &sig), Err(Ok(Error::Unauthorized)));
+
+    // Only other devices count
+    assert_eq!(cosign(&env, &client, &owner, "update_owner", &payload), Err(Error::Unauthorized));
+    assert_eq!(cosign(&env, &client, &signing_key(7), "update_owner", &payload), Err(Error::Unauthorized));
+    cosign(&env, &client, &phone, "update_owner", &payload).unwrap();
+    assert_eq!(cosign(&env, &client, &phone, "update_owner", &payload), Err(Error::AlreadyExists));
+
+    let sig = sign_action(&env, &owner, "update_owner", &payload, client.get_nonce());
+    client.update_owner(&new_owner, &sig);
+    assert_eq!(client.get_owner(), new_owner);
+    assert_eq!(client.get_cosigners(&change_id(&env, "update_owner", &payload)).len(), 0);
+}
+
+#[test]
+fn test_auth_policy_owner_and_guardian_and_matrix_edits() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (phone, guardian) = (signing_key(2), signing_key(5));
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+    set_auth_policy(&env, &client, &owner, "set_batch_limits", &AuthLevel::OwnerAndGuardian).unwrap();
+
+    let limits = BatchLimits { max_ops: 2, max_depth: 1, max_complexity: 10 };
+    let payload = limits.clone().to_xdr(&env);
+    cosign(&env, &client, &phone, "set_batch_limits", &payload).unwrap();
+    let sig = sign_action(&env, &owner, "set_batch_limits", &payload, client.get_nonce());
+    assert_eq!(client.try_set_batch_limits(&limits, &sig), Err(Ok(Error::Unauthorized)));
+
+    cosign(&env, &client, &guardian, "set_batch_limits", &payload).unwrap();
+    let sig = sign_action(&env, &owner, "set_batch_limits", &payload, client.get_nonce());
+    client.set_batch_limits(&limits, &sig);
+    assert_eq!(client.get_batch_limits(), limits);
+
+    // The owner key alone can't lower the row again
+    let (action, level) = (Symbol::new(&env, "set_batch_limits"), AuthLevel::Single);
+    assert_eq!(set_auth_policy(&env, &client, &owner, "set_batch_limits", &level), Err(Error::Unauthorized));
+    cosign(&env, &client, &guardian, "set_auth_policy", &(action.clone(), level.clone()).to_xdr(&env)).unwrap();
+    set_auth_policy(&env, &client, &owner, "set_batch_limits", &level).unwrap();
+    assert_eq!(client.get_auth_policies().len(), 0);
+}
+
+// ============================================================================
+// ALLOWANCE TESTS
+// ============================================================================
+
+fn approve(env: &Env, client: &WalletContractClient, owner: &SigningKey, spender: &Address, asset: &Address, amount: i128, expiry: u64) -> Result<(), Error> {
+    let payload = (spender.clone(), asset.clone(), amount, expiry).to_xdr(env);
+    let sig = sign_action(env, owner, "approve", &payload, client.get_nonce());
+    client.try_approve(spender, asset, &amount, &expiry, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_allowance_pulls_up_to_approved_amount() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let asset = create_token(&env);
+    env.mock_all_auths();
+    token::StellarAssetClient::new(&env, &asset).mint(&client.address, &1_000);
+    let (dapp, merchant) = (Address::generate(&env), Address::generate(&env));
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    assert_eq!(approve(&env, &client, &owner, &dapp, &asset, 0, 2_000), Err(Error::InvalidAmount));
+    assert_eq!(approve(&env, &client, &owner, &dapp, &asset, 300, 1_000), Err(Error::Expired));
+    assert_eq!(approve(&env, &client, &owner, &dapp, &asset, 300, 1_001 + MAX_ALLOWANCE_WINDOW), Err(Error::LimitExceeded));
+    approve(&env, &client, &owner, &dapp, &asset, 300, 2_000).unwrap();
+
+    assert_eq!(client.pull(&dapp, &asset, &merchant, &100), 200);
+    assert_eq!(client.pull(&dapp, &asset, &merchant, &100), 100);
+    assert_eq!(client.try_pull(&dapp, &asset, &merchant, &101), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(token::Client::new(&env, &asset).balance(&merchant), 200);
+    assert_eq!(client.get_recent_ops(&1).get(0).unwrap().kind, OpKind::AllowancePull);
+
+    // Other spenders and assets have nothing to pull
+    assert_eq!(client.try_pull(&merchant, &asset, &merchant, &1), Err(Ok(Error::NotFound)));
+    assert_eq!(client.try_pull(&dapp, &create_token(&env), &merchant, &1), Err(Ok(Error::NotFound)));
+
+    // Spending it all clears it
+    client.pull(&dapp, &asset, &merchant, &100);
+    assert_eq!(client.get_allowance(&dapp, &asset), None);
+}
+
+#[test]
+fn test_allowance_expires_and_is_revoked() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let asset = create_token(&env);
+    env.mock_all_auths();
+    token::StellarAssetClient::new(&env, &asset).mint(&client.address, &1_000);
+    let dapp = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    approve(&env, &client, &owner, &dapp, &asset, 300, 2_000).unwrap();
+    assert_eq!(client.get_allowance(&dapp, &asset), Some(Allowance { amount: 300, expiry: 2_000 }));
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert_eq!(client.try_pull(&dapp, &asset, &dapp, &1), Err(Ok(Error::NotFound)));
+
+    approve(&env, &client, &owner, &dapp, &asset, 300, 3_000).unwrap();
+    let payload = (dapp.clone(), asset.clone()).to_xdr(&env);
+    let sig = sign_action(&env, &owner, "revoke_allowance", &payload, client.get_nonce());
+    client.revoke_allowance(&dapp, &asset, &sig);
+    assert_eq!(client.get_allowance(&dapp, &asset), None);
+    let sig = sign_action(&env, &owner, "revoke_allowance", &payload, client.get_nonce());
+    assert_eq!(client.try_revoke_allowance(&dapp, &asset, &sig), Err(Ok(Error::NotFound)));
+}
+
+// ============================================================================
+// SUBSCRIPTION TESTS
+// ============================================================================
+
+fn create_subscription(env: &Env, client: &WalletContractClient, owner: &SigningKey, merchant: &Address, asset: &Address, amount: i128, interval: u64) -> Result<u32, Error> {
+    let payload = (merchant.clone(), asset.clone(), amount, interval).to_xdr(env);
+    let sig = sign_action(env, owner, "create_subscription", &payload, client.get_nonce());
+    client.try_create_subscription(merchant, asset, &amount, &interval, &sig).map(|id| id.unwrap()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_subscription_collects_once_per_interval() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let asset = create_token(&env);
+    env.mock_all_auths();
+    token::StellarAssetClient::new(&env, &asset).mint(&client.address, &250);
+    let merchant = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+
+    assert_eq!(create_subscription(&env, &client, &owner, &merchant, &asset, 0, DAY), Err(Error::InvalidAmount));
+    assert_eq!(create_subscription(&env, &client, &owner, &merchant, &asset, 100, DAY - 1), Err(Error::LimitExceeded));
+    let id = create_subscription(&env, &client, &owner, &merchant, &asset, 100, DAY).unwrap();
+
+    // Due right away, then once per day
+    assert!(client.collect(&id));
+    assert_eq!(client.try_collect(&id), Err(Ok(Error::Timelocked)));
+    assert_eq!(client.get_subscription(&id).unwrap().next_due, 11 * DAY);
+
+    // Days nobody collected are skipped, not charged together
+    env.ledger().with_mut(|li| li.timestamp = 13 * DAY + 5);
+    assert!(client.collect(&id));
+    assert_eq!(token::Client::new(&env, &asset).balance(&merchant), 200);
+    assert_eq!(client.get_subscription(&id).unwrap().next_due, 14 * DAY);
+    assert_eq!(client.get_recent_ops(&1).get(0).unwrap().kind, OpKind::Subscription);
+
+    // 50 left can't cover the next payment
+    env.ledger().with_mut(|li| li.timestamp = 14 * DAY);
+    assert!(!client.collect(&id));
+    assert_eq!(client.get_subscription(&id).unwrap().next_due, 15 * DAY);
+    assert_eq!(token::Client::new(&env, &asset).balance(&merchant), 200);
+}
+
+#[test]
+fn test_subscription_pause_and_cancel() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let asset = create_token(&env);
+    env.mock_all_auths();
+    token::StellarAssetClient::new(&env, &asset).mint(&client.address, &1_000);
+    let merchant = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let id = create_subscription(&env, &client, &owner, &merchant, &asset, 100, DAY).unwrap();
+
+    let sig = sign_action(&env, &owner, "set_subscription_paused", &(id, true).to_xdr(&env), client.get_nonce());
+    client.set_subscription_paused(&id, &true, &sig);
+    assert_eq!(client.try_collect(&id), Err(Ok(Error::Unauthorized)));
+
+    // Resuming later starts from the resume
+    env.ledger().with_mut(|li| li.timestamp = 20 * DAY);
+    let sig = sign_action(&env, &owner, "set_subscription_paused", &(id, false).to_xdr(&env), client.get_nonce());
+    client.set_subscription_paused(&id, &false, &sig);
+    assert_eq!(client.get_subscription(&id).unwrap().next_due, 20 * DAY);
+    assert!(client.collect(&id));
+
+    let payload = Bytes::from_array(&env, &id.to_be_bytes());
+    let sig = sign_action(&env, &owner, "cancel_subscription", &payload, client.get_nonce());
+    client.cancel_subscription(&id, &sig);
+    assert_eq!(client.list_subscriptions().len(), 0);
+    assert_eq!(client.try_collect(&id), Err(Ok(Error::NotFound)));
+}
+
+// ============================================================================
+// SCHEDULED TRANSACTION TESTS
+// ============================================================================
+
+fn schedule(env: &Env, client: &WalletContractClient, owner: &SigningKey, op: &Invocation, after: u32) -> Result<u32, Error> {
+    let sig = sign_action(env, owner, "schedule", &(op.clone(), after).to_xdr(env), client.get_nonce());
+    client.try_schedule(op, &after, &sig).map(|id| id.unwrap()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_scheduled_transfer_runs_once_after_its_ledger() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_000);
+    let landlord = Address::generate(&env);
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+    let rent = transfer_op(&env, &usdc, &client.address, &landlord, 600);
+
+    assert_eq!(schedule(&env, &client, &owner, &rent, 100), Err(Error::Expired));
+    assert_eq!(schedule(&env, &client, &owner, &rent, 101 + MAX_SCHEDULE_DELAY), Err(Error::LimitExceeded));
+    let id = schedule(&env, &client, &owner, &rent, 200).unwrap();
+    assert_eq!(client.try_execute_scheduled(&id).err(), Some(Ok(Error::Timelocked)));
+
+    // Any keeper can run it once its ledger has passed
+    env.ledger().with_mut(|li| li.sequence_number = 201);
+    client.execute_scheduled(&id);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&landlord), 600);
+    assert_eq!(client.try_execute_scheduled(&id).err(), Some(Ok(Error::NotFound)));
+
+    // Past the window it no longer runs. Scheduling kept the wallet, which
+    // nobody touched since, alive to say so.
+    let id = schedule(&env, &client, &owner, &rent, 300).unwrap();
+    assert_eq!(instance_ttl(&env, &client), 100 + SCHEDULE_EXECUTION_WINDOW);
+    env.ledger().with_mut(|li| li.sequence_number = 301 + SCHEDULE_EXECUTION_WINDOW);
+    assert_eq!(client.try_execute_scheduled(&id).err(), Some(Ok(Error::Expired)));
+}
+
+#[test]
+fn test_scheduled_transfer_cancelled() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let rent = transfer_op(&env, &usdc, &client.address, &Address::generate(&env), 600);
+    let id = schedule(&env, &client, &owner, &rent, 200).unwrap();
+
+    let payload = Bytes::from_array(&env, &id.to_be_bytes());
+    let sig = sign_action(&env, &owner, "cancel_scheduled", &payload, client.get_nonce());
+    client.cancel_scheduled(&id, &sig);
+    assert!(client.get_scheduled(&id).is_none());
+
+    env.ledger().with_mut(|li| li.sequence_number = 201);
+    assert_eq!(client.try_execute_scheduled(&id).err(), Some(Ok(Error::NotFound)));
+}
+
+// ============================================================================
+// LARGE TRANSFER TESTS
+// ============================================================================
+
+fn set_large_transfer_threshold(env: &Env, client: &WalletContractClient, owner: &SigningKey, asset: &Address, threshold: i128) {
+    let sig = sign_action(env, owner, "set_large_transfer_threshold", &(asset.clone(), threshold).to_xdr(env), client.get_nonce());
+    client.set_large_transfer_threshold(asset, &threshold, &sig);
+}
+
+fn queue_transfer(env: &Env, client: &WalletContractClient, owner: &SigningKey, asset: &Address, to: &Address, amount: i128) -> u32 {
+    let sig = sign_action(env, owner, "queue_transfer", &(asset.clone(), to.clone(), amount).to_xdr(env), client.get_nonce());
+    client.queue_transfer(asset, to, &amount, &sig)
+}
+
+#[test]
+fn test_large_transfers_wait_in_the_queue() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &2_000);
+    let to = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 500);
+
+    // Immediate paths refuse more than the threshold, split or not
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let send = |amount: i128| {
+        let context = Context::Contract(ContractContext {
+            contract: usdc.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (client.address.clone(), to.clone(), amount).into_val(&env),
+        });
+        let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+        let signature = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context])
+    };
+    assert_eq!(send(501), Err(Ok(Error::Timelocked)));
+    assert_eq!(send(500), Ok(()));
+    let ops = vec![&env, transfer_op(&env, &usdc, &client.address, &to, 300), transfer_op(&env, &usdc, &client.address, &to, 300)];
+    assert_eq!(client.try_execute_batch(&ops, &sign_batch(&env, &client, &owner, &ops)), Err(Ok(Error::Timelocked)));
+
+    let id = queue_transfer(&env, &client, &owner, &usdc, &to, 1_500);
+    assert_eq!(client.try_execute_queued_transfer(&id), Err(Ok(Error::Timelocked)));
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    client.execute_queued_transfer(&id);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&to), 1_500);
+    assert_eq!(client.get_queued_transfer(&id), None);
+}
+
+#[test]
+fn test_large_transfer_threshold_counts_approvals_and_burns() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::{IntoVal, Val};
+
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let spender = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 500);
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let authorize = |fn_name: &str, args: Vec<Val>| {
+        let context = Context::Contract(ContractContext {
+            contract: usdc.clone(),
+            fn_name: Symbol::new(&env, fn_name),
+            args,
+        });
+        let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+        let signature = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context])
+    };
+
+    // The spender could transfer_from all of it later, without the wallet
+    let approve = |amount: i128| (client.address.clone(), spender.clone(), amount, 1_000u32).into_val(&env);
+    assert_eq!(authorize("approve", approve(501)), Err(Ok(Error::Timelocked)));
+    assert_eq!(authorize("approve", approve(500)), Ok(()));
+    let burn = |amount: i128| (client.address.clone(), amount).into_val(&env);
+    assert_eq!(authorize("burn", burn(501)), Err(Ok(Error::Timelocked)));
+    assert_eq!(authorize("burn", burn(500)), Ok(()));
+}
+
+#[test]
+fn test_large_transfer_cancelled_by_device_and_raise_delayed() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let phone = signing_key(2);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 500);
+    let id = queue_transfer(&env, &client, &owner, &usdc, &Address::generate(&env), 1_500);
+
+    let payload = Bytes::from_array(&env, &id.to_be_bytes());
+    let proof = guardian_proof(&env, &client, &signing_key(7), "cancel_queued_transfer", &payload);
+    assert_eq!(client.try_cancel_queued_transfer(&id, &proof), Err(Ok(Error::Unauthorized)));
+    let proof = SignerProof::Ed25519(
+        public_key(&env, &phone),
+        sign_action(&env, &phone, "cancel_queued_transfer", &payload, client.get_nonce()),
+    );
+    client.cancel_queued_transfer(&id, &proof);
+    assert_eq!(client.get_queued_transfer(&id), None);
+
+    // Raising the threshold waits out the delay; lowering doesn't
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 5_000);
+    assert_eq!(client.get_large_transfer_threshold(&usdc), 500);
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    assert_eq!(client.get_large_transfer_threshold(&usdc), 5_000);
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 100);
+    assert_eq!(client.get_large_transfer_threshold(&usdc), 100);
+}
+
+#[test]
+fn test_subscription_schedule_and_queue_ids_are_independent() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let to = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 500);
+
+    // Each feature numbers its own records, so none overwrites another's
+    assert_eq!(create_subscription(&env, &client, &owner, &to, &usdc, 100, DAY), Ok(0));
+    let rent = transfer_op(&env, &usdc, &client.address, &to, 100);
+    assert_eq!(schedule(&env, &client, &owner, &rent, 200), Ok(0));
+    assert_eq!(queue_transfer(&env, &client, &owner, &usdc, &to, 1_500), 0);
+    assert_eq!(create_subscription(&env, &client, &owner, &to, &usdc, 200, DAY), Ok(1));
+
+    assert_eq!(client.get_subscription(&0).unwrap().amount, 100);
+    assert_eq!(client.get_scheduled(&0).unwrap().execute_after_ledger, 200);
+    assert_eq!(client.get_queued_transfer(&0).unwrap().amount, 1_500);
+}
+
+// ============================================================================
+// CONTACT TESTS
+// ============================================================================
+
+fn add_contact(env: &Env, client: &WalletContractClient, owner: &SigningKey, address: &Address, label: &str) {
+    let label = String::from_str(env, label);
+    let sig = sign_action(env, owner, "add_contact", &(address.clone(), label.clone()).to_xdr(env), client.get_nonce());
+    client.add_contact(address, &label, &sig);
+}
+
+fn set_contacts_only(env: &Env, client: &WalletContractClient, owner: &SigningKey, enabled: bool) {
+    let sig = sign_action(env, owner, "set_contacts_only", &enabled.to_xdr(env), client.get_nonce());
+    client.set_contacts_only(&enabled, &sig);
+}
+
+#[test]
+fn test_contacts_only_mode_restricts_transfers() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &2_000);
+    let landlord = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    add_contact(&env, &client, &owner, &landlord, "Landlord");
+    set_contacts_only(&env, &client, &owner, true);
+    assert!(client.is_contacts_only());
+
+    // A new contact is only spendable-to after the cooling-off
+    let pay = |to: &Address| {
+        let ops = vec![&env, transfer_op(&env, &usdc, &client.address, to, 100)];
+        client.try_execute_batch(&ops, &sign_batch(&env, &client, &owner, &ops)).map(|_| ())
+    };
+    assert_eq!(pay(&landlord), Err(Ok(Error::Unauthorized)));
+    env.ledger().with_mut(|li| li.timestamp += CONTACT_COOLING_OFF);
+    assert_eq!(pay(&landlord), Ok(()));
+    assert_eq!(pay(&stranger), Err(Ok(Error::Unauthorized)));
+
+    let sig = sign_action(&env, &owner, "queue_transfer", &(usdc.clone(), stranger.clone(), 100i128).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_queue_transfer(&usdc, &stranger, &100, &sig), Err(Ok(Error::Unauthorized)));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&landlord), 100);
+}
+
+#[test]
+fn test_contacts_only_covers_approvals_distributions_and_quotes() {
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    let setup = setup_quotes(&env);
+    let (client, owner) = (&setup.client, &setup.owner);
+    let stranger = Address::generate(&env);
+    set_contacts_only(&env, client, owner, true);
+
+    let approve = Invocation {
+        contract: setup.usdc.clone(),
+        fn_name: Symbol::new(&env, "approve"),
+        args: (client.address.clone(), stranger.clone(), 100i128, 1_000u32).into_val(&env),
+        auth: Vec::new(&env),
+    };
+    let ops = vec![&env, approve];
+    assert_eq!(client.try_execute_batch(&ops, &sign_batch(&env, client, owner, &ops)), Err(Ok(Error::Unauthorized)));
+
+    set_shares(&env, client, owner, &vec![&env, ShareHolder { holder: stranger.clone(), shares: 1 }]);
+    let sig = sign_action(&env, owner, "distribute", &(setup.usdc.clone(), 100i128).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_distribute(&setup.usdc, &100, &sig), Err(Ok(Error::Unauthorized)));
+
+    let quote = quote(&env, &setup, 1, &stranger);
+    let blob = sign_quote(&env, &setup.maker, &quote);
+    let payload = (quote.quote_id.clone(), blob.clone()).to_xdr(&env);
+    let sig = sign_action(&env, owner, "accept_quote", &payload, client.get_nonce());
+    assert_eq!(client.try_accept_quote(&quote.quote_id, &blob, &sig), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_contacts_only_switch_off_waits_out_cooling_off() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let landlord = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    add_contact(&env, &client, &owner, &landlord, "Landlord");
+    let spendable_at = client.get_contact(&landlord).unwrap().spendable_at;
+
+    // Relabelling keeps the original cooling-off
+    env.ledger().with_mut(|li| li.timestamp += DAY / 2);
+    add_contact(&env, &client, &owner, &landlord, "Rent");
+    let contact = client.get_contact(&landlord).unwrap();
+    assert_eq!(contact.label, String::from_str(&env, "Rent"));
+    assert_eq!(contact.spendable_at, spendable_at);
+    assert_eq!(client.try_add_contact(&client.address, &String::from_str(&env, "Me"), &BytesN::from_array(&env, &[0u8; 64])), Err(Ok(Error::InvalidOwner)));
+
+    set_contacts_only(&env, &client, &owner, true);
+    set_contacts_only(&env, &client, &owner, false);
+    assert!(client.is_contacts_only());
+    env.ledger().with_mut(|li| li.timestamp += CONTACT_COOLING_OFF);
+    assert!(!client.is_contacts_only());
+
+    let sig = sign_action(&env, &owner, "remove_contact", &landlord.clone().to_xdr(&env), client.get_nonce());
+    client.remove_contact(&landlord, &sig);
+    assert_eq!(client.list_contacts().len(), 0);
+}
+
+// ============================================================================
+// VELOCITY LIMIT TESTS
+// ============================================================================
+
+fn set_velocity_limit(env: &Env, client: &WalletContractClient, owner: &SigningKey, max_ops: u32, window: u64) {
+    let sig = sign_action(env, owner, "set_velocity_limit", &(max_ops, window).to_xdr(env), client.get_nonce());
+    client.set_velocity_limit(&max_ops, &window, &sig);
+}
+
+fn authorize(env: &Env, client: &WalletContractClient, owner: &SigningKey) -> Result<(), Error> {
+    let payload = BytesN::from_array(env, &[5u8; 32]);
+    let message = Bytes::from_slice(env, &auth_message(&payload, client.get_nonce()));
+    try_check_auth(env, client, &payload, AuthSignature::Ed25519(sign_raw(env, owner, &message)))
+}
+
+#[test]
+fn test_velocity_limit_slides_over_the_window() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    set_velocity_limit(&env, &client, &owner, 3, 3_600);
+
+    for _ in 0..3 {
+        authorize(&env, &client, &owner).unwrap();
+    }
+    assert_eq!(client.get_velocity_usage(), 3);
+    assert_eq!(authorize(&env, &client, &owner), Err(Error::LimitExceeded));
+
+    // Halfway into the next window half of the last one still counts
+    env.ledger().with_mut(|li| li.timestamp += 3_600 + 1_800);
+    assert_eq!(client.get_velocity_usage(), 1);
+    authorize(&env, &client, &owner).unwrap();
+    authorize(&env, &client, &owner).unwrap();
+    assert_eq!(authorize(&env, &client, &owner), Err(Error::LimitExceeded));
+
+    set_velocity_limit(&env, &client, &owner, 0, 0);
+    assert_eq!(client.get_velocity_limit(), None);
+    authorize(&env, &client, &owner).unwrap();
+}
+
+#[test]
+fn test_velocity_bypass_needs_a_second_device() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let phone = signing_key(2);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    set_velocity_limit(&env, &client, &owner, 1, 3_600);
+    authorize(&env, &client, &owner).unwrap();
+    assert_eq!(authorize(&env, &client, &owner), Err(Error::LimitExceeded));
+
+    let payload = 2u32.to_xdr(&env);
+    let sig = sign_action(&env, &owner, "grant_velocity_bypass", &payload, client.get_nonce());
+    assert_eq!(client.try_grant_velocity_bypass(&2, &sig), Err(Ok(Error::Unauthorized)));
+    cosign(&env, &client, &phone, "grant_velocity_bypass", &payload).unwrap();
+    let sig = sign_action(&env, &owner, "grant_velocity_bypass", &payload, client.get_nonce());
+    client.grant_velocity_bypass(&2, &sig);
+
+    authorize(&env, &client, &owner).unwrap();
+    authorize(&env, &client, &owner).unwrap();
+    assert_eq!(client.get_velocity_bypass(), None);
+    assert_eq!(authorize(&env, &client, &owner), Err(Error::LimitExceeded));
+}
+
+// ============================================================================
+// FIAT LIMIT TESTS
+// ============================================================================
+
+/// Oracle adapter quoting in USD, with prices set by the test
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, asset: OracleAsset, price: i128, timestamp: u64) {
+        env.storage().instance().set(&asset, &(price, timestamp));
+    }
+
+    pub fn price(env: Env, asset: OracleAsset) -> (i128, u64) {
+        if asset == OracleAsset::Other(Symbol::new(&env, "USD")) {
+            return (100_000_000_000_000, env.ledger().timestamp());
+        }
+        env.storage().instance().get(&asset).unwrap()
+    }
+}
+
+fn set_fiat_limit(env: &Env, client: &WalletContractClient, owner: &SigningKey, currency: &str, daily_limit: i128, on_stale: StalePrice) {
+    let currency = Symbol::new(env, currency);
+    let payload = (currency.clone(), daily_limit, on_stale).to_xdr(env);
+    let sig = sign_action(env, owner, "set_fiat_limit", &payload, client.get_nonce());
+    client.set_fiat_limit(&currency, &daily_limit, &on_stale, &sig);
+}
+
+#[test]
+fn test_fiat_limit_converts_transfers_through_the_oracle() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    // 1 USDC = 1 USD, 1 MXN = 0.05 USD
+    let mxn = OracleAsset::Other(Symbol::new(&env, "MXN"));
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &(10 * DAY));
+    oracle.set_price(&mxn, &5_000_000_000_000, &(10 * DAY));
+
+    let sig = sign_action(&env, &owner, "set_oracle", &oracle.address.clone().to_xdr(&env), client.get_nonce());
+    client.set_oracle(&oracle.address, &sig);
+    // 1,000 MXN a day
+    set_fiat_limit(&env, &client, &owner, "MXN", 100_000, StalePrice::Refuse);
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let send = |amount: i128| {
+        let context = Context::Contract(ContractContext {
+            contract: usdc.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (client.address.clone(), Address::generate(&env), amount).into_val(&env),
+        });
+        let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+        let signature = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context])
+    };
+    // 40 USDC is 800 MXN, 15 more would pass the limit
+    assert_eq!(send(400_000_000), Ok(()));
+    assert_eq!(client.get_fiat_spent_today(), 80_000);
+    assert_eq!(send(150_000_000), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(send(100_000_000), Ok(()));
+
+    // A stale price refuses the transfer, or once `Skip` applies lets it through
+    env.ledger().with_mut(|li| li.timestamp += DAY);
+    assert_eq!(send(10_000_000), Err(Ok(Error::Expired)));
+    set_fiat_limit(&env, &client, &owner, "MXN", 100_000, StalePrice::Skip);
+    assert_eq!(send(10_000_000), Err(Ok(Error::Expired)));
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    assert_eq!(send(10_000_000), Ok(()));
+    assert_eq!(client.get_fiat_spent_today(), 0);
+
+    // Priced in the oracle's own base currency
+    set_fiat_limit(&env, &client, &owner, "USD", 5_000, StalePrice::Refuse);
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &env.ledger().timestamp());
+    assert_eq!(send(500_000_000), Ok(()));
+    assert_eq!(client.get_fiat_spent_today(), 5_000);
+    assert_eq!(send(100_000), Err(Ok(Error::LimitExceeded)));
+}
+
+#[test]
+fn test_fiat_limit_loosens_and_oracle_swaps_after_the_delay() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    let other = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let set_oracle = |oracle: &Address| {
+        let sig = sign_action(&env, &owner, "set_oracle", &oracle.clone().to_xdr(&env), client.get_nonce());
+        client.set_oracle(oracle, &sig);
+    };
+    let daily_limit = || client.get_fiat_limit().map(|limit| limit.daily_limit);
+
+    // The first oracle and limit apply at once
+    set_oracle(&oracle.address);
+    assert_eq!(client.get_oracle(), Some(oracle.address.clone()));
+    set_fiat_limit(&env, &client, &owner, "USD", 5_000, StalePrice::Refuse);
+    assert_eq!(daily_limit(), Some(5_000));
+
+    // Raising waits, tightening applies at once and drops the raise
+    set_fiat_limit(&env, &client, &owner, "USD", 50_000, StalePrice::Refuse);
+    assert_eq!(daily_limit(), Some(5_000));
+    assert_eq!(client.get_pending_fiat_limit().unwrap().effective_at, 10 * DAY + LARGE_TRANSFER_DELAY);
+    set_fiat_limit(&env, &client, &owner, "USD", 1_000, StalePrice::Refuse);
+    assert_eq!(daily_limit(), Some(1_000));
+    assert_eq!(client.get_pending_fiat_limit(), None);
+
+    // Removing waits too
+    set_fiat_limit(&env, &client, &owner, "USD", 0, StalePrice::Refuse);
+    assert_eq!(daily_limit(), Some(1_000));
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    assert_eq!(daily_limit(), None);
+
+    // Swapping the oracle waits, setting the current one drops the swap
+    set_oracle(&other.address);
+    set_oracle(&oracle.address);
+    assert_eq!(client.get_pending_oracle(), None);
+    set_oracle(&other.address);
+    assert_eq!(client.get_oracle(), Some(oracle.address.clone()));
+    assert_eq!(client.get_pending_oracle().unwrap().oracle, other.address);
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    assert_eq!(client.get_oracle(), Some(other.address.clone()));
+    assert_eq!(client.get_pending_oracle(), None);
+}
+
+#[test]
+fn test_fiat_limit_counts_approvals_batches_pulls_and_collections() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &10_000_000_000);
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    env.ledger().with_mut(|li| {
+        li.timestamp = 10 * DAY;
+        li.sequence_number = 100;
+    });
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &(10 * DAY));
+    let sig = sign_action(&env, &owner, "set_oracle", &oracle.address.clone().to_xdr(&env), client.get_nonce());
+    client.set_oracle(&oracle.address, &sig);
+    // $50 a day
+    set_fiat_limit(&env, &client, &owner, "USD", 5_000, StalePrice::Refuse);
+    let merchant = Address::generate(&env);
+
+    // Approving 10 USDC spends it
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let context = Context::Contract(ContractContext {
+        contract: usdc.clone(),
+        fn_name: Symbol::new(&env, "approve"),
+        args: (client.address.clone(), merchant.clone(), 100_000_000i128, 1_000u32).into_val(&env),
+    });
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let signature = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+    env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context]).unwrap();
+    assert_eq!(client.get_fiat_spent_today(), 1_000);
+
+    let ops = vec![&env, transfer_op(&env, &usdc, &client.address, &merchant, 100_000_000)];
+    client.execute_batch(&ops, &sign_batch(&env, &client, &owner, &ops));
+    assert_eq!(client.get_fiat_spent_today(), 2_000);
+
+    // Scheduling spends nothing until the call runs
+    let rent = transfer_op(&env, &usdc, &client.address, &merchant, 300_000_000);
+    let id = schedule(&env, &client, &owner, &rent, 200).unwrap();
+    assert_eq!(client.get_fiat_spent_today(), 2_000);
+
+    approve(&env, &client, &owner, &merchant, &usdc, 100_000_000, 11 * DAY).unwrap();
+    client.pull(&merchant, &usdc, &merchant, &100_000_000);
+    assert_eq!(client.get_fiat_spent_today(), 3_000);
+
+    let subscription = create_subscription(&env, &client, &owner, &merchant, &usdc, 100_000_000, DAY).unwrap();
+    assert!(client.collect(&subscription));
+    assert_eq!(client.get_fiat_spent_today(), 4_000);
+
+    // $30 more would pass the limit
+    env.ledger().with_mut(|li| li.sequence_number = 201);
+    assert_eq!(client.try_execute_scheduled(&id).err(), Some(Ok(Error::LimitExceeded)));
+    assert_eq!(client.get_fiat_spent_today(), 4_000);
+}
+
+// ============================================================================
+// OWN ACCOUNT TESTS
+// ============================================================================
+
+fn add_own_account(env: &Env, client: &WalletContractClient, owner: &SigningKey, address: &Address) -> Result<(), Error> {
+    let sig = sign_action(env, owner, "add_own_account", &address.clone().to_xdr(env), client.get_nonce());
+    client.try_add_own_account(address, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_transfers_to_own_accounts_skip_the_fiat_limit() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &10_000_000_000);
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &(10 * DAY));
+    let sig = sign_action(&env, &owner, "set_oracle", &oracle.address.clone().to_xdr(&env), client.get_nonce());
+    client.set_oracle(&oracle.address, &sig);
+    set_fiat_limit(&env, &client, &owner, "USD", 5_000, StalePrice::Refuse);
+    let (vault, merchant) = (Address::generate(&env), Address::generate(&env));
+
+    assert_eq!(add_own_account(&env, &client, &owner, &client.address), Err(Error::InvalidOwner));
+    add_own_account(&env, &client, &owner, &vault).unwrap();
+    assert_eq!(add_own_account(&env, &client, &owner, &vault), Err(Error::AlreadyExists));
+    let send = |to: &Address, amount: i128| {
+        let ops = vec![&env, transfer_op(&env, &usdc, &client.address, to, amount)];
+        client.execute_batch(&ops, &sign_batch(&env, &client, &owner, &ops));
+    };
+
+    // Still cooling off, so it counts like any payment
+    assert!(!client.is_own_account(&vault));
+    send(&vault, 100_000_000);
+    assert_eq!(client.get_fiat_spent_today(), 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 11 * DAY);
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &(11 * DAY));
+    assert!(client.is_own_account(&vault));
+    send(&vault, 1_000_000_000);
+    assert_eq!(client.get_fiat_spent_today(), 0);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&vault), 1_100_000_000);
+    send(&merchant, 100_000_000);
+    assert_eq!(client.get_fiat_spent_today(), 1_000);
+
+    // Unlinked, it counts again
+    let sig = sign_action(&env, &owner, "remove_own_account", &vault.clone().to_xdr(&env), client.get_nonce());
+    client.remove_own_account(&vault, &sig);
+    assert_eq!(client.list_own_accounts().len(), 0);
+    send(&vault, 100_000_000);
+    assert_eq!(client.get_fiat_spent_today(), 2_000);
+}
+
+// ============================================================================
+// SIGNATURE ERROR TESTS
+// ============================================================================
+
+fn raw_check_auth(env: &Env, client: &WalletContractClient, signature: AuthSignature) -> Result<(), Result<Error, soroban_sdk::InvokeError>> {
+    use soroban_sdk::IntoVal;
+    let payload = BytesN::from_array(env, &[5u8; 32]);
+    env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(env), &Vec::new(env))
+}
+
+#[test]
+fn test_malformed_owner_signature_is_typed_error() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    // S above the group order, and a zeroed signature (small-order R)
+    for bytes in [[0xffu8; 64], [0u8; 64]] {
+        let signature = BytesN::from_array(&env, &bytes);
+        assert_eq!(client.try_revoke_all_sessions(&signature), Err(Ok(Error::InvalidSignature)));
+    }
+    assert_eq!(client.get_nonce(), 0);
+
+    let sig = sign_action(&env, &key, "revoke_all_sessions", &Bytes::new(&env), 0);
+    client.revoke_all_sessions(&sig);
+}
+
+#[test]
+fn test_malformed_check_auth_signature_is_typed_error() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+
+    let zeroed = AuthSignature::Ed25519(BytesN::from_array(&env, &[0u8; 64]));
+    assert_eq!(raw_check_auth(&env, &client, zeroed), Err(Ok(Error::InvalidSignature)));
+
+    // A well-formed signature by the wrong key still fails in the host
+    let message = Bytes::from_slice(&env, &auth_message(&BytesN::from_array(&env, &[5u8; 32]), 0));
+    let forged = AuthSignature::Ed25519(sign_raw(&env, &signing_key(2), &message));
+    assert!(matches!(raw_check_auth(&env, &client, forged), Err(Err(_))));
+}
+
+#[test]
+fn test_high_s_passkey_signature_is_typed_error() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    register_passkey(&env, &client, &key);
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let mut signed = assertion(&env, &auth_message(&payload, client.get_nonce()), 0x01);
+    let mut bytes = signed.signature.to_array();
+    bytes[32..].copy_from_slice(&[0xffu8; 32]);
+    signed.signature = BytesN::from_array(&env, &bytes);
+
+    assert_eq!(raw_check_auth(&env, &client, AuthSignature::Secp256r1(signed)), Err(Ok(Error::InvalidSignature)));
+}
+
+// ============================================================================
+// DEV MODE TESTS (cargo test --features dev-mode)
+// ============================================================================
+
+#[cfg(feature = "dev-mode")]
+#[test]
+fn test_dev_hooks() {
+    let env = create_test_env();
+    let key = signing_key(1);
+    let client = setup_wallet(&env, &key);
+    let session = signing_key(9);
+    let start = env.ledger().timestamp();
+    create_session(&env, &client, &key, &session, start + 3600);
+
+    let sig = sign_action(&env, &key, "dev_fast_forward", &3601u64.to_xdr(&env), client.get_nonce());
+    client.dev_fast_forward(&3601, &sig);
+    assert_eq!(client.dev_now(), start + 3601);
+    assert_eq!(client.try_get_session(&public_key(&env, &session)), Err(Ok(Error::Expired)));
+
+    let sig = sign_action(&env, &key, "dev_set_nonce", &40u64.to_xdr(&env), client.get_nonce());
+    client.dev_set_nonce(&40, &sig);
+    assert_eq!(client.get_nonce(), 40);
+}
+
+#[cfg(feature = "dev-mode")]
+#[test]
+fn test_dev_build_refuses_mainnet() {
+    let env = create_test_env();
+    let mainnet = env.crypto().sha256(&Bytes::from_slice(&env, b"Public Global Stellar Network ; September 2015"));
+    env.ledger().set_network_id(mainnet.to_array());
+    let client = WalletContractClient::new(&env, &create_contract(&env));
+
+    let owner = public_key(&env, &signing_key(1));
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    assert_eq!(client.try_init(&owner, &email_hash), Err(Ok(Error::Unauthorized)));
+}
+
+// ============================================================================
+// OWNER ROTATION TESTS
+// ============================================================================
+
+fn action_bytes(action: &str, nonce: u64) -> std::vec::Vec<u8> {
+    let mut message = action.as_bytes().to_vec();
+    message.extend_from_slice(&nonce.to_be_bytes());
+    message
+}
+
+fn set_rotation_delay(env: &Env, client: &WalletContractClient, key: &SigningKey, delay: u64) {
+    let sig = sign_action(env, key, "set_rotation_delay", &delay.to_xdr(env), client.get_nonce());
+    client.set_rotation_delay(&delay, &sig);
+}
+
+fn propose_rotation(env: &Env, client: &WalletContractClient, key: &SigningKey, new_owner: &SigningKey) {
+    let new_owner = public_key(env, new_owner);
+    let sig = sign_action(env, key, "propose_owner_rotation", &new_owner.clone().to_xdr(env), client.get_nonce());
+    client.propose_owner_rotation(&new_owner, &sig);
+}
+
+#[test]
+fn test_timelocked_rotation() {
+    let env = create_test_env();
+    let (owner, next) = (signing_key(1), signing_key(2));
+    let client = setup_wallet(&env, &owner);
+    set_rotation_delay(&env, &client, &owner, 86_400);
+
+    // The immediate path is closed, and the delay can't be lowered
+    let new_owner = public_key(&env, &next);
+    let sig = sign_action(&env, &owner, "update_owner", &Bytes::from_array(&env, &new_owner.to_array()), client.get_nonce());
+    assert_eq!(client.try_update_owner(&new_owner, &sig), Err(Ok(Error::Timelocked)));
+    let sig = sign_action(&env, &owner, "set_rotation_delay", &60u64.to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_set_rotation_delay(&60, &sig), Err(Ok(Error::Timelocked)));
+
+    let start = env.ledger().timestamp();
+    propose_rotation(&env, &client, &owner, &next);
+    assert_eq!(client.get_pending_rotation(), Some(PendingRotation { new_owner: new_owner.clone(), eta: start + 86_400 }));
+
+    let confirm = |client: &WalletContractClient| {
+        let message = Bytes::from_slice(&env, &action_bytes("confirm_rotation", client.get_nonce()));
+        client.try_confirm_rotation(&sign_raw(&env, &next, &message))
+    };
+    assert_eq!(confirm(&client), Err(Ok(Error::Timelocked)));
+    env.ledger().set_timestamp(start + 86_400);
+    assert_eq!(confirm(&client), Ok(Ok(())));
+    assert_eq!(client.get_owner(), new_owner);
+    assert_eq!(client.get_pending_rotation(), None);
+}
+
+#[test]
+fn test_cancel_rotation_by_other_device() {
+    let env = create_test_env();
+    let (owner, attacker, laptop) = (signing_key(1), signing_key(2), signing_key(3));
+    let client = setup_wallet(&env, &owner);
+    set_rotation_delay(&env, &client, &owner, 86_400);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &laptop)), 1);
+    register_passkey(&env, &client, &owner);
+
+    // A key that isn't registered can't cancel
+    propose_rotation(&env, &client, &owner, &attacker);
+    let message = Bytes::from_slice(&env, &action_bytes("cancel_rotation", client.get_nonce()));
+    let stranger = SignerProof::Ed25519(public_key(&env, &attacker), sign_raw(&env, &attacker, &message));
+    assert_eq!(client.try_cancel_rotation(&stranger), Err(Ok(Error::Unauthorized)));
+
+    let laptop_proof = SignerProof::Ed25519(public_key(&env, &laptop), sign_raw(&env, &laptop, &message));
+    client.cancel_rotation(&laptop_proof);
+    assert_eq!(client.get_pending_rotation(), None);
+    assert_eq!(client.try_cancel_rotation(&laptop_proof), Err(Ok(Error::NotFound)));
+
+    // The passkey can cancel too
+    propose_rotation(&env, &client, &owner, &attacker);
+    let challenge = action_bytes("cancel_rotation", client.get_nonce());
+    let proof = SignerProof::Secp256r1(passkey_public(&env, &passkey()), assertion(&env, &challenge, 0x01));
+    client.cancel_rotation(&proof);
+    assert_eq!(client.get_pending_rotation(), None);
+    assert_eq!(client.get_owner(), public_key(&env, &owner));
+}
+
+#[test]
+fn test_cancel_rotation_by_linked_device() {
+    let env = create_test_env();
+    let (owner, attacker, phone) = (signing_key(1), signing_key(2), signing_key(3));
+    let client = setup_wallet(&env, &owner);
+    set_rotation_delay(&env, &client, &owner, 86_400);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+
+    propose_rotation(&env, &client, &owner, &attacker);
+    let message = Bytes::from_slice(&env, &action_bytes("cancel_rotation", client.get_nonce()));
+    let proof = SignerProof::Ed25519(public_key(&env, &phone), sign_raw(&env, &phone, &message));
+    client.cancel_rotation(&proof);
+    assert_eq!(client.get_pending_rotation(), None);
+}
+
+// ============================================================================
+// SOCIAL RECOVERY TESTS
+// ============================================================================
+
+/// A guardian's proof over `sha256(action || payload || nonce)`
+fn guardian_proof(env: &Env, client: &WalletContractClient, guardian: &SigningKey, action: &str, payload: &Bytes) -> SignerProof {
+    let mut message = Bytes::new(env);
+    message.extend_from_slice(action.as_bytes());
+    message.append(payload);
+    message.extend_from_array(&client.get_nonce().to_be_bytes());
+    let digest = Bytes::from_array(env, &env.crypto().sha256(&message).to_array());
+    SignerProof::Ed25519(public_key(env, guardian), sign_raw(env, guardian, &digest))
+}
+
+/// Add `guardians` and require `threshold` of them
+fn setup_guardians(env: &Env, client: &WalletContractClient, owner: &SigningKey, guardians: &[&SigningKey], threshold: u32) {
+    for guardian in guardians {
+        let guardian = Signer::Ed25519(public_key(env, guardian));
+        let sig = sign_action(env, owner, "add_guardian", &guardian.clone().to_xdr(env), client.get_nonce());
+        client.add_guardian(&guardian, &sig);
+    }
+    let sig = sign_action(env, owner, "set_guardian_threshold", &threshold.to_xdr(env), client.get_nonce());
+    client.set_guardian_threshold(&threshold, &sig);
+}
+
+#[test]
+fn test_guardian_recovery() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let (first, second, third) = (signing_key(20), signing_key(21), signing_key(22));
+    let client = setup_wallet(&env, &owner);
+    setup_guardians(&env, &client, &owner, &[&first, &second, &third], 2);
+
+    let new_owner = public_key(&env, &signing_key(3));
+    let proof = guardian_proof(&env, &client, &first, "initiate_recovery", &new_owner.clone().to_xdr(&env));
+    client.initiate_recovery(&new_owner, &proof);
+    assert_eq!(client.try_execute_recovery(), Err(Ok(Error::Timelocked)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + RECOVERY_DELAY);
+    // One approval of two
+    assert_eq!(client.try_execute_recovery(), Err(Ok(Error::Unauthorized)));
+
+    let proof = guardian_proof(&env, &client, &first, "approve_recovery", &Bytes::new(&env));
+    assert_eq!(client.try_approve_recovery(&proof), Err(Ok(Error::AlreadyExists)));
+    let proof = guardian_proof(&env, &client, &second, "approve_recovery", &Bytes::new(&env));
+    client.approve_recovery(&proof);
+
+    client.execute_recovery();
+    assert_eq!(client.get_owner(), new_owner);
+    assert_eq!(client.get_recovery(), None);
+}
+
+#[test]
+fn test_owner_vetoes_recovery() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let guardian = signing_key(20);
+    let client = setup_wallet(&env, &owner);
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+
+    let new_owner = public_key(&env, &signing_key(3));
+    let payload = new_owner.clone().to_xdr(&env);
+
+    // Not a guardian
+    let proof = guardian_proof(&env, &client, &signing_key(23), "initiate_recovery", &payload);
+    assert_eq!(client.try_initiate_recovery(&new_owner, &proof), Err(Ok(Error::Unauthorized)));
+
+    let proof = guardian_proof(&env, &client, &guardian, "initiate_recovery", &payload);
+    client.initiate_recovery(&new_owner, &proof);
+
+    let sig = sign_action(&env, &owner, "veto_recovery", &Bytes::new(&env), client.get_nonce());
+    client.veto_recovery(&sig);
+    assert_eq!(client.get_recovery(), None);
+    assert_eq!(client.try_execute_recovery(), Err(Ok(Error::NotFound)));
+    assert_eq!(client.get_owner(), public_key(&env, &owner));
+}
+
+#[test]
+fn test_guardian_changes_wait_out_guardian_delay() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let (guardian, other) = (signing_key(20), signing_key(21));
+    let client = setup_wallet(&env, &owner);
+    // Setting up while recovery is off applies at once
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+
+    let thief = Signer::Ed25519(public_key(&env, &signing_key(30)));
+    let payload = thief.clone().to_xdr(&env);
+    let change = change_id(&env, "add_guardian", &payload);
+    let add = |client: &WalletContractClient| {
+        let sig = sign_action(&env, &owner, "add_guardian", &payload, client.get_nonce());
+        client.try_add_guardian(&thief, &sig)
+    };
+    let propose = |client: &WalletContractClient| {
+        let sig = sign_action(&env, &owner, "propose_guardian_change", &Bytes::from_array(&env, &change.to_array()), client.get_nonce());
+        client.propose_guardian_change(&change, &sig)
+    };
+    assert_eq!(add(&client), Err(Ok(Error::Timelocked)));
+    assert_eq!(propose(&client), 1_000 + DEFAULT_GUARDIAN_DELAY);
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + RECOVERY_DELAY);
+    assert_eq!(add(&client), Err(Ok(Error::Timelocked)));
+
+    // A guardian cancels; an outsider can't
+    let cancel = Bytes::from_array(&env, &change.to_array());
+    let proof = guardian_proof(&env, &client, &other, "cancel_guardian_change", &cancel);
+    assert_eq!(client.try_cancel_guardian_change(&change, &proof), Err(Ok(Error::Unauthorized)));
+    let proof = guardian_proof(&env, &client, &guardian, "cancel_guardian_change", &cancel);
+    client.cancel_guardian_change(&change, &proof);
+    assert_eq!(client.get_guardian_change(&change), None);
+
+    propose(&client);
+    env.ledger().with_mut(|li| li.timestamp += DEFAULT_GUARDIAN_DELAY);
+    add(&client).unwrap().unwrap();
+    assert_eq!(client.get_guardians().len(), 2);
+    assert_eq!(client.get_guardian_change(&change), None);
+
+    // Raising the delay applies at once, lowering waits; never under the recovery delay
+    let set_delay = |client: &WalletContractClient, delay: u64| {
+        let sig = sign_action(&env, &owner, "set_guardian_delay", &delay.to_xdr(&env), client.get_nonce());
+        client.try_set_guardian_delay(&delay, &sig)
+    };
+    assert_eq!(set_delay(&client, RECOVERY_DELAY - 1), Err(Ok(Error::InvalidAmount)));
+    set_delay(&client, MAX_GUARDIAN_DELAY).unwrap().unwrap();
+    assert_eq!(set_delay(&client, RECOVERY_DELAY), Err(Ok(Error::Timelocked)));
+    assert_eq!(client.get_guardian_delay(), MAX_GUARDIAN_DELAY);
+}
+
+#[test]
+fn test_guardian_rotates_its_own_key() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let (first, second, rotated) = (signing_key(20), signing_key(21), signing_key(22));
+    let client = setup_wallet(&env, &owner);
+    setup_guardians(&env, &client, &owner, &[&first, &second], 2);
+    let key = |k: &SigningKey| Signer::Ed25519(public_key(&env, k));
+    let rotate = |old: &SigningKey, signer: &SigningKey, new: &Signer| {
+        let proof = guardian_proof(&env, &client, signer, "guardian_rotate", &new.clone().to_xdr(&env));
+        client.try_guardian_rotate(&key(old), new, &proof)
+    };
+
+    // Only the old key can sign its rotation, and only to a fresh key
+    assert_eq!(rotate(&first, &second, &key(&rotated)), Err(Ok(Error::Unauthorized)));
+    assert_eq!(rotate(&first, &first, &key(&second)), Err(Ok(Error::AlreadyExists)));
+    assert_eq!(rotate(&first, &first, &key(&owner)), Err(Ok(Error::InvalidOwner)));
+
+    // Takes the old key's place without waiting out the guardian delay
+    rotate(&first, &first, &key(&rotated)).unwrap().unwrap();
+    assert_eq!(client.get_guardians(), vec![&env, key(&rotated), key(&second)]);
+    assert_eq!(client.get_guardian_threshold(), 2);
+    assert_eq!(client.get_guardian_rotated_at(&key(&rotated)), Some(1_000));
+
+    // The new key can't rotate again until the interval is over
+    let next = key(&signing_key(23));
+    assert_eq!(rotate(&rotated, &rotated, &next), Err(Ok(Error::LimitExceeded)));
+    env.ledger().with_mut(|li| li.timestamp += GUARDIAN_ROTATION_INTERVAL);
+    assert_eq!(client.get_guardian_rotated_at(&key(&rotated)), None);
+    rotate(&rotated, &rotated, &next).unwrap().unwrap();
+    assert_eq!(client.get_guardians().first_index_of(&next), Some(0));
+}
+
+// ============================================================================
+// AUTO-CONVERSION TESTS
+// ============================================================================
+
+fn set_conversion_rule(env: &Env, client: &WalletContractClient, owner: &SigningKey, from: &Address, rule: &Option<ConversionRule>) {
+    let payload = (from.clone(), rule.clone()).to_xdr(env);
+    let sig = sign_action(env, owner, "set_conversion_rule", &payload, client.get_nonce());
+    client.set_conversion_rule(from, rule, &sig);
+}
+
+/// `set_conversion_rule`, then wait out the delay a loosening change takes
+fn set_conversion_rule_now(env: &Env, client: &WalletContractClient, owner: &SigningKey, from: &Address, rule: &Option<ConversionRule>) {
+    set_conversion_rule(env, client, owner, from, rule);
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    assert_eq!(client.get_conversion_rule(from), *rule);
+}
+
+mod skimming_router {
+    use super::*;
+
+    /// Router that reports a fair trade but pays out half of it
+    #[contract]
+    pub struct SkimmingRouter;
+
+    #[contractimpl]
+    impl SkimmingRouter {
+        pub fn router_pair_for(env: Env, _token_a: Address, _token_b: Address) -> Address {
+            env.current_contract_address()
+        }
+
+        pub fn router_get_amounts_out(env: Env, amount_in: i128, _path: Vec<Address>) -> Vec<i128> {
+            vec![&env, amount_in, mock_amount_out(amount_in)]
+        }
+
+        pub fn swap_exact_tokens_for_tokens(
+            env: Env,
+            amount_in: i128,
+            _amount_out_min: i128,
+            path: Vec<Address>,
+            to: Address,
+            _deadline: u64,
+        ) -> Vec<i128> {
+            let amount_out = mock_amount_out(amount_in);
+            let router = env.current_contract_address();
+            token::Client::new(&env, &path.get(0).unwrap()).transfer(&to, &router, &amount_in);
+            token::Client::new(&env, &path.get(1).unwrap()).transfer(&router, &to, &(amount_out / 2));
+            vec![&env, amount_in, amount_out]
+        }
+    }
+}
+use skimming_router::SkimmingRouter;
+
+#[test]
+fn test_convert_within_rule() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (eurc, usdc) = (create_token(&env), create_token(&env));
+    let router = env.register(MockRouter, ());
+    let relayer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &eurc).mint(&client.address, &100_000_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&router, &1_000_000_000);
+
+    let rule = ConversionRule {
+        to_token: usdc.clone(),
+        router: router.clone(),
+        executor: relayer.clone(),
+        max_slippage_bps: 30,
+        daily_cap: 25_000_000,
+    };
+    set_conversion_rule_now(&env, &client, &owner, &eurc, &Some(rule));
+
+    let bought = client.convert(&eurc, &10_000_000);
+    assert_eq!(bought, mock_amount_out(10_000_000));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&client.address), bought);
+    assert_eq!(token::Client::new(&env, &eurc).balance(&client.address), 90_000_000);
+
+    client.convert(&eurc, &10_000_000);
+    // Daily cap
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::LimitExceeded)));
+    env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60);
+    client.convert(&eurc, &10_000_000);
+}
+
+#[test]
+fn test_convert_rejects_slippage() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (eurc, usdc) = (create_token(&env), create_token(&env));
+    let router = env.register(MockRouter, ());
+    token::StellarAssetClient::new(&env, &eurc).mint(&client.address, &2_000_000_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&router, &1_000_000_000);
+
+    let rule = ConversionRule {
+        to_token: usdc.clone(),
+        router,
+        executor: Address::generate(&env),
+        max_slippage_bps: 30,
+        daily_cap: 2_000_000_000,
+    };
+    set_conversion_rule_now(&env, &client, &owner, &eurc, &Some(rule));
+
+    // ~5% price impact against the pool
+    assert_eq!(client.try_convert(&eurc, &1_000_000_000), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&client.address), 0);
+
+    set_conversion_rule(&env, &client, &owner, &eurc, &None);
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::NotFound)));
+}
+
+#[test]
+fn test_convert_measures_slippage_against_the_oracle() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (eurc, usdc) = (create_token(&env), create_token(&env));
+    let router = env.register(MockRouter, ());
+    token::StellarAssetClient::new(&env, &eurc).mint(&client.address, &100_000_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&router, &1_000_000_000);
+    let rule = ConversionRule {
+        to_token: usdc.clone(),
+        router: router.clone(),
+        executor: Address::generate(&env),
+        max_slippage_bps: 30,
+        daily_cap: 100_000_000,
+    };
+    set_conversion_rule_now(&env, &client, &owner, &eurc, &Some(rule));
+
+    // The pool pays 1 USDC per 2 EURC while the oracle has 1 EURC = 1.08 USD
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    let now = env.ledger().timestamp();
+    oracle.set_price(&OracleAsset::Stellar(eurc.clone()), &108_000_000_000_000, &now);
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &now);
+    let sig = sign_action(&env, &owner, "set_oracle", &oracle.address.clone().to_xdr(&env), client.get_nonce());
+    client.set_oracle(&oracle.address, &sig);
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::LimitExceeded)));
+
+    oracle.set_price(&OracleAsset::Stellar(eurc.clone()), &50_000_000_000_000, &now);
+    assert_eq!(client.convert(&eurc, &10_000_000), mock_amount_out(10_000_000));
+}
+
+#[test]
+fn test_conversion_rule_loosening_waits_out_the_delay() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (eurc, usdc) = (create_token(&env), create_token(&env));
+    let router = env.register(MockRouter, ());
+    token::StellarAssetClient::new(&env, &eurc).mint(&client.address, &100_000_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&router, &1_000_000_000);
+    let rule = ConversionRule {
+        to_token: usdc.clone(),
+        router: router.clone(),
+        executor: Address::generate(&env),
+        max_slippage_bps: 30,
+        daily_cap: 25_000_000,
+    };
+
+    // A new rule is a loosening change
+    set_conversion_rule(&env, &client, &owner, &eurc, &Some(rule.clone()));
+    assert_eq!(client.get_conversion_rule(&eurc), None);
+    assert_eq!(client.get_pending_conversion_rule(&eurc).unwrap().rule, rule);
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::NotFound)));
+    env.ledger().with_mut(|li| li.timestamp += LARGE_TRANSFER_DELAY);
+    assert_eq!(client.get_conversion_rule(&eurc), Some(rule.clone()));
+
+    // Another router waits, a lower cap applies at once
+    let skimmer = ConversionRule { router: env.register(SkimmingRouter, ()), ..rule.clone() };
+    set_conversion_rule(&env, &client, &owner, &eurc, &Some(skimmer));
+    assert_eq!(client.get_conversion_rule(&eurc), Some(rule.clone()));
+    let tighter = ConversionRule { daily_cap: 10_000_000, ..rule.clone() };
+    set_conversion_rule(&env, &client, &owner, &eurc, &Some(tighter.clone()));
+    assert_eq!(client.get_conversion_rule(&eurc), Some(tighter));
+    assert_eq!(client.get_pending_conversion_rule(&eurc), None);
+
+    // The router is held to the contract policy
+    list_contract(&env, &client, &owner, &router, Some(ContractListing::Denied));
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_convert_checks_what_arrives_not_what_the_router_reports() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (eurc, usdc) = (create_token(&env), create_token(&env));
+    let router = env.register(SkimmingRouter, ());
+    token::StellarAssetClient::new(&env, &eurc).mint(&client.address, &100_000_000);
+    token::StellarAssetClient::new(&env, &usdc).mint(&router, &1_000_000_000);
+    let rule = ConversionRule {
+        to_token: usdc.clone(),
+        router,
+        executor: Address::generate(&env),
+        max_slippage_bps: 30,
+        daily_cap: 100_000_000,
+    };
+    set_conversion_rule_now(&env, &client, &owner, &eurc, &Some(rule));
+
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::LimitExceeded)));
+    assert_eq!(token::Client::new(&env, &eurc).balance(&client.address), 100_000_000);
+
+    // What is sold counts against the large-transfer threshold
+    set_large_transfer_threshold(&env, &client, &owner, &eurc, 5_000_000);
+    assert_eq!(client.try_convert(&eurc, &10_000_000), Err(Ok(Error::Timelocked)));
+}
+
+// ============================================================================
+// ZK EMAIL RECOVERY TESTS
+// ============================================================================
+
+/// Verifier whose "proof" is `email_hash || command`
+#[contract]
+struct MockEmailVerifier;
+
+#[contractimpl]
+impl MockEmailVerifier {
+    pub fn verify(_env: Env, proof: Bytes, command: BytesN<32>) -> BytesN<32> {
+        assert_eq!(proof.slice(32..), Bytes::from(command));
+        proof.slice(..32).try_into().unwrap()
+    }
+}
+
+/// Proof for rotating `client` to `new_owner`, as sent from `email_hash`
+fn email_proof(env: &Env, client: &WalletContractClient, email_hash: &BytesN<32>, new_owner: &BytesN<32>) -> Bytes {
+    let mut message = Bytes::from_slice(env, b"recover_with_email_proof");
+    message.extend_from_array(&new_owner.to_array());
+    message.extend_from_array(&client.get_nonce().to_be_bytes());
+    let mut proof = Bytes::from(email_hash.clone());
+    proof.extend_from_array(&env.crypto().sha256(&message).to_array());
+    proof
+}
+
+fn set_verifier(env: &Env, client: &WalletContractClient, owner: &SigningKey) {
+    let verifier = Some(env.register(MockEmailVerifier, ()));
+    let sig = sign_action(env, owner, "set_verifier", &verifier.clone().to_xdr(env), client.get_nonce());
+    client.set_verifier(&verifier, &sig);
+}
+
+#[test]
+fn test_recover_with_email_proof() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let new_owner = public_key(&env, &signing_key(3));
+
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &new_owner);
+    assert_eq!(client.try_recover_with_email_proof(&proof, &new_owner), Err(Ok(Error::Unauthorized)));
+
+    set_verifier(&env, &client, &owner);
+    // Proven email isn't the wallet's
+    let proof = email_proof(&env, &client, &BytesN::from_array(&env, &[9u8; 32]), &new_owner);
+    assert_eq!(client.try_recover_with_email_proof(&proof, &new_owner), Err(Ok(Error::Unauthorized)));
+
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &new_owner);
+    client.recover_with_email_proof(&proof, &new_owner);
+    assert_eq!(client.get_owner(), new_owner);
+    // The nonce moved on, so the same proof can't be replayed
+    assert!(client.try_recover_with_email_proof(&proof, &public_key(&env, &signing_key(4))).is_err());
+}
+
+#[test]
+fn test_email_recovery_respects_rotation_delay() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    set_verifier(&env, &client, &owner);
+    let sig = sign_action(&env, &owner, "set_rotation_delay", &600u64.to_xdr(&env), client.get_nonce());
+    client.set_rotation_delay(&600, &sig);
+
+    let new_owner = public_key(&env, &signing_key(3));
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &new_owner);
+    client.recover_with_email_proof(&proof, &new_owner);
+
+    assert_eq!(client.get_owner(), public_key(&env, &owner));
+    assert_eq!(client.get_pending_rotation(), Some(PendingRotation { new_owner, eta: 1_600 }));
+    assert_eq!(client.get_recovery_cooldown(), None);
+
+    // The cooldown starts when the recovered key takes over
+    env.ledger().with_mut(|li| li.timestamp = 1_600);
+    let message = Bytes::from_slice(&env, &action_bytes("confirm_rotation", client.get_nonce()));
+    client.confirm_rotation(&sign_raw(&env, &signing_key(3), &message));
+    assert_eq!(client.get_recovery_cooldown().unwrap().until, 1_600 + RECOVERY_COOLDOWN);
+}
+
+#[test]
+fn test_email_recovery_caps_spending_until_a_guardian_lifts_it() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let (owner, recovered, guardian) = (signing_key(1), signing_key(3), signing_key(4));
+    let client = setup_wallet(&env, &owner);
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &10_000_000_000);
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    oracle.set_price(&OracleAsset::Stellar(usdc.clone()), &100_000_000_000_000, &(10 * DAY));
+    let sig = sign_action(&env, &owner, "set_oracle", &oracle.address.clone().to_xdr(&env), client.get_nonce());
+    client.set_oracle(&oracle.address, &sig);
+    set_verifier(&env, &client, &owner);
+
+    let new_owner = public_key(&env, &recovered);
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &new_owner);
+    client.recover_with_email_proof(&proof, &new_owner);
+    let cooldown = client.get_recovery_cooldown().unwrap();
+    assert_eq!(cooldown.until, 11 * DAY);
+    assert_eq!(cooldown.currency, Symbol::new(&env, "USD"));
+    assert_eq!(cooldown.cap, RECOVERY_SPEND_CAP);
+
+    // $100 during the cooldown, and the oracle pricing it stays put
+    let merchant = Address::generate(&env);
+    let send = |amount: i128| {
+        let ops = vec![&env, transfer_op(&env, &usdc, &client.address, &merchant, amount)];
+        client.try_execute_batch(&ops, &sign_batch(&env, &client, &recovered, &ops)).map(|_| ())
+    };
+    assert_eq!(send(600_000_000), Ok(()));
+    assert_eq!(client.get_cooldown_spent(), 6_000);
+    assert_eq!(send(500_000_000).err(), Some(Ok(Error::LimitExceeded)));
+    let sig = sign_action(&env, &recovered, "set_oracle", &merchant.clone().to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_set_oracle(&merchant, &sig), Err(Ok(Error::Timelocked)));
+
+    // Only a guardian lifts it
+    let stranger = guardian_proof(&env, &client, &recovered, "lift_recovery_cooldown", &Bytes::new(&env));
+    assert_eq!(client.try_lift_recovery_cooldown(&stranger), Err(Ok(Error::Unauthorized)));
+    let proof = guardian_proof(&env, &client, &guardian, "lift_recovery_cooldown", &Bytes::new(&env));
+    client.lift_recovery_cooldown(&proof);
+    assert_eq!(client.get_recovery_cooldown(), None);
+    assert_eq!(send(500_000_000), Ok(()));
+}
+
+fn set_recovery_cooldown(
+    env: &Env,
+    client: &WalletContractClient,
+    owner: &SigningKey,
+    duration: u64,
+    cap: i128,
+    asset_caps: &Map<Address, i128>,
+) -> Result<(), Error> {
+    let payload = (duration, cap, asset_caps.clone()).to_xdr(env);
+    let sig = sign_action(env, owner, "set_recovery_cooldown", &payload, client.get_nonce());
+    client.try_set_recovery_cooldown(&duration, &cap, asset_caps, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_recovery_cooldown_is_configurable_and_caps_each_asset_without_an_oracle() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let (owner, recovered) = (signing_key(1), signing_key(3));
+    let client = setup_wallet(&env, &owner);
+    let (usdc, xlm) = (create_token(&env), create_token(&env));
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &10_000_000_000);
+    token::StellarAssetClient::new(&env, &xlm).mint(&client.address, &10_000_000_000);
+    set_verifier(&env, &client, &owner);
+
+    let caps = Map::from_array(&env, [(usdc.clone(), 300_000_000i128)]);
+    assert_eq!(set_recovery_cooldown(&env, &client, &owner, 0, 5_000, &caps), Err(Error::LimitExceeded));
+    assert_eq!(set_recovery_cooldown(&env, &client, &owner, MAX_RECOVERY_COOLDOWN + 1, 5_000, &caps), Err(Error::LimitExceeded));
+    assert_eq!(set_recovery_cooldown(&env, &client, &owner, 2 * DAY, -1, &caps), Err(Error::InvalidAmount));
+    set_recovery_cooldown(&env, &client, &owner, 2 * DAY, 5_000, &caps).unwrap();
+
+    let new_owner = public_key(&env, &recovered);
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &new_owner);
+    client.recover_with_email_proof(&proof, &new_owner);
+    let cooldown = client.get_recovery_cooldown().unwrap();
+    assert_eq!(cooldown.until, 12 * DAY);
+    assert_eq!(cooldown.cap, 5_000);
+
+    // No oracle, so each asset counts against its own cap, and one without
+    // a cap can't go out
+    let merchant = Address::generate(&env);
+    let send = |asset: &Address, amount: i128| {
+        let ops = vec![&env, transfer_op(&env, asset, &client.address, &merchant, amount)];
+        client.try_execute_batch(&ops, &sign_batch(&env, &client, &recovered, &ops)).map(|_| ())
+    };
+    assert_eq!(send(&usdc, 200_000_000), Ok(()));
+    assert_eq!(client.get_cooldown_asset_spent(&usdc), 200_000_000);
+    assert_eq!(send(&usdc, 200_000_000).err(), Some(Ok(Error::LimitExceeded)));
+    assert_eq!(send(&xlm, 1).err(), Some(Ok(Error::LimitExceeded)));
+
+    // The recovered key can't loosen it
+    assert_eq!(set_recovery_cooldown(&env, &client, &recovered, DAY, 5_000, &Map::new(&env)), Err(Error::Timelocked));
+
+    env.ledger().with_mut(|li| li.timestamp = 12 * DAY);
+    assert_eq!(client.get_recovery_cooldown(), None);
+    assert_eq!(send(&xlm, 500_000_000), Ok(()));
+}
+
+#[test]
+fn test_recovery_cooldown_counts_own_accounts_and_queued_transfers() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let (owner, recovered) = (signing_key(1), signing_key(3));
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &10_000_000_000);
+    set_verifier(&env, &client, &owner);
+    let caps = Map::from_array(&env, [(usdc.clone(), 300_000_000i128)]);
+    set_recovery_cooldown(&env, &client, &owner, 3 * DAY, 5_000, &caps).unwrap();
+
+    let new_owner = public_key(&env, &recovered);
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &new_owner);
+    client.recover_with_email_proof(&proof, &new_owner);
+
+    // An account the recovered key links and waits out is still capped
+    let vault = Address::generate(&env);
+    add_own_account(&env, &client, &recovered, &vault).unwrap();
+    env.ledger().with_mut(|li| li.timestamp += OWN_ACCOUNT_COOLING_OFF);
+    assert!(client.is_own_account(&vault));
+    let send = |amount: i128| {
+        let ops = vec![&env, transfer_op(&env, &usdc, &client.address, &vault, amount)];
+        client.try_execute_batch(&ops, &sign_batch(&env, &client, &recovered, &ops)).map(|_| ())
+    };
+    assert_eq!(send(5_000_000_000).err(), Some(Ok(Error::LimitExceeded)));
+    assert_eq!(send(200_000_000), Ok(()));
+    assert_eq!(client.get_cooldown_asset_spent(&usdc), 200_000_000);
+
+    // So is a transfer queued to outwait it
+    let payload = (usdc.clone(), vault.clone(), 200_000_000i128).to_xdr(&env);
+    let sig = sign_action(&env, &recovered, "queue_transfer", &payload, client.get_nonce());
+    assert_eq!(client.try_queue_transfer(&usdc, &vault, &200_000_000, &sig), Err(Ok(Error::LimitExceeded)));
+    queue_transfer(&env, &client, &recovered, &usdc, &vault, 100_000_000);
+    assert_eq!(client.get_cooldown_asset_spent(&usdc), 300_000_000);
+}
+
+fn update_email_hash(env: &Env, client: &WalletContractClient, owner: &SigningKey, new_hash: &BytesN<32>) {
+    let payload = Bytes::from_array(env, &new_hash.to_array());
+    let sig = sign_action(env, owner, "update_email_hash", &payload, client.get_nonce());
+    client.update_email_hash(new_hash, &sig);
+}
+
+#[test]
+fn test_update_email_hash_invalidates_old_email() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    set_verifier(&env, &client, &owner);
+    let (old_hash, new_hash) = (client.get_email_hash(), BytesN::from_array(&env, &[8u8; 32]));
+
+    let dummy = BytesN::from_array(&env, &[0u8; 64]);
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    assert_eq!(client.try_update_email_hash(&zero, &dummy), Err(Ok(Error::InvalidEmailHash)));
+    assert_eq!(client.try_update_email_hash(&old_hash, &dummy), Err(Ok(Error::AlreadyExists)));
+    // Signed over a different hash
+    let payload = Bytes::from_array(&env, &[7u8; 32]);
+    let sig = sign_action(&env, &owner, "update_email_hash", &payload, client.get_nonce());
+    assert!(client.try_update_email_hash(&new_hash, &sig).is_err());
+
+    update_email_hash(&env, &client, &owner, &new_hash);
+    assert_eq!(client.get_email_hash(), new_hash);
+
+    let new_owner = public_key(&env, &signing_key(3));
+    let proof = email_proof(&env, &client, &old_hash, &new_owner);
+    assert_eq!(client.try_recover_with_email_proof(&proof, &new_owner), Err(Ok(Error::Unauthorized)));
+    let proof = email_proof(&env, &client, &new_hash, &new_owner);
+    client.recover_with_email_proof(&proof, &new_owner);
+    assert_eq!(client.get_owner(), new_owner);
+}
+
+#[test]
+fn test_update_email_hash_drops_rotation_from_old_email() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    set_verifier(&env, &client, &owner);
+    set_rotation_delay(&env, &client, &owner, 600);
+
+    // Someone with the old mailbox starts a recovery
+    let thief = public_key(&env, &signing_key(3));
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &thief);
+    client.recover_with_email_proof(&proof, &thief);
+    assert!(client.get_pending_rotation().is_some());
+
+    update_email_hash(&env, &client, &owner, &BytesN::from_array(&env, &[8u8; 32]));
+    assert_eq!(client.get_pending_rotation(), None);
+    env.ledger().with_mut(|li| li.timestamp = 1_600);
+    let sig = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_confirm_rotation(&sig), Err(Ok(Error::NotFound)));
+    assert_eq!(client.get_owner(), public_key(&env, &owner));
+
+    // A rotation the owner proposed itself is left alone
+    let new_owner = public_key(&env, &signing_key(4));
+    let sig = sign_action(&env, &owner, "propose_owner_rotation", &new_owner.clone().to_xdr(&env), client.get_nonce());
+    client.propose_owner_rotation(&new_owner, &sig);
+    update_email_hash(&env, &client, &owner, &BytesN::from_array(&env, &[9u8; 32]));
+    assert!(client.get_pending_rotation().is_some());
+}
+
+// ============================================================================
+// FRAGMENT RELEASE TESTS
+// ============================================================================
+
+#[test]
+fn test_fragment_release_after_recovery() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let device = signing_key(3);
+    let client = setup_wallet(&env, &owner);
+    set_verifier(&env, &client, &owner);
+    let device_key = public_key(&env, &device);
+
+    // No recovery yet
+    let sig = sign_action(&env, &device, "authorize_fragment_release", &Bytes::new(&env), client.get_nonce());
+    assert_eq!(client.try_authorize_fragment_release(&sig), Err(Ok(Error::NotFound)));
+
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &device_key);
+    client.recover_with_email_proof(&proof, &device_key);
+
+    // Only the recovered key can authorize
+    let sig = sign_action(&env, &owner, "authorize_fragment_release", &Bytes::new(&env), client.get_nonce());
+    assert!(client.try_authorize_fragment_release(&sig).is_err());
+    let sig = sign_action(&env, &device, "authorize_fragment_release", &Bytes::new(&env), client.get_nonce());
+    client.authorize_fragment_release(&sig);
+
+    assert!(client.is_fragment_release_authorized(&device_key));
+    assert!(!client.is_fragment_release_authorized(&public_key(&env, &owner)));
+
+    env.ledger().with_mut(|li| li.timestamp += FRAGMENT_RELEASE_WINDOW + 1);
+    assert!(!client.is_fragment_release_authorized(&device_key));
+}
+
+#[test]
+fn test_fragment_release_waits_for_rotation_delay() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let device = signing_key(3);
+    let client = setup_wallet(&env, &owner);
+    set_verifier(&env, &client, &owner);
+    let sig = sign_action(&env, &owner, "set_rotation_delay", &600u64.to_xdr(&env), client.get_nonce());
+    client.set_rotation_delay(&600, &sig);
+
+    let device_key = public_key(&env, &device);
+    let proof = email_proof(&env, &client, &client.get_email_hash(), &device_key);
+    client.recover_with_email_proof(&proof, &device_key);
+
+    let sig = sign_action(&env, &device, "authorize_fragment_release", &Bytes::new(&env), client.get_nonce());
+    assert_eq!(client.try_authorize_fragment_release(&sig), Err(Ok(Error::Timelocked)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_600);
+    let sig = sign_action(&env, &device, "confirm_rotation", &Bytes::new(&env), client.get_nonce());
+    client.confirm_rotation(&sig);
+    let sig = sign_action(&env, &device, "authorize_fragment_release", &Bytes::new(&env), client.get_nonce());
+    client.authorize_fragment_release(&sig);
+    assert!(client.is_fragment_release_authorized(&device_key));
+}
+
+// ============================================================================
+// ATTESTATION SLOT TESTS
+// ============================================================================
+
+fn grant_attestor(env: &Env, client: &WalletContractClient, owner: &SigningKey, slot: &Symbol, attestor: &Address) {
+    let payload = (slot.clone(), attestor.clone()).to_xdr(env);
+    let sig = sign_action(env, owner, "grant_attestor", &payload, client.get_nonce());
+    client.grant_attestor(slot, attestor, &sig);
+}
+
+#[test]
+fn test_attestor_writes_only_its_slot() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let bureau = Address::generate(&env);
+    let (credit, employment) = (Symbol::new(&env, "credit"), Symbol::new(&env, "employment"));
+
+    grant_attestor(&env, &client, &owner, &credit, &bureau);
+    let hash = BytesN::from_array(&env, &[7u8; 32]);
+    client.attest(&credit, &hash);
+    assert_eq!(env.auths()[0].0, bureau);
+    assert_eq!(
+        client.get_attestation(&credit),
+        Some(Attestation { attestor: bureau.clone(), hash: hash.clone(), attested_at: 1_000 })
+    );
+
+    assert_eq!(client.try_attest(&employment, &hash), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_revoke_attestor() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let bureau = Address::generate(&env);
+    let slot = Symbol::new(&env, "credit");
+
+    grant_attestor(&env, &client, &owner, &slot, &bureau);
+    client.attest(&slot, &BytesN::from_array(&env, &[7u8; 32]));
+
+    let sig = sign_action(&env, &owner, "revoke_attestor", &slot.clone().to_xdr(&env), client.get_nonce());
+    client.revoke_attestor(&slot, &sig);
+    assert_eq!(client.get_attestor(&slot), None);
+    assert_eq!(client.try_attest(&slot, &BytesN::from_array(&env, &[8u8; 32])), Err(Ok(Error::Unauthorized)));
+    // The last attestation stays, still naming who wrote it
+    assert_eq!(client.get_attestation(&slot).unwrap().attestor, bureau);
+}
+
+// ============================================================================
+// BATCH EXECUTION TESTS
+// ============================================================================
+
+fn transfer_op(env: &Env, token: &Address, from: &Address, to: &Address, amount: i128) -> Invocation {
+    use soroban_sdk::IntoVal;
+    Invocation {
+        contract: token.clone(),
+        fn_name: Symbol::new(env, "transfer"),
+        args: (from.clone(), to.clone(), amount).into_val(env),
+        auth: Vec::new(env),
+    }
+}
+
+fn sign_batch(env: &Env, client: &WalletContractClient, key: &SigningKey, ops: &Vec<Invocation>) -> BytesN<64> {
+    sign_action(env, key, "execute_batch", &ops.clone().to_xdr(env), client.get_nonce())
+}
+
+#[test]
+fn test_execute_batch_runs_all_ops_with_one_signature() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+
+    let ops = vec![
+        &env,
+        transfer_op(&env, &usdc, &client.address, &alice, 30),
+        transfer_op(&env, &usdc, &client.address, &bob, 20),
+    ];
+    let results = client.execute_batch(&ops, &sign_batch(&env, &client, &owner, &ops));
+
+    assert_eq!(results.len(), 2);
+    let usdc = token::Client::new(&env, &usdc);
+    assert_eq!(usdc.balance(&alice), 30);
+    assert_eq!(usdc.balance(&bob), 20);
+    assert_eq!(usdc.balance(&client.address), 50);
+    assert_eq!(client.get_nonce(), 1);
+}
+
+#[test]
+fn test_execute_batch_is_atomic() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let alice = Address::generate(&env);
+
+    // The second transfer overdraws, so the first one is undone too
+    let ops = vec![
+        &env,
+        transfer_op(&env, &usdc, &client.address, &alice, 60),
+        transfer_op(&env, &usdc, &client.address, &alice, 60),
+    ];
+    let sig = sign_batch(&env, &client, &owner, &ops);
+    assert!(client.try_execute_batch(&ops, &sig).is_err());
+
+    assert_eq!(token::Client::new(&env, &usdc).balance(&client.address), 100);
+    assert_eq!(client.get_nonce(), 0);
+}
+
+#[test]
+fn test_execute_batch_rejects_bad_batches() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let alice = Address::generate(&env);
+
+    let empty = Vec::new(&env);
+    let sig = sign_batch(&env, &client, &owner, &empty);
+    assert_eq!(client.try_execute_batch(&empty, &sig), Err(Ok(Error::InvalidAmount)));
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..=MAX_BATCH_OPS {
+        too_many.push_back(transfer_op(&env, &usdc, &client.address, &alice, 1));
+    }
+    // Rejected before the signature is checked
+    let sig = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_execute_batch(&too_many, &sig), Err(Ok(Error::LimitExceeded)));
+
+    let reentrant = vec![&env, transfer_op(&env, &client.address, &client.address, &alice, 1)];
+    let sig = sign_batch(&env, &client, &owner, &reentrant);
+    assert_eq!(client.try_execute_batch(&reentrant, &sig), Err(Ok(Error::Unauthorized)));
+
+    // Signed for a different batch
+    let ops = vec![&env, transfer_op(&env, &usdc, &client.address, &alice, 1)];
+    let other = vec![&env, transfer_op(&env, &usdc, &client.address, &alice, 2)];
+    let sig = sign_batch(&env, &client, &owner, &other);
+    assert!(client.try_execute_batch(&ops, &sig).is_err());
+}
+
+#[test]
+fn test_execute_batch_checks_nested_transfers() {
+    use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (usdc, other) = (create_token(&env), create_token(&env));
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let attacker = Address::generate(&env);
+
+    // A harmless call that pre-authorizes a transfer out of the wallet
+    let ops = vec![
+        &env,
+        Invocation {
+            contract: other,
+            fn_name: Symbol::new(&env, "balance"),
+            args: (client.address.clone(),).into_val(&env),
+            auth: vec![
+                &env,
+                InvokerContractAuthEntry::Contract(SubContractInvocation {
+                    context: ContractContext {
+                        contract: usdc.clone(),
+                        fn_name: Symbol::new(&env, "transfer"),
+                        args: (client.address.clone(), attacker.clone(), 60i128).into_val(&env),
+                    },
+                    sub_invocations: Vec::new(&env),
+                }),
+            ],
+        },
+    ];
+    let execute = |ops: &Vec<Invocation>| {
+        let sig = sign_batch(&env, &client, &owner, ops);
+        client.try_execute_batch(ops, &sig)
+    };
+
+    list_contract(&env, &client, &owner, &usdc, Some(ContractListing::Denied));
+    assert_eq!(execute(&ops), Err(Ok(Error::Unauthorized)));
+    list_contract(&env, &client, &owner, &usdc, None);
+
+    set_contacts_only(&env, &client, &owner, true);
+    assert_eq!(execute(&ops), Err(Ok(Error::Unauthorized)));
+    add_contact(&env, &client, &owner, &attacker, "attacker");
+    env.ledger().with_mut(|li| li.timestamp += CONTACT_COOLING_OFF);
+
+    set_large_transfer_threshold(&env, &client, &owner, &usdc, 50);
+    assert_eq!(execute(&ops), Err(Ok(Error::Timelocked)));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&client.address), 100);
+}
+
+#[test]
+fn test_batch_limits_are_configurable() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    let alice = Address::generate(&env);
+    assert_eq!(
+        client.get_batch_limits(),
+        BatchLimits { max_ops: MAX_BATCH_OPS, max_depth: MAX_BATCH_DEPTH, max_complexity: MAX_BATCH_COMPLEXITY }
+    );
+
+    let zero = BatchLimits { max_ops: 0, max_depth: 2, max_complexity: 10 };
+    let sig = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_set_batch_limits(&zero, &sig), Err(Ok(Error::InvalidAmount)));
+    let too_deep = BatchLimits { max_ops: 2, max_depth: MAX_BATCH_DEPTH + 1, max_complexity: 10 };
+    assert_eq!(client.try_set_batch_limits(&too_deep, &sig), Err(Ok(Error::LimitExceeded)));
+
+    let limits = BatchLimits { max_ops: 2, max_depth: 2, max_complexity: 10 };
+    let sig = sign_action(&env, &owner, "set_batch_limits", &limits.clone().to_xdr(&env), client.get_nonce());
+    client.set_batch_limits(&limits, &sig);
+    assert_eq!(client.get_batch_limits(), limits);
+
+    let op = transfer_op(&env, &usdc, &client.address, &alice, 1);
+    let ops = vec![&env, op.clone(), op.clone(), op];
+    let sig = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_execute_batch(&ops, &sig), Err(Ok(Error::LimitExceeded)));
+}
+
+#[test]
+fn test_execute_batch_rejects_complex_batches() {
+    use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let alice = Address::generate(&env);
+    let transfer = Symbol::new(&env, "transfer");
+
+    let weight = 40u32;
+    let sig = sign_action(
+        &env,
+        &owner,
+        "set_op_weight",
+        &(transfer.clone(), weight).to_xdr(&env),
+        client.get_nonce(),
+    );
+    client.set_op_weight(&transfer, &weight, &sig);
+    assert_eq!(client.get_op_weights().get(transfer.clone()), Some(weight));
+
+    // 3 x 40 is over the default complexity of 100, checked before the signature
+    let mut ops = Vec::new(&env);
+    for _ in 0..3 {
+        ops.push_back(transfer_op(&env, &usdc, &client.address, &alice, 1));
+    }
+    let sig = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_execute_batch(&ops, &sig), Err(Ok(Error::LimitExceeded)));
+    ops.pop_back();
+    let sig = sign_batch(&env, &client, &owner, &ops);
+    client.execute_batch(&ops, &sig);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 2);
+
+    // Nested authorizations count towards the depth
+    let mut auth = Vec::new(&env);
+    for _ in 0..MAX_BATCH_DEPTH {
+        auth = vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: usdc.clone(),
+                    fn_name: Symbol::new(&env, "approve"),
+                    args: Vec::new(&env),
+                },
+                sub_invocations: auth,
+            }),
+        ];
+    }
+    let mut deep = transfer_op(&env, &usdc, &client.address, &alice, 1);
+    deep.auth = auth;
+    let deep = vec![&env, deep];
+    let sig = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_execute_batch(&deep, &sig), Err(Ok(Error::LimitExceeded)));
+
+    // Back to the default weight
+    let sig = sign_action(&env, &owner, "set_op_weight", &(transfer.clone(), 0u32).to_xdr(&env), client.get_nonce());
+    client.set_op_weight(&transfer, &0, &sig);
+    assert!(client.get_op_weights().is_empty());
+}
+
+#[test]
+fn test_execute_batch_best_effort_continues_past_failures() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let (alice, bob, carol) = (Address::generate(&env), Address::generate(&env), Address::generate(&env));
+
+    let ops = vec![
+        &env,
+        transfer_op(&env, &usdc, &client.address, &alice, 30),
+        // Overdraws
+        transfer_op(&env, &usdc, &client.address, &bob, 500),
+        transfer_op(&env, &usdc, &client.address, &carol, 20),
+    ];
+    let sig = sign_action(&env, &owner, "execute_batch_best_effort", &(ops.clone(), 1u32).to_xdr(&env), 0);
+    let outcomes = client.execute_batch_best_effort(&ops, &1, &sig);
+
+    assert_eq!(outcomes.get(0).unwrap(), BatchOpOutcome::Ok);
+    assert_ne!(outcomes.get(1).unwrap(), BatchOpOutcome::Ok);
+    assert_eq!(outcomes.get(2).unwrap(), BatchOpOutcome::Ok);
+    let usdc = token::Client::new(&env, &usdc);
+    assert_eq!(usdc.balance(&alice), 30);
+    assert_eq!(usdc.balance(&bob), 0);
+    assert_eq!(usdc.balance(&carol), 20);
+    assert_eq!(client.get_nonce(), 1);
+}
+
+#[test]
+fn test_execute_batch_best_effort_skips_after_failure_bound() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let alice = Address::generate(&env);
+
+    let ops = vec![
+        &env,
+        transfer_op(&env, &usdc, &client.address, &alice, 500),
+        transfer_op(&env, &usdc, &client.address, &alice, 10),
+    ];
+    let sig = sign_action(&env, &owner, "execute_batch_best_effort", &(ops.clone(), 0u32).to_xdr(&env), 0);
+    let outcomes = client.execute_batch_best_effort(&ops, &0, &sig);
+
+    assert_ne!(outcomes.get(0).unwrap(), BatchOpOutcome::Ok);
+    assert_eq!(outcomes.get(1).unwrap(), BatchOpOutcome::Skipped);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 0);
+}
+
+// ============================================================================
+// CONTRACT POLICY TESTS
+// ============================================================================
+
+fn list_contract(env: &Env, client: &WalletContractClient, key: &SigningKey, contract: &Address, listing: Option<ContractListing>) {
+    let payload = (contract.clone(), listing).to_xdr(env);
+    let sig = sign_action(env, key, "set_contract_listing", &payload, client.get_nonce());
+    client.set_contract_listing(contract, &listing, &sig);
+}
+
+fn sign_invoke(env: &Env, client: &WalletContractClient, key: &SigningKey, op: &Invocation) -> BytesN<64> {
+    let payload = (op.contract.clone(), op.fn_name.clone(), op.args.clone()).to_xdr(env);
+    sign_action(env, key, "invoke", &payload, client.get_nonce())
+}
+
+#[test]
+fn test_invoke_respects_denylist() {
+    use soroban_sdk::{IntoVal, TryFromVal};
+
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let usdc = create_token(&env);
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let alice = Address::generate(&env);
+
+    let op = transfer_op(&env, &usdc, &client.address, &alice, 40);
+    client.invoke(&op.contract, &op.fn_name, &op.args, &sign_invoke(&env, &client, &owner, &op));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 40);
+
+    // A blocked invoke goes through, so its event stays on chain
+    list_contract(&env, &client, &owner, &usdc, Some(ContractListing::Denied));
+    let nonce = client.get_nonce();
+    let outcome = client.invoke(&op.contract, &op.fn_name, &op.args, &sign_invoke(&env, &client, &owner, &op));
+    assert!(outcome.is_none());
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (Symbol::new(&env, "contract_blocked"), usdc.clone()).into_val(&env));
+    let blocked = ContractBlockedEvent { contract: usdc.clone(), fn_name: op.fn_name.clone() };
+    assert_eq!(ContractBlockedEvent::try_from_val(&env, &data).unwrap(), blocked);
+    assert_eq!(client.get_nonce(), nonce + 1);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 40);
+
+    // Batches are held to the same policy
+    let ops = vec![&env, op];
+    let sig = sign_batch(&env, &client, &owner, &ops);
+    assert_eq!(client.try_execute_batch(&ops, &sig), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_invoke_allowlist_mode() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (usdc, eurc) = (create_token(&env), create_token(&env));
+    token::StellarAssetClient::new(&env, &usdc).mock_all_auths().mint(&client.address, &100);
+    let alice = Address::generate(&env);
+
+    let sig = sign_action(&env, &owner, "set_allowlist_mode", &true.to_xdr(&env), client.get_nonce());
+    client.set_allowlist_mode(&true, &sig);
+    assert!(client.is_allowlist_mode());
+    list_contract(&env, &client, &owner, &usdc, Some(ContractListing::Allowed));
+    assert_eq!(client.get_contract_listing(&usdc), Some(ContractListing::Allowed));
+
+    let unlisted = transfer_op(&env, &eurc, &client.address, &alice, 1);
+    let sig = sign_invoke(&env, &client, &owner, &unlisted);
+    let outcome = client.invoke(&unlisted.contract, &unlisted.fn_name, &unlisted.args, &sig);
+    assert!(outcome.is_none());
+
+    let allowed = transfer_op(&env, &usdc, &client.address, &alice, 25);
+    client.invoke(&allowed.contract, &allowed.fn_name, &allowed.args, &sign_invoke(&env, &client, &owner, &allowed));
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 25);
+
+    list_contract(&env, &client, &owner, &usdc, None);
+    let sig = sign_invoke(&env, &client, &owner, &allowed);
+    let outcome = client.invoke(&allowed.contract, &allowed.fn_name, &allowed.args, &sig);
+    assert!(outcome.is_none());
+    assert_eq!(token::Client::new(&env, &usdc).balance(&alice), 25);
+}
+
+#[test]
+fn test_check_auth_refuses_denied_contracts() {
+    use soroban_sdk::auth::{Context, ContractContext};
+    use soroban_sdk::IntoVal;
+
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let drainer = Address::generate(&env);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let authorize = |contract: &Address| {
+        let context = Context::Contract(ContractContext {
+            contract: contract.clone(),
+            fn_name: Symbol::new(&env, "claim"),
+            args: (client.address.clone(),).into_val(&env),
+        });
+        let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+        let signature = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+        env.try_invoke_contract_check_auth::<Error>(&client.address, &payload, signature.into_val(&env), &vec![&env, context])
+    };
+    assert_eq!(authorize(&drainer), Ok(()));
+
+    list_contract(&env, &client, &owner, &drainer, Some(ContractListing::Denied));
+    assert_eq!(authorize(&drainer), Err(Ok(Error::Unauthorized)));
+
+    // In allowlist mode only listed contracts can be authorized
+    let sig = sign_action(&env, &owner, "set_allowlist_mode", &true.to_xdr(&env), client.get_nonce());
+    client.set_allowlist_mode(&true, &sig);
+    let dapp = Address::generate(&env);
+    assert_eq!(authorize(&dapp), Err(Ok(Error::Unauthorized)));
+    list_contract(&env, &client, &owner, &dapp, Some(ContractListing::Allowed));
+    assert_eq!(authorize(&dapp), Ok(()));
+}
+
+// ============================================================================
+// ASSET DISPLAY TESTS
+// ============================================================================
+
+fn set_display(env: &Env, client: &WalletContractClient, key: &SigningKey, asset: &Address, display: &Option<AssetDisplay>) -> Result<(), Error> {
+    let payload = (asset.clone(), display.clone()).to_xdr(env);
+    let sig = sign_action(env, key, "set_asset_display", &payload, client.get_nonce());
+    client.try_set_asset_display(asset, display, &sig).map(|_| ()).map_err(|e| e.unwrap())
+}
+
+#[test]
+fn test_asset_display_overrides() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let cetes = Address::generate(&env);
+
+    let display = AssetDisplay { symbol: String::from_str(&env, "CETES"), decimals: 2 };
+    set_display(&env, &client, &owner, &cetes, &Some(display.clone())).unwrap();
+    assert_eq!(client.get_asset_displays().get(cetes.clone()), Some(display));
+
+    set_display(&env, &client, &owner, &cetes, &None).unwrap();
+    assert!(client.get_asset_displays().is_empty());
+}
+
+#[test]
+fn test_asset_display_rejects_invalid() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let asset = Address::generate(&env);
+
+    let display = |symbol: &str, decimals: u32| Some(AssetDisplay { symbol: String::from_str(&env, symbol), decimals });
+    assert_eq!(set_display(&env, &client, &owner, &asset, &display("", 2)), Err(Error::InvalidAmount));
+    assert_eq!(set_display(&env, &client, &owner, &asset, &display("THIRTEENCHARS", 2)), Err(Error::InvalidAmount));
+    assert_eq!(set_display(&env, &client, &owner, &asset, &display("USD", 19)), Err(Error::InvalidAmount));
+
+    for _ in 0..MAX_DISPLAY_OVERRIDES {
+        set_display(&env, &client, &owner, &Address::generate(&env), &display("TKN", 7)).unwrap();
+    }
+    assert_eq!(set_display(&env, &client, &owner, &asset, &display("TKN", 7)), Err(Error::LimitExceeded));
+}
+
+// ============================================================================
+// NEW KEY APPROVAL TESTS
+// ============================================================================
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Wallet whose owner was just rotated from `signing_key(1)` to `new_owner`,
+/// with a 1-day new-key window and `signing_key(9)` as an older signer
+fn setup_rotated_wallet<'a>(env: &'a Env, new_owner: &SigningKey) -> WalletContractClient<'a> {
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let owner = signing_key(1);
+    let client = setup_wallet(env, &owner);
+    add_signer(env, &client, &owner, &Signer::Ed25519(public_key(env, &signing_key(9))), 1);
+    let sig = sign_action(env, &owner, "set_new_key_window", &DAY.to_xdr(env), client.get_nonce());
+    client.set_new_key_window(&DAY, &sig);
+
+    env.ledger().with_mut(|li| li.timestamp += 2 * DAY);
+    let new_key = public_key(env, new_owner);
+    let payload = Bytes::from_array(env, &new_key.to_array());
+    client.update_owner(&new_key, &sign_action(env, &owner, "update_owner", &payload, client.get_nonce()));
+    client
+}
+
+fn change_id(env: &Env, action: &str, payload: &Bytes) -> BytesN<32> {
+    let mut preimage = Bytes::from_slice(env, action.as_bytes());
+    preimage.append(payload);
+    env.crypto().sha256(&preimage).into()
+}
+
+fn propose_change(env: &Env, client: &WalletContractClient, owner: &SigningKey, change: &BytesN<32>) {
+    let payload = Bytes::from_array(env, &change.to_array());
+    client.propose_change(change, &sign_action(env, owner, "propose_change", &payload, client.get_nonce()));
+}
+
+#[test]
+fn test_new_key_waits_out_window_for_high_risk_change() {
+    let env = create_test_env();
+    let new_owner = signing_key(2);
+    let client = setup_rotated_wallet(&env, &new_owner);
+    let guardian = Signer::Ed25519(public_key(&env, &signing_key(5)));
+    let payload = guardian.clone().to_xdr(&env);
+
+    let sig = sign_action(&env, &new_owner, "add_guardian", &payload, client.get_nonce());
+    assert_eq!(client.try_add_guardian(&guardian, &sig), Err(Ok(Error::Timelocked)));
+
+    // So is every other entrypoint marked high-risk
+    let salt = Some(BytesN::from_array(&env, &[4u8; 32]));
+    let sig = sign_action(&env, &new_owner, "set_event_privacy", &salt.clone().to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_set_event_privacy(&salt, &sig), Err(Ok(Error::Timelocked)));
+
+    // Low-risk settings are unaffected
+    let display = Some(AssetDisplay { symbol: String::from_str(&env, "CETES"), decimals: 2 });
+    set_display(&env, &client, &new_owner, &Address::generate(&env), &display).unwrap();
+
+    propose_change(&env, &client, &new_owner, &change_id(&env, "add_guardian", &payload));
+    let sig = sign_action(&env, &new_owner, "add_guardian", &payload, client.get_nonce());
+    assert_eq!(client.try_add_guardian(&guardian, &sig), Err(Ok(Error::Timelocked)));
+
+    env.ledger().with_mut(|li| li.timestamp += DAY);
+    let sig = sign_action(&env, &new_owner, "add_guardian", &payload, client.get_nonce());
+    client.add_guardian(&guardian, &sig);
+    assert_eq!(client.get_guardians(), vec![&env, guardian]);
+}
+
+#[test]
+fn test_new_key_waits_out_window_to_change_auth_policy() {
+    let env = create_test_env();
+    let new_owner = signing_key(2);
+    let client = setup_rotated_wallet(&env, &new_owner);
+    let level = AuthLevel::Single;
+    let payload = (Symbol::new(&env, "update_owner"), level.clone()).to_xdr(&env);
+
+    assert_eq!(set_auth_policy(&env, &client, &new_owner, "update_owner", &level), Err(Error::Timelocked));
+    propose_change(&env, &client, &new_owner, &change_id(&env, "set_auth_policy", &payload));
+    env.ledger().with_mut(|li| li.timestamp += DAY);
+    set_auth_policy(&env, &client, &new_owner, "update_owner", &level).unwrap();
+}
+
+#[test]
+fn test_new_key_waits_out_window_to_leave_contacts_only_mode() {
+    let env = create_test_env();
+    let new_owner = signing_key(2);
+    let client = setup_rotated_wallet(&env, &new_owner);
+    let payload = false.to_xdr(&env);
+
+    let sig = sign_action(&env, &new_owner, "set_contacts_only", &payload, client.get_nonce());
+    assert_eq!(client.try_set_contacts_only(&false, &sig), Err(Ok(Error::Timelocked)));
+    propose_change(&env, &client, &new_owner, &change_id(&env, "set_contacts_only", &payload));
+    env.ledger().with_mut(|li| li.timestamp += DAY);
+    let sig = sign_action(&env, &new_owner, "set_contacts_only", &payload, client.get_nonce());
+    client.set_contacts_only(&false, &sig);
+}
+
+#[test]
+fn test_new_key_change_co_approved_by_older_signer() {
+    let env = create_test_env();
+    let new_owner = signing_key(2);
+    let client = setup_rotated_wallet(&env, &new_owner);
+    let guardian = Signer::Ed25519(public_key(&env, &signing_key(5)));
+    let payload = guardian.clone().to_xdr(&env);
+    let change = change_id(&env, "add_guardian", &payload);
+    propose_change(&env, &client, &new_owner, &change);
+
+    // A signer added inside the window can't co-approve
+    let fresh = signing_key(8);
+    env.as_contract(&client.address, || {
+        let mut signers = WalletContract::get_signers(env.clone());
+        signers.set(Signer::Ed25519(public_key(&env, &fresh)), 1);
+        env.storage().instance().set(&SignersKey::Signers, &signers);
+        env.storage().persistent().set(&ApprovalKey::SignerSince(Signer::Ed25519(public_key(&env, &fresh))), &(12 * DAY));
+    });
+    let id = Bytes::from_array(&env, &change.to_array());
+    let proof = guardian_proof(&env, &client, &fresh, "approve_change", &id);
+    assert_eq!(client.try_approve_change(&change, &proof), Err(Ok(Error::Unauthorized)));
+
+    let proof = guardian_proof(&env, &client, &signing_key(9), "approve_change", &id);
+    client.approve_change(&change, &proof);
+
+    let sig = sign_action(&env, &new_owner, "add_guardian", &payload, client.get_nonce());
+    client.add_guardian(&guardian, &sig);
+    // The approval is used up
+    assert_eq!(client.get_pending_change(&change), None);
+}
+
+#[test]
+fn test_keys_added_inside_window_cannot_cosign_high_risk_change() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let sig = sign_action(&env, &owner, "set_new_key_window", &DAY.to_xdr(&env), client.get_nonce());
+    client.set_new_key_window(&DAY, &sig);
+
+    // The owner key is old by now, the guardian isn't
+    env.ledger().with_mut(|li| li.timestamp += 2 * DAY);
+    let guardian = signing_key(5);
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+    set_auth_policy(&env, &client, &owner, "set_batch_limits", &AuthLevel::OwnerAndGuardian).unwrap();
+
+    let limits = BatchLimits { max_ops: 2, max_depth: 1, max_complexity: 10 };
+    let payload = limits.clone().to_xdr(&env);
+    cosign(&env, &client, &guardian, "set_batch_limits", &payload).unwrap();
+    let sig = sign_action(&env, &owner, "set_batch_limits", &payload, client.get_nonce());
+    assert_eq!(client.try_set_batch_limits(&limits, &sig), Err(Ok(Error::Unauthorized)));
+
+    env.ledger().with_mut(|li| li.timestamp += DAY);
+    let sig = sign_action(&env, &owner, "set_batch_limits", &payload, client.get_nonce());
+    client.set_batch_limits(&limits, &sig);
+    assert_eq!(client.get_batch_limits(), limits);
+}
+
+// ============================================================================
+// DEVICE TESTS
+// ============================================================================
+
+fn add_device(env: &Env, client: &WalletContractClient, owner: &SigningKey, device: &SigningKey, label: &str) {
+    let (public_key, label) = (public_key(env, device), String::from_str(env, label));
+    let sig = sign_action(env, owner, "add_device", &(public_key.clone(), label.clone()).to_xdr(env), client.get_nonce());
+    client.add_device(&public_key, &label, &sig);
+}
+
+#[test]
+fn test_device_signs_and_is_marked_used() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (phone, laptop) = (signing_key(2), signing_key(3));
+    env.ledger().with_mut(|li| li.sequence_number = 10);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+    add_device(&env, &client, &owner, &laptop, "Work laptop");
+
+    let devices = client.list_devices();
+    assert_eq!(devices.len(), 2);
+    let device = devices.iter().find(|d| d.public_key == public_key(&env, &phone)).unwrap();
+    assert_eq!(device.label, String::from_str(&env, "Pixel 8"));
+    assert_eq!((device.added_at, device.last_used), (10, 0));
+
+    env.ledger().with_mut(|li| li.sequence_number = 25);
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let signature = AuthSignature::Device(public_key(&env, &phone), sign_raw(&env, &phone, &message));
+    assert!(check_auth(&env, &client, &payload, signature));
+
+    let used = client.list_devices().iter().map(|d| (d.public_key, d.last_used)).collect::<std::vec::Vec<_>>();
+    assert!(used.contains(&(public_key(&env, &phone), 25)));
+    assert!(used.contains(&(public_key(&env, &laptop), 0)));
+}
+
+#[test]
+fn test_removed_device_is_cut_off() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let phone = signing_key(2);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+
+    let key = public_key(&env, &phone);
+    let payload = Bytes::from_array(&env, &key.to_array());
+    // The owner key alone can't unlink it
+    let sig = sign_action(&env, &owner, "remove_device", &payload, client.get_nonce());
+    let forged = sign_action(&env, &owner, "confirm_device_removal", &payload, client.get_nonce());
+    assert!(client.try_remove_device(&key, &forged, &sig).is_err());
+
+    let confirmation = sign_action(&env, &phone, "confirm_device_removal", &payload, client.get_nonce());
+    client.remove_device(&key, &confirmation, &sig);
+    assert!(client.list_devices().is_empty());
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let signature = AuthSignature::Device(key.clone(), sign_raw(&env, &phone, &message));
+    assert!(!check_auth(&env, &client, &payload, signature));
+
+    let dummy = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_remove_device(&key, &dummy, &dummy), Err(Ok(Error::NotFound)));
+}
+
+#[test]
+fn test_lost_device_is_removed_after_delay_unless_it_cancels() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let phone = signing_key(2);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+    env.ledger().with_mut(|li| li.timestamp = 10 * DAY);
+    let key = public_key(&env, &phone);
+    let payload = Bytes::from_array(&env, &key.to_array());
+    let queue = |client: &WalletContractClient| {
+        let sig = sign_action(&env, &owner, "queue_device_removal", &payload, client.get_nonce());
+        client.try_queue_device_removal(&key, &sig)
+    };
+
+    assert_eq!(queue(&client), Ok(Ok(10 * DAY + DEVICE_REMOVAL_DELAY)));
+    assert_eq!(queue(&client).err(), Some(Ok(Error::AlreadyExists)));
+    assert_eq!(client.try_execute_device_removal(&key), Err(Ok(Error::Timelocked)));
+
+    // The phone sees it and cancels; only it can
+    let sig = sign_action(&env, &owner, "cancel_device_removal", &payload, client.get_nonce());
+    assert!(client.try_cancel_device_removal(&key, &sig).is_err());
+    let sig = sign_action(&env, &phone, "cancel_device_removal", &payload, client.get_nonce());
+    client.cancel_device_removal(&key, &sig);
+    assert_eq!(client.get_device_removal(&key), None);
+    assert_eq!(client.try_execute_device_removal(&key), Err(Ok(Error::NotFound)));
+
+    // Lost for good: it goes once the delay is over
+    queue(&client).unwrap().unwrap();
+    env.ledger().with_mut(|li| li.timestamp += DEVICE_REMOVAL_DELAY);
+    client.execute_device_removal(&key);
+    assert!(client.list_devices().is_empty());
+    assert_eq!(client.get_device_removal(&key), None);
+}
+
+#[test]
+fn test_add_device_rejects_owner_duplicates_and_bad_labels() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let phone = signing_key(2);
+    add_device(&env, &client, &owner, &phone, "Pixel 8");
+
+    let dummy = BytesN::from_array(&env, &[0u8; 64]);
+    let label = String::from_str(&env, "Tablet");
+    assert_eq!(client.try_add_device(&public_key(&env, &phone), &label, &dummy), Err(Ok(Error::AlreadyExists)));
+    assert_eq!(client.try_add_device(&public_key(&env, &owner), &label, &dummy), Err(Ok(Error::AlreadyExists)));
+    let long = String::from_str(&env, "a label well past thirty-two bytes");
+    assert_eq!(client.try_add_device(&public_key(&env, &signing_key(4)), &long, &dummy), Err(Ok(Error::InvalidAmount)));
+}
+
+// ============================================================================
+// BALANCE SNAPSHOT TESTS
+// ============================================================================
+
+#[test]
+fn test_balance_snapshot_commits_hash_of_balances() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    set_committer(&env, &client, &owner);
+    let (usdc, cetes) = (create_token(&env), create_token(&env));
+    token::StellarAssetClient::new(&env, &usdc).mint(&client.address, &1_500);
+    token::StellarAssetClient::new(&env, &cetes).mint(&client.address, &42);
+
+    let assets = vec![&env, usdc.clone(), cetes.clone()];
+    let sig = sign_action(&env, &owner, "set_snapshot_assets", &assets.clone().to_xdr(&env), client.get_nonce());
+    client.set_snapshot_assets(&assets, &sig);
+
+    // The relayer's sweep keeps the dormant wallet live until its first snapshot
+    client.bump(&(SNAPSHOT_EPOCH_LEDGERS * 4));
+    env.ledger().with_mut(|li| li.sequence_number = SNAPSHOT_EPOCH_LEDGERS * 3 + 5);
+    let commitment = client.commit_balance_snapshot();
+    assert_eq!(instance_ttl(&env, &client), INSTANCE_BUMP_LEDGERS);
+    assert_eq!(commitment.ledger, SNAPSHOT_EPOCH_LEDGERS * 3 + 5);
+    assert_eq!(client.get_balance_snapshot(&3), commitment);
+    assert_eq!(client.get_latest_snapshot_epoch(), 3);
+
+    // The balances an auditor is shown reproduce the committed hash
+    let snapshot = BalanceSnapshot {
+        epoch: 3,
+        ledger: commitment.ledger,
+        balances: vec![
+            &env,
+            AssetBalance { asset: usdc, balance: 1_500 },
+            AssetBalance { asset: cetes, balance: 42 },
+        ],
+    };
+    let hash: BytesN<32> = env.crypto().sha256(&snapshot.clone().to_xdr(&env)).into();
+    assert_eq!(hash, commitment.hash);
+    let mut doctored = snapshot;
+    doctored.balances.set(0, AssetBalance { asset: create_token(&env), balance: 1_500 });
+    assert_ne!(BytesN::<32>::from(env.crypto().sha256(&doctored.to_xdr(&env))), commitment.hash);
+
+    // One snapshot per epoch
+    assert_eq!(client.try_commit_balance_snapshot(), Err(Ok(Error::AlreadyExists)));
+    env.ledger().with_mut(|li| li.sequence_number = SNAPSHOT_EPOCH_LEDGERS * 4);
+    client.commit_balance_snapshot();
+    assert_eq!(client.get_latest_snapshot_epoch(), 4);
+}
+
+#[test]
+fn test_balance_snapshot_needs_committer_and_assets() {
+    let env = create_test_env();
+    env.mock_all_auths();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+
+    assert_eq!(client.try_commit_balance_snapshot(), Err(Ok(Error::NotFound)));
+    set_committer(&env, &client, &owner);
+    assert_eq!(client.try_commit_balance_snapshot(), Err(Ok(Error::NotFound)));
+    assert_eq!(client.try_get_balance_snapshot(&0), Err(Ok(Error::NotFound)));
+}
+
+// ============================================================================
+// SUPPORT READ ACCESS TESTS
+// ============================================================================
+
+fn grant_read_access(env: &Env, client: &WalletContractClient, owner: &SigningKey, token_hash: &BytesN<32>, expires_at: u64) {
+    let payload = (token_hash.clone(), expires_at).to_xdr(env);
+    let sig = sign_action(env, owner, "grant_read_access", &payload, client.get_nonce());
+    client.grant_read_access(token_hash, &expires_at, &sig);
+}
+
+#[test]
+fn test_read_grant_expires_and_revokes() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let token_hash = BytesN::from_array(&env, &[7u8; 32]);
+
+    grant_read_access(&env, &client, &owner, &token_hash, 1_000 + 3_600);
+    let grant = client.get_read_grant(&token_hash).unwrap();
+    assert_eq!((grant.granted_at, grant.expires_at), (1_000, 4_600));
+    assert_eq!(client.get_read_grant(&BytesN::from_array(&env, &[8u8; 32])), None);
+
+    env.ledger().with_mut(|li| li.timestamp = 4_601);
+    assert_eq!(client.get_read_grant(&token_hash), None);
+
+    // Revoked before expiry: gone at once
+    grant_read_access(&env, &client, &owner, &token_hash, 8_000);
+    let payload = Bytes::from_array(&env, &token_hash.to_array());
+    let sig = sign_action(&env, &owner, "revoke_read_access", &payload, client.get_nonce());
+    client.revoke_read_access(&token_hash, &sig);
+    assert_eq!(client.get_read_grant(&token_hash), None);
+    assert_eq!(client.try_revoke_read_access(&token_hash, &sig), Err(Ok(Error::NotFound)));
+}
+
+#[test]
+fn test_read_grant_is_time_boxed() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (token_hash, dummy) = (BytesN::from_array(&env, &[7u8; 32]), BytesN::from_array(&env, &[0u8; 64]));
+
+    assert_eq!(client.try_grant_read_access(&token_hash, &1_000, &dummy), Err(Ok(Error::Expired)));
+    let too_long = 1_000 + MAX_READ_GRANT_DURATION + 1;
+    assert_eq!(client.try_grant_read_access(&token_hash, &too_long, &dummy), Err(Ok(Error::LimitExceeded)));
+}
+
+// ============================================================================
+// TTL TESTS
+// ============================================================================
+
+fn instance_ttl(env: &Env, client: &WalletContractClient) -> u32 {
+    use soroban_sdk::testutils::storage::Instance as _;
+    env.as_contract(&client.address, || env.storage().instance().get_ttl())
+}
+
+#[test]
+fn test_check_auth_extends_instance_ttl() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    assert!(instance_ttl(&env, &client) < INSTANCE_BUMP_LEDGERS);
+
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    let message = Bytes::from_slice(&env, &auth_message(&payload, 0));
+    assert!(check_auth(&env, &client, &payload, AuthSignature::Ed25519(sign_raw(&env, &owner, &message))));
+    assert_eq!(instance_ttl(&env, &client), INSTANCE_BUMP_LEDGERS);
+}
+
+#[test]
+fn test_bump_extends_dormant_wallet() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+
+    client.bump(&(INSTANCE_BUMP_LEDGERS * 4));
+    assert_eq!(instance_ttl(&env, &client), INSTANCE_BUMP_LEDGERS * 4);
+    // Never shortens
+    client.bump(&100);
+    assert_eq!(instance_ttl(&env, &client), INSTANCE_BUMP_LEDGERS * 4);
+
+    let too_long = env.storage().max_ttl() + 1;
+    assert_eq!(client.try_bump(&too_long), Err(Ok(Error::LimitExceeded)));
+}
+
+// ============================================================================
+// UPGRADE TESTS
+// ============================================================================
+
+/// Built by `stellar contract build` (`make build`)
+const WALLET_WASM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/wasm32v1-none/release/accountAbstraction.wasm");
+
+fn propose_upgrade(env: &Env, client: &WalletContractClient, owner: &SigningKey, wasm_hash: &BytesN<32>) {
+    let payload = Bytes::from_array(env, &wasm_hash.to_array());
+    let sig = sign_action(env, owner, "propose_upgrade", &payload, client.get_nonce());
+    client.propose_upgrade(wasm_hash, &sig);
+}
+
+#[test]
+fn test_upgrade_waits_out_delay_and_can_be_cancelled() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (owner, laptop) = (signing_key(1), signing_key(3));
+    let client = setup_wallet(&env, &owner);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &laptop)), 1);
+    let wasm_hash = BytesN::from_array(&env, &[9u8; 32]);
+
+    propose_upgrade(&env, &client, &owner, &wasm_hash);
+    let pending = client.get_pending_upgrade().unwrap();
+    assert_eq!((pending.wasm_hash, pending.eta), (wasm_hash.clone(), 1_000 + UPGRADE_DELAY));
+    assert_eq!(client.try_apply_upgrade(), Err(Ok(Error::Timelocked)));
+    let dummy = BytesN::from_array(&env, &[0u8; 64]);
+    assert_eq!(client.try_propose_upgrade(&wasm_hash, &dummy), Err(Ok(Error::AlreadyExists)));
+
+    // Another device stops an upgrade it didn't expect
+    let message = Bytes::from_slice(&env, &action_bytes("cancel_upgrade", client.get_nonce()));
+    let proof = SignerProof::Ed25519(public_key(&env, &laptop), sign_raw(&env, &laptop, &message));
+    client.cancel_upgrade(&proof);
+    assert_eq!(client.get_pending_upgrade(), None);
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + UPGRADE_DELAY);
+    assert_eq!(client.try_apply_upgrade(), Err(Ok(Error::NotFound)));
+}
+
+#[test]
+fn test_apply_upgrade_after_delay() {
+    let env = create_test_env();
+    let Ok(wasm) = std::fs::read(WALLET_WASM) else {
+        std::eprintln!("skipping: {WALLET_WASM} not built");
+        return;
+    };
+    let wasm_hash = env.deployer().upload_contract_wasm(wasm.as_slice());
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+
+    propose_upgrade(&env, &client, &owner, &wasm_hash);
+    env.ledger().with_mut(|li| li.timestamp = UPGRADE_DELAY);
+    client.apply_upgrade();
+    assert_eq!(client.get_pending_upgrade(), None);
+    // State carries over to the new code
+    assert_eq!(client.get_owner(), public_key(&env, &owner));
+}
+
+/// Factory stand-in serving one latest version
+#[contract]
+struct MockFactory;
+
+#[contractimpl]
+impl MockFactory {
+    pub fn set_latest(env: Env, wasm_hash: BytesN<32>, info: Option<VersionInfo>) {
+        env.storage().instance().set(&Symbol::new(&env, "latest"), &(wasm_hash, info));
+    }
+
+    pub fn get_wallet_wasm_hash(env: Env) -> BytesN<32> {
+        let (wasm_hash, _): (BytesN<32>, Option<VersionInfo>) =
+            env.storage().instance().get(&Symbol::new(&env, "latest")).unwrap();
+        wasm_hash
+    }
+
+    pub fn get_version_info(env: Env, wasm_hash: BytesN<32>) -> Option<VersionInfo> {
+        let (latest, info): (BytesN<32>, Option<VersionInfo>) =
+            env.storage().instance().get(&Symbol::new(&env, "latest")).unwrap();
+        info.filter(|_| latest == wasm_hash)
+    }
+}
+
+fn version_info(env: &Env, breaking: u32) -> VersionInfo {
+    VersionInfo {
+        changelog: Changelog {
+            breaking,
+            migrated_keys: Vec::new(env),
+            new_entrypoints: vec![env, Symbol::new(env, "upgrade_to_latest")],
+        },
+        registered_at: 1,
+    }
+}
+
+fn upgrade_to_latest_sig(env: &Env, client: &WalletContractClient, owner: &SigningKey, factory: &Address, wasm_hash: &BytesN<32>, ack: bool) -> BytesN<64> {
+    let payload = (factory.clone(), wasm_hash.clone(), ack).to_xdr(env);
+    sign_action(env, owner, "upgrade_to_latest", &payload, client.get_nonce())
+}
+
+#[test]
+fn test_upgrade_to_latest_reads_factory_changelog() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let factory = env.register(MockFactory, ());
+    let factory_client = MockFactoryClient::new(&env, &factory);
+    let wasm_hash = BytesN::from_array(&env, &[9u8; 32]);
+
+    // Code the factory has no changelog for is refused
+    factory_client.set_latest(&wasm_hash, &None);
+    let sig = upgrade_to_latest_sig(&env, &client, &owner, &factory, &wasm_hash, false);
+    assert_eq!(client.try_upgrade_to_latest(&factory, &false, &sig), Err(Ok(Error::NotFound)));
+
+    factory_client.set_latest(&wasm_hash, &Some(version_info(&env, 0)));
+    assert_eq!(client.upgrade_to_latest(&factory, &false, &sig), wasm_hash);
+    let pending = client.get_pending_upgrade().unwrap();
+    assert_eq!((pending.wasm_hash, pending.eta), (wasm_hash, 1_000 + UPGRADE_DELAY));
+}
+
+#[test]
+fn test_upgrade_to_latest_requires_breaking_acknowledgment() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let factory = env.register(MockFactory, ());
+    let wasm_hash = BytesN::from_array(&env, &[9u8; 32]);
+    let info = version_info(&env, acceslyinterface::versions::BREAKING_STORAGE);
+    MockFactoryClient::new(&env, &factory).set_latest(&wasm_hash, &Some(info));
+
+    let sig = upgrade_to_latest_sig(&env, &client, &owner, &factory, &wasm_hash, false);
+    assert_eq!(client.try_upgrade_to_latest(&factory, &false, &sig), Err(Ok(Error::BreakingUpgrade)));
+    // The acknowledgment is part of what the owner signs
+    assert!(client.try_upgrade_to_latest(&factory, &true, &sig).is_err());
+    assert_eq!(client.get_pending_upgrade(), None);
+
+    let sig = upgrade_to_latest_sig(&env, &client, &owner, &factory, &wasm_hash, true);
+    client.upgrade_to_latest(&factory, &true, &sig);
+    assert_eq!(client.get_pending_upgrade().unwrap().wasm_hash, wasm_hash);
+}
+
+// ============================================================================
+// STORAGE MIGRATION TESTS
+// ============================================================================
+
+/// Strip a wallet back to the v1 layout: owner, email hash and nonce
+fn downgrade_to_v1(env: &Env, client: &WalletContractClient) {
+    env.as_contract(&client.address, || {
+        let storage = env.storage().instance();
+        storage.remove(&DataKey::StorageVersion);
+        storage.remove(&SignersKey::Signers);
+        storage.remove(&SignersKey::Threshold);
+        storage.remove(&SessionKey::SessionEpoch);
+        storage.remove(&BatchKey::Limits);
+    });
+}
+
+#[test]
+fn test_init_writes_current_layout() {
+    let env = create_test_env();
+    let client = setup_wallet(&env, &signing_key(1));
+
+    assert_eq!(client.get_storage_version(), STORAGE_VERSION);
+    // Nothing to do
+    assert_eq!(client.migrate(), STORAGE_VERSION);
+}
+
+#[test]
+fn test_migrate_v1_layout() {
+    let env = create_test_env();
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let sig = sign_action(&env, &owner, "revoke_all_sessions", &Bytes::new(&env), client.get_nonce());
+    client.revoke_all_sessions(&sig);
+    downgrade_to_v1(&env, &client);
+    // The epoch a v1 wallet already had survives
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&SessionKey::SessionEpoch, &1u32)
+    });
+    assert_eq!(client.get_storage_version(), 1);
+    let report = client.self_check(&Vec::new(&env));
+    assert_eq!(report.issues, vec![&env, Symbol::new(&env, "storage_not_migrated")]);
+
+    assert_eq!(client.migrate(), STORAGE_VERSION);
+    assert_eq!(client.get_storage_version(), STORAGE_VERSION);
+    let limits = client.get_batch_limits();
+    env.as_contract(&client.address, || {
+        let storage = env.storage().instance();
+        assert_eq!(storage.get(&SignersKey::Signers), Some(soroban_sdk::Map::<Signer, u32>::new(&env)));
+        assert_eq!(storage.get(&SignersKey::Threshold), Some(0u32));
+        assert_eq!(storage.get(&SessionKey::SessionEpoch), Some(1u32));
+        assert_eq!(storage.get(&BatchKey::Limits), Some(limits));
+    });
+    assert!(client.self_check(&Vec::new(&env)).issues.is_empty());
+    // The owner key still signs
+    let sig = sign_action(&env, &owner, "revoke_all_sessions", &Bytes::new(&env), client.get_nonce());
+    client.revoke_all_sessions(&sig);
+}
+
+#[test]
+fn test_migrate_rejects_newer_layout() {
+    let env = create_test_env();
+    let client = setup_wallet(&env, &signing_key(1));
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::StorageVersion, &(STORAGE_VERSION + 1))
+    });
+
+    assert_eq!(client.try_migrate(), Err(Ok(Error::UnsupportedVersion)));
+
+    let uninitialized = WalletContractClient::new(&env, &create_contract(&env));
+    assert_eq!(uninitialized.try_migrate(), Err(Ok(Error::NotInitialized)));
+}
+
+// ============================================================================
+// EMERGENCY FREEZE TESTS
+// ============================================================================
+
+fn freeze_proof(env: &Env, client: &WalletContractClient, key: &SigningKey) -> SignerProof {
+    let message = Bytes::from_slice(env, &action_bytes("freeze", client.get_nonce()));
+    SignerProof::Ed25519(public_key(env, key), sign_raw(env, key, &message))
+}
+
+#[test]
+fn test_freeze_blocks_wallet_until_unfreeze() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (owner, phone) = (signing_key(1), signing_key(3));
+    let client = setup_wallet(&env, &owner);
+    add_device(&env, &client, &owner, &phone, "phone");
+
+    let stranger = freeze_proof(&env, &client, &signing_key(23));
+    assert_eq!(client.try_freeze(&stranger), Err(Ok(Error::Unauthorized)));
+    client.freeze(&freeze_proof(&env, &client, &phone));
+    let freeze = client.get_freeze().unwrap();
+    assert_eq!(freeze.frozen_at, 1_000);
+    assert_eq!(freeze.frozen_by, Signer::Ed25519(public_key(&env, &phone)));
+
+    // Neither the owner key nor the device authorizes anything
+    let payload = BytesN::from_array(&env, &[7u8; 32]);
+    let nonce = client.get_nonce();
+    let message = Bytes::from_slice(&env, &auth_message(&payload, nonce));
+    let owner_sig = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+    assert_eq!(try_check_auth(&env, &client, &payload, owner_sig.clone()), Err(Error::Frozen));
+    let device_sig = AuthSignature::Device(public_key(&env, &phone), sign_raw(&env, &phone, &message));
+    assert_eq!(try_check_auth(&env, &client, &payload, device_sig), Err(Error::Frozen));
+    let sig = sign_action(&env, &owner, "set_new_key_window", &60u64.to_xdr(&env), nonce);
+    assert_eq!(client.try_set_new_key_window(&60, &sig), Err(Ok(Error::Frozen)));
+
+    let sig = sign_action(&env, &owner, "unfreeze", &Bytes::new(&env), nonce);
+    assert_eq!(client.try_unfreeze(&sig), Err(Ok(Error::Timelocked)));
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + UNFREEZE_DELAY);
+    client.unfreeze(&sig);
+    assert_eq!(client.get_freeze(), None);
+
+    let message = Bytes::from_slice(&env, &auth_message(&payload, client.get_nonce()));
+    let owner_sig = AuthSignature::Ed25519(sign_raw(&env, &owner, &message));
+    assert!(check_auth(&env, &client, &payload, owner_sig));
+}
+
+#[test]
+fn test_guardian_freeze_leaves_recovery_open() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (owner, guardian) = (signing_key(1), signing_key(4));
+    let client = setup_wallet(&env, &owner);
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+
+    client.freeze(&freeze_proof(&env, &client, &guardian));
+
+    // Guardians can still start a recovery and the owner can still veto it
+    let new_owner = public_key(&env, &signing_key(5));
+    let payload = new_owner.clone().to_xdr(&env);
+    let proof = guardian_proof(&env, &client, &guardian, "initiate_recovery", &payload);
+    client.initiate_recovery(&new_owner, &proof);
+    let sig = sign_action(&env, &owner, "veto_recovery", &Bytes::new(&env), client.get_nonce());
+    client.veto_recovery(&sig);
+
+    // Freezing again restarts the delay
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + UNFREEZE_DELAY / 2);
+    client.freeze(&freeze_proof(&env, &client, &guardian));
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + UNFREEZE_DELAY);
+    let sig = sign_action(&env, &owner, "unfreeze", &Bytes::new(&env), client.get_nonce());
+    assert_eq!(client.try_unfreeze(&sig), Err(Ok(Error::Timelocked)));
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + UNFREEZE_DELAY / 2 + UNFREEZE_DELAY);
+    client.unfreeze(&sig);
+}
+
+#[test]
+fn test_reported_key_is_quarantined_and_wallet_frozen() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (owner, phone, laptop) = (signing_key(1), signing_key(3), signing_key(4));
+    let client = setup_wallet(&env, &owner);
+    add_device(&env, &client, &owner, &phone, "phone");
+    add_device(&env, &client, &owner, &laptop, "laptop");
+
+    let fingerprint: BytesN<32> = env.crypto().sha256(&public_key(&env, &laptop).into()).into();
+    let evidence = BytesN::from_array(&env, &[5u8; 32]);
+    let payload = (fingerprint.clone(), evidence.clone()).to_xdr(&env);
+    let stranger = guardian_proof(&env, &client, &signing_key(23), "report_compromise", &payload);
+    assert_eq!(client.try_report_compromise(&fingerprint, &evidence, &stranger), Err(Ok(Error::Unauthorized)));
+
+    let proof = guardian_proof(&env, &client, &phone, "report_compromise", &payload);
+    client.report_compromise(&fingerprint, &evidence, &proof);
+    let reported = client.get_quarantined(&fingerprint).unwrap();
+    assert_eq!(reported.evidence_hash, evidence);
+    assert_eq!(reported.reported_by, Signer::Ed25519(public_key(&env, &phone)));
+    assert_eq!(client.get_freeze().unwrap().frozen_by, Signer::Ed25519(public_key(&env, &phone)));
+    let proof = guardian_proof(&env, &client, &phone, "report_compromise", &payload);
+    assert_eq!(client.try_report_compromise(&fingerprint, &evidence, &proof), Err(Ok(Error::AlreadyExists)));
+
+    // Lifting the freeze doesn't lift the quarantine
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + UNFREEZE_DELAY);
+    let sig = sign_action(&env, &owner, "unfreeze", &Bytes::new(&env), client.get_nonce());
+    client.unfreeze(&sig);
+    let payload32 = BytesN::from_array(&env, &[7u8; 32]);
+    let message = Bytes::from_slice(&env, &auth_message(&payload32, client.get_nonce()));
+    let laptop_sig = AuthSignature::Device(public_key(&env, &laptop), sign_raw(&env, &laptop, &message));
+    assert_eq!(try_check_auth(&env, &client, &payload32, laptop_sig.clone()), Err(Error::Quarantined));
+    let phone_sig = AuthSignature::Device(public_key(&env, &phone), sign_raw(&env, &phone, &message));
+    assert!(check_auth(&env, &client, &payload32, phone_sig));
+
+    // The quarantined key can't report anyone either
+    let other: BytesN<32> = env.crypto().sha256(&public_key(&env, &phone).into()).into();
+    let payload = (other.clone(), evidence.clone()).to_xdr(&env);
+    let proof = guardian_proof(&env, &client, &laptop, "report_compromise", &payload);
+    assert_eq!(client.try_report_compromise(&other, &evidence, &proof), Err(Ok(Error::Quarantined)));
+
+    let release = Bytes::from_array(&env, &fingerprint.to_array());
+    let sig = sign_action(&env, &owner, "release_quarantine", &release, client.get_nonce());
+    client.release_quarantine(&fingerprint, &sig);
+    assert_eq!(client.get_quarantined(&fingerprint), None);
+    let message = Bytes::from_slice(&env, &auth_message(&payload32, client.get_nonce()));
+    let laptop_sig = AuthSignature::Device(public_key(&env, &laptop), sign_raw(&env, &laptop, &message));
+    assert!(check_auth(&env, &client, &payload32, laptop_sig));
+}
+
+#[test]
+fn test_owner_key_reported_only_when_guardians_can_recover() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (owner, phone, guardian) = (signing_key(1), signing_key(3), signing_key(20));
+    let client = setup_wallet(&env, &owner);
+    add_device(&env, &client, &owner, &phone, "phone");
+
+    let fingerprint: BytesN<32> = env.crypto().sha256(&public_key(&env, &owner).into()).into();
+    let evidence = BytesN::from_array(&env, &[5u8; 32]);
+    let payload = (fingerprint.clone(), evidence.clone()).to_xdr(&env);
+    // Without guardians nothing could replace the owner key, so a device
+    // can't lock it out
+    let proof = guardian_proof(&env, &client, &phone, "report_compromise", &payload);
+    assert_eq!(client.try_report_compromise(&fingerprint, &evidence, &proof), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.get_freeze(), None);
+    assert_eq!(client.get_quarantined(&fingerprint), None);
+
+    setup_guardians(&env, &client, &owner, &[&guardian], 1);
+    let proof = guardian_proof(&env, &client, &phone, "report_compromise", &payload);
+    client.report_compromise(&fingerprint, &evidence, &proof);
+    assert!(client.get_freeze().is_some());
+    let sig = sign_action(&env, &owner, "unfreeze", &Bytes::new(&env), client.get_nonce());
+    assert_eq!(client.try_unfreeze(&sig), Err(Ok(Error::Timelocked)));
+
+    // The guardians replace the owner key
+    let new_owner = public_key(&env, &signing_key(2));
+    let proof = guardian_proof(&env, &client, &guardian, "initiate_recovery", &new_owner.clone().to_xdr(&env));
+    client.initiate_recovery(&new_owner, &proof);
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + RECOVERY_DELAY);
+    client.execute_recovery();
+    assert_eq!(client.get_owner(), new_owner);
+}
+
+// ============================================================================
+// PRE-AUTHORIZATION TESTS
+// ============================================================================
+
+fn pre_authorize(env: &Env, client: &WalletContractClient, owner: &SigningKey, payload: &BytesN<32>, expiry: u64) {
+    let sig = sign_action(env, owner, "pre_authorize", &(payload.clone(), expiry).to_xdr(env), client.get_nonce());
+    client.pre_authorize(payload, &expiry, &sig);
+}
+
+#[test]
+fn test_preauth_accepts_payload_once_until_expiry() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (payload, other, late) = (
+        BytesN::from_array(&env, &[7u8; 32]),
+        BytesN::from_array(&env, &[8u8; 32]),
+        BytesN::from_array(&env, &[9u8; 32]),
+    );
+
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::PreAuthorized), Err(Error::Unauthorized));
+    pre_authorize(&env, &client, &owner, &payload, 2_000);
+    pre_authorize(&env, &client, &owner, &late, 2_000);
+    assert_eq!(client.get_preauth(&payload), Some(2_000));
+
+    // Only the approved payload goes through, and only once
+    assert_eq!(try_check_auth(&env, &client, &other, AuthSignature::PreAuthorized), Err(Error::Unauthorized));
+    assert!(check_auth(&env, &client, &payload, AuthSignature::PreAuthorized));
+    assert_eq!(client.get_preauth(&payload), None);
+    assert!(!check_auth(&env, &client, &payload, AuthSignature::PreAuthorized));
+
+    env.ledger().with_mut(|li| li.timestamp = 2_001);
+    assert_eq!(client.get_preauth(&late), None);
+    assert_eq!(try_check_auth(&env, &client, &late, AuthSignature::PreAuthorized), Err(Error::Expired));
+}
+
+#[test]
+fn test_preauth_rejects_bad_expiry_and_threshold() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let payload = BytesN::from_array(&env, &[7u8; 32]);
+    let nonce = client.get_nonce();
+
+    for (expiry, error) in [(1_000, Error::Expired), (1_001 + MAX_PREAUTH_WINDOW, Error::LimitExceeded)] {
+        let sig = sign_action(&env, &owner, "pre_authorize", &(payload.clone(), expiry).to_xdr(&env), nonce);
+        assert_eq!(client.try_pre_authorize(&payload, &expiry, &sig), Err(Ok(error)));
+    }
+    let sig = sign_action(&env, &signing_key(2), "pre_authorize", &(payload.clone(), 2_000u64).to_xdr(&env), nonce);
+    assert!(client.try_pre_authorize(&payload, &2_000, &sig).is_err());
+
+    // An approval made before a threshold was set can't bypass it
+    pre_authorize(&env, &client, &owner, &payload, 2_000);
+    add_signer(&env, &client, &owner, &Signer::Ed25519(public_key(&env, &owner)), 1);
+    set_threshold(&env, &client, &owner, 1);
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::PreAuthorized), Err(Error::Unauthorized));
+    let other = BytesN::from_array(&env, &[8u8; 32]);
+    let sig = sign_action(&env, &owner, "pre_authorize", &(other.clone(), 2_000u64).to_xdr(&env), client.get_nonce());
+    assert_eq!(client.try_pre_authorize(&other, &2_000, &sig), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_revoke_preauth() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let payload = BytesN::from_array(&env, &[7u8; 32]);
+    let hash_bytes = Bytes::from_array(&env, &payload.to_array());
+
+    let sig = sign_action(&env, &owner, "revoke_preauth", &hash_bytes, client.get_nonce());
+    assert_eq!(client.try_revoke_preauth(&payload, &sig), Err(Ok(Error::NotFound)));
+
+    pre_authorize(&env, &client, &owner, &payload, 2_000);
+    let sig = sign_action(&env, &owner, "revoke_preauth", &hash_bytes, client.get_nonce());
+    client.revoke_preauth(&payload, &sig);
+    assert_eq!(client.get_preauth(&payload), None);
+    assert_eq!(try_check_auth(&env, &client, &payload, AuthSignature::PreAuthorized), Err(Error::Unauthorized));
+}
+
+// ============================================================================
+// HEALTH CHECK TESTS
+// ============================================================================
+
+#[test]
+fn test_self_check_healthy_wallet() {
+    let env = create_test_env();
+    let client = setup_wallet(&env, &signing_key(1));
+
+    let report = client.self_check(&Vec::new(&env));
+    assert_eq!(report.version, String::from_str(&env, env!("CARGO_PKG_VERSION")));
+    assert!(report.issues.is_empty());
+    assert!(report.orphaned_sessions.is_empty());
+}
+
+#[test]
+fn test_self_check_reports_problems() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+    let (revoked, expired, live) = (signing_key(8), signing_key(9), signing_key(10));
+
+    create_session(&env, &client, &owner, &revoked, 5_000);
+    let sig = sign_action(&env, &owner, "revoke_all_sessions", &Bytes::new(&env), client.get_nonce());
+    client.revoke_all_sessions(&sig);
+    create_session(&env, &client, &owner, &expired, 2_000);
+    create_session(&env, &client, &owner, &live, 9_000);
+    env.ledger().with_mut(|li| li.timestamp = 3_000);
+
+    env.as_contract(&client.address, || env.storage().instance().remove(&DataKey::Nonce));
+
+    let keys = vec![
+        &env,
+        public_key(&env, &revoked),
+        public_key(&env, &expired),
+        public_key(&env, &live),
+        // Never created
+        public_key(&env, &signing_key(11)),
+    ];
+    let report = client.self_check(&keys);
+    assert_eq!(report.issues, vec![&env, Symbol::new(&env, "missing_nonce")]);
+    assert_eq!(
+        report.orphaned_sessions,
+        vec![&env, public_key(&env, &revoked), public_key(&env, &expired)]
+    );
+}
+
+#[test]
+fn test_ping_reports_version_ledger_and_channels() {
+    let env = create_test_env();
+    env.ledger().with_mut(|li| li.sequence_number = 42);
+    let owner = signing_key(1);
+    let client = setup_wallet(&env, &owner);
+
+    let (version, ledger, channels) = client.ping();
+    assert_eq!(version, String::from_str(&env, env!("CARGO_PKG_VERSION")));
+    assert_eq!(ledger, 42);
+    assert_eq!(channels, 0);
+    // Once per ledger
+    assert_eq!(client.try_ping(), Err(Ok(Error::LimitExceeded)));
+
+    // Each channel counts once however often it's used; channel 0 never does
+    let payload = BytesN::from_array(&env, &[5u8; 32]);
+    for (channel, nonce) in [(1, 0), (1, 1), (2, 0)] {
+        let message = Bytes::from_slice(&env, &channel_auth_message(&payload, channel, nonce));
+        let signature = ChannelSignature::Ed25519(sign_raw(&env, &owner, &message));
+        assert!(check_auth(&env, &client, &payload, AuthSignature::Channel(channel, signature)));
+    }
+    let plain = sign_raw(&env, &owner, &Bytes::from_slice(&env, &auth_message(&payload, 0)));
+    assert!(check_auth(&env, &client, &payload, AuthSignature::Ed25519(plain)));
+    env.ledger().with_mut(|li| li.sequence_number = 43);
+    assert_eq!(client.ping().2, 2);
+}
+
 // ============================================================================
 // STORAGE ISOLATION TESTS
 // ============================================================================
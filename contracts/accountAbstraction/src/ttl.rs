@@ -0,0 +1,41 @@
+// ============================================================================
+// INSTANCE TTL
+//
+// Most wallet state lives in instance storage, which is archived together
+// with the contract once its TTL runs out. Every `__check_auth` tops the
+// instance up to a month, so a wallet in use never gets near archival.
+// Dormant wallets are kept alive by the relayer calling `bump`, which
+// anyone may call: extending a TTL costs the caller fees and changes
+// nothing else.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, Env};
+
+use crate::*;
+
+/// Ledgers per day at 5s ledgers
+const DAY_LEDGERS: u32 = 17_280;
+/// What `__check_auth` extends the instance to
+pub const INSTANCE_BUMP_LEDGERS: u32 = 30 * DAY_LEDGERS;
+/// Below this many ledgers left, `__check_auth` extends
+const INSTANCE_BUMP_THRESHOLD: u32 = INSTANCE_BUMP_LEDGERS - DAY_LEDGERS;
+
+#[contractimpl]
+impl WalletContract {
+    /// Extend the wallet instance and code to live at least `ttl` more
+    /// ledgers. Meant for the relayer's sweep of dormant wallets.
+    pub fn bump(env: Env, ttl: u32) -> Result<(), Error> {
+        if ttl > env.storage().max_ttl() {
+            return Err(Error::LimitExceeded);
+        }
+        env.storage().instance().extend_ttl(ttl, ttl);
+        Ok(())
+    }
+}
+
+/// Top the instance up to `INSTANCE_BUMP_LEDGERS` when it runs low
+pub(crate) fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_BUMP_THRESHOLD, INSTANCE_BUMP_LEDGERS);
+}
@@ -0,0 +1,170 @@
+// ============================================================================
+// TIMELOCKED UPGRADES
+//
+// The owner can move the wallet to new code, in two steps: `propose_upgrade`
+// (owner-signed, over the wasm hash) starts a fixed delay, and once it has
+// passed anyone may `apply_upgrade`, normally the relayer. New code can do
+// anything with the wallet, so a stolen owner key must not be able to swap
+// it in quietly: during the delay the owner or any other registered device
+// can cancel, as with a key rotation.
+//
+// `upgrade_to_latest` proposes whatever code the factory currently deploys
+// new wallets with, after reading the changelog the factory registered for
+// it. Breaking releases are only taken when the owner acknowledges them,
+// and the signature covers the factory, the hash and the acknowledgment, so
+// a relayer can't substitute any of them.
+// ============================================================================
+
+use acceslyinterface::VersionInfo;
+use soroban_sdk::{
+    contractclient, contractimpl, contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol,
+};
+
+use crate::freeze::ensure_not_frozen;
+use crate::rotation::is_registered;
+use crate::signers::verify_proof;
+use crate::*;
+
+/// Delay between proposing and applying an upgrade (2 days)
+pub const UPGRADE_DELAY: u64 = 2 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUpgrade {
+    pub wasm_hash: BytesN<32>,
+    /// Earliest ledger timestamp `apply_upgrade` is accepted at
+    pub eta: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum UpgradeKey {
+    PendingUpgrade,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeCancelledEvent {
+    pub wasm_hash: BytesN<32>,
+    /// Device that cancelled
+    pub cancelled_by: Signer,
+}
+
+/// The parts of the wallet factory wallets read their upgrades from
+#[contractclient(name = "WalletFactoryClient")]
+pub trait WalletFactory {
+    fn get_wallet_wasm_hash(env: Env) -> BytesN<32>;
+    fn get_version_info(env: Env, wasm_hash: BytesN<32>) -> Option<VersionInfo>;
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Schedule an upgrade to the uploaded wasm `wasm_hash`
+    pub fn propose_upgrade(
+        env: Env,
+        wasm_hash: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if Self::get_pending_upgrade(env.clone()).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let payload = Bytes::from_array(&env, &wasm_hash.to_array());
+        Self::require_high_risk_signature(&env, "propose_upgrade", payload, signature)?;
+
+        schedule_upgrade(&env, wasm_hash);
+        Ok(())
+    }
+
+    /// Schedule an upgrade to the code `factory` currently deploys, returning
+    /// its hash. Breaking versions need `acknowledge_breaking`. Owner-signed
+    /// over (factory, wasm_hash, acknowledge_breaking).
+    pub fn upgrade_to_latest(
+        env: Env,
+        factory: Address,
+        acknowledge_breaking: bool,
+        signature: BytesN<64>,
+    ) -> Result<BytesN<32>, Error> {
+        if Self::get_pending_upgrade(env.clone()).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let factory_client = WalletFactoryClient::new(&env, &factory);
+        let wasm_hash = factory_client.get_wallet_wasm_hash();
+        // Code without a changelog can't be judged, so isn't taken
+        let info = factory_client
+            .get_version_info(&wasm_hash)
+            .ok_or(Error::NotFound)?;
+        if info.changelog.is_breaking() && !acknowledge_breaking {
+            return Err(Error::BreakingUpgrade);
+        }
+
+        let payload = (factory, wasm_hash.clone(), acknowledge_breaking).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "upgrade_to_latest", payload, signature)?;
+
+        schedule_upgrade(&env, wasm_hash.clone());
+        Ok(wasm_hash)
+    }
+
+    /// Switch to the proposed code once the delay has passed. The new
+    /// code only runs from the next call on, so `migrate` follows separately.
+    pub fn apply_upgrade(env: Env) -> Result<(), Error> {
+        let pending = Self::get_pending_upgrade(env.clone()).ok_or(Error::NotFound)?;
+        ensure_not_frozen(&env)?;
+        if now(&env) < pending.eta {
+            return Err(Error::Timelocked);
+        }
+
+        env.storage().instance().remove(&UpgradeKey::PendingUpgrade);
+        env.events().publish(
+            (Symbol::new(&env, events::WALLET_UPGRADED),),
+            pending.wasm_hash.clone(),
+        );
+        env.deployer()
+            .update_current_contract_wasm(pending.wasm_hash);
+
+        Ok(())
+    }
+
+    /// Cancel the pending upgrade. Signed by the owner, a registered
+    /// signer or the passkey.
+    pub fn cancel_upgrade(env: Env, proof: SignerProof) -> Result<(), Error> {
+        let pending = Self::get_pending_upgrade(env.clone()).ok_or(Error::NotFound)?;
+
+        let device = proof.signer();
+        if !is_registered(&env, &device)? {
+            return Err(Error::Unauthorized);
+        }
+
+        let message = Self::action_message(&env, "cancel_upgrade", Bytes::new(&env))?;
+        verify_proof(&env, &proof, &message)?;
+        Self::get_and_increment_nonce(env.clone())?;
+
+        env.storage().instance().remove(&UpgradeKey::PendingUpgrade);
+        env.events().publish(
+            (Symbol::new(&env, events::UPGRADE_CANCELLED),),
+            UpgradeCancelledEvent {
+                wasm_hash: pending.wasm_hash,
+                cancelled_by: device,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        env.storage().instance().get(&UpgradeKey::PendingUpgrade)
+    }
+}
+
+fn schedule_upgrade(env: &Env, wasm_hash: BytesN<32>) {
+    let pending = PendingUpgrade {
+        wasm_hash,
+        eta: now(env).saturating_add(UPGRADE_DELAY),
+    };
+    env.storage()
+        .instance()
+        .set(&UpgradeKey::PendingUpgrade, &pending);
+    env.events()
+        .publish((Symbol::new(env, events::UPGRADE_PROPOSED),), pending);
+}
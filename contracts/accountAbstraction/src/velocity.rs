@@ -0,0 +1,181 @@
+// ============================================================================
+// VELOCITY LIMITS
+//
+// A cap on how many authorizations `__check_auth` grants per time window,
+// against a script draining the wallet through a leaked session key or
+// device one small payment at a time. Each `__check_auth` that gets this far
+// counts as one operation, whatever the signature type.
+//
+// The window slides: authorizations are counted in fixed buckets of one
+// window each, kept in temporary storage, and the previous bucket counts
+// for the share of it still inside the window. That's an estimate, but it
+// never lets through more than twice the limit in any window and costs two
+// reads.
+//
+// When the owner really needs more, `grant_velocity_bypass` allows extra
+// operations until the end of the window. It always needs a second device
+// (see `auth_policy`), so the key being rate limited can't lift the limit.
+// ============================================================================
+
+use accesly_policy::velocity_usage;
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, BytesN, Env, Symbol};
+
+use crate::fragments::ledgers_for;
+use crate::*;
+
+/// Most operations a limit or bypass can allow
+pub const MAX_VELOCITY_OPS: u32 = 1_000;
+/// Shortest window a limit can use (1 minute)
+pub const MIN_VELOCITY_WINDOW: u64 = 60;
+/// Longest window a limit can use (7 days)
+pub const MAX_VELOCITY_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VelocityLimit {
+    /// Authorizations allowed per window
+    pub max_ops: u32,
+    /// Seconds
+    pub window: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VelocityBypass {
+    /// Operations still allowed over the limit
+    pub remaining: u32,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum VelocityKey {
+    Limit,
+    /// Authorizations counted in a bucket, by bucket index
+    Count(u64),
+    Bypass,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Allow at most `max_ops` authorizations per `window` seconds; 0
+    /// removes the limit. Owner-signed over (max_ops, window).
+    pub fn set_velocity_limit(
+        env: Env,
+        max_ops: u32,
+        window: u64,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if max_ops > MAX_VELOCITY_OPS || window > MAX_VELOCITY_WINDOW {
+            return Err(Error::LimitExceeded);
+        }
+        if max_ops > 0 && window < MIN_VELOCITY_WINDOW {
+            return Err(Error::InvalidAmount);
+        }
+
+        let payload = (max_ops, window).to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_velocity_limit", payload, signature)?;
+
+        let limit = VelocityLimit { max_ops, window };
+        if max_ops == 0 {
+            env.storage().instance().remove(&VelocityKey::Limit);
+        } else {
+            env.storage().instance().set(&VelocityKey::Limit, &limit);
+        }
+        env.events()
+            .publish((Symbol::new(&env, events::VELOCITY_LIMIT),), limit);
+
+        Ok(())
+    }
+
+    pub fn get_velocity_limit(env: Env) -> Option<VelocityLimit> {
+        env.storage().instance().get(&VelocityKey::Limit)
+    }
+
+    /// Authorizations counted in the current window
+    pub fn get_velocity_usage(env: Env) -> u32 {
+        match Self::get_velocity_limit(env.clone()) {
+            Some(limit) => usage(&env, &limit),
+            None => 0,
+        }
+    }
+
+    /// Allow `ops` authorizations over the limit until the window ends,
+    /// replacing any bypass left. Owner-signed over `ops`, and co-signed by
+    /// another device through `cosign_action`.
+    pub fn grant_velocity_bypass(env: Env, ops: u32, signature: BytesN<64>) -> Result<(), Error> {
+        let limit = Self::get_velocity_limit(env.clone()).ok_or(Error::NotFound)?;
+        if ops == 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if ops > MAX_VELOCITY_OPS {
+            return Err(Error::LimitExceeded);
+        }
+
+        let payload = ops.to_xdr(&env);
+        Self::require_high_risk_signature(&env, "grant_velocity_bypass", payload, signature)?;
+
+        let bypass = VelocityBypass {
+            remaining: ops,
+            expires_at: now(&env).saturating_add(limit.window),
+        };
+        let storage = env.storage().temporary();
+        storage.set(&VelocityKey::Bypass, &bypass);
+        let ledgers = ledgers_for(limit.window);
+        storage.extend_ttl(&VelocityKey::Bypass, ledgers, ledgers);
+        env.events()
+            .publish((Symbol::new(&env, events::VELOCITY_BYPASS),), bypass);
+
+        Ok(())
+    }
+
+    /// Bypass in force, if any
+    pub fn get_velocity_bypass(env: Env) -> Option<VelocityBypass> {
+        env.storage()
+            .temporary()
+            .get::<_, VelocityBypass>(&VelocityKey::Bypass)
+            .filter(|bypass| now(&env) < bypass.expires_at && bypass.remaining > 0)
+    }
+}
+
+/// Authorizations in the sliding window ending now
+fn usage(env: &Env, limit: &VelocityLimit) -> u32 {
+    let now = now(env);
+    let bucket = now / limit.window;
+    let previous = match bucket {
+        0 => 0,
+        _ => count(env, bucket - 1),
+    };
+    velocity_usage(count(env, bucket), previous, limit.window, now)
+}
+
+fn count(env: &Env, bucket: u64) -> u32 {
+    env.storage()
+        .temporary()
+        .get(&VelocityKey::Count(bucket))
+        .unwrap_or(0)
+}
+
+/// Count one authorization, failing with `LimitExceeded` once the window is
+/// full and no bypass is left
+pub(crate) fn record_authorization(env: &Env) -> Result<(), Error> {
+    let Some(limit) = WalletContract::get_velocity_limit(env.clone()) else {
+        return Ok(());
+    };
+    if usage(env, &limit) >= limit.max_ops {
+        let mut bypass =
+            WalletContract::get_velocity_bypass(env.clone()).ok_or(Error::LimitExceeded)?;
+        bypass.remaining -= 1;
+        env.storage().temporary().set(&VelocityKey::Bypass, &bypass);
+        return Ok(());
+    }
+
+    let bucket = now(env) / limit.window;
+    let key = VelocityKey::Count(bucket);
+    let storage = env.storage().temporary();
+    storage.set(&key, &(count(env, bucket) + 1));
+    // Still needed while it is the previous bucket
+    let ledgers = ledgers_for(limit.window.saturating_mul(2));
+    storage.extend_ttl(&key, ledgers, ledgers);
+    Ok(())
+}
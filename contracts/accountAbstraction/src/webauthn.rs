@@ -0,0 +1,192 @@
+// ============================================================================
+// PASSKEY SIGNATURES (SECP256R1 / WEBAUTHN)
+//
+// The owner can register a P-256 passkey next to the ed25519 owner key.
+// `__check_auth` then accepts either signature type. A passkey never signs
+// our message directly: the authenticator signs
+// sha256(authenticatorData || sha256(clientDataJSON)), and the message is
+// carried base64url-encoded as the `challenge` inside clientDataJSON. The
+// wallet checks the challenge and the user-presence flag, rebuilds the
+// digest and verifies it against the registered key.
+// ============================================================================
+
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Bytes, BytesN, Env, Symbol, Vec};
+
+use crate::signature::verify_secp256r1;
+use crate::*;
+
+/// Largest clientDataJSON accepted; browsers produce ~150-250 bytes
+pub const MAX_CLIENT_DATA_LEN: u32 = 1024;
+
+/// rpIdHash (32) + flags (1) + signCount (4)
+const MIN_AUTHENTICATOR_DATA_LEN: u32 = 37;
+const FLAG_USER_PRESENT: u8 = 0x01;
+/// Longest message signed through `__check_auth`
+/// (payload || channel || nonce || valid_until_ledger)
+const MAX_CHALLENGE_LEN: usize = 48;
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Signature passed to `__check_auth`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuthSignature {
+    Ed25519(BytesN<64>),
+    Secp256r1(WebAuthnSignature),
+    /// Signatures from weighted signers, see `add_signer`
+    Multisig(Vec<SignerProof>),
+    /// Session key and its ed25519 signature, see `create_session`
+    Session(BytesN<32>, BytesN<64>),
+    /// Linked device key and its ed25519 signature, see `add_device`
+    Device(BytesN<32>, BytesN<64>),
+    /// A signature on a nonce channel other than the wallet nonce
+    Channel(u32, ChannelSignature),
+    /// A signature that lapses at a ledger sequence, on any channel
+    Expiring(ExpiringSignature),
+    /// No signature: the payload was approved with `pre_authorize`
+    PreAuthorized,
+    /// A signature co-signed by the relayer submitting it, see `set_relayers`
+    Relayed(RelayedSignature),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WebAuthnSignature {
+    pub authenticator_data: Bytes,
+    pub client_data_json: Bytes,
+    /// Raw r || s, with s in the lower half of the curve order
+    pub signature: BytesN<64>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum PasskeyKey {
+    /// Uncompressed SEC1 P-256 public key
+    Passkey,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PasskeyChangedEvent {
+    pub public_key: Option<BytesN<65>>,
+}
+
+#[contractimpl]
+impl WalletContract {
+    /// Register, replace or (with `None`) remove the owner's passkey
+    pub fn set_passkey(
+        env: Env,
+        public_key: Option<BytesN<65>>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        if let Some(key) = &public_key {
+            // Only uncompressed points are accepted by secp256r1_verify
+            if key.get(0) != Some(0x04) {
+                return Err(Error::InvalidOwner);
+            }
+        }
+
+        let payload = public_key.clone().to_xdr(&env);
+        Self::require_high_risk_signature(&env, "set_passkey", payload, signature)?;
+
+        match &public_key {
+            Some(key) => {
+                env.storage().instance().set(&PasskeyKey::Passkey, key);
+                approvals::record_signer_added(&env, &Signer::Secp256r1(key.clone()));
+            }
+            None => env.storage().instance().remove(&PasskeyKey::Passkey),
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, events::PASSKEY_CHANGED),),
+            PasskeyChangedEvent { public_key },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_passkey(env: Env) -> Option<BytesN<65>> {
+        env.storage().instance().get(&PasskeyKey::Passkey)
+    }
+}
+
+/// The registered passkey, required to accept a passkey signature
+pub(crate) fn passkey(env: &Env) -> Result<BytesN<65>, Error> {
+    env.storage()
+        .instance()
+        .get(&PasskeyKey::Passkey)
+        .ok_or(Error::Unauthorized)
+}
+
+/// Verify a WebAuthn assertion over `message` by `public_key`
+pub(crate) fn verify_webauthn(
+    env: &Env,
+    public_key: &BytesN<65>,
+    message: &Bytes,
+    assertion: &WebAuthnSignature,
+) -> Result<(), Error> {
+    let auth_data = &assertion.authenticator_data;
+    if auth_data.len() < MIN_AUTHENTICATOR_DATA_LEN
+        || auth_data.get(32).unwrap_or(0) & FLAG_USER_PRESENT == 0
+    {
+        return Err(Error::InvalidSignature);
+    }
+
+    let client_data_len = assertion.client_data_json.len();
+    if client_data_len > MAX_CLIENT_DATA_LEN {
+        return Err(Error::InvalidSignature);
+    }
+    let mut client_data = [0u8; MAX_CLIENT_DATA_LEN as usize];
+    let client_data = &mut client_data[..client_data_len as usize];
+    assertion.client_data_json.copy_into_slice(client_data);
+
+    if find(client_data, b"\"type\":\"webauthn.get\"").is_none() {
+        return Err(Error::InvalidSignature);
+    }
+    if !challenge_matches(client_data, message) {
+        return Err(Error::InvalidSignature);
+    }
+
+    let mut signed = auth_data.clone();
+    signed.extend_from_array(&env.crypto().sha256(&assertion.client_data_json).to_array());
+    let digest = env.crypto().sha256(&signed);
+    verify_secp256r1(env, public_key, &digest, &assertion.signature)
+}
+
+/// Whether clientDataJSON's challenge is base64url(`message`), unpadded
+fn challenge_matches(client_data: &[u8], message: &Bytes) -> bool {
+    let len = message.len() as usize;
+    if len > MAX_CHALLENGE_LEN {
+        return false;
+    }
+    let mut raw = [0u8; MAX_CHALLENGE_LEN];
+    message.copy_into_slice(&mut raw[..len]);
+
+    let mut encoded = [0u8; (MAX_CHALLENGE_LEN * 4).div_ceil(3)];
+    let encoded = base64url_encode(&raw[..len], &mut encoded);
+
+    const KEY: &[u8] = b"\"challenge\":\"";
+    let Some(start) = find(client_data, KEY).map(|i| i + KEY.len()) else {
+        return false;
+    };
+    client_data[start..].starts_with(encoded)
+        && client_data.get(start + encoded.len()) == Some(&b'"')
+}
+
+fn base64url_encode<'a>(input: &[u8], out: &'a mut [u8]) -> &'a [u8] {
+    let mut len = 0;
+    for chunk in input.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out[len] = BASE64URL[(bits >> (18 - 6 * i) & 0x3f) as usize];
+            len += 1;
+        }
+    }
+    &out[..len]
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -1,6 +1,7 @@
 #![no_std]
+#![allow(non_snake_case, clippy::too_many_arguments)]
 
-use soroban_sdk::{contract, contractimpl, token, Address, Env, IntoVal};
+use soroban_sdk::{contract, contractimpl, token, Address, Env, IntoVal, String};
 
 // ---------------------------------------------------------------------------
 // Internal helper: move tokens between parties via this contract as escrow.
@@ -96,4 +97,13 @@ impl AtomicSwapContract {
         // Move token_b from B to A.
         move_token(&env, &token_b, &b, &a, amount_b, amount_a);
     }
+
+    /// Crate version and the ledger the call ran in. `accesly-cli health`
+    /// simulates it to check the deployed swap code still executes.
+    pub fn ping(env: Env) -> (String, u32) {
+        (
+            String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            env.ledger().sequence(),
+        )
+    }
 }
@@ -0,0 +1,212 @@
+#![no_std]
+#![allow(non_snake_case)]
+
+use acceslyinterface::events;
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, Address, BytesN, Env, String, Symbol,
+};
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotFound = 3,
+    SameAttestor = 4,
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// KYC status, aligned with the SEP-12 customer statuses.
+///
+/// SEP-12 `ACCEPTED` maps to `Approved` and `PROCESSING` to `Pending`;
+/// `NEEDS_INFO` and `REJECTED` map one-to-one.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum KycStatus {
+    Pending = 0,
+    Approved = 1,
+    Rejected = 2,
+    NeedsInfo = 3,
+}
+
+/// Latest attestation for a subject (usually an Accesly wallet address).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KycRecord {
+    pub status: KycStatus,
+    /// Attestor that wrote this record
+    pub attestor: Address,
+    /// Hash of the provider-side customer id (never the raw id)
+    pub reference: BytesN<32>,
+    pub updated_at: u64,
+}
+
+// ============================================================================
+// STORAGE KEYS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Attestor,
+    Record(Address),
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KycStatusEvent {
+    pub subject: Address,
+    pub status: KycStatus,
+    pub attestor: Address,
+    pub reference: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestorRotatedEvent {
+    pub old_attestor: Address,
+    pub new_attestor: Address,
+}
+
+// ============================================================================
+// CONTRACT
+// ============================================================================
+
+#[contract]
+pub struct KycAttestationContract;
+
+#[contractimpl]
+impl KycAttestationContract {
+    /// Initialize with an admin and the first attestor (e.g. Etherfuse)
+    pub fn init(env: Env, admin: Address, attestor: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Attestor, &attestor);
+
+        Ok(())
+    }
+
+    /// Get the attestor currently allowed to write statuses
+    pub fn get_attestor(env: Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Attestor)
+            .ok_or(Error::NotInitialized)
+    }
+
+    /// Swap the SEP-12 provider. Records written by the previous attestor stay
+    /// readable, so wallet-side checks keep working across the rotation.
+    pub fn rotate_attestor(env: Env, new_attestor: Address) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+
+        let old_attestor = Self::get_attestor(env.clone())?;
+        if old_attestor == new_attestor {
+            return Err(Error::SameAttestor);
+        }
+
+        env.storage().instance().set(&DataKey::Attestor, &new_attestor);
+
+        env.events().publish(
+            (Symbol::new(&env, events::ATTESTOR_ROTATED),),
+            AttestorRotatedEvent {
+                old_attestor,
+                new_attestor,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Record the SEP-12 status of `subject`. Only the current attestor can write.
+    pub fn set_status(
+        env: Env,
+        subject: Address,
+        status: KycStatus,
+        reference: BytesN<32>,
+    ) -> Result<(), Error> {
+        let attestor = Self::get_attestor(env.clone())?;
+        attestor.require_auth();
+
+        let record = KycRecord {
+            status,
+            attestor: attestor.clone(),
+            reference: reference.clone(),
+            updated_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Record(subject.clone()), &record);
+
+        env.events().publish(
+            (Symbol::new(&env, events::KYC_STATUS), subject.clone()),
+            KycStatusEvent {
+                subject,
+                status,
+                attestor,
+                reference,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the latest record for `subject`
+    pub fn get_record(env: Env, subject: Address) -> Result<KycRecord, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Record(subject))
+            .ok_or(Error::NotFound)
+    }
+
+    /// Get the status for `subject`, `Pending` if nothing was attested yet
+    pub fn get_status(env: Env, subject: Address) -> KycStatus {
+        env.storage()
+            .persistent()
+            .get::<_, KycRecord>(&DataKey::Record(subject))
+            .map(|record| record.status)
+            .unwrap_or(KycStatus::Pending)
+    }
+
+    /// Wallet-side check: true only for `Approved`
+    pub fn is_approved(env: Env, subject: Address) -> bool {
+        Self::get_status(env, subject) == KycStatus::Approved
+    }
+
+    /// Crate version and current ledger, so monitoring can tell the
+    /// attestation contract is reachable before wallets depend on it
+    pub fn ping(env: Env) -> (String, u32) {
+        (
+            String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            env.ledger().sequence(),
+        )
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod test;
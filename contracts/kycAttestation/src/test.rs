@@ -0,0 +1,109 @@
+// src/test.rs
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env};
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn setup(env: &Env) -> (KycAttestationContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(KycAttestationContract, ());
+    let client = KycAttestationContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let attestor = Address::generate(env);
+    client.init(&admin, &attestor);
+
+    (client, admin, attestor)
+}
+
+// ============================================================================
+// STATUS TESTS
+// ============================================================================
+
+#[test]
+fn test_status_defaults_to_pending() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    let wallet = Address::generate(&env);
+    assert_eq!(client.get_status(&wallet), KycStatus::Pending);
+    assert!(!client.is_approved(&wallet));
+}
+
+#[test]
+fn test_set_status_approved() {
+    let env = Env::default();
+    let (client, _, attestor) = setup(&env);
+
+    let wallet = Address::generate(&env);
+    let reference = BytesN::from_array(&env, &[7u8; 32]);
+    client.set_status(&wallet, &KycStatus::Approved, &reference);
+
+    assert!(client.is_approved(&wallet));
+    let record = client.get_record(&wallet);
+    assert_eq!(record.attestor, attestor);
+    assert_eq!(record.reference, reference);
+}
+
+#[test]
+fn test_needs_info_is_not_approved() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    let wallet = Address::generate(&env);
+    let reference = BytesN::from_array(&env, &[7u8; 32]);
+    client.set_status(&wallet, &KycStatus::NeedsInfo, &reference);
+
+    assert_eq!(client.get_status(&wallet), KycStatus::NeedsInfo);
+    assert!(!client.is_approved(&wallet));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_get_record_not_found() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    client.get_record(&Address::generate(&env));
+}
+
+// ============================================================================
+// ATTESTOR ROTATION TESTS
+// ============================================================================
+
+#[test]
+fn test_rotate_attestor_keeps_records() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    let wallet = Address::generate(&env);
+    let reference = BytesN::from_array(&env, &[7u8; 32]);
+    client.set_status(&wallet, &KycStatus::Approved, &reference);
+
+    let new_attestor = Address::generate(&env);
+    client.rotate_attestor(&new_attestor);
+
+    assert_eq!(client.get_attestor(), new_attestor);
+    assert!(client.is_approved(&wallet));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_rotate_attestor_same() {
+    let env = Env::default();
+    let (client, _, attestor) = setup(&env);
+
+    client.rotate_attestor(&attestor);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_init_already_initialized() {
+    let env = Env::default();
+    let (client, admin, attestor) = setup(&env);
+
+    client.init(&admin, &attestor);
+}
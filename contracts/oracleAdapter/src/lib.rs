@@ -0,0 +1,203 @@
+#![no_std]
+#![allow(non_snake_case)]
+
+// ============================================================================
+// ORACLE ADAPTER
+//
+// One price interface over Reflector (SEP-40) feeds, for the wallet's fiat
+// limits and auto-conversion. Reflector runs separate feeds for Stellar
+// assets, exchange rates and fiat currencies, each with its own decimals;
+// the admin maps every asset to the feed and feed symbol that prices it,
+// and `price` answers for all of them in `DECIMALS` decimals of the base
+// currency. Every mapped feed must quote in that base.
+//
+// Each mapping carries its own staleness threshold, since feeds update at
+// different resolutions. A price older than that is an error, not a
+// number, so callers fall back instead of acting on it.
+// ============================================================================
+
+use acceslyinterface::events;
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contracttype, Address, Env, Symbol,
+};
+
+/// Decimals of every price `price` returns
+pub const DECIMALS: u32 = 14;
+/// Most a mapping can let a price age, in seconds (1 day)
+pub const MAX_FEED_AGE: u64 = 24 * 60 * 60;
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NoFeed = 3,
+    NoPrice = 4,
+    StalePrice = 5,
+    InvalidFeed = 6,
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Asset as SEP-40 names it: a Stellar asset contract, or a ticker
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Where an asset's price comes from
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Feed {
+    /// Reflector contract
+    pub oracle: Address,
+    /// The asset as that feed names it
+    pub asset: Asset,
+    /// Oldest price accepted, in seconds
+    pub max_age: u64,
+}
+
+// ============================================================================
+// STORAGE KEYS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// Ticker of the currency prices are quoted in
+    Base,
+    Feed(Asset),
+}
+
+// ============================================================================
+// REFLECTOR CLIENT
+// ============================================================================
+
+/// Subset of the SEP-40 price feed interface
+#[contractclient(name = "ReflectorClient")]
+pub trait Reflector {
+    fn decimals(env: Env) -> u32;
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+// ============================================================================
+// CONTRACT
+// ============================================================================
+
+#[contract]
+pub struct OracleAdapterContract;
+
+#[contractimpl]
+impl OracleAdapterContract {
+    /// Initialize with an admin and the ticker prices are quoted in
+    pub fn init(env: Env, admin: Address, base: Symbol) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Base, &base);
+
+        Ok(())
+    }
+
+    /// Price `asset` from `feed`, or stop pricing it with `None`. Admin only.
+    pub fn set_feed(env: Env, asset: Asset, feed: Option<Feed>) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+        if let Some(feed) = &feed {
+            if feed.max_age == 0 || feed.max_age > MAX_FEED_AGE {
+                return Err(Error::InvalidFeed);
+            }
+        }
+
+        let key = DataKey::Feed(asset.clone());
+        match &feed {
+            Some(feed) => env.storage().persistent().set(&key, feed),
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events()
+            .publish((Symbol::new(&env, events::ORACLE_FEED_SET), asset), feed);
+
+        Ok(())
+    }
+
+    pub fn get_feed(env: Env, asset: Asset) -> Option<Feed> {
+        env.storage().persistent().get(&DataKey::Feed(asset))
+    }
+
+    /// Ticker prices are quoted in
+    pub fn base(env: Env) -> Result<Symbol, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Base)
+            .ok_or(Error::NotInitialized)
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        DECIMALS
+    }
+
+    /// Latest price of `asset` in the base currency, with `DECIMALS`
+    /// decimals, and when it was published. The base itself is always 1.
+    pub fn price(env: Env, asset: Asset) -> Result<(i128, u64), Error> {
+        if asset == Asset::Other(Self::base(env.clone())?) {
+            return Ok((10i128.pow(DECIMALS), env.ledger().timestamp()));
+        }
+
+        let feed = Self::get_feed(env.clone(), asset).ok_or(Error::NoFeed)?;
+        let reflector = ReflectorClient::new(&env, &feed.oracle);
+        let data = reflector
+            .lastprice(&feed.asset)
+            .filter(|data| data.price > 0)
+            .ok_or(Error::NoPrice)?;
+        if env.ledger().timestamp().saturating_sub(data.timestamp) > feed.max_age {
+            return Err(Error::StalePrice);
+        }
+
+        let decimals = reflector.decimals();
+        let price = if decimals <= DECIMALS {
+            10i128
+                .checked_pow(DECIMALS - decimals)
+                .and_then(|scale| data.price.checked_mul(scale))
+        } else {
+            10i128
+                .checked_pow(decimals - DECIMALS)
+                .map(|scale| data.price / scale)
+        };
+        let price = price.filter(|&price| price > 0).ok_or(Error::NoPrice)?;
+
+        Ok((price, data.timestamp))
+    }
+
+    fn admin(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod test;
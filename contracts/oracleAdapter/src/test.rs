@@ -0,0 +1,136 @@
+// src/test.rs
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    Address, Env, Symbol,
+};
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Reflector feed with fixed decimals and prices set by the test
+#[contract]
+struct MockReflector;
+
+#[contractimpl]
+impl MockReflector {
+    pub fn set_decimals(env: Env, decimals: u32) {
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "decimals"), &decimals);
+    }
+
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&asset, &PriceData { price, timestamp });
+    }
+
+    pub fn decimals(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "decimals"))
+            .unwrap()
+    }
+
+    pub fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().instance().get(&asset)
+    }
+}
+
+struct Setup<'a> {
+    client: OracleAdapterContractClient<'a>,
+    reflector: MockReflectorClient<'a>,
+}
+
+/// Adapter quoting in USD over a 7-decimal feed, at timestamp 10_000
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+
+    let client = OracleAdapterContractClient::new(env, &env.register(OracleAdapterContract, ()));
+    client.init(&Address::generate(env), &Symbol::new(env, "USD"));
+    let reflector = MockReflectorClient::new(env, &env.register(MockReflector, ()));
+    reflector.set_decimals(&7);
+
+    Setup { client, reflector }
+}
+
+fn feed(reflector: &MockReflectorClient, asset: &Asset, max_age: u64) -> Option<Feed> {
+    Some(Feed {
+        oracle: reflector.address.clone(),
+        asset: asset.clone(),
+        max_age,
+    })
+}
+
+// ============================================================================
+// PRICE TESTS
+// ============================================================================
+
+#[test]
+fn test_price_is_scaled_to_adapter_decimals() {
+    let env = Env::default();
+    let s = setup(&env);
+    let usdc = Asset::Stellar(Address::generate(&env));
+    // The feed names the asset by ticker
+    let ticker = Asset::Other(Symbol::new(&env, "USDC"));
+    s.reflector.set_price(&ticker, &9_990_000, &9_900);
+    s.client.set_feed(&usdc, &feed(&s.reflector, &ticker, 300));
+
+    assert_eq!(s.client.price(&usdc), (99_900_000_000_000, 9_900));
+    assert_eq!(
+        s.client.price(&Asset::Other(Symbol::new(&env, "USD"))),
+        (100_000_000_000_000, 10_000)
+    );
+
+    // Feeds with more decimals than the adapter are scaled down
+    s.reflector.set_decimals(&18);
+    s.reflector
+        .set_price(&ticker, &999_000_000_000_000_000, &9_900);
+    assert_eq!(s.client.price(&usdc), (99_900_000_000_000, 9_900));
+}
+
+#[test]
+fn test_price_errors() {
+    let env = Env::default();
+    let s = setup(&env);
+    let mxn = Asset::Other(Symbol::new(&env, "MXN"));
+    assert_eq!(s.client.try_price(&mxn), Err(Ok(Error::NoFeed)));
+
+    s.client.set_feed(&mxn, &feed(&s.reflector, &mxn, 300));
+    assert_eq!(s.client.try_price(&mxn), Err(Ok(Error::NoPrice)));
+
+    s.reflector.set_price(&mxn, &500_000, &9_000);
+    assert_eq!(s.client.try_price(&mxn), Err(Ok(Error::StalePrice)));
+    s.client.set_feed(&mxn, &feed(&s.reflector, &mxn, 1_000));
+    assert_eq!(s.client.price(&mxn), (5_000_000_000_000, 9_000));
+
+    s.client.set_feed(&mxn, &None);
+    assert_eq!(s.client.get_feed(&mxn), None);
+    assert_eq!(s.client.try_price(&mxn), Err(Ok(Error::NoFeed)));
+}
+
+#[test]
+fn test_set_feed_bounds_max_age() {
+    let env = Env::default();
+    let s = setup(&env);
+    let mxn = Asset::Other(Symbol::new(&env, "MXN"));
+
+    assert_eq!(
+        s.client.try_set_feed(&mxn, &feed(&s.reflector, &mxn, 0)),
+        Err(Ok(Error::InvalidFeed))
+    );
+    assert_eq!(
+        s.client
+            .try_set_feed(&mxn, &feed(&s.reflector, &mxn, MAX_FEED_AGE + 1)),
+        Err(Ok(Error::InvalidFeed))
+    );
+    assert_eq!(
+        s.client
+            .try_init(&Address::generate(&env), &Symbol::new(&env, "EUR")),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+}
@@ -0,0 +1,361 @@
+#![no_std]
+#![allow(non_snake_case)]
+
+use acceslyinterface::events;
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, Address, Env, String, Symbol,
+};
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotRelayer = 3,
+    NoBudget = 4,
+    BudgetExceeded = 5,
+    InvalidAmount = 6,
+    InsufficientFunds = 7,
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// What an app may spend on fees, in stroops.
+///
+/// With a `period` the limit applies per window of that many ledgers,
+/// counted from when the budget was first set; with `0` it is a lifetime cap.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Budget {
+    pub limit: i128,
+    /// Window length in ledgers, 0 for no reset
+    pub period: u32,
+    /// Ledger the current window started at
+    pub period_start: u32,
+    /// Spent in the current window
+    pub spent: i128,
+    /// Spent since the budget was first set, for billing
+    pub total_spent: i128,
+}
+
+impl Budget {
+    /// The budget as of ledger `now`, with `spent` cleared if a new window
+    /// started since the last sponsorship
+    fn current(mut self, now: u32) -> Self {
+        if self.period > 0 && now >= self.period_start.saturating_add(self.period) {
+            let elapsed = now - self.period_start;
+            self.period_start = now - elapsed % self.period;
+            self.spent = 0;
+        }
+        self
+    }
+
+    fn remaining(&self) -> i128 {
+        (self.limit - self.spent).max(0)
+    }
+}
+
+// ============================================================================
+// STORAGE KEYS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// SAC of the asset fees are paid in (native XLM)
+    Token,
+    Relayer(Address),
+    Budget(String),
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// One sponsorship, what apps are billed from
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSponsoredEvent {
+    pub app_id: String,
+    pub wallet: Address,
+    pub relayer: Address,
+    pub amount: i128,
+    /// Spent in the current window, this sponsorship included
+    pub spent: i128,
+    pub remaining: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BudgetSetEvent {
+    pub app_id: String,
+    pub limit: i128,
+    pub period: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelayerChangedEvent {
+    pub relayer: Address,
+    pub allowed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymasterFundsEvent {
+    pub account: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
+// ============================================================================
+// CONTRACT
+// ============================================================================
+
+#[contract]
+pub struct PaymasterContract;
+
+#[contractimpl]
+impl PaymasterContract {
+    /// Initialize with an admin and the SAC fees are paid in
+    pub fn init(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+
+        Ok(())
+    }
+
+    /// Move `amount` from `from` into the paymaster
+    pub fn deposit(env: Env, from: Address, amount: i128) -> Result<i128, Error> {
+        from.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let token = Self::token(&env)?;
+        token.transfer(&from, &env.current_contract_address(), &amount);
+        let balance = token.balance(&env.current_contract_address());
+
+        env.events().publish(
+            (Symbol::new(&env, events::PAYMASTER_FUNDED), from.clone()),
+            PaymasterFundsEvent {
+                account: from,
+                amount,
+                balance,
+            },
+        );
+
+        Ok(balance)
+    }
+
+    /// Pay `amount` out of the paymaster to `to`. Admin only.
+    pub fn withdraw(env: Env, to: Address, amount: i128) -> Result<i128, Error> {
+        Self::admin(&env)?.require_auth();
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let token = Self::token(&env)?;
+        if token.balance(&env.current_contract_address()) < amount {
+            return Err(Error::InsufficientFunds);
+        }
+        token.transfer(&env.current_contract_address(), &to, &amount);
+        let balance = token.balance(&env.current_contract_address());
+
+        env.events().publish(
+            (Symbol::new(&env, events::PAYMASTER_WITHDRAWN), to.clone()),
+            PaymasterFundsEvent {
+                account: to,
+                amount,
+                balance,
+            },
+        );
+
+        Ok(balance)
+    }
+
+    /// Allow or disallow `relayer` to draw on app budgets. Admin only.
+    pub fn set_relayer(env: Env, relayer: Address, allowed: bool) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+
+        let key = DataKey::Relayer(relayer.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, events::RELAYER_CHANGED), relayer.clone()),
+            RelayerChangedEvent { relayer, allowed },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_relayer(env: Env, relayer: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Relayer(relayer))
+    }
+
+    /// Set what `app_id` may spend: `limit` stroops per `period` ledgers, or
+    /// in total with a `period` of 0. Admin only. Changing an existing budget
+    /// keeps what was already spent in the current window.
+    pub fn set_budget(env: Env, app_id: String, limit: i128, period: u32) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+        if limit < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let now = env.ledger().sequence();
+        let key = DataKey::Budget(app_id.clone());
+        let budget = match env.storage().persistent().get::<_, Budget>(&key) {
+            Some(budget) if budget.period == period => Budget {
+                limit,
+                ..budget.current(now)
+            },
+            Some(budget) => Budget {
+                limit,
+                period,
+                period_start: now,
+                ..budget.current(now)
+            },
+            None => Budget {
+                limit,
+                period,
+                period_start: now,
+                spent: 0,
+                total_spent: 0,
+            },
+        };
+        env.storage().persistent().set(&key, &budget);
+
+        env.events().publish(
+            (Symbol::new(&env, events::BUDGET_SET), app_id.clone()),
+            BudgetSetEvent {
+                app_id,
+                limit,
+                period,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Budget of `app_id` as of the current ledger
+    pub fn get_budget(env: Env, app_id: String) -> Result<Budget, Error> {
+        env.storage()
+            .persistent()
+            .get::<_, Budget>(&DataKey::Budget(app_id))
+            .map(|budget| budget.current(env.ledger().sequence()))
+            .ok_or(Error::NoBudget)
+    }
+
+    /// What `app_id` can still spend in the current window
+    pub fn remaining(env: Env, app_id: String) -> Result<i128, Error> {
+        Ok(Self::get_budget(env, app_id)?.remaining())
+    }
+
+    /// Reimburse `relayer` `amount` stroops of fees it paid for `wallet` on
+    /// behalf of `app_id`, out of that app's budget. Only allowlisted
+    /// relayers can call. Returns what the app has left in this window.
+    pub fn sponsor(
+        env: Env,
+        relayer: Address,
+        app_id: String,
+        wallet: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        relayer.require_auth();
+        if !Self::is_relayer(env.clone(), relayer.clone()) {
+            return Err(Error::NotRelayer);
+        }
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut budget = Self::get_budget(env.clone(), app_id.clone())?;
+        if amount > budget.remaining() {
+            return Err(Error::BudgetExceeded);
+        }
+
+        let token = Self::token(&env)?;
+        if token.balance(&env.current_contract_address()) < amount {
+            return Err(Error::InsufficientFunds);
+        }
+
+        budget.spent += amount;
+        budget.total_spent += amount;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Budget(app_id.clone()), &budget);
+        token.transfer(&env.current_contract_address(), &relayer, &amount);
+
+        let remaining = budget.remaining();
+        env.events().publish(
+            (
+                Symbol::new(&env, events::FEE_SPONSORED),
+                app_id.clone(),
+                wallet.clone(),
+            ),
+            FeeSponsoredEvent {
+                app_id,
+                wallet,
+                relayer,
+                amount,
+                spent: budget.spent,
+                remaining,
+            },
+        );
+
+        Ok(remaining)
+    }
+
+    /// Funds held for sponsorship, in stroops
+    pub fn balance(env: Env) -> Result<i128, Error> {
+        Ok(Self::token(&env)?.balance(&env.current_contract_address()))
+    }
+
+    /// Crate version and current ledger. Touches no budgets, so monitoring
+    /// can simulate it as often as it likes.
+    pub fn ping(env: Env) -> (String, u32) {
+        (
+            String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            env.ledger().sequence(),
+        )
+    }
+
+    fn admin(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)
+    }
+
+    fn token(env: &Env) -> Result<token::Client<'_>, Error> {
+        let token: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Token)
+            .ok_or(Error::NotInitialized)?;
+        Ok(token::Client::new(env, &token))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,192 @@
+// src/test.rs
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger as _},
+    token, Address, Env, String,
+};
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+struct Setup<'a> {
+    client: PaymasterContractClient<'a>,
+    xlm: token::Client<'a>,
+    relayer: Address,
+}
+
+/// Paymaster holding 1_000_000 stroops, one allowlisted relayer and a
+/// "demo" app budget of 10_000 per 100 ledgers
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.sequence_number = 1_000);
+
+    let xlm_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let contract_id = env.register(PaymasterContract, ());
+    let client = PaymasterContractClient::new(env, &contract_id);
+    client.init(&Address::generate(env), &xlm_id);
+
+    let funder = Address::generate(env);
+    token::StellarAssetClient::new(env, &xlm_id).mint(&funder, &1_000_000);
+    client.deposit(&funder, &1_000_000);
+
+    let relayer = Address::generate(env);
+    client.set_relayer(&relayer, &true);
+    client.set_budget(&app(env), &10_000, &100);
+
+    Setup {
+        client,
+        xlm: token::Client::new(env, &xlm_id),
+        relayer,
+    }
+}
+
+fn app(env: &Env) -> String {
+    String::from_str(env, "demo")
+}
+
+// ============================================================================
+// SPONSOR TESTS
+// ============================================================================
+
+#[test]
+fn test_sponsor_pays_relayer_from_budget() {
+    let env = Env::default();
+    let s = setup(&env);
+    let wallet = Address::generate(&env);
+
+    let remaining = s.client.sponsor(&s.relayer, &app(&env), &wallet, &4_000);
+
+    assert_eq!(remaining, 6_000);
+    assert_eq!(s.xlm.balance(&s.relayer), 4_000);
+    assert_eq!(s.client.balance(), 996_000);
+    let budget = s.client.get_budget(&app(&env));
+    assert_eq!(budget.spent, 4_000);
+    assert_eq!(budget.total_spent, 4_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_sponsor_rejects_unlisted_relayer() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    s.client.sponsor(
+        &Address::generate(&env),
+        &app(&env),
+        &Address::generate(&env),
+        &100,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_sponsor_rejects_removed_relayer() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    s.client.set_relayer(&s.relayer, &false);
+    assert!(!s.client.is_relayer(&s.relayer));
+    s.client
+        .sponsor(&s.relayer, &app(&env), &Address::generate(&env), &100);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_sponsor_unknown_app() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    s.client.sponsor(
+        &s.relayer,
+        &String::from_str(&env, "other"),
+        &Address::generate(&env),
+        &100,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_sponsor_over_budget() {
+    let env = Env::default();
+    let s = setup(&env);
+    let wallet = Address::generate(&env);
+
+    s.client.sponsor(&s.relayer, &app(&env), &wallet, &8_000);
+    s.client.sponsor(&s.relayer, &app(&env), &wallet, &2_001);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_sponsor_insufficient_funds() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    s.client.set_budget(&app(&env), &2_000_000, &0);
+    s.client
+        .sponsor(&s.relayer, &app(&env), &Address::generate(&env), &1_500_000);
+}
+
+// ============================================================================
+// BUDGET TESTS
+// ============================================================================
+
+#[test]
+fn test_budget_resets_each_period() {
+    let env = Env::default();
+    let s = setup(&env);
+    let wallet = Address::generate(&env);
+
+    s.client.sponsor(&s.relayer, &app(&env), &wallet, &10_000);
+    assert_eq!(s.client.remaining(&app(&env)), 0);
+
+    // Two and a half windows later: the window started at ledger 1_200
+    env.ledger().with_mut(|li| li.sequence_number = 1_250);
+    let budget = s.client.get_budget(&app(&env));
+    assert_eq!(budget.period_start, 1_200);
+    assert_eq!(budget.spent, 0);
+
+    s.client.sponsor(&s.relayer, &app(&env), &wallet, &3_000);
+    let budget = s.client.get_budget(&app(&env));
+    assert_eq!(budget.spent, 3_000);
+    assert_eq!(budget.total_spent, 13_000);
+}
+
+#[test]
+fn test_set_budget_keeps_spent() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    s.client
+        .sponsor(&s.relayer, &app(&env), &Address::generate(&env), &4_000);
+    s.client.set_budget(&app(&env), &5_000, &100);
+
+    assert_eq!(s.client.remaining(&app(&env)), 1_000);
+}
+
+// ============================================================================
+// FUNDS TESTS
+// ============================================================================
+
+#[test]
+fn test_withdraw() {
+    let env = Env::default();
+    let s = setup(&env);
+    let to = Address::generate(&env);
+
+    assert_eq!(s.client.withdraw(&to, &250_000), 750_000);
+    assert_eq!(s.xlm.balance(&to), 250_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_init_twice() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    s.client
+        .init(&Address::generate(&env), &Address::generate(&env));
+}
@@ -0,0 +1,966 @@
+#![no_std]
+#![allow(non_snake_case)]
+
+// ============================================================================
+// WALLET FACTORY
+//
+// Deploys and initializes a WalletContract in one invocation. The contract
+// address is derived from the user's lookup key (see below), so the backend
+// knows it before deploying, and because `init` runs in the same
+// transaction there is no window in which someone else can initialize a
+// freshly deployed wallet. Only the admin (the backend) can deploy: with
+// predictable addresses, anyone else could claim a user's address with
+// their own key. When a registry is configured, each deployment is also
+// recorded there.
+//
+// A wallet can also keep a watchlist here: classic accounts and contracts
+// the user holds funds in outside the smart account, watch-only, so the SDK
+// builds the consolidated portfolio from one on-chain list instead of
+// per-device settings. Only the wallet itself can change its list.
+//
+// `deploy_with_preset` does the same and starts the wallet with one of the
+// named policy bundles in `acceslyinterface::presets`.
+//
+// Each wasm hash new wallets can be deployed with is registered together
+// with a changelog (see `acceslyinterface::versions`). Deployed wallets
+// read it through `get_version_info` when they `upgrade_to_latest`.
+//
+// Integrators the admin registers as apps deploy through `deploy_for_app`
+// themselves, up to the quota the admin gave them. An app picks the wallet
+// address with a salt in its own namespace, so only that app can claim it,
+// and pins the registered wasm hash it tested against. Each reason a
+// deployment can fail has its own error code. Nothing vouches for the email
+// hash an app passes, so app wallets stay out of the lookup index and the
+// registry: an app can't take an address's slot before the backend deploys
+// the wallet the user actually verified.
+//
+// Nothing here is keyed by the raw email hash, which anyone can compute
+// from an address. The backend derives a lookup key, sha256(pepper ||
+// email_hash) with a pepper only it knows, and deploys with it: the wallet
+// address comes from that key and the wallet is indexed under it. Wallets
+// deployed some other way are added with `index_wallet`, and the backend
+// asks `lookup_peppered` in a simulation, so the pepper never lands in a
+// ledger. Only the pepper's hash is stored, to turn away queries with the
+// wrong one. The registry is the exception: recovery and the SDK look
+// wallets up there by email hash without the backend, so deployments are
+// only recorded in it once the admin sets one. Wallets deployed while one
+// is set are bound to it and move their own entry when their email
+// changes, including through `set_linked_email`; the backend re-indexes
+// them here under the new key.
+//
+// One person can hold several wallets, say a personal and a business one.
+// Each wallet can link itself to an identity commitment (an opaque hash the
+// backend derives for the person) with a role, and `get_linked_wallets`
+// lists them. The wallet's own signature doesn't show it belongs to that
+// person, so the backend attests the first link of an identity, and each
+// later one needs the wallet linked longest as well. `add_linked_guardian`
+// and `set_linked_email` then apply a guardian or a recovery email to every
+// linked wallet in one transaction, with one owner signature per wallet, so
+// they can't drift apart. Each wallet still enforces its own rules, guardian
+// delays included.
+//
+// Each registered version can carry a capability manifest (see
+// `acceslyinterface::manifest`), signed by the build key the admin sets.
+// Anyone can publish it, since the signature is what vouches for it, and
+// `get_manifest` returns it with the signature for clients to check.
+// ============================================================================
+
+use acceslyinterface::events;
+pub use acceslyinterface::{Changelog, Manifest, PolicyPreset, SignedManifest, VersionInfo};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, vec, xdr::ToXdr, Address, Bytes, BytesN,
+    Env, IntoVal, String, Symbol, Val, Vec,
+};
+
+/// Domain separator for the deployment salt
+const SALT_PREFIX: &[u8] = b"accesly:wallet:";
+/// Domain separator for salts in an app's namespace
+const APP_SALT_PREFIX: &[u8] = b"accesly:app-wallet:";
+
+/// Ed25519 group order L, little-endian
+const ED25519_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+/// Encodings of the identity and the order-4 point with y = 0, the only
+/// small-order points a zeroed key or signature decodes to
+const ED25519_IDENTITY: [u8; 32] = {
+    let mut point = [0u8; 32];
+    point[0] = 1;
+    point
+};
+const ED25519_ZERO_Y: [u8; 32] = [0u8; 32];
+
+pub const MAX_WATCHLIST: u32 = 20;
+pub const MAX_WATCH_LABEL_LEN: u32 = 32;
+pub const MAX_LINKED_WALLETS: u32 = 10;
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidOwner = 3,
+    InvalidEmailHash = 4,
+    AlreadyDeployed = 5,
+    AlreadyWatched = 6,
+    NotWatched = 7,
+    WatchlistFull = 8,
+    InvalidLabel = 9,
+    InvalidAccount = 10,
+    InvalidChangelog = 11,
+    VersionExists = 12,
+    UnknownPreset = 13,
+    /// The caller isn't a registered app
+    AppNotRegistered = 14,
+    /// The app already deployed a wallet with this salt
+    SaltAlreadyUsed = 15,
+    /// The wasm hash isn't a registered wallet version
+    WasmHashUnknown = 16,
+    /// The app has used up its deployment quota
+    DeployQuotaExceeded = 17,
+    /// The pepper doesn't match the stored pepper hash, or none is set
+    InvalidPepper = 18,
+    /// A wallet is already indexed under this key
+    AlreadyIndexed = 19,
+    /// The identity commitment is all zeros
+    InvalidIdentity = 20,
+    /// The wallet is already linked to an identity
+    AlreadyLinked = 21,
+    /// The wallet isn't linked to an identity
+    NotLinked = 22,
+    /// The identity has MAX_LINKED_WALLETS wallets
+    TooManyLinkedWallets = 23,
+    /// There isn't exactly one signature per linked wallet
+    SignatureCountMismatch = 24,
+    /// No build key is set to check manifests against
+    ManifestSignerNotSet = 25,
+    /// A manifest list is over MAX_MANIFEST_ITEMS
+    InvalidManifest = 26,
+    /// The manifest signature is malformed
+    InvalidSignature = 27,
+}
+
+// ============================================================================
+// STORAGE KEYS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// WalletContract code new wallets are deployed with
+    WalletWasmHash,
+    /// WalletRegistry new wallets are recorded in
+    Registry,
+    /// Vec<WatchEntry> of a wallet
+    Watchlist(Address),
+    /// VersionInfo of a wallet wasm hash
+    Version(BytesN<32>),
+    /// AppInfo of a registered app
+    App(Address),
+    /// Wallet deployed at an app salt
+    AppSalt(BytesN<32>),
+    /// sha256 of the backend's lookup pepper
+    PepperHash,
+    /// Wallet indexed under sha256(pepper || email_hash)
+    Peppered(BytesN<32>),
+    /// Key a wallet is indexed under
+    PepperedKey(Address),
+    /// Vec<LinkedWallet> of an identity commitment
+    Linked(BytesN<32>),
+    /// Identity commitment a wallet is linked to
+    Identity(Address),
+    /// ed25519 key manifests are signed with
+    ManifestSigner,
+    /// SignedManifest of a wallet wasm hash
+    Manifest(BytesN<32>),
+}
+
+/// An account a wallet watches without controlling it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WatchEntry {
+    /// Classic account or contract
+    pub account: Address,
+    pub label: String,
+    /// Ledger sequence it was added at
+    pub added_at: u32,
+}
+
+/// An integrator allowed to deploy wallets itself
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppInfo {
+    /// Most wallets the app can deploy
+    pub quota: u32,
+    pub deployed: u32,
+}
+
+/// What a linked wallet is for
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WalletRole {
+    Personal,
+    Business,
+}
+
+/// A wallet linked to an identity
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinkedWallet {
+    pub wallet: Address,
+    pub role: WalletRole,
+    /// Ledger sequence it was linked at
+    pub linked_at: u32,
+}
+
+/// A guardian key, encoded as the wallet's `Signer` of the same kind.
+/// Wallets only take guardians by key.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GuardianKey {
+    Ed25519(BytesN<32>),
+    /// Uncompressed SEC1 P-256 public key
+    Secp256r1(BytesN<65>),
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletDeployedEvent {
+    pub wallet: Address,
+    pub owner: BytesN<32>,
+    pub wasm_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletWasmUpdatedEvent {
+    pub old_wasm_hash: BytesN<32>,
+    pub new_wasm_hash: BytesN<32>,
+}
+
+// ============================================================================
+// CONTRACT
+// ============================================================================
+
+#[contract]
+pub struct WalletFactoryContract;
+
+#[contractimpl]
+impl WalletFactoryContract {
+    /// Initialize with the admin and the wallet code to deploy
+    pub fn init(env: Env, admin: Address, wallet_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::WalletWasmHash, &wallet_wasm_hash);
+        // The first version has nothing to be compared with
+        let changelog = Changelog {
+            breaking: 0,
+            migrated_keys: Vec::new(&env),
+            new_entrypoints: Vec::new(&env),
+        };
+        record_version(&env, &wallet_wasm_hash, changelog);
+
+        Ok(())
+    }
+
+    pub fn get_wallet_wasm_hash(env: Env) -> Result<BytesN<32>, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::WalletWasmHash)
+            .ok_or(Error::NotInitialized)
+    }
+
+    /// Deploy future wallets with new code, registered with what it changes.
+    /// Wallets already deployed keep theirs until they upgrade.
+    pub fn set_wallet_wasm_hash(
+        env: Env,
+        wasm_hash: BytesN<32>,
+        changelog: Changelog,
+    ) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+
+        if !changelog.is_valid() {
+            return Err(Error::InvalidChangelog);
+        }
+        // A changelog describes one step; re-pointing at an older version
+        // would make it describe the wrong one
+        if Self::get_version_info(env.clone(), wasm_hash.clone()).is_some() {
+            return Err(Error::VersionExists);
+        }
+
+        let old_wasm_hash = Self::get_wallet_wasm_hash(env.clone())?;
+        record_version(&env, &wasm_hash, changelog);
+        env.storage()
+            .instance()
+            .set(&DataKey::WalletWasmHash, &wasm_hash);
+
+        env.events().publish(
+            (Symbol::new(&env, events::WALLET_WASM_UPDATED),),
+            WalletWasmUpdatedEvent {
+                old_wasm_hash,
+                new_wasm_hash: wasm_hash,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Changelog `wasm_hash` was registered with, if it ever was
+    pub fn get_version_info(env: Env, wasm_hash: BytesN<32>) -> Option<VersionInfo> {
+        env.storage().persistent().get(&DataKey::Version(wasm_hash))
+    }
+
+    pub fn get_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Registry)
+    }
+
+    /// Record future deployments in `registry`
+    pub fn set_registry(env: Env, registry: Address) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::Registry, &registry);
+        Ok(())
+    }
+
+    /// Check lookups against the pepper hashing to `pepper_hash`. Wallets
+    /// indexed under an older pepper need indexing again. Admin only.
+    pub fn set_pepper_hash(env: Env, pepper_hash: BytesN<32>) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::PepperHash, &pepper_hash);
+        Ok(())
+    }
+
+    /// Record `wallet` under `key`, sha256(pepper || email_hash) as the
+    /// backend computes it, dropping any key it was indexed under before,
+    /// as after an email change. Admin only.
+    pub fn index_wallet(env: Env, key: BytesN<32>, wallet: Address) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Peppered(key.clone()))
+        {
+            return Err(Error::AlreadyIndexed);
+        }
+        record_index(&env, key, &wallet);
+
+        Ok(())
+    }
+
+    /// The wallet indexed for `email_hash` under `pepper`, if any. Meant to
+    /// be simulated, not submitted.
+    pub fn lookup_peppered(
+        env: Env,
+        pepper: Bytes,
+        email_hash: BytesN<32>,
+    ) -> Result<Option<Address>, Error> {
+        let pepper_hash: BytesN<32> = env
+            .storage()
+            .instance()
+            .get(&DataKey::PepperHash)
+            .ok_or(Error::InvalidPepper)?;
+        if BytesN::from(env.crypto().sha256(&pepper)) != pepper_hash {
+            return Err(Error::InvalidPepper);
+        }
+
+        let mut preimage = pepper;
+        preimage.extend_from_array(&email_hash.to_array());
+        let key: BytesN<32> = env.crypto().sha256(&preimage).into();
+        Ok(env.storage().persistent().get(&DataKey::Peppered(key)))
+    }
+
+    /// Address the wallet deployed under the lookup key `key` has, or will
+    /// have once deployed
+    pub fn get_wallet_address(env: Env, key: BytesN<32>) -> Address {
+        env.deployer()
+            .with_current_contract(wallet_salt(&env, &key))
+            .deployed_address()
+    }
+
+    /// Deploy the wallet for `email_hash`, initialize it with `owner` and
+    /// index it under `key`, sha256(pepper || email_hash) as the backend
+    /// computes it. Admin only.
+    pub fn deploy_wallet(
+        env: Env,
+        owner: BytesN<32>,
+        email_hash: BytesN<32>,
+        key: BytesN<32>,
+    ) -> Result<Address, Error> {
+        Self::deploy(&env, owner, email_hash, key, None)
+    }
+
+    /// `deploy_wallet`, starting the wallet with the policy preset
+    /// `preset_id` (`acceslyinterface::presets::PRESET_*`)
+    pub fn deploy_with_preset(
+        env: Env,
+        owner: BytesN<32>,
+        email_hash: BytesN<32>,
+        key: BytesN<32>,
+        preset_id: u32,
+    ) -> Result<Address, Error> {
+        let preset = acceslyinterface::preset(preset_id).ok_or(Error::UnknownPreset)?;
+        Self::deploy(&env, owner, email_hash, key, Some(preset))
+    }
+
+    /// Let `app` deploy up to `quota` wallets in total, counting those it
+    /// already deployed. Admin only.
+    pub fn register_app(env: Env, app: Address, quota: u32) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+
+        let info = AppInfo {
+            quota,
+            deployed: Self::get_app(env.clone(), app.clone()).map_or(0, |info| info.deployed),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::App(app.clone()), &info);
+        env.events()
+            .publish((Symbol::new(&env, events::APP_REGISTERED), app), info);
+
+        Ok(())
+    }
+
+    pub fn get_app(env: Env, app: Address) -> Option<AppInfo> {
+        env.storage().persistent().get(&DataKey::App(app))
+    }
+
+    /// Address the wallet `app` deploys with `salt` has, or will have
+    pub fn get_app_wallet_address(env: Env, app: Address, salt: BytesN<32>) -> Address {
+        env.deployer()
+            .with_current_contract(app_salt(&env, &app, &salt))
+            .deployed_address()
+    }
+
+    /// Deploy the wallet for `email_hash` at `salt` in `app`'s namespace,
+    /// with the registered code `wasm_hash`, and initialize it with
+    /// `owner`. Authorized by the app; counts against its quota. The wallet
+    /// isn't indexed by `email_hash`, here or in the registry.
+    pub fn deploy_for_app(
+        env: Env,
+        app: Address,
+        salt: BytesN<32>,
+        owner: BytesN<32>,
+        email_hash: BytesN<32>,
+        wasm_hash: BytesN<32>,
+    ) -> Result<Address, Error> {
+        app.require_auth();
+
+        let mut info = Self::get_app(env.clone(), app.clone()).ok_or(Error::AppNotRegistered)?;
+        if info.deployed >= info.quota {
+            return Err(Error::DeployQuotaExceeded);
+        }
+        check_keys(&owner, &email_hash)?;
+        let salt = app_salt(&env, &app, &salt);
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::AppSalt(salt.clone()))
+        {
+            return Err(Error::SaltAlreadyUsed);
+        }
+        if Self::get_version_info(env.clone(), wasm_hash.clone()).is_none() {
+            return Err(Error::WasmHashUnknown);
+        }
+
+        let wallet = Self::deploy_at(&env, salt.clone(), wasm_hash, owner, email_hash, None);
+        info.deployed += 1;
+        env.storage().persistent().set(&DataKey::App(app), &info);
+        env.storage()
+            .persistent()
+            .set(&DataKey::AppSalt(salt), &wallet);
+
+        Ok(wallet)
+    }
+
+    /// Check manifests against the ed25519 build key `signer`. Manifests
+    /// already published stay. Admin only.
+    pub fn set_manifest_signer(env: Env, signer: BytesN<32>) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::ManifestSigner, &signer);
+        Ok(())
+    }
+
+    pub fn get_manifest_signer(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&DataKey::ManifestSigner)
+    }
+
+    /// Store `manifest` for the registered `wasm_hash`, replacing any
+    /// before it. Signed by the build key over `manifest_message`.
+    pub fn publish_manifest(
+        env: Env,
+        wasm_hash: BytesN<32>,
+        manifest: Manifest,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        let signer = Self::get_manifest_signer(env.clone()).ok_or(Error::ManifestSignerNotSet)?;
+        if Self::get_version_info(env.clone(), wasm_hash.clone()).is_none() {
+            return Err(Error::WasmHashUnknown);
+        }
+        if !manifest.is_valid() {
+            return Err(Error::InvalidManifest);
+        }
+
+        let mut message = Bytes::new(&env);
+        accesly_messages::manifest_message(
+            &mut message,
+            &wasm_hash.to_array(),
+            &manifest.clone().to_xdr(&env),
+        );
+        verify_ed25519(&env, &signer, &message, &signature)?;
+
+        let signed = SignedManifest {
+            manifest,
+            signer,
+            signature,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Manifest(wasm_hash.clone()), &signed);
+        env.events().publish(
+            (Symbol::new(&env, events::MANIFEST_PUBLISHED), wasm_hash),
+            signed.signer,
+        );
+
+        Ok(())
+    }
+
+    /// Capability manifest of `wasm_hash`, with its signature
+    pub fn get_manifest(env: Env, wasm_hash: BytesN<32>) -> Option<SignedManifest> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Manifest(wasm_hash))
+    }
+
+    /// Add `account` to `wallet`'s watchlist. Authorized by the wallet.
+    pub fn add_watch(
+        env: Env,
+        wallet: Address,
+        account: Address,
+        label: String,
+    ) -> Result<(), Error> {
+        wallet.require_auth();
+
+        if account == wallet {
+            return Err(Error::InvalidAccount);
+        }
+        if label.len() > MAX_WATCH_LABEL_LEN {
+            return Err(Error::InvalidLabel);
+        }
+        let mut watchlist = Self::get_watchlist(env.clone(), wallet.clone());
+        if watchlist.iter().any(|entry| entry.account == account) {
+            return Err(Error::AlreadyWatched);
+        }
+        if watchlist.len() >= MAX_WATCHLIST {
+            return Err(Error::WatchlistFull);
+        }
+
+        let entry = WatchEntry {
+            account,
+            label,
+            added_at: env.ledger().sequence(),
+        };
+        watchlist.push_back(entry.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Watchlist(wallet.clone()), &watchlist);
+        env.events()
+            .publish((Symbol::new(&env, events::WATCH_ADDED), wallet), entry);
+
+        Ok(())
+    }
+
+    /// Drop `account` from `wallet`'s watchlist. Authorized by the wallet.
+    pub fn remove_watch(env: Env, wallet: Address, account: Address) -> Result<(), Error> {
+        wallet.require_auth();
+
+        let mut watchlist = Self::get_watchlist(env.clone(), wallet.clone());
+        let index = watchlist
+            .iter()
+            .position(|entry| entry.account == account)
+            .ok_or(Error::NotWatched)?;
+        watchlist.remove(index as u32);
+
+        let key = DataKey::Watchlist(wallet.clone());
+        if watchlist.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &watchlist);
+        }
+        env.events()
+            .publish((Symbol::new(&env, events::WATCH_REMOVED), wallet), account);
+
+        Ok(())
+    }
+
+    /// Accounts `wallet` watches, oldest first
+    pub fn get_watchlist(env: Env, wallet: Address) -> Vec<WatchEntry> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Watchlist(wallet))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Link `wallet` to `identity_commitment` as `role`. Authorized by the
+    /// wallet, and by the admin for an identity's first wallet or by the
+    /// wallet linked longest for any after it.
+    pub fn link_wallet(
+        env: Env,
+        identity_commitment: BytesN<32>,
+        wallet: Address,
+        role: WalletRole,
+    ) -> Result<(), Error> {
+        wallet.require_auth();
+
+        if is_zero(&identity_commitment) {
+            return Err(Error::InvalidIdentity);
+        }
+        if Self::get_identity(env.clone(), wallet.clone()).is_some() {
+            return Err(Error::AlreadyLinked);
+        }
+        let mut linked = Self::get_linked_wallets(env.clone(), identity_commitment.clone());
+        if linked.len() >= MAX_LINKED_WALLETS {
+            return Err(Error::TooManyLinkedWallets);
+        }
+        match linked.first() {
+            Some(oldest) => oldest.wallet.require_auth(),
+            None => Self::admin(&env)?.require_auth(),
+        }
+
+        let entry = LinkedWallet {
+            wallet: wallet.clone(),
+            role,
+            linked_at: env.ledger().sequence(),
+        };
+        linked.push_back(entry.clone());
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::Linked(identity_commitment.clone()), &linked);
+        storage.set(&DataKey::Identity(wallet), &identity_commitment);
+        env.events().publish(
+            (
+                Symbol::new(&env, events::WALLET_LINKED),
+                identity_commitment,
+            ),
+            entry,
+        );
+
+        Ok(())
+    }
+
+    /// Unlink `wallet` from its identity. Authorized by the wallet.
+    pub fn unlink_wallet(env: Env, wallet: Address) -> Result<(), Error> {
+        wallet.require_auth();
+
+        let identity_commitment =
+            Self::get_identity(env.clone(), wallet.clone()).ok_or(Error::NotLinked)?;
+        let mut linked = Self::get_linked_wallets(env.clone(), identity_commitment.clone());
+        if let Some(index) = linked.iter().position(|entry| entry.wallet == wallet) {
+            linked.remove(index as u32);
+        }
+
+        let storage = env.storage().persistent();
+        let key = DataKey::Linked(identity_commitment.clone());
+        if linked.is_empty() {
+            storage.remove(&key);
+        } else {
+            storage.set(&key, &linked);
+        }
+        storage.remove(&DataKey::Identity(wallet.clone()));
+        env.events().publish(
+            (
+                Symbol::new(&env, events::WALLET_UNLINKED),
+                identity_commitment,
+            ),
+            wallet,
+        );
+
+        Ok(())
+    }
+
+    /// Wallets linked to `identity_commitment`, oldest first
+    pub fn get_linked_wallets(env: Env, identity_commitment: BytesN<32>) -> Vec<LinkedWallet> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Linked(identity_commitment))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Identity commitment `wallet` is linked to, if any
+    pub fn get_identity(env: Env, wallet: Address) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::Identity(wallet))
+    }
+
+    /// Add `guardian` to every wallet linked to `identity_commitment`, with
+    /// each wallet's owner signature for `add_guardian`, in the order of
+    /// `get_linked_wallets`
+    pub fn add_linked_guardian(
+        env: Env,
+        identity_commitment: BytesN<32>,
+        guardian: GuardianKey,
+        signatures: Vec<BytesN<64>>,
+    ) -> Result<(), Error> {
+        Self::invoke_linked(
+            &env,
+            identity_commitment,
+            "add_guardian",
+            guardian.into_val(&env),
+            signatures,
+        )
+    }
+
+    /// Move every wallet linked to `identity_commitment` to the recovery
+    /// email hashing to `email_hash`, with each wallet's owner signature for
+    /// `update_email_hash`, in the order of `get_linked_wallets`. Wallets
+    /// bound to the registry move their entries there as they go.
+    pub fn set_linked_email(
+        env: Env,
+        identity_commitment: BytesN<32>,
+        email_hash: BytesN<32>,
+        signatures: Vec<BytesN<64>>,
+    ) -> Result<(), Error> {
+        Self::invoke_linked(
+            &env,
+            identity_commitment,
+            "update_email_hash",
+            email_hash.into_val(&env),
+            signatures,
+        )
+    }
+
+    /// Crate version of the factory and current ledger. This is the factory's
+    /// own version, not that of the wallets it deploys.
+    pub fn ping(env: Env) -> (String, u32) {
+        (
+            String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            env.ledger().sequence(),
+        )
+    }
+
+    fn deploy(
+        env: &Env,
+        owner: BytesN<32>,
+        email_hash: BytesN<32>,
+        key: BytesN<32>,
+        preset: Option<PolicyPreset>,
+    ) -> Result<Address, Error> {
+        Self::admin(env)?.require_auth();
+        check_keys(&owner, &email_hash)?;
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Peppered(key.clone()))
+        {
+            return Err(Error::AlreadyDeployed);
+        }
+
+        let wasm_hash = Self::get_wallet_wasm_hash(env.clone())?;
+        let salt = wallet_salt(env, &key);
+        let wallet = Self::deploy_at(
+            env,
+            salt.clone(),
+            wasm_hash,
+            owner.clone(),
+            email_hash.clone(),
+            preset,
+        );
+
+        record_index(env, key, &wallet);
+        if let Some(registry) = Self::get_registry(env.clone()) {
+            env.invoke_contract::<()>(
+                &registry,
+                &Symbol::new(env, "register"),
+                vec![
+                    env,
+                    email_hash.into_val(env),
+                    owner.into_val(env),
+                    wallet.into_val(env),
+                ],
+            );
+            // So the wallet moves its entry when its email changes
+            env.invoke_contract::<()>(
+                &wallet,
+                &Symbol::new(env, "bind_registry"),
+                vec![
+                    env,
+                    env.current_contract_address().into_val(env),
+                    salt.into_val(env),
+                    registry.into_val(env),
+                ],
+            );
+        }
+
+        Ok(wallet)
+    }
+
+    /// Deploy `wasm_hash` at `salt` and initialize it
+    fn deploy_at(
+        env: &Env,
+        salt: BytesN<32>,
+        wasm_hash: BytesN<32>,
+        owner: BytesN<32>,
+        email_hash: BytesN<32>,
+        preset: Option<PolicyPreset>,
+    ) -> Address {
+        let wallet = env
+            .deployer()
+            .with_current_contract(salt)
+            .deploy_v2(wasm_hash.clone(), ());
+        let mut args = vec![env, owner.into_val(env), email_hash.into_val(env)];
+        let init = match preset {
+            Some(preset) => {
+                args.push_back(preset.into_val(env));
+                "init_with_preset"
+            }
+            None => "init",
+        };
+        env.invoke_contract::<()>(&wallet, &Symbol::new(env, init), args);
+
+        env.events().publish(
+            (Symbol::new(env, events::WALLET_DEPLOYED), wallet.clone()),
+            WalletDeployedEvent {
+                wallet: wallet.clone(),
+                owner,
+                wasm_hash,
+            },
+        );
+
+        wallet
+    }
+
+    /// Call `function(arg, signature)` on each wallet linked to
+    /// `identity_commitment`, with its own signature
+    fn invoke_linked(
+        env: &Env,
+        identity_commitment: BytesN<32>,
+        function: &str,
+        arg: Val,
+        signatures: Vec<BytesN<64>>,
+    ) -> Result<(), Error> {
+        let linked = Self::get_linked_wallets(env.clone(), identity_commitment);
+        if linked.is_empty() {
+            return Err(Error::NotLinked);
+        }
+        if signatures.len() != linked.len() {
+            return Err(Error::SignatureCountMismatch);
+        }
+
+        let function = Symbol::new(env, function);
+        for (entry, signature) in linked.iter().zip(signatures.iter()) {
+            env.invoke_contract::<()>(
+                &entry.wallet,
+                &function,
+                vec![env, arg, signature.into_val(env)],
+            );
+        }
+
+        Ok(())
+    }
+
+    fn admin(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)
+    }
+}
+
+fn record_version(env: &Env, wasm_hash: &BytesN<32>, changelog: Changelog) {
+    let info = VersionInfo {
+        changelog,
+        registered_at: env.ledger().sequence(),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Version(wasm_hash.clone()), &info);
+}
+
+/// Fail unless `owner` and `email_hash` are set
+fn check_keys(owner: &BytesN<32>, email_hash: &BytesN<32>) -> Result<(), Error> {
+    if is_zero(owner) {
+        return Err(Error::InvalidOwner);
+    }
+    if is_zero(email_hash) {
+        return Err(Error::InvalidEmailHash);
+    }
+    Ok(())
+}
+
+/// Index `wallet` under `key` in place of its previous key
+fn record_index(env: &Env, key: BytesN<32>, wallet: &Address) {
+    let storage = env.storage().persistent();
+    let previous: Option<BytesN<32>> = storage.get(&DataKey::PepperedKey(wallet.clone()));
+    if let Some(previous) = previous {
+        storage.remove(&DataKey::Peppered(previous));
+    }
+    storage.set(&DataKey::Peppered(key.clone()), wallet);
+    storage.set(&DataKey::PepperedKey(wallet.clone()), &key);
+}
+
+fn app_salt(env: &Env, app: &Address, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::from_slice(env, APP_SALT_PREFIX);
+    preimage.append(&app.clone().to_xdr(env));
+    preimage.extend_from_array(&salt.to_array());
+    env.crypto().sha256(&preimage).into()
+}
+
+fn wallet_salt(env: &Env, key: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::from_slice(env, SALT_PREFIX);
+    preimage.extend_from_array(&key.to_array());
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Verify an ed25519 signature, with malformed input reported as
+/// `InvalidSignature` rather than a host trap. A well-formed signature that
+/// doesn't match still traps, since Soroban has no non-trapping verifier.
+fn verify_ed25519(
+    env: &Env,
+    public_key: &BytesN<32>,
+    message: &Bytes,
+    signature: &BytesN<64>,
+) -> Result<(), Error> {
+    let key = public_key.to_array();
+    let signature_bytes = signature.to_array();
+    let (r, s) = signature_bytes.split_at(32);
+
+    let small_order = |point: &[u8]| point == ED25519_IDENTITY || point == ED25519_ZERO_Y;
+    if small_order(&key) || small_order(r) {
+        return Err(Error::InvalidSignature);
+    }
+    // S is little-endian and must be reduced mod L
+    if !s.iter().rev().lt(ED25519_ORDER.iter().rev()) {
+        return Err(Error::InvalidSignature);
+    }
+
+    env.crypto().ed25519_verify(public_key, message, signature);
+    Ok(())
+}
+
+fn is_zero(bytes: &BytesN<32>) -> bool {
+    bytes.to_array().iter().all(|&b| b == 0)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,836 @@
+// src/test.rs
+
+extern crate std;
+
+use super::*;
+use acceslyinterface::manifest::MAX_MANIFEST_ITEMS;
+use acceslyinterface::presets::{HIGH_SECURITY, PRESET_HIGH_SECURITY};
+use acceslyinterface::versions::{BREAKING_AUTH, MAX_CHANGELOG_ITEMS};
+use accountAbstraction::{Signer, WalletContract, WalletContractClient};
+use ed25519_dalek::{Signer as _, SigningKey};
+use soroban_sdk::{
+    testutils::{Address as _, MockAuth, MockAuthInvoke},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol,
+};
+use walletRegistry::{WalletRegistryContract, WalletRegistryContractClient};
+
+/// Built by `stellar contract build` (`make build` in contracts/accountAbstraction)
+const WALLET_WASM: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../target/wasm32v1-none/release/accountAbstraction.wasm"
+);
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn setup(env: &Env, wasm_hash: &BytesN<32>) -> (WalletFactoryContractClient<'static>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(WalletFactoryContract, ());
+    let client = WalletFactoryContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    client.init(&admin, wasm_hash);
+
+    (client, admin)
+}
+
+fn changelog(env: &Env, breaking: u32) -> Changelog {
+    Changelog {
+        breaking,
+        migrated_keys: vec![env, Symbol::new(env, "Signers")],
+        new_entrypoints: vec![env, Symbol::new(env, "upgrade_to_latest")],
+    }
+}
+
+/// Upload the wallet WASM. Panics when it hasn't been built, so a missing
+/// build fails the deploy tests instead of passing them unrun.
+fn upload_wallet(env: &Env) -> BytesN<32> {
+    let wasm = std::fs::read(WALLET_WASM).unwrap_or_else(|_| {
+        panic!("{WALLET_WASM} not built: run `make build` in contracts/accountAbstraction")
+    });
+    env.deployer().upload_contract_wasm(wasm.as_slice())
+}
+
+/// Register a wallet owned by `key`, without going through the factory
+fn setup_wallet<'a>(env: &'a Env, key: &SigningKey) -> WalletContractClient<'a> {
+    let wallet = WalletContractClient::new(env, &env.register(WalletContract, ()));
+    let owner = BytesN::from_array(env, &key.verifying_key().to_bytes());
+    wallet.init(&owner, &BytesN::from_array(env, &[2u8; 32]));
+    wallet
+}
+
+/// `setup_wallet`, at the address `factory` deploys to with `salt`
+fn setup_wallet_at<'a>(
+    env: &'a Env,
+    key: &SigningKey,
+    factory: &Address,
+    salt: &BytesN<32>,
+) -> WalletContractClient<'a> {
+    let address = env
+        .deployer()
+        .with_address(factory.clone(), salt.clone())
+        .deployed_address();
+    let wallet = WalletContractClient::new(env, &env.register_at(&address, WalletContract, ()));
+    let owner = BytesN::from_array(env, &key.verifying_key().to_bytes());
+    wallet.init(&owner, &BytesN::from_array(env, &[2u8; 32]));
+    wallet
+}
+
+/// sha256(pepper || email_hash), the key the backend indexes a wallet under
+fn lookup_key(env: &Env, pepper: &Bytes, email_hash: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = pepper.clone();
+    preimage.extend_from_array(&email_hash.to_array());
+    env.crypto().sha256(&preimage).into()
+}
+
+/// `key`'s signature over `action || payload || nonce` for `wallet`
+fn sign_action(
+    wallet: &WalletContractClient,
+    key: &SigningKey,
+    action: &str,
+    payload: &Bytes,
+) -> BytesN<64> {
+    let mut message = std::vec::Vec::from(action.as_bytes());
+    message.extend(payload.iter());
+    message.extend(wallet.get_nonce().to_be_bytes());
+    BytesN::from_array(&wallet.env, &key.sign(&message).to_bytes())
+}
+
+// ============================================================================
+// ADDRESS TESTS
+// ============================================================================
+
+#[test]
+fn test_wallet_address_is_deterministic() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let (other_factory, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+
+    let key = BytesN::from_array(&env, &[2u8; 32]);
+    let address = client.get_wallet_address(&key);
+    assert_eq!(client.get_wallet_address(&key), address);
+    assert_ne!(
+        client.get_wallet_address(&BytesN::from_array(&env, &[3u8; 32])),
+        address
+    );
+    assert_ne!(other_factory.get_wallet_address(&key), address);
+}
+
+// ============================================================================
+// DEPLOYMENT TESTS
+// ============================================================================
+
+#[test]
+fn test_deploy_wallet() {
+    let env = Env::default();
+    let wasm_hash = upload_wallet(&env);
+    let (client, admin) = setup(&env, &wasm_hash);
+
+    let registry_id = env.register(WalletRegistryContract, ());
+    let registry = WalletRegistryContractClient::new(&env, &registry_id);
+    registry.init(&admin, &client.address);
+    client.set_registry(&registry_id);
+
+    let pepper = Bytes::from_array(&env, &[7u8; 16]);
+    client.set_pepper_hash(&env.crypto().sha256(&pepper).into());
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let key = lookup_key(&env, &pepper, &email_hash);
+    let predicted = client.get_wallet_address(&key);
+
+    let wallet = client.deploy_wallet(&owner, &email_hash, &key);
+    assert_eq!(wallet, predicted);
+    assert_eq!(
+        client.lookup_peppered(&pepper, &email_hash),
+        Some(wallet.clone())
+    );
+    // The address doesn't follow from the email hash alone
+    assert_ne!(client.get_wallet_address(&email_hash), wallet);
+
+    // Initialized in the same call
+    let wallet_client = WalletContractClient::new(&env, &wallet);
+    assert_eq!(wallet_client.get_owner(), owner);
+    assert_eq!(wallet_client.get_email_hash(), email_hash);
+
+    assert_eq!(registry.lookup(&email_hash), wallet);
+    assert_eq!(registry.lookup_by_owner(&owner), wallet);
+
+    assert_eq!(
+        client.try_deploy_wallet(&owner, &email_hash, &key),
+        Err(Ok(Error::AlreadyDeployed))
+    );
+}
+
+#[test]
+fn test_deploy_wallet_rejects_zero_inputs() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    let filled = BytesN::from_array(&env, &[2u8; 32]);
+
+    assert_eq!(
+        client.try_deploy_wallet(&zero, &filled, &filled),
+        Err(Ok(Error::InvalidOwner))
+    );
+    assert_eq!(
+        client.try_deploy_wallet(&filled, &zero, &filled),
+        Err(Ok(Error::InvalidEmailHash))
+    );
+}
+
+#[test]
+fn test_deploy_with_preset() {
+    let env = Env::default();
+    let wasm_hash = upload_wallet(&env);
+    let (client, _) = setup(&env, &wasm_hash);
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let key = BytesN::from_array(&env, &[3u8; 32]);
+
+    let wallet = client.deploy_with_preset(&owner, &email_hash, &key, &PRESET_HIGH_SECURITY);
+    assert_eq!(wallet, client.get_wallet_address(&key));
+
+    let wallet_client = WalletContractClient::new(&env, &wallet);
+    assert_eq!(wallet_client.get_owner(), owner);
+    assert_eq!(
+        wallet_client.get_rotation_delay(),
+        HIGH_SECURITY.rotation_delay
+    );
+    assert_eq!(
+        wallet_client.get_batch_limits().max_ops,
+        HIGH_SECURITY.max_batch_ops
+    );
+    assert!(wallet_client.is_contacts_only());
+}
+
+#[test]
+fn test_deploy_with_unknown_preset() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+
+    assert_eq!(
+        client.try_deploy_with_preset(&owner, &email_hash, &email_hash, &0),
+        Err(Ok(Error::UnknownPreset))
+    );
+}
+
+// ============================================================================
+// APP TESTS
+// ============================================================================
+
+#[test]
+fn test_deploy_for_app() {
+    let env = Env::default();
+    let wasm_hash = upload_wallet(&env);
+    let (client, _) = setup(&env, &wasm_hash);
+    let app = Address::generate(&env);
+    client.register_app(&app, &1);
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let salt = BytesN::from_array(&env, &[3u8; 32]);
+
+    let wallet = client.deploy_for_app(&app, &salt, &owner, &email_hash, &wasm_hash);
+    assert_eq!(wallet, client.get_app_wallet_address(&app, &salt));
+    assert_eq!(WalletContractClient::new(&env, &wallet).get_owner(), owner);
+    assert_eq!(
+        client.get_app(&app),
+        Some(AppInfo {
+            quota: 1,
+            deployed: 1
+        })
+    );
+
+    let other_email = BytesN::from_array(&env, &[4u8; 32]);
+    assert_eq!(
+        client.try_deploy_for_app(&app, &salt, &owner, &other_email, &wasm_hash),
+        Err(Ok(Error::DeployQuotaExceeded))
+    );
+    client.register_app(&app, &3);
+    assert_eq!(
+        client.try_deploy_for_app(&app, &salt, &owner, &other_email, &wasm_hash),
+        Err(Ok(Error::SaltAlreadyUsed))
+    );
+}
+
+#[test]
+fn test_deploy_for_app_cannot_squat_an_email() {
+    let env = Env::default();
+    let wasm_hash = upload_wallet(&env);
+    let (client, admin) = setup(&env, &wasm_hash);
+    let registry_id = env.register(WalletRegistryContract, ());
+    let registry = WalletRegistryContractClient::new(&env, &registry_id);
+    registry.init(&admin, &client.address);
+    client.set_registry(&registry_id);
+    let app = Address::generate(&env);
+    client.register_app(&app, &2);
+
+    // The app deploys for an email it never verified, before the backend
+    let victim = BytesN::from_array(&env, &[2u8; 32]);
+    let squatter = BytesN::from_array(&env, &[6u8; 32]);
+    let salt = BytesN::from_array(&env, &[3u8; 32]);
+    client.deploy_for_app(&app, &salt, &squatter, &victim, &wasm_hash);
+    assert_eq!(
+        registry.try_lookup(&victim),
+        Err(Ok(walletRegistry::Error::NotFound))
+    );
+
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let key = BytesN::from_array(&env, &[4u8; 32]);
+    let wallet = client.deploy_wallet(&owner, &victim, &key);
+    assert_eq!(registry.lookup(&victim), wallet);
+}
+
+#[test]
+fn test_deploy_for_app_rejects_unknown_apps_and_code() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let app = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[3u8; 32]);
+    let owner = BytesN::from_array(&env, &[1u8; 32]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let unknown = BytesN::from_array(&env, &[9u8; 32]);
+
+    assert_eq!(
+        client.try_deploy_for_app(&app, &salt, &owner, &email_hash, &unknown),
+        Err(Ok(Error::AppNotRegistered))
+    );
+    client.register_app(&app, &0);
+    assert_eq!(
+        client.try_deploy_for_app(&app, &salt, &owner, &email_hash, &unknown),
+        Err(Ok(Error::DeployQuotaExceeded))
+    );
+    client.register_app(&app, &1);
+    assert_eq!(
+        client.try_deploy_for_app(&app, &salt, &owner, &email_hash, &unknown),
+        Err(Ok(Error::WasmHashUnknown))
+    );
+    assert_ne!(
+        client.get_app_wallet_address(&app, &salt),
+        client.get_wallet_address(&email_hash)
+    );
+}
+
+// ============================================================================
+// PEPPERED INDEX TESTS
+// ============================================================================
+
+#[test]
+fn test_peppered_lookup() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let pepper = Bytes::from_array(&env, &[7u8; 16]);
+    let email_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let wallet = Address::generate(&env);
+
+    assert_eq!(
+        client.try_lookup_peppered(&pepper, &email_hash),
+        Err(Ok(Error::InvalidPepper))
+    );
+    client.set_pepper_hash(&env.crypto().sha256(&pepper).into());
+
+    let key = lookup_key(&env, &pepper, &email_hash);
+    assert_eq!(client.lookup_peppered(&pepper, &email_hash), None);
+    client.index_wallet(&key, &wallet);
+    assert_eq!(
+        client.lookup_peppered(&pepper, &email_hash),
+        Some(wallet.clone())
+    );
+    assert_eq!(
+        client.try_index_wallet(&key, &wallet),
+        Err(Ok(Error::AlreadyIndexed))
+    );
+
+    // After an email change the wallet is indexed under the new key only
+    let new_email_hash = BytesN::from_array(&env, &[3u8; 32]);
+    client.index_wallet(&lookup_key(&env, &pepper, &new_email_hash), &wallet);
+    assert_eq!(
+        client.lookup_peppered(&pepper, &new_email_hash),
+        Some(wallet.clone())
+    );
+    assert_eq!(client.lookup_peppered(&pepper, &email_hash), None);
+
+    // The index is useless without the pepper
+    let guess = Bytes::from_array(&env, &[8u8; 16]);
+    assert_eq!(
+        client.try_lookup_peppered(&guess, &email_hash),
+        Err(Ok(Error::InvalidPepper))
+    );
+}
+
+// ============================================================================
+// LINKED WALLET TESTS
+// ============================================================================
+
+#[test]
+fn test_link_wallets() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let identity = BytesN::from_array(&env, &[9u8; 32]);
+    let personal = Address::generate(&env);
+    let business = Address::generate(&env);
+
+    client.link_wallet(&identity, &personal, &WalletRole::Personal);
+    client.link_wallet(&identity, &business, &WalletRole::Business);
+    let linked = client.get_linked_wallets(&identity);
+    assert_eq!(linked.len(), 2);
+    assert_eq!(linked.get(1).unwrap().wallet, business);
+    assert_eq!(linked.get(1).unwrap().role, WalletRole::Business);
+    assert_eq!(client.get_identity(&personal), Some(identity.clone()));
+
+    // A wallet belongs to one identity
+    let other = BytesN::from_array(&env, &[8u8; 32]);
+    assert_eq!(
+        client.try_link_wallet(&other, &personal, &WalletRole::Personal),
+        Err(Ok(Error::AlreadyLinked))
+    );
+    assert_eq!(
+        client.try_link_wallet(
+            &BytesN::from_array(&env, &[0u8; 32]),
+            &Address::generate(&env),
+            &WalletRole::Personal
+        ),
+        Err(Ok(Error::InvalidIdentity))
+    );
+
+    client.unlink_wallet(&personal);
+    assert_eq!(client.get_linked_wallets(&identity).len(), 1);
+    assert_eq!(client.get_identity(&personal), None);
+    assert_eq!(
+        client.try_unlink_wallet(&personal),
+        Err(Ok(Error::NotLinked))
+    );
+    client.link_wallet(&other, &personal, &WalletRole::Personal);
+}
+
+#[test]
+fn test_link_wallet_needs_backend_or_a_linked_wallet() {
+    let env = Env::default();
+    let (client, admin) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let identity = BytesN::from_array(&env, &[9u8; 32]);
+    let (personal, business) = (Address::generate(&env), Address::generate(&env));
+
+    // Only the backend vouches for a wallet belonging to a new identity
+    client.link_wallet(&identity, &personal, &WalletRole::Personal);
+    assert!(env.auths().iter().any(|(address, _)| *address == admin));
+    // and only a wallet already linked vouches for the next
+    client.link_wallet(&identity, &business, &WalletRole::Business);
+    assert!(env.auths().iter().any(|(address, _)| *address == personal));
+
+    // A third party's own signature isn't enough to join either
+    let intruder = Address::generate(&env);
+    let args = (identity.clone(), intruder.clone(), WalletRole::Personal).into_val(&env);
+    env.mock_auths(&[MockAuth {
+        address: &intruder,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "link_wallet",
+            args,
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client
+        .try_link_wallet(&identity, &intruder, &WalletRole::Personal)
+        .is_err());
+    let fresh = BytesN::from_array(&env, &[7u8; 32]);
+    let args = (fresh.clone(), intruder.clone(), WalletRole::Personal).into_val(&env);
+    env.mock_auths(&[MockAuth {
+        address: &intruder,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "link_wallet",
+            args,
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client
+        .try_link_wallet(&fresh, &intruder, &WalletRole::Personal)
+        .is_err());
+
+    env.mock_all_auths();
+    assert_eq!(client.get_linked_wallets(&identity).len(), 2);
+    assert_eq!(client.get_identity(&intruder), None);
+}
+
+#[test]
+fn test_linked_guardian_and_email() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let identity = BytesN::from_array(&env, &[9u8; 32]);
+    let keys = [
+        SigningKey::from_bytes(&[3u8; 32]),
+        SigningKey::from_bytes(&[4u8; 32]),
+    ];
+    let wallets = keys.each_ref().map(|key| setup_wallet(&env, key));
+    client.link_wallet(&identity, &wallets[0].address, &WalletRole::Personal);
+    client.link_wallet(&identity, &wallets[1].address, &WalletRole::Business);
+
+    let guardian = BytesN::from_array(&env, &[5u8; 32]);
+    let payload = Signer::Ed25519(guardian.clone()).to_xdr(&env);
+    let signatures = vec![
+        &env,
+        sign_action(&wallets[0], &keys[0], "add_guardian", &payload),
+        sign_action(&wallets[1], &keys[1], "add_guardian", &payload),
+    ];
+    assert_eq!(
+        client.try_add_linked_guardian(
+            &identity,
+            &GuardianKey::Ed25519(guardian.clone()),
+            &vec![&env, signatures.get(0).unwrap()]
+        ),
+        Err(Ok(Error::SignatureCountMismatch))
+    );
+    client.add_linked_guardian(
+        &identity,
+        &GuardianKey::Ed25519(guardian.clone()),
+        &signatures,
+    );
+    for wallet in &wallets {
+        assert_eq!(
+            wallet.get_guardians(),
+            vec![&env, Signer::Ed25519(guardian.clone())]
+        );
+    }
+
+    let email_hash = BytesN::from_array(&env, &[6u8; 32]);
+    let payload = Bytes::from_array(&env, &email_hash.to_array());
+    let signatures = vec![
+        &env,
+        sign_action(&wallets[0], &keys[0], "update_email_hash", &payload),
+        sign_action(&wallets[1], &keys[1], "update_email_hash", &payload),
+    ];
+    client.set_linked_email(&identity, &email_hash, &signatures);
+    for wallet in &wallets {
+        assert_eq!(wallet.get_email_hash(), email_hash);
+    }
+
+    assert_eq!(
+        client.try_set_linked_email(
+            &BytesN::from_array(&env, &[8u8; 32]),
+            &email_hash,
+            &Vec::new(&env)
+        ),
+        Err(Ok(Error::NotLinked))
+    );
+}
+
+#[test]
+fn test_email_change_moves_the_registry_entry() {
+    let env = Env::default();
+    let (client, admin) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let registry_id = env.register(WalletRegistryContract, ());
+    let registry = WalletRegistryContractClient::new(&env, &registry_id);
+    registry.init(&admin, &client.address);
+    // The registry admin vouches for each move from inside the wallet's call
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let key = SigningKey::from_bytes(&[3u8; 32]);
+    let salt = BytesN::from_array(&env, &[4u8; 32]);
+    let wallet = setup_wallet_at(&env, &key, &client.address, &salt);
+    let old_hash = wallet.get_email_hash();
+    registry.register(&old_hash, &wallet.get_owner(), &wallet.address);
+
+    // Only the contract that deployed the wallet binds it
+    assert_eq!(
+        wallet.try_bind_registry(&Address::generate(&env), &salt, &registry_id),
+        Err(Ok(accountAbstraction::Error::Unauthorized))
+    );
+    wallet.bind_registry(&client.address, &salt, &registry_id);
+    assert_eq!(wallet.get_registry(), Some(registry_id.clone()));
+
+    // Changed on the wallet itself
+    let new_hash = BytesN::from_array(&env, &[6u8; 32]);
+    let payload = Bytes::from_array(&env, &new_hash.to_array());
+    let signature = sign_action(&wallet, &key, "update_email_hash", &payload);
+    wallet.update_email_hash(&new_hash, &signature);
+    assert_eq!(registry.lookup(&new_hash), wallet.address);
+    assert_eq!(
+        registry.try_lookup(&old_hash),
+        Err(Ok(walletRegistry::Error::NotFound))
+    );
+
+    // Changed for every linked wallet at once
+    let identity = BytesN::from_array(&env, &[9u8; 32]);
+    client.link_wallet(&identity, &wallet.address, &WalletRole::Personal);
+    let linked_hash = BytesN::from_array(&env, &[7u8; 32]);
+    let payload = Bytes::from_array(&env, &linked_hash.to_array());
+    let signatures = vec![
+        &env,
+        sign_action(&wallet, &key, "update_email_hash", &payload),
+    ];
+    client.set_linked_email(&identity, &linked_hash, &signatures);
+    assert_eq!(registry.lookup(&linked_hash), wallet.address);
+    assert_eq!(
+        registry.try_lookup(&new_hash),
+        Err(Ok(walletRegistry::Error::NotFound))
+    );
+
+    // Moving onto a hash another wallet holds drops the entry, and the
+    // wallet can still change its email afterwards
+    let taken_hash = BytesN::from_array(&env, &[8u8; 32]);
+    let other = Address::generate(&env);
+    registry.register(&taken_hash, &BytesN::from_array(&env, &[5u8; 32]), &other);
+    for hash in [taken_hash.clone(), BytesN::from_array(&env, &[10u8; 32])] {
+        let payload = Bytes::from_array(&env, &hash.to_array());
+        let signature = sign_action(&wallet, &key, "update_email_hash", &payload);
+        wallet.update_email_hash(&hash, &signature);
+        assert_eq!(wallet.get_email_hash(), hash);
+    }
+    assert_eq!(registry.lookup(&taken_hash), other);
+    assert_eq!(
+        registry.lookup_by_owner(&wallet.get_owner()),
+        wallet.address
+    );
+}
+
+// ============================================================================
+// MANIFEST TESTS
+// ============================================================================
+
+/// `key`'s signature over the manifest message
+fn sign_manifest(
+    env: &Env,
+    key: &SigningKey,
+    wasm_hash: &BytesN<32>,
+    manifest: &Manifest,
+) -> BytesN<64> {
+    let mut message = Bytes::new(env);
+    accesly_messages::manifest_message(
+        &mut message,
+        &wasm_hash.to_array(),
+        &manifest.clone().to_xdr(env),
+    );
+    let message: std::vec::Vec<u8> = message.iter().collect();
+    BytesN::from_array(env, &key.sign(&message).to_bytes())
+}
+
+#[test]
+fn test_publish_manifest() {
+    let env = Env::default();
+    let wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let (client, _) = setup(&env, &wasm_hash);
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let manifest = Manifest {
+        entrypoints: vec![&env, Symbol::new(&env, "upgrade_to_latest")],
+        errors: vec![&env, 1, 2],
+        events: vec![&env, Symbol::new(&env, "key_rotated")],
+        policies: vec![&env, Symbol::new(&env, "fiat_limit")],
+    };
+    let signature = sign_manifest(&env, &key, &wasm_hash, &manifest);
+
+    assert_eq!(
+        client.try_publish_manifest(&wasm_hash, &manifest, &signature),
+        Err(Ok(Error::ManifestSignerNotSet))
+    );
+    let signer = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+    client.set_manifest_signer(&signer);
+
+    let unknown = BytesN::from_array(&env, &[9u8; 32]);
+    assert_eq!(
+        client.try_publish_manifest(&unknown, &manifest, &signature),
+        Err(Ok(Error::WasmHashUnknown))
+    );
+    // Signed by another key
+    let forged = sign_manifest(
+        &env,
+        &SigningKey::from_bytes(&[8u8; 32]),
+        &wasm_hash,
+        &manifest,
+    );
+    assert!(client
+        .try_publish_manifest(&wasm_hash, &manifest, &forged)
+        .is_err());
+    assert_eq!(client.get_manifest(&wasm_hash), None);
+
+    client.publish_manifest(&wasm_hash, &manifest, &signature);
+    assert_eq!(
+        client.get_manifest(&wasm_hash),
+        Some(SignedManifest {
+            manifest: manifest.clone(),
+            signer,
+            signature,
+        })
+    );
+
+    let mut oversized = manifest;
+    for code in 0..=MAX_MANIFEST_ITEMS {
+        oversized.errors.push_back(code);
+    }
+    let signature = sign_manifest(&env, &key, &wasm_hash, &oversized);
+    assert_eq!(
+        client.try_publish_manifest(&wasm_hash, &oversized, &signature),
+        Err(Ok(Error::InvalidManifest))
+    );
+}
+
+#[test]
+fn test_publish_manifest_rejects_forged_signatures() {
+    let env = Env::default();
+    let wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let (client, _) = setup(&env, &wasm_hash);
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    client.set_manifest_signer(&BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+    let manifest = Manifest {
+        entrypoints: vec![&env, Symbol::new(&env, "upgrade_to_latest")],
+        errors: vec![&env, 1],
+        events: vec![&env],
+        policies: vec![&env],
+    };
+
+    // Zeroed, and with S pushed past the group order
+    let zeroed = BytesN::from_array(&env, &[0u8; 64]);
+    let mut high_s = sign_manifest(&env, &key, &wasm_hash, &manifest).to_array();
+    high_s[63] |= 0xf0;
+    for forged in [zeroed, BytesN::from_array(&env, &high_s)] {
+        assert_eq!(
+            client.try_publish_manifest(&wasm_hash, &manifest, &forged),
+            Err(Ok(Error::InvalidSignature))
+        );
+    }
+    assert_eq!(client.get_manifest(&wasm_hash), None);
+}
+
+#[test]
+fn test_set_wallet_wasm_hash_requires_admin() {
+    let env = Env::default();
+    let (client, admin) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+
+    let new_hash = BytesN::from_array(&env, &[9u8; 32]);
+    client.set_wallet_wasm_hash(&new_hash, &changelog(&env, 0));
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_wallet_wasm_hash(), new_hash);
+}
+
+#[test]
+fn test_init_twice() {
+    let env = Env::default();
+    let hash = BytesN::from_array(&env, &[1u8; 32]);
+    let (client, admin) = setup(&env, &hash);
+
+    assert_eq!(
+        client.try_init(&admin, &hash),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+}
+
+// ============================================================================
+// VERSION TESTS
+// ============================================================================
+
+#[test]
+fn test_version_info_recorded_with_each_wasm_hash() {
+    let env = Env::default();
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    let (client, _) = setup(&env, &first);
+
+    let initial = client.get_version_info(&first).unwrap();
+    assert!(!initial.changelog.is_breaking());
+    assert!(initial.changelog.new_entrypoints.is_empty());
+
+    let second = BytesN::from_array(&env, &[2u8; 32]);
+    assert_eq!(client.get_version_info(&second), None);
+    client.set_wallet_wasm_hash(&second, &changelog(&env, BREAKING_AUTH));
+    let info = client.get_version_info(&second).unwrap();
+    assert_eq!(info.changelog, changelog(&env, BREAKING_AUTH));
+    assert_eq!(info.registered_at, env.ledger().sequence());
+    // The earlier entry is kept
+    assert_eq!(client.get_version_info(&first), Some(initial));
+}
+
+#[test]
+fn test_set_wallet_wasm_hash_rejects_bad_changelogs() {
+    let env = Env::default();
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    let (client, _) = setup(&env, &first);
+    let second = BytesN::from_array(&env, &[2u8; 32]);
+
+    assert_eq!(
+        client.try_set_wallet_wasm_hash(&second, &changelog(&env, 1 << 7)),
+        Err(Ok(Error::InvalidChangelog))
+    );
+    let mut long = changelog(&env, 0);
+    for _ in 0..MAX_CHANGELOG_ITEMS {
+        long.new_entrypoints.push_back(Symbol::new(&env, "extra"));
+    }
+    assert_eq!(
+        client.try_set_wallet_wasm_hash(&second, &long),
+        Err(Ok(Error::InvalidChangelog))
+    );
+
+    // Going back to a registered version would reuse its changelog
+    assert_eq!(
+        client.try_set_wallet_wasm_hash(&first, &changelog(&env, 0)),
+        Err(Ok(Error::VersionExists))
+    );
+    assert_eq!(client.get_wallet_wasm_hash(), first);
+}
+
+// ============================================================================
+// WATCHLIST TESTS
+// ============================================================================
+
+#[test]
+fn test_watchlist() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let wallet = Address::generate(&env);
+    let (savings, vault) = (Address::generate(&env), Address::generate(&env));
+
+    client.add_watch(&wallet, &savings, &String::from_str(&env, "Savings"));
+    assert_eq!(env.auths()[0].0, wallet);
+    client.add_watch(&wallet, &vault, &String::from_str(&env, "Vault"));
+
+    let watchlist = client.get_watchlist(&wallet);
+    assert_eq!(watchlist.len(), 2);
+    assert_eq!(watchlist.get(0).unwrap().account, savings);
+    assert_eq!(
+        watchlist.get(1).unwrap().label,
+        String::from_str(&env, "Vault")
+    );
+    // Lists are per wallet
+    assert!(client.get_watchlist(&Address::generate(&env)).is_empty());
+
+    client.remove_watch(&wallet, &savings);
+    assert_eq!(client.get_watchlist(&wallet).get(0).unwrap().account, vault);
+    assert_eq!(
+        client.try_remove_watch(&wallet, &savings),
+        Err(Ok(Error::NotWatched))
+    );
+    client.remove_watch(&wallet, &vault);
+    assert!(client.get_watchlist(&wallet).is_empty());
+}
+
+#[test]
+fn test_watchlist_rejects_bad_entries() {
+    let env = Env::default();
+    let (client, _) = setup(&env, &BytesN::from_array(&env, &[1u8; 32]));
+    let wallet = Address::generate(&env);
+    let label = String::from_str(&env, "Savings");
+
+    assert_eq!(
+        client.try_add_watch(&wallet, &wallet, &label),
+        Err(Ok(Error::InvalidAccount))
+    );
+    let long_label = String::from_str(&env, "a label that is far too long to show");
+    assert_eq!(
+        client.try_add_watch(&wallet, &Address::generate(&env), &long_label),
+        Err(Ok(Error::InvalidLabel))
+    );
+
+    let account = Address::generate(&env);
+    client.add_watch(&wallet, &account, &label);
+    assert_eq!(
+        client.try_add_watch(&wallet, &account, &label),
+        Err(Ok(Error::AlreadyWatched))
+    );
+    for _ in 1..MAX_WATCHLIST {
+        client.add_watch(&wallet, &Address::generate(&env), &label);
+    }
+    assert_eq!(
+        client.try_add_watch(&wallet, &Address::generate(&env), &label),
+        Err(Ok(Error::WatchlistFull))
+    );
+}
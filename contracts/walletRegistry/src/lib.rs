@@ -0,0 +1,273 @@
+#![no_std]
+#![allow(non_snake_case)]
+
+// ============================================================================
+// WALLET REGISTRY
+//
+// On-chain index from email hash and owner key to wallet address, so recovery
+// and the SDK can find a user's wallet without asking the Accesly backend.
+// Only the factory writes new entries, in the same call that deploys the
+// wallet. Owner keys rotate, so the owner index is refreshed with
+// `sync_owner`, which anyone can call: it reads the owner from the wallet
+// itself rather than trusting the caller. Email hashes rotate too, and the
+// wallet moves its entry with `sync_email` as it changes. The registry
+// can't tell whether the new address is the user's, and a wallet moving to
+// someone else's hash would squat it, so the admin (the Accesly backend,
+// which verified the address) authorizes every move along with the wallet
+// listed under the old hash. An email hash still names one wallet: when
+// another already holds the new one, the moving wallet leaves the email
+// index and stays findable by owner, and later email changes leave the
+// registry alone.
+// ============================================================================
+
+use acceslyinterface::events;
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, vec, Address, BytesN, Env, String, Symbol,
+};
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotFound = 3,
+    AlreadyRegistered = 4,
+}
+
+// ============================================================================
+// STORAGE KEYS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    /// The only address allowed to register wallets
+    Factory,
+    /// Entry for an email hash
+    Wallet(BytesN<32>),
+    /// Wallet whose current owner is this key
+    Owner(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletEntry {
+    pub wallet: Address,
+    /// Owner as of registration or the last `sync_owner`
+    pub owner: BytesN<32>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletRegisteredEvent {
+    pub email_hash: BytesN<32>,
+    pub wallet: Address,
+    pub owner: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnerSyncedEvent {
+    pub old_owner: BytesN<32>,
+    pub new_owner: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmailSyncedEvent {
+    pub old_email_hash: BytesN<32>,
+    pub new_email_hash: BytesN<32>,
+    /// False when another wallet already held the new hash
+    pub listed: bool,
+}
+
+// ============================================================================
+// CONTRACT
+// ============================================================================
+
+#[contract]
+pub struct WalletRegistryContract;
+
+#[contractimpl]
+impl WalletRegistryContract {
+    pub fn init(env: Env, admin: Address, factory: Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Factory, &factory);
+
+        Ok(())
+    }
+
+    pub fn get_factory(env: Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Factory)
+            .ok_or(Error::NotInitialized)
+    }
+
+    /// Hand registration over to a new factory
+    pub fn set_factory(env: Env, factory: Address) -> Result<(), Error> {
+        Self::admin(&env)?.require_auth();
+
+        env.storage().instance().set(&DataKey::Factory, &factory);
+
+        Ok(())
+    }
+
+    /// Record a freshly deployed wallet. Factory only.
+    pub fn register(
+        env: Env,
+        email_hash: BytesN<32>,
+        owner: BytesN<32>,
+        wallet: Address,
+    ) -> Result<(), Error> {
+        Self::get_factory(env.clone())?.require_auth();
+
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Wallet(email_hash.clone()))
+            || storage.has(&DataKey::Owner(owner.clone()))
+        {
+            return Err(Error::AlreadyRegistered);
+        }
+
+        storage.set(
+            &DataKey::Wallet(email_hash.clone()),
+            &WalletEntry {
+                wallet: wallet.clone(),
+                owner: owner.clone(),
+            },
+        );
+        storage.set(&DataKey::Owner(owner.clone()), &wallet);
+
+        env.events().publish(
+            (Symbol::new(&env, events::WALLET_REGISTERED), wallet.clone()),
+            WalletRegisteredEvent {
+                email_hash,
+                wallet,
+                owner,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn lookup(env: Env, email_hash: BytesN<32>) -> Result<Address, Error> {
+        Self::entry(&env, email_hash).map(|entry| entry.wallet)
+    }
+
+    pub fn lookup_by_owner(env: Env, owner: BytesN<32>) -> Result<Address, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Owner(owner))
+            .ok_or(Error::NotFound)
+    }
+
+    /// Re-index the wallet for `email_hash` under the owner it has now.
+    /// Returns the current owner.
+    pub fn sync_owner(env: Env, email_hash: BytesN<32>) -> Result<BytesN<32>, Error> {
+        let mut entry = Self::entry(&env, email_hash.clone())?;
+        let owner: BytesN<32> =
+            env.invoke_contract(&entry.wallet, &Symbol::new(&env, "get_owner"), vec![&env]);
+        if owner == entry.owner {
+            return Ok(owner);
+        }
+
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Owner(owner.clone())) {
+            return Err(Error::AlreadyRegistered);
+        }
+        storage.remove(&DataKey::Owner(entry.owner.clone()));
+        storage.set(&DataKey::Owner(owner.clone()), &entry.wallet);
+
+        let old_owner = core::mem::replace(&mut entry.owner, owner.clone());
+        storage.set(&DataKey::Wallet(email_hash), &entry);
+
+        env.events().publish(
+            (Symbol::new(&env, events::OWNER_SYNCED), entry.wallet),
+            OwnerSyncedEvent {
+                old_owner,
+                new_owner: owner.clone(),
+            },
+        );
+
+        Ok(owner)
+    }
+
+    /// Move `wallet`'s entry from `email_hash` to `new_email_hash`.
+    /// Authorized by the wallet and by the admin, vouching that the new
+    /// address is the user's. A wallet not listed under `email_hash` has
+    /// nothing to move.
+    pub fn sync_email(
+        env: Env,
+        wallet: Address,
+        email_hash: BytesN<32>,
+        new_email_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        wallet.require_auth();
+        let entry = match Self::entry(&env, email_hash.clone()) {
+            Ok(entry) if entry.wallet == wallet => entry,
+            _ => return Ok(()),
+        };
+        Self::admin(&env)?.require_auth();
+
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::Wallet(email_hash.clone()));
+        let listed = !storage.has(&DataKey::Wallet(new_email_hash.clone()));
+        if listed {
+            storage.set(&DataKey::Wallet(new_email_hash.clone()), &entry);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, events::EMAIL_SYNCED), wallet),
+            EmailSyncedEvent {
+                old_email_hash: email_hash,
+                new_email_hash,
+                listed,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Crate version and current ledger, read by the CLI health check
+    pub fn ping(env: Env) -> (String, u32) {
+        (
+            String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            env.ledger().sequence(),
+        )
+    }
+
+    fn admin(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)
+    }
+
+    fn entry(env: &Env, email_hash: BytesN<32>) -> Result<WalletEntry, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Wallet(email_hash))
+            .ok_or(Error::NotFound)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,165 @@
+// src/test.rs
+
+use super::*;
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, MockAuth, MockAuthInvoke},
+    Address, BytesN, Env, IntoVal,
+};
+
+/// Stand-in for a wallet: only `get_owner` is read by the registry
+#[contract]
+struct MockWallet;
+
+#[contractimpl]
+impl MockWallet {
+    pub fn set_owner(env: Env, owner: BytesN<32>) {
+        env.storage().instance().set(&0u32, &owner);
+    }
+
+    pub fn get_owner(env: Env) -> BytesN<32> {
+        env.storage().instance().get(&0u32).unwrap()
+    }
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn setup(env: &Env) -> (WalletRegistryContractClient<'static>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(WalletRegistryContract, ());
+    let client = WalletRegistryContractClient::new(env, &contract_id);
+
+    let factory = Address::generate(env);
+    client.init(&Address::generate(env), &factory);
+
+    (client, factory)
+}
+
+// ============================================================================
+// LOOKUP TESTS
+// ============================================================================
+
+#[test]
+fn test_register_and_lookup() {
+    let env = Env::default();
+    let (client, factory) = setup(&env);
+
+    let email_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let owner = BytesN::from_array(&env, &[2u8; 32]);
+    let wallet = Address::generate(&env);
+
+    client.register(&email_hash, &owner, &wallet);
+    assert_eq!(env.auths()[0].0, factory);
+
+    assert_eq!(client.lookup(&email_hash), wallet);
+    assert_eq!(client.lookup_by_owner(&owner), wallet);
+    assert_eq!(
+        client.try_lookup(&BytesN::from_array(&env, &[3u8; 32])),
+        Err(Ok(Error::NotFound))
+    );
+
+    assert_eq!(
+        client.try_register(&email_hash, &owner, &Address::generate(&env)),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+}
+
+#[test]
+fn test_sync_owner_follows_rotation() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+
+    let old_owner = BytesN::from_array(&env, &[2u8; 32]);
+    let new_owner = BytesN::from_array(&env, &[3u8; 32]);
+    let wallet = env.register(MockWallet, ());
+    let wallet_client = MockWalletClient::new(&env, &wallet);
+    wallet_client.set_owner(&old_owner);
+
+    let email_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.register(&email_hash, &old_owner, &wallet);
+
+    // Unchanged owner is a no-op
+    assert_eq!(client.sync_owner(&email_hash), old_owner);
+
+    wallet_client.set_owner(&new_owner);
+    assert_eq!(client.sync_owner(&email_hash), new_owner);
+    assert_eq!(client.lookup_by_owner(&new_owner), wallet);
+    assert_eq!(
+        client.try_lookup_by_owner(&old_owner),
+        Err(Ok(Error::NotFound))
+    );
+    assert_eq!(client.lookup(&email_hash), wallet);
+}
+
+#[test]
+fn test_sync_email_moves_the_entry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let client = WalletRegistryContractClient::new(&env, &env.register(WalletRegistryContract, ()));
+    client.init(&admin, &Address::generate(&env));
+
+    let (old_hash, new_hash) = (
+        BytesN::from_array(&env, &[1u8; 32]),
+        BytesN::from_array(&env, &[4u8; 32]),
+    );
+    let owner = BytesN::from_array(&env, &[2u8; 32]);
+    let wallet = Address::generate(&env);
+    client.register(&old_hash, &owner, &wallet);
+
+    client.sync_email(&wallet, &old_hash, &new_hash);
+    let auths = env.auths();
+    assert_eq!((&auths[0].0, &auths[1].0), (&wallet, &admin));
+    assert_eq!(client.lookup(&new_hash), wallet);
+    assert_eq!(client.try_lookup(&old_hash), Err(Ok(Error::NotFound)));
+
+    // Another wallet can't move it away
+    let squatter = Address::generate(&env);
+    let squatted = BytesN::from_array(&env, &[6u8; 32]);
+    client.sync_email(&squatter, &new_hash, &squatted);
+    assert_eq!(client.lookup(&new_hash), wallet);
+    assert_eq!(client.try_lookup(&squatted), Err(Ok(Error::NotFound)));
+
+    // A second wallet moving to the same address leaves the email index
+    let other_hash = BytesN::from_array(&env, &[5u8; 32]);
+    let other_owner = BytesN::from_array(&env, &[3u8; 32]);
+    let other = Address::generate(&env);
+    client.register(&other_hash, &other_owner, &other);
+    client.sync_email(&other, &other_hash, &new_hash);
+    assert_eq!(client.lookup(&new_hash), wallet);
+    assert_eq!(client.try_lookup(&other_hash), Err(Ok(Error::NotFound)));
+    assert_eq!(client.lookup_by_owner(&other_owner), other);
+
+    // ...and can still change its email after, without the admin
+    client.sync_email(&other, &new_hash, &other_hash);
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(client.lookup(&new_hash), wallet);
+}
+
+#[test]
+fn test_sync_email_needs_the_admin() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let (old_hash, new_hash) = (
+        BytesN::from_array(&env, &[1u8; 32]),
+        BytesN::from_array(&env, &[4u8; 32]),
+    );
+    let wallet = Address::generate(&env);
+    client.register(&old_hash, &BytesN::from_array(&env, &[2u8; 32]), &wallet);
+
+    env.mock_auths(&[MockAuth {
+        address: &wallet,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "sync_email",
+            args: (wallet.clone(), old_hash.clone(), new_hash.clone()).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client
+        .try_sync_email(&wallet, &old_hash, &new_hash)
+        .is_err());
+    assert_eq!(client.lookup(&old_hash), wallet);
+}
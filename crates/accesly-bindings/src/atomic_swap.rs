@@ -0,0 +1,62 @@
+// Generated by `cargo xtask bindings` from the atomicSwap contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::from_try_host;
+use soroban_sdk::{Address, Env, String};
+
+use atomicSwap::AtomicSwapContractClient;
+
+pub type Error = acceslyinterface::Error<core::convert::Infallible>;
+
+/// `AtomicSwapContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub AtomicSwapContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(AtomicSwapContractClient::new(env, address))
+    }
+
+    /// Crate version and the ledger the call ran in. `accesly-cli health`
+    /// simulates it to check the deployed swap code still executes.
+    pub fn ping(&self) -> Result<(String, u32), Error> {
+        from_try_host(self.0.try_ping())
+    }
+
+    /// Atomically swap `token_a` for `token_b` between parties `a` and `b`.
+    ///
+    /// Parameters:
+    /// - `a`           : Party A — offers `amount_a` of `token_a`
+    /// - `b`           : Party B — offers `amount_b` of `token_b`
+    /// - `token_a`     : SAC contract address for asset A (e.g. USDC)
+    /// - `token_b`     : SAC contract address for asset B (e.g. EURC)
+    /// - `amount_a`    : Amount of `token_a` that A is offering
+    /// - `min_b_for_a` : Minimum `token_b` that A expects in return
+    /// - `amount_b`    : Amount of `token_b` that B is offering
+    /// - `min_a_for_b` : Minimum `token_a` that B expects in return
+    ///
+    /// Both parties must provide a Soroban authorization entry for their
+    /// respective `require_auth_for_args` call before this transaction executes.
+    pub fn swap(
+        &self,
+        a: &Address,
+        b: &Address,
+        token_a: &Address,
+        token_b: &Address,
+        amount_a: &i128,
+        min_b_for_a: &i128,
+        amount_b: &i128,
+        min_a_for_b: &i128,
+    ) -> Result<(), Error> {
+        from_try_host(self.0.try_swap(
+            a,
+            b,
+            token_a,
+            token_b,
+            amount_a,
+            min_b_for_a,
+            amount_b,
+            min_a_for_b,
+        ))
+    }
+}
@@ -0,0 +1,66 @@
+// Generated by `cargo xtask bindings` from the kycAttestation contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::{from_try, from_try_host};
+use soroban_sdk::{Address, BytesN, Env, String};
+
+use kycAttestation::{KycAttestationContractClient, KycRecord, KycStatus};
+
+pub type Error = acceslyinterface::Error<kycAttestation::Error>;
+
+/// `KycAttestationContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub KycAttestationContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(KycAttestationContractClient::new(env, address))
+    }
+
+    /// Get the attestor currently allowed to write statuses
+    pub fn get_attestor(&self) -> Result<Address, Error> {
+        from_try(self.0.try_get_attestor())
+    }
+
+    /// Get the latest record for `subject`
+    pub fn get_record(&self, subject: &Address) -> Result<KycRecord, Error> {
+        from_try(self.0.try_get_record(subject))
+    }
+
+    /// Get the status for `subject`, `Pending` if nothing was attested yet
+    pub fn get_status(&self, subject: &Address) -> Result<KycStatus, Error> {
+        from_try_host(self.0.try_get_status(subject))
+    }
+
+    /// Initialize with an admin and the first attestor (e.g. Etherfuse)
+    pub fn init(&self, admin: &Address, attestor: &Address) -> Result<(), Error> {
+        from_try(self.0.try_init(admin, attestor))
+    }
+
+    /// Wallet-side check: true only for `Approved`
+    pub fn is_approved(&self, subject: &Address) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_approved(subject))
+    }
+
+    /// Crate version and current ledger, so monitoring can tell the
+    /// attestation contract is reachable before wallets depend on it
+    pub fn ping(&self) -> Result<(String, u32), Error> {
+        from_try_host(self.0.try_ping())
+    }
+
+    /// Swap the SEP-12 provider. Records written by the previous attestor stay
+    /// readable, so wallet-side checks keep working across the rotation.
+    pub fn rotate_attestor(&self, new_attestor: &Address) -> Result<(), Error> {
+        from_try(self.0.try_rotate_attestor(new_attestor))
+    }
+
+    /// Record the SEP-12 status of `subject`. Only the current attestor can write.
+    pub fn set_status(
+        &self,
+        subject: &Address,
+        status: &KycStatus,
+        reference: &BytesN<32>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_status(subject, status, reference))
+    }
+}
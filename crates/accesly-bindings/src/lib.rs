@@ -0,0 +1,26 @@
+// ---------------------------------------------------------------------------
+// accesly-bindings
+//
+// `Result`-returning clients for the workspace contracts, generated by
+// `cargo xtask bindings` from each contract's spec. Every method calls the
+// contract client's `try_` variant and returns `Result<T, Error>`, so
+// callers match on the contract's error enum:
+//
+//     match wallet.remove_device(&key, &confirmation, &signature) {
+//         Err(wallet::Error::Contract(WalletError::NotFound)) => ..,
+//         ..
+//     }
+//
+// The modules are generated; edit the contracts and rerun the task.
+// ---------------------------------------------------------------------------
+
+pub mod atomic_swap;
+pub mod kyc_attestation;
+pub mod oracle_adapter;
+pub mod paymaster;
+pub mod wallet;
+pub mod wallet_factory;
+pub mod wallet_registry;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,48 @@
+// Generated by `cargo xtask bindings` from the oracleAdapter contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::{from_try, from_try_host};
+use soroban_sdk::{Address, Env, Symbol};
+
+use oracleAdapter::{Asset, Feed, OracleAdapterContractClient};
+
+pub type Error = acceslyinterface::Error<oracleAdapter::Error>;
+
+/// `OracleAdapterContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub OracleAdapterContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(OracleAdapterContractClient::new(env, address))
+    }
+
+    /// Ticker prices are quoted in
+    pub fn base(&self) -> Result<Symbol, Error> {
+        from_try(self.0.try_base())
+    }
+
+    pub fn decimals(&self) -> Result<u32, Error> {
+        from_try_host(self.0.try_decimals())
+    }
+
+    pub fn get_feed(&self, asset: &Asset) -> Result<Option<Feed>, Error> {
+        from_try_host(self.0.try_get_feed(asset))
+    }
+
+    /// Initialize with an admin and the ticker prices are quoted in
+    pub fn init(&self, admin: &Address, base: &Symbol) -> Result<(), Error> {
+        from_try(self.0.try_init(admin, base))
+    }
+
+    /// Latest price of `asset` in the base currency, with `DECIMALS`
+    /// decimals, and when it was published. The base itself is always 1.
+    pub fn price(&self, asset: &Asset) -> Result<(i128, u64), Error> {
+        from_try(self.0.try_price(asset))
+    }
+
+    /// Price `asset` from `feed`, or stop pricing it with `None`. Admin only.
+    pub fn set_feed(&self, asset: &Asset, feed: &Option<Feed>) -> Result<(), Error> {
+        from_try(self.0.try_set_feed(asset, feed))
+    }
+}
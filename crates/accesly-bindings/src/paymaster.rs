@@ -0,0 +1,84 @@
+// Generated by `cargo xtask bindings` from the paymaster contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::{from_try, from_try_host};
+use soroban_sdk::{Address, Env, String};
+
+use paymaster::{Budget, PaymasterContractClient};
+
+pub type Error = acceslyinterface::Error<paymaster::Error>;
+
+/// `PaymasterContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub PaymasterContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(PaymasterContractClient::new(env, address))
+    }
+
+    /// Funds held for sponsorship, in stroops
+    pub fn balance(&self) -> Result<i128, Error> {
+        from_try(self.0.try_balance())
+    }
+
+    /// Move `amount` from `from` into the paymaster
+    pub fn deposit(&self, from: &Address, amount: &i128) -> Result<i128, Error> {
+        from_try(self.0.try_deposit(from, amount))
+    }
+
+    /// Budget of `app_id` as of the current ledger
+    pub fn get_budget(&self, app_id: &String) -> Result<Budget, Error> {
+        from_try(self.0.try_get_budget(app_id))
+    }
+
+    /// Initialize with an admin and the SAC fees are paid in
+    pub fn init(&self, admin: &Address, token: &Address) -> Result<(), Error> {
+        from_try(self.0.try_init(admin, token))
+    }
+
+    pub fn is_relayer(&self, relayer: &Address) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_relayer(relayer))
+    }
+
+    /// Crate version and current ledger. Touches no budgets, so monitoring
+    /// can simulate it as often as it likes.
+    pub fn ping(&self) -> Result<(String, u32), Error> {
+        from_try_host(self.0.try_ping())
+    }
+
+    /// What `app_id` can still spend in the current window
+    pub fn remaining(&self, app_id: &String) -> Result<i128, Error> {
+        from_try(self.0.try_remaining(app_id))
+    }
+
+    /// Set what `app_id` may spend: `limit` stroops per `period` ledgers, or
+    /// in total with a `period` of 0. Admin only. Changing an existing budget
+    /// keeps what was already spent in the current window.
+    pub fn set_budget(&self, app_id: &String, limit: &i128, period: &u32) -> Result<(), Error> {
+        from_try(self.0.try_set_budget(app_id, limit, period))
+    }
+
+    /// Allow or disallow `relayer` to draw on app budgets. Admin only.
+    pub fn set_relayer(&self, relayer: &Address, allowed: &bool) -> Result<(), Error> {
+        from_try(self.0.try_set_relayer(relayer, allowed))
+    }
+
+    /// Reimburse `relayer` `amount` stroops of fees it paid for `wallet` on
+    /// behalf of `app_id`, out of that app's budget. Only allowlisted
+    /// relayers can call. Returns what the app has left in this window.
+    pub fn sponsor(
+        &self,
+        relayer: &Address,
+        app_id: &String,
+        wallet: &Address,
+        amount: &i128,
+    ) -> Result<i128, Error> {
+        from_try(self.0.try_sponsor(relayer, app_id, wallet, amount))
+    }
+
+    /// Pay `amount` out of the paymaster to `to`. Admin only.
+    pub fn withdraw(&self, to: &Address, amount: &i128) -> Result<i128, Error> {
+        from_try(self.0.try_withdraw(to, amount))
+    }
+}
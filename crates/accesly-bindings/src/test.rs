@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use accountAbstraction::WalletContract;
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env};
+use walletRegistry::WalletRegistryContract;
+
+use crate::{wallet, wallet_registry};
+
+fn setup_wallet(env: &Env) -> wallet::Client<'_> {
+    let wallet = wallet::Client::new(env, &env.register(WalletContract, ()));
+    wallet
+        .init(
+            &BytesN::from_array(env, &[1u8; 32]),
+            &BytesN::from_array(env, &[2u8; 32]),
+        )
+        .unwrap();
+    wallet
+}
+
+#[test]
+fn test_contract_errors_are_typed() {
+    let env = Env::default();
+    let wallet = setup_wallet(&env);
+
+    assert_eq!(wallet.get_owner(), Ok(BytesN::from_array(&env, &[1u8; 32])));
+    assert_eq!(
+        wallet.init(
+            &BytesN::from_array(&env, &[1u8; 32]),
+            &BytesN::from_array(&env, &[2u8; 32]),
+        ),
+        Err(wallet::Error::Contract(
+            accountAbstraction::Error::AlreadyInitialized
+        ))
+    );
+    assert_eq!(
+        wallet.remove_device(
+            &BytesN::from_array(&env, &[3u8; 32]),
+            &BytesN::from_array(&env, &[0u8; 64]),
+            &BytesN::from_array(&env, &[0u8; 64]),
+        ),
+        Err(wallet::Error::Contract(accountAbstraction::Error::NotFound))
+    );
+
+    let registry = wallet_registry::Client::new(&env, &env.register(WalletRegistryContract, ()));
+    assert_eq!(
+        registry.get_factory().unwrap_err().contract(),
+        Some(walletRegistry::Error::NotInitialized)
+    );
+}
+
+#[test]
+fn test_host_failures_are_not_contract_errors() {
+    let env = Env::default();
+    let wallet = wallet::Client::new(&env, &Address::generate(&env));
+
+    assert!(matches!(
+        wallet.get_guardians(),
+        Err(wallet::Error::Host(_))
+    ));
+    assert!(matches!(wallet.get_owner(), Err(wallet::Error::Host(_))));
+}
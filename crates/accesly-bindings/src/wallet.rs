@@ -0,0 +1,1634 @@
+// Generated by `cargo xtask bindings` from the accountAbstraction contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::{from_try, from_try_host};
+use soroban_sdk::{Address, Bytes, BytesN, Env, Map, String, Symbol, Val, Vec};
+
+use accountAbstraction::{
+    Allowance, AssetDisplay, Attestation, AuthLevel, BatchLimits, BatchOpOutcome, Contact,
+    ContractListing, ConversionRule, ConversionRuleChange, CooldownConfig, DepositBinding, Device,
+    FeeConfig, FiatLimit, FiatLimitChange, Freeze, HealthReport, Invocation, LendAction, MetaTx,
+    NotificationFilter, OpSummary, OracleChange, OwnAccount, PendingChange, PendingRotation,
+    PendingUpgrade, PolicyPreset, QuarantinedKey, QueuedTransfer, QuoteReceipt, ReadGrant,
+    ReceiptsCommitment, RecoveryCooldown, RecoveryRequest, ReserveConfig, ScheduledOp, Session,
+    SessionCall, SessionScope, ShareHolder, Signer, SignerProof, SnapshotCommitment, Sponsorship,
+    StalePrice, Subscription, VelocityBypass, VelocityLimit, VirtualAccount, WalletContractClient,
+};
+
+pub type Error = acceslyinterface::Error<accountAbstraction::Error>;
+
+/// `WalletContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub WalletContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(WalletContractClient::new(env, address))
+    }
+
+    /// Verify a signed quote and pay `destination` on its terms. Each quote
+    /// can be accepted once, and not for more than a large-transfer
+    /// threshold.
+    pub fn accept_quote(
+        &self,
+        quote_id: &BytesN<32>,
+        signed_quote_blob: &Bytes,
+        signature: &BytesN<64>,
+    ) -> Result<QuoteReceipt, Error> {
+        from_try(
+            self.0
+                .try_accept_quote(quote_id, signed_quote_blob, signature),
+        )
+    }
+
+    /// Save `address` as a contact, or relabel it. A new contact can receive
+    /// transfers after `CONTACT_COOLING_OFF`. Owner-signed over
+    /// (address, label).
+    pub fn add_contact(
+        &self,
+        address: &Address,
+        label: &String,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_add_contact(address, label, signature))
+    }
+
+    /// Link a device key, labelled as it should show in settings
+    pub fn add_device(
+        &self,
+        public_key: &BytesN<32>,
+        label: &String,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_add_device(public_key, label, signature))
+    }
+
+    pub fn add_guardian(&self, guardian: &Signer, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_add_guardian(guardian, signature))
+    }
+
+    /// Link `address` as one of the owner's own accounts. Transfers to it
+    /// are internal after `OWN_ACCOUNT_COOLING_OFF`. Owner-signed over the
+    /// address.
+    pub fn add_own_account(&self, address: &Address, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_add_own_account(address, signature))
+    }
+
+    pub fn add_signer(
+        &self,
+        signer: &Signer,
+        weight: &u32,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_add_signer(signer, weight, signature))
+    }
+
+    /// Switch to the proposed code once the delay has passed. The new
+    /// code only runs from the next call on, so `migrate` follows separately.
+    pub fn apply_upgrade(&self) -> Result<(), Error> {
+        from_try(self.0.try_apply_upgrade())
+    }
+
+    /// Let `spender` pull up to `amount` of `asset` until `expiry`,
+    /// replacing any allowance it had. Owner-signed over
+    /// (spender, asset, amount, expiry).
+    pub fn approve(
+        &self,
+        spender: &Address,
+        asset: &Address,
+        amount: &i128,
+        expiry: &u64,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_approve(spender, asset, amount, expiry, signature),
+        )
+    }
+
+    /// Co-approve a proposed change. Signed by a weighted signer added
+    /// before the new-key window, over the hashed action message.
+    pub fn approve_change(&self, change: &BytesN<32>, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_approve_change(change, proof))
+    }
+
+    /// Approve supplying up to `amount` of `asset` as collateral to `pool`.
+    /// Replaces any previous approval.
+    pub fn approve_collateral(
+        &self,
+        pool: &Address,
+        asset: &Address,
+        amount: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_approve_collateral(pool, asset, amount, signature),
+        )
+    }
+
+    /// Approve the pending recovery. Signed by a guardian.
+    pub fn approve_recovery(&self, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_approve_recovery(proof))
+    }
+
+    /// Write `hash` into `slot`. Authorized by the slot's attestor.
+    pub fn attest(&self, slot: &Symbol, hash: &BytesN<32>) -> Result<(), Error> {
+        from_try(self.0.try_attest(slot, hash))
+    }
+
+    /// Authorize the backend to release fragments to the recovered key.
+    /// Signed by that key over "authorize_fragment_release" || nonce.
+    pub fn authorize_fragment_release(&self, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_authorize_fragment_release(signature))
+    }
+
+    /// Keep the wallet's entry in `registry` in step with its email hash.
+    /// Authorized by `deployer`, which must have deployed the wallet with
+    /// `salt`.
+    pub fn bind_registry(
+        &self,
+        deployer: &Address,
+        salt: &BytesN<32>,
+        registry: &Address,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_bind_registry(deployer, salt, registry))
+    }
+
+    /// Extend the wallet instance and code to live at least `ttl` more
+    /// ledgers. Meant for the relayer's sweep of dormant wallets.
+    pub fn bump(&self, ttl: &u32) -> Result<(), Error> {
+        from_try(self.0.try_bump(ttl))
+    }
+
+    /// Cancel a queued removal. Signed by the device being removed, over the
+    /// `cancel_device_removal` action message for its public key.
+    pub fn cancel_device_removal(
+        &self,
+        public_key: &BytesN<32>,
+        device_signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_cancel_device_removal(public_key, device_signature),
+        )
+    }
+
+    /// Cancel a proposed guardian change. Signed by a guardian, a linked
+    /// device, a registered signer, the passkey or the owner, over the
+    /// hashed action message.
+    pub fn cancel_guardian_change(
+        &self,
+        change: &BytesN<32>,
+        proof: &SignerProof,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_cancel_guardian_change(change, proof))
+    }
+
+    /// Cancel a queued transfer. Signed by the owner, a registered signer,
+    /// the passkey or a linked device.
+    pub fn cancel_queued_transfer(&self, id: &u32, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_cancel_queued_transfer(id, proof))
+    }
+
+    /// Cancel the pending rotation. Signed by the owner, a registered
+    /// signer, the passkey or a linked device.
+    pub fn cancel_rotation(&self, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_cancel_rotation(proof))
+    }
+
+    /// Drop a scheduled call before it runs. Owner-signed over the id.
+    pub fn cancel_scheduled(&self, id: &u32, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_cancel_scheduled(id, signature))
+    }
+
+    /// End a subscription. Owner-signed over the id.
+    pub fn cancel_subscription(&self, id: &u32, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_cancel_subscription(id, signature))
+    }
+
+    /// Cancel the pending upgrade. Signed by the owner, a registered
+    /// signer or the passkey.
+    pub fn cancel_upgrade(&self, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_cancel_upgrade(proof))
+    }
+
+    /// Pay the subscription's amount for the current interval, once it is
+    /// due. Anyone can call. Returns whether it was paid; `false` when the
+    /// wallet couldn't cover it and the interval was skipped.
+    pub fn collect(&self, id: &u32) -> Result<bool, Error> {
+        from_try(self.0.try_collect(id))
+    }
+
+    /// Read the registered assets' balances and commit their hash for the
+    /// current epoch. Called by the relayer, once per epoch.
+    pub fn commit_balance_snapshot(&self) -> Result<SnapshotCommitment, Error> {
+        from_try(self.0.try_commit_balance_snapshot())
+    }
+
+    /// Commit the receipts root for `epoch`. Epochs must strictly increase.
+    pub fn commit_receipts_root(
+        &self,
+        epoch: &u64,
+        root: &BytesN<32>,
+        receipt_count: &u32,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_commit_receipts_root(epoch, root, receipt_count))
+    }
+
+    /// Complete a rotation once its delay has passed. Signed by the new key.
+    pub fn confirm_rotation(&self, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_confirm_rotation(signature))
+    }
+
+    /// Convert `amount` of `from_token` under its rule and return how much
+    /// was received. Authorized by the rule's executor.
+    pub fn convert(&self, from_token: &Address, amount: &i128) -> Result<i128, Error> {
+        from_try(self.0.try_convert(from_token, amount))
+    }
+
+    /// Co-sign the change sha256(action || payload) for the owner to carry
+    /// out. Signed by a linked device, registered signer, the passkey or a
+    /// guardian, over the hashed action message.
+    pub fn cosign_action(&self, change: &BytesN<32>, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_cosign_action(change, proof))
+    }
+
+    /// Grant `public_key` the calls in `allowed` until `expires_at`, with
+    /// the hash of the risk summary shown to the owner, if any. Owner-signed
+    /// over (public_key, scope, allowed, expires_at, risk_summary).
+    pub fn create_session(
+        &self,
+        public_key: &BytesN<32>,
+        scope: &Symbol,
+        allowed: &Vec<SessionCall>,
+        expires_at: &u64,
+        risk_summary: &Option<BytesN<32>>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_create_session(
+            public_key,
+            scope,
+            allowed,
+            expires_at,
+            risk_summary,
+            signature,
+        ))
+    }
+
+    /// Pay `merchant` `amount` of `asset` every `interval` seconds, starting
+    /// now. Owner-signed over (merchant, asset, amount, interval). Returns
+    /// the subscription id.
+    pub fn create_subscription(
+        &self,
+        merchant: &Address,
+        asset: &Address,
+        amount: &i128,
+        interval: &u64,
+        signature: &BytesN<64>,
+    ) -> Result<u32, Error> {
+        from_try(
+            self.0
+                .try_create_subscription(merchant, asset, amount, interval, signature),
+        )
+    }
+
+    /// Re-issue the live session `parent_session_id` to `public_key`,
+    /// limited to `narrowed_scope`. Owner-signed over (parent_session_id,
+    /// public_key, narrowed_scope).
+    pub fn derive_session(
+        &self,
+        parent_session_id: &BytesN<32>,
+        public_key: &BytesN<32>,
+        narrowed_scope: &SessionScope,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_derive_session(
+            parent_session_id,
+            public_key,
+            narrowed_scope,
+            signature,
+        ))
+    }
+
+    /// Derive the virtual id for a reference: first 8 bytes of
+    /// sha256(wallet_address_xdr || reference). Payers can compute it offline.
+    pub fn derive_virtual_id(&self, reference: &BytesN<32>) -> Result<u64, Error> {
+        from_try_host(self.0.try_derive_virtual_id(reference))
+    }
+
+    /// Pay `amount` of `asset` out to the holders in proportion to their
+    /// shares. Amounts round down; the remainder stays in the wallet. More
+    /// than the large-transfer threshold has to go through the queue.
+    pub fn distribute(
+        &self,
+        asset: &Address,
+        amount: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_distribute(asset, amount, signature))
+    }
+
+    /// Run `ops` in order, all or nothing, and return each call's result
+    pub fn execute_batch(
+        &self,
+        ops: &Vec<Invocation>,
+        signature: &BytesN<64>,
+    ) -> Result<Vec<Val>, Error> {
+        from_try(self.0.try_execute_batch(ops, signature))
+    }
+
+    /// Run `ops` in order, rolling back and reporting each call that fails
+    /// instead of the whole batch. Once more than `max_failures` calls have
+    /// failed the rest are skipped. Only outcomes are returned, not the
+    /// calls' return values.
+    pub fn execute_batch_best_effort(
+        &self,
+        ops: &Vec<Invocation>,
+        max_failures: &u32,
+        signature: &BytesN<64>,
+    ) -> Result<Vec<BatchOpOutcome>, Error> {
+        from_try(
+            self.0
+                .try_execute_batch_best_effort(ops, max_failures, signature),
+        )
+    }
+
+    /// Unlink a device whose queued removal is due. Anyone can call.
+    pub fn execute_device_removal(&self, public_key: &BytesN<32>) -> Result<(), Error> {
+        from_try(self.0.try_execute_device_removal(public_key))
+    }
+
+    /// Run the call in `envelope` on the owner's signature, charging `fee`
+    /// (at most `envelope.max_fee`) for the relayer. Returns the call's result.
+    pub fn execute_meta_tx(
+        &self,
+        envelope: &MetaTx,
+        signature: &BytesN<64>,
+        fee: &i128,
+    ) -> Result<Val, Error> {
+        from_try(self.0.try_execute_meta_tx(envelope, signature, fee))
+    }
+
+    /// Send a queued transfer once its delay has passed. Anyone can call.
+    pub fn execute_queued_transfer(&self, id: &u32) -> Result<(), Error> {
+        from_try(self.0.try_execute_queued_transfer(id))
+    }
+
+    /// Rotate to the recovered key once enough guardians approved and the
+    /// delay has passed. Anyone can call.
+    pub fn execute_recovery(&self) -> Result<(), Error> {
+        from_try(self.0.try_execute_recovery())
+    }
+
+    /// Run a scheduled call whose time has come. Anyone can call. Returns
+    /// the call's result.
+    pub fn execute_scheduled(&self, id: &u32) -> Result<Val, Error> {
+        from_try(self.0.try_execute_scheduled(id))
+    }
+
+    /// Freeze the wallet, or restart the unfreeze delay of a frozen one.
+    /// Signed by the owner, a registered signer, the passkey, a linked
+    /// device or a guardian.
+    pub fn freeze(&self, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_freeze(proof))
+    }
+
+    /// Live allowance of `spender` for `asset`, if any
+    pub fn get_allowance(
+        &self,
+        spender: &Address,
+        asset: &Address,
+    ) -> Result<Option<Allowance>, Error> {
+        from_try_host(self.0.try_get_allowance(spender, asset))
+    }
+
+    /// Get and increment nonce atomically
+    pub fn get_and_increment_nonce(&self) -> Result<u64, Error> {
+        from_try(self.0.try_get_and_increment_nonce())
+    }
+
+    /// Every display override, for the SDK to apply in one read
+    pub fn get_asset_displays(&self) -> Result<Map<Address, AssetDisplay>, Error> {
+        from_try_host(self.0.try_get_asset_displays())
+    }
+
+    pub fn get_attestation(&self, slot: &Symbol) -> Result<Option<Attestation>, Error> {
+        from_try_host(self.0.try_get_attestation(slot))
+    }
+
+    pub fn get_attestor(&self, slot: &Symbol) -> Result<Option<Address>, Error> {
+        from_try_host(self.0.try_get_attestor(slot))
+    }
+
+    /// Every action above `Single`
+    pub fn get_auth_policies(&self) -> Result<Map<Symbol, AuthLevel>, Error> {
+        from_try_host(self.0.try_get_auth_policies())
+    }
+
+    pub fn get_auth_policy(&self, action: &Symbol) -> Result<AuthLevel, Error> {
+        from_try_host(self.0.try_get_auth_policy(action))
+    }
+
+    pub fn get_balance_snapshot(&self, epoch: &u32) -> Result<SnapshotCommitment, Error> {
+        from_try(self.0.try_get_balance_snapshot(epoch))
+    }
+
+    pub fn get_batch_limits(&self) -> Result<BatchLimits, Error> {
+        from_try_host(self.0.try_get_batch_limits())
+    }
+
+    /// Next nonce on `channel`; channel 0 is `get_nonce`
+    pub fn get_channel_nonce(&self, channel: &u32) -> Result<u64, Error> {
+        from_try(self.0.try_get_channel_nonce(channel))
+    }
+
+    /// Sessions derived straight from `public_key`
+    pub fn get_child_sessions(&self, public_key: &BytesN<32>) -> Result<Vec<BytesN<32>>, Error> {
+        from_try_host(self.0.try_get_child_sessions(public_key))
+    }
+
+    pub fn get_collateral_allowance(&self, pool: &Address, asset: &Address) -> Result<i128, Error> {
+        from_try_host(self.0.try_get_collateral_allowance(pool, asset))
+    }
+
+    pub fn get_contact(&self, address: &Address) -> Result<Option<Contact>, Error> {
+        from_try_host(self.0.try_get_contact(address))
+    }
+
+    pub fn get_contract_listing(
+        &self,
+        contract: &Address,
+    ) -> Result<Option<ContractListing>, Error> {
+        from_try_host(self.0.try_get_contract_listing(contract))
+    }
+
+    /// Rule in force now for `from_token`
+    pub fn get_conversion_rule(
+        &self,
+        from_token: &Address,
+    ) -> Result<Option<ConversionRule>, Error> {
+        from_try_host(self.0.try_get_conversion_rule(from_token))
+    }
+
+    /// How much of `asset` was spent so far during the cooldown, without
+    /// an oracle
+    pub fn get_cooldown_asset_spent(&self, asset: &Address) -> Result<i128, Error> {
+        from_try_host(self.0.try_get_cooldown_asset_spent(asset))
+    }
+
+    /// Cents spent so far during the cooldown
+    pub fn get_cooldown_spent(&self) -> Result<i128, Error> {
+        from_try_host(self.0.try_get_cooldown_spent())
+    }
+
+    pub fn get_cosigners(&self, change: &BytesN<32>) -> Result<Vec<Signer>, Error> {
+        from_try_host(self.0.try_get_cosigners(change))
+    }
+
+    /// Get the deposit binding registered for an anchor
+    pub fn get_deposit_binding(
+        &self,
+        anchor_domain_hash: &BytesN<32>,
+    ) -> Result<DepositBinding, Error> {
+        from_try(self.0.try_get_deposit_binding(anchor_domain_hash))
+    }
+
+    /// When the queued removal of a device can be executed, if one is queued
+    pub fn get_device_removal(&self, public_key: &BytesN<32>) -> Result<Option<u64>, Error> {
+        from_try_host(self.0.try_get_device_removal(public_key))
+    }
+
+    /// Get the email hash
+    pub fn get_email_hash(&self) -> Result<BytesN<32>, Error> {
+        from_try(self.0.try_get_email_hash())
+    }
+
+    pub fn get_fee_config(&self) -> Result<Option<FeeConfig>, Error> {
+        from_try_host(self.0.try_get_fee_config())
+    }
+
+    /// Limit in force now
+    pub fn get_fiat_limit(&self) -> Result<Option<FiatLimit>, Error> {
+        from_try_host(self.0.try_get_fiat_limit())
+    }
+
+    /// Cents counted against the limit today
+    pub fn get_fiat_spent_today(&self) -> Result<i128, Error> {
+        from_try_host(self.0.try_get_fiat_spent_today())
+    }
+
+    pub fn get_freeze(&self) -> Result<Option<Freeze>, Error> {
+        from_try_host(self.0.try_get_freeze())
+    }
+
+    /// When a proposed guardian change can be made, if one is proposed
+    pub fn get_guardian_change(&self, change: &BytesN<32>) -> Result<Option<u64>, Error> {
+        from_try_host(self.0.try_get_guardian_change(change))
+    }
+
+    pub fn get_guardian_delay(&self) -> Result<u64, Error> {
+        from_try_host(self.0.try_get_guardian_delay())
+    }
+
+    /// When `guardian` was rotated in, while it can't rotate again
+    pub fn get_guardian_rotated_at(&self, guardian: &Signer) -> Result<Option<u64>, Error> {
+        from_try_host(self.0.try_get_guardian_rotated_at(guardian))
+    }
+
+    pub fn get_guardian_threshold(&self) -> Result<u32, Error> {
+        from_try_host(self.0.try_get_guardian_threshold())
+    }
+
+    pub fn get_guardians(&self) -> Result<Vec<Signer>, Error> {
+        from_try_host(self.0.try_get_guardians())
+    }
+
+    /// Threshold in force for `asset` now, 0 when there is none
+    pub fn get_large_transfer_threshold(&self, asset: &Address) -> Result<i128, Error> {
+        from_try_host(self.0.try_get_large_transfer_threshold(asset))
+    }
+
+    /// Get the most recently committed epoch
+    pub fn get_latest_receipts_epoch(&self) -> Result<u64, Error> {
+        from_try(self.0.try_get_latest_receipts_epoch())
+    }
+
+    pub fn get_latest_snapshot_epoch(&self) -> Result<u32, Error> {
+        from_try(self.0.try_get_latest_snapshot_epoch())
+    }
+
+    pub fn get_new_key_window(&self) -> Result<u64, Error> {
+        from_try_host(self.0.try_get_new_key_window())
+    }
+
+    /// Get the current nonce
+    pub fn get_nonce(&self) -> Result<u64, Error> {
+        from_try(self.0.try_get_nonce())
+    }
+
+    pub fn get_notification_filters(&self) -> Result<Map<Symbol, NotificationFilter>, Error> {
+        from_try_host(self.0.try_get_notification_filters())
+    }
+
+    /// Weights set with `set_op_weight`; other functions weigh
+    /// `DEFAULT_OP_WEIGHT`
+    pub fn get_op_weights(&self) -> Result<Map<Symbol, u32>, Error> {
+        from_try_host(self.0.try_get_op_weights())
+    }
+
+    /// Oracle in force now
+    pub fn get_oracle(&self) -> Result<Option<Address>, Error> {
+        from_try_host(self.0.try_get_oracle())
+    }
+
+    /// Get the current owner public key
+    pub fn get_owner(&self) -> Result<BytesN<32>, Error> {
+        from_try(self.0.try_get_owner())
+    }
+
+    /// Session `public_key` was derived from, if any
+    pub fn get_parent_session(&self, public_key: &BytesN<32>) -> Result<Option<BytesN<32>>, Error> {
+        from_try_host(self.0.try_get_parent_session(public_key))
+    }
+
+    pub fn get_passkey(&self) -> Result<Option<BytesN<65>>, Error> {
+        from_try_host(self.0.try_get_passkey())
+    }
+
+    pub fn get_pending_change(&self, change: &BytesN<32>) -> Result<Option<PendingChange>, Error> {
+        from_try_host(self.0.try_get_pending_change(change))
+    }
+
+    /// Loosening change to the rule for `from_token` waiting out the delay,
+    /// if any
+    pub fn get_pending_conversion_rule(
+        &self,
+        from_token: &Address,
+    ) -> Result<Option<ConversionRuleChange>, Error> {
+        from_try_host(self.0.try_get_pending_conversion_rule(from_token))
+    }
+
+    /// Loosening change waiting out the delay, if any
+    pub fn get_pending_fiat_limit(&self) -> Result<Option<FiatLimitChange>, Error> {
+        from_try_host(self.0.try_get_pending_fiat_limit())
+    }
+
+    /// Oracle swap waiting out the delay, if any
+    pub fn get_pending_oracle(&self) -> Result<Option<OracleChange>, Error> {
+        from_try_host(self.0.try_get_pending_oracle())
+    }
+
+    pub fn get_pending_rotation(&self) -> Result<Option<PendingRotation>, Error> {
+        from_try_host(self.0.try_get_pending_rotation())
+    }
+
+    pub fn get_pending_upgrade(&self) -> Result<Option<PendingUpgrade>, Error> {
+        from_try_host(self.0.try_get_pending_upgrade())
+    }
+
+    /// Expiry of the live approval for `payload_hash`, if any
+    pub fn get_preauth(&self, payload_hash: &BytesN<32>) -> Result<Option<u64>, Error> {
+        from_try_host(self.0.try_get_preauth(payload_hash))
+    }
+
+    pub fn get_quarantined(
+        &self,
+        signer_fingerprint: &BytesN<32>,
+    ) -> Result<Option<QuarantinedKey>, Error> {
+        from_try_host(self.0.try_get_quarantined(signer_fingerprint))
+    }
+
+    pub fn get_queued_transfer(&self, id: &u32) -> Result<Option<QueuedTransfer>, Error> {
+        from_try_host(self.0.try_get_queued_transfer(id))
+    }
+
+    /// Receipt of an accepted quote
+    pub fn get_quote_receipt(&self, quote_id: &BytesN<32>) -> Result<QuoteReceipt, Error> {
+        from_try(self.0.try_get_quote_receipt(quote_id))
+    }
+
+    /// The grant for `token_hash`, if it exists and hasn't expired
+    pub fn get_read_grant(&self, token_hash: &BytesN<32>) -> Result<Option<ReadGrant>, Error> {
+        from_try_host(self.0.try_get_read_grant(token_hash))
+    }
+
+    /// Get the receipts commitment for `epoch`
+    pub fn get_receipts_root(&self, epoch: &u64) -> Result<ReceiptsCommitment, Error> {
+        from_try(self.0.try_get_receipts_root(epoch))
+    }
+
+    /// Most recent operations, newest first. `limit` follows the shared
+    /// page size rules (0 for the default, capped at `MAX_PAGE_LIMIT`).
+    pub fn get_recent_ops(&self, limit: &u32) -> Result<Vec<OpSummary>, Error> {
+        from_try_host(self.0.try_get_recent_ops(limit))
+    }
+
+    pub fn get_recovery(&self) -> Result<Option<RecoveryRequest>, Error> {
+        from_try_host(self.0.try_get_recovery())
+    }
+
+    /// The cooldown in force, if any
+    pub fn get_recovery_cooldown(&self) -> Result<Option<RecoveryCooldown>, Error> {
+        from_try_host(self.0.try_get_recovery_cooldown())
+    }
+
+    /// Cooldown the next email recovery starts
+    pub fn get_recovery_cooldown_config(&self) -> Result<CooldownConfig, Error> {
+        from_try_host(self.0.try_get_recovery_cooldown_config())
+    }
+
+    pub fn get_registry(&self) -> Result<Option<Address>, Error> {
+        from_try_host(self.0.try_get_registry())
+    }
+
+    pub fn get_relayers(&self) -> Result<Vec<BytesN<32>>, Error> {
+        from_try_host(self.0.try_get_relayers())
+    }
+
+    pub fn get_reserve(&self) -> Result<Option<ReserveConfig>, Error> {
+        from_try_host(self.0.try_get_reserve())
+    }
+
+    pub fn get_rotation_delay(&self) -> Result<u64, Error> {
+        from_try_host(self.0.try_get_rotation_delay())
+    }
+
+    pub fn get_scheduled(&self, id: &u32) -> Result<Option<ScheduledOp>, Error> {
+        from_try_host(self.0.try_get_scheduled(id))
+    }
+
+    /// A live session: created, not revoked and not expired
+    pub fn get_session(&self, public_key: &BytesN<32>) -> Result<Session, Error> {
+        from_try(self.0.try_get_session(public_key))
+    }
+
+    /// Risk summary hash a live session was approved with
+    pub fn get_session_risk(&self, public_key: &BytesN<32>) -> Result<Option<BytesN<32>>, Error> {
+        from_try_host(self.0.try_get_session_risk(public_key))
+    }
+
+    pub fn get_shares(&self) -> Result<Vec<ShareHolder>, Error> {
+        from_try_host(self.0.try_get_shares())
+    }
+
+    pub fn get_signers(&self) -> Result<Map<Signer, u32>, Error> {
+        from_try_host(self.0.try_get_signers())
+    }
+
+    pub fn get_snapshot_assets(&self) -> Result<Vec<Address>, Error> {
+        from_try_host(self.0.try_get_snapshot_assets())
+    }
+
+    /// Get the sponsor and what the wallet owes it
+    pub fn get_sponsorship(&self) -> Result<Sponsorship, Error> {
+        from_try(self.0.try_get_sponsorship())
+    }
+
+    pub fn get_storage_version(&self) -> Result<u32, Error> {
+        from_try_host(self.0.try_get_storage_version())
+    }
+
+    pub fn get_subscription(&self, id: &u32) -> Result<Option<Subscription>, Error> {
+        from_try_host(self.0.try_get_subscription(id))
+    }
+
+    pub fn get_threshold(&self) -> Result<u32, Error> {
+        from_try_host(self.0.try_get_threshold())
+    }
+
+    /// Bypass in force, if any
+    pub fn get_velocity_bypass(&self) -> Result<Option<VelocityBypass>, Error> {
+        from_try_host(self.0.try_get_velocity_bypass())
+    }
+
+    pub fn get_velocity_limit(&self) -> Result<Option<VelocityLimit>, Error> {
+        from_try_host(self.0.try_get_velocity_limit())
+    }
+
+    /// Authorizations counted in the current window
+    pub fn get_velocity_usage(&self) -> Result<u32, Error> {
+        from_try_host(self.0.try_get_velocity_usage())
+    }
+
+    pub fn get_verifier(&self) -> Result<Option<Address>, Error> {
+        from_try_host(self.0.try_get_verifier())
+    }
+
+    /// Resolve a virtual id to its reference and label
+    pub fn get_virtual_id(&self, virtual_id: &u64) -> Result<VirtualAccount, Error> {
+        from_try(self.0.try_get_virtual_id(virtual_id))
+    }
+
+    /// Let `attestor` write `slot`, replacing any previous attestor
+    pub fn grant_attestor(
+        &self,
+        slot: &Symbol,
+        attestor: &Address,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_grant_attestor(slot, attestor, signature))
+    }
+
+    /// Grant read access to whoever holds the token hashing to `token_hash`
+    pub fn grant_read_access(
+        &self,
+        token_hash: &BytesN<32>,
+        expires_at: &u64,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_grant_read_access(token_hash, expires_at, signature),
+        )
+    }
+
+    /// Allow `ops` authorizations over the limit until the window ends,
+    /// replacing any bypass left. Owner-signed over `ops`, and co-signed by
+    /// another device through `cosign_action`.
+    pub fn grant_velocity_bypass(&self, ops: &u32, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_grant_velocity_bypass(ops, signature))
+    }
+
+    /// Replace the guardian key `old_pk` with `new_pk`. Signed by `old_pk`
+    /// over the hashed action message, with `new_pk` as the payload.
+    pub fn guardian_rotate(
+        &self,
+        old_pk: &Signer,
+        new_pk: &Signer,
+        signature_old: &SignerProof,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_guardian_rotate(old_pk, new_pk, signature_old))
+    }
+
+    /// Initialize the wallet contract
+    pub fn init(&self, owner: &BytesN<32>, email_hash: &BytesN<32>) -> Result<(), Error> {
+        from_try(self.0.try_init(owner, email_hash))
+    }
+
+    /// `init`, then apply `preset`
+    pub fn init_with_preset(
+        &self,
+        owner: &BytesN<32>,
+        email_hash: &BytesN<32>,
+        preset: &PolicyPreset,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_init_with_preset(owner, email_hash, preset))
+    }
+
+    /// Start recovering the wallet to `new_owner`. Signed by a guardian,
+    /// whose approval counts.
+    pub fn initiate_recovery(
+        &self,
+        new_owner: &BytesN<32>,
+        proof: &SignerProof,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_initiate_recovery(new_owner, proof))
+    }
+
+    /// Call `fn_name` on `contract` as the wallet and return its result, if
+    /// the policy allows it. A blocked call still succeeds, returning
+    /// `None`, so the `contract_blocked` event it publishes stays on chain.
+    pub fn invoke(
+        &self,
+        contract: &Address,
+        fn_name: &Symbol,
+        args: &Vec<Val>,
+        signature: &BytesN<64>,
+    ) -> Result<Option<Val>, Error> {
+        from_try(self.0.try_invoke(contract, fn_name, args, signature))
+    }
+
+    pub fn is_allowlist_mode(&self) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_allowlist_mode())
+    }
+
+    /// Whether outgoing transfers are restricted to contacts now
+    pub fn is_contacts_only(&self) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_contacts_only())
+    }
+
+    pub fn is_event_privacy_enabled(&self) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_event_privacy_enabled())
+    }
+
+    pub fn is_fragment_release_authorized(
+        &self,
+        new_device_key: &BytesN<32>,
+    ) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_fragment_release_authorized(new_device_key))
+    }
+
+    pub fn is_lending_pool(&self, pool: &Address) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_lending_pool(pool))
+    }
+
+    /// Whether an envelope that hasn't expired yet used `nonce`
+    pub fn is_meta_tx_used(&self, nonce: &u64) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_meta_tx_used(nonce))
+    }
+
+    /// Whether transfers to `address` are internal now
+    pub fn is_own_account(&self, address: &Address) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_own_account(address))
+    }
+
+    pub fn is_quote_signer(&self, signer: &BytesN<32>) -> Result<bool, Error> {
+        from_try_host(self.0.try_is_quote_signer(signer))
+    }
+
+    /// Supply, withdraw, repay or supply collateral on a whitelisted pool
+    pub fn lend(
+        &self,
+        pool: &Address,
+        action: &LendAction,
+        asset: &Address,
+        amount: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_lend(pool, action, asset, amount, signature))
+    }
+
+    /// End the post-recovery cooldown now. Signed by a guardian over the
+    /// hashed action message.
+    pub fn lift_recovery_cooldown(&self, proof: &SignerProof) -> Result<(), Error> {
+        from_try(self.0.try_lift_recovery_cooldown(proof))
+    }
+
+    pub fn list_contacts(&self) -> Result<Vec<Contact>, Error> {
+        from_try_host(self.0.try_list_contacts())
+    }
+
+    /// List deposit bindings, one page at a time
+    pub fn list_deposit_bindings(
+        &self,
+        cursor: &u32,
+        limit: &u32,
+    ) -> Result<(Vec<(BytesN<32>, DepositBinding)>, Option<u32>), Error> {
+        from_try_host(self.0.try_list_deposit_bindings(cursor, limit))
+    }
+
+    pub fn list_devices(&self) -> Result<Vec<Device>, Error> {
+        from_try_host(self.0.try_list_devices())
+    }
+
+    pub fn list_own_accounts(&self) -> Result<Vec<OwnAccount>, Error> {
+        from_try_host(self.0.try_list_own_accounts())
+    }
+
+    pub fn list_quarantined(&self) -> Result<Map<BytesN<32>, QuarantinedKey>, Error> {
+        from_try_host(self.0.try_list_quarantined())
+    }
+
+    /// List committed epochs in commit order, one page at a time
+    pub fn list_receipts_roots(
+        &self,
+        cursor: &u32,
+        limit: &u32,
+    ) -> Result<(Vec<(u64, ReceiptsCommitment)>, Option<u32>), Error> {
+        from_try_host(self.0.try_list_receipts_roots(cursor, limit))
+    }
+
+    pub fn list_subscriptions(&self) -> Result<Vec<Subscription>, Error> {
+        from_try_host(self.0.try_list_subscriptions())
+    }
+
+    /// List registered virtual ids, one page at a time
+    pub fn list_virtual_ids(
+        &self,
+        cursor: &u32,
+        limit: &u32,
+    ) -> Result<(Vec<(u64, VirtualAccount)>, Option<u32>), Error> {
+        from_try_host(self.0.try_list_virtual_ids(cursor, limit))
+    }
+
+    /// Bring storage up to `STORAGE_VERSION`, returning the version
+    pub fn migrate(&self) -> Result<u32, Error> {
+        from_try(self.0.try_migrate())
+    }
+
+    /// Pay `amount` of `token` into this wallet, attributed to `virtual_id`
+    pub fn pay_virtual(
+        &self,
+        from: &Address,
+        virtual_id: &u64,
+        token: &Address,
+        amount: &i128,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_pay_virtual(from, virtual_id, token, amount))
+    }
+
+    /// Crate version, current ledger and the number of nonce channels in
+    /// use besides the wallet nonce. Fails with `LimitExceeded` if it
+    /// already ran in this ledger.
+    pub fn ping(&self) -> Result<(String, u32, u32), Error> {
+        from_try(self.0.try_ping())
+    }
+
+    /// Approve the transaction whose auth payload is `payload_hash` until
+    /// `expiry`. Owner-signed over (payload_hash, expiry).
+    pub fn pre_authorize(
+        &self,
+        payload_hash: &BytesN<32>,
+        expiry: &u64,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_pre_authorize(payload_hash, expiry, signature))
+    }
+
+    /// Propose the high-risk change sha256(action || payload). Signed by
+    /// the owner.
+    pub fn propose_change(&self, change: &BytesN<32>, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_propose_change(change, signature))
+    }
+
+    /// Start the guardian delay for the change sha256(action || payload).
+    /// Owner-signed over the change id. Returns when it can be made.
+    pub fn propose_guardian_change(
+        &self,
+        change: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<u64, Error> {
+        from_try(self.0.try_propose_guardian_change(change, signature))
+    }
+
+    pub fn propose_owner_rotation(
+        &self,
+        new_owner: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_propose_owner_rotation(new_owner, signature))
+    }
+
+    /// Schedule an upgrade to the uploaded wasm `wasm_hash`
+    pub fn propose_upgrade(
+        &self,
+        wasm_hash: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_propose_upgrade(wasm_hash, signature))
+    }
+
+    /// Transfer `amount` of `asset` to `to` out of `spender`'s allowance.
+    /// Authorized by `spender`. Returns the allowance left.
+    pub fn pull(
+        &self,
+        spender: &Address,
+        asset: &Address,
+        to: &Address,
+        amount: &i128,
+    ) -> Result<i128, Error> {
+        from_try(self.0.try_pull(spender, asset, to, amount))
+    }
+
+    /// Unlink a device that can't confirm, after `DEVICE_REMOVAL_DELAY`.
+    /// Owner-signed over the public key. Returns when it can be executed.
+    pub fn queue_device_removal(
+        &self,
+        public_key: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<u64, Error> {
+        from_try(self.0.try_queue_device_removal(public_key, signature))
+    }
+
+    /// Queue a transfer of `amount` of `asset` to `to`, to run after the
+    /// delay. Owner-signed over (asset, to, amount). Returns its id.
+    pub fn queue_transfer(
+        &self,
+        asset: &Address,
+        to: &Address,
+        amount: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<u32, Error> {
+        from_try(self.0.try_queue_transfer(asset, to, amount, signature))
+    }
+
+    /// Record XLM the sponsor spent activating or maintaining this wallet
+    pub fn record_sponsorship(&self, sponsor: &Address, amount: &i128) -> Result<(), Error> {
+        from_try(self.0.try_record_sponsorship(sponsor, amount))
+    }
+
+    /// Rotate to `new_owner` with a proof of an email from the wallet's
+    /// address
+    pub fn recover_with_email_proof(
+        &self,
+        proof: &Bytes,
+        new_owner: &BytesN<32>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_recover_with_email_proof(proof, new_owner))
+    }
+
+    /// Register a virtual id for a payer/invoice reference and return it
+    pub fn register_virtual_id(
+        &self,
+        reference: &BytesN<32>,
+        label: &String,
+        signature: &BytesN<64>,
+    ) -> Result<u64, Error> {
+        from_try(self.0.try_register_virtual_id(reference, label, signature))
+    }
+
+    /// Let a quarantined key sign again. Owner-signed over the fingerprint.
+    pub fn release_quarantine(
+        &self,
+        signer_fingerprint: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_release_quarantine(signer_fingerprint, signature))
+    }
+
+    /// Delete a contact; transfers to it stop being allowed right away.
+    /// Owner-signed over the address.
+    pub fn remove_contact(&self, address: &Address, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_remove_contact(address, signature))
+    }
+
+    /// Remove the deposit binding for an anchor
+    pub fn remove_deposit_binding(
+        &self,
+        anchor_domain_hash: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_remove_deposit_binding(anchor_domain_hash, signature),
+        )
+    }
+
+    /// Unlink a device; its signatures stop being accepted right away.
+    /// `device_signature` is the device's own, over the
+    /// `confirm_device_removal` action message for the public key, at the
+    /// same nonce as the owner's.
+    pub fn remove_device(
+        &self,
+        public_key: &BytesN<32>,
+        device_signature: &BytesN<64>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_remove_device(public_key, device_signature, signature),
+        )
+    }
+
+    pub fn remove_guardian(&self, guardian: &Signer, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_remove_guardian(guardian, signature))
+    }
+
+    /// Unlink an own account; transfers to it count again right away.
+    /// Owner-signed over the address.
+    pub fn remove_own_account(
+        &self,
+        address: &Address,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_remove_own_account(address, signature))
+    }
+
+    /// Remove a signer. Fails if the rest can no longer reach the threshold.
+    pub fn remove_signer(&self, signer: &Signer, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_remove_signer(signer, signature))
+    }
+
+    /// Pay back the outstanding sponsorship in XLM. When `pay_token` isn't
+    /// `native_token`, exactly the outstanding XLM is bought through `router`,
+    /// spending at most `max_in` of `pay_token`.
+    pub fn repay_sponsor(
+        &self,
+        router: &Address,
+        native_token: &Address,
+        pay_token: &Address,
+        max_in: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_repay_sponsor(router, native_token, pay_token, max_in, signature),
+        )
+    }
+
+    /// Quarantine the key with `signer_fingerprint` and freeze the wallet.
+    /// Signed by the owner, a registered signer, the passkey or a linked
+    /// device, over the hashed action message. The owner key can only be
+    /// reported while guardian recovery is set up.
+    pub fn report_compromise(
+        &self,
+        signer_fingerprint: &BytesN<32>,
+        evidence_hash: &BytesN<32>,
+        proof: &SignerProof,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_report_compromise(signer_fingerprint, evidence_hash, proof),
+        )
+    }
+
+    /// Revoke every session created so far
+    pub fn revoke_all_sessions(&self, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_revoke_all_sessions(signature))
+    }
+
+    /// Withdraw `spender`'s allowance for `asset`. Owner-signed over
+    /// (spender, asset).
+    pub fn revoke_allowance(
+        &self,
+        spender: &Address,
+        asset: &Address,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_revoke_allowance(spender, asset, signature))
+    }
+
+    pub fn revoke_attestor(&self, slot: &Symbol, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_revoke_attestor(slot, signature))
+    }
+
+    /// Withdraw an unused approval. Owner-signed over the payload hash.
+    pub fn revoke_preauth(
+        &self,
+        payload_hash: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_revoke_preauth(payload_hash, signature))
+    }
+
+    pub fn revoke_read_access(
+        &self,
+        token_hash: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_revoke_read_access(token_hash, signature))
+    }
+
+    /// Revoke a session and every session derived from it
+    pub fn revoke_session(
+        &self,
+        public_key: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_revoke_session(public_key, signature))
+    }
+
+    /// Store `op` for anyone to execute after `execute_after_ledger`.
+    /// Owner-signed over (op, execute_after_ledger). Returns its id.
+    pub fn schedule(
+        &self,
+        op: &Invocation,
+        execute_after_ledger: &u32,
+        signature: &BytesN<64>,
+    ) -> Result<u32, Error> {
+        from_try(self.0.try_schedule(op, execute_after_ledger, signature))
+    }
+
+    /// Check storage and configuration, and which of `sessions` are dead
+    /// entries
+    pub fn self_check(&self, sessions: &Vec<BytesN<32>>) -> Result<HealthReport, Error> {
+        from_try(self.0.try_self_check(sessions))
+    }
+
+    /// Switch between allowlist mode and (the default) denylist mode
+    pub fn set_allowlist_mode(&self, enabled: &bool, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_allowlist_mode(enabled, signature))
+    }
+
+    /// Set or (with `None`) remove the display override for `asset`
+    pub fn set_asset_display(
+        &self,
+        asset: &Address,
+        display: &Option<AssetDisplay>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_asset_display(asset, display, signature))
+    }
+
+    /// Require `level` for the owner-signed `action`; `Single` drops the row.
+    /// Needs the highest level in the matrix.
+    pub fn set_auth_policy(
+        &self,
+        action: &Symbol,
+        level: &AuthLevel,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_auth_policy(action, level, signature))
+    }
+
+    /// Set the batch limits, each between 1 and its ceiling
+    pub fn set_batch_limits(
+        &self,
+        limits: &BatchLimits,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_batch_limits(limits, signature))
+    }
+
+    /// Turn contacts-only mode on, at once, or off, after
+    /// `CONTACT_COOLING_OFF`. Owner-signed over `enabled`.
+    pub fn set_contacts_only(&self, enabled: &bool, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_contacts_only(enabled, signature))
+    }
+
+    /// Allow, deny or (with `None`) unlist `contract`. An `invoke` the
+    /// policy blocks returns `None` and publishes `contract_blocked`;
+    /// batches and authorizations calling it fail with `Unauthorized`.
+    pub fn set_contract_listing(
+        &self,
+        contract: &Address,
+        listing: &Option<ContractListing>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_set_contract_listing(contract, listing, signature),
+        )
+    }
+
+    /// Set or (with `None`) remove the rule for `from_token`. Removing or
+    /// tightening applies at once, loosening after `LARGE_TRANSFER_DELAY`.
+    /// Owner-signed over (from_token, rule).
+    pub fn set_conversion_rule(
+        &self,
+        from_token: &Address,
+        rule: &Option<ConversionRule>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_conversion_rule(from_token, rule, signature))
+    }
+
+    /// Register (or replace) the deposit binding for an anchor
+    pub fn set_deposit_binding(
+        &self,
+        anchor_domain_hash: &BytesN<32>,
+        memo_hash: &BytesN<32>,
+        route: &Address,
+        label: &String,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_deposit_binding(
+            anchor_domain_hash,
+            memo_hash,
+            route,
+            label,
+            signature,
+        ))
+    }
+
+    /// Turn privacy mode on with `salt`, rotate the salt, or turn it off
+    /// with `None`
+    pub fn set_event_privacy(
+        &self,
+        salt: &Option<BytesN<32>>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_event_privacy(salt, signature))
+    }
+
+    /// Pay the sponsor's fees in `asset`, up to `max_fee` per batch or
+    /// authorization. A `max_fee` of 0 turns fee mode off.
+    pub fn set_fee_config(
+        &self,
+        asset: &Address,
+        max_fee: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_fee_config(asset, max_fee, signature))
+    }
+
+    /// Limit what the wallet spends per day to `daily_limit` cents of
+    /// `currency`; 0 removes the limit. Needs an oracle. Tightening applies
+    /// at once, loosening after `LARGE_TRANSFER_DELAY`. Owner-signed over
+    /// (currency, daily_limit, on_stale).
+    pub fn set_fiat_limit(
+        &self,
+        currency: &Symbol,
+        daily_limit: &i128,
+        on_stale: &StalePrice,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_set_fiat_limit(currency, daily_limit, on_stale, signature),
+        )
+    }
+
+    /// Set how long guardian changes wait once recovery is on, between
+    /// `RECOVERY_DELAY` and `MAX_GUARDIAN_DELAY`. Lowering it waits out the
+    /// current delay. Owner-signed over the delay.
+    pub fn set_guardian_delay(&self, delay: &u64, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_guardian_delay(delay, signature))
+    }
+
+    /// Require `threshold` guardian approvals to recover; 0 turns recovery off
+    pub fn set_guardian_threshold(
+        &self,
+        threshold: &u32,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_guardian_threshold(threshold, signature))
+    }
+
+    /// Hold transfers of more than `threshold` of `asset` for the delay; 0
+    /// removes the limit. Lowering applies at once, raising or removing
+    /// after `LARGE_TRANSFER_DELAY`. Owner-signed over (asset, threshold).
+    pub fn set_large_transfer_threshold(
+        &self,
+        asset: &Address,
+        threshold: &i128,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_set_large_transfer_threshold(asset, threshold, signature),
+        )
+    }
+
+    /// Allow or disallow a lending pool
+    pub fn set_lending_pool(
+        &self,
+        pool: &Address,
+        allowed: &bool,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_lending_pool(pool, allowed, signature))
+    }
+
+    /// Set how long a new owner key needs approval for high-risk changes;
+    /// 0 turns the check off
+    pub fn set_new_key_window(&self, window: &u64, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_new_key_window(window, signature))
+    }
+
+    /// Set or (with `None`) remove the filter for `channel`
+    pub fn set_notification_filter(
+        &self,
+        channel: &Symbol,
+        filter: &Option<NotificationFilter>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_set_notification_filter(channel, filter, signature),
+        )
+    }
+
+    /// Weigh calls to `fn_name` in the complexity score. 0 goes back to
+    /// the default weight.
+    pub fn set_op_weight(
+        &self,
+        fn_name: &Symbol,
+        weight: &u32,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_op_weight(fn_name, weight, signature))
+    }
+
+    /// Price fiat limits and conversions through `oracle`, an oracle
+    /// adapter. The first oracle applies at once, a swap after
+    /// `LARGE_TRANSFER_DELAY`. Owner-signed over the address.
+    pub fn set_oracle(&self, oracle: &Address, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_oracle(oracle, signature))
+    }
+
+    /// Register, replace or (with `None`) remove the owner's passkey
+    pub fn set_passkey(
+        &self,
+        public_key: &Option<BytesN<65>>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_passkey(public_key, signature))
+    }
+
+    /// Trust (or stop trusting) a quote signing key
+    pub fn set_quote_signer(
+        &self,
+        signer: &BytesN<32>,
+        trusted: &bool,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_quote_signer(signer, trusted, signature))
+    }
+
+    /// Set the address (normally the relayer) allowed to commit receipt roots
+    pub fn set_receipts_committer(
+        &self,
+        committer: &Address,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_receipts_committer(committer, signature))
+    }
+
+    /// Cap spending after an email recovery to `cap` cents for `duration`
+    /// seconds, or without an oracle to `asset_caps` of each asset. Refused
+    /// during a cooldown. Owner-signed over (duration, cap, asset_caps).
+    pub fn set_recovery_cooldown(
+        &self,
+        duration: &u64,
+        cap: &i128,
+        asset_caps: &Map<Address, i128>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_set_recovery_cooldown(duration, cap, asset_caps, signature),
+        )
+    }
+
+    /// Only accept authorizations co-signed by one of `relayers`; an empty
+    /// list accepts them from anyone again
+    pub fn set_relayers(
+        &self,
+        relayers: &Vec<BytesN<32>>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_relayers(relayers, signature))
+    }
+
+    /// Set or (with `None`) clear the reserve
+    pub fn set_reserve(
+        &self,
+        config: &Option<ReserveConfig>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_reserve(config, signature))
+    }
+
+    /// Require `delay` seconds between proposing and confirming a rotation
+    pub fn set_rotation_delay(&self, delay: &u64, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_rotation_delay(delay, signature))
+    }
+
+    /// Replace the share records. An empty list removes them.
+    pub fn set_shares(
+        &self,
+        holders: &Vec<ShareHolder>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_shares(holders, signature))
+    }
+
+    /// Set the assets balance snapshots cover
+    pub fn set_snapshot_assets(
+        &self,
+        assets: &Vec<Address>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_snapshot_assets(assets, signature))
+    }
+
+    /// Pause or resume a subscription. Owner-signed over (id, paused).
+    pub fn set_subscription_paused(
+        &self,
+        id: &u32,
+        paused: &bool,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_subscription_paused(id, paused, signature))
+    }
+
+    /// Set the weight `__check_auth` requires. 0 turns multi-signer mode
+    /// off and restores single owner / passkey signatures.
+    pub fn set_threshold(&self, threshold: &u32, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_set_threshold(threshold, signature))
+    }
+
+    /// Allow at most `max_ops` authorizations per `window` seconds; 0
+    /// removes the limit. Owner-signed over (max_ops, window).
+    pub fn set_velocity_limit(
+        &self,
+        max_ops: &u32,
+        window: &u64,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_velocity_limit(max_ops, window, signature))
+    }
+
+    /// Set or (with `None`) remove the zk-email verifier
+    pub fn set_verifier(
+        &self,
+        verifier: &Option<Address>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_verifier(verifier, signature))
+    }
+
+    /// Shares recorded for `holder` (0 if none)
+    pub fn share_balance(&self, holder: &Address) -> Result<u32, Error> {
+        from_try_host(self.0.try_share_balance(holder))
+    }
+
+    /// XLM missing to get back to the reserve, 0 when there is enough
+    pub fn top_up_needed(&self) -> Result<i128, Error> {
+        from_try_host(self.0.try_top_up_needed())
+    }
+
+    pub fn total_shares(&self) -> Result<u64, Error> {
+        from_try_host(self.0.try_total_shares())
+    }
+
+    /// Lift the freeze, once `UNFREEZE_DELAY` has passed since it was last
+    /// frozen
+    pub fn unfreeze(&self, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_unfreeze(signature))
+    }
+
+    /// Move the wallet to the email address hashing to `new_hash`, and its
+    /// registry entry with it when bound to one; the registry admin has to
+    /// authorize that move too
+    pub fn update_email_hash(
+        &self,
+        new_hash: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_update_email_hash(new_hash, signature))
+    }
+
+    /// Update the owner public key (key rotation)
+    pub fn update_owner(
+        &self,
+        new_owner: &BytesN<32>,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_update_owner(new_owner, signature))
+    }
+
+    pub fn update_signer_weight(
+        &self,
+        signer: &Signer,
+        weight: &u32,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_update_signer_weight(signer, weight, signature))
+    }
+
+    /// Schedule an upgrade to the code `factory` currently deploys, returning
+    /// its hash. Breaking versions need `acknowledge_breaking`. Owner-signed
+    /// over (factory, wasm_hash, acknowledge_breaking).
+    pub fn upgrade_to_latest(
+        &self,
+        factory: &Address,
+        acknowledge_breaking: &bool,
+        signature: &BytesN<64>,
+    ) -> Result<BytesN<32>, Error> {
+        from_try(
+            self.0
+                .try_upgrade_to_latest(factory, acknowledge_breaking, signature),
+        )
+    }
+
+    /// Check whether a deposit matches the binding registered for its anchor
+    pub fn verify_deposit(
+        &self,
+        anchor_domain_hash: &BytesN<32>,
+        memo_hash: &BytesN<32>,
+        route: &Address,
+    ) -> Result<bool, Error> {
+        from_try_host(
+            self.0
+                .try_verify_deposit(anchor_domain_hash, memo_hash, route),
+        )
+    }
+
+    /// Cancel the pending recovery. Signed by the current owner.
+    pub fn veto_recovery(&self, signature: &BytesN<64>) -> Result<(), Error> {
+        from_try(self.0.try_veto_recovery(signature))
+    }
+}
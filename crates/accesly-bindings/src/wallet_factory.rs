@@ -0,0 +1,261 @@
+// Generated by `cargo xtask bindings` from the walletFactory contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::{from_try, from_try_host};
+use soroban_sdk::{Address, Bytes, BytesN, Env, String, Vec};
+
+use walletFactory::{
+    AppInfo, Changelog, GuardianKey, LinkedWallet, Manifest, SignedManifest, VersionInfo,
+    WalletFactoryContractClient, WalletRole, WatchEntry,
+};
+
+pub type Error = acceslyinterface::Error<walletFactory::Error>;
+
+/// `WalletFactoryContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub WalletFactoryContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(WalletFactoryContractClient::new(env, address))
+    }
+
+    /// Add `guardian` to every wallet linked to `identity_commitment`, with
+    /// each wallet's owner signature for `add_guardian`, in the order of
+    /// `get_linked_wallets`
+    pub fn add_linked_guardian(
+        &self,
+        identity_commitment: &BytesN<32>,
+        guardian: &GuardianKey,
+        signatures: &Vec<BytesN<64>>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_add_linked_guardian(identity_commitment, guardian, signatures),
+        )
+    }
+
+    /// Add `account` to `wallet`'s watchlist. Authorized by the wallet.
+    pub fn add_watch(
+        &self,
+        wallet: &Address,
+        account: &Address,
+        label: &String,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_add_watch(wallet, account, label))
+    }
+
+    /// Deploy the wallet for `email_hash` at `salt` in `app`'s namespace,
+    /// with the registered code `wasm_hash`, and initialize it with
+    /// `owner`. Authorized by the app; counts against its quota. The wallet
+    /// isn't indexed by `email_hash`, here or in the registry.
+    pub fn deploy_for_app(
+        &self,
+        app: &Address,
+        salt: &BytesN<32>,
+        owner: &BytesN<32>,
+        email_hash: &BytesN<32>,
+        wasm_hash: &BytesN<32>,
+    ) -> Result<Address, Error> {
+        from_try(
+            self.0
+                .try_deploy_for_app(app, salt, owner, email_hash, wasm_hash),
+        )
+    }
+
+    /// Deploy the wallet for `email_hash`, initialize it with `owner` and
+    /// index it under `key`, sha256(pepper || email_hash) as the backend
+    /// computes it. Admin only.
+    pub fn deploy_wallet(
+        &self,
+        owner: &BytesN<32>,
+        email_hash: &BytesN<32>,
+        key: &BytesN<32>,
+    ) -> Result<Address, Error> {
+        from_try(self.0.try_deploy_wallet(owner, email_hash, key))
+    }
+
+    /// `deploy_wallet`, starting the wallet with the policy preset
+    /// `preset_id` (`acceslyinterface::presets::PRESET_*`)
+    pub fn deploy_with_preset(
+        &self,
+        owner: &BytesN<32>,
+        email_hash: &BytesN<32>,
+        key: &BytesN<32>,
+        preset_id: &u32,
+    ) -> Result<Address, Error> {
+        from_try(
+            self.0
+                .try_deploy_with_preset(owner, email_hash, key, preset_id),
+        )
+    }
+
+    pub fn get_app(&self, app: &Address) -> Result<Option<AppInfo>, Error> {
+        from_try_host(self.0.try_get_app(app))
+    }
+
+    /// Address the wallet `app` deploys with `salt` has, or will have
+    pub fn get_app_wallet_address(
+        &self,
+        app: &Address,
+        salt: &BytesN<32>,
+    ) -> Result<Address, Error> {
+        from_try_host(self.0.try_get_app_wallet_address(app, salt))
+    }
+
+    /// Identity commitment `wallet` is linked to, if any
+    pub fn get_identity(&self, wallet: &Address) -> Result<Option<BytesN<32>>, Error> {
+        from_try_host(self.0.try_get_identity(wallet))
+    }
+
+    /// Wallets linked to `identity_commitment`, oldest first
+    pub fn get_linked_wallets(
+        &self,
+        identity_commitment: &BytesN<32>,
+    ) -> Result<Vec<LinkedWallet>, Error> {
+        from_try_host(self.0.try_get_linked_wallets(identity_commitment))
+    }
+
+    /// Capability manifest of `wasm_hash`, with its signature
+    pub fn get_manifest(&self, wasm_hash: &BytesN<32>) -> Result<Option<SignedManifest>, Error> {
+        from_try_host(self.0.try_get_manifest(wasm_hash))
+    }
+
+    pub fn get_manifest_signer(&self) -> Result<Option<BytesN<32>>, Error> {
+        from_try_host(self.0.try_get_manifest_signer())
+    }
+
+    pub fn get_registry(&self) -> Result<Option<Address>, Error> {
+        from_try_host(self.0.try_get_registry())
+    }
+
+    /// Changelog `wasm_hash` was registered with, if it ever was
+    pub fn get_version_info(&self, wasm_hash: &BytesN<32>) -> Result<Option<VersionInfo>, Error> {
+        from_try_host(self.0.try_get_version_info(wasm_hash))
+    }
+
+    /// Address the wallet deployed under the lookup key `key` has, or will
+    /// have once deployed
+    pub fn get_wallet_address(&self, key: &BytesN<32>) -> Result<Address, Error> {
+        from_try_host(self.0.try_get_wallet_address(key))
+    }
+
+    pub fn get_wallet_wasm_hash(&self) -> Result<BytesN<32>, Error> {
+        from_try(self.0.try_get_wallet_wasm_hash())
+    }
+
+    /// Accounts `wallet` watches, oldest first
+    pub fn get_watchlist(&self, wallet: &Address) -> Result<Vec<WatchEntry>, Error> {
+        from_try_host(self.0.try_get_watchlist(wallet))
+    }
+
+    /// Record `wallet` under `key`, sha256(pepper || email_hash) as the
+    /// backend computes it, dropping any key it was indexed under before,
+    /// as after an email change. Admin only.
+    pub fn index_wallet(&self, key: &BytesN<32>, wallet: &Address) -> Result<(), Error> {
+        from_try(self.0.try_index_wallet(key, wallet))
+    }
+
+    /// Initialize with the admin and the wallet code to deploy
+    pub fn init(&self, admin: &Address, wallet_wasm_hash: &BytesN<32>) -> Result<(), Error> {
+        from_try(self.0.try_init(admin, wallet_wasm_hash))
+    }
+
+    /// Link `wallet` to `identity_commitment` as `role`. Authorized by the
+    /// wallet, and by the admin for an identity's first wallet or by the
+    /// wallet linked longest for any after it.
+    pub fn link_wallet(
+        &self,
+        identity_commitment: &BytesN<32>,
+        wallet: &Address,
+        role: &WalletRole,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_link_wallet(identity_commitment, wallet, role))
+    }
+
+    /// The wallet indexed for `email_hash` under `pepper`, if any. Meant to
+    /// be simulated, not submitted.
+    pub fn lookup_peppered(
+        &self,
+        pepper: &Bytes,
+        email_hash: &BytesN<32>,
+    ) -> Result<Option<Address>, Error> {
+        from_try(self.0.try_lookup_peppered(pepper, email_hash))
+    }
+
+    /// Crate version of the factory and current ledger. This is the factory's
+    /// own version, not that of the wallets it deploys.
+    pub fn ping(&self) -> Result<(String, u32), Error> {
+        from_try_host(self.0.try_ping())
+    }
+
+    /// Store `manifest` for the registered `wasm_hash`, replacing any
+    /// before it. Signed by the build key over `manifest_message`.
+    pub fn publish_manifest(
+        &self,
+        wasm_hash: &BytesN<32>,
+        manifest: &Manifest,
+        signature: &BytesN<64>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_publish_manifest(wasm_hash, manifest, signature))
+    }
+
+    /// Let `app` deploy up to `quota` wallets in total, counting those it
+    /// already deployed. Admin only.
+    pub fn register_app(&self, app: &Address, quota: &u32) -> Result<(), Error> {
+        from_try(self.0.try_register_app(app, quota))
+    }
+
+    /// Drop `account` from `wallet`'s watchlist. Authorized by the wallet.
+    pub fn remove_watch(&self, wallet: &Address, account: &Address) -> Result<(), Error> {
+        from_try(self.0.try_remove_watch(wallet, account))
+    }
+
+    /// Move every wallet linked to `identity_commitment` to the recovery
+    /// email hashing to `email_hash`, with each wallet's owner signature for
+    /// `update_email_hash`, in the order of `get_linked_wallets`. Wallets
+    /// bound to the registry move their entries there as they go.
+    pub fn set_linked_email(
+        &self,
+        identity_commitment: &BytesN<32>,
+        email_hash: &BytesN<32>,
+        signatures: &Vec<BytesN<64>>,
+    ) -> Result<(), Error> {
+        from_try(
+            self.0
+                .try_set_linked_email(identity_commitment, email_hash, signatures),
+        )
+    }
+
+    /// Check manifests against the ed25519 build key `signer`. Manifests
+    /// already published stay. Admin only.
+    pub fn set_manifest_signer(&self, signer: &BytesN<32>) -> Result<(), Error> {
+        from_try(self.0.try_set_manifest_signer(signer))
+    }
+
+    /// Check lookups against the pepper hashing to `pepper_hash`. Wallets
+    /// indexed under an older pepper need indexing again. Admin only.
+    pub fn set_pepper_hash(&self, pepper_hash: &BytesN<32>) -> Result<(), Error> {
+        from_try(self.0.try_set_pepper_hash(pepper_hash))
+    }
+
+    /// Record future deployments in `registry`
+    pub fn set_registry(&self, registry: &Address) -> Result<(), Error> {
+        from_try(self.0.try_set_registry(registry))
+    }
+
+    /// Deploy future wallets with new code, registered with what it changes.
+    /// Wallets already deployed keep theirs until they upgrade.
+    pub fn set_wallet_wasm_hash(
+        &self,
+        wasm_hash: &BytesN<32>,
+        changelog: &Changelog,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_set_wallet_wasm_hash(wasm_hash, changelog))
+    }
+
+    /// Unlink `wallet` from its identity. Authorized by the wallet.
+    pub fn unlink_wallet(&self, wallet: &Address) -> Result<(), Error> {
+        from_try(self.0.try_unlink_wallet(wallet))
+    }
+}
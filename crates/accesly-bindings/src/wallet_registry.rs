@@ -0,0 +1,74 @@
+// Generated by `cargo xtask bindings` from the walletRegistry contract spec.
+// Do not edit; rerun the task after changing the contract.
+
+use acceslyinterface::{from_try, from_try_host};
+use soroban_sdk::{Address, BytesN, Env, String};
+
+use walletRegistry::WalletRegistryContractClient;
+
+pub type Error = acceslyinterface::Error<walletRegistry::Error>;
+
+/// `WalletRegistryContractClient` returning `Result<_, Error>`
+pub struct Client<'a>(pub WalletRegistryContractClient<'a>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+impl Client<'_> {
+    pub fn new(env: &Env, address: &Address) -> Self {
+        Self(WalletRegistryContractClient::new(env, address))
+    }
+
+    pub fn get_factory(&self) -> Result<Address, Error> {
+        from_try(self.0.try_get_factory())
+    }
+
+    pub fn init(&self, admin: &Address, factory: &Address) -> Result<(), Error> {
+        from_try(self.0.try_init(admin, factory))
+    }
+
+    pub fn lookup(&self, email_hash: &BytesN<32>) -> Result<Address, Error> {
+        from_try(self.0.try_lookup(email_hash))
+    }
+
+    pub fn lookup_by_owner(&self, owner: &BytesN<32>) -> Result<Address, Error> {
+        from_try(self.0.try_lookup_by_owner(owner))
+    }
+
+    /// Crate version and current ledger, read by the CLI health check
+    pub fn ping(&self) -> Result<(String, u32), Error> {
+        from_try_host(self.0.try_ping())
+    }
+
+    /// Record a freshly deployed wallet. Factory only.
+    pub fn register(
+        &self,
+        email_hash: &BytesN<32>,
+        owner: &BytesN<32>,
+        wallet: &Address,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_register(email_hash, owner, wallet))
+    }
+
+    /// Hand registration over to a new factory
+    pub fn set_factory(&self, factory: &Address) -> Result<(), Error> {
+        from_try(self.0.try_set_factory(factory))
+    }
+
+    /// Move `wallet`'s entry from `email_hash` to `new_email_hash`.
+    /// Authorized by the wallet and by the admin, vouching that the new
+    /// address is the user's. A wallet not listed under `email_hash` has
+    /// nothing to move.
+    pub fn sync_email(
+        &self,
+        wallet: &Address,
+        email_hash: &BytesN<32>,
+        new_email_hash: &BytesN<32>,
+    ) -> Result<(), Error> {
+        from_try(self.0.try_sync_email(wallet, email_hash, new_email_hash))
+    }
+
+    /// Re-index the wallet for `email_hash` under the owner it has now.
+    /// Returns the current owner.
+    pub fn sync_owner(&self, email_hash: &BytesN<32>) -> Result<BytesN<32>, Error> {
+        from_try(self.0.try_sync_owner(email_hash))
+    }
+}
@@ -0,0 +1,764 @@
+// ---------------------------------------------------------------------------
+// `accesly-cli audit replay <wallet>`
+//
+// Periodic evidence that a wallet's replay protections hold in production.
+// For a ledger range it pulls the wallet's nonce events (`auth_success`,
+// `key_rotated`, `dev_set_nonce`) and every auth entry the wallet was asked
+// to sign, then looks for:
+//
+//   - nonces consumed twice, going backwards, or skipped on a channel other
+//     than 0 (channel 0 is shared with owner actions, which emit no nonce)
+//   - auth entries submitted more than once: the same signature payload, or
+//     the same credential nonce with a different payload
+//   - rejected ed25519 signatures that turn out to be valid for a nonce the
+//     wallet had already consumed, or for another network's payload
+//
+// Anything accepted twice is a violation and makes the command exit non-zero;
+// rejected replays are reported as attempts. Signatures are matched against
+// the keys they carry (sessions, devices) or the owner keys the range's events
+// name, over the last `NONCE_WINDOW` nonces of the channel; WebAuthn and
+// multisig proofs are not re-verified.
+// ---------------------------------------------------------------------------
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use accesly_client::recovery_kit::hex;
+use acceslyinterface::events;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Args, Subcommand};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use soroban_sdk::{
+    xdr::{
+        Hash, HashIdPreimage, HashIdPreimageSorobanAuthorization, Limits, ReadXdr, ScAddress,
+        ScMap, ScVal, SorobanAuthorizedInvocation, SorobanCredentials, WriteXdr,
+    },
+    Address, Env,
+};
+
+use crate::replay::{extract_invocations, Invocation};
+use crate::rpc::{RpcClient, RpcEvent};
+
+/// How many nonces below a channel's latest a rejected signature is tried
+/// against
+pub const NONCE_WINDOW: u64 = 1024;
+
+/// Networks a rejected signature is checked against besides the audited one
+pub const KNOWN_NETWORKS: [(&str, &str); 4] = [
+    ("mainnet", "Public Global Stellar Network ; September 2015"),
+    ("testnet", "Test SDF Network ; September 2015"),
+    ("futurenet", "Test SDF Future Network ; October 2022"),
+    ("standalone", "Standalone Network ; February 2017"),
+];
+
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    #[command(subcommand)]
+    pub command: AuditCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuditCommand {
+    /// Scan a wallet's history for nonce anomalies and replayed signatures
+    Replay(ReplayAuditArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayAuditArgs {
+    /// Wallet contract address (C...)
+    #[arg(value_parser = crate::contract_address)]
+    pub wallet: String,
+    /// Soroban RPC endpoint used to fetch transactions and events
+    #[arg(long, env = "ACCESLY_RPC_URL")]
+    pub rpc_url: String,
+    /// Passphrase of the network the wallet lives on
+    #[arg(long, env = "ACCESLY_NETWORK_PASSPHRASE")]
+    pub network_passphrase: String,
+    /// First ledger of the range (inclusive)
+    #[arg(long)]
+    pub start_ledger: u32,
+    /// Last ledger of the range (inclusive), the latest ledger by default
+    #[arg(long)]
+    pub end_ledger: Option<u32>,
+    /// Also write the report as JSON to this file
+    #[arg(long)]
+    pub json: Option<PathBuf>,
+}
+
+// ============================================================================
+// Inputs
+// ============================================================================
+
+/// An ed25519 signature from an auth entry, with the nonce channel and
+/// expiry it was wrapped in and the key it names, if it names one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ed25519Proof {
+    pub key: Option<[u8; 32]>,
+    pub signature: [u8; 64],
+    pub channel: u32,
+    pub valid_until_ledger: Option<u32>,
+}
+
+/// One auth entry for the wallet, as submitted in a transaction
+#[derive(Clone, Debug)]
+pub struct AuthUse {
+    pub tx_hash: String,
+    pub ledger: u32,
+    /// Whether the transaction succeeded, i.e. `__check_auth` accepted it
+    pub accepted: bool,
+    pub credential_nonce: i64,
+    pub expiration_ledger: u32,
+    pub invocation: SorobanAuthorizedInvocation,
+    pub proof: Option<Ed25519Proof>,
+}
+
+impl AuthUse {
+    /// The `signature_payload` this entry hashes to on `network_id`
+    pub fn payload(&self, network_id: &[u8; 32]) -> [u8; 32] {
+        let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+            network_id: Hash(*network_id),
+            nonce: self.credential_nonce,
+            signature_expiration_ledger: self.expiration_ledger,
+            invocation: self.invocation.clone(),
+        });
+        Sha256::digest(preimage.to_xdr(Limits::none()).unwrap()).into()
+    }
+}
+
+/// A wallet event that moves a nonce
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NonceEvent {
+    /// `__check_auth` consumed `nonce` on `channel`
+    Consumed {
+        owner: [u8; 32],
+        channel: u32,
+        nonce: u64,
+    },
+    /// `update_owner` consumed the wallet nonce
+    Rotated {
+        old_owner: [u8; 32],
+        new_owner: [u8; 32],
+        nonce: u64,
+    },
+    /// `dev_set_nonce` forced the wallet nonce
+    Forced { nonce: u64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct NonceRecord {
+    pub tx_hash: String,
+    pub ledger: u32,
+    pub event: NonceEvent,
+}
+
+/// Network id for a passphrase
+pub fn network_id(passphrase: &str) -> [u8; 32] {
+    Sha256::digest(passphrase.as_bytes()).into()
+}
+
+/// The wallet's auth entries in an invocation
+pub fn auth_uses(invocation: &Invocation, wallet: &ScAddress) -> Vec<AuthUse> {
+    invocation
+        .auth
+        .iter()
+        .filter_map(|entry| match &entry.credentials {
+            SorobanCredentials::Address(credentials) if &credentials.address == wallet => {
+                Some(AuthUse {
+                    tx_hash: invocation.tx_hash.clone(),
+                    ledger: invocation.ledger,
+                    accepted: invocation.succeeded_on_chain,
+                    credential_nonce: credentials.nonce,
+                    expiration_ledger: credentials.signature_expiration_ledger,
+                    invocation: entry.root_invocation.clone(),
+                    proof: ed25519_proof(&credentials.signature, 0, None),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Decode the ed25519 signature out of an `AuthSignature`, unwrapping
+/// `Channel`, `Expiring` and `Relayed`
+pub fn ed25519_proof(
+    signature: &ScVal,
+    channel: u32,
+    valid_until_ledger: Option<u32>,
+) -> Option<Ed25519Proof> {
+    let ScVal::Vec(Some(parts)) = signature else {
+        return None;
+    };
+    let (ScVal::Symbol(variant), fields) = parts.split_first()? else {
+        return None;
+    };
+    let proof = |key, signature: &ScVal| {
+        Some(Ed25519Proof {
+            key,
+            signature: bytes(signature)?,
+            channel,
+            valid_until_ledger,
+        })
+    };
+
+    match (variant.to_utf8_string_lossy().as_str(), fields) {
+        ("Ed25519", [signature]) => proof(None, signature),
+        ("Session" | "Device", [key, signature]) => proof(Some(bytes(key)?), signature),
+        ("Channel", [ScVal::U32(channel), inner]) => ed25519_proof(inner, *channel, None),
+        ("Expiring", [ScVal::Map(Some(expiring))]) => {
+            let ScVal::U32(channel) = field(expiring, "channel")? else {
+                return None;
+            };
+            let ScVal::U32(valid_until) = field(expiring, "valid_until_ledger")? else {
+                return None;
+            };
+            ed25519_proof(field(expiring, "signature")?, *channel, Some(*valid_until))
+        }
+        ("Relayed", [ScVal::Map(Some(relayed))]) => {
+            let ScVal::U32(channel) = field(relayed, "channel")? else {
+                return None;
+            };
+            let valid_until = match field(relayed, "valid_until_ledger")? {
+                ScVal::U32(ledger) => Some(*ledger),
+                ScVal::Void => None,
+                _ => return None,
+            };
+            ed25519_proof(field(relayed, "signature")?, *channel, valid_until)
+        }
+        _ => None,
+    }
+}
+
+/// The nonce an event records, if it is one of the wallet's nonce events.
+/// Events of calls that failed and were rolled back are skipped.
+pub fn nonce_record(event: &RpcEvent) -> Result<Option<NonceRecord>, String> {
+    if !event.in_successful_contract_call {
+        return Ok(None);
+    }
+    let Some(topic) = event.topic.first() else {
+        return Ok(None);
+    };
+    let ScVal::Symbol(name) = decode(topic)? else {
+        return Ok(None);
+    };
+    let name = name.to_utf8_string_lossy();
+    let value = decode(&event.value)?;
+    let malformed = || format!("malformed {name} event in {}", event.tx_hash);
+
+    let nonce_event = match (name.as_str(), &value) {
+        (events::AUTH_SUCCESS, ScVal::Map(Some(data))) => NonceEvent::Consumed {
+            owner: field(data, "owner").and_then(bytes).ok_or_else(malformed)?,
+            channel: match field(data, "channel") {
+                Some(ScVal::U32(channel)) => *channel,
+                _ => return Err(malformed()),
+            },
+            nonce: u64_field(data, "nonce").ok_or_else(malformed)?,
+        },
+        (events::KEY_ROTATED, ScVal::Map(Some(data))) => NonceEvent::Rotated {
+            old_owner: field(data, "old_owner")
+                .and_then(bytes)
+                .ok_or_else(malformed)?,
+            new_owner: field(data, "new_owner")
+                .and_then(bytes)
+                .ok_or_else(malformed)?,
+            nonce: u64_field(data, "nonce").ok_or_else(malformed)?,
+        },
+        (events::DEV_SET_NONCE, ScVal::U64(nonce)) => NonceEvent::Forced { nonce: *nonce },
+        (events::AUTH_SUCCESS | events::KEY_ROTATED | events::DEV_SET_NONCE, _) => {
+            return Err(malformed())
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(NonceRecord {
+        tx_hash: event.tx_hash.clone(),
+        ledger: event.ledger,
+        event: nonce_event,
+    }))
+}
+
+fn decode(xdr: &str) -> Result<ScVal, String> {
+    let raw = STANDARD
+        .decode(xdr)
+        .map_err(|e| format!("event is not base64: {e}"))?;
+    ScVal::from_xdr(raw, Limits::none()).map_err(|e| format!("event is not valid XDR: {e}"))
+}
+
+fn field<'a>(map: &'a ScMap, key: &str) -> Option<&'a ScVal> {
+    map.iter().find_map(|entry| match &entry.key {
+        ScVal::Symbol(name) if name.to_utf8_string_lossy() == key => Some(&entry.val),
+        _ => None,
+    })
+}
+
+fn u64_field(map: &ScMap, key: &str) -> Option<u64> {
+    match field(map, key)? {
+        ScVal::U64(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn bytes<const N: usize>(value: &ScVal) -> Option<[u8; N]> {
+    match value {
+        ScVal::Bytes(bytes) => bytes.0.as_slice().try_into().ok(),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Findings
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Something was accepted twice: replay protection did not hold
+    Violation,
+    /// A replay was submitted and rejected
+    Attempt,
+    /// Not a replay, but outside normal operation
+    Notice,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// A nonce consumed by more than one transaction
+    NonceReused {
+        channel: u32,
+        nonce: u64,
+        tx_hashes: Vec<String>,
+    },
+    /// A nonce below the one the channel had reached
+    NonceRegressed {
+        channel: u32,
+        expected: u64,
+        nonce: u64,
+        tx_hash: String,
+    },
+    /// Nonces skipped on a channel only `__check_auth` advances
+    NonceGap {
+        channel: u32,
+        expected: u64,
+        nonce: u64,
+        tx_hash: String,
+    },
+    /// `dev_set_nonce` ran
+    NonceForced { nonce: u64, tx_hash: String },
+    /// One signature payload submitted in several transactions
+    DuplicatePayload {
+        payload: String,
+        tx_hashes: Vec<String>,
+        accepted: usize,
+    },
+    /// One credential nonce used for different payloads
+    CredentialNonceReused {
+        credential_nonce: i64,
+        tx_hashes: Vec<String>,
+        accepted: usize,
+    },
+    /// A rejected signature valid for a nonce already consumed
+    StaleSignature {
+        tx_hash: String,
+        channel: u32,
+        nonce: u64,
+        consumed_in: String,
+    },
+    /// A rejected signature valid for the payload on another network
+    CrossNetworkSignature { tx_hash: String, network: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub anomaly: Anomaly,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Violation => "violation",
+            Severity::Attempt => "attempt",
+            Severity::Notice => "notice",
+        };
+        write!(f, "[{severity}] ")?;
+        match &self.anomaly {
+            Anomaly::NonceReused { channel, nonce, tx_hashes } => write!(
+                f,
+                "nonce {nonce} on channel {channel} consumed by {}",
+                tx_hashes.join(", ")
+            ),
+            Anomaly::NonceRegressed { channel, expected, nonce, tx_hash } => write!(
+                f,
+                "nonce {nonce} on channel {channel} consumed after {expected} was reached, in {tx_hash}"
+            ),
+            Anomaly::NonceGap { channel, expected, nonce, tx_hash } => write!(
+                f,
+                "channel {channel} jumped from {expected} to {nonce} in {tx_hash}"
+            ),
+            Anomaly::NonceForced { nonce, tx_hash } => {
+                write!(f, "dev_set_nonce set the nonce to {nonce} in {tx_hash}")
+            }
+            Anomaly::DuplicatePayload { payload, tx_hashes, accepted } => write!(
+                f,
+                "payload {payload} submitted in {} ({accepted} accepted)",
+                tx_hashes.join(", ")
+            ),
+            Anomaly::CredentialNonceReused { credential_nonce, tx_hashes, accepted } => write!(
+                f,
+                "credential nonce {credential_nonce} reused in {} ({accepted} accepted)",
+                tx_hashes.join(", ")
+            ),
+            Anomaly::StaleSignature { tx_hash, channel, nonce, consumed_in } => write!(
+                f,
+                "{tx_hash} replayed a signature for nonce {nonce} on channel {channel}, consumed in {consumed_in}"
+            ),
+            Anomaly::CrossNetworkSignature { tx_hash, network } => {
+                write!(f, "{tx_hash} carried a signature made for {network}")
+            }
+        }
+    }
+}
+
+/// Run every check over a range's auth entries and nonce events, both in
+/// chain order. `others` are the networks a rejected signature is also
+/// tried against.
+pub fn analyze(
+    uses: &[AuthUse],
+    records: &[NonceRecord],
+    network_id: &[u8; 32],
+    others: &[(&str, [u8; 32])],
+) -> Vec<Finding> {
+    let mut findings = nonce_findings(records);
+    findings.extend(payload_findings(uses, network_id));
+    findings.extend(signature_findings(uses, records, network_id, others));
+    findings.sort_by_key(|finding| finding.severity);
+    findings
+}
+
+fn nonce_findings(records: &[NonceRecord]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut next: BTreeMap<u32, u64> = BTreeMap::new();
+    let mut consumed: BTreeMap<(u32, u64), Vec<String>> = BTreeMap::new();
+
+    for record in records {
+        let (channel, nonce) = match record.event {
+            NonceEvent::Consumed { channel, nonce, .. } => (channel, nonce),
+            NonceEvent::Rotated { nonce, .. } => (0, nonce),
+            NonceEvent::Forced { nonce } => {
+                findings.push(Finding {
+                    severity: Severity::Notice,
+                    anomaly: Anomaly::NonceForced {
+                        nonce,
+                        tx_hash: record.tx_hash.clone(),
+                    },
+                });
+                next.insert(0, nonce);
+                continue;
+            }
+        };
+        consumed
+            .entry((channel, nonce))
+            .or_default()
+            .push(record.tx_hash.clone());
+
+        if let Some(&expected) = next.get(&channel) {
+            let anomaly = if nonce < expected {
+                Some((
+                    Severity::Violation,
+                    Anomaly::NonceRegressed {
+                        channel,
+                        expected,
+                        nonce,
+                        tx_hash: record.tx_hash.clone(),
+                    },
+                ))
+            } else if nonce > expected && channel != 0 {
+                Some((
+                    Severity::Notice,
+                    Anomaly::NonceGap {
+                        channel,
+                        expected,
+                        nonce,
+                        tx_hash: record.tx_hash.clone(),
+                    },
+                ))
+            } else {
+                None
+            };
+            if let Some((severity, anomaly)) = anomaly {
+                findings.push(Finding { severity, anomaly });
+            }
+        }
+        next.insert(channel, nonce + 1);
+    }
+
+    for ((channel, nonce), tx_hashes) in consumed {
+        if tx_hashes.len() > 1 {
+            findings.push(Finding {
+                severity: Severity::Violation,
+                anomaly: Anomaly::NonceReused {
+                    channel,
+                    nonce,
+                    tx_hashes,
+                },
+            });
+        }
+    }
+    findings
+}
+
+fn payload_findings(uses: &[AuthUse], network_id: &[u8; 32]) -> Vec<Finding> {
+    let mut by_payload: BTreeMap<[u8; 32], Vec<&AuthUse>> = BTreeMap::new();
+    let mut by_nonce: BTreeMap<i64, Vec<(&AuthUse, [u8; 32])>> = BTreeMap::new();
+    for auth in uses {
+        let payload = auth.payload(network_id);
+        by_payload.entry(payload).or_default().push(auth);
+        by_nonce
+            .entry(auth.credential_nonce)
+            .or_default()
+            .push((auth, payload));
+    }
+
+    // Twice accepted is a violation, anything after the first rejected an attempt
+    let severity = |accepted: usize| {
+        if accepted > 1 {
+            Severity::Violation
+        } else {
+            Severity::Attempt
+        }
+    };
+
+    let mut findings = Vec::new();
+    for (payload, group) in by_payload {
+        if group.len() < 2 {
+            continue;
+        }
+        let accepted = group.iter().filter(|auth| auth.accepted).count();
+        findings.push(Finding {
+            severity: severity(accepted),
+            anomaly: Anomaly::DuplicatePayload {
+                payload: hex(&payload),
+                tx_hashes: group.iter().map(|auth| auth.tx_hash.clone()).collect(),
+                accepted,
+            },
+        });
+    }
+    // Same payload twice is already reported above
+    for (credential_nonce, group) in by_nonce {
+        let payloads: BTreeSet<_> = group.iter().map(|(_, payload)| payload).collect();
+        if payloads.len() < 2 {
+            continue;
+        }
+        let accepted = group.iter().filter(|(auth, _)| auth.accepted).count();
+        findings.push(Finding {
+            severity: severity(accepted),
+            anomaly: Anomaly::CredentialNonceReused {
+                credential_nonce,
+                tx_hashes: group.iter().map(|(auth, _)| auth.tx_hash.clone()).collect(),
+                accepted,
+            },
+        });
+    }
+    findings
+}
+
+fn signature_findings(
+    uses: &[AuthUse],
+    records: &[NonceRecord],
+    network_id: &[u8; 32],
+    others: &[(&str, [u8; 32])],
+) -> Vec<Finding> {
+    let mut owners = BTreeSet::new();
+    let mut consumed: BTreeMap<(u32, u64), (u32, &str)> = BTreeMap::new();
+    let mut latest: BTreeMap<u32, u64> = BTreeMap::new();
+    for record in records {
+        let (channel, nonce) = match record.event {
+            NonceEvent::Consumed {
+                owner,
+                channel,
+                nonce,
+            } => {
+                owners.insert(owner);
+                (channel, nonce)
+            }
+            NonceEvent::Rotated {
+                old_owner,
+                new_owner,
+                nonce,
+            } => {
+                owners.insert(old_owner);
+                owners.insert(new_owner);
+                (0, nonce)
+            }
+            NonceEvent::Forced { .. } => continue,
+        };
+        consumed
+            .entry((channel, nonce))
+            .or_insert((record.ledger, &record.tx_hash));
+        let highest = latest.entry(channel).or_insert(nonce);
+        *highest = (*highest).max(nonce);
+    }
+
+    let mut findings = Vec::new();
+    // Accepted entries were verified by the wallet itself
+    for auth in uses.iter().filter(|auth| !auth.accepted) {
+        let Some(proof) = &auth.proof else {
+            continue;
+        };
+        let Some(&highest) = latest.get(&proof.channel) else {
+            continue;
+        };
+        let keys: Vec<VerifyingKey> = match proof.key {
+            Some(key) => vec![key],
+            None => owners.iter().copied().collect(),
+        }
+        .iter()
+        .filter_map(|key| VerifyingKey::from_bytes(key).ok())
+        .collect();
+        let signature = Signature::from_bytes(&proof.signature);
+
+        // Newest nonces first: a replay is most likely of a recent signature
+        let lowest = highest.saturating_sub(NONCE_WINDOW);
+        let signed_nonce = |network_id: &[u8; 32]| {
+            let payload = auth.payload(network_id);
+            (lowest..=highest + 1).rev().find(|&nonce| {
+                let mut message = Vec::new();
+                accesly_messages::auth_message(
+                    &mut message,
+                    &payload,
+                    proof.channel,
+                    nonce,
+                    proof.valid_until_ledger,
+                );
+                keys.iter()
+                    .any(|key| key.verify_strict(&message, &signature).is_ok())
+            })
+        };
+
+        if let Some(nonce) = signed_nonce(network_id) {
+            if let Some((ledger, tx_hash)) = consumed.get(&(proof.channel, nonce)) {
+                if *ledger <= auth.ledger {
+                    findings.push(Finding {
+                        severity: Severity::Attempt,
+                        anomaly: Anomaly::StaleSignature {
+                            tx_hash: auth.tx_hash.clone(),
+                            channel: proof.channel,
+                            nonce,
+                            consumed_in: tx_hash.to_string(),
+                        },
+                    });
+                }
+            }
+            continue;
+        }
+        if let Some((network, _)) = others.iter().find(|(_, id)| signed_nonce(id).is_some()) {
+            findings.push(Finding {
+                severity: Severity::Attempt,
+                anomaly: Anomaly::CrossNetworkSignature {
+                    tx_hash: auth.tx_hash.clone(),
+                    network: network.to_string(),
+                },
+            });
+        }
+    }
+    findings
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct AuditReport {
+    pub wallet: String,
+    pub network_passphrase: String,
+    pub start_ledger: u32,
+    pub end_ledger: u32,
+    pub transactions_scanned: usize,
+    pub auth_entries: usize,
+    pub nonce_events: usize,
+    /// No violations in the range
+    pub passed: bool,
+    pub findings: Vec<Finding>,
+}
+
+pub fn run(args: AuditArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        AuditCommand::Replay(args) => run_replay(args),
+    }
+}
+
+fn run_replay(args: ReplayAuditArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let rpc = RpcClient::new(&args.rpc_url);
+    let end_ledger = match args.end_ledger {
+        Some(ledger) => ledger,
+        None => rpc.latest_ledger()?,
+    };
+    if end_ledger < args.start_ledger {
+        return Err("end-ledger must not be before start-ledger".into());
+    }
+
+    let env = Env::default();
+    let wallet = ScAddress::from(&Address::from_str(&env, &args.wallet));
+    let network = network_id(&args.network_passphrase);
+    let others: Vec<(&str, [u8; 32])> = KNOWN_NETWORKS
+        .iter()
+        .filter(|(_, passphrase)| *passphrase != args.network_passphrase)
+        .map(|(name, passphrase)| (*name, network_id(passphrase)))
+        .collect();
+
+    let transactions = rpc.get_transactions(args.start_ledger, end_ledger)?;
+    let mut uses = Vec::new();
+    for tx in &transactions {
+        match extract_invocations(tx, &wallet) {
+            Ok(found) => uses.extend(found.iter().flat_map(|call| auth_uses(call, &wallet))),
+            Err(e) => eprintln!("skipping {}: {e}", tx.tx_hash),
+        }
+    }
+
+    let mut records = Vec::new();
+    for event in rpc.get_events(&args.wallet, args.start_ledger, end_ledger)? {
+        match nonce_record(&event) {
+            Ok(Some(record)) => records.push(record),
+            Ok(None) => {}
+            Err(e) => eprintln!("skipping event: {e}"),
+        }
+    }
+
+    let findings = analyze(&uses, &records, &network, &others);
+    let report = AuditReport {
+        wallet: args.wallet,
+        network_passphrase: args.network_passphrase,
+        start_ledger: args.start_ledger,
+        end_ledger,
+        transactions_scanned: transactions.len(),
+        auth_entries: uses.len(),
+        nonce_events: records.len(),
+        passed: !findings.iter().any(|f| f.severity == Severity::Violation),
+        findings,
+    };
+
+    println!(
+        "replay audit of {} in ledgers {}..={}: {} transaction(s), {} auth entr(ies), {} nonce event(s)",
+        report.wallet,
+        report.start_ledger,
+        report.end_ledger,
+        report.transactions_scanned,
+        report.auth_entries,
+        report.nonce_events
+    );
+    for finding in &report.findings {
+        println!("  {finding}");
+    }
+    if let Some(path) = &args.json {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+
+    if !report.passed {
+        return Err("replay protection violations found".into());
+    }
+    println!("replay protections held");
+    Ok(())
+}
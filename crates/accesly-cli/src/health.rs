@@ -0,0 +1,129 @@
+// ---------------------------------------------------------------------------
+// `accesly-cli health`
+//
+// Uptime check for deployed contracts. Every contract exposes a `ping` view;
+// this command simulates a `ping` transaction against each address given,
+// which goes through the same RPC path a wallet call does (envelope decoding,
+// footprint, host execution) without submitting anything or paying fees. A
+// broken RPC node or a contract upgrade that no longer executes shows up as a
+// failed ping, and the command exits non-zero so monitoring can alert on it.
+// ---------------------------------------------------------------------------
+
+use accesly_client::strkey::parse_account;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Args;
+use soroban_sdk::{
+    xdr::{
+        HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount,
+        Operation, OperationBody, Preconditions, ReadXdr, ScAddress, ScVal, SequenceNumber,
+        Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, WriteXdr,
+    },
+    Address, Env,
+};
+
+use crate::rpc::RpcClient;
+
+#[derive(Args, Debug)]
+pub struct HealthArgs {
+    /// Soroban RPC endpoint to check through
+    #[arg(long, env = "ACCESLY_RPC_URL")]
+    pub rpc_url: String,
+    /// Account the simulated transactions are sourced from (G...)
+    #[arg(long, env = "ACCESLY_SOURCE_ACCOUNT", value_parser = crate::account_address)]
+    pub source: String,
+    /// Contracts to ping (C...)
+    #[arg(required = true, value_parser = crate::contract_address)]
+    pub contracts: Vec<String>,
+}
+
+/// Build the unsigned envelope simulating a no-argument `contract.function()`
+/// from `source`
+pub fn view_envelope(
+    source: [u8; 32],
+    contract: &ScAddress,
+    function: &str,
+) -> TransactionEnvelope {
+    let op = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: contract.clone(),
+                function_name: function.try_into().unwrap(),
+                args: Default::default(),
+            }),
+            auth: Default::default(),
+        }),
+    };
+
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(source)),
+            // Simulation ignores the fee and sequence number
+            fee: 100,
+            seq_num: SequenceNumber(0),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: [op].to_vec().try_into().unwrap(),
+            ext: TransactionExt::V0,
+        },
+        signatures: Default::default(),
+    })
+}
+
+/// Simulate `ping` on `contract` and return its result
+fn ping(
+    rpc: &RpcClient,
+    env: &Env,
+    source: [u8; 32],
+    contract: &str,
+) -> Result<ScVal, Box<dyn std::error::Error>> {
+    simulate_view(rpc, env, source, contract, "ping")
+}
+
+/// Simulate a no-argument view on `contract` and return its result
+pub fn simulate_view(
+    rpc: &RpcClient,
+    env: &Env,
+    source: [u8; 32],
+    contract: &str,
+    function: &str,
+) -> Result<ScVal, Box<dyn std::error::Error>> {
+    let contract = ScAddress::from(&Address::from_str(env, contract));
+    let envelope = view_envelope(source, &contract, function);
+    let simulation =
+        rpc.simulate_transaction(&STANDARD.encode(envelope.to_xdr(Limits::none())?))?;
+
+    if let Some(error) = simulation.error {
+        return Err(error.into());
+    }
+    let result = simulation
+        .results
+        .first()
+        .ok_or("simulation returned no result")?;
+    Ok(ScVal::from_xdr(
+        STANDARD.decode(&result.xdr)?,
+        Limits::none(),
+    )?)
+}
+
+pub fn run(args: HealthArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let env = Env::default();
+    let source = parse_account(&args.source)?;
+    let rpc = RpcClient::new(&args.rpc_url);
+
+    let mut failed = 0usize;
+    for contract in &args.contracts {
+        match ping(&rpc, &env, source, contract) {
+            Ok(result) => println!("OK   {contract} {result:?}"),
+            Err(e) => {
+                failed += 1;
+                println!("FAIL {contract} {e}");
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{failed} of {} contract(s) failed", args.contracts.len()).into());
+    }
+    Ok(())
+}
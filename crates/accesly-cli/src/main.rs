@@ -0,0 +1,64 @@
+// ---------------------------------------------------------------------------
+// accesly-cli
+//
+// Operator tooling for Accesly smart wallets. Each subcommand lives in its own
+// module and exposes an `Args` struct plus a `run` function.
+// ---------------------------------------------------------------------------
+
+use accesly_client::strkey::{parse_account, parse_contract, StrKeyError};
+use clap::{Parser, Subcommand};
+
+mod audit;
+mod health;
+mod recovery_kit;
+mod replay;
+mod rpc;
+
+#[derive(Parser, Debug)]
+#[command(name = "accesly-cli", version, about = "Operator tooling for Accesly wallets")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replay a wallet's transactions in a ledger range against a local snapshot
+    Replay(replay::ReplayArgs),
+    /// Simulate `ping` on deployed contracts to check they still execute
+    Health(health::HealthArgs),
+    /// Print or verify an offline recovery kit
+    RecoveryKit(recovery_kit::RecoveryKitArgs),
+    /// Produce compliance evidence from a wallet's on-chain history
+    Audit(audit::AuditArgs),
+}
+
+/// Argument parser for `C...` addresses, so a mistyped address is reported
+/// by clap instead of trapping inside the host
+fn contract_address(s: &str) -> Result<String, StrKeyError> {
+    parse_contract(s).map(|_| s.to_string())
+}
+
+/// Argument parser for `G...` account addresses
+fn account_address(s: &str) -> Result<String, StrKeyError> {
+    parse_account(s).map(|_| s.to_string())
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Replay(args) => replay::run(args),
+        Command::Health(args) => health::run(args),
+        Command::RecoveryKit(args) => recovery_kit::run(args),
+        Command::Audit(args) => audit::run(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,178 @@
+// ---------------------------------------------------------------------------
+// `accesly-cli recovery-kit`
+//
+// `generate` reads the wallet's owner, email hash and guardians through
+// simulated view calls, bundles them with the user's F3 container and prints
+// the encrypted sheet described in `accesly_client::recovery_kit`. `verify`
+// opens a sheet (checking line checksums, digest and password) and compares
+// it with the wallet's current state, exiting non-zero when the paper no
+// longer matches the chain and should be reprinted.
+// ---------------------------------------------------------------------------
+
+use std::path::PathBuf;
+
+use accesly_client::recovery_kit::{
+    check_kit, hex, open_kit, seal_kit, GuardianRecord, RecoveryKit, RecoveryState,
+};
+use accesly_client::strkey::parse_account;
+use clap::{Args, Subcommand};
+use soroban_sdk::{xdr::ScVal, Env};
+
+use crate::health::simulate_view;
+use crate::rpc::RpcClient;
+
+#[derive(Args, Debug)]
+pub struct RecoveryKitArgs {
+    #[command(subcommand)]
+    pub command: KitCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KitCommand {
+    /// Print an encrypted recovery kit for a wallet
+    Generate(GenerateArgs),
+    /// Check a printed kit and compare it with the wallet on chain
+    Verify(VerifyArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ChainArgs {
+    /// Soroban RPC endpoint to read the wallet through
+    #[arg(long, env = "ACCESLY_RPC_URL")]
+    pub rpc_url: String,
+    /// Account the simulated reads are sourced from (G...)
+    #[arg(long, env = "ACCESLY_SOURCE_ACCOUNT", value_parser = crate::account_address)]
+    pub source: String,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    pub chain: ChainArgs,
+    /// Wallet contract address (C...)
+    #[arg(long, value_parser = crate::contract_address)]
+    pub wallet: String,
+    /// F3 container as exported by the backend
+    #[arg(long)]
+    pub f3: PathBuf,
+    /// Password the kit is encrypted under
+    #[arg(long, env = "ACCESLY_KIT_PASSWORD")]
+    pub password: String,
+    /// Write the sheet here instead of stdout
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub chain: ChainArgs,
+    /// Kit sheet, printed or retyped
+    pub kit: PathBuf,
+    /// Password the kit was encrypted under
+    #[arg(long, env = "ACCESLY_KIT_PASSWORD")]
+    pub password: String,
+}
+
+/// Raw bytes of a `BytesN` view result
+fn bytes_value(value: &ScVal) -> Result<&[u8], String> {
+    match value {
+        ScVal::Bytes(bytes) => Ok(bytes.as_slice()),
+        other => Err(format!("expected bytes, got {other:?}")),
+    }
+}
+
+/// Decode one `Signer` (`Ed25519(key)` / `Secp256r1(key)`)
+fn guardian_record(value: &ScVal) -> Result<GuardianRecord, String> {
+    let ScVal::Vec(Some(parts)) = value else {
+        return Err(format!("expected a signer, got {value:?}"));
+    };
+    match parts.as_slice() {
+        [ScVal::Symbol(kind), key] => {
+            let key = hex(bytes_value(key)?);
+            match kind.to_utf8_string_lossy().as_str() {
+                "Ed25519" => Ok(GuardianRecord::Ed25519(key)),
+                "Secp256r1" => Ok(GuardianRecord::Secp256r1(key)),
+                other => Err(format!("unknown signer kind {other}")),
+            }
+        }
+        _ => Err(format!("expected a signer, got {value:?}")),
+    }
+}
+
+/// Decode the result of `get_guardians`
+pub fn guardian_records(value: &ScVal) -> Result<Vec<GuardianRecord>, String> {
+    match value {
+        ScVal::Vec(Some(guardians)) => guardians.iter().map(guardian_record).collect(),
+        ScVal::Vec(None) => Ok(Vec::new()),
+        other => Err(format!("expected a guardian list, got {other:?}")),
+    }
+}
+
+/// Read the state a kit depends on, with the ledger it was read at
+fn recovery_state(
+    chain: &ChainArgs,
+    wallet: &str,
+) -> Result<(RecoveryState, u32), Box<dyn std::error::Error>> {
+    let env = Env::default();
+    let source = parse_account(&chain.source)?;
+    let rpc = RpcClient::new(&chain.rpc_url);
+    let view = |function: &str| simulate_view(&rpc, &env, source, wallet, function);
+
+    let threshold = match view("get_guardian_threshold")? {
+        ScVal::U32(threshold) => threshold,
+        other => return Err(format!("expected a threshold, got {other:?}").into()),
+    };
+    let state = RecoveryState {
+        owner: hex(bytes_value(&view("get_owner")?)?),
+        email_hash: hex(bytes_value(&view("get_email_hash")?)?),
+        guardians: guardian_records(&view("get_guardians")?)?,
+        guardian_threshold: threshold,
+    };
+    Ok((state, rpc.latest_ledger()?))
+}
+
+fn generate(args: GenerateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let f3 = std::fs::read(&args.f3)?;
+    if f3.is_empty() {
+        return Err("F3 container is empty".into());
+    }
+    let (state, ledger) = recovery_state(&args.chain, &args.wallet)?;
+    if state.guardians.is_empty() {
+        eprintln!("warning: wallet has no guardians; recovery will need the email route");
+    }
+
+    let kit = RecoveryKit::new(&args.wallet, ledger, state, &f3);
+    let sheet = seal_kit(&kit, &args.password)?;
+    match args.out {
+        Some(path) => std::fs::write(path, sheet)?,
+        None => print!("{sheet}"),
+    }
+    Ok(())
+}
+
+fn verify(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let kit = open_kit(&std::fs::read_to_string(&args.kit)?, &args.password)?;
+    println!(
+        "kit for {} made at ledger {}",
+        kit.wallet, kit.created_at_ledger
+    );
+
+    let (chain, _) = recovery_state(&args.chain, &kit.wallet)?;
+    let mismatches = check_kit(&kit, &chain);
+    if mismatches.is_empty() {
+        println!("OK   kit matches the wallet");
+        return Ok(());
+    }
+    for mismatch in &mismatches {
+        println!("DIFF {mismatch}");
+    }
+    Err(format!("kit is out of date ({} difference(s))", mismatches.len()).into())
+}
+
+pub fn run(args: RecoveryKitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        KitCommand::Generate(args) => generate(args),
+        KitCommand::Verify(args) => verify(args),
+    }
+}
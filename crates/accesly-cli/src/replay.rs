@@ -0,0 +1,206 @@
+// ---------------------------------------------------------------------------
+// `accesly-cli replay`
+//
+// Reproduces production failures offline. Transactions touching a wallet are
+// fetched from RPC for a ledger range and re-executed, in order, against a
+// local host loaded from a ledger snapshot taken just before the range
+// (`stellar snapshot create --address <wallet> --ledger <start - 1>`). The
+// snapshot carries the deployed WASM, so the same code version is replayed.
+//
+// Recorded authorization entries are replayed as-is with `set_auths`, which
+// means `__check_auth` runs against the real signatures and nonces instead of
+// mocked auth.
+// ---------------------------------------------------------------------------
+
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Args;
+use soroban_sdk::{
+    testutils::Ledger,
+    xdr::{
+        FeeBumpTransactionInnerTx, HostFunction, Limits, OperationBody, ReadXdr, ScAddress,
+        ScVal, SorobanAuthorizationEntry, SorobanCredentials, TransactionEnvelope,
+    },
+    Address, Env, Symbol, TryFromVal, Val, Vec,
+};
+
+use crate::rpc::{RpcClient, RpcTransaction};
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Soroban RPC endpoint used to fetch the transactions
+    #[arg(long, env = "ACCESLY_RPC_URL")]
+    pub rpc_url: String,
+    /// Wallet contract address (C...)
+    #[arg(long, value_parser = crate::contract_address)]
+    pub wallet: String,
+    /// First ledger of the range (inclusive)
+    #[arg(long)]
+    pub start_ledger: u32,
+    /// Last ledger of the range (inclusive)
+    #[arg(long)]
+    pub end_ledger: u32,
+    /// Ledger snapshot taken at `start_ledger - 1`
+    #[arg(long)]
+    pub snapshot: PathBuf,
+    /// Only print invocations whose replay outcome differs from the chain
+    #[arg(long)]
+    pub divergent_only: bool,
+}
+
+/// A single `InvokeContract` host function extracted from a transaction.
+#[derive(Clone, Debug)]
+pub struct Invocation {
+    pub tx_hash: String,
+    pub ledger: u32,
+    pub timestamp: u64,
+    pub succeeded_on_chain: bool,
+    pub contract: ScAddress,
+    pub function: String,
+    pub args: std::vec::Vec<ScVal>,
+    pub auth: std::vec::Vec<SorobanAuthorizationEntry>,
+}
+
+/// Outcome of replaying one invocation locally.
+#[derive(Debug)]
+pub enum ReplayOutcome {
+    Ok,
+    Trapped(String),
+}
+
+/// Decode a transaction envelope and return every contract invocation that
+/// either targets `wallet` or carries an authorization entry for it.
+pub fn extract_invocations(
+    tx: &RpcTransaction,
+    wallet: &ScAddress,
+) -> Result<std::vec::Vec<Invocation>, String> {
+    let raw = STANDARD
+        .decode(&tx.envelope_xdr)
+        .map_err(|e| format!("envelope is not base64: {e}"))?;
+    let envelope = TransactionEnvelope::from_xdr(raw, Limits::none())
+        .map_err(|e| format!("envelope is not valid XDR: {e}"))?;
+
+    let operations = match &envelope {
+        TransactionEnvelope::TxV0(e) => e.tx.operations.to_vec(),
+        TransactionEnvelope::Tx(e) => e.tx.operations.to_vec(),
+        TransactionEnvelope::TxFeeBump(e) => match &e.tx.inner_tx {
+            FeeBumpTransactionInnerTx::Tx(inner) => inner.tx.operations.to_vec(),
+        },
+    };
+
+    let mut out = std::vec::Vec::new();
+    for op in operations {
+        let OperationBody::InvokeHostFunction(invoke) = op.body else {
+            continue;
+        };
+        let HostFunction::InvokeContract(call) = invoke.host_function else {
+            continue;
+        };
+
+        let authorizes_wallet = invoke.auth.iter().any(|entry| match &entry.credentials {
+            SorobanCredentials::Address(creds) => &creds.address == wallet,
+            SorobanCredentials::SourceAccount => false,
+        });
+        if &call.contract_address != wallet && !authorizes_wallet {
+            continue;
+        }
+
+        out.push(Invocation {
+            tx_hash: tx.tx_hash.clone(),
+            ledger: tx.ledger,
+            timestamp: tx.created_at,
+            succeeded_on_chain: tx.status == "SUCCESS",
+            contract: call.contract_address,
+            function: call.function_name.to_utf8_string_lossy(),
+            args: call.args.to_vec(),
+            auth: invoke.auth.to_vec(),
+        });
+    }
+    Ok(out)
+}
+
+/// Execute one invocation on `env` with the recorded auth entries.
+pub fn replay_invocation(env: &Env, invocation: &Invocation) -> ReplayOutcome {
+    env.ledger().set_sequence_number(invocation.ledger);
+    env.ledger().set_timestamp(invocation.timestamp);
+    env.set_auths(&invocation.auth);
+
+    let contract = match Address::try_from_val(env, &invocation.contract) {
+        Ok(address) => address,
+        Err(e) => return ReplayOutcome::Trapped(format!("bad contract address: {e:?}")),
+    };
+    let mut args = Vec::<Val>::new(env);
+    for arg in &invocation.args {
+        match Val::try_from_val(env, arg) {
+            Ok(val) => args.push_back(val),
+            Err(e) => return ReplayOutcome::Trapped(format!("bad argument: {e:?}")),
+        }
+    }
+    let func = Symbol::new(env, &invocation.function);
+
+    match env.try_invoke_contract::<Val, soroban_sdk::Error>(&contract, &func, args) {
+        Ok(Ok(_)) => ReplayOutcome::Ok,
+        Ok(Err(e)) => ReplayOutcome::Trapped(format!("unexpected return value: {e:?}")),
+        Err(Ok(e)) => ReplayOutcome::Trapped(format!("{e:?}")),
+        Err(Err(e)) => ReplayOutcome::Trapped(format!("{e:?}")),
+    }
+}
+
+pub fn run(args: ReplayArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.end_ledger < args.start_ledger {
+        return Err("end-ledger must not be before start-ledger".into());
+    }
+
+    let env = Env::from_ledger_snapshot_file(&args.snapshot);
+    let wallet = ScAddress::from(&Address::from_str(&env, &args.wallet));
+
+    let rpc = RpcClient::new(&args.rpc_url);
+    let transactions = rpc.get_transactions(args.start_ledger, args.end_ledger)?;
+
+    let mut invocations = std::vec::Vec::new();
+    for tx in &transactions {
+        match extract_invocations(tx, &wallet) {
+            Ok(found) => invocations.extend(found),
+            Err(e) => eprintln!("skipping {}: {e}", tx.tx_hash),
+        }
+    }
+    println!(
+        "replaying {} invocation(s) from {} transaction(s) in ledgers {}..={}",
+        invocations.len(),
+        transactions.len(),
+        args.start_ledger,
+        args.end_ledger
+    );
+
+    let mut divergent = 0usize;
+    for invocation in &invocations {
+        let outcome = replay_invocation(&env, invocation);
+        let replay_ok = matches!(outcome, ReplayOutcome::Ok);
+        let diverges = replay_ok != invocation.succeeded_on_chain;
+        if diverges {
+            divergent += 1;
+        }
+        if args.divergent_only && !diverges {
+            continue;
+        }
+
+        let chain = if invocation.succeeded_on_chain { "ok" } else { "failed" };
+        let local = match &outcome {
+            ReplayOutcome::Ok => "ok".to_string(),
+            ReplayOutcome::Trapped(reason) => format!("trapped: {reason}"),
+        };
+        println!(
+            "{}{} ledger={} fn={} chain={} replay={}",
+            if diverges { "! " } else { "  " },
+            invocation.tx_hash,
+            invocation.ledger,
+            invocation.function,
+            chain,
+            local
+        );
+    }
+
+    println!("{divergent} divergent invocation(s)");
+    Ok(())
+}
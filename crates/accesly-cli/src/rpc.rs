@@ -0,0 +1,205 @@
+// ---------------------------------------------------------------------------
+// Minimal Soroban RPC client
+//
+// Only the JSON-RPC methods the CLI actually needs are implemented. Responses
+// are decoded into plain structs; XDR payloads stay base64 until a command
+// decides it needs them.
+// ---------------------------------------------------------------------------
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Page size requested from `getTransactions` (the RPC maximum is 200).
+const PAGE_LIMIT: u32 = 200;
+
+/// Page size requested from `getEvents` (the RPC maximum is 10000).
+const EVENT_PAGE_LIMIT: u32 = 1000;
+
+#[derive(Debug)]
+pub struct RpcError(pub String);
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rpc error: {}", self.0)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// A transaction as returned by `getTransactions`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    pub status: String,
+    pub ledger: u32,
+    pub created_at: u64,
+    pub envelope_xdr: String,
+    #[serde(default)]
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionsPage {
+    transactions: Vec<RpcTransaction>,
+    latest_ledger: u32,
+    cursor: String,
+}
+
+/// A contract event as returned by `getEvents`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEvent {
+    pub ledger: u32,
+    #[serde(default)]
+    pub tx_hash: String,
+    /// Topics as base64 `ScVal` XDR
+    pub topic: Vec<String>,
+    /// Data as base64 `ScVal` XDR
+    pub value: String,
+    pub in_successful_contract_call: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsPage {
+    events: Vec<RpcEvent>,
+    latest_ledger: u32,
+    cursor: String,
+}
+
+/// One host function result from `simulateTransaction`.
+#[derive(Debug, Deserialize)]
+pub struct SimulationResult {
+    /// Return value as base64 `ScVal` XDR
+    pub xdr: String,
+}
+
+/// Response of `simulateTransaction`; `error` is set when the host trapped.
+#[derive(Debug, Deserialize)]
+pub struct Simulation {
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub results: Vec<SimulationResult>,
+}
+
+pub struct RpcClient {
+    url: String,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string() }
+    }
+
+    /// Send a JSON-RPC request and return its `result` member.
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = ureq::post(&self.url)
+            .send_json(body)
+            .map_err(|e| RpcError(e.to_string()))?
+            .into_json()
+            .map_err(|e| RpcError(e.to_string()))?;
+
+        if let Some(err) = response.get("error") {
+            return Err(RpcError(err.to_string()));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| RpcError("response has no result".into()))
+    }
+
+    /// Fetch every transaction in `[start_ledger, end_ledger]`, following cursors.
+    pub fn get_transactions(
+        &self,
+        start_ledger: u32,
+        end_ledger: u32,
+    ) -> Result<Vec<RpcTransaction>, RpcError> {
+        let mut out = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match &cursor {
+                None => json!({ "startLedger": start_ledger, "pagination": { "limit": PAGE_LIMIT } }),
+                Some(c) => json!({ "pagination": { "cursor": c, "limit": PAGE_LIMIT } }),
+            };
+            let page: TransactionsPage = serde_json::from_value(self.call("getTransactions", params)?)
+                .map_err(|e| RpcError(e.to_string()))?;
+
+            let exhausted = page.transactions.is_empty();
+            for tx in page.transactions {
+                if tx.ledger > end_ledger {
+                    return Ok(out);
+                }
+                out.push(tx);
+            }
+
+            if exhausted || page.latest_ledger < start_ledger {
+                return Ok(out);
+            }
+            cursor = Some(page.cursor);
+        }
+    }
+
+    /// Fetch every event `contract` published in `[start_ledger, end_ledger]`,
+    /// following cursors. Nodes only keep a few days of events, so older
+    /// ranges come back short rather than failing.
+    pub fn get_events(
+        &self,
+        contract: &str,
+        start_ledger: u32,
+        end_ledger: u32,
+    ) -> Result<Vec<RpcEvent>, RpcError> {
+        let filters = json!([{ "type": "contract", "contractIds": [contract] }]);
+        let mut out = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match &cursor {
+                None => json!({
+                    "startLedger": start_ledger,
+                    "filters": filters,
+                    "pagination": { "limit": EVENT_PAGE_LIMIT },
+                }),
+                Some(c) => json!({
+                    "filters": filters,
+                    "pagination": { "cursor": c, "limit": EVENT_PAGE_LIMIT },
+                }),
+            };
+            let page: EventsPage = serde_json::from_value(self.call("getEvents", params)?)
+                .map_err(|e| RpcError(e.to_string()))?;
+
+            let exhausted = page.events.is_empty();
+            for event in page.events {
+                if event.ledger > end_ledger {
+                    return Ok(out);
+                }
+                out.push(event);
+            }
+
+            if exhausted || page.latest_ledger < start_ledger {
+                return Ok(out);
+            }
+            cursor = Some(page.cursor);
+        }
+    }
+
+    /// Simulate a base64 transaction envelope without submitting it.
+    pub fn simulate_transaction(&self, envelope_xdr: &str) -> Result<Simulation, RpcError> {
+        let params = json!({ "transaction": envelope_xdr });
+        serde_json::from_value(self.call("simulateTransaction", params)?)
+            .map_err(|e| RpcError(e.to_string()))
+    }
+
+    /// Sequence of the latest ledger the node has ingested.
+    pub fn latest_ledger(&self) -> Result<u32, RpcError> {
+        let result = self.call("getLatestLedger", json!({}))?;
+        result
+            .get("sequence")
+            .and_then(Value::as_u64)
+            .map(|sequence| sequence as u32)
+            .ok_or_else(|| RpcError("latest ledger has no sequence".into()))
+    }
+}
@@ -0,0 +1,521 @@
+// src/test.rs
+
+use crate::audit::{
+    analyze, ed25519_proof, network_id, nonce_record, Anomaly, AuthUse, Ed25519Proof, Finding,
+    NonceEvent, NonceRecord, Severity,
+};
+use crate::health::view_envelope;
+use crate::recovery_kit::guardian_records;
+use crate::replay::extract_invocations;
+use crate::Cli;
+use crate::rpc::{RpcEvent, RpcTransaction};
+use accesly_client::recovery_kit::GuardianRecord;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Parser;
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{
+    testutils::Address as _,
+    xdr::{
+        HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount,
+        Operation, OperationBody, Preconditions, ScAddress, ScMap, ScMapEntry, ScSymbol, ScVal,
+        SequenceNumber, SorobanAuthorizedFunction, SorobanAuthorizedInvocation, Transaction,
+        TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, WriteXdr, ScVec,
+    },
+    Address, Env,
+};
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn invoke_op(contract: &ScAddress, function: &str) -> Operation {
+    Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: contract.clone(),
+                function_name: ScSymbol(function.try_into().unwrap()),
+                args: [ScVal::U64(7)].to_vec().try_into().unwrap(),
+            }),
+            auth: Default::default(),
+        }),
+    }
+}
+
+fn rpc_tx(operations: std::vec::Vec<Operation>, status: &str) -> RpcTransaction {
+    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256([9u8; 32])),
+            fee: 100,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: operations.try_into().unwrap(),
+            ext: TransactionExt::V0,
+        },
+        signatures: Default::default(),
+    });
+
+    RpcTransaction {
+        status: status.to_string(),
+        ledger: 100,
+        created_at: 1_700_000_000,
+        envelope_xdr: STANDARD.encode(envelope.to_xdr(Limits::none()).unwrap()),
+        tx_hash: "abc".to_string(),
+    }
+}
+
+// ============================================================================
+// REPLAY EXTRACTION TESTS
+// ============================================================================
+
+#[test]
+fn test_extract_invocations_targeting_wallet() {
+    let env = Env::default();
+    let wallet = ScAddress::from(&Address::generate(&env));
+
+    let tx = rpc_tx(std::vec![invoke_op(&wallet, "bump")], "SUCCESS");
+    let found = extract_invocations(&tx, &wallet).unwrap();
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].function, "bump");
+    assert_eq!(found[0].args, std::vec![ScVal::U64(7)]);
+    assert_eq!(found[0].ledger, 100);
+    assert!(found[0].succeeded_on_chain);
+}
+
+#[test]
+fn test_extract_invocations_ignores_other_contracts() {
+    let env = Env::default();
+    let wallet = ScAddress::from(&Address::generate(&env));
+    let other = ScAddress::from(&Address::generate(&env));
+
+    let tx = rpc_tx(
+        std::vec![invoke_op(&other, "transfer"), invoke_op(&wallet, "init")],
+        "FAILED",
+    );
+    let found = extract_invocations(&tx, &wallet).unwrap();
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].function, "init");
+    assert!(!found[0].succeeded_on_chain);
+}
+
+#[test]
+fn test_extract_invocations_rejects_garbage() {
+    let env = Env::default();
+    let wallet = ScAddress::from(&Address::generate(&env));
+
+    let mut tx = rpc_tx(std::vec![], "SUCCESS");
+    tx.envelope_xdr = "not-base64!".to_string();
+
+    assert!(extract_invocations(&tx, &wallet).is_err());
+}
+
+#[test]
+fn test_replay_rejects_malformed_wallet() {
+    let parse = |wallet: &str| {
+        Cli::try_parse_from([
+            "accesly-cli", "replay", "--rpc-url", "http://localhost", "--wallet", wallet,
+            "--start-ledger", "1", "--end-ledger", "2", "--snapshot", "snap.json",
+        ])
+    };
+
+    assert!(parse("CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC").is_ok());
+    // Checksum typo and an account key where a contract is expected
+    assert!(parse("CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSD").is_err());
+    assert!(parse("GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN").is_err());
+}
+
+// ============================================================================
+// HEALTH TESTS
+// ============================================================================
+
+#[test]
+fn test_ping_envelope_calls_ping_without_args() {
+    let env = Env::default();
+    let contract = ScAddress::from(&Address::generate(&env));
+
+    let envelope = view_envelope([9u8; 32], &contract, "ping");
+    let TransactionEnvelope::Tx(v1) = &envelope else {
+        panic!("expected a v1 envelope");
+    };
+    assert_eq!(v1.tx.source_account, MuxedAccount::Ed25519(Uint256([9u8; 32])));
+
+    // Round-trips through the same decoder replay uses
+    let tx = RpcTransaction {
+        status: "SUCCESS".to_string(),
+        ledger: 1,
+        created_at: 0,
+        envelope_xdr: STANDARD.encode(envelope.to_xdr(Limits::none()).unwrap()),
+        tx_hash: String::new(),
+    };
+    let found = extract_invocations(&tx, &contract).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].function, "ping");
+    assert!(found[0].args.is_empty());
+}
+
+#[test]
+fn test_health_requires_contracts_and_valid_source() {
+    let parse = |args: &[&str]| {
+        let mut argv = std::vec!["accesly-cli", "health", "--rpc-url", "http://localhost"];
+        argv.extend_from_slice(args);
+        Cli::try_parse_from(argv)
+    };
+    let source = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+    let contract = "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
+
+    assert!(parse(&["--source", source, contract, contract]).is_ok());
+    assert!(parse(&["--source", source]).is_err());
+    // Source and contract swapped
+    assert!(parse(&["--source", contract, source]).is_err());
+}
+
+// ============================================================================
+// RECOVERY KIT TESTS
+// ============================================================================
+
+fn signer_val(kind: &str, key: &[u8]) -> ScVal {
+    let parts = std::vec![
+        ScVal::Symbol(ScSymbol(kind.try_into().unwrap())),
+        ScVal::Bytes(key.to_vec().try_into().unwrap()),
+    ];
+    ScVal::Vec(Some(ScVec(parts.try_into().unwrap())))
+}
+
+#[test]
+fn test_guardian_records_decode_signers() {
+    let guardians = ScVal::Vec(Some(ScVec(
+        std::vec![
+            signer_val("Ed25519", &[0xab; 32]),
+            signer_val("Secp256r1", &[0x04; 65]),
+        ]
+        .try_into()
+        .unwrap(),
+    )));
+
+    assert_eq!(
+        guardian_records(&guardians).unwrap(),
+        std::vec![
+            GuardianRecord::Ed25519("ab".repeat(32)),
+            GuardianRecord::Secp256r1("04".repeat(65)),
+        ]
+    );
+    assert_eq!(guardian_records(&ScVal::Vec(None)).unwrap(), std::vec![]);
+
+    let unknown = ScVal::Vec(Some(ScVec(
+        std::vec![signer_val("Rsa", &[1; 4])].try_into().unwrap(),
+    )));
+    assert!(guardian_records(&unknown).is_err());
+    assert!(guardian_records(&ScVal::U32(1)).is_err());
+}
+
+#[test]
+fn test_recovery_kit_commands_parse() {
+    let parse = |args: &[&str]| {
+        let mut argv = std::vec![
+            "accesly-cli", "recovery-kit", args[0], "--rpc-url", "http://localhost",
+            "--source", "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN",
+            "--password", "pw",
+        ];
+        argv.extend_from_slice(&args[1..]);
+        Cli::try_parse_from(argv)
+    };
+    let wallet = "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
+
+    assert!(parse(&["generate", "--wallet", wallet, "--f3", "f3.bin"]).is_ok());
+    // F3 is required, and the wallet must be a contract
+    assert!(parse(&["generate", "--wallet", wallet]).is_err());
+    let account = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+    assert!(parse(&["generate", "--wallet", account, "--f3", "f3.bin"]).is_err());
+    assert!(parse(&["verify", "kit.txt"]).is_ok());
+    assert!(parse(&["verify"]).is_err());
+}
+
+// ============================================================================
+// REPLAY AUDIT TESTS
+// ============================================================================
+
+fn authorized_invocation(function: &str) -> SorobanAuthorizedInvocation {
+    let env = Env::default();
+    SorobanAuthorizedInvocation {
+        function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+            contract_address: ScAddress::from(&Address::generate(&env)),
+            function_name: ScSymbol(function.try_into().unwrap()),
+            args: Default::default(),
+        }),
+        sub_invocations: Default::default(),
+    }
+}
+
+fn auth_use(
+    tx_hash: &str,
+    accepted: bool,
+    credential_nonce: i64,
+    invocation: &SorobanAuthorizedInvocation,
+) -> AuthUse {
+    AuthUse {
+        tx_hash: tx_hash.to_string(),
+        ledger: 20,
+        accepted,
+        credential_nonce,
+        expiration_ledger: 100,
+        invocation: invocation.clone(),
+        proof: None,
+    }
+}
+
+fn consumed(tx_hash: &str, channel: u32, nonce: u64) -> NonceRecord {
+    NonceRecord {
+        tx_hash: tx_hash.to_string(),
+        ledger: 10,
+        event: NonceEvent::Consumed { owner: [1; 32], channel, nonce },
+    }
+}
+
+fn kinds(findings: &[Finding]) -> std::vec::Vec<(Severity, &Anomaly)> {
+    findings.iter().map(|f| (f.severity, &f.anomaly)).collect()
+}
+
+#[test]
+fn test_audit_flags_nonce_anomalies() {
+    let records = std::vec![
+        consumed("a", 0, 1),
+        // Owner actions consume channel 0 without an event, so no gap here
+        consumed("b", 0, 4),
+        consumed("c", 2, 0),
+        consumed("d", 2, 2),
+        consumed("e", 0, 4),
+        NonceRecord {
+            tx_hash: "f".to_string(),
+            ledger: 10,
+            event: NonceEvent::Forced { nonce: 1 },
+        },
+        consumed("g", 0, 1),
+    ];
+    let findings = analyze(&[], &records, &[0; 32], &[]);
+
+    let tx = |hash: &str| hash.to_string();
+    assert_eq!(
+        kinds(&findings),
+        std::vec![
+            (Severity::Violation, &Anomaly::NonceRegressed {
+                channel: 0,
+                expected: 5,
+                nonce: 4,
+                tx_hash: tx("e"),
+            }),
+            (Severity::Violation, &Anomaly::NonceReused {
+                channel: 0,
+                nonce: 1,
+                tx_hashes: std::vec![tx("a"), tx("g")],
+            }),
+            (Severity::Violation, &Anomaly::NonceReused {
+                channel: 0,
+                nonce: 4,
+                tx_hashes: std::vec![tx("b"), tx("e")],
+            }),
+            (Severity::Notice, &Anomaly::NonceGap {
+                channel: 2,
+                expected: 1,
+                nonce: 2,
+                tx_hash: tx("d"),
+            }),
+            (Severity::Notice, &Anomaly::NonceForced { nonce: 1, tx_hash: tx("f") }),
+        ]
+    );
+}
+
+#[test]
+fn test_audit_flags_duplicate_payloads() {
+    let transfer = authorized_invocation("transfer");
+    let approve = authorized_invocation("approve");
+    let uses = std::vec![
+        auth_use("a", true, 1, &transfer),
+        auth_use("b", false, 1, &transfer),
+        auth_use("c", true, 2, &transfer),
+        auth_use("d", true, 2, &approve),
+        auth_use("e", true, 3, &approve),
+    ];
+    let findings = analyze(&uses, &[], &[0; 32], &[]);
+
+    let mut found: std::vec::Vec<_> = findings
+        .iter()
+        .map(|f| match &f.anomaly {
+            Anomaly::DuplicatePayload { tx_hashes, accepted, .. } => {
+                ("payload", f.severity, tx_hashes.clone(), *accepted)
+            }
+            Anomaly::CredentialNonceReused { tx_hashes, accepted, .. } => {
+                ("nonce", f.severity, tx_hashes.clone(), *accepted)
+            }
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    found.sort();
+
+    let txs = |hashes: &[&str]| hashes.iter().map(|h| h.to_string()).collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        found,
+        std::vec![
+            ("nonce", Severity::Violation, txs(&["c", "d"]), 2),
+            ("payload", Severity::Attempt, txs(&["a", "b"]), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_audit_matches_rejected_signatures() {
+    let owner = SigningKey::from_bytes(&[7; 32]);
+    let mainnet = network_id("Public Global Stellar Network ; September 2015");
+    let testnet = network_id("Test SDF Network ; September 2015");
+    let records = std::vec![NonceRecord {
+        tx_hash: "a".to_string(),
+        ledger: 10,
+        event: NonceEvent::Consumed {
+            owner: owner.verifying_key().to_bytes(),
+            channel: 0,
+            nonce: 5,
+        },
+    }];
+
+    let invocation = authorized_invocation("transfer");
+    let signed = |tx_hash: &str, credential_nonce: i64, network: &[u8; 32], nonce: u64| {
+        let mut auth = auth_use(tx_hash, false, credential_nonce, &invocation);
+        let mut message = std::vec::Vec::new();
+        accesly_messages::auth_message(&mut message, &auth.payload(network), 0, nonce, None);
+        auth.proof = Some(Ed25519Proof {
+            key: None,
+            signature: owner.sign(&message).to_bytes(),
+            channel: 0,
+            valid_until_ledger: None,
+        });
+        auth
+    };
+    let uses = std::vec![
+        // An old mainnet signature, then one made on testnet
+        signed("stale", 1, &mainnet, 5),
+        signed("foreign", 2, &testnet, 6),
+        // Valid for the wallet's next nonce: rejected for some other reason
+        signed("current", 3, &mainnet, 6),
+    ];
+    let findings = analyze(&uses, &records, &mainnet, &[("testnet", testnet)]);
+
+    assert_eq!(
+        kinds(&findings),
+        std::vec![
+            (Severity::Attempt, &Anomaly::StaleSignature {
+                tx_hash: "stale".to_string(),
+                channel: 0,
+                nonce: 5,
+                consumed_in: "a".to_string(),
+            }),
+            (Severity::Attempt, &Anomaly::CrossNetworkSignature {
+                tx_hash: "foreign".to_string(),
+                network: "testnet".to_string(),
+            }),
+        ]
+    );
+}
+
+fn symbol(name: &str) -> ScVal {
+    ScVal::Symbol(ScSymbol(name.try_into().unwrap()))
+}
+
+fn sc_map(entries: std::vec::Vec<(&str, ScVal)>) -> ScVal {
+    let entries: std::vec::Vec<ScMapEntry> = entries
+        .into_iter()
+        .map(|(key, val)| ScMapEntry { key: symbol(key), val })
+        .collect();
+    ScVal::Map(Some(ScMap(entries.try_into().unwrap())))
+}
+
+fn sc_vec(items: std::vec::Vec<ScVal>) -> ScVal {
+    ScVal::Vec(Some(ScVec(items.try_into().unwrap())))
+}
+
+fn sc_bytes(bytes: &[u8]) -> ScVal {
+    ScVal::Bytes(bytes.to_vec().try_into().unwrap())
+}
+
+#[test]
+fn test_audit_decodes_signatures_and_events() {
+    let ed25519 = sc_vec(std::vec![symbol("Ed25519"), sc_bytes(&[9; 64])]);
+    let expiring = sc_vec(std::vec![
+        symbol("Expiring"),
+        sc_map(std::vec![
+            ("channel", ScVal::U32(3)),
+            ("signature", sc_vec(std::vec![symbol("Device"), sc_bytes(&[2; 32]), sc_bytes(&[9; 64])])),
+            ("valid_until_ledger", ScVal::U32(500)),
+        ]),
+    ]);
+    assert_eq!(
+        ed25519_proof(&expiring, 0, None),
+        Some(Ed25519Proof {
+            key: Some([2; 32]),
+            signature: [9; 64],
+            channel: 3,
+            valid_until_ledger: Some(500),
+        })
+    );
+    let relayed = sc_vec(std::vec![
+        symbol("Relayed"),
+        sc_map(std::vec![
+            ("channel", ScVal::U32(0)),
+            ("relayer", sc_bytes(&[5; 32])),
+            ("relayer_signature", sc_bytes(&[6; 64])),
+            ("signature", ed25519.clone()),
+            ("valid_until_ledger", ScVal::Void),
+        ]),
+    ]);
+    let proof = ed25519_proof(&relayed, 0, None).unwrap();
+    assert_eq!((proof.key, proof.signature, proof.valid_until_ledger), (None, [9; 64], None));
+    let channel = sc_vec(std::vec![symbol("Channel"), ScVal::U32(4), ed25519]);
+    assert_eq!(ed25519_proof(&channel, 0, None).unwrap().channel, 4);
+    assert_eq!(ed25519_proof(&sc_vec(std::vec![symbol("PreAuthorized")]), 0, None), None);
+
+    let encode = |val: &ScVal| STANDARD.encode(val.to_xdr(Limits::none()).unwrap());
+    let mut event = RpcEvent {
+        ledger: 7,
+        tx_hash: "a".to_string(),
+        topic: std::vec![encode(&symbol("auth_success"))],
+        value: encode(&sc_map(std::vec![
+            ("channel", ScVal::U32(0)),
+            ("nonce", ScVal::U64(12)),
+            ("owner", sc_bytes(&[1; 32])),
+        ])),
+        in_successful_contract_call: true,
+    };
+    let record = nonce_record(&event).unwrap().unwrap();
+    assert_eq!(record.ledger, 7);
+    assert_eq!(
+        record.event,
+        NonceEvent::Consumed { owner: [1; 32], channel: 0, nonce: 12 }
+    );
+
+    event.in_successful_contract_call = false;
+    assert!(nonce_record(&event).unwrap().is_none());
+    event.in_successful_contract_call = true;
+    event.value = encode(&ScVal::U32(1));
+    assert!(nonce_record(&event).is_err());
+    event.topic = std::vec![encode(&symbol("wallet_frozen"))];
+    assert!(nonce_record(&event).unwrap().is_none());
+}
+
+#[test]
+fn test_audit_replay_parses_positional_wallet() {
+    let parse = |wallet: &str, extra: &[&str]| {
+        let mut argv = std::vec![
+            "accesly-cli", "audit", "replay", wallet, "--rpc-url", "http://localhost",
+            "--network-passphrase", "Test SDF Network ; September 2015",
+        ];
+        argv.extend_from_slice(extra);
+        Cli::try_parse_from(argv)
+    };
+    let wallet = "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
+
+    assert!(parse(wallet, &["--start-ledger", "1"]).is_ok());
+    assert!(parse(wallet, &["--start-ledger", "1", "--end-ledger", "9", "--json", "r.json"]).is_ok());
+    assert!(parse(wallet, &[]).is_err());
+    assert!(parse("GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN", &["--start-ledger", "1"]).is_err());
+}
@@ -0,0 +1,212 @@
+// ---------------------------------------------------------------------------
+// Canonical amounts
+//
+// Exact conversion between on-chain i128 amounts and decimal strings. No
+// floating point anywhere: the string is split into integer and fraction
+// digits and scaled by the asset's decimals with checked integer math.
+//
+// Formatting is locale independent and canonical: ASCII digits, '-' for
+// negatives, '.' as the separator, no grouping, no trailing zeros, so equal
+// amounts always produce equal strings.
+// ---------------------------------------------------------------------------
+
+/// Decimals of XLM and every classic Stellar asset (1 unit = 10^7 stroops)
+pub const STELLAR_DECIMALS: u32 = 7;
+
+/// Largest decimals value whose scale factor fits in an i128
+pub const MAX_DECIMALS: u32 = 38;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AmountError {
+    /// Not of the form `-?digits(.digits)?`
+    Malformed,
+    /// More fractional digits than the asset has decimals
+    TooPrecise,
+    /// Does not fit in an i128 at the asset's scale
+    Overflow,
+    /// `decimals` is above `MAX_DECIMALS`
+    UnsupportedDecimals(u32),
+}
+
+impl std::fmt::Display for AmountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed amount"),
+            Self::TooPrecise => write!(f, "amount has more decimals than the asset"),
+            Self::Overflow => write!(f, "amount out of range"),
+            Self::UnsupportedDecimals(decimals) => write!(f, "unsupported decimals {decimals}"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+/// How to drop fractional digits beyond the asset's decimals. Modes are
+/// symmetric around zero: rounding `-x` gives the negation of rounding `x`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rounding {
+    /// Truncate toward zero
+    Down,
+    /// Away from zero whenever anything is dropped
+    Up,
+    /// To nearest, ties away from zero
+    HalfUp,
+    /// To nearest, ties to the even neighbour (banker's rounding)
+    HalfEven,
+}
+
+/// Format `value` (in the asset's smallest unit) as a canonical decimal
+pub fn format_amount(value: i128, decimals: u32) -> Result<String, AmountError> {
+    let scale = scale(decimals)?.unsigned_abs();
+    let magnitude = value.unsigned_abs();
+    let int = magnitude / scale;
+    let frac = magnitude % scale;
+
+    let mut out = String::new();
+    if value < 0 {
+        out.push('-');
+    }
+    out.push_str(&int.to_string());
+    if frac != 0 {
+        let digits = format!("{frac:0width$}", width = decimals as usize);
+        out.push('.');
+        out.push_str(digits.trim_end_matches('0'));
+    }
+    Ok(out)
+}
+
+/// Parse a decimal exactly. Fails with `TooPrecise` rather than rounding.
+pub fn parse_amount(s: &str, decimals: u32) -> Result<i128, AmountError> {
+    let (negative, int, frac) = split(s)?;
+    if frac.len() > decimals as usize && frac[decimals as usize..].bytes().any(|b| b != b'0') {
+        return Err(AmountError::TooPrecise);
+    }
+    assemble(negative, int, frac, decimals, Rounding::Down)
+}
+
+/// Parse a decimal, rounding extra fractional digits with `rounding`
+pub fn parse_amount_rounded(s: &str, decimals: u32, rounding: Rounding) -> Result<i128, AmountError> {
+    let (negative, int, frac) = split(s)?;
+    assemble(negative, int, frac, decimals, rounding)
+}
+
+/// Convert `value` between two decimal scales, e.g. a 7-decimal stroop
+/// amount into an 18-decimal token amount
+pub fn rescale(value: i128, from: u32, to: u32, rounding: Rounding) -> Result<i128, AmountError> {
+    scale(from)?;
+    scale(to)?;
+    if to >= from {
+        return value
+            .checked_mul(scale(to - from)?)
+            .ok_or(AmountError::Overflow);
+    }
+
+    let divisor = scale(from - to)?;
+    let quotient = value / divisor;
+    let remainder = (value % divisor).unsigned_abs();
+    let bump = round_up(rounding, quotient.unsigned_abs(), remainder, divisor.unsigned_abs());
+    Ok(if !bump {
+        quotient
+    } else if value < 0 {
+        quotient - 1
+    } else {
+        quotient + 1
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn scale(decimals: u32) -> Result<i128, AmountError> {
+    if decimals > MAX_DECIMALS {
+        return Err(AmountError::UnsupportedDecimals(decimals));
+    }
+    Ok(10i128.pow(decimals))
+}
+
+/// Split into sign, integer digits and fraction digits
+fn split(s: &str) -> Result<(bool, &str, &str), AmountError> {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) if !frac.is_empty() => (int, frac),
+        Some(_) => return Err(AmountError::Malformed),
+        None => (unsigned, ""),
+    };
+    if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(AmountError::Malformed);
+    }
+    Ok((negative, int, frac))
+}
+
+fn assemble(negative: bool, int: &str, frac: &str, decimals: u32, rounding: Rounding) -> Result<i128, AmountError> {
+    let scale = scale(decimals)?.unsigned_abs();
+    let keep = frac.len().min(decimals as usize);
+    let (kept, dropped) = frac.split_at(keep);
+
+    let mut magnitude = digits(int)?
+        .checked_mul(scale)
+        .ok_or(AmountError::Overflow)?;
+    if !kept.is_empty() {
+        let padded = digits(kept)? * 10u128.pow(decimals - keep as u32);
+        magnitude = magnitude.checked_add(padded).ok_or(AmountError::Overflow)?;
+    }
+
+    if let Some(first) = dropped.bytes().next() {
+        let first = (first - b'0') as u128;
+        let rest_nonzero = dropped[1..].bytes().any(|b| b != b'0');
+        // Compare the dropped part against one half: map it onto 0..=19 so
+        // a tie is exactly 10
+        let remainder = first * 2 + rest_nonzero as u128;
+        let bump = match rounding {
+            Rounding::Down => false,
+            Rounding::Up => first != 0 || rest_nonzero,
+            Rounding::HalfUp => first >= 5,
+            Rounding::HalfEven => remainder > 10 || (remainder == 10 && magnitude % 2 == 1),
+        };
+        if bump {
+            magnitude = magnitude.checked_add(1).ok_or(AmountError::Overflow)?;
+        }
+    }
+
+    let limit = if negative {
+        i128::MIN.unsigned_abs()
+    } else {
+        i128::MAX as u128
+    };
+    if magnitude > limit {
+        return Err(AmountError::Overflow);
+    }
+    Ok(if negative {
+        0i128.wrapping_sub_unsigned(magnitude)
+    } else {
+        magnitude as i128
+    })
+}
+
+fn digits(s: &str) -> Result<u128, AmountError> {
+    s.bytes().try_fold(0u128, |acc, b| {
+        acc.checked_mul(10)
+            .and_then(|acc| acc.checked_add((b - b'0') as u128))
+            .ok_or(AmountError::Overflow)
+    })
+}
+
+/// Whether dropping `remainder / divisor` from `quotient` rounds its
+/// magnitude up
+fn round_up(rounding: Rounding, quotient: u128, remainder: u128, divisor: u128) -> bool {
+    if remainder == 0 {
+        return false;
+    }
+    // Distance to the next multiple; the remainder is a tie when they match
+    let to_next = divisor - remainder;
+    match rounding {
+        Rounding::Down => false,
+        Rounding::Up => true,
+        Rounding::HalfUp => remainder >= to_next,
+        Rounding::HalfEven => remainder > to_next || (remainder == to_next && quotient % 2 == 1),
+    }
+}
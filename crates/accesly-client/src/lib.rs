@@ -0,0 +1,21 @@
+// ---------------------------------------------------------------------------
+// accesly-client
+//
+// Off-chain helpers shared by Accesly backends and tooling. Nothing in here
+// talks to the network; modules only build, parse and verify data.
+// ---------------------------------------------------------------------------
+
+pub mod amount;
+pub mod login;
+pub mod payment_uri;
+pub mod reasons;
+pub mod receipts;
+pub mod recovery_kit;
+pub mod session_risk;
+pub mod snapshot;
+pub mod strkey;
+pub mod support;
+pub mod wasm_meta;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,220 @@
+// ---------------------------------------------------------------------------
+// Sign-in with a smart account
+//
+// A backend authenticates a user by having one of the wallet's keys sign a
+// login challenge, then checking that key against the wallet's signer set
+// as the chain has it: the owner, a linked device, any ed25519 signer, or an
+// unexpired session key granted with the `login` scope. Passkey assertions aren't
+// accepted here; they need the WebAuthn client data the wallet verifies
+// on-chain.
+//
+// Signer sets are fetched through a `SignerSource` and cached per wallet
+// against the version from `ping()` and the nonce from `get_nonce()`:
+// every change to the signer set is signed and moves the nonce, and an
+// upgrade changes the version, so an unchanged (version, nonce) pair means
+// the cached set is still current. A cache hit costs two simulated views
+// instead of several.
+// ---------------------------------------------------------------------------
+
+use std::collections::HashMap;
+
+use ed25519_dalek::{Signature, Verifier as _, VerifyingKey};
+
+const CHALLENGE_TAG: &[u8] = b"accesly-login";
+/// Session scope that allows signing in
+pub const LOGIN_SCOPE: &str = "login";
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum LoginError {
+    /// The source failed while reading wallet state
+    Source(String),
+    /// The challenge is past its expiry
+    Expired,
+    /// The key is not an active signer of the wallet
+    UnknownSigner,
+    /// The key is a session without the login scope, or an expired one
+    SessionNotAllowed,
+    InvalidSignature,
+}
+
+impl std::fmt::Display for LoginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Source(err) => write!(f, "signer source error: {err}"),
+            Self::Expired => write!(f, "login challenge expired"),
+            Self::UnknownSigner => write!(f, "key is not a signer of the wallet"),
+            Self::SessionNotAllowed => write!(f, "session key can't be used to sign in"),
+            Self::InvalidSignature => write!(f, "invalid login signature"),
+        }
+    }
+}
+
+impl std::error::Error for LoginError {}
+
+/// Challenge issued by the backend for one sign-in attempt
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoginChallenge {
+    /// Domain of the backend asking, so a signature can't be reused on
+    /// another site
+    pub domain: String,
+    /// Wallet contract address (C...)
+    pub wallet: String,
+    /// Random per attempt, kept by the backend until used
+    pub nonce: [u8; 16],
+    /// Unix time after which the challenge is refused
+    pub expires_at: u64,
+}
+
+impl LoginChallenge {
+    /// The bytes the key signs: tag, then strings prefixed with their u32
+    /// length, nonce and expiry big-endian
+    pub fn message(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64 + self.domain.len() + self.wallet.len());
+        out.extend_from_slice(CHALLENGE_TAG);
+        for field in [&self.domain, &self.wallet] {
+            out.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            out.extend_from_slice(field.as_bytes());
+        }
+        out.extend_from_slice(&self.nonce);
+        out.extend_from_slice(&self.expires_at.to_be_bytes());
+        out
+    }
+}
+
+/// The wallet's `ping()` result
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletPing {
+    pub version: String,
+    pub ledger: u32,
+    /// Nonce channels in use besides the wallet nonce
+    pub nonce_channels_in_use: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionSigner {
+    pub public_key: [u8; 32],
+    pub scope: String,
+    /// Ledger timestamp after which the session is no longer accepted
+    pub expires_at: u64,
+}
+
+/// The keys that can act for a wallet
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SignerSet {
+    pub owner: [u8; 32],
+    /// Keys from `list_devices`
+    pub devices: Vec<[u8; 32]>,
+    /// ed25519 keys from `get_signers`
+    pub signers: Vec<[u8; 32]>,
+    /// Sessions still in the current revocation epoch
+    pub sessions: Vec<SessionSigner>,
+}
+
+/// Which of the wallet's keys signed in
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LoginSigner {
+    Owner,
+    Device([u8; 32]),
+    Signer([u8; 32]),
+    Session([u8; 32]),
+}
+
+/// Reads wallet state, normally by simulating its views over RPC
+pub trait SignerSource {
+    fn ping(&self, wallet: &str) -> Result<WalletPing, LoginError>;
+    /// The wallet nonce, `get_nonce()`
+    fn nonce(&self, wallet: &str) -> Result<u64, LoginError>;
+    fn signer_set(&self, wallet: &str) -> Result<SignerSet, LoginError>;
+}
+
+struct CachedSigners {
+    version: String,
+    nonce: u64,
+    set: SignerSet,
+}
+
+pub struct LoginVerifier<S> {
+    source: S,
+    cache: HashMap<String, CachedSigners>,
+}
+
+impl<S: SignerSource> LoginVerifier<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Check `signature` by `public_key` over `challenge` at unix time
+    /// `now`, and return which signer it was
+    pub fn verify(
+        &mut self,
+        challenge: &LoginChallenge,
+        public_key: &[u8; 32],
+        signature: &[u8; 64],
+        now: u64,
+    ) -> Result<LoginSigner, LoginError> {
+        if now > challenge.expires_at {
+            return Err(LoginError::Expired);
+        }
+
+        let signer = match_signer(self.signer_set(&challenge.wallet)?, public_key, now)?;
+
+        let key = VerifyingKey::from_bytes(public_key).map_err(|_| LoginError::InvalidSignature)?;
+        key.verify(&challenge.message(), &Signature::from_bytes(signature))
+            .map_err(|_| LoginError::InvalidSignature)?;
+
+        Ok(signer)
+    }
+
+    /// Current signer set of `wallet`, from the cache while the wallet's
+    /// version and nonce haven't moved
+    pub fn signer_set(&mut self, wallet: &str) -> Result<&SignerSet, LoginError> {
+        let ping = self.source.ping(wallet)?;
+        let nonce = self.source.nonce(wallet)?;
+        let fresh = self
+            .cache
+            .get(wallet)
+            .is_some_and(|cached| cached.version == ping.version && cached.nonce == nonce);
+        if !fresh {
+            let set = self.source.signer_set(wallet)?;
+            self.cache.insert(
+                wallet.to_string(),
+                CachedSigners {
+                    version: ping.version,
+                    nonce,
+                    set,
+                },
+            );
+        }
+        Ok(&self.cache[wallet].set)
+    }
+}
+
+fn match_signer(
+    set: &SignerSet,
+    public_key: &[u8; 32],
+    now: u64,
+) -> Result<LoginSigner, LoginError> {
+    if &set.owner == public_key {
+        return Ok(LoginSigner::Owner);
+    }
+    if set.devices.contains(public_key) {
+        return Ok(LoginSigner::Device(*public_key));
+    }
+    if set.signers.contains(public_key) {
+        return Ok(LoginSigner::Signer(*public_key));
+    }
+    match set.sessions.iter().find(|s| &s.public_key == public_key) {
+        Some(session) if session.scope == LOGIN_SCOPE && now <= session.expires_at => {
+            Ok(LoginSigner::Session(*public_key))
+        }
+        Some(_) => Err(LoginError::SessionNotAllowed),
+        None => Err(LoginError::UnknownSigner),
+    }
+}
@@ -0,0 +1,265 @@
+// ---------------------------------------------------------------------------
+// Payment request URIs (SEP-7 style)
+//
+// Accesly users pay each other by scanning a `web+stellar:pay?...` QR code.
+// The destination is the payee's smart account (C...) and the asset is
+// either native XLM or a classic credit asset. Requests can be signed by the
+// requester's origin-domain key using the SEP-7 signing payload, so the
+// paying app can verify who asked for the money.
+// ---------------------------------------------------------------------------
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use crate::amount::{parse_amount, STELLAR_DECIMALS};
+use crate::strkey::{parse_account, StrKey, StrKeyError};
+
+pub const SCHEME_PREFIX: &str = "web+stellar:pay?";
+
+/// SEP-7 payload prefix: 35 zero bytes followed by 0x04.
+const SIGNATURE_PREFIX: [u8; 36] = {
+    let mut prefix = [0u8; 36];
+    prefix[35] = 4;
+    prefix
+};
+const SIGNATURE_DOMAIN: &[u8] = b"stellar.sep.7 - URI Scheme";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UriError {
+    /// The URI does not start with `web+stellar:pay?`
+    InvalidScheme,
+    /// A `key=value` pair could not be decoded
+    MalformedParam(String),
+    /// A parameter appears that this parser does not understand
+    UnknownParam(String),
+    /// `destination` is missing
+    MissingDestination,
+    /// `destination` is not a valid G, M or C address
+    InvalidDestination(StrKeyError),
+    /// `amount` is not a positive decimal with at most 7 decimals
+    InvalidAmount,
+    /// `asset_code` and `asset_issuer` must come together
+    IncompleteAsset,
+    /// `asset_issuer` is not a valid G address
+    InvalidIssuer(StrKeyError),
+    /// `memo` is not a base64 encoded 32-byte hash
+    InvalidMemo,
+    /// The request carries no `signature`
+    Unsigned,
+    /// The signature does not verify against the given key
+    BadSignature,
+}
+
+impl std::fmt::Display for UriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid payment uri: {self:?}")
+    }
+}
+
+impl std::error::Error for UriError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PaymentAsset {
+    Native,
+    Credit { code: String, issuer: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentRequest {
+    /// Payee smart account (C...)
+    pub destination: String,
+    pub asset: PaymentAsset,
+    /// Decimal amount as displayed to the user, e.g. "12.5"
+    pub amount: Option<String>,
+    /// Invoice reference, sent as a MEMO_HASH
+    pub memo_hash: Option<[u8; 32]>,
+    /// Where the signed transaction should be posted instead of submitted
+    pub callback: Option<String>,
+    pub msg: Option<String>,
+    /// Domain whose URI_REQUEST_SIGNING_KEY signed the request
+    pub origin_domain: Option<String>,
+}
+
+impl PaymentRequest {
+    pub fn new(destination: &str) -> Self {
+        Self {
+            destination: destination.to_string(),
+            asset: PaymentAsset::Native,
+            amount: None,
+            memo_hash: None,
+            callback: None,
+            msg: None,
+            origin_domain: None,
+        }
+    }
+
+    /// Encode as an unsigned URI
+    pub fn to_uri(&self) -> String {
+        let mut params: Vec<(&str, String)> = vec![("destination", self.destination.clone())];
+        if let Some(amount) = &self.amount {
+            params.push(("amount", amount.clone()));
+        }
+        if let PaymentAsset::Credit { code, issuer } = &self.asset {
+            params.push(("asset_code", code.clone()));
+            params.push(("asset_issuer", issuer.clone()));
+        }
+        if let Some(hash) = &self.memo_hash {
+            params.push(("memo", STANDARD.encode(hash)));
+            params.push(("memo_type", "MEMO_HASH".to_string()));
+        }
+        if let Some(callback) = &self.callback {
+            params.push(("callback", format!("url:{callback}")));
+        }
+        if let Some(msg) = &self.msg {
+            params.push(("msg", msg.clone()));
+        }
+        if let Some(domain) = &self.origin_domain {
+            params.push(("origin_domain", domain.clone()));
+        }
+
+        let query: Vec<String> = params
+            .iter()
+            .map(|(k, v)| format!("{k}={}", percent_encode(v)))
+            .collect();
+        format!("{SCHEME_PREFIX}{}", query.join("&"))
+    }
+
+    /// Encode and sign with the origin domain's request signing key.
+    /// The signature is appended as the last parameter, as SEP-7 requires.
+    pub fn to_signed_uri(&self, key: &SigningKey) -> String {
+        let uri = self.to_uri();
+        let signature = key.sign(&signing_payload(&uri));
+        format!(
+            "{uri}&signature={}",
+            percent_encode(&STANDARD.encode(signature.to_bytes()))
+        )
+    }
+
+    /// Parse a payment URI. Signatures are not checked here; use `verify_uri`.
+    pub fn parse(uri: &str) -> Result<Self, UriError> {
+        let query = uri.strip_prefix(SCHEME_PREFIX).ok_or(UriError::InvalidScheme)?;
+
+        let mut request = Self::new("");
+        let mut asset_code = None;
+        let mut asset_issuer = None;
+        let mut memo = None;
+        let mut memo_type = None;
+
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, raw) = pair
+                .split_once('=')
+                .ok_or_else(|| UriError::MalformedParam(pair.to_string()))?;
+            let value =
+                percent_decode(raw).ok_or_else(|| UriError::MalformedParam(pair.to_string()))?;
+
+            match key {
+                "destination" => request.destination = value,
+                "amount" => request.amount = Some(value),
+                "asset_code" => asset_code = Some(value),
+                "asset_issuer" => asset_issuer = Some(value),
+                "memo" => memo = Some(value),
+                "memo_type" => memo_type = Some(value),
+                "callback" => {
+                    let url = value
+                        .strip_prefix("url:")
+                        .ok_or_else(|| UriError::MalformedParam(pair.to_string()))?;
+                    request.callback = Some(url.to_string());
+                }
+                "msg" => request.msg = Some(value),
+                "origin_domain" => request.origin_domain = Some(value),
+                "signature" | "network_passphrase" => {}
+                other => return Err(UriError::UnknownParam(other.to_string())),
+            }
+        }
+
+        if request.destination.is_empty() {
+            return Err(UriError::MissingDestination);
+        }
+        StrKey::parse(&request.destination).map_err(UriError::InvalidDestination)?;
+        if let Some(amount) = &request.amount {
+            if !parse_amount(amount, STELLAR_DECIMALS).is_ok_and(|value| value > 0) {
+                return Err(UriError::InvalidAmount);
+            }
+        }
+        request.asset = match (asset_code, asset_issuer) {
+            (None, None) => PaymentAsset::Native,
+            (Some(code), Some(issuer)) => {
+                parse_account(&issuer).map_err(UriError::InvalidIssuer)?;
+                PaymentAsset::Credit { code, issuer }
+            }
+            _ => return Err(UriError::IncompleteAsset),
+        };
+        if let Some(memo) = memo {
+            if memo_type.as_deref() != Some("MEMO_HASH") {
+                return Err(UriError::InvalidMemo);
+            }
+            let bytes = STANDARD.decode(memo).map_err(|_| UriError::InvalidMemo)?;
+            request.memo_hash = Some(bytes.try_into().map_err(|_| UriError::InvalidMemo)?);
+        }
+
+        Ok(request)
+    }
+}
+
+/// Verify the trailing `signature` of a signed URI against the requester key
+/// and return the parsed request.
+pub fn verify_uri(uri: &str, key: &VerifyingKey) -> Result<PaymentRequest, UriError> {
+    let (unsigned, encoded) = uri.rsplit_once("&signature=").ok_or(UriError::Unsigned)?;
+    let decoded = percent_decode(encoded).ok_or(UriError::BadSignature)?;
+    let bytes: [u8; 64] = STANDARD
+        .decode(decoded)
+        .map_err(|_| UriError::BadSignature)?
+        .try_into()
+        .map_err(|_| UriError::BadSignature)?;
+
+    key.verify(&signing_payload(unsigned), &Signature::from_bytes(&bytes))
+        .map_err(|_| UriError::BadSignature)?;
+
+    PaymentRequest::parse(uri)
+}
+
+/// SEP-7 signing payload for an unsigned URI
+fn signing_payload(unsigned_uri: &str) -> Vec<u8> {
+    let mut payload = SIGNATURE_PREFIX.to_vec();
+    payload.extend_from_slice(SIGNATURE_DOMAIN);
+    payload.extend_from_slice(unsigned_uri.as_bytes());
+    payload
+}
+
+/// Percent-encode everything outside the RFC 3986 unreserved set
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
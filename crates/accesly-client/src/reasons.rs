@@ -0,0 +1,686 @@
+// ---------------------------------------------------------------------------
+// Event reasons
+//
+// User-facing explanations of on-chain actions, keyed by the event code the
+// contracts publish as their first topic (`acceslyinterface::events`). The
+// contracts only emit codes, so the wording lives here and every app and
+// support tool shows the same sentence. A test checks the table covers
+// every code the contracts define.
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Lang {
+    En,
+    Es,
+}
+
+impl Lang {
+    /// Language for a BCP 47 tag such as `es-MX`; English unless the
+    /// primary subtag is Spanish
+    pub fn from_tag(tag: &str) -> Self {
+        let primary = tag.split(['-', '_']).next().unwrap_or_default();
+        if primary.eq_ignore_ascii_case("es") {
+            Lang::Es
+        } else {
+            Lang::En
+        }
+    }
+}
+
+pub struct Reason {
+    pub code: &'static str,
+    pub en: &'static str,
+    pub es: &'static str,
+}
+
+pub const REASONS: &[Reason] = &[
+    Reason {
+        code: "wallet_created",
+        en: "Wallet created",
+        es: "Billetera creada",
+    },
+    Reason {
+        code: "auth_success",
+        en: "Transaction approved",
+        es: "Transacción aprobada",
+    },
+    Reason {
+        code: "key_rotated",
+        en: "Wallet key changed",
+        es: "Se cambió la llave de la billetera",
+    },
+    Reason {
+        code: "rotation_delay",
+        en: "Waiting period for key changes updated",
+        es: "Se actualizó el tiempo de espera para cambiar la llave",
+    },
+    Reason {
+        code: "rotation_proposed",
+        en: "Key change requested; it takes effect after the waiting period",
+        es: "Se solicitó un cambio de llave; se aplicará al terminar el tiempo de espera",
+    },
+    Reason {
+        code: "rotation_cancelled",
+        en: "Key change cancelled from another device",
+        es: "Se canceló el cambio de llave desde otro dispositivo",
+    },
+    Reason {
+        code: "passkey_changed",
+        en: "Passkey updated",
+        es: "Se actualizó la llave de acceso",
+    },
+    Reason {
+        code: "signer_changed",
+        en: "Approving devices updated",
+        es: "Se actualizaron los dispositivos que aprueban",
+    },
+    Reason {
+        code: "threshold_changed",
+        en: "Number of approvals required updated",
+        es: "Se actualizó el número de aprobaciones requeridas",
+    },
+    Reason {
+        code: "guardian_changed",
+        en: "Recovery contacts updated",
+        es: "Se actualizaron los contactos de recuperación",
+    },
+    Reason {
+        code: "guardian_threshold",
+        en: "Number of recovery contacts required updated",
+        es: "Se actualizó el número de contactos de recuperación requeridos",
+    },
+    Reason {
+        code: "guardian_delay",
+        en: "Waiting period for changing recovery contacts updated",
+        es: "Se actualizó el tiempo de espera para cambiar los contactos de recuperación",
+    },
+    Reason {
+        code: "guardian_change_proposed",
+        en: "A change to your recovery contacts was requested; it can be cancelled during the waiting period",
+        es: "Se solicitó un cambio en tus contactos de recuperación; puede cancelarse durante el tiempo de espera",
+    },
+    Reason {
+        code: "guardian_change_cancelled",
+        en: "A change to your recovery contacts was cancelled",
+        es: "Se canceló un cambio en tus contactos de recuperación",
+    },
+    Reason {
+        code: "guardian_rotated",
+        en: "A recovery contact replaced their key",
+        es: "Un contacto de recuperación reemplazó su clave",
+    },
+    Reason {
+        code: "recovery_initiated",
+        en: "Wallet recovery started by a recovery contact",
+        es: "Un contacto de recuperación inició la recuperación de la billetera",
+    },
+    Reason {
+        code: "recovery_approved",
+        en: "A recovery contact approved the wallet recovery",
+        es: "Un contacto de recuperación aprobó la recuperación de la billetera",
+    },
+    Reason {
+        code: "recovery_vetoed",
+        en: "Wallet recovery blocked by the owner",
+        es: "El dueño bloqueó la recuperación de la billetera",
+    },
+    Reason {
+        code: "verifier_set",
+        en: "Email recovery settings updated",
+        es: "Se actualizó la recuperación por correo",
+    },
+    Reason {
+        code: "email_recovery",
+        en: "Wallet recovered by email",
+        es: "Billetera recuperada por correo",
+    },
+    Reason {
+        code: "recovery_cooldown",
+        en: "Spending limited for a while after recovery",
+        es: "Gastos limitados por un tiempo tras la recuperación",
+    },
+    Reason {
+        code: "recovery_cooldown_lifted",
+        en: "A guardian lifted the spending limit after recovery",
+        es: "Un guardián levantó el límite de gastos tras la recuperación",
+    },
+    Reason {
+        code: "recovery_cooldown_config",
+        en: "Spending limit after recovery updated",
+        es: "Se actualizó el límite de gastos tras la recuperación",
+    },
+    Reason {
+        code: "email_rotated",
+        en: "Wallet email address changed",
+        es: "Se cambió el correo electrónico de la billetera",
+    },
+    Reason {
+        code: "fragment_release",
+        en: "New device allowed to restore the wallet key",
+        es: "Se permitió a un nuevo dispositivo restaurar la llave de la billetera",
+    },
+    Reason {
+        code: "session_created",
+        en: "App connected with limited permissions",
+        es: "Se conectó una app con permisos limitados",
+    },
+    Reason {
+        code: "session_derived",
+        en: "App passed on part of its permissions to another app",
+        es: "Una app cedió parte de sus permisos a otra app",
+    },
+    Reason {
+        code: "session_revoked",
+        en: "App disconnected",
+        es: "Se desconectó una app",
+    },
+    Reason {
+        code: "sessions_revoked",
+        en: "All connected apps disconnected",
+        es: "Se desconectaron todas las apps",
+    },
+    Reason {
+        code: "session_auth",
+        en: "Transaction approved by a connected app",
+        es: "Transacción aprobada por una app conectada",
+    },
+    Reason {
+        code: "deposit_binding_set",
+        en: "Deposit account linked",
+        es: "Se vinculó una cuenta de depósito",
+    },
+    Reason {
+        code: "deposit_binding_removed",
+        en: "Deposit account unlinked",
+        es: "Se desvinculó una cuenta de depósito",
+    },
+    Reason {
+        code: "virtual_id_registered",
+        en: "Payment reference created",
+        es: "Se creó una referencia de pago",
+    },
+    Reason {
+        code: "virtual_payment",
+        en: "Payment received through a reference",
+        es: "Pago recibido mediante una referencia",
+    },
+    Reason {
+        code: "shares_set",
+        en: "Payment split updated",
+        es: "Se actualizó la distribución de pagos",
+    },
+    Reason {
+        code: "distribution",
+        en: "Funds split among recipients",
+        es: "Fondos repartidos entre los destinatarios",
+    },
+    Reason {
+        code: "quote_accepted",
+        en: "Exchange completed at the quoted rate",
+        es: "Cambio realizado a la tasa cotizada",
+    },
+    Reason {
+        code: "conversion",
+        en: "Incoming funds converted automatically",
+        es: "Fondos recibidos convertidos automáticamente",
+    },
+    Reason {
+        code: "conversion_rule",
+        en: "Automatic conversion rule changed",
+        es: "Cambió una regla de conversión automática",
+    },
+    Reason {
+        code: "lending",
+        en: "Funds moved in a lending pool",
+        es: "Movimiento de fondos en un fondo de préstamos",
+    },
+    Reason {
+        code: "batch_executed",
+        en: "Several operations executed together",
+        es: "Varias operaciones ejecutadas en conjunto",
+    },
+    Reason {
+        code: "batch_op",
+        en: "Result of one operation in a batch",
+        es: "Resultado de una operación dentro de un lote",
+    },
+    Reason {
+        code: "batch_limits",
+        en: "Batch limits updated",
+        es: "Límites de lote actualizados",
+    },
+    Reason {
+        code: "op_weight",
+        en: "Batch operation weight updated",
+        es: "Peso de operación de lote actualizado",
+    },
+    Reason {
+        code: "contract_invoked",
+        en: "Contract called from the wallet",
+        es: "Contrato llamado desde la billetera",
+    },
+    Reason {
+        code: "allowlist_mode",
+        en: "Contract allowlist turned on or off",
+        es: "Lista de contratos permitidos activada o desactivada",
+    },
+    Reason {
+        code: "contract_listing",
+        en: "Contract allowed or blocked for the wallet",
+        es: "Contrato permitido o bloqueado para la billetera",
+    },
+    Reason {
+        code: "contract_blocked",
+        en: "Call to a contract blocked by the wallet's policy",
+        es: "La política de la billetera bloqueó una llamada a un contrato",
+    },
+    Reason {
+        code: "asset_display",
+        en: "Display name or decimals of an asset changed",
+        es: "Cambió el nombre o los decimales con que se muestra un activo",
+    },
+    Reason {
+        code: "change_proposed",
+        en: "A recently added key proposed a security change",
+        es: "Una llave agregada recientemente propuso un cambio de seguridad",
+    },
+    Reason {
+        code: "change_approved",
+        en: "A security change was approved by an older key",
+        es: "Una llave anterior aprobó un cambio de seguridad",
+    },
+    Reason {
+        code: "device_added",
+        en: "New device linked to the wallet",
+        es: "Se vinculó un nuevo dispositivo a la billetera",
+    },
+    Reason {
+        code: "device_removed",
+        en: "Device unlinked from the wallet, confirmed from that device",
+        es: "Se desvinculó un dispositivo de la billetera, con confirmación desde ese dispositivo",
+    },
+    Reason {
+        code: "device_removal_queued",
+        en: "A device will be unlinked after a waiting period; it can cancel until then",
+        es: "Un dispositivo se desvinculará después de un tiempo de espera; hasta entonces puede cancelarlo",
+    },
+    Reason {
+        code: "device_removal_executed",
+        en: "Device unlinked from the wallet after the waiting period",
+        es: "Se desvinculó un dispositivo de la billetera después del tiempo de espera",
+    },
+    Reason {
+        code: "device_removal_cancelled",
+        en: "Device unlinking cancelled from that device",
+        es: "Se canceló la desvinculación desde el propio dispositivo",
+    },
+    Reason {
+        code: "read_access_granted",
+        en: "Support given temporary read-only access",
+        es: "Se dio a soporte acceso temporal de solo lectura",
+    },
+    Reason {
+        code: "read_access_revoked",
+        en: "Support read-only access revoked",
+        es: "Se revocó el acceso de solo lectura de soporte",
+    },
+    Reason {
+        code: "upgrade_proposed",
+        en: "Wallet update scheduled",
+        es: "Se programó una actualización de la billetera",
+    },
+    Reason {
+        code: "upgrade_cancelled",
+        en: "Scheduled wallet update cancelled",
+        es: "Se canceló la actualización programada de la billetera",
+    },
+    Reason {
+        code: "wallet_upgraded",
+        en: "Wallet updated to new code",
+        es: "La billetera se actualizó a un nuevo código",
+    },
+    Reason {
+        code: "storage_migrated",
+        en: "Wallet storage migrated to a new version",
+        es: "Almacenamiento de la billetera migrado a una nueva versión",
+    },
+    Reason {
+        code: "wallet_frozen",
+        en: "Wallet frozen",
+        es: "Billetera congelada",
+    },
+    Reason {
+        code: "wallet_unfrozen",
+        en: "Wallet unfrozen",
+        es: "Billetera descongelada",
+    },
+    Reason {
+        code: "key_compromised",
+        en: "A key was reported compromised and the wallet was frozen",
+        es: "Se reportó una clave comprometida y se congeló la billetera",
+    },
+    Reason {
+        code: "quarantine_released",
+        en: "A quarantined key was released",
+        es: "Se liberó una clave en cuarentena",
+    },
+    Reason {
+        code: "preauth_granted",
+        en: "Transaction approved in advance",
+        es: "Transacción aprobada por adelantado",
+    },
+    Reason {
+        code: "preauth_revoked",
+        en: "Advance approval withdrawn",
+        es: "Se retiró la aprobación anticipada",
+    },
+    Reason {
+        code: "sponsorship_recorded",
+        en: "Network fees covered by a sponsor",
+        es: "Un patrocinador cubrió las comisiones de red",
+    },
+    Reason {
+        code: "sponsor_repaid",
+        en: "Sponsored fees repaid",
+        es: "Se devolvieron las comisiones patrocinadas",
+    },
+    Reason {
+        code: "receipts_committed",
+        en: "Receipts recorded on-chain",
+        es: "Recibos registrados en la red",
+    },
+    Reason {
+        code: "snapshot_assets",
+        en: "Assets covered by balance snapshots updated",
+        es: "Se actualizaron los activos incluidos en las instantáneas de saldo",
+    },
+    Reason {
+        code: "balance_snapshot",
+        en: "Balance snapshot recorded on-chain",
+        es: "Instantánea de saldos registrada en la red",
+    },
+    Reason {
+        code: "notify_filter",
+        en: "Notification preferences updated",
+        es: "Se actualizaron las preferencias de notificación",
+    },
+    Reason {
+        code: "attestor_granted",
+        en: "Third party allowed to add a credential",
+        es: "Se permitió a un tercero agregar una credencial",
+    },
+    Reason {
+        code: "attestor_revoked",
+        en: "Third party can no longer add credentials",
+        es: "Un tercero ya no puede agregar credenciales",
+    },
+    Reason {
+        code: "attestation_recorded",
+        en: "Credential added to the wallet",
+        es: "Se agregó una credencial a la billetera",
+    },
+    Reason {
+        code: "relayers_set",
+        en: "Relayers allowed to submit for the wallet changed",
+        es: "Se cambiaron los relayers autorizados a enviar por la billetera",
+    },
+    Reason {
+        code: "meta_tx_executed",
+        en: "Action submitted for the wallet by its relayer",
+        es: "El relayer envió una acción en nombre de la billetera",
+    },
+    Reason {
+        code: "auth_policy_set",
+        en: "Signatures required for a wallet setting changed",
+        es: "Se cambiaron las firmas requeridas para un ajuste de la billetera",
+    },
+    Reason {
+        code: "action_cosigned",
+        en: "Another device approved a wallet setting change",
+        es: "Otro dispositivo aprobó un cambio de ajustes de la billetera",
+    },
+    Reason {
+        code: "allowance_approved",
+        en: "An app was allowed to charge the wallet",
+        es: "Se autorizó a una app a cobrar de la billetera",
+    },
+    Reason {
+        code: "allowance_revoked",
+        en: "An app can no longer charge the wallet",
+        es: "Una app ya no puede cobrar de la billetera",
+    },
+    Reason {
+        code: "allowance_pulled",
+        en: "An app charged the wallet under its allowance",
+        es: "Una app cobró de la billetera según su autorización",
+    },
+    Reason {
+        code: "subscription_created",
+        en: "Recurring payment set up",
+        es: "Se configuró un pago recurrente",
+    },
+    Reason {
+        code: "subscription_paused",
+        en: "Recurring payment paused or resumed",
+        es: "Se pausó o reanudó un pago recurrente",
+    },
+    Reason {
+        code: "subscription_cancelled",
+        en: "Recurring payment cancelled",
+        es: "Se canceló un pago recurrente",
+    },
+    Reason {
+        code: "subscription_collected",
+        en: "Recurring payment charged",
+        es: "Se cobró un pago recurrente",
+    },
+    Reason {
+        code: "subscription_missed",
+        en: "A recurring payment was missed",
+        es: "No se realizó un pago recurrente",
+    },
+    Reason {
+        code: "tx_scheduled",
+        en: "Transaction scheduled for later",
+        es: "Se programó una transacción para más adelante",
+    },
+    Reason {
+        code: "scheduled_executed",
+        en: "Scheduled transaction sent",
+        es: "Se envió una transacción programada",
+    },
+    Reason {
+        code: "scheduled_cancelled",
+        en: "Scheduled transaction cancelled",
+        es: "Se canceló una transacción programada",
+    },
+    Reason {
+        code: "large_transfer_threshold",
+        en: "Limit for transfers that need a waiting period changed",
+        es: "Cambió el límite de transferencias que requieren un tiempo de espera",
+    },
+    Reason {
+        code: "large_transfer_queued",
+        en: "Large transfer requested; it goes out after the waiting period",
+        es: "Se solicitó una transferencia grande; se enviará al terminar el tiempo de espera",
+    },
+    Reason {
+        code: "large_transfer_executed",
+        en: "Large transfer sent",
+        es: "Se envió una transferencia grande",
+    },
+    Reason {
+        code: "large_transfer_cancelled",
+        en: "Large transfer cancelled from another device",
+        es: "Se canceló una transferencia grande desde otro dispositivo",
+    },
+    Reason {
+        code: "contact_added",
+        en: "Contact saved; transfers to it are allowed after a waiting period",
+        es: "Se guardó un contacto; podrás enviarle transferencias después de un tiempo de espera",
+    },
+    Reason {
+        code: "contact_removed",
+        en: "Contact removed",
+        es: "Se eliminó un contacto",
+    },
+    Reason {
+        code: "contacts_only",
+        en: "Contacts-only mode for transfers changed",
+        es: "Cambió el modo de transferencias solo a contactos",
+    },
+    Reason {
+        code: "velocity_limit",
+        en: "Limit on how many operations the wallet can authorize per period changed",
+        es: "Cambió el límite de operaciones que la billetera puede autorizar por periodo",
+    },
+    Reason {
+        code: "velocity_bypass",
+        en: "Extra operations allowed over the limit, approved from two devices",
+        es: "Se permitieron operaciones adicionales sobre el límite, aprobadas desde dos dispositivos",
+    },
+    Reason {
+        code: "oracle_set",
+        en: "Price source for spending limits changed",
+        es: "Cambió la fuente de precios de los límites de gasto",
+    },
+    Reason {
+        code: "fiat_limit",
+        en: "Daily spending limit changed",
+        es: "Cambió el límite de gasto diario",
+    },
+    Reason {
+        code: "preset_applied",
+        en: "Wallet created with a recommended set of security limits",
+        es: "Billetera creada con un conjunto recomendado de límites de seguridad",
+    },
+    Reason {
+        code: "own_account_added",
+        en: "Account linked as your own; moving money to it won't count against your limits after a waiting period",
+        es: "Se vinculó una cuenta como tuya; mover dinero a ella no contará en tus límites después de un tiempo de espera",
+    },
+    Reason {
+        code: "own_account_removed",
+        en: "Account unlinked from your own accounts",
+        es: "Se desvinculó una cuenta de tus cuentas propias",
+    },
+    Reason {
+        code: "internal_transfer",
+        en: "Money moved between your own accounts",
+        es: "Se movió dinero entre tus cuentas propias",
+    },
+    Reason {
+        code: "dev_set_nonce",
+        en: "Test wallet: counter reset",
+        es: "Billetera de prueba: se reinició el contador",
+    },
+    Reason {
+        code: "dev_fast_forward",
+        en: "Test wallet: clock moved forward",
+        es: "Billetera de prueba: se adelantó el reloj",
+    },
+    Reason {
+        code: "wallet_deployed",
+        en: "Wallet activated on the network",
+        es: "Billetera activada en la red",
+    },
+    Reason {
+        code: "wallet_wasm_updated",
+        en: "New wallets will use an updated version",
+        es: "Las nuevas billeteras usarán una versión actualizada",
+    },
+    Reason {
+        code: "app_registered",
+        en: "App allowed to create wallets",
+        es: "Aplicación autorizada para crear billeteras",
+    },
+    Reason {
+        code: "manifest_published",
+        en: "Capabilities of a wallet version published",
+        es: "Capacidades de una versión de billetera publicadas",
+    },
+    Reason {
+        code: "watch_added",
+        en: "Account added to the watchlist",
+        es: "Cuenta agregada a la lista de seguimiento",
+    },
+    Reason {
+        code: "watch_removed",
+        en: "Account removed from the watchlist",
+        es: "Cuenta eliminada de la lista de seguimiento",
+    },
+    Reason {
+        code: "wallet_linked",
+        en: "Wallet linked to your other wallets",
+        es: "Billetera vinculada a tus otras billeteras",
+    },
+    Reason {
+        code: "wallet_unlinked",
+        en: "Wallet unlinked from your other wallets",
+        es: "Billetera desvinculada de tus otras billeteras",
+    },
+    Reason {
+        code: "wallet_registered",
+        en: "Wallet listed in the public directory",
+        es: "Billetera registrada en el directorio público",
+    },
+    Reason {
+        code: "owner_synced",
+        en: "Directory updated with the wallet's new key",
+        es: "Se actualizó el directorio con la nueva llave de la billetera",
+    },
+    Reason {
+        code: "email_synced",
+        en: "Directory updated with the wallet's new email",
+        es: "Se actualizó el directorio con el nuevo correo de la billetera",
+    },
+    Reason {
+        code: "attestor_rotated",
+        en: "Identity verifier changed",
+        es: "Se cambió el verificador de identidad",
+    },
+    Reason {
+        code: "kyc_status",
+        en: "Identity verification status updated",
+        es: "Se actualizó el estado de verificación de identidad",
+    },
+    Reason {
+        code: "paymaster_funded",
+        en: "Fee sponsorship funds added",
+        es: "Se agregaron fondos para patrocinar comisiones",
+    },
+    Reason {
+        code: "paymaster_withdrawn",
+        en: "Fee sponsorship funds withdrawn",
+        es: "Se retiraron fondos de patrocinio de comisiones",
+    },
+    Reason {
+        code: "relayer_changed",
+        en: "Relayer allowed to spend sponsorship budgets changed",
+        es: "Se cambió un relayer autorizado a usar presupuestos de patrocinio",
+    },
+    Reason {
+        code: "budget_set",
+        en: "App fee sponsorship budget updated",
+        es: "Se actualizó el presupuesto de patrocinio de comisiones de la app",
+    },
+    Reason {
+        code: "fee_sponsored",
+        en: "Network fee paid by the app",
+        es: "La app pagó la comisión de red",
+    },
+    Reason {
+        code: "oracle_feed_set",
+        en: "Price feed for an asset changed",
+        es: "Cambió la fuente de precios de un activo",
+    },
+];
+
+/// Localized explanation of the event with `code`, `None` for unknown codes
+pub fn describe(code: &str, lang: Lang) -> Option<&'static str> {
+    let reason = REASONS.iter().find(|r| r.code == code)?;
+    Some(match lang {
+        Lang::En => reason.en,
+        Lang::Es => reason.es,
+    })
+}
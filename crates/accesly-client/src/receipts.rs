@@ -0,0 +1,141 @@
+// ---------------------------------------------------------------------------
+// Receipt Merkle trees
+//
+// Builds the per-epoch receipts tree whose root the wallet stores with
+// `commit_receipts_root`, and proves/verifies individual receipts against it.
+//
+// Hashing is domain separated (0x00 for leaves, 0x01 for inner nodes) and an
+// odd node is promoted to the next level unchanged rather than duplicated,
+// so two different receipt lists can never share a root.
+// ---------------------------------------------------------------------------
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+/// One wallet operation as exported in a statement.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Receipt {
+    pub tx_hash: [u8; 32],
+    pub ledger: u32,
+    pub op_index: u32,
+    /// Asset contract (C...) or "native"
+    pub asset: String,
+    pub amount: i128,
+    pub counterparty: String,
+}
+
+impl Receipt {
+    /// Canonical byte encoding: fixed-width integers big-endian, strings
+    /// prefixed with their u32 length.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(96 + self.asset.len() + self.counterparty.len());
+        out.extend_from_slice(&self.tx_hash);
+        out.extend_from_slice(&self.ledger.to_be_bytes());
+        out.extend_from_slice(&self.op_index.to_be_bytes());
+        out.extend_from_slice(&(self.asset.len() as u32).to_be_bytes());
+        out.extend_from_slice(self.asset.as_bytes());
+        out.extend_from_slice(&self.amount.to_be_bytes());
+        out.extend_from_slice(&(self.counterparty.len() as u32).to_be_bytes());
+        out.extend_from_slice(self.counterparty.as_bytes());
+        out
+    }
+
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([LEAF_TAG]);
+        hasher.update(self.canonical_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Sibling on the path from a leaf to the root.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProofStep {
+    Left([u8; 32]),
+    Right([u8; 32]),
+}
+
+pub struct ReceiptTree {
+    /// levels[0] are the leaves, the last level holds only the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl ReceiptTree {
+    /// Build the tree for an epoch. Returns `None` for an empty epoch.
+    pub fn build(receipts: &[Receipt]) -> Option<Self> {
+        if receipts.is_empty() {
+            return None;
+        }
+
+        let mut levels = vec![receipts.iter().map(Receipt::leaf_hash).collect::<Vec<_>>()];
+        while levels.last().map_or(0, Vec::len) > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Some(Self { levels })
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Inclusion proof for the receipt at `index`
+    pub fn proof(&self, mut index: usize) -> Option<Vec<ProofStep>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let mut steps = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                steps.push(if sibling < index {
+                    ProofStep::Left(level[sibling])
+                } else {
+                    ProofStep::Right(level[sibling])
+                });
+            }
+            index /= 2;
+        }
+        Some(steps)
+    }
+}
+
+/// Check that `receipt` is included under `root` (as read from the wallet's
+/// `get_receipts_root`).
+pub fn verify_receipt(receipt: &Receipt, proof: &[ProofStep], root: &[u8; 32]) -> bool {
+    let computed = proof.iter().fold(receipt.leaf_hash(), |acc, step| match step {
+        ProofStep::Left(sibling) => node_hash(sibling, &acc),
+        ProofStep::Right(sibling) => node_hash(&acc, sibling),
+    });
+    &computed == root
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_TAG]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
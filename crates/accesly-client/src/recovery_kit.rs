@@ -0,0 +1,310 @@
+// ---------------------------------------------------------------------------
+// Offline recovery kits
+//
+// A paper fallback for a user who loses every device. The kit holds what a
+// recovery needs besides the guardians' cooperation: the wallet address, the
+// guardian set and threshold, and the F3 fragment exactly as the backend
+// keeps it (already PBKDF2-encrypted, see `email_fragments`). It also records
+// the owner key and email hash it was made against, so `check_kit` can tell
+// when a rotation or guardian change has left the paper stale.
+//
+// The JSON kit is sealed in the snapshot container layout under its own
+// magic and printed as armored base64:
+//
+//   ACCESLY RECOVERY KIT
+//   Wallet: C...
+//   <emergency instructions>
+//   -----BEGIN ACCESLY RECOVERY KIT-----
+//   <64 base64 chars> <line checksum>
+//   ...
+//   -----END ACCESLY RECOVERY KIT-----
+//   Digest: <sha256 of the container, first 16 bytes>
+//
+// Someone typing the block back in gets told which line is wrong by its
+// checksum, and the digest catches lines dropped or swapped, before the
+// password is even asked for. Anything past that fails the AES-GCM tag.
+// ---------------------------------------------------------------------------
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::snapshot::{open_bytes, seal_bytes, SnapshotError, DEFAULT_KDF_ROUNDS};
+
+const MAGIC: &[u8; 6] = b"ACRKIT";
+/// Version of the JSON document inside the container
+pub const KIT_VERSION: u32 = 1;
+
+const TITLE: &str = "ACCESLY RECOVERY KIT";
+const BEGIN: &str = "-----BEGIN ACCESLY RECOVERY KIT-----";
+const END: &str = "-----END ACCESLY RECOVERY KIT-----";
+const LINE_WIDTH: usize = 64;
+/// Bytes of sha256 printed after each line and in the digest
+const LINE_CHECKSUM_LEN: usize = 2;
+const DIGEST_LEN: usize = 16;
+
+/// Printed in the clear above the encrypted block
+pub const EMERGENCY_INSTRUCTIONS: &[&str] = &[
+    "This sheet restores access to the wallet above if every device is lost.",
+    "Keep it offline and store the kit password somewhere else.",
+    "If a device may have been stolen, freeze the wallet first from a guardian or another device.",
+    "To recover, start a recovery from a new device and ask the guardians in this kit to approve it.",
+    "Once the recovery delay has passed, enter this kit and its password to rebuild the key.",
+    "After changing guardians or email, run `accesly-cli recovery-kit verify` and print a new kit if it reports differences.",
+];
+
+#[derive(Debug)]
+pub enum KitError {
+    /// Missing armor, bad base64 or a misplaced line
+    InvalidFormat,
+    /// Line of the encrypted block (1-based) whose checksum doesn't match
+    LineChecksum(usize),
+    /// Every line checks out but the block as a whole doesn't
+    DigestMismatch,
+    /// The printed wallet address isn't the one sealed in the kit
+    WalletMismatch,
+    UnsupportedVersion(u32),
+    /// Wrong password, bad container or invalid document
+    Container(SnapshotError),
+}
+
+impl std::fmt::Display for KitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "not a recovery kit"),
+            Self::LineChecksum(line) => write!(f, "line {line} of the kit was mistyped"),
+            Self::DigestMismatch => write!(f, "kit lines are missing or out of order"),
+            Self::WalletMismatch => write!(f, "printed wallet does not match the kit"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported kit version {version}"),
+            Self::Container(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for KitError {}
+
+impl From<SnapshotError> for KitError {
+    fn from(err: SnapshotError) -> Self {
+        Self::Container(err)
+    }
+}
+
+impl From<serde_json::Error> for KitError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Container(SnapshotError::Json(err))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Kit document
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GuardianRecord {
+    /// Public key, hex
+    Ed25519(String),
+    /// Uncompressed SEC1 P-256 public key, hex
+    Secp256r1(String),
+}
+
+/// The parts of a wallet's on-chain state a kit depends on
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryState {
+    /// Owner ed25519 public key, hex
+    pub owner: String,
+    /// Email hash, hex
+    pub email_hash: String,
+    pub guardians: Vec<GuardianRecord>,
+    pub guardian_threshold: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryKit {
+    pub version: u32,
+    /// Wallet contract address (C...)
+    pub wallet: String,
+    pub created_at_ledger: u32,
+    pub state: RecoveryState,
+    /// F3 fragment as held by the backend, base64
+    pub f3_container: String,
+}
+
+impl RecoveryKit {
+    pub fn new(wallet: &str, ledger: u32, state: RecoveryState, f3_container: &[u8]) -> Self {
+        Self {
+            version: KIT_VERSION,
+            wallet: wallet.to_string(),
+            created_at_ledger: ledger,
+            state,
+            f3_container: STANDARD.encode(f3_container),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Printing and reading
+// ---------------------------------------------------------------------------
+
+/// Encrypt `kit` under `password` and render the printable sheet
+pub fn seal_kit(kit: &RecoveryKit, password: &str) -> Result<String, KitError> {
+    seal_kit_with_rounds(kit, password, DEFAULT_KDF_ROUNDS)
+}
+
+pub fn seal_kit_with_rounds(
+    kit: &RecoveryKit,
+    password: &str,
+    rounds: u32,
+) -> Result<String, KitError> {
+    let container = seal_bytes(MAGIC, &serde_json::to_vec(kit)?, password, rounds)?;
+    let body = STANDARD.encode(&container);
+
+    let mut sheet = format!("{TITLE}\nWallet: {}\n\n", kit.wallet);
+    for (i, instruction) in EMERGENCY_INSTRUCTIONS.iter().enumerate() {
+        sheet.push_str(&format!("{}. {instruction}\n", i + 1));
+    }
+    sheet.push('\n');
+    sheet.push_str(BEGIN);
+    sheet.push('\n');
+    // base64 is ASCII, so byte chunks are whole characters
+    for line in body.as_bytes().chunks(LINE_WIDTH) {
+        let line = std::str::from_utf8(line).unwrap();
+        sheet.push_str(&format!(
+            "{line} {}\n",
+            checksum(line.as_bytes(), LINE_CHECKSUM_LEN)
+        ));
+    }
+    sheet.push_str(END);
+    sheet.push('\n');
+    sheet.push_str(&format!("Digest: {}\n", checksum(&container, DIGEST_LEN)));
+
+    Ok(sheet)
+}
+
+/// Check and decrypt a sheet produced by `seal_kit`. Whitespace and the
+/// case of checksums don't matter, so a retyped sheet reads back.
+pub fn open_kit(sheet: &str, password: &str) -> Result<RecoveryKit, KitError> {
+    let lines: Vec<&str> = sheet.lines().map(str::trim).collect();
+    let begin = lines
+        .iter()
+        .position(|l| *l == BEGIN)
+        .ok_or(KitError::InvalidFormat)?;
+    let end = lines
+        .iter()
+        .position(|l| *l == END)
+        .ok_or(KitError::InvalidFormat)?;
+    if end <= begin {
+        return Err(KitError::InvalidFormat);
+    }
+
+    let mut body = String::new();
+    for (i, line) in lines[begin + 1..end].iter().enumerate() {
+        let (data, sum) = line.rsplit_once(' ').ok_or(KitError::InvalidFormat)?;
+        let data = data.trim_end();
+        if !sum.eq_ignore_ascii_case(&checksum(data.as_bytes(), LINE_CHECKSUM_LEN)) {
+            return Err(KitError::LineChecksum(i + 1));
+        }
+        body.push_str(data);
+    }
+    let container = STANDARD.decode(body).map_err(|_| KitError::InvalidFormat)?;
+
+    let digest = lines[end + 1..]
+        .iter()
+        .find_map(|l| l.strip_prefix("Digest:"))
+        .ok_or(KitError::InvalidFormat)?;
+    if !digest
+        .trim()
+        .eq_ignore_ascii_case(&checksum(&container, DIGEST_LEN))
+    {
+        return Err(KitError::DigestMismatch);
+    }
+
+    let kit: RecoveryKit = serde_json::from_slice(&open_bytes(MAGIC, &container, password)?)?;
+    if kit.version != KIT_VERSION {
+        return Err(KitError::UnsupportedVersion(kit.version));
+    }
+    // The clear-text address is what the user reads, so it must not lie
+    let printed = lines[..begin]
+        .iter()
+        .find_map(|l| l.strip_prefix("Wallet:"))
+        .map(str::trim);
+    if printed != Some(kit.wallet.as_str()) {
+        return Err(KitError::WalletMismatch);
+    }
+
+    Ok(kit)
+}
+
+fn checksum(data: &[u8], len: usize) -> String {
+    hex(&Sha256::digest(data)[..len])
+}
+
+/// Lowercase hex, as kits and snapshots store keys and hashes
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Verification against the chain
+// ---------------------------------------------------------------------------
+
+/// A way the wallet has moved on since the kit was printed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KitMismatch {
+    /// The key was rotated, so the F3 fragment no longer rebuilds it
+    OwnerChanged,
+    /// Recovery email changed; the F3 fragment is bound to the old one
+    EmailHashChanged,
+    GuardianThresholdChanged {
+        kit: u32,
+        chain: u32,
+    },
+    /// Guardian listed in the kit that can no longer approve
+    GuardianRemoved(GuardianRecord),
+    /// Guardian the kit doesn't list
+    GuardianAdded(GuardianRecord),
+}
+
+impl std::fmt::Display for KitMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OwnerChanged => write!(f, "owner key was rotated"),
+            Self::EmailHashChanged => write!(f, "recovery email changed"),
+            Self::GuardianThresholdChanged { kit, chain } => {
+                write!(f, "guardian threshold is {chain}, kit says {kit}")
+            }
+            Self::GuardianRemoved(guardian) => write!(f, "guardian {guardian:?} was removed"),
+            Self::GuardianAdded(guardian) => write!(f, "guardian {guardian:?} is not in the kit"),
+        }
+    }
+}
+
+/// Differences between `kit` and the wallet's current `chain` state; empty
+/// when the kit is still good
+pub fn check_kit(kit: &RecoveryKit, chain: &RecoveryState) -> Vec<KitMismatch> {
+    let mut mismatches = Vec::new();
+
+    if kit.state.owner != chain.owner {
+        mismatches.push(KitMismatch::OwnerChanged);
+    }
+    if kit.state.email_hash != chain.email_hash {
+        mismatches.push(KitMismatch::EmailHashChanged);
+    }
+    if kit.state.guardian_threshold != chain.guardian_threshold {
+        mismatches.push(KitMismatch::GuardianThresholdChanged {
+            kit: kit.state.guardian_threshold,
+            chain: chain.guardian_threshold,
+        });
+    }
+    for guardian in &kit.state.guardians {
+        if !chain.guardians.contains(guardian) {
+            mismatches.push(KitMismatch::GuardianRemoved(guardian.clone()));
+        }
+    }
+    for guardian in &chain.guardians {
+        if !kit.state.guardians.contains(guardian) {
+            mismatches.push(KitMismatch::GuardianAdded(guardian.clone()));
+        }
+    }
+
+    mismatches
+}
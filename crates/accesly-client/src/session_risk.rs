@@ -0,0 +1,241 @@
+// ---------------------------------------------------------------------------
+// Session risk summaries
+//
+// Before the owner grants a session over a contract the app doesn't know,
+// the app simulates a few representative calls the dApp would make (with
+// `accesly_sdk::Accesly::assess_session`, which never submits anything) and
+// shows what they did:
+//
+//   - the auth tree: every invocation each call asks the wallet to sign for,
+//     including ones the dApp's contract makes on the wallet's behalf;
+//   - asset movements: token transfers, mints and burns from the
+//     simulation's events.
+//
+// `RiskSummary::new` turns those simulations into flags the UI can put in
+// front of the owner, and `hash` commits to the whole summary. The hash is
+// passed to the wallet's `create_session`, signed with the session and kept
+// beside it, so what the owner consented to can be checked later.
+// ---------------------------------------------------------------------------
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Version of the summary document the hash is taken over
+pub const SUMMARY_VERSION: u32 = 1;
+
+/// A call a session would be used for
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProbeCall {
+    /// Contract address (C...)
+    pub contract: String,
+    pub function: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Invocation {
+    Call {
+        contract: String,
+        function: String,
+    },
+    /// A contract deployment
+    Deploy,
+}
+
+/// One invocation in an auth tree and the ones it authorizes below it
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuthNode {
+    pub invocation: Invocation,
+    pub sub_invocations: Vec<AuthNode>,
+}
+
+/// Authorization a call needs from one address
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuthRequest {
+    /// Address that has to sign (G... or C...), or `None` for the
+    /// transaction source
+    pub address: Option<String>,
+    pub root: AuthNode,
+}
+
+/// Tokens moved by a call. `from` is `None` for a mint, `to` for a burn.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AssetMovement {
+    /// Token contract (C...)
+    pub asset: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub amount: i128,
+}
+
+/// What simulating one representative call showed
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedCall {
+    pub call: ProbeCall,
+    pub auth: Vec<AuthRequest>,
+    pub movements: Vec<AssetMovement>,
+    /// Host error when the call failed in simulation
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RiskFlag {
+    /// The wallet signs for an invocation other than the call itself, e.g.
+    /// a token transfer a router makes from the wallet
+    IndirectWalletAuth { contract: String, function: String },
+    /// Total leaving the wallet in one asset across the probed calls
+    WalletOutflow { asset: String, amount: i128 },
+    /// The wallet signs for a contract deployment
+    Deployment,
+    /// The call failed in simulation, so what it does is unknown
+    SimulationFailed { contract: String, function: String },
+}
+
+impl std::fmt::Display for RiskFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IndirectWalletAuth { contract, function } => {
+                write!(f, "the wallet also authorizes {function} on {contract}")
+            }
+            Self::WalletOutflow { asset, amount } => {
+                write!(f, "{amount} of {asset} leaves the wallet")
+            }
+            Self::Deployment => write!(f, "the wallet authorizes deploying a contract"),
+            Self::SimulationFailed { contract, function } => {
+                write!(f, "{function} on {contract} could not be simulated")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum RiskLevel {
+    /// The calls touch nothing beyond themselves
+    Low,
+    /// Indirect authorizations, or calls whose effects are unknown
+    Medium,
+    /// Funds leave the wallet or contracts get deployed
+    High,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RiskSummary {
+    pub version: u32,
+    /// Wallet the calls were simulated for (C...)
+    pub wallet: String,
+    pub calls: Vec<SimulatedCall>,
+    pub flags: Vec<RiskFlag>,
+}
+
+impl RiskSummary {
+    /// Summarize the simulated `calls` from `wallet`'s point of view
+    pub fn new(wallet: &str, calls: Vec<SimulatedCall>) -> Self {
+        let mut flags = Vec::new();
+        for simulated in &calls {
+            let call = &simulated.call;
+            if simulated.error.is_some() {
+                push_flag(
+                    &mut flags,
+                    RiskFlag::SimulationFailed {
+                        contract: call.contract.clone(),
+                        function: call.function.clone(),
+                    },
+                );
+            }
+            for request in &simulated.auth {
+                if request.address.as_deref() == Some(wallet) {
+                    wallet_auth_flags(&request.root, call, &mut flags);
+                }
+            }
+            for movement in &simulated.movements {
+                if movement.from.as_deref() == Some(wallet) {
+                    add_outflow(&mut flags, &movement.asset, movement.amount);
+                }
+            }
+        }
+
+        Self {
+            version: SUMMARY_VERSION,
+            wallet: wallet.to_string(),
+            calls,
+            flags,
+        }
+    }
+
+    pub fn level(&self) -> RiskLevel {
+        self.flags
+            .iter()
+            .map(|flag| match flag {
+                RiskFlag::WalletOutflow { .. } | RiskFlag::Deployment => RiskLevel::High,
+                RiskFlag::IndirectWalletAuth { .. } | RiskFlag::SimulationFailed { .. } => {
+                    RiskLevel::Medium
+                }
+            })
+            .max()
+            .unwrap_or(RiskLevel::Low)
+    }
+
+    /// sha256 of the summary's JSON, the hash `create_session` stores
+    pub fn hash(&self) -> [u8; 32] {
+        // Plain structs and vectors serialize in a fixed order
+        Sha256::digest(serde_json::to_vec(self).unwrap()).into()
+    }
+}
+
+fn push_flag(flags: &mut Vec<RiskFlag>, flag: RiskFlag) {
+    if !flags.contains(&flag) {
+        flags.push(flag);
+    }
+}
+
+fn add_outflow(flags: &mut Vec<RiskFlag>, asset: &str, amount: i128) {
+    for flag in flags.iter_mut() {
+        if let RiskFlag::WalletOutflow {
+            asset: a,
+            amount: total,
+        } = flag
+        {
+            if a == asset {
+                *total = total.saturating_add(amount);
+                return;
+            }
+        }
+    }
+    flags.push(RiskFlag::WalletOutflow {
+        asset: asset.to_string(),
+        amount,
+    });
+}
+
+/// Flag everything in the wallet's auth tree besides `call` itself
+fn wallet_auth_flags(node: &AuthNode, call: &ProbeCall, flags: &mut Vec<RiskFlag>) {
+    match &node.invocation {
+        Invocation::Deploy => push_flag(flags, RiskFlag::Deployment),
+        Invocation::Call { contract, function }
+            if *contract != call.contract || *function != call.function =>
+        {
+            push_flag(
+                flags,
+                RiskFlag::IndirectWalletAuth {
+                    contract: contract.clone(),
+                    function: function.clone(),
+                },
+            )
+        }
+        Invocation::Call { .. } => {}
+    }
+    for sub in &node.sub_invocations {
+        wallet_auth_flags(sub, call, flags);
+    }
+}
+
+/// Contracts among `calls` that aren't in `known`, the ones a session
+/// should be assessed for before it is granted
+pub fn unknown_contracts<'a>(calls: &'a [ProbeCall], known: &[&str]) -> Vec<&'a str> {
+    let mut unknown: Vec<&str> = Vec::new();
+    for call in calls {
+        if !known.contains(&call.contract.as_str()) && !unknown.contains(&call.contract.as_str()) {
+            unknown.push(&call.contract);
+        }
+    }
+    unknown
+}
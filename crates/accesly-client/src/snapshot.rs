@@ -0,0 +1,313 @@
+// ---------------------------------------------------------------------------
+// Encrypted account snapshots
+//
+// Data-portability export of a wallet: its configuration (owner, deposit
+// bindings, virtual ids, committed receipt roots) plus the operation history.
+// `collect` pulls everything through a `SnapshotSource` that follows the
+// wallet's paged listing views, `seal` packs the result into a versioned
+// password-encrypted container and `open` reverses it. `restore_plan` lists
+// the owner-signed calls needed to carry the settings over to a new wallet.
+//
+// Container layout (all integers big-endian):
+//
+//   magic "ACSNAP" | version u8 | kdf u8 | rounds u32 | salt [16] | nonce [12]
+//   | AES-256-GCM(json snapshot)
+//
+// The header is authenticated as associated data, so the KDF parameters
+// can't be downgraded without the open failing. The encryption and key
+// derivation are accesly-fragment-crypto's, which frames fragments the same
+// way.
+// ---------------------------------------------------------------------------
+
+use accesly_fragment_crypto::{open_with_header, seal_with_header, Kdf};
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::receipts::Receipt;
+
+const MAGIC: &[u8; 6] = b"ACSNAP";
+pub const CONTAINER_VERSION: u8 = 1;
+const KDF_PBKDF2_SHA256: u8 = 1;
+pub const DEFAULT_KDF_ROUNDS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 1 + 4 + SALT_LEN + NONCE_LEN;
+
+/// Version of the JSON document inside the container
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The source failed while pulling wallet state
+    Source(String),
+    /// Not an Accesly snapshot container
+    InvalidFormat,
+    /// Container or snapshot version this build can't read
+    UnsupportedVersion(u32),
+    /// Wrong password, or the container was modified
+    DecryptionFailed,
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Source(err) => write!(f, "snapshot source error: {err}"),
+            Self::InvalidFormat => write!(f, "not an account snapshot"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {version}")
+            }
+            Self::DecryptionFailed => write!(f, "wrong password or corrupted snapshot"),
+            Self::Json(err) => write!(f, "invalid snapshot document: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Snapshot document
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletConfig {
+    /// Owner ed25519 public key, hex
+    pub owner: String,
+    /// Email hash the wallet was created with, hex
+    pub email_hash: String,
+    pub nonce: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DepositBindingRecord {
+    /// sha256 of the anchor home domain, hex
+    pub anchor_domain_hash: String,
+    pub memo_hash: String,
+    pub route: String,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VirtualIdRecord {
+    pub virtual_id: u64,
+    /// Payer or invoice reference, hex
+    pub reference: String,
+    pub label: String,
+    pub payment_count: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptsRootRecord {
+    pub epoch: u64,
+    pub root: String,
+    pub receipt_count: u32,
+    pub ledger: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalletSnapshot {
+    pub version: u32,
+    /// Wallet contract address (C...)
+    pub wallet: String,
+    pub taken_at_ledger: u32,
+    pub config: WalletConfig,
+    pub deposit_bindings: Vec<DepositBindingRecord>,
+    pub virtual_ids: Vec<VirtualIdRecord>,
+    pub receipts_roots: Vec<ReceiptsRootRecord>,
+    pub history: Vec<Receipt>,
+}
+
+// ---------------------------------------------------------------------------
+// Collection
+// ---------------------------------------------------------------------------
+
+/// Read access to a wallet's state. Backends implement this on top of RPC
+/// simulation; the page methods mirror the wallet's `list_*` views and return
+/// the next cursor, if any.
+pub trait SnapshotSource {
+    fn latest_ledger(&self) -> Result<u32, SnapshotError>;
+    fn config(&self) -> Result<WalletConfig, SnapshotError>;
+    fn deposit_bindings(
+        &self,
+        cursor: u32,
+    ) -> Result<(Vec<DepositBindingRecord>, Option<u32>), SnapshotError>;
+    fn virtual_ids(
+        &self,
+        cursor: u32,
+    ) -> Result<(Vec<VirtualIdRecord>, Option<u32>), SnapshotError>;
+    fn receipts_roots(
+        &self,
+        cursor: u32,
+    ) -> Result<(Vec<ReceiptsRootRecord>, Option<u32>), SnapshotError>;
+    /// Full operation history, oldest first
+    fn history(&self) -> Result<Vec<Receipt>, SnapshotError>;
+}
+
+/// Pull the full configuration and history of `wallet` from `source`
+pub fn collect(
+    wallet: &str,
+    source: &impl SnapshotSource,
+) -> Result<WalletSnapshot, SnapshotError> {
+    Ok(WalletSnapshot {
+        version: SNAPSHOT_VERSION,
+        wallet: wallet.to_string(),
+        taken_at_ledger: source.latest_ledger()?,
+        config: source.config()?,
+        deposit_bindings: drain_pages(|cursor| source.deposit_bindings(cursor))?,
+        virtual_ids: drain_pages(|cursor| source.virtual_ids(cursor))?,
+        receipts_roots: drain_pages(|cursor| source.receipts_roots(cursor))?,
+        history: source.history()?,
+    })
+}
+
+fn drain_pages<T>(
+    mut page: impl FnMut(u32) -> Result<(Vec<T>, Option<u32>), SnapshotError>,
+) -> Result<Vec<T>, SnapshotError> {
+    let mut items = Vec::new();
+    let mut cursor = 0;
+    loop {
+        let (mut batch, next_cursor) = page(cursor)?;
+        items.append(&mut batch);
+        match next_cursor {
+            // A cursor that doesn't move forward would loop forever
+            Some(next) if next > cursor => cursor = next,
+            Some(_) => return Err(SnapshotError::Source("page cursor did not advance".into())),
+            None => return Ok(items),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Container
+// ---------------------------------------------------------------------------
+
+/// Encrypt `snapshot` under `password` with the default KDF cost
+pub fn seal(snapshot: &WalletSnapshot, password: &str) -> Result<Vec<u8>, SnapshotError> {
+    seal_with_rounds(snapshot, password, DEFAULT_KDF_ROUNDS)
+}
+
+pub fn seal_with_rounds(
+    snapshot: &WalletSnapshot,
+    password: &str,
+    rounds: u32,
+) -> Result<Vec<u8>, SnapshotError> {
+    seal_bytes(MAGIC, &serde_json::to_vec(snapshot)?, password, rounds)
+}
+
+/// Decrypt and parse a container produced by `seal`
+pub fn open(container: &[u8], password: &str) -> Result<WalletSnapshot, SnapshotError> {
+    let plaintext = open_bytes(MAGIC, container, password)?;
+    let snapshot: WalletSnapshot = serde_json::from_slice(&plaintext)?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+    }
+    Ok(snapshot)
+}
+
+/// Encrypt `plaintext` into a container tagged with `magic`. Other
+/// documents (recovery kits) share the layout under their own magic.
+pub(crate) fn seal_bytes(
+    magic: &[u8; 6],
+    plaintext: &[u8],
+    password: &str,
+    rounds: u32,
+) -> Result<Vec<u8>, SnapshotError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(magic);
+    header.push(CONTAINER_VERSION);
+    header.push(KDF_PBKDF2_SHA256);
+    header.extend_from_slice(&rounds.to_be_bytes());
+    header.extend_from_slice(&salt);
+
+    let key = derive_key(password, &salt, rounds)?;
+    seal_with_header(header, &key, plaintext).map_err(|_| SnapshotError::DecryptionFailed)
+}
+
+/// Decrypt a container produced by `seal_bytes` with the same `magic`
+pub(crate) fn open_bytes(
+    magic: &[u8; 6],
+    container: &[u8],
+    password: &str,
+) -> Result<Vec<u8>, SnapshotError> {
+    if container.len() < HEADER_LEN || &container[..magic.len()] != magic {
+        return Err(SnapshotError::InvalidFormat);
+    }
+    let (header, ciphertext) = container.split_at(HEADER_LEN);
+
+    let version = header[6];
+    if version != CONTAINER_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version.into()));
+    }
+    if header[7] != KDF_PBKDF2_SHA256 {
+        return Err(SnapshotError::InvalidFormat);
+    }
+    let rounds = u32::from_be_bytes(header[8..12].try_into().unwrap());
+    let salt = &header[12..12 + SALT_LEN];
+
+    let key = derive_key(password, salt, rounds)?;
+    open_with_header(header, ciphertext, &key)
+        .map(|plaintext| plaintext.to_vec())
+        .map_err(|_| SnapshotError::DecryptionFailed)
+}
+
+/// PBKDF2-HMAC-SHA256 key, refusing round counts past the fragment
+/// containers' ceiling so a forged header can't stall the open
+fn derive_key(password: &str, salt: &[u8], rounds: u32) -> Result<Zeroizing<[u8; 32]>, SnapshotError> {
+    Kdf::Pbkdf2Sha256 { rounds }
+        .derive_key(password.as_bytes(), salt)
+        .map_err(|_| SnapshotError::InvalidFormat)
+}
+
+// ---------------------------------------------------------------------------
+// Restore
+// ---------------------------------------------------------------------------
+
+/// Owner-signed call needed to bring a new wallet in line with a snapshot
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RestoreAction {
+    SetDepositBinding(DepositBindingRecord),
+    /// Ids are derived from the wallet address, so the new wallet gets a
+    /// new id for the same reference
+    RegisterVirtualId {
+        reference: String,
+        label: String,
+    },
+}
+
+/// Settings in `snapshot` that `target` (a snapshot of the new wallet) is
+/// missing or has with different values. Owner, history and receipt roots
+/// belong to the old wallet and are not carried over.
+pub fn restore_plan(snapshot: &WalletSnapshot, target: &WalletSnapshot) -> Vec<RestoreAction> {
+    let mut actions = Vec::new();
+
+    for binding in &snapshot.deposit_bindings {
+        if !target.deposit_bindings.contains(binding) {
+            actions.push(RestoreAction::SetDepositBinding(binding.clone()));
+        }
+    }
+
+    for virtual_id in &snapshot.virtual_ids {
+        let registered = target
+            .virtual_ids
+            .iter()
+            .any(|existing| existing.reference == virtual_id.reference);
+        if !registered {
+            actions.push(RestoreAction::RegisterVirtualId {
+                reference: virtual_id.reference.clone(),
+                label: virtual_id.label.clone(),
+            });
+        }
+    }
+
+    actions
+}
@@ -0,0 +1,230 @@
+// ---------------------------------------------------------------------------
+// StrKey addresses
+//
+// Strict parsing of the three address forms users hand us: accounts (G...),
+// contracts (C...) and muxed accounts (M...). A StrKey is base32 over
+// `version || payload || crc16`, and a typo almost always still decodes, so
+// the checksum, the version byte, the payload length and the unused tail
+// bits are all checked. Anything that doesn't round-trip to the exact input
+// is rejected, which rules out lowercase and padded variants too.
+//
+// Validating up front turns a malformed destination into a readable error
+// instead of a host trap at submission.
+// ---------------------------------------------------------------------------
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+const VERSION_ACCOUNT: u8 = 6 << 3;
+const VERSION_MUXED_ACCOUNT: u8 = 12 << 3;
+const VERSION_CONTRACT: u8 = 2 << 3;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StrKeyError {
+    /// A character outside the base32 alphabet
+    InvalidCharacter(char),
+    /// Not a G, C or M key
+    UnknownVersion(char),
+    /// Wrong length for the key's version
+    InvalidLength,
+    /// The trailing CRC16 does not match
+    BadChecksum,
+    /// Decodes, but is not how the key is normally written
+    NonCanonical,
+    /// A valid key of the wrong kind, e.g. an account where a contract is expected
+    UnexpectedKind {
+        expected: StrKeyKind,
+        found: StrKeyKind,
+    },
+}
+
+impl std::fmt::Display for StrKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrKeyError::InvalidCharacter(c) => write!(f, "invalid address: unexpected {c:?}"),
+            StrKeyError::UnknownVersion(c) => {
+                write!(f, "invalid address: unknown prefix {c:?}")
+            }
+            StrKeyError::InvalidLength => write!(f, "invalid address: wrong length"),
+            StrKeyError::BadChecksum => write!(f, "invalid address: checksum mismatch"),
+            StrKeyError::NonCanonical => write!(f, "invalid address: not canonically encoded"),
+            StrKeyError::UnexpectedKind { expected, found } => {
+                write!(f, "expected {expected}, got {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StrKeyError {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StrKeyKind {
+    Account,
+    MuxedAccount,
+    Contract,
+}
+
+impl std::fmt::Display for StrKeyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            StrKeyKind::Account => "an account (G...)",
+            StrKeyKind::MuxedAccount => "a muxed account (M...)",
+            StrKeyKind::Contract => "a contract (C...)",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StrKey {
+    /// ed25519 public key of a classic account
+    Account([u8; 32]),
+    /// Classic account plus a 64-bit sub-account id
+    MuxedAccount { key: [u8; 32], id: u64 },
+    /// Contract id
+    Contract([u8; 32]),
+}
+
+impl StrKey {
+    pub fn parse(s: &str) -> Result<Self, StrKeyError> {
+        let bytes = base32_decode(s)?;
+        if bytes.len() < 3 {
+            return Err(StrKeyError::InvalidLength);
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 2);
+        let (version, payload) = (body[0], &body[1..]);
+
+        let key = match version {
+            VERSION_ACCOUNT | VERSION_CONTRACT if payload.len() == 32 => {
+                let raw: [u8; 32] = payload.try_into().unwrap();
+                if version == VERSION_ACCOUNT {
+                    StrKey::Account(raw)
+                } else {
+                    StrKey::Contract(raw)
+                }
+            }
+            VERSION_MUXED_ACCOUNT if payload.len() == 40 => StrKey::MuxedAccount {
+                key: payload[..32].try_into().unwrap(),
+                id: u64::from_be_bytes(payload[32..].try_into().unwrap()),
+            },
+            VERSION_ACCOUNT | VERSION_CONTRACT | VERSION_MUXED_ACCOUNT => {
+                return Err(StrKeyError::InvalidLength)
+            }
+            _ => return Err(StrKeyError::UnknownVersion(s.chars().next().unwrap_or('?'))),
+        };
+
+        if crc16_xmodem(body).to_le_bytes() != checksum {
+            return Err(StrKeyError::BadChecksum);
+        }
+        if key.to_string() != s {
+            return Err(StrKeyError::NonCanonical);
+        }
+        Ok(key)
+    }
+
+    pub fn kind(&self) -> StrKeyKind {
+        match self {
+            StrKey::Account(_) => StrKeyKind::Account,
+            StrKey::MuxedAccount { .. } => StrKeyKind::MuxedAccount,
+            StrKey::Contract(_) => StrKeyKind::Contract,
+        }
+    }
+
+    fn expect(self, expected: StrKeyKind) -> Result<Self, StrKeyError> {
+        if self.kind() != expected {
+            return Err(StrKeyError::UnexpectedKind {
+                expected,
+                found: self.kind(),
+            });
+        }
+        Ok(self)
+    }
+}
+
+impl std::fmt::Display for StrKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = Vec::with_capacity(43);
+        match self {
+            StrKey::Account(key) => {
+                bytes.push(VERSION_ACCOUNT);
+                bytes.extend_from_slice(key);
+            }
+            StrKey::MuxedAccount { key, id } => {
+                bytes.push(VERSION_MUXED_ACCOUNT);
+                bytes.extend_from_slice(key);
+                bytes.extend_from_slice(&id.to_be_bytes());
+            }
+            StrKey::Contract(id) => {
+                bytes.push(VERSION_CONTRACT);
+                bytes.extend_from_slice(id);
+            }
+        }
+        bytes.extend_from_slice(&crc16_xmodem(&bytes).to_le_bytes());
+        f.write_str(&base32_encode(&bytes))
+    }
+}
+
+/// Parse a `G...` account address into its ed25519 public key
+pub fn parse_account(s: &str) -> Result<[u8; 32], StrKeyError> {
+    match StrKey::parse(s)?.expect(StrKeyKind::Account)? {
+        StrKey::Account(key) => Ok(key),
+        _ => unreachable!(),
+    }
+}
+
+/// Parse a `C...` contract address into its 32-byte id
+pub fn parse_contract(s: &str) -> Result<[u8; 32], StrKeyError> {
+    match StrKey::parse(s)?.expect(StrKeyKind::Contract)? {
+        StrKey::Contract(id) => Ok(id),
+        _ => unreachable!(),
+    }
+}
+
+fn base32_decode(s: &str) -> Result<Vec<u8>, StrKeyError> {
+    let mut bytes = Vec::with_capacity(s.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in s.chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or(StrKeyError::InvalidCharacter(c))? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+    out
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
@@ -0,0 +1,121 @@
+// ---------------------------------------------------------------------------
+// Support read tokens
+//
+// A user lets Accesly support look at their wallet configuration by
+// generating a `ReadToken`, granting its hash with the wallet's
+// `grant_read_access`, and giving support the token. The support backend
+// checks the token with `verify_read_token`: it hashes the token and asks
+// the wallet, through a `GrantSource`, whether that hash holds an unexpired
+// grant. The token itself never goes on-chain.
+//
+// Nothing is cached: a revoke on-chain must cut access off on the very
+// next check.
+// ---------------------------------------------------------------------------
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+
+const TOKEN_TAG: &[u8] = b"accesly-read-token";
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ReadAccessError {
+    /// The source failed while reading wallet state
+    Source(String),
+    /// The token isn't 32 base64url-encoded bytes
+    Malformed,
+    /// No grant for the token, or it was revoked
+    NotGranted,
+    Expired,
+}
+
+impl std::fmt::Display for ReadAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Source(err) => write!(f, "grant source error: {err}"),
+            Self::Malformed => write!(f, "malformed read token"),
+            Self::NotGranted => write!(f, "read token not granted or revoked"),
+            Self::Expired => write!(f, "read token expired"),
+        }
+    }
+}
+
+impl std::error::Error for ReadAccessError {}
+
+/// The secret a user hands to support
+#[derive(Clone, Eq, PartialEq)]
+pub struct ReadToken([u8; 32]);
+
+impl ReadToken {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// Parse the unpadded base64url form produced by `encode`
+    pub fn parse(token: &str) -> Result<Self, ReadAccessError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .map_err(|_| ReadAccessError::Malformed)?;
+        Ok(Self(
+            bytes.try_into().map_err(|_| ReadAccessError::Malformed)?,
+        ))
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.0)
+    }
+
+    /// What goes into `grant_read_access`: sha256(tag || token)
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::new()
+            .chain_update(TOKEN_TAG)
+            .chain_update(self.0)
+            .finalize()
+            .into()
+    }
+}
+
+// Keep the secret out of logs
+impl std::fmt::Debug for ReadToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReadToken(..)")
+    }
+}
+
+/// A grant as `get_read_grant` returns it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReadGrant {
+    pub granted_at: u64,
+    pub expires_at: u64,
+}
+
+/// Reads grants, normally by simulating `get_read_grant` over RPC
+pub trait GrantSource {
+    fn read_grant(
+        &self,
+        wallet: &str,
+        token_hash: &[u8; 32],
+    ) -> Result<Option<ReadGrant>, ReadAccessError>;
+}
+
+/// Check that `token` currently grants read access to `wallet` at unix time
+/// `now`, returning the grant
+pub fn verify_read_token(
+    source: &impl GrantSource,
+    wallet: &str,
+    token: &str,
+    now: u64,
+) -> Result<ReadGrant, ReadAccessError> {
+    let token = ReadToken::parse(token)?;
+    let grant = source
+        .read_grant(wallet, &token.hash())?
+        .ok_or(ReadAccessError::NotGranted)?;
+    // The wallet filters expired grants by ledger time; check against the
+    // backend's clock too, since the ledger can lag
+    if now > grant.expires_at {
+        return Err(ReadAccessError::Expired);
+    }
+    Ok(grant)
+}
@@ -0,0 +1,168 @@
+// ---------------------------------------------------------------------------
+// Contract WASM metadata
+//
+// Soroban contracts carry a `contractmetav0` custom section: a stream of XDR
+// `ScMetaEntry` values, each a key/value string pair. The SDK writes the
+// toolchain (`rsver`) and SDK version (`rssdkver`) there, and `contractmeta!`
+// adds our own entries. Reading it needs no XDR library: only the section
+// framing and the two length-prefixed strings.
+// ---------------------------------------------------------------------------
+
+const WASM_MAGIC: &[u8; 4] = b"\0asm";
+const WASM_VERSION: &[u8; 4] = &[1, 0, 0, 0];
+const CUSTOM_SECTION_ID: u8 = 0;
+pub const CONTRACT_META_SECTION: &str = "contractmetav0";
+const SC_META_V0: u32 = 0;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WasmMetaError {
+    /// Not a WASM module
+    NotWasm,
+    /// A section or entry runs past the end of the module
+    Truncated,
+    /// A meta entry of a kind this parser doesn't know
+    UnknownEntryKind(u32),
+    /// A key or value is not UTF-8
+    InvalidString,
+}
+
+impl std::fmt::Display for WasmMetaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid contract wasm: {self:?}")
+    }
+}
+
+impl std::error::Error for WasmMetaError {}
+
+/// All `contractmetav0` entries in `wasm`, in the order they appear.
+/// Contracts without the section yield an empty list.
+pub fn read_contract_meta(wasm: &[u8]) -> Result<Vec<(String, String)>, WasmMetaError> {
+    let mut entries = Vec::new();
+    for payload in custom_sections(wasm, CONTRACT_META_SECTION)? {
+        let mut reader = Reader { data: payload };
+        while !reader.data.is_empty() {
+            let kind = reader.u32_be()?;
+            if kind != SC_META_V0 {
+                return Err(WasmMetaError::UnknownEntryKind(kind));
+            }
+            let key = reader.xdr_string()?;
+            let val = reader.xdr_string()?;
+            entries.push((key, val));
+        }
+    }
+    Ok(entries)
+}
+
+/// Build identity written by the wallet's build script, plus the toolchain
+/// the SDK records. Fields are None for WASM built before the entries
+/// existed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuildInfo {
+    pub version: Option<String>,
+    /// Git commit, suffixed with `-dirty` for builds from a modified tree
+    pub commit: Option<String>,
+    /// Enabled cargo features
+    pub features: Vec<String>,
+    pub rust_version: Option<String>,
+    pub sdk_version: Option<String>,
+}
+
+impl BuildInfo {
+    pub fn from_meta(entries: &[(String, String)]) -> Self {
+        let value = |key| meta_value(entries, key).map(str::to_string);
+        Self {
+            version: value("version"),
+            commit: value("commit"),
+            features: meta_value(entries, "features")
+                .map(|list| {
+                    list.split(',')
+                        .filter(|f| !f.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            rust_version: value("rsver"),
+            sdk_version: value("rssdkver"),
+        }
+    }
+
+    pub fn from_wasm(wasm: &[u8]) -> Result<Self, WasmMetaError> {
+        Ok(Self::from_meta(&read_contract_meta(wasm)?))
+    }
+
+    /// Built from a tree with uncommitted changes, so not reproducible
+    pub fn is_dirty(&self) -> bool {
+        self.commit.as_deref().is_some_and(|c| c.ends_with("-dirty"))
+    }
+}
+
+/// First value stored under `key`
+pub fn meta_value<'a>(entries: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    entries
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Payloads of every custom section called `name`
+fn custom_sections<'a>(wasm: &'a [u8], name: &str) -> Result<Vec<&'a [u8]>, WasmMetaError> {
+    if wasm.len() < 8 || &wasm[..4] != WASM_MAGIC || &wasm[4..8] != WASM_VERSION {
+        return Err(WasmMetaError::NotWasm);
+    }
+
+    let mut reader = Reader { data: &wasm[8..] };
+    let mut sections = Vec::new();
+    while !reader.data.is_empty() {
+        let id = reader.take(1)?[0];
+        let size = reader.leb_u32()? as usize;
+        let mut content = Reader {
+            data: reader.take(size)?,
+        };
+        if id == CUSTOM_SECTION_ID {
+            let name_len = content.leb_u32()? as usize;
+            if content.take(name_len)? == name.as_bytes() {
+                sections.push(content.data);
+            }
+        }
+    }
+    Ok(sections)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], WasmMetaError> {
+        if n > self.data.len() {
+            return Err(WasmMetaError::Truncated);
+        }
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn leb_u32(&mut self) -> Result<u32, WasmMetaError> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(WasmMetaError::Truncated)
+    }
+
+    fn u32_be(&mut self) -> Result<u32, WasmMetaError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// XDR string: u32 length, bytes, zero padding to a multiple of 4
+    fn xdr_string(&mut self) -> Result<String, WasmMetaError> {
+        let len = self.u32_be()? as usize;
+        let bytes = self.take(len)?;
+        self.take((4 - len % 4) % 4)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| WasmMetaError::InvalidString)
+    }
+}
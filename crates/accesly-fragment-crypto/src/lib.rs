@@ -0,0 +1,377 @@
+// ---------------------------------------------------------------------------
+// accesly-fragment-crypto
+//
+// Encryption at rest for the wallet key fragments:
+//
+//   F2  envelope-encrypted under a KMS data key. `GenerateDataKey` gives the
+//       plaintext key, used here and then dropped, and its encrypted blob,
+//       kept in the header so `Decrypt` can recover the key later.
+//   F3  encrypted under a key derived from the recovery email and a random
+//       salt, with PBKDF2-HMAC-SHA256 or Argon2id.
+//
+// Container layout (all integers big-endian):
+//
+//   magic "ACFRAG" | version u8 | scheme u8 | scheme params | nonce [12]
+//   | AES-256-GCM(fragment)
+//
+//   scheme 1, data key   key_len u16 | encrypted data key
+//   scheme 2, PBKDF2     rounds u32 | salt [16]
+//   scheme 3, Argon2id   memory_kib u32 | iterations u32 | parallelism u32
+//                        | salt [16]
+//
+// The whole header is authenticated as associated data. KDF parameters live
+// in it, so they can be raised for new fragments (and old ones re-sealed with
+// `rekey_f3`) without breaking what's already stored.
+//
+// The framing (header | nonce | ciphertext, header and nonce as associated
+// data) and the KDFs are public, so the client's other password-encrypted
+// containers (account snapshots, recovery kits) use them under their own
+// headers.
+// ---------------------------------------------------------------------------
+
+use aes_gcm::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+const MAGIC: &[u8; 6] = b"ACFRAG";
+pub const CONTAINER_VERSION: u8 = 1;
+const SCHEME_DATA_KEY: u8 = 1;
+const SCHEME_PBKDF2_SHA256: u8 = 2;
+const SCHEME_ARGON2ID: u8 = 3;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+// Ceilings on what a header can ask for, so a forged one can't stall a
+// Lambda deriving keys
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+const MAX_ARGON2_MEMORY_KIB: u32 = 1 << 20;
+const MAX_ARGON2_ITERATIONS: u32 = 64;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum FragmentCryptoError {
+    /// Not a fragment container, or a truncated one
+    InvalidFormat,
+    /// Container version or scheme this build can't read
+    UnsupportedVersion(u8),
+    UnsupportedScheme(u8),
+    /// KDF parameters out of range, or the wrong kind of container for the call
+    InvalidParams,
+    /// Wrong key or email, or the container was modified
+    DecryptionFailed,
+}
+
+impl std::fmt::Display for FragmentCryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "not an encrypted fragment"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported fragment container version {version}")
+            }
+            Self::UnsupportedScheme(scheme) => write!(f, "unsupported fragment scheme {scheme}"),
+            Self::InvalidParams => write!(f, "invalid fragment encryption parameters"),
+            Self::DecryptionFailed => write!(f, "wrong key or corrupted fragment"),
+        }
+    }
+}
+
+impl std::error::Error for FragmentCryptoError {}
+
+/// How an F3 key is derived from the email
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kdf {
+    Pbkdf2Sha256 {
+        rounds: u32,
+    },
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+impl Kdf {
+    pub const DEFAULT_PBKDF2: Kdf = Kdf::Pbkdf2Sha256 { rounds: 600_000 };
+    /// OWASP's baseline: 19 MiB, two passes, one lane
+    pub const DEFAULT_ARGON2ID: Kdf = Kdf::Argon2id {
+        memory_kib: 19_456,
+        iterations: 2,
+        parallelism: 1,
+    };
+
+    fn check(&self) -> Result<(), FragmentCryptoError> {
+        let ok = match *self {
+            Kdf::Pbkdf2Sha256 { rounds } => (1..=MAX_PBKDF2_ROUNDS).contains(&rounds),
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                memory_kib <= MAX_ARGON2_MEMORY_KIB
+                    && iterations <= MAX_ARGON2_ITERATIONS
+                    && argon2::Params::new(memory_kib, iterations, parallelism, Some(32)).is_ok()
+            }
+        };
+        ok.then_some(()).ok_or(FragmentCryptoError::InvalidParams)
+    }
+
+    fn derive(&self, email: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, FragmentCryptoError> {
+        let password = Zeroizing::new(normalize_email(email));
+        self.derive_key(password.as_bytes(), salt)
+    }
+
+    /// Derive a 256-bit key from `password` and `salt`, refusing parameters
+    /// past the ceilings
+    pub fn derive_key(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<Zeroizing<[u8; 32]>, FragmentCryptoError> {
+        self.check()?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        match *self {
+            Kdf::Pbkdf2Sha256 { rounds } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, rounds, key.as_mut())
+            }
+            Kdf::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(32))
+                    .map_err(|_| FragmentCryptoError::InvalidParams)?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, key.as_mut())
+                    .map_err(|_| FragmentCryptoError::InvalidParams)?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// A KMS data key, as `GenerateDataKey` returns it
+pub struct DataKey {
+    pub plaintext: Zeroizing<[u8; 32]>,
+    /// `CiphertextBlob`, decryptable only through KMS
+    pub encrypted: Vec<u8>,
+}
+
+/// What a container's header says about how to open it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Header {
+    /// F2: pass `encrypted_key` to KMS `Decrypt`, then call `open_f2`
+    DataKey { encrypted_key: Vec<u8> },
+    /// F3: derived from the email with `kdf`
+    Password { kdf: Kdf, salt: [u8; SALT_LEN] },
+}
+
+/// Read a container's header without decrypting anything
+pub fn inspect(container: &[u8]) -> Result<Header, FragmentCryptoError> {
+    parse(container).map(|(header, _, _)| header)
+}
+
+/// Encrypt F2 under a fresh KMS data key
+pub fn seal_f2(fragment: &[u8], key: &DataKey) -> Result<Vec<u8>, FragmentCryptoError> {
+    let key_len =
+        u16::try_from(key.encrypted.len()).map_err(|_| FragmentCryptoError::InvalidParams)?;
+    let mut header = start_header(SCHEME_DATA_KEY);
+    header.extend_from_slice(&key_len.to_be_bytes());
+    header.extend_from_slice(&key.encrypted);
+    seal_with_header(header, &key.plaintext, fragment)
+}
+
+/// Decrypt F2 with the data key KMS returned for the header's encrypted key
+pub fn open_f2(
+    container: &[u8],
+    plaintext_key: &[u8; 32],
+) -> Result<Zeroizing<Vec<u8>>, FragmentCryptoError> {
+    let (header, aad, rest) = parse(container)?;
+    let Header::DataKey { .. } = header else {
+        return Err(FragmentCryptoError::InvalidParams);
+    };
+    open_with_header(aad, rest, plaintext_key)
+}
+
+/// Encrypt F3 under a key derived from `email` with `kdf`
+pub fn seal_f3(fragment: &[u8], email: &str, kdf: Kdf) -> Result<Vec<u8>, FragmentCryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = kdf.derive(email, &salt)?;
+
+    let mut header = match kdf {
+        Kdf::Pbkdf2Sha256 { rounds } => {
+            let mut header = start_header(SCHEME_PBKDF2_SHA256);
+            header.extend_from_slice(&rounds.to_be_bytes());
+            header
+        }
+        Kdf::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+        } => {
+            let mut header = start_header(SCHEME_ARGON2ID);
+            header.extend_from_slice(&memory_kib.to_be_bytes());
+            header.extend_from_slice(&iterations.to_be_bytes());
+            header.extend_from_slice(&parallelism.to_be_bytes());
+            header
+        }
+    };
+    header.extend_from_slice(&salt);
+    seal_with_header(header, &key, fragment)
+}
+
+pub fn open_f3(container: &[u8], email: &str) -> Result<Zeroizing<Vec<u8>>, FragmentCryptoError> {
+    let (header, aad, rest) = parse(container)?;
+    let Header::Password { kdf, salt } = header else {
+        return Err(FragmentCryptoError::InvalidParams);
+    };
+    let key = kdf.derive(email, &salt)?;
+    open_with_header(aad, rest, &key)
+}
+
+/// Whether an F3 container was sealed with parameters other than `kdf`
+pub fn needs_rekey(container: &[u8], kdf: Kdf) -> Result<bool, FragmentCryptoError> {
+    match inspect(container)? {
+        Header::Password { kdf: current, .. } => Ok(current != kdf),
+        Header::DataKey { .. } => Err(FragmentCryptoError::InvalidParams),
+    }
+}
+
+/// Re-seal an F3 container under new KDF parameters and a fresh salt
+pub fn rekey_f3(container: &[u8], email: &str, kdf: Kdf) -> Result<Vec<u8>, FragmentCryptoError> {
+    let fragment = open_f3(container, email)?;
+    seal_f3(&fragment, email, kdf)
+}
+
+/// The email as the key is derived from it: trimmed and lowercased, so
+/// `Alice@Example.com ` opens what `alice@example.com` sealed
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn start_header(scheme: u8) -> Vec<u8> {
+    let mut header = Vec::with_capacity(64);
+    header.extend_from_slice(MAGIC);
+    header.push(CONTAINER_VERSION);
+    header.push(scheme);
+    header
+}
+
+/// Append a fresh nonce to `header` and the AES-256-GCM encryption of
+/// `plaintext` under `key`, authenticating the header and nonce
+pub fn seal_with_header(
+    mut header: Vec<u8>,
+    key: &[u8; 32],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, FragmentCryptoError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    header.extend_from_slice(&nonce);
+    let ciphertext = Aes256Gcm::new(key.into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| FragmentCryptoError::DecryptionFailed)?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Decrypt what `seal_with_header` produced, split into the header with its
+/// trailing nonce and the ciphertext after it
+pub fn open_with_header(
+    aad: &[u8],
+    ciphertext: &[u8],
+    key: &[u8; 32],
+) -> Result<Zeroizing<Vec<u8>>, FragmentCryptoError> {
+    let nonce_at = aad
+        .len()
+        .checked_sub(NONCE_LEN)
+        .ok_or(FragmentCryptoError::InvalidFormat)?;
+    let nonce = Nonce::from(<[u8; NONCE_LEN]>::try_from(&aad[nonce_at..]).unwrap());
+    Aes256Gcm::new(key.into())
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| FragmentCryptoError::DecryptionFailed)
+}
+
+/// Split a container into its header, the authenticated bytes (header and
+/// nonce) and the ciphertext
+fn parse(container: &[u8]) -> Result<(Header, &[u8], &[u8]), FragmentCryptoError> {
+    let mut reader = Reader {
+        bytes: container,
+        pos: 0,
+    };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(FragmentCryptoError::InvalidFormat);
+    }
+    let version = reader.u8()?;
+    if version != CONTAINER_VERSION {
+        return Err(FragmentCryptoError::UnsupportedVersion(version));
+    }
+
+    let header = match reader.u8()? {
+        SCHEME_DATA_KEY => {
+            let len = u16::from_be_bytes(reader.take(2)?.try_into().unwrap());
+            Header::DataKey {
+                encrypted_key: reader.take(len.into())?.to_vec(),
+            }
+        }
+        SCHEME_PBKDF2_SHA256 => Header::Password {
+            kdf: Kdf::Pbkdf2Sha256 {
+                rounds: reader.u32()?,
+            },
+            salt: reader.take(SALT_LEN)?.try_into().unwrap(),
+        },
+        SCHEME_ARGON2ID => Header::Password {
+            kdf: Kdf::Argon2id {
+                memory_kib: reader.u32()?,
+                iterations: reader.u32()?,
+                parallelism: reader.u32()?,
+            },
+            salt: reader.take(SALT_LEN)?.try_into().unwrap(),
+        },
+        scheme => return Err(FragmentCryptoError::UnsupportedScheme(scheme)),
+    };
+    reader.take(NONCE_LEN)?;
+
+    let (aad, ciphertext) = container.split_at(reader.pos);
+    Ok((header, aad, ciphertext))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FragmentCryptoError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(FragmentCryptoError::InvalidFormat)?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, FragmentCryptoError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, FragmentCryptoError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,128 @@
+// src/test.rs
+
+use super::*;
+
+const FRAGMENT: &[u8] = b"\x01\x02\x02fragment bytes";
+// Fast parameters; the defaults are far too slow for a test run
+const PBKDF2: Kdf = Kdf::Pbkdf2Sha256 { rounds: 1_000 };
+const ARGON2ID: Kdf = Kdf::Argon2id {
+    memory_kib: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+fn data_key() -> DataKey {
+    DataKey {
+        plaintext: Zeroizing::new([7u8; 32]),
+        encrypted: b"kms ciphertext blob".to_vec(),
+    }
+}
+
+#[test]
+fn test_f2_round_trip() {
+    let container = seal_f2(FRAGMENT, &data_key()).unwrap();
+    assert_eq!(
+        inspect(&container).unwrap(),
+        Header::DataKey {
+            encrypted_key: b"kms ciphertext blob".to_vec()
+        }
+    );
+    assert_eq!(
+        open_f2(&container, &[7u8; 32]).unwrap().as_slice(),
+        FRAGMENT
+    );
+    assert_eq!(
+        open_f2(&container, &[8u8; 32]).unwrap_err(),
+        FragmentCryptoError::DecryptionFailed
+    );
+}
+
+#[test]
+fn test_f3_round_trip_with_either_kdf() {
+    for kdf in [PBKDF2, ARGON2ID] {
+        let container = seal_f3(FRAGMENT, "alice@example.com", kdf).unwrap();
+        let opened = open_f3(&container, " Alice@Example.COM").unwrap();
+        assert_eq!(opened.as_slice(), FRAGMENT);
+        assert_eq!(
+            open_f3(&container, "bob@example.com").unwrap_err(),
+            FragmentCryptoError::DecryptionFailed
+        );
+        // Wrong kind of container for the call
+        assert_eq!(
+            open_f2(&container, &[7u8; 32]).unwrap_err(),
+            FragmentCryptoError::InvalidParams
+        );
+    }
+}
+
+#[test]
+fn test_header_is_authenticated() {
+    let mut container = seal_f3(FRAGMENT, "alice@example.com", PBKDF2).unwrap();
+    // Lower the rounds in the header: the key changes and so does the AAD
+    container[11] ^= 1;
+    assert_eq!(
+        open_f3(&container, "alice@example.com").unwrap_err(),
+        FragmentCryptoError::DecryptionFailed
+    );
+
+    let mut container = seal_f2(FRAGMENT, &data_key()).unwrap();
+    container[10] ^= 1;
+    assert_eq!(
+        open_f2(&container, &[7u8; 32]).unwrap_err(),
+        FragmentCryptoError::DecryptionFailed
+    );
+}
+
+#[test]
+fn test_rekey_moves_to_new_parameters() {
+    let container = seal_f3(FRAGMENT, "alice@example.com", PBKDF2).unwrap();
+    assert!(needs_rekey(&container, ARGON2ID).unwrap());
+    assert!(!needs_rekey(&container, PBKDF2).unwrap());
+
+    let rekeyed = rekey_f3(&container, "alice@example.com", ARGON2ID).unwrap();
+    assert!(matches!(
+        inspect(&rekeyed).unwrap(),
+        Header::Password { kdf, .. } if kdf == ARGON2ID
+    ));
+    assert_eq!(
+        open_f3(&rekeyed, "alice@example.com").unwrap().as_slice(),
+        FRAGMENT
+    );
+}
+
+#[test]
+fn test_rejects_bad_containers() {
+    let container = seal_f2(FRAGMENT, &data_key()).unwrap();
+    assert_eq!(
+        inspect(b"ACSNAP").unwrap_err(),
+        FragmentCryptoError::InvalidFormat
+    );
+    assert_eq!(
+        inspect(&container[..20]).unwrap_err(),
+        FragmentCryptoError::InvalidFormat
+    );
+
+    let mut future = container.clone();
+    future[6] = 2;
+    assert_eq!(
+        inspect(&future).unwrap_err(),
+        FragmentCryptoError::UnsupportedVersion(2)
+    );
+    let mut unknown = container;
+    unknown[7] = 9;
+    assert_eq!(
+        inspect(&unknown).unwrap_err(),
+        FragmentCryptoError::UnsupportedScheme(9)
+    );
+
+    // A header can't demand an unbounded key derivation
+    let greedy = Kdf::Argon2id {
+        memory_kib: MAX_ARGON2_MEMORY_KIB + 1,
+        iterations: 1,
+        parallelism: 1,
+    };
+    assert_eq!(
+        seal_f3(FRAGMENT, "alice@example.com", greedy).unwrap_err(),
+        FragmentCryptoError::InvalidParams
+    );
+}
@@ -0,0 +1,141 @@
+#![no_std]
+
+// ---------------------------------------------------------------------------
+// accesly-messages
+//
+// The byte strings owner keys sign, in one place. The wallet builds them into
+// `soroban_sdk::Bytes` to verify; the SDK and tooling build them into a
+// `Vec<u8>` to sign. Both go through the functions below, so a layout change
+// lands on both sides at once:
+//
+//   owner actions    action || payload || nonce
+//   __check_auth     signature_payload || [channel ||] nonce [|| valid_until_ledger]
+//   relayer co-sign  "relayed" || signature_payload
+//   meta-tx          "execute_meta_tx" || network_id || wallet || envelope
+//   change ids       action || payload            (hashed by the caller)
+//   manifests        "accesly:manifest:" || wasm_hash || manifest XDR
+//
+// Every integer is big-endian. `no_std` and dependency-free unless a feature
+// pulls in the buffer it writes to.
+// ---------------------------------------------------------------------------
+
+#[cfg(any(feature = "alloc", test))]
+extern crate alloc;
+
+/// Action name `update_owner` signs under
+pub const UPDATE_OWNER: &str = "update_owner";
+/// Prefix of what a relayer signs to co-sign an authorization
+pub const RELAYED: &str = "relayed";
+/// Prefix of what the owner signs for a meta-transaction envelope
+pub const META_TX: &str = "execute_meta_tx";
+
+/// Prefix of what the build key signs to publish a capability manifest
+pub const MANIFEST: &str = "accesly:manifest:";
+
+/// A buffer a message is written into
+pub trait MessageBuf {
+    /// How the caller holds an action's payload
+    type Payload: ?Sized;
+
+    fn put(&mut self, bytes: &[u8]);
+    fn put_payload(&mut self, payload: &Self::Payload);
+}
+
+/// `action || payload || nonce`, what `require_owner_signature` checks
+pub fn action_message<M: MessageBuf>(out: &mut M, action: &str, payload: &M::Payload, nonce: u64) {
+    change_preimage(out, action, payload);
+    out.put(&nonce.to_be_bytes());
+}
+
+/// `"update_owner" || new_owner || nonce`
+pub fn update_owner_message<M: MessageBuf>(out: &mut M, new_owner: &[u8; 32], nonce: u64) {
+    out.put(UPDATE_OWNER.as_bytes());
+    out.put(new_owner);
+    out.put(&nonce.to_be_bytes());
+}
+
+/// What a `__check_auth` signature signs: payload || nonce on channel 0,
+/// payload || channel || nonce on any other, and
+/// payload || channel || nonce || valid_until_ledger when it expires
+pub fn auth_message<M: MessageBuf>(
+    out: &mut M,
+    signature_payload: &[u8; 32],
+    channel: u32,
+    nonce: u64,
+    valid_until_ledger: Option<u32>,
+) {
+    out.put(signature_payload);
+    if channel != 0 || valid_until_ledger.is_some() {
+        out.put(&channel.to_be_bytes());
+    }
+    out.put(&nonce.to_be_bytes());
+    if let Some(ledger) = valid_until_ledger {
+        out.put(&ledger.to_be_bytes());
+    }
+}
+
+/// `"relayed" || signature_payload`, what an allowlisted relayer signs to
+/// vouch for submitting an authorization
+pub fn relayer_message<M: MessageBuf>(out: &mut M, signature_payload: &[u8; 32]) {
+    out.put(RELAYED.as_bytes());
+    out.put(signature_payload);
+}
+
+/// `"execute_meta_tx" || network_id || wallet || envelope`, what
+/// `execute_meta_tx` checks, with `wallet` the XDR of the wallet's address.
+/// Unlike owner actions there's no wallet nonce, only the envelope's own,
+/// so the message names the wallet and network it is good for.
+pub fn meta_tx_message<M: MessageBuf>(
+    out: &mut M,
+    network_id: &[u8; 32],
+    wallet: &M::Payload,
+    envelope: &M::Payload,
+) {
+    out.put(META_TX.as_bytes());
+    out.put(network_id);
+    out.put_payload(wallet);
+    out.put_payload(envelope);
+}
+
+/// `action || payload`, hashed into the id `propose_change` takes
+pub fn change_preimage<M: MessageBuf>(out: &mut M, action: &str, payload: &M::Payload) {
+    out.put(action.as_bytes());
+    out.put_payload(payload);
+}
+
+/// `"accesly:manifest:" || wasm_hash || manifest`, what the build key signs
+/// over the XDR of a wallet version's capability manifest
+pub fn manifest_message<M: MessageBuf>(out: &mut M, wasm_hash: &[u8; 32], manifest: &M::Payload) {
+    out.put(MANIFEST.as_bytes());
+    out.put(wasm_hash);
+    out.put_payload(manifest);
+}
+
+#[cfg(any(feature = "alloc", test))]
+impl MessageBuf for alloc::vec::Vec<u8> {
+    type Payload = [u8];
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    fn put_payload(&mut self, payload: &[u8]) {
+        self.extend_from_slice(payload);
+    }
+}
+
+#[cfg(feature = "soroban")]
+impl MessageBuf for soroban_sdk::Bytes {
+    type Payload = soroban_sdk::Bytes;
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    fn put_payload(&mut self, payload: &soroban_sdk::Bytes) {
+        self.append(payload);
+    }
+}
+
+#[cfg(test)]
+mod test;
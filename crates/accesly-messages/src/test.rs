@@ -0,0 +1,84 @@
+// src/test.rs
+
+use super::*;
+use alloc::vec::Vec;
+
+#[test]
+fn test_action_message_layout() {
+    let mut message = Vec::new();
+    action_message(&mut message, "freeze", &[1u8, 2][..], 7);
+    assert_eq!(&message[..6], b"freeze");
+    assert_eq!(&message[6..8], &[1, 2]);
+    assert_eq!(&message[8..], &7u64.to_be_bytes());
+
+    let mut update = Vec::new();
+    update_owner_message(&mut update, &[3u8; 32], 7);
+    let mut generic = Vec::new();
+    action_message(&mut generic, UPDATE_OWNER, &[3u8; 32][..], 7);
+    assert_eq!(update, generic);
+}
+
+#[test]
+fn test_auth_message_layout() {
+    let payload = [9u8; 32];
+    let build = |channel, valid_until| {
+        let mut message = Vec::new();
+        auth_message(&mut message, &payload, channel, 5, valid_until);
+        message
+    };
+    assert_eq!(build(0, None).len(), 40);
+    assert_eq!(build(2, None).len(), 44);
+
+    let expiring = build(0, Some(77));
+    assert_eq!(expiring.len(), 48);
+    assert_eq!(&expiring[32..36], &0u32.to_be_bytes());
+    assert_eq!(&expiring[36..44], &5u64.to_be_bytes());
+    assert_eq!(&expiring[44..], &77u32.to_be_bytes());
+
+    // A relayer's co-signature never has the shape of an owner's
+    let mut relayed = Vec::new();
+    relayer_message(&mut relayed, &payload);
+    assert_eq!(&relayed[..7], b"relayed");
+    assert_eq!(&relayed[7..], &payload);
+}
+
+#[cfg(feature = "soroban")]
+#[test]
+fn test_bytes_and_vec_agree() {
+    use soroban_sdk::{Bytes, Env};
+
+    let env = Env::default();
+    let payload = [4u8, 5, 6];
+    let bytes_payload = Bytes::from_slice(&env, &payload);
+    let same = |bytes: Bytes, vec: Vec<u8>| assert_eq!(bytes, Bytes::from_slice(&env, &vec));
+
+    let (mut on_chain, mut off_chain) = (Bytes::new(&env), Vec::new());
+    action_message(&mut on_chain, "approve_change", &bytes_payload, 11);
+    action_message(&mut off_chain, "approve_change", &payload[..], 11);
+    same(on_chain, off_chain);
+
+    let (mut on_chain, mut off_chain) = (Bytes::new(&env), Vec::new());
+    update_owner_message(&mut on_chain, &[8u8; 32], 2);
+    update_owner_message(&mut off_chain, &[8u8; 32], 2);
+    same(on_chain, off_chain);
+
+    let (mut on_chain, mut off_chain) = (Bytes::new(&env), Vec::new());
+    auth_message(&mut on_chain, &[1u8; 32], 3, 9, Some(100));
+    auth_message(&mut off_chain, &[1u8; 32], 3, 9, Some(100));
+    same(on_chain, off_chain);
+
+    let (mut on_chain, mut off_chain) = (Bytes::new(&env), Vec::new());
+    relayer_message(&mut on_chain, &[1u8; 32]);
+    relayer_message(&mut off_chain, &[1u8; 32]);
+    same(on_chain, off_chain);
+
+    let (mut on_chain, mut off_chain) = (Bytes::new(&env), Vec::new());
+    meta_tx_message(&mut on_chain, &[4u8; 32], &bytes_payload, &bytes_payload);
+    meta_tx_message(&mut off_chain, &[4u8; 32], &payload[..], &payload[..]);
+    same(on_chain, off_chain);
+
+    let (mut on_chain, mut off_chain) = (Bytes::new(&env), Vec::new());
+    change_preimage(&mut on_chain, "add_signer", &bytes_payload);
+    change_preimage(&mut off_chain, "add_signer", &payload[..]);
+    same(on_chain, off_chain);
+}
@@ -0,0 +1,215 @@
+#![no_std]
+
+// ---------------------------------------------------------------------------
+// accesly-policy
+//
+// The rules the wallet holds what it authorizes to, as plain functions over
+// plain values. The wallet reads its settings from storage and decides
+// through the functions below; the relayer and the indexer read the same
+// settings through the wallet's views and can re-evaluate a call with
+// `evaluate` ("would this have passed?") for monitoring and alerting,
+// without a copy of the rules drifting out of step with the contract.
+//
+// `evaluate` runs, in the order `__check_auth` does, the checks that depend
+// only on the wallet's settings and running totals: the contract policy,
+// the large-transfer thresholds and the fiat limit. Signatures, the XLM
+// reserve, fee mode, contacts-only mode and the post-recovery cooldown are
+// left out, as is velocity, which only counts authorizations.
+//
+// `no_std` and dependency-free.
+// ---------------------------------------------------------------------------
+
+/// How the owner listed a contract in the contract policy
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Listing {
+    Allowed,
+    Denied,
+}
+
+/// What a transfer of an asset without a fresh price does under the fiat
+/// limit
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StalePrice {
+    Refuse,
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FiatLimit {
+    /// Most the wallet can send per day, in cents
+    pub daily_limit: i128,
+    pub on_stale: StalePrice,
+}
+
+/// Why a call doesn't pass, named after the wallet error it fails with
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Denial {
+    /// The contract policy forbids the contract
+    Unauthorized,
+    /// Over a large-transfer threshold
+    Timelocked,
+    /// Past the fiat limit
+    LimitExceeded,
+    /// No fresh price, and the fiat limit refuses unpriced transfers
+    Expired,
+}
+
+/// Argument positions of a SEP-41 call that spends from `from`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OutflowArgs {
+    pub from: u32,
+    /// The recipient, or the spender of an approval. `None` for burns.
+    pub to: Option<u32>,
+    pub amount: u32,
+}
+
+/// Token functions that move value out of the wallet. An approval counts:
+/// the spender can take the amount later without asking again.
+pub const OUTFLOW_CALLS: [(&str, OutflowArgs); 5] = [
+    ("transfer", outflow_args(0, Some(1), 2)),
+    ("approve", outflow_args(0, Some(1), 2)),
+    ("transfer_from", outflow_args(1, Some(2), 3)),
+    ("burn", outflow_args(0, None, 1)),
+    ("burn_from", outflow_args(1, None, 2)),
+];
+
+const fn outflow_args(from: u32, to: Option<u32>, amount: u32) -> OutflowArgs {
+    OutflowArgs { from, to, amount }
+}
+
+/// Argument positions of `fn_name`, if it spends
+pub fn outflow(fn_name: &str) -> Option<OutflowArgs> {
+    OUTFLOW_CALLS
+        .iter()
+        .find(|(name, _)| *name == fn_name)
+        .map(|(_, args)| *args)
+}
+
+/// Whether the contract policy lets the wallet call a contract with
+/// `listing`
+pub fn is_invocable(listing: Option<Listing>, allowlist_mode: bool) -> bool {
+    match listing {
+        Some(Listing::Denied) => false,
+        Some(Listing::Allowed) => true,
+        None => !allowlist_mode,
+    }
+}
+
+/// Threshold in force at `now`: `previous` until a raise takes effect
+pub fn effective_threshold(threshold: i128, previous: i128, effective_at: u64, now: u64) -> i128 {
+    if now < effective_at {
+        previous
+    } else {
+        threshold
+    }
+}
+
+/// Fail with `Timelocked` if `sent` of an asset is over its `threshold`;
+/// 0 means no threshold
+pub fn check_threshold(sent: i128, threshold: i128) -> Result<(), Denial> {
+    if threshold > 0 && sent > threshold {
+        return Err(Denial::Timelocked);
+    }
+    Ok(())
+}
+
+/// Add the fiat `value` of a transfer to `spent`; `None` when its asset has
+/// no fresh price
+pub fn add_fiat(spent: i128, value: Option<i128>, on_stale: StalePrice) -> Result<i128, Denial> {
+    match value {
+        Some(value) => Ok(spent.saturating_add(value)),
+        None if on_stale == StalePrice::Skip => Ok(spent),
+        None => Err(Denial::Expired),
+    }
+}
+
+/// Fail with `LimitExceeded` if `spent` is past `limit`
+pub fn check_fiat(spent: i128, limit: i128) -> Result<(), Denial> {
+    if spent > limit {
+        return Err(Denial::LimitExceeded);
+    }
+    Ok(())
+}
+
+/// Authorizations in the sliding window ending at `now`, from the counts of
+/// the current bucket and the one before. Each bucket is one window long,
+/// starting at multiples of `window`; the previous one counts for the share
+/// of it still inside the window.
+pub fn velocity_usage(current: u32, previous: u32, window: u64, now: u64) -> u32 {
+    if now < window {
+        return current;
+    }
+    let elapsed = now % window;
+    let previous = u64::from(previous) * (window - elapsed) / window;
+    current.saturating_add(previous as u32)
+}
+
+/// The wallet's settings and running totals, as `evaluate` needs them
+pub trait WalletView {
+    type Address: Eq;
+
+    fn allowlist_mode(&self) -> bool;
+    fn listing(&self, contract: &Self::Address) -> Option<Listing>;
+    /// Large-transfer threshold in force for `asset`; 0 for none
+    fn threshold(&self, asset: &Self::Address) -> i128;
+    fn fiat_limit(&self) -> Option<FiatLimit>;
+    /// Cents counted against the fiat limit today
+    fn fiat_spent_today(&self) -> i128;
+    /// `amount` of `asset` in cents of the limit's currency, `None` without
+    /// a fresh price
+    fn fiat_value(&self, asset: &Self::Address, amount: i128) -> Option<i128>;
+}
+
+/// A contract call the wallet would authorize
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call<A> {
+    pub contract: A,
+    /// Amount it moves out of the wallet (see `outflow`), 0 for none
+    pub outflow: i128,
+    /// Whether it goes to one of the owner's own accounts, which isn't
+    /// spending under the fiat limit
+    pub internal: bool,
+}
+
+/// Whether the wallet would authorize `calls` together, as one
+/// authorization or batch
+pub fn evaluate<V: WalletView>(view: &V, calls: &[Call<V::Address>]) -> Result<(), Denial> {
+    let allowlist_mode = view.allowlist_mode();
+    for call in calls {
+        if !is_invocable(view.listing(&call.contract), allowlist_mode) {
+            return Err(Denial::Unauthorized);
+        }
+    }
+
+    // Amounts are summed per asset, so each is checked and priced once
+    let assets = calls
+        .iter()
+        .enumerate()
+        .filter(|(i, call)| !calls[..*i].iter().any(|c| c.contract == call.contract))
+        .map(|(_, call)| &call.contract);
+    let sum = |asset: &V::Address, spending: bool| {
+        calls
+            .iter()
+            .filter(|call| call.contract == *asset && !(spending && call.internal))
+            .fold(0i128, |total, call| total.saturating_add(call.outflow))
+    };
+
+    for asset in assets.clone() {
+        check_threshold(sum(asset, false), view.threshold(asset))?;
+    }
+
+    let Some(limit) = view.fiat_limit() else {
+        return Ok(());
+    };
+    let mut spent = view.fiat_spent_today();
+    for asset in assets {
+        let amount = sum(asset, true);
+        if amount > 0 {
+            spent = add_fiat(spent, view.fiat_value(asset, amount), limit.on_stale)?;
+        }
+    }
+    check_fiat(spent, limit.daily_limit)
+}
+
+#[cfg(test)]
+mod test;
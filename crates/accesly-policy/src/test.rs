@@ -0,0 +1,146 @@
+// src/test.rs
+
+use super::*;
+
+/// Settings for `evaluate`, with assets priced at one cent a unit except
+/// "stale"
+#[derive(Default)]
+struct View {
+    allowlist_mode: bool,
+    allowed: &'static [&'static str],
+    denied: &'static [&'static str],
+    thresholds: &'static [(&'static str, i128)],
+    fiat_limit: Option<FiatLimit>,
+    fiat_spent_today: i128,
+}
+
+impl WalletView for View {
+    type Address = &'static str;
+
+    fn allowlist_mode(&self) -> bool {
+        self.allowlist_mode
+    }
+
+    fn listing(&self, contract: &&'static str) -> Option<Listing> {
+        if self.denied.contains(contract) {
+            Some(Listing::Denied)
+        } else if self.allowed.contains(contract) {
+            Some(Listing::Allowed)
+        } else {
+            None
+        }
+    }
+
+    fn threshold(&self, asset: &&'static str) -> i128 {
+        self.thresholds
+            .iter()
+            .find(|(a, _)| a == asset)
+            .map_or(0, |(_, threshold)| *threshold)
+    }
+
+    fn fiat_limit(&self) -> Option<FiatLimit> {
+        self.fiat_limit
+    }
+
+    fn fiat_spent_today(&self) -> i128 {
+        self.fiat_spent_today
+    }
+
+    fn fiat_value(&self, asset: &&'static str, amount: i128) -> Option<i128> {
+        (*asset != "stale").then_some(amount)
+    }
+}
+
+fn call(contract: &'static str, outflow: i128) -> Call<&'static str> {
+    Call {
+        contract,
+        outflow,
+        internal: false,
+    }
+}
+
+#[test]
+fn test_outflow_positions() {
+    assert_eq!(outflow("transfer"), Some(outflow_args(0, Some(1), 2)));
+    assert_eq!(outflow("burn_from"), Some(outflow_args(1, None, 2)));
+    assert_eq!(outflow("mint"), None);
+}
+
+#[test]
+fn test_contract_policy() {
+    assert!(is_invocable(None, false));
+    assert!(!is_invocable(None, true));
+    assert!(is_invocable(Some(Listing::Allowed), true));
+    assert!(!is_invocable(Some(Listing::Denied), false));
+
+    let view = View {
+        allowlist_mode: true,
+        allowed: &["dex"],
+        ..View::default()
+    };
+    assert_eq!(evaluate(&view, &[call("dex", 0)]), Ok(()));
+    assert_eq!(
+        evaluate(&view, &[call("dex", 0), call("drainer", 0)]),
+        Err(Denial::Unauthorized)
+    );
+}
+
+#[test]
+fn test_thresholds_sum_per_asset() {
+    assert_eq!(effective_threshold(500, 100, 10, 9), 100);
+    assert_eq!(effective_threshold(500, 100, 10, 10), 500);
+
+    let view = View {
+        thresholds: &[("usdc", 100)],
+        ..View::default()
+    };
+    assert_eq!(
+        evaluate(&view, &[call("usdc", 60), call("xlm", 500)]),
+        Ok(())
+    );
+    // Splitting a transfer doesn't get it under
+    assert_eq!(
+        evaluate(&view, &[call("usdc", 60), call("usdc", 60)]),
+        Err(Denial::Timelocked)
+    );
+}
+
+#[test]
+fn test_fiat_limit() {
+    let mut view = View {
+        fiat_limit: Some(FiatLimit {
+            daily_limit: 1_000,
+            on_stale: StalePrice::Refuse,
+        }),
+        fiat_spent_today: 700,
+        ..View::default()
+    };
+    assert_eq!(evaluate(&view, &[call("usdc", 300)]), Ok(()));
+    assert_eq!(
+        evaluate(&view, &[call("usdc", 200), call("xlm", 200)]),
+        Err(Denial::LimitExceeded)
+    );
+
+    // Transfers to the owner's own accounts aren't spending
+    let internal = Call {
+        internal: true,
+        ..call("usdc", 5_000)
+    };
+    assert_eq!(evaluate(&view, &[internal]), Ok(()));
+
+    assert_eq!(evaluate(&view, &[call("stale", 1)]), Err(Denial::Expired));
+    view.fiat_limit = Some(FiatLimit {
+        daily_limit: 1_000,
+        on_stale: StalePrice::Skip,
+    });
+    assert_eq!(evaluate(&view, &[call("stale", 5_000)]), Ok(()));
+}
+
+#[test]
+fn test_velocity_window_slides() {
+    // Within the first window only the current bucket counts
+    assert_eq!(velocity_usage(3, 9, 60, 30), 3);
+    // A quarter into the window, three quarters of the last bucket count
+    assert_eq!(velocity_usage(1, 8, 60, 135), 7);
+    assert_eq!(velocity_usage(1, 8, 60, 120), 9);
+}
@@ -0,0 +1,152 @@
+// ---------------------------------------------------------------------------
+// Channel accounts
+//
+// Every transaction the relayer builds is sourced by a channel account,
+// which lends it a sequence number and pays its fee. A lease gives one relay
+// sole use of a channel until it's dropped, so the channel's sequence can be
+// cached rather than read from the node each time: loaded on first use,
+// advanced once the node accepts a transaction, and forgotten after a
+// `txBAD_SEQ` so the next lease reloads it.
+//
+// Free channels are leased round-robin, so concurrent relays each get their
+// own sequence and the fees spread evenly over the pool.
+// ---------------------------------------------------------------------------
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use accesly_sdk::rpc::{Rpc, Transport};
+use accesly_sdk::SdkError;
+use ed25519_dalek::SigningKey;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+struct Channel {
+    key: SigningKey,
+    /// Last sequence number used, when known
+    sequence: Mutex<Option<i64>>,
+}
+
+pub struct ChannelPool {
+    channels: Vec<Channel>,
+    /// Free channels, least recently used first
+    free: Mutex<VecDeque<usize>>,
+    available: Semaphore,
+}
+
+impl ChannelPool {
+    /// Panics without any channel, as every lease would wait forever
+    pub fn new(keys: Vec<SigningKey>) -> Self {
+        assert!(!keys.is_empty(), "a relayer needs at least one channel");
+        Self {
+            free: Mutex::new((0..keys.len()).collect()),
+            available: Semaphore::new(keys.len()),
+            channels: keys
+                .into_iter()
+                .map(|key| Channel {
+                    key,
+                    sequence: Mutex::new(None),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Wait for a free channel and hold it until the lease is dropped
+    pub async fn lease(&self) -> Lease<'_> {
+        let permit = self
+            .available
+            .acquire()
+            .await
+            .expect("the pool never closes its semaphore");
+        let index = self
+            .free
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("a permit means a channel is free");
+        Lease {
+            pool: self,
+            index,
+            _permit: permit,
+        }
+    }
+
+    /// Channel `index`, if it's free right now
+    pub fn try_lease(&self, index: usize) -> Option<Lease<'_>> {
+        let permit = self.available.try_acquire().ok()?;
+        let mut free = self.free.lock().unwrap();
+        let position = free.iter().position(|&free| free == index)?;
+        free.remove(position);
+        Some(Lease {
+            pool: self,
+            index,
+            _permit: permit,
+        })
+    }
+
+    pub fn public_key(&self, index: usize) -> [u8; 32] {
+        self.channels[index].key.verifying_key().to_bytes()
+    }
+}
+
+pub struct Lease<'a> {
+    pool: &'a ChannelPool,
+    index: usize,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl Lease<'_> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn channel(&self) -> &Channel {
+        &self.pool.channels[self.index]
+    }
+
+    pub fn key(&self) -> &SigningKey {
+        &self.channel().key
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.pool.public_key(self.index)
+    }
+
+    /// Sequence number for the channel's next transaction
+    pub async fn next_sequence<T: Transport>(&self, rpc: &Rpc<T>) -> Result<i64, SdkError> {
+        let cached = *self.channel().sequence.lock().unwrap();
+        let current = match cached {
+            Some(sequence) => sequence,
+            None => {
+                let sequence = rpc.sequence(&self.public_key()).await?;
+                *self.channel().sequence.lock().unwrap() = Some(sequence);
+                sequence
+            }
+        };
+        Ok(current + 1)
+    }
+
+    /// The node accepted the channel's transaction at `sequence`
+    pub fn consumed(&self, sequence: i64) {
+        *self.channel().sequence.lock().unwrap() = Some(sequence);
+    }
+
+    /// The cached sequence is stale; reload it on next use
+    pub fn invalidate(&self) {
+        *self.channel().sequence.lock().unwrap() = None;
+    }
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        // Runs before the permit is released, so a woken waiter finds it
+        self.pool.free.lock().unwrap().push_back(self.index);
+    }
+}
@@ -0,0 +1,68 @@
+use accesly_client::strkey::StrKeyError;
+use accesly_sdk::SdkError;
+
+#[derive(Debug)]
+pub enum RelayError {
+    Sdk(SdkError),
+    /// A malformed address, no entries, an unsigned one, or one the channel
+    /// account would authorize
+    InvalidRequest(String),
+    /// The node refused the transaction; holds the result code
+    Rejected {
+        hash: String,
+        code: String,
+    },
+    /// Getting in would take a fee past `RelayConfig::max_fee`
+    FeeCapExceeded {
+        fee: i64,
+    },
+    /// A signature carries a key reported compromised; holds its fingerprint
+    Quarantined {
+        fingerprint: [u8; 32],
+    },
+}
+
+impl std::fmt::Display for RelayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sdk(err) => write!(f, "{err}"),
+            Self::InvalidRequest(err) => write!(f, "invalid relay request: {err}"),
+            Self::Rejected { hash, code } => write!(f, "transaction {hash} rejected: {code}"),
+            Self::FeeCapExceeded { fee } => {
+                write!(f, "inclusion fee of {fee} stroops is over the cap")
+            }
+            Self::Quarantined { fingerprint } => {
+                write!(f, "signed by quarantined key ")?;
+                fingerprint
+                    .iter()
+                    .try_for_each(|byte| write!(f, "{byte:02x}"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RelayError {}
+
+impl From<SdkError> for RelayError {
+    fn from(err: SdkError) -> Self {
+        Self::Sdk(err)
+    }
+}
+
+impl From<soroban_sdk::xdr::Error> for RelayError {
+    fn from(err: soroban_sdk::xdr::Error) -> Self {
+        Self::InvalidRequest(err.to_string())
+    }
+}
+
+impl From<StrKeyError> for RelayError {
+    fn from(err: StrKeyError) -> Self {
+        Self::InvalidRequest(err.to_string())
+    }
+}
+
+impl From<base64::DecodeError> for RelayError {
+    fn from(err: base64::DecodeError) -> Self {
+        Self::InvalidRequest(err.to_string())
+    }
+}
@@ -0,0 +1,37 @@
+// ---------------------------------------------------------------------------
+// accesly-relayer
+//
+// Gets wallet calls on chain at the relayer's expense. Apps send a contract
+// call with the Soroban auth entries its signers already signed; the relayer
+// wraps it in a transaction sourced by one of its channel accounts, pays the
+// fees, and sees it into a ledger, fee bumping through surges. The channels
+// pay for what they source and are kept funded by moving XLM between them.
+// Keys a wallet reports compromised are refused once their event is fed in.
+//
+//     let relayer = Relayer::new(rpc_url, passphrase, channel_keys, fee_key);
+//     let request = RelayRequest::from_xdr(&contract, &signed_entries)?;
+//     let relayed = relayer.relay(&request).await?;
+//
+//     // In a task of its own, reporting each round to the operator
+//     relayer.run_rebalancer(interval, report_round).await;
+//
+//     // For each event from the wallets it relays for
+//     relayer.follow_event(&topics);
+//
+// Built on `accesly-sdk` for RPC and transaction assembly, so it talks to
+// the network exactly as the SDK does and takes any `Transport`.
+// ---------------------------------------------------------------------------
+
+mod channels;
+mod error;
+mod quarantine;
+mod rebalance;
+mod relay;
+
+pub use channels::{ChannelPool, Lease};
+pub use error::RelayError;
+pub use rebalance::{plan_rebalance, Rebalanced, Transfer};
+pub use relay::{RelayConfig, RelayRequest, Relayed, Relayer};
+
+#[cfg(test)]
+mod test;
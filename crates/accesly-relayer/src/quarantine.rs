@@ -0,0 +1,106 @@
+// ---------------------------------------------------------------------------
+// Quarantined keys
+//
+// When a wallet signer reports a key compromised, the wallet publishes
+// `key_compromised` with the key's fingerprint as the second topic and
+// refuses the key from then on. A relayer following those events (feeding
+// them to `Relayer::follow_event`) refuses requests whose signatures carry
+// the key before leasing a channel, so nothing it signed reaches the
+// network through any relayer in the fleet. `quarantine_released` lets the
+// key through again.
+//
+// Fingerprints are the wallet's: sha256 of a raw public key, or of the XDR
+// of an address. Every 32- or 65-byte value and every address in an entry's
+// signature is taken for a key; ed25519 and P-256 signatures are 64 bytes,
+// so they never are. The owner's plain signature doesn't carry its key, but
+// a report freezes the wallet, which covers the owner key on chain.
+// ---------------------------------------------------------------------------
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use acceslyinterface::events::{KEY_COMPROMISED, QUARANTINE_RELEASED};
+use sha2::{Digest, Sha256};
+use soroban_sdk::xdr::{Limits, ScVal, SorobanCredentials, WriteXdr};
+
+use crate::{RelayError, RelayRequest};
+
+#[derive(Default)]
+pub(crate) struct Quarantine {
+    fingerprints: Mutex<HashSet<[u8; 32]>>,
+}
+
+impl Quarantine {
+    pub(crate) fn add(&self, fingerprint: [u8; 32]) {
+        self.fingerprints.lock().unwrap().insert(fingerprint);
+    }
+
+    pub(crate) fn remove(&self, fingerprint: &[u8; 32]) {
+        self.fingerprints.lock().unwrap().remove(fingerprint);
+    }
+
+    pub(crate) fn contains(&self, fingerprint: &[u8; 32]) -> bool {
+        self.fingerprints.lock().unwrap().contains(fingerprint)
+    }
+
+    /// Apply a wallet event with `topics`; whether it was a quarantine event
+    pub(crate) fn follow(&self, topics: &[ScVal]) -> bool {
+        let [ScVal::Symbol(code), ScVal::Bytes(fingerprint), ..] = topics else {
+            return false;
+        };
+        let Ok(fingerprint) = <[u8; 32]>::try_from(fingerprint.as_slice()) else {
+            return false;
+        };
+        match code.to_utf8_string_lossy().as_str() {
+            KEY_COMPROMISED => self.add(fingerprint),
+            QUARANTINE_RELEASED => self.remove(&fingerprint),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Fail with `Quarantined` when a signature in `request` carries a
+    /// quarantined key
+    pub(crate) fn check(&self, request: &RelayRequest) -> Result<(), RelayError> {
+        let fingerprints = self.fingerprints.lock().unwrap();
+        if fingerprints.is_empty() {
+            return Ok(());
+        }
+        let mut keys = Vec::new();
+        for entry in &request.auth {
+            if let SorobanCredentials::Address(credentials) = &entry.credentials {
+                collect_keys(&credentials.signature, &mut keys);
+            }
+        }
+        match keys.into_iter().find(|key| fingerprints.contains(key)) {
+            Some(fingerprint) => Err(RelayError::Quarantined { fingerprint }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Fingerprint of every key-shaped value in `signature`
+fn collect_keys(signature: &ScVal, keys: &mut Vec<[u8; 32]>) {
+    match signature {
+        ScVal::Bytes(bytes) if bytes.len() == 32 || bytes.len() == 65 => {
+            keys.push(Sha256::digest(bytes.as_slice()).into());
+        }
+        ScVal::Address(_) => {
+            if let Ok(xdr) = signature.to_xdr(Limits::none()) {
+                keys.push(Sha256::digest(xdr).into());
+            }
+        }
+        ScVal::Vec(Some(items)) => {
+            for item in items.iter() {
+                collect_keys(item, keys);
+            }
+        }
+        ScVal::Map(Some(entries)) => {
+            for entry in entries.iter() {
+                collect_keys(&entry.key, keys);
+                collect_keys(&entry.val, keys);
+            }
+        }
+        _ => {}
+    }
+}
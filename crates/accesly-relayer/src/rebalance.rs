@@ -0,0 +1,198 @@
+// ---------------------------------------------------------------------------
+// Pool rebalancing
+//
+// Channels pay the fees of what they source, so the busiest drain first.
+// `rebalance` tops every channel under `min_balance` back up to
+// `target_balance` with XLM from the channels over the target. Each donor
+// sends one transaction with a payment per recipient, under its own lease
+// so its sequence stays in step with relays; a donor that's busy relaying is
+// skipped until the next round. What the pool as a whole can't cover is
+// reported as the shortfall, for the operator to fund.
+// ---------------------------------------------------------------------------
+
+use std::cmp::Reverse;
+use std::time::Duration;
+
+use accesly_sdk::rpc::Transport;
+use accesly_sdk::tx::{sign, BASE_FEE};
+use accesly_sdk::SdkError;
+use soroban_sdk::xdr::{
+    Asset, Memo, MuxedAccount, Operation, OperationBody, PaymentOp, Preconditions, SequenceNumber,
+    Transaction, TransactionExt, Uint256,
+};
+
+use crate::relay::Outcome;
+use crate::{RelayError, Relayer};
+
+/// Most operations a transaction may carry
+const MAX_OPERATIONS: usize = 100;
+
+/// `amount` stroops from channel `from` to channel `to`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub amount: i64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Rebalanced {
+    /// Transfers that made it into a ledger
+    pub transfers: Vec<Transfer>,
+    /// Planned transfers left for the next round: the donor was busy or its
+    /// transaction didn't land
+    pub skipped: Vec<Transfer>,
+    /// Stroops the pool is short of bringing every channel to the minimum
+    pub shortfall: i64,
+}
+
+/// Transfers bringing every balance under `min` up to `target` from those
+/// over it, largest needs first, and what's left uncovered
+pub fn plan_rebalance(balances: &[i64], min: i64, target: i64) -> (Vec<Transfer>, i64) {
+    let mut needy: Vec<(usize, i64)> = balances
+        .iter()
+        .enumerate()
+        .filter(|(_, &balance)| balance < min)
+        .map(|(index, &balance)| (index, (target - balance).max(0)))
+        .collect();
+    let mut donors: Vec<(usize, i64)> = balances
+        .iter()
+        .enumerate()
+        .filter(|(_, &balance)| balance > target)
+        .map(|(index, &balance)| (index, balance - target))
+        .collect();
+    needy.sort_by_key(|&(index, need)| (Reverse(need), index));
+    donors.sort_by_key(|&(index, spare)| (Reverse(spare), index));
+
+    let mut transfers = Vec::new();
+    let mut shortfall = 0;
+    let mut donor = 0;
+    for (to, mut need) in needy {
+        while need > 0 && donor < donors.len() {
+            let (from, spare) = &mut donors[donor];
+            let amount = need.min(*spare);
+            transfers.push(Transfer {
+                from: *from,
+                to,
+                amount,
+            });
+            need -= amount;
+            *spare -= amount;
+            if *spare == 0 {
+                donor += 1;
+            }
+        }
+        shortfall += need;
+    }
+    (transfers, shortfall)
+}
+
+/// Payments of `(recipient, stroops)` from `source`
+fn payments(
+    source: &[u8; 32],
+    sequence: i64,
+    payments: &[([u8; 32], i64)],
+) -> Result<Transaction, SdkError> {
+    let operations: Vec<Operation> = payments
+        .iter()
+        .map(|(to, amount)| Operation {
+            source_account: None,
+            body: OperationBody::Payment(PaymentOp {
+                destination: MuxedAccount::Ed25519(Uint256(*to)),
+                asset: Asset::Native,
+                amount: *amount,
+            }),
+        })
+        .collect();
+    Ok(Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(*source)),
+        fee: BASE_FEE * operations.len() as u32,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: operations.try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+impl<T: Transport> Relayer<T> {
+    /// Move XLM between channels so none is under `min_balance`
+    pub async fn rebalance(&self) -> Result<Rebalanced, RelayError> {
+        let channels = &self.channels;
+        let mut balances = Vec::with_capacity(channels.len());
+        for index in 0..channels.len() {
+            let account = self.rpc.account(&channels.public_key(index)).await?;
+            balances.push(account.balance);
+        }
+        let (planned, shortfall) = plan_rebalance(
+            &balances,
+            self.config.min_balance,
+            self.config.target_balance,
+        );
+
+        let mut donors: Vec<usize> = planned.iter().map(|transfer| transfer.from).collect();
+        donors.sort_unstable();
+        donors.dedup();
+
+        let mut rebalanced = Rebalanced {
+            shortfall,
+            ..Rebalanced::default()
+        };
+        for from in donors {
+            let transfers: Vec<Transfer> = planned
+                .iter()
+                .filter(|transfer| transfer.from == from)
+                .copied()
+                .collect();
+            let Some(lease) = channels.try_lease(from) else {
+                rebalanced.skipped.extend(transfers);
+                continue;
+            };
+
+            for batch in transfers.chunks(MAX_OPERATIONS) {
+                let sequence = lease.next_sequence(&self.rpc).await?;
+                let recipients: Vec<([u8; 32], i64)> = batch
+                    .iter()
+                    .map(|transfer| (channels.public_key(transfer.to), transfer.amount))
+                    .collect();
+                let tx = payments(&lease.public_key(), sequence, &recipients)?;
+                let sent = self
+                    .rpc
+                    .send_status(&sign(tx, &self.network_id, lease.key())?)
+                    .await?;
+
+                let landed = match Outcome::of(&sent.status) {
+                    Outcome::Accepted => {
+                        lease.consumed(sequence);
+                        self.confirm(&[sent.hash]).await?.is_some()
+                    }
+                    Outcome::BadSequence => {
+                        lease.invalidate();
+                        false
+                    }
+                    Outcome::Surge | Outcome::Rejected(_) => false,
+                };
+                if landed {
+                    rebalanced.transfers.extend_from_slice(batch);
+                } else {
+                    rebalanced.skipped.extend_from_slice(batch);
+                }
+            }
+        }
+        Ok(rebalanced)
+    }
+
+    /// `rebalance` every `interval`, for as long as the relayer runs,
+    /// handing each round's outcome to `report`. A failed round doesn't stop
+    /// the next one; check `Rebalanced::shortfall` to know when the pool
+    /// needs funding.
+    pub async fn run_rebalancer<F>(&self, interval: Duration, mut report: F)
+    where
+        F: FnMut(Result<Rebalanced, RelayError>),
+    {
+        loop {
+            tokio::time::sleep(interval).await;
+            report(self.rebalance().await);
+        }
+    }
+}
@@ -0,0 +1,414 @@
+// ---------------------------------------------------------------------------
+// Relaying
+//
+// A relay takes a contract call with its signed auth entries and gets it
+// into a ledger:
+//
+//   1. lease a channel account and take its next sequence number
+//   2. build the call with the signed entries and simulate it; simulation
+//      runs the entries through `__check_auth`, so a bad signature fails
+//      before anything is paid
+//   3. attach the simulated resources, bid `base_fee` for inclusion, sign
+//      as the channel and submit
+//   4. poll for the result
+//
+// When the network is surging (the node answers TRY_AGAIN_LATER or
+// txINSUFFICIENT_FEE, or the transaction sits unconfirmed) the signed
+// transaction is wrapped in a fee bump from the fee account, bidding
+// `bump_factor` times the last bid, until `max_fee`. Core only lets a fee
+// bump replace a queued transaction at 10x its fee, hence the default
+// factor. Every accepted hash is polled, since any one of them may be the
+// one that lands. A `txBAD_SEQ` means the channel was used from elsewhere:
+// its sequence is reloaded and the call rebuilt, once.
+// ---------------------------------------------------------------------------
+
+use std::time::Duration;
+
+use accesly_client::strkey::parse_contract;
+use accesly_sdk::messages::network_id;
+use accesly_sdk::rpc::{HttpTransport, Rpc, SendStatus, Simulation, Transport, TxStatus};
+use accesly_sdk::tx::{fee_bump, invoke, set_auth, sign, unsigned, BASE_FEE};
+use accesly_sdk::SdkError;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::SigningKey;
+use soroban_sdk::xdr::{
+    Hash, InnerTransactionResultResult, Limits, ReadXdr, ScAddress, ScVal,
+    SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanCredentials, Transaction,
+    TransactionExt, TransactionResultResult,
+};
+
+use crate::channels::{ChannelPool, Lease};
+use crate::quarantine::Quarantine;
+use crate::RelayError;
+
+/// Largest transaction the network takes (`tx_max_size_bytes`, 129 KiB)
+pub(crate) const MAX_TX_SIZE: usize = 132_096;
+/// Deepest XDR nesting decoded from a client, as deep as the host goes
+const MAX_XDR_DEPTH: u32 = 500;
+
+#[derive(Clone, Debug)]
+pub struct RelayConfig {
+    /// Inclusion fee bid per operation on first submission, in stroops
+    pub base_fee: u32,
+    /// Highest inclusion fee per operation a fee bump may bid
+    pub max_fee: i64,
+    /// What each fee bump multiplies the last bid by
+    pub bump_factor: i64,
+    /// Polls before an accepted transaction counts as stuck
+    pub confirm_attempts: u32,
+    pub poll_interval: Duration,
+    /// Channels under this many stroops get topped up by `rebalance`...
+    pub min_balance: i64,
+    /// ...to this many, from channels holding more
+    pub target_balance: i64,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        Self {
+            base_fee: BASE_FEE,
+            max_fee: 100_000,
+            bump_factor: 10,
+            confirm_attempts: 10,
+            poll_interval: accesly_sdk::rpc::POLL_INTERVAL,
+            min_balance: 50_000_000,
+            target_balance: 200_000_000,
+        }
+    }
+}
+
+/// A contract call and the auth entries its signers signed for it
+#[derive(Clone, Debug)]
+pub struct RelayRequest {
+    pub contract: ScAddress,
+    pub function: String,
+    pub args: Vec<ScVal>,
+    pub auth: Vec<SorobanAuthorizationEntry>,
+}
+
+impl RelayRequest {
+    /// The call the first entry authorizes at its root, carrying `entries`
+    pub fn from_auth(entries: Vec<SorobanAuthorizationEntry>) -> Result<Self, RelayError> {
+        let first = entries
+            .first()
+            .ok_or_else(|| RelayError::InvalidRequest("no auth entries".into()))?;
+        let SorobanAuthorizedFunction::ContractFn(call) = &first.root_invocation.function else {
+            return Err(RelayError::InvalidRequest(
+                "entry does not authorize a contract call".into(),
+            ));
+        };
+        Ok(Self {
+            contract: call.contract_address.clone(),
+            function: call.function_name.to_utf8_string_lossy(),
+            args: call.args.to_vec(),
+            auth: entries,
+        })
+    }
+
+    /// `from_auth` over base64 XDR entries, as clients send them along with
+    /// the `C...` address of the contract they call. The address is checked
+    /// before anything is decoded and must be the one the entries authorize.
+    pub fn from_xdr<S: AsRef<str>>(contract: &str, entries: &[S]) -> Result<Self, RelayError> {
+        let contract = ScAddress::Contract(Hash(parse_contract(contract)?));
+        // Everything has to fit in one transaction, so that bounds what is
+        // decoded; too long to possibly fit is refused before decoding
+        let mut limits = Limits {
+            depth: MAX_XDR_DEPTH,
+            len: MAX_TX_SIZE,
+        };
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                if entry.as_ref().len() > limits.len.div_ceil(3) * 4 {
+                    return Err(RelayError::InvalidRequest(
+                        "entries are larger than a transaction".into(),
+                    ));
+                }
+                let bytes = STANDARD.decode(entry.as_ref())?;
+                let entry = SorobanAuthorizationEntry::from_xdr(&bytes, limits.clone())?;
+                limits.len = limits.len.saturating_sub(bytes.len());
+                Ok(entry)
+            })
+            .collect::<Result<_, RelayError>>()?;
+        let request = Self::from_auth(entries)?;
+        if request.contract != contract {
+            return Err(RelayError::InvalidRequest(
+                "entries authorize a call to another contract".into(),
+            ));
+        }
+        Ok(request)
+    }
+
+    /// Entries must carry their own signatures; a source-account entry would
+    /// be authorized by the channel's envelope signature
+    fn check(&self) -> Result<(), RelayError> {
+        if self.auth.is_empty() {
+            return Err(RelayError::InvalidRequest("no auth entries".into()));
+        }
+        for entry in &self.auth {
+            match &entry.credentials {
+                SorobanCredentials::Address(credentials)
+                    if credentials.signature != ScVal::Void => {}
+                SorobanCredentials::Address(credentials) => {
+                    return Err(RelayError::InvalidRequest(format!(
+                        "entry for {} is not signed",
+                        credentials.address
+                    )))
+                }
+                SorobanCredentials::SourceAccount => {
+                    return Err(RelayError::InvalidRequest(
+                        "source account credentials".into(),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Relayed {
+    /// Hash of whichever submission made it into a ledger
+    pub hash: String,
+    /// Simulated return value of the call
+    pub result: ScVal,
+    pub fee_bumps: u32,
+}
+
+/// What a submission's answer means for the relay
+pub(crate) enum Outcome {
+    Accepted,
+    Surge,
+    BadSequence,
+    Rejected(String),
+}
+
+impl Outcome {
+    pub(crate) fn of(status: &SendStatus) -> Self {
+        let SendStatus::Error(result) = status else {
+            return match status {
+                SendStatus::TryAgainLater => Outcome::Surge,
+                _ => Outcome::Accepted,
+            };
+        };
+        match result.as_ref().map(|result| &result.result) {
+            Some(TransactionResultResult::TxInsufficientFee) => Outcome::Surge,
+            Some(TransactionResultResult::TxBadSeq) => Outcome::BadSequence,
+            Some(TransactionResultResult::TxFeeBumpInnerFailed(inner)) => {
+                match &inner.result.result {
+                    InnerTransactionResultResult::TxBadSeq => Outcome::BadSequence,
+                    other => Outcome::Rejected(other.name().to_string()),
+                }
+            }
+            Some(other) => Outcome::Rejected(other.name().to_string()),
+            None => Outcome::Rejected("ERROR".into()),
+        }
+    }
+}
+
+pub struct Relayer<T = HttpTransport> {
+    pub(crate) rpc: Rpc<T>,
+    pub(crate) network_id: [u8; 32],
+    pub(crate) channels: ChannelPool,
+    fee_account: SigningKey,
+    pub(crate) config: RelayConfig,
+    quarantine: Quarantine,
+}
+
+impl Relayer<HttpTransport> {
+    /// Relayer for the RPC node at `rpc_url`, sourcing transactions from
+    /// `channels` and paying fee bumps from `fee_account`
+    pub fn new(
+        rpc_url: &str,
+        network_passphrase: &str,
+        channels: Vec<SigningKey>,
+        fee_account: SigningKey,
+    ) -> Self {
+        Self::with_transport(
+            HttpTransport::new(rpc_url),
+            network_passphrase,
+            channels,
+            fee_account,
+        )
+    }
+}
+
+impl<T: Transport> Relayer<T> {
+    pub fn with_transport(
+        transport: T,
+        network_passphrase: &str,
+        channels: Vec<SigningKey>,
+        fee_account: SigningKey,
+    ) -> Self {
+        Self {
+            rpc: Rpc::new(transport),
+            network_id: network_id(network_passphrase),
+            channels: ChannelPool::new(channels),
+            fee_account,
+            config: RelayConfig::default(),
+            quarantine: Quarantine::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: RelayConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn rpc(&self) -> &Rpc<T> {
+        &self.rpc
+    }
+
+    pub fn channels(&self) -> &ChannelPool {
+        &self.channels
+    }
+
+    /// Refuse requests signed with the key that has `fingerprint`
+    pub fn quarantine(&self, fingerprint: [u8; 32]) {
+        self.quarantine.add(fingerprint);
+    }
+
+    pub fn release(&self, fingerprint: &[u8; 32]) {
+        self.quarantine.remove(fingerprint);
+    }
+
+    pub fn is_quarantined(&self, fingerprint: &[u8; 32]) -> bool {
+        self.quarantine.contains(fingerprint)
+    }
+
+    /// Quarantine or release a key from a wallet's `key_compromised` or
+    /// `quarantine_released` event topics; whether the event was one of them
+    pub fn follow_event(&self, topics: &[ScVal]) -> bool {
+        self.quarantine.follow(topics)
+    }
+
+    /// Submit `request` and wait until it's in a ledger
+    pub async fn relay(&self, request: &RelayRequest) -> Result<Relayed, RelayError> {
+        request.check()?;
+        self.quarantine.check(request)?;
+        let lease = self.channels.lease().await;
+
+        let mut rebuilt = false;
+        let (sequence, envelope, simulation, first) = loop {
+            let sequence = lease.next_sequence(&self.rpc).await?;
+            let (tx, simulation) = self.build(&lease, sequence, request).await?;
+            let envelope = sign(tx, &self.network_id, lease.key())?;
+            let sent = self.rpc.send_status(&envelope).await?;
+            match Outcome::of(&sent.status) {
+                Outcome::BadSequence if !rebuilt => {
+                    lease.invalidate();
+                    rebuilt = true;
+                }
+                outcome => break (sequence, envelope, simulation, (sent.hash, outcome)),
+            }
+        };
+
+        let mut accepted = Vec::new();
+        let mut bid = i64::from(self.config.base_fee);
+        let mut fee_bumps = 0;
+        let (mut hash, mut outcome) = first;
+        loop {
+            match outcome {
+                Outcome::Accepted => {
+                    lease.consumed(sequence);
+                    accepted.push(hash.clone());
+                    if let Some(hash) = self.confirm(&accepted).await? {
+                        return Ok(Relayed {
+                            hash,
+                            result: simulation.result,
+                            fee_bumps,
+                        });
+                    }
+                }
+                Outcome::Surge => {}
+                // An earlier submission took the sequence: it landed or is
+                // about to, so only polling is left
+                Outcome::BadSequence if !accepted.is_empty() => {
+                    if let Some(hash) = self.confirm(&accepted).await? {
+                        return Ok(Relayed {
+                            hash,
+                            result: simulation.result,
+                            fee_bumps,
+                        });
+                    }
+                    let last = accepted.last().cloned().unwrap_or(hash);
+                    return Err(SdkError::Timeout(last).into());
+                }
+                Outcome::BadSequence => {
+                    lease.invalidate();
+                    return Err(RelayError::Rejected {
+                        hash,
+                        code: "TxBadSeq".into(),
+                    });
+                }
+                Outcome::Rejected(code) => return Err(RelayError::Rejected { hash, code }),
+            }
+
+            bid = bid.saturating_mul(self.config.bump_factor);
+            if bid > self.config.max_fee {
+                return Err(match accepted.last() {
+                    Some(hash) => SdkError::Timeout(hash.clone()).into(),
+                    None => RelayError::FeeCapExceeded { fee: bid },
+                });
+            }
+            // One inclusion fee for the inner operation, one for the bump
+            let fee = i64::try_from(simulation.min_resource_fee)
+                .unwrap_or(i64::MAX)
+                .saturating_add(bid.saturating_mul(2));
+            let bump = fee_bump(envelope.clone(), fee, &self.network_id, &self.fee_account)?;
+            fee_bumps += 1;
+            let sent = self.rpc.send_status(&bump).await?;
+            outcome = Outcome::of(&sent.status);
+            hash = sent.hash;
+        }
+    }
+
+    /// The call as the channel's transaction at `sequence`, with the
+    /// simulated resources attached
+    async fn build(
+        &self,
+        lease: &Lease<'_>,
+        sequence: i64,
+        request: &RelayRequest,
+    ) -> Result<(Transaction, Simulation), RelayError> {
+        let mut tx = invoke(
+            &lease.public_key(),
+            sequence,
+            &request.contract,
+            &request.function,
+            request.args.clone(),
+        )?;
+        set_auth(&mut tx, request.auth.clone())?;
+        let simulation = self.rpc.simulate(&unsigned(&tx)).await?;
+
+        tx.fee = self
+            .config
+            .base_fee
+            .saturating_add(simulation.min_resource_fee.try_into().unwrap_or(u32::MAX));
+        tx.ext = TransactionExt::V1(simulation.transaction_data.clone());
+        Ok((tx, simulation))
+    }
+
+    /// Poll `hashes` until one is in a ledger, or `None` if none shows up
+    pub(crate) async fn confirm(&self, hashes: &[String]) -> Result<Option<String>, RelayError> {
+        for attempt in 0..self.config.confirm_attempts {
+            if attempt > 0 {
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+            for hash in hashes {
+                match self.rpc.status(hash).await? {
+                    TxStatus::Success => return Ok(Some(hash.clone())),
+                    TxStatus::NotFound => {}
+                    TxStatus::Failed(status) => {
+                        return Err(SdkError::TransactionFailed {
+                            hash: hash.clone(),
+                            status,
+                        }
+                        .into())
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
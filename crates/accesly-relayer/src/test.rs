@@ -0,0 +1,572 @@
+// src/test.rs
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use accesly_client::strkey::StrKey;
+use accesly_sdk::rpc::Transport;
+use accesly_sdk::SdkError;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::SigningKey;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use soroban_sdk::{
+    testutils::Address as _,
+    xdr::{
+        AccountEntry, AccountEntryExt, AccountId, Asset, FeeBumpTransactionInnerTx, Hash,
+        InvokeContractArgs, LedgerEntryData, Limits, MuxedAccount, OperationBody, PublicKey,
+        ReadXdr, ScAddress, ScVal, SequenceNumber, SorobanAddressCredentials,
+        SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+        SorobanCredentials, SorobanTransactionData, Thresholds, TransactionEnvelope,
+        TransactionResult, TransactionResultExt, TransactionResultResult, Uint256, WriteXdr,
+    },
+    Address, Env,
+};
+
+use crate::relay::MAX_TX_SIZE;
+use crate::{
+    plan_rebalance, ChannelPool, RelayConfig, RelayError, RelayRequest, Relayer, Transfer,
+};
+
+const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+/// Answers each request with the next scripted response, checking the method
+struct FakeRpc {
+    script: Mutex<VecDeque<(&'static str, Value)>>,
+    requests: Mutex<Vec<(String, Value)>>,
+}
+
+impl FakeRpc {
+    fn new(script: Vec<(&'static str, Value)>) -> Self {
+        Self {
+            script: Mutex::new(script.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Transport for FakeRpc {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, SdkError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), params));
+        let (expected, response) = self
+            .script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("unexpected request");
+        assert_eq!(method, expected);
+        Ok(response)
+    }
+}
+
+fn relayer(script: Vec<(&'static str, Value)>) -> Relayer<FakeRpc> {
+    pool_relayer(vec![key(5)], script)
+}
+
+fn pool_relayer(channels: Vec<SigningKey>, script: Vec<(&'static str, Value)>) -> Relayer<FakeRpc> {
+    Relayer::with_transport(FakeRpc::new(script), PASSPHRASE, channels, key(6)).with_config(
+        RelayConfig {
+            confirm_attempts: 2,
+            poll_interval: Duration::ZERO,
+            ..RelayConfig::default()
+        },
+    )
+}
+
+fn xdr64(value: &impl WriteXdr) -> String {
+    STANDARD.encode(value.to_xdr(Limits::none()).unwrap())
+}
+
+/// Wallet entry for `token.transfer(wallet, to, 1)`, signed unless `signature`
+/// is void
+fn transfer_entry(env: &Env, signature: ScVal) -> SorobanAuthorizationEntry {
+    let wallet = ScAddress::from(&Address::generate(env));
+    let to = ScAddress::from(&Address::generate(env));
+    let args = [
+        ScVal::Address(wallet.clone()),
+        ScVal::Address(to),
+        ScVal::U32(1),
+    ];
+    SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: wallet,
+            nonce: 7,
+            signature_expiration_ledger: 1_060,
+            signature,
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: ScAddress::from(&Address::generate(env)),
+                function_name: "transfer".try_into().unwrap(),
+                args: args.to_vec().try_into().unwrap(),
+            }),
+            sub_invocations: Default::default(),
+        },
+    }
+}
+
+/// The `C...` address of the contract `entry` authorizes a call to
+fn contract_of(entry: &SorobanAuthorizationEntry) -> String {
+    let SorobanAuthorizedFunction::ContractFn(call) = &entry.root_invocation.function else {
+        unreachable!()
+    };
+    let ScAddress::Contract(Hash(id)) = &call.contract_address else {
+        unreachable!()
+    };
+    StrKey::Contract(*id).to_string()
+}
+
+fn signed_request(env: &Env) -> RelayRequest {
+    let signature = ScVal::Vec(Some(vec![ScVal::U32(0)].try_into().unwrap()));
+    let entry = transfer_entry(env, signature);
+    RelayRequest::from_xdr(&contract_of(&entry), &[xdr64(&entry)]).unwrap()
+}
+
+fn simulation(fee: u64) -> Value {
+    // Empty footprint and zero resources
+    let data = SorobanTransactionData::from_xdr([0u8; 32], Limits::none()).unwrap();
+    json!({
+        "transactionData": xdr64(&data),
+        "minResourceFee": fee.to_string(),
+        "results": [{ "xdr": xdr64(&ScVal::Void), "auth": [] }],
+        "latestLedger": 1_000,
+    })
+}
+
+/// `getLedgerEntries` result for `source`'s account at `sequence`
+fn account(source: &SigningKey, sequence: i64) -> Value {
+    funded(source, sequence, 100_000_000)
+}
+
+fn funded(source: &SigningKey, sequence: i64, balance: i64) -> Value {
+    let entry = LedgerEntryData::Account(AccountEntry {
+        account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+            source.verifying_key().to_bytes(),
+        ))),
+        balance,
+        seq_num: SequenceNumber(sequence),
+        num_sub_entries: 0,
+        inflation_dest: None,
+        flags: 0,
+        home_domain: Default::default(),
+        thresholds: Thresholds([1, 0, 0, 0]),
+        signers: Default::default(),
+        ext: AccountEntryExt::V0,
+    });
+    json!({ "entries": [{ "xdr": xdr64(&entry) }] })
+}
+
+fn sent(status: &str, hash: &str) -> Value {
+    json!({ "status": status, "hash": hash })
+}
+
+fn send_error(hash: &str, result: TransactionResultResult) -> Value {
+    let result = TransactionResult {
+        fee_charged: 100,
+        result,
+        ext: TransactionResultExt::V0,
+    };
+    json!({ "status": "ERROR", "hash": hash, "errorResultXdr": xdr64(&result) })
+}
+
+/// Envelopes the relayer submitted, in order
+fn submitted(relayer: &Relayer<FakeRpc>) -> Vec<TransactionEnvelope> {
+    let requests = relayer.rpc().transport().requests.lock().unwrap();
+    requests
+        .iter()
+        .filter(|(method, _)| method == "sendTransaction")
+        .map(|(_, params)| {
+            TransactionEnvelope::from_xdr(
+                STANDARD
+                    .decode(params["transaction"].as_str().unwrap())
+                    .unwrap(),
+                Limits::none(),
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+fn sequence_of(envelope: &TransactionEnvelope) -> i64 {
+    match envelope {
+        TransactionEnvelope::Tx(envelope) => envelope.tx.seq_num.0,
+        _ => panic!("expected a v1 envelope"),
+    }
+}
+
+// ============================================================================
+// RELAY TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn test_relay_sources_from_a_channel_and_caches_its_sequence() {
+    let env = Env::default();
+    let request = signed_request(&env);
+    assert_eq!(request.function, "transfer");
+
+    let relayer = relayer(vec![
+        ("getLedgerEntries", account(&key(5), 41)),
+        ("simulateTransaction", simulation(5_000)),
+        ("sendTransaction", sent("PENDING", "aa")),
+        ("getTransaction", json!({ "status": "NOT_FOUND" })),
+        ("getTransaction", json!({ "status": "SUCCESS" })),
+        // The second relay reuses the cached sequence
+        ("simulateTransaction", simulation(5_000)),
+        ("sendTransaction", sent("PENDING", "bb")),
+        ("getTransaction", json!({ "status": "SUCCESS" })),
+    ]);
+
+    let relayed = relayer.relay(&request).await.unwrap();
+    assert_eq!(relayed.hash, "aa");
+    assert_eq!(relayed.fee_bumps, 0);
+    assert_eq!(relayer.relay(&request).await.unwrap().hash, "bb");
+
+    let envelopes = submitted(&relayer);
+    assert_eq!(sequence_of(&envelopes[0]), 42);
+    assert_eq!(sequence_of(&envelopes[1]), 43);
+    let TransactionEnvelope::Tx(envelope) = &envelopes[0] else {
+        unreachable!()
+    };
+    assert_eq!(
+        envelope.tx.source_account,
+        MuxedAccount::Ed25519(Uint256(key(5).verifying_key().to_bytes()))
+    );
+    assert_eq!(envelope.tx.fee, 5_100);
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("expected a contract call");
+    };
+    assert_eq!(op.auth.to_vec(), request.auth);
+}
+
+#[tokio::test]
+async fn test_surge_is_fee_bumped_by_the_fee_account() {
+    let env = Env::default();
+    let relayer = relayer(vec![
+        ("getLedgerEntries", account(&key(5), 41)),
+        ("simulateTransaction", simulation(5_000)),
+        ("sendTransaction", sent("TRY_AGAIN_LATER", "aa")),
+        ("sendTransaction", sent("PENDING", "bb")),
+        ("getTransaction", json!({ "status": "SUCCESS" })),
+    ]);
+
+    let relayed = relayer.relay(&signed_request(&env)).await.unwrap();
+    assert_eq!(relayed.hash, "bb");
+    assert_eq!(relayed.fee_bumps, 1);
+
+    let envelopes = submitted(&relayer);
+    let TransactionEnvelope::TxFeeBump(bump) = &envelopes[1] else {
+        panic!("expected a fee bump");
+    };
+    assert_eq!(
+        bump.tx.fee_source,
+        MuxedAccount::Ed25519(Uint256(key(6).verifying_key().to_bytes()))
+    );
+    // Resource fee plus ten times the bid, for the operation and the bump
+    assert_eq!(bump.tx.fee, 5_000 + 2 * 1_000);
+    let FeeBumpTransactionInnerTx::Tx(inner) = &bump.tx.inner_tx;
+    assert_eq!(TransactionEnvelope::Tx(inner.clone()), envelopes[0]);
+}
+
+#[tokio::test]
+async fn test_stuck_transaction_is_bumped_and_both_hashes_polled() {
+    let env = Env::default();
+    let relayer = relayer(vec![
+        ("getLedgerEntries", account(&key(5), 41)),
+        ("simulateTransaction", simulation(5_000)),
+        ("sendTransaction", sent("PENDING", "aa")),
+        ("getTransaction", json!({ "status": "NOT_FOUND" })),
+        ("getTransaction", json!({ "status": "NOT_FOUND" })),
+        ("sendTransaction", sent("PENDING", "bb")),
+        ("getTransaction", json!({ "status": "NOT_FOUND" })),
+        ("getTransaction", json!({ "status": "SUCCESS" })),
+    ]);
+
+    let relayed = relayer.relay(&signed_request(&env)).await.unwrap();
+    assert_eq!(relayed.hash, "bb");
+    assert_eq!(relayed.fee_bumps, 1);
+
+    let requests = relayer.rpc().transport().requests.lock().unwrap();
+    let polled: Vec<&str> = requests
+        .iter()
+        .filter(|(method, _)| method == "getTransaction")
+        .map(|(_, params)| params["hash"].as_str().unwrap())
+        .collect();
+    assert_eq!(polled, ["aa", "aa", "aa", "bb"]);
+}
+
+#[tokio::test]
+async fn test_bad_sequence_reloads_the_channel_once() {
+    let env = Env::default();
+    let relayer = relayer(vec![
+        ("getLedgerEntries", account(&key(5), 41)),
+        ("simulateTransaction", simulation(5_000)),
+        (
+            "sendTransaction",
+            send_error("aa", TransactionResultResult::TxBadSeq),
+        ),
+        ("getLedgerEntries", account(&key(5), 50)),
+        ("simulateTransaction", simulation(5_000)),
+        ("sendTransaction", sent("PENDING", "bb")),
+        ("getTransaction", json!({ "status": "SUCCESS" })),
+    ]);
+
+    assert_eq!(
+        relayer.relay(&signed_request(&env)).await.unwrap().hash,
+        "bb"
+    );
+    let envelopes = submitted(&relayer);
+    assert_eq!(sequence_of(&envelopes[0]), 42);
+    assert_eq!(sequence_of(&envelopes[1]), 51);
+}
+
+#[tokio::test]
+async fn test_relay_stops_at_the_fee_cap() {
+    let env = Env::default();
+    let relayer = relayer(vec![
+        ("getLedgerEntries", account(&key(5), 41)),
+        ("simulateTransaction", simulation(5_000)),
+        ("sendTransaction", sent("TRY_AGAIN_LATER", "aa")),
+        (
+            "sendTransaction",
+            send_error("bb", TransactionResultResult::TxInsufficientFee),
+        ),
+    ])
+    .with_config(RelayConfig {
+        max_fee: 1_000,
+        ..RelayConfig::default()
+    });
+
+    let err = relayer.relay(&signed_request(&env)).await.unwrap_err();
+    assert!(
+        matches!(err, RelayError::FeeCapExceeded { fee: 10_000 }),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_unsigned_and_source_account_entries_are_refused() {
+    let env = Env::default();
+    let relayer = relayer(Vec::new());
+
+    let unsigned = RelayRequest::from_auth(vec![transfer_entry(&env, ScVal::Void)]).unwrap();
+    let err = relayer.relay(&unsigned).await.unwrap_err();
+    assert!(err.to_string().contains("is not signed"), "{err}");
+
+    let mut source = signed_request(&env);
+    source.auth[0].credentials = SorobanCredentials::SourceAccount;
+    assert!(matches!(
+        relayer.relay(&source).await.unwrap_err(),
+        RelayError::InvalidRequest(_)
+    ));
+    let contract = StrKey::Contract([7; 32]).to_string();
+    assert!(RelayRequest::from_xdr(&contract, &["not xdr"]).is_err());
+}
+
+#[test]
+fn test_malformed_contract_address_is_refused() {
+    let env = Env::default();
+    let entry = transfer_entry(&env, ScVal::Void);
+    let entries = [xdr64(&entry)];
+    let contract = contract_of(&entry);
+    let refused = |contract: &str| match RelayRequest::from_xdr(contract, &entries) {
+        Err(RelayError::InvalidRequest(err)) => err,
+        other => panic!("{other:?}"),
+    };
+
+    // A one-character typo fails the checksum
+    let mut typo = contract.clone().into_bytes();
+    typo[10] = if typo[10] == b'A' { b'B' } else { b'A' };
+    let typo = String::from_utf8(typo).unwrap();
+    assert!(refused(&typo).contains("checksum"), "{}", refused(&typo));
+    assert!(refused(&contract.to_lowercase()).contains("unexpected"));
+    let account = StrKey::Account([7; 32]).to_string();
+    assert!(refused(&account).contains("expected a contract"));
+    let other = StrKey::Contract([7; 32]).to_string();
+    assert!(refused(&other).contains("another contract"));
+
+    assert!(RelayRequest::from_xdr(&contract, &entries).is_ok());
+}
+
+#[test]
+fn test_oversized_entries_are_refused() {
+    let env = Env::default();
+    let signature = ScVal::Vec(Some(vec![ScVal::U32(0)].try_into().unwrap()));
+    let mut entry = transfer_entry(&env, signature);
+    let contract = contract_of(&entry);
+    let padded = |entry: &mut SorobanAuthorizationEntry, size: usize| {
+        let SorobanAuthorizedFunction::ContractFn(call) = &mut entry.root_invocation.function
+        else {
+            unreachable!()
+        };
+        let padding = ScVal::Bytes(vec![0u8; size].try_into().unwrap());
+        call.args = vec![padding].try_into().unwrap();
+        xdr64(entry)
+    };
+    let refused = |entries: &[String]| {
+        matches!(
+            RelayRequest::from_xdr(&contract, entries),
+            Err(RelayError::InvalidRequest(_))
+        )
+    };
+
+    assert!(refused(&[padded(&mut entry, MAX_TX_SIZE)]));
+    // Nor does splitting it up help
+    let half = padded(&mut entry, MAX_TX_SIZE / 2);
+    assert!(refused(&[half.clone(), half]));
+
+    let fits = padded(&mut entry, MAX_TX_SIZE / 4);
+    assert!(RelayRequest::from_xdr(&contract, &[fits.clone(), fits]).is_ok());
+}
+
+#[tokio::test]
+async fn test_quarantined_keys_are_refused() {
+    let env = Env::default();
+    let relayer = relayer(Vec::new());
+    let device = [9u8; 32];
+    let fingerprint: [u8; 32] = Sha256::digest(device).into();
+    let signature = ScVal::Vec(Some(
+        vec![
+            ScVal::Symbol("Device".try_into().unwrap()),
+            ScVal::Bytes(device.to_vec().try_into().unwrap()),
+            ScVal::Bytes([1u8; 64].to_vec().try_into().unwrap()),
+        ]
+        .try_into()
+        .unwrap(),
+    ));
+    let request = RelayRequest::from_auth(vec![transfer_entry(&env, signature)]).unwrap();
+
+    let event = |code: &str| {
+        [
+            ScVal::Symbol(code.try_into().unwrap()),
+            ScVal::Bytes(fingerprint.to_vec().try_into().unwrap()),
+        ]
+    };
+    assert!(!relayer.follow_event(&event("wallet_frozen")));
+    assert!(relayer.follow_event(&event("key_compromised")));
+    assert!(relayer.is_quarantined(&fingerprint));
+    assert!(matches!(
+        relayer.relay(&request).await.unwrap_err(),
+        RelayError::Quarantined { fingerprint: refused } if refused == fingerprint
+    ));
+
+    assert!(relayer.follow_event(&event("quarantine_released")));
+    assert!(!relayer.is_quarantined(&fingerprint));
+}
+
+// ============================================================================
+// POOL TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn test_channels_are_leased_round_robin() {
+    let pool = ChannelPool::new(vec![key(5), key(7), key(8)]);
+
+    let first = pool.lease().await;
+    let second = pool.lease().await;
+    assert_eq!((first.index(), second.index()), (0, 1));
+    assert!(pool.try_lease(1).is_none());
+    drop(first);
+
+    // Channel 2 has waited longest, then the one just released
+    assert_eq!(pool.lease().await.index(), 2);
+    assert_eq!(pool.lease().await.index(), 0);
+    drop(second);
+    assert_eq!(pool.try_lease(1).unwrap().public_key(), pool.public_key(1));
+}
+
+#[test]
+fn test_rebalance_plan() {
+    // Largest need first, from the donor with the most to spare
+    let (transfers, shortfall) = plan_rebalance(&[50, 1, 300, 8, 150], 10, 100);
+    assert_eq!(
+        transfers,
+        [
+            Transfer {
+                from: 2,
+                to: 1,
+                amount: 99
+            },
+            Transfer {
+                from: 2,
+                to: 3,
+                amount: 92
+            },
+        ]
+    );
+    assert_eq!(shortfall, 0);
+
+    let (transfers, shortfall) = plan_rebalance(&[5, 150, 120], 10, 100);
+    assert_eq!(
+        transfers,
+        [
+            Transfer {
+                from: 1,
+                to: 0,
+                amount: 50
+            },
+            Transfer {
+                from: 2,
+                to: 0,
+                amount: 20
+            },
+        ]
+    );
+    assert_eq!(shortfall, 25);
+}
+
+#[tokio::test]
+async fn test_rebalance_tops_up_drained_channels() {
+    let relayer = pool_relayer(
+        vec![key(5), key(7), key(8)],
+        vec![
+            ("getLedgerEntries", funded(&key(5), 10, 10_000_000)),
+            ("getLedgerEntries", funded(&key(7), 70, 600_000_000)),
+            ("getLedgerEntries", funded(&key(8), 80, 100_000_000)),
+            // The donor's sequence, under its lease
+            ("getLedgerEntries", funded(&key(7), 70, 600_000_000)),
+            ("sendTransaction", sent("PENDING", "aa")),
+            ("getTransaction", json!({ "status": "SUCCESS" })),
+        ],
+    );
+
+    let rebalanced = relayer.rebalance().await.unwrap();
+    let topped_up = Transfer {
+        from: 1,
+        to: 0,
+        amount: 190_000_000,
+    };
+    assert_eq!(rebalanced.transfers, [topped_up]);
+    assert!(rebalanced.skipped.is_empty());
+    assert_eq!(rebalanced.shortfall, 0);
+
+    let envelopes = submitted(&relayer);
+    let TransactionEnvelope::Tx(envelope) = &envelopes[0] else {
+        panic!("expected a v1 envelope");
+    };
+    assert_eq!(
+        envelope.tx.source_account,
+        MuxedAccount::Ed25519(Uint256(key(7).verifying_key().to_bytes()))
+    );
+    assert_eq!(envelope.tx.seq_num.0, 71);
+    let OperationBody::Payment(payment) = &envelope.tx.operations[0].body else {
+        panic!("expected a payment");
+    };
+    assert_eq!(
+        payment.destination,
+        MuxedAccount::Ed25519(Uint256(key(5).verifying_key().to_bytes()))
+    );
+    assert_eq!(payment.asset, Asset::Native);
+    assert_eq!(payment.amount, 190_000_000);
+}
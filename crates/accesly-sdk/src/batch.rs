@@ -0,0 +1,180 @@
+// ---------------------------------------------------------------------------
+// Batch payloads
+//
+// `execute_batch` has the owner sign `ops.to_xdr()`, the `ScVal` encoding of
+// the contract's `Vec<Invocation>`, and the best-effort variant the tuple
+// `(ops, max_failures)`. These build the same bytes from plain XDR types, so
+// apps can sign a batch without a host `Env`. A `#[contracttype]` struct
+// encodes as a map keyed by field name in sorted order and an enum variant
+// as `[name, fields...]`; the fuzz target in `fuzz/` checks the two encoders
+// stay byte for byte identical.
+//
+// A meta-transaction envelope wraps one such call with its own nonce, fee
+// cap and expiry, and the owner signs it without a wallet nonce.
+// ---------------------------------------------------------------------------
+
+use soroban_sdk::xdr::{
+    Int128Parts, Limits, ScAddress, ScMap, ScMapEntry, ScSymbol, ScVal, ScVec, WriteXdr,
+};
+
+use crate::messages::action_message;
+use crate::SdkError;
+
+/// One call of a batch, the contract's `Invocation`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchCall {
+    pub contract: ScAddress,
+    pub function: String,
+    pub args: Vec<ScVal>,
+    /// Nested calls the wallet authorizes for this one
+    pub auth: Vec<NestedCall>,
+}
+
+/// A call below a batch call that the wallet authorizes, the
+/// `InvokerContractAuthEntry::Contract` variant
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NestedCall {
+    pub contract: ScAddress,
+    pub function: String,
+    pub args: Vec<ScVal>,
+    pub sub_calls: Vec<NestedCall>,
+}
+
+impl BatchCall {
+    pub fn to_scval(&self) -> Result<ScVal, SdkError> {
+        let auth = self
+            .auth
+            .iter()
+            .map(NestedCall::to_scval)
+            .collect::<Result<_, _>>()?;
+        map(vec![
+            ("args", vec_val(self.args.clone())?),
+            ("auth", vec_val(auth)?),
+            ("contract", ScVal::Address(self.contract.clone())),
+            ("fn_name", symbol(&self.function)?),
+        ])
+    }
+}
+
+impl NestedCall {
+    pub fn to_scval(&self) -> Result<ScVal, SdkError> {
+        let context = map(vec![
+            ("args", vec_val(self.args.clone())?),
+            ("contract", ScVal::Address(self.contract.clone())),
+            ("fn_name", symbol(&self.function)?),
+        ])?;
+        let sub_invocations = self
+            .sub_calls
+            .iter()
+            .map(NestedCall::to_scval)
+            .collect::<Result<_, _>>()?;
+        let invocation = map(vec![
+            ("context", context),
+            ("sub_invocations", vec_val(sub_invocations)?),
+        ])?;
+        vec_val(vec![symbol("Contract")?, invocation])
+    }
+}
+
+/// A meta-transaction envelope, the contract's `MetaTx`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetaTx {
+    pub call: BatchCall,
+    /// Any value the wallet hasn't seen in an envelope yet
+    pub nonce: u64,
+    /// Most the relayer can charge, in the wallet's fee asset
+    pub max_fee: i128,
+    /// Unix time after which the wallet refuses the envelope
+    pub expiry: u64,
+}
+
+impl MetaTx {
+    pub fn to_scval(&self) -> Result<ScVal, SdkError> {
+        map(vec![
+            ("call", self.call.to_scval()?),
+            ("expiry", ScVal::U64(self.expiry)),
+            (
+                "max_fee",
+                ScVal::I128(Int128Parts {
+                    hi: (self.max_fee >> 64) as i64,
+                    lo: self.max_fee as u64,
+                }),
+            ),
+            ("nonce", ScVal::U64(self.nonce)),
+        ])
+    }
+}
+
+/// What `execute_batch(ops)` has the owner sign as its payload
+pub fn batch_payload(ops: &[BatchCall]) -> Result<Vec<u8>, SdkError> {
+    Ok(ops_val(ops)?.to_xdr(Limits::none())?)
+}
+
+/// What `execute_batch_best_effort(ops, max_failures)` has the owner sign
+/// as its payload
+pub fn best_effort_payload(ops: &[BatchCall], max_failures: u32) -> Result<Vec<u8>, SdkError> {
+    let tuple = vec_val(vec![ops_val(ops)?, ScVal::U32(max_failures)])?;
+    Ok(tuple.to_xdr(Limits::none())?)
+}
+
+/// Message the owner signs for `execute_batch(ops)`
+pub fn batch_message(ops: &[BatchCall], nonce: u64) -> Result<Vec<u8>, SdkError> {
+    Ok(action_message("execute_batch", &batch_payload(ops)?, nonce))
+}
+
+/// Message the owner signs for `execute_batch_best_effort(ops, max_failures)`
+pub fn best_effort_message(
+    ops: &[BatchCall],
+    max_failures: u32,
+    nonce: u64,
+) -> Result<Vec<u8>, SdkError> {
+    let payload = best_effort_payload(ops, max_failures)?;
+    Ok(action_message("execute_batch_best_effort", &payload, nonce))
+}
+
+/// Message the owner signs for `execute_meta_tx(envelope, ..)` on `wallet`,
+/// on the network with `network_id`
+pub fn meta_tx_message(
+    network_id: &[u8; 32],
+    wallet: &ScAddress,
+    envelope: &MetaTx,
+) -> Result<Vec<u8>, SdkError> {
+    let mut message = Vec::new();
+    let wallet = ScVal::Address(wallet.clone()).to_xdr(Limits::none())?;
+    let payload = envelope.to_scval()?.to_xdr(Limits::none())?;
+    accesly_messages::meta_tx_message(&mut message, network_id, &wallet[..], &payload[..]);
+    Ok(message)
+}
+
+fn ops_val(ops: &[BatchCall]) -> Result<ScVal, SdkError> {
+    vec_val(
+        ops.iter()
+            .map(BatchCall::to_scval)
+            .collect::<Result<_, _>>()?,
+    )
+}
+
+fn symbol(name: &str) -> Result<ScVal, SdkError> {
+    let symbol: ScSymbol = name
+        .try_into()
+        .map_err(|_| SdkError::InvalidResponse(format!("bad function {name}")))?;
+    Ok(ScVal::Symbol(symbol))
+}
+
+fn vec_val(items: Vec<ScVal>) -> Result<ScVal, SdkError> {
+    Ok(ScVal::Vec(Some(ScVec(items.try_into()?))))
+}
+
+/// Struct fields, already in sorted order
+fn map(fields: Vec<(&str, ScVal)>) -> Result<ScVal, SdkError> {
+    let entries = fields
+        .into_iter()
+        .map(|(key, val)| {
+            Ok(ScMapEntry {
+                key: symbol(key)?,
+                val,
+            })
+        })
+        .collect::<Result<Vec<_>, SdkError>>()?;
+    Ok(ScVal::Map(Some(ScMap(entries.try_into()?))))
+}
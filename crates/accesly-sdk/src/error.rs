@@ -0,0 +1,63 @@
+use soroban_sdk::xdr;
+
+#[derive(Debug)]
+pub enum SdkError {
+    /// The node couldn't be reached or answered with a JSON-RPC error
+    Rpc(String),
+    /// The node answered with something that doesn't decode
+    InvalidResponse(String),
+    /// The call failed in simulation; holds the host's diagnostic
+    Simulation(String),
+    /// Submitted, but rejected or failed on chain
+    TransactionFailed {
+        hash: String,
+        status: String,
+    },
+    /// Still not in a ledger when polling gave up
+    Timeout(String),
+    /// Auth is needed from an address the SDK holds no key for
+    MissingSigner(String),
+    InvalidAddress(String),
+}
+
+impl std::fmt::Display for SdkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "rpc error: {err}"),
+            Self::InvalidResponse(err) => write!(f, "unexpected rpc response: {err}"),
+            Self::Simulation(err) => write!(f, "simulation failed: {err}"),
+            Self::TransactionFailed { hash, status } => {
+                write!(f, "transaction {hash} failed with status {status}")
+            }
+            Self::Timeout(hash) => write!(f, "transaction {hash} was not confirmed in time"),
+            Self::MissingSigner(address) => write!(f, "no key to authorize {address}"),
+            Self::InvalidAddress(address) => write!(f, "invalid address {address}"),
+        }
+    }
+}
+
+impl std::error::Error for SdkError {}
+
+impl From<reqwest::Error> for SdkError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Rpc(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for SdkError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::InvalidResponse(err.to_string())
+    }
+}
+
+impl From<base64::DecodeError> for SdkError {
+    fn from(err: base64::DecodeError) -> Self {
+        Self::InvalidResponse(err.to_string())
+    }
+}
+
+impl From<xdr::Error> for SdkError {
+    fn from(err: xdr::Error) -> Self {
+        Self::InvalidResponse(err.to_string())
+    }
+}
@@ -0,0 +1,27 @@
+// ---------------------------------------------------------------------------
+// accesly-sdk
+//
+// Async Rust SDK for apps driving Accesly wallets over Soroban RPC. Where
+// `accesly-bindings` calls the contracts inside a host `Env`, this crate
+// builds, signs and submits real transactions: it reads the wallet nonce,
+// builds owner messages byte for byte as the wallet verifies them, signs
+// with the owner's ed25519 key and waits for the result.
+//
+//     let accesly = Accesly::new(rpc_url, passphrase, relayer_key);
+//     let wallet = accesly.create_wallet(factory, &owner_key, &email_hash, &key).await?;
+//     accesly.send_payment(&wallet, &owner, token, to, 10_000_000).await?;
+// ---------------------------------------------------------------------------
+
+pub mod batch;
+mod error;
+pub mod messages;
+pub mod rpc;
+pub mod session_risk;
+pub mod tx;
+pub mod wallet;
+
+pub use error::SdkError;
+pub use wallet::Accesly;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,115 @@
+// ---------------------------------------------------------------------------
+// Signed messages
+//
+// The byte strings the wallet verifies, built by `accesly_messages` like the
+// contract builds them:
+//
+//   owner actions    action || payload || nonce          (`action_message`)
+//   __check_auth     signature_payload || nonce          (`auth_message`,
+//                    with a channel and expiry when set)
+//
+// `signature_payload` is the hash the host passes `__check_auth`: sha256 of
+// the `HashIdPreimage::SorobanAuthorization` for the auth entry, so it
+// commits to the network, the entry's nonce and expiration ledger and the
+// whole invocation tree.
+// ---------------------------------------------------------------------------
+
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+use soroban_sdk::xdr::{
+    Hash, HashIdPreimage, HashIdPreimageSorobanAuthorization, Limits, ScBytes, ScVal, ScVec,
+    SorobanAuthorizationEntry, SorobanAuthorizedInvocation, SorobanCredentials, WriteXdr,
+};
+
+use crate::SdkError;
+
+/// Network id for a passphrase, as the host and transaction hashes use it
+pub fn network_id(passphrase: &str) -> [u8; 32] {
+    Sha256::digest(passphrase.as_bytes()).into()
+}
+
+/// `action || payload || nonce`, what `require_owner_signature` checks
+pub fn action_message(action: &str, payload: &[u8], nonce: u64) -> Vec<u8> {
+    let mut message = Vec::new();
+    accesly_messages::action_message(&mut message, action, payload, nonce);
+    message
+}
+
+/// Message the owner signs for `update_owner(new_owner)`
+pub fn update_owner_message(new_owner: &[u8; 32], nonce: u64) -> Vec<u8> {
+    let mut message = Vec::new();
+    accesly_messages::update_owner_message(&mut message, new_owner, nonce);
+    message
+}
+
+/// What a `__check_auth` signature signs: payload || nonce on channel 0,
+/// payload || channel || nonce on any other, and
+/// payload || channel || nonce || valid_until_ledger when it expires
+pub fn auth_message(
+    payload: &[u8; 32],
+    channel: u32,
+    nonce: u64,
+    valid_until_ledger: Option<u32>,
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    accesly_messages::auth_message(&mut message, payload, channel, nonce, valid_until_ledger);
+    message
+}
+
+/// The `signature_payload` `__check_auth` receives for an auth entry with
+/// this nonce, expiration ledger and invocation
+pub fn authorization_payload(
+    network_id: &[u8; 32],
+    nonce: i64,
+    signature_expiration_ledger: u32,
+    invocation: &SorobanAuthorizedInvocation,
+) -> Result<[u8; 32], SdkError> {
+    let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+        network_id: Hash(*network_id),
+        nonce,
+        signature_expiration_ledger,
+        invocation: invocation.clone(),
+    });
+    Ok(Sha256::digest(preimage.to_xdr(Limits::none())?).into())
+}
+
+/// `AuthSignature::Ed25519(signature)` as the host decodes it
+pub fn ed25519_auth_signature(signature: &[u8; 64]) -> ScVal {
+    let variant = ScVal::Symbol("Ed25519".try_into().unwrap());
+    let signature = ScVal::Bytes(ScBytes(signature.to_vec().try_into().unwrap()));
+    ScVal::Vec(Some(ScVec(
+        [variant, signature].to_vec().try_into().unwrap(),
+    )))
+}
+
+/// Sign a wallet's auth entry with the owner key against wallet nonce
+/// `nonce`, valid up to `expiration_ledger`
+pub fn sign_auth_entry(
+    entry: &SorobanAuthorizationEntry,
+    network_id: &[u8; 32],
+    owner: &SigningKey,
+    nonce: u64,
+    expiration_ledger: u32,
+) -> Result<SorobanAuthorizationEntry, SdkError> {
+    let SorobanCredentials::Address(credentials) = &entry.credentials else {
+        return Err(SdkError::InvalidResponse(
+            "auth entry is for the source account".into(),
+        ));
+    };
+
+    let mut credentials = credentials.clone();
+    credentials.signature_expiration_ledger = expiration_ledger;
+    let payload = authorization_payload(
+        network_id,
+        credentials.nonce,
+        expiration_ledger,
+        &entry.root_invocation,
+    )?;
+    let signature = owner.sign(&auth_message(&payload, 0, nonce, None));
+    credentials.signature = ed25519_auth_signature(&signature.to_bytes());
+
+    Ok(SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(credentials),
+        root_invocation: entry.root_invocation.clone(),
+    })
+}
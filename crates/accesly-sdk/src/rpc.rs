@@ -0,0 +1,323 @@
+// ---------------------------------------------------------------------------
+// Async Soroban RPC
+//
+// The handful of JSON-RPC methods a wallet flow needs, over a `Transport` so
+// apps can bring their own HTTP stack (or a fake, in tests). XDR is decoded
+// here; callers only see typed values.
+// ---------------------------------------------------------------------------
+
+use std::future::Future;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use soroban_sdk::xdr::{
+    AccountEntry, AccountId, DiagnosticEvent, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits,
+    PublicKey, ReadXdr, ScVal, SorobanAuthorizationEntry, SorobanTransactionData,
+    TransactionEnvelope, TransactionResult, Uint256, WriteXdr,
+};
+
+use crate::SdkError;
+
+/// How often `wait` asks for a submitted transaction
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How many times `wait` asks before giving up, about six ledger closes
+pub const POLL_ATTEMPTS: u32 = 30;
+
+/// Carries one JSON-RPC request and returns its `result` member
+pub trait Transport {
+    fn call(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> impl Future<Output = Result<Value, SdkError>> + Send;
+}
+
+/// `Transport` over HTTP with reqwest
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, SdkError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(err) = response.get("error") {
+            return Err(SdkError::Rpc(err.to_string()));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| SdkError::InvalidResponse("response has no result".into()))
+    }
+}
+
+/// Decoded `simulateTransaction` response for a single invocation
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// Return value of the invocation
+    pub result: ScVal,
+    /// Auth entries the invocation needs; signed ones come back as given
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    /// Footprint and resources to attach before submitting
+    pub transaction_data: SorobanTransactionData,
+    /// Resource fee in stroops, on top of the inclusion fee
+    pub min_resource_fee: u64,
+    /// Events the invocation emitted, including those of failed subcalls
+    pub events: Vec<DiagnosticEvent>,
+    pub latest_ledger: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSimulation {
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    results: Vec<RawResult>,
+    #[serde(default)]
+    transaction_data: String,
+    #[serde(default)]
+    min_resource_fee: String,
+    #[serde(default)]
+    events: Vec<String>,
+    latest_ledger: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawResult {
+    xdr: String,
+    #[serde(default)]
+    auth: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSend {
+    status: String,
+    hash: String,
+    #[serde(default)]
+    error_result_xdr: Option<String>,
+}
+
+/// How the node took a submitted transaction
+#[derive(Clone, Debug, PartialEq)]
+pub enum SendStatus {
+    Pending,
+    /// Already queued or in a ledger
+    Duplicate,
+    /// The queue is full for now, or outbid
+    TryAgainLater,
+    /// Failed validation; the result says why when the node sent one
+    Error(Option<TransactionResult>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Sent {
+    pub hash: String,
+    pub status: SendStatus,
+}
+
+/// Where a submitted transaction is, per `getTransaction`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TxStatus {
+    NotFound,
+    Success,
+    Failed(String),
+}
+
+pub struct Rpc<T> {
+    transport: T,
+}
+
+impl<T: Transport> Rpc<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Sequence of the latest ledger the node has ingested
+    pub async fn latest_ledger(&self) -> Result<u32, SdkError> {
+        let result = self.transport.call("getLatestLedger", json!({})).await?;
+        result
+            .get("sequence")
+            .and_then(Value::as_u64)
+            .map(|sequence| sequence as u32)
+            .ok_or_else(|| SdkError::InvalidResponse("latest ledger has no sequence".into()))
+    }
+
+    /// Current sequence number of the classic account `account`
+    pub async fn sequence(&self, account: &[u8; 32]) -> Result<i64, SdkError> {
+        Ok(self.account(account).await?.seq_num.0)
+    }
+
+    /// Ledger entry of the classic account `account`
+    pub async fn account(&self, account: &[u8; 32]) -> Result<AccountEntry, SdkError> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(*account))),
+        });
+        let params = json!({ "keys": [STANDARD.encode(key.to_xdr(Limits::none())?)] });
+        let result = self.transport.call("getLedgerEntries", params).await?;
+
+        let entry = result
+            .get("entries")
+            .and_then(|entries| entries.get(0))
+            .and_then(|entry| entry.get("xdr"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| SdkError::InvalidResponse("source account not found".into()))?;
+        match LedgerEntryData::from_xdr(STANDARD.decode(entry)?, Limits::none())? {
+            LedgerEntryData::Account(account) => Ok(account),
+            _ => Err(SdkError::InvalidResponse("not an account entry".into())),
+        }
+    }
+
+    /// Simulate `envelope`, failing with the host's diagnostic if it traps
+    pub async fn simulate(&self, envelope: &TransactionEnvelope) -> Result<Simulation, SdkError> {
+        let params = json!({ "transaction": STANDARD.encode(envelope.to_xdr(Limits::none())?) });
+        let raw: RawSimulation =
+            serde_json::from_value(self.transport.call("simulateTransaction", params).await?)?;
+
+        if let Some(error) = raw.error {
+            return Err(SdkError::Simulation(error));
+        }
+        let result = raw
+            .results
+            .first()
+            .ok_or_else(|| SdkError::InvalidResponse("simulation returned no result".into()))?;
+        let auth = result
+            .auth
+            .iter()
+            .map(|entry| {
+                Ok(SorobanAuthorizationEntry::from_xdr(
+                    STANDARD.decode(entry)?,
+                    Limits::none(),
+                )?)
+            })
+            .collect::<Result<_, SdkError>>()?;
+        let events = raw
+            .events
+            .iter()
+            .map(|event| {
+                Ok(DiagnosticEvent::from_xdr(
+                    STANDARD.decode(event)?,
+                    Limits::none(),
+                )?)
+            })
+            .collect::<Result<_, SdkError>>()?;
+
+        Ok(Simulation {
+            result: ScVal::from_xdr(STANDARD.decode(&result.xdr)?, Limits::none())?,
+            auth,
+            transaction_data: SorobanTransactionData::from_xdr(
+                STANDARD.decode(&raw.transaction_data)?,
+                Limits::none(),
+            )?,
+            min_resource_fee: raw
+                .min_resource_fee
+                .parse()
+                .map_err(|_| SdkError::InvalidResponse("bad minResourceFee".into()))?,
+            events,
+            latest_ledger: raw.latest_ledger,
+        })
+    }
+
+    /// Submit a signed envelope and return its hash once the node accepted it
+    pub async fn send(&self, envelope: &TransactionEnvelope) -> Result<String, SdkError> {
+        let sent = self.send_status(envelope).await?;
+        match sent.status {
+            SendStatus::Pending | SendStatus::Duplicate => Ok(sent.hash),
+            SendStatus::TryAgainLater => Err(SdkError::TransactionFailed {
+                hash: sent.hash,
+                status: "TRY_AGAIN_LATER".into(),
+            }),
+            SendStatus::Error(_) => Err(SdkError::TransactionFailed {
+                hash: sent.hash,
+                status: "ERROR".into(),
+            }),
+        }
+    }
+
+    /// Submit a signed envelope and return what the node made of it, for
+    /// callers that retry on their own
+    pub async fn send_status(&self, envelope: &TransactionEnvelope) -> Result<Sent, SdkError> {
+        let params = json!({ "transaction": STANDARD.encode(envelope.to_xdr(Limits::none())?) });
+        let sent: RawSend =
+            serde_json::from_value(self.transport.call("sendTransaction", params).await?)?;
+        let status = match sent.status.as_str() {
+            "PENDING" => SendStatus::Pending,
+            "DUPLICATE" => SendStatus::Duplicate,
+            "TRY_AGAIN_LATER" => SendStatus::TryAgainLater,
+            "ERROR" => SendStatus::Error(match sent.error_result_xdr {
+                Some(result) => Some(TransactionResult::from_xdr(
+                    STANDARD.decode(result)?,
+                    Limits::none(),
+                )?),
+                None => None,
+            }),
+            other => {
+                return Err(SdkError::InvalidResponse(format!(
+                    "unknown send status {other}"
+                )))
+            }
+        };
+        Ok(Sent {
+            hash: sent.hash,
+            status,
+        })
+    }
+
+    /// Ask once where transaction `hash` is
+    pub async fn status(&self, hash: &str) -> Result<TxStatus, SdkError> {
+        let result = self
+            .transport
+            .call("getTransaction", json!({ "hash": hash }))
+            .await?;
+        Ok(match result.get("status").and_then(Value::as_str) {
+            Some("SUCCESS") => TxStatus::Success,
+            Some("NOT_FOUND") => TxStatus::NotFound,
+            status => TxStatus::Failed(status.unwrap_or("unknown").to_string()),
+        })
+    }
+
+    /// Poll until transaction `hash` is in a ledger, failing unless it succeeded
+    pub async fn wait(&self, hash: &str) -> Result<(), SdkError> {
+        for attempt in 0..POLL_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            match self.status(hash).await? {
+                TxStatus::Success => return Ok(()),
+                TxStatus::NotFound => continue,
+                TxStatus::Failed(status) => {
+                    return Err(SdkError::TransactionFailed {
+                        hash: hash.to_string(),
+                        status,
+                    })
+                }
+            }
+        }
+        Err(SdkError::Timeout(hash.to_string()))
+    }
+}
@@ -0,0 +1,143 @@
+// ---------------------------------------------------------------------------
+// Session risk assessment
+//
+// Simulates the calls a dApp session would make and reduces each simulation
+// to the plain `accesly_client::session_risk` records: the auth tree from the
+// recorded auth entries, and asset movements from the token events
+// (`transfer`, `mint`, `burn`, `clawback`, as SEP-41 and the Stellar Asset
+// Contract emit them). Simulation runs in the node's sandbox, so nothing is
+// signed or submitted.
+// ---------------------------------------------------------------------------
+
+use accesly_client::session_risk::{
+    AssetMovement, AuthNode, AuthRequest, Invocation, ProbeCall, RiskSummary, SimulatedCall,
+};
+use soroban_sdk::xdr::{
+    ContractEventBody, ContractEventType, DiagnosticEvent, ScAddress, ScVal,
+    SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+    SorobanCredentials,
+};
+
+use crate::rpc::Transport;
+use crate::{Accesly, SdkError};
+
+/// A representative call, with sample arguments
+#[derive(Clone, Debug)]
+pub struct Probe {
+    /// Contract address (C...)
+    pub contract: String,
+    pub function: String,
+    pub args: Vec<ScVal>,
+}
+
+impl<T: Transport> Accesly<T> {
+    /// Simulate `probes` and summarize what they'd have `wallet` authorize
+    /// and move. A probe that fails in simulation is reported in the
+    /// summary, not as an error.
+    pub async fn assess_session(
+        &self,
+        wallet: &str,
+        probes: &[Probe],
+    ) -> Result<RiskSummary, SdkError> {
+        let mut calls = Vec::with_capacity(probes.len());
+        for probe in probes {
+            let call = ProbeCall {
+                contract: probe.contract.clone(),
+                function: probe.function.clone(),
+            };
+            let simulated = match self
+                .simulate(&probe.contract, &probe.function, probe.args.clone())
+                .await
+            {
+                Ok(simulation) => SimulatedCall {
+                    call,
+                    auth: simulation.auth.iter().map(auth_request).collect(),
+                    movements: simulation.events.iter().filter_map(movement).collect(),
+                    error: None,
+                },
+                Err(SdkError::Simulation(error)) => SimulatedCall {
+                    call,
+                    auth: Vec::new(),
+                    movements: Vec::new(),
+                    error: Some(error),
+                },
+                Err(err) => return Err(err),
+            };
+            calls.push(simulated);
+        }
+        Ok(RiskSummary::new(wallet, calls))
+    }
+}
+
+pub fn auth_request(entry: &SorobanAuthorizationEntry) -> AuthRequest {
+    let address = match &entry.credentials {
+        SorobanCredentials::Address(credentials) => Some(credentials.address.to_string()),
+        SorobanCredentials::SourceAccount => None,
+    };
+    AuthRequest {
+        address,
+        root: auth_node(&entry.root_invocation),
+    }
+}
+
+fn auth_node(invocation: &SorobanAuthorizedInvocation) -> AuthNode {
+    let node = match &invocation.function {
+        SorobanAuthorizedFunction::ContractFn(call) => Invocation::Call {
+            contract: call.contract_address.to_string(),
+            function: call.function_name.to_utf8_string_lossy(),
+        },
+        _ => Invocation::Deploy,
+    };
+    AuthNode {
+        invocation: node,
+        sub_invocations: invocation.sub_invocations.iter().map(auth_node).collect(),
+    }
+}
+
+/// The movement a token event records, if it is one. Events of calls that
+/// failed and were rolled back are skipped.
+pub fn movement(event: &DiagnosticEvent) -> Option<AssetMovement> {
+    if !event.in_successful_contract_call || event.event.type_ != ContractEventType::Contract {
+        return None;
+    }
+    let asset = ScAddress::Contract(event.event.contract_id.clone()?).to_string();
+    let ContractEventBody::V0(body) = &event.event.body;
+
+    let topics = body.topics.as_slice();
+    let ScVal::Symbol(name) = topics.first()? else {
+        return None;
+    };
+    let address = |topic: Option<&ScVal>| match topic {
+        Some(ScVal::Address(address)) => Some(address.to_string()),
+        _ => None,
+    };
+    // The Stellar Asset Contract puts its admin ahead of the account in
+    // `mint` and `clawback`, so take the last address there
+    let last_address = || topics.iter().rev().find_map(|topic| address(Some(topic)));
+
+    let (from, to) = match name.to_utf8_string_lossy().as_str() {
+        "transfer" => (Some(address(topics.get(1))?), Some(address(topics.get(2))?)),
+        "mint" => (None, Some(last_address()?)),
+        "burn" => (Some(address(topics.get(1))?), None),
+        "clawback" => (Some(last_address()?), None),
+        _ => return None,
+    };
+    Some(AssetMovement {
+        asset,
+        from,
+        to,
+        amount: amount(&body.data)?,
+    })
+}
+
+/// An `i128` amount, bare or as the `amount` of a map
+fn amount(data: &ScVal) -> Option<i128> {
+    match data {
+        ScVal::I128(parts) => Some(((parts.hi as i128) << 64) | parts.lo as i128),
+        ScVal::Map(Some(map)) => map.iter().find_map(|entry| match &entry.key {
+            ScVal::Symbol(key) if key.to_utf8_string_lossy() == "amount" => amount(&entry.val),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,607 @@
+#![cfg(test)]
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use accesly_bindings::wallet;
+use accountAbstraction::{Invocation, WalletContract};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use serde_json::{json, Value};
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    testutils::Address as _,
+    token,
+    xdr::{
+        AccountEntry, AccountEntryExt, AccountId, ContractEvent, ContractEventBody,
+        ContractEventType, ContractEventV0, DiagnosticEvent, ExtensionPoint, Int128Parts,
+        InvokeContractArgs, LedgerEntryData, Limits, OperationBody, PublicKey, ReadXdr, ScAddress,
+        ScVal, SequenceNumber, SorobanAddressCredentials, SorobanAuthorizationEntry,
+        SorobanAuthorizedFunction, SorobanAuthorizedInvocation, SorobanCredentials,
+        SorobanTransactionData, Thresholds, ToXdr, TransactionEnvelope, Uint256, WriteXdr,
+    },
+    Address, BytesN, Env, IntoVal, Symbol,
+};
+
+use crate::batch::{
+    batch_message, batch_payload, best_effort_payload, meta_tx_message, BatchCall, MetaTx,
+    NestedCall,
+};
+use crate::messages::{
+    auth_message, authorization_payload, network_id, sign_auth_entry, update_owner_message,
+};
+use crate::rpc::Transport;
+use crate::session_risk::Probe;
+use crate::tx::transaction_hash;
+use crate::{Accesly, SdkError};
+use accesly_client::session_risk::{RiskFlag, RiskLevel};
+
+const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn setup_wallet<'a>(env: &'a Env, owner: &SigningKey) -> wallet::Client<'a> {
+    let wallet = wallet::Client::new(env, &env.register(WalletContract, ()));
+    wallet
+        .init(
+            &BytesN::from_array(env, &owner.verifying_key().to_bytes()),
+            &BytesN::from_array(env, &[2u8; 32]),
+        )
+        .unwrap();
+    wallet
+}
+
+/// Unsigned auth entry for `wallet` covering `token.transfer(wallet, to, amount)`
+fn transfer_entry(
+    wallet: &ScAddress,
+    token: &ScAddress,
+    to: &ScAddress,
+    amount: i128,
+) -> SorobanAuthorizationEntry {
+    let args = [
+        ScVal::Address(wallet.clone()),
+        ScVal::Address(to.clone()),
+        ScVal::I128(soroban_sdk::xdr::Int128Parts {
+            hi: (amount >> 64) as i64,
+            lo: amount as u64,
+        }),
+    ];
+    SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: wallet.clone(),
+            nonce: 7,
+            signature_expiration_ledger: 0,
+            signature: ScVal::Void,
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: token.clone(),
+                function_name: "transfer".try_into().unwrap(),
+                args: args.to_vec().try_into().unwrap(),
+            }),
+            sub_invocations: Default::default(),
+        },
+    }
+}
+
+#[test]
+fn test_owner_messages_verify_on_the_wallet() {
+    let env = Env::default();
+    let owner = key(1);
+    let wallet = setup_wallet(&env, &owner);
+    let new_owner = key(3).verifying_key().to_bytes();
+
+    let nonce = wallet.get_nonce().unwrap();
+    let signature = owner.sign(&update_owner_message(&new_owner, nonce));
+    wallet
+        .update_owner(
+            &BytesN::from_array(&env, &new_owner),
+            &BytesN::from_array(&env, &signature.to_bytes()),
+        )
+        .unwrap();
+    assert_eq!(wallet.get_owner(), Ok(BytesN::from_array(&env, &new_owner)));
+
+    // The nonce moved on, so the same signature can't be replayed
+    assert!(wallet
+        .update_owner(
+            &BytesN::from_array(&env, &[4u8; 32]),
+            &BytesN::from_array(&env, &signature.to_bytes()),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_signed_entry_authorizes_a_transfer() {
+    let env = Env::default();
+    let owner = key(1);
+    let wallet = setup_wallet(&env, &owner);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token)
+        .mock_all_auths()
+        .mint(&wallet.0.address, &1_000);
+    let to = Address::generate(&env);
+
+    let entry = transfer_entry(
+        &ScAddress::from(&wallet.0.address),
+        &ScAddress::from(&token),
+        &ScAddress::from(&to),
+        250,
+    );
+    let signed = sign_auth_entry(
+        &entry,
+        &env.ledger().network_id().to_array(),
+        &owner,
+        wallet.get_nonce().unwrap(),
+        env.ledger().sequence() + 100,
+    )
+    .unwrap();
+
+    env.set_auths(&[signed]);
+    token::Client::new(&env, &token).transfer(&wallet.0.address, &to, &250);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 250);
+    assert_eq!(wallet.get_nonce(), Ok(1));
+}
+
+#[test]
+fn test_auth_message_layout() {
+    let payload = [9u8; 32];
+    assert_eq!(auth_message(&payload, 0, 5, None).len(), 40);
+    assert_eq!(auth_message(&payload, 2, 5, None).len(), 44);
+
+    let expiring = auth_message(&payload, 0, 5, Some(77));
+    assert_eq!(expiring.len(), 48);
+    assert_eq!(&expiring[32..36], &0u32.to_be_bytes());
+    assert_eq!(&expiring[44..], &77u32.to_be_bytes());
+}
+
+#[test]
+fn test_batch_payload_matches_the_contract() {
+    let env = Env::default();
+    let token = Address::generate(&env);
+    let router = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    let nested = InvokerContractAuthEntry::Contract(SubContractInvocation {
+        context: ContractContext {
+            contract: token.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (router.clone(), 5i128).into_val(&env),
+        },
+        sub_invocations: soroban_sdk::Vec::new(&env),
+    });
+    let ops = soroban_sdk::vec![
+        &env,
+        Invocation {
+            contract: router.clone(),
+            fn_name: Symbol::new(&env, "swap"),
+            args: (7u32, true).into_val(&env),
+            auth: soroban_sdk::vec![&env, nested],
+        },
+        Invocation {
+            contract: token.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (to.clone(), -3i64).into_val(&env),
+            auth: soroban_sdk::Vec::new(&env),
+        },
+    ];
+
+    let calls = vec![
+        BatchCall {
+            contract: ScAddress::from(&router),
+            function: "swap".to_string(),
+            args: vec![ScVal::U32(7), ScVal::Bool(true)],
+            auth: vec![NestedCall {
+                contract: ScAddress::from(&token),
+                function: "transfer".to_string(),
+                args: vec![
+                    ScVal::Address(ScAddress::from(&router)),
+                    ScVal::I128(Int128Parts { hi: 0, lo: 5 }),
+                ],
+                sub_calls: Vec::new(),
+            }],
+        },
+        BatchCall {
+            contract: ScAddress::from(&token),
+            function: "transfer".to_string(),
+            args: vec![ScVal::Address(ScAddress::from(&to)), ScVal::I64(-3)],
+            auth: Vec::new(),
+        },
+    ];
+
+    assert_eq!(
+        batch_payload(&calls).unwrap(),
+        ops.clone().to_xdr(&env).iter().collect::<Vec<u8>>()
+    );
+    assert_eq!(
+        best_effort_payload(&calls, 2).unwrap(),
+        (ops, 2u32).to_xdr(&env).iter().collect::<Vec<u8>>()
+    );
+}
+
+#[test]
+fn test_batch_message_verifies_on_the_wallet() {
+    let env = Env::default();
+    let owner = key(1);
+    let wallet = setup_wallet(&env, &owner);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token)
+        .mock_all_auths()
+        .mint(&wallet.0.address, &1_000);
+    let to = Address::generate(&env);
+
+    let calls = vec![BatchCall {
+        contract: ScAddress::from(&token),
+        function: "transfer".to_string(),
+        args: vec![
+            ScVal::Address(ScAddress::from(&wallet.0.address)),
+            ScVal::Address(ScAddress::from(&to)),
+            ScVal::I128(Int128Parts { hi: 0, lo: 400 }),
+        ],
+        auth: Vec::new(),
+    }];
+    let ops = soroban_sdk::vec![
+        &env,
+        Invocation {
+            contract: token.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (wallet.0.address.clone(), to.clone(), 400i128).into_val(&env),
+            auth: soroban_sdk::Vec::new(&env),
+        },
+    ];
+
+    let message = batch_message(&calls, wallet.get_nonce().unwrap()).unwrap();
+    let signature = BytesN::from_array(&env, &owner.sign(&message).to_bytes());
+    wallet.execute_batch(&ops, &signature).unwrap();
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 400);
+}
+
+#[test]
+fn test_meta_tx_message_verifies_on_the_wallet() {
+    let env = Env::default();
+    let owner = key(1);
+    let wallet = setup_wallet(&env, &owner);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token)
+        .mock_all_auths()
+        .mint(&wallet.0.address, &1_000);
+    let to = Address::generate(&env);
+    let expiry = env.ledger().timestamp() + 600;
+
+    let envelope = MetaTx {
+        call: BatchCall {
+            contract: ScAddress::from(&token),
+            function: "transfer".to_string(),
+            args: vec![
+                ScVal::Address(ScAddress::from(&wallet.0.address)),
+                ScVal::Address(ScAddress::from(&to)),
+                ScVal::I128(Int128Parts { hi: 0, lo: 250 }),
+            ],
+            auth: Vec::new(),
+        },
+        nonce: 42,
+        max_fee: 0,
+        expiry,
+    };
+    let on_chain = accountAbstraction::MetaTx {
+        call: Invocation {
+            contract: token.clone(),
+            fn_name: Symbol::new(&env, "transfer"),
+            args: (wallet.0.address.clone(), to.clone(), 250i128).into_val(&env),
+            auth: soroban_sdk::Vec::new(&env),
+        },
+        nonce: 42,
+        max_fee: 0,
+        expiry,
+    };
+
+    let network = env.ledger().network_id().to_array();
+    let message = meta_tx_message(&network, &ScAddress::from(&wallet.0.address), &envelope).unwrap();
+    let signature = BytesN::from_array(&env, &owner.sign(&message).to_bytes());
+    wallet.execute_meta_tx(&on_chain, &signature, &0).unwrap();
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 250);
+    assert!(wallet.is_meta_tx_used(&42).unwrap());
+}
+
+/// Answers each request with the next scripted response, checking the method
+struct FakeRpc {
+    script: Mutex<VecDeque<(&'static str, Value)>>,
+    requests: Mutex<Vec<(String, Value)>>,
+}
+
+impl FakeRpc {
+    fn new(script: Vec<(&'static str, Value)>) -> Self {
+        Self {
+            script: Mutex::new(script.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Transport for FakeRpc {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, SdkError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), params));
+        let (expected, response) = self
+            .script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("unexpected request");
+        assert_eq!(method, expected);
+        Ok(response)
+    }
+}
+
+fn xdr64(value: &impl WriteXdr) -> String {
+    STANDARD.encode(value.to_xdr(Limits::none()).unwrap())
+}
+
+fn simulation(
+    result: ScVal,
+    auth: &[SorobanAuthorizationEntry],
+    events: &[DiagnosticEvent],
+    fee: u64,
+) -> Value {
+    // Empty footprint and zero resources
+    let data = SorobanTransactionData::from_xdr([0u8; 32], Limits::none()).unwrap();
+    json!({
+        "transactionData": xdr64(&data),
+        "minResourceFee": fee.to_string(),
+        "results": [{ "xdr": xdr64(&result), "auth": auth.iter().map(xdr64).collect::<Vec<_>>() }],
+        "events": events.iter().map(xdr64).collect::<Vec<_>>(),
+        "latestLedger": 1_000,
+    })
+}
+
+/// `getLedgerEntries` result for `source`'s account at `sequence`
+fn account(source: &SigningKey, sequence: i64) -> Value {
+    let entry = LedgerEntryData::Account(AccountEntry {
+        account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+            source.verifying_key().to_bytes(),
+        ))),
+        balance: 100_000_000,
+        seq_num: SequenceNumber(sequence),
+        num_sub_entries: 0,
+        inflation_dest: None,
+        flags: 0,
+        home_domain: Default::default(),
+        thresholds: Thresholds([1, 0, 0, 0]),
+        signers: Default::default(),
+        ext: AccountEntryExt::V0,
+    });
+    json!({ "entries": [{ "xdr": xdr64(&entry) }] })
+}
+
+#[tokio::test]
+async fn test_send_payment_signs_and_submits() {
+    let env = Env::default();
+    let relayer = key(5);
+    let owner = key(1);
+    let wallet = ScAddress::from(&Address::generate(&env));
+    let token = ScAddress::from(&Address::generate(&env));
+    let to = ScAddress::from(&Address::generate(&env));
+    let entry = transfer_entry(&wallet, &token, &to, 10_000_000);
+
+    let rpc = FakeRpc::new(vec![
+        ("getLedgerEntries", account(&relayer, 41)),
+        (
+            "simulateTransaction",
+            simulation(ScVal::Void, &[entry], &[], 5_000),
+        ),
+        // get_nonce
+        (
+            "simulateTransaction",
+            simulation(ScVal::U64(3), &[], &[], 100),
+        ),
+        ("getLatestLedger", json!({ "sequence": 1_000 })),
+        (
+            "simulateTransaction",
+            simulation(ScVal::Void, &[], &[], 6_000),
+        ),
+        (
+            "sendTransaction",
+            json!({ "status": "PENDING", "hash": "ab12" }),
+        ),
+        ("getTransaction", json!({ "status": "SUCCESS" })),
+    ]);
+    let accesly = Accesly::with_transport(rpc, PASSPHRASE, relayer.clone());
+
+    let hash = accesly
+        .send_payment(
+            &wallet.to_string(),
+            &owner,
+            &token.to_string(),
+            &to.to_string(),
+            10_000_000,
+        )
+        .await
+        .unwrap();
+    assert_eq!(hash, "ab12");
+
+    let requests = accesly.rpc().transport().requests.lock().unwrap();
+    let (_, params) = requests
+        .iter()
+        .find(|(method, _)| method == "sendTransaction")
+        .unwrap();
+    let envelope = TransactionEnvelope::from_xdr(
+        STANDARD
+            .decode(params["transaction"].as_str().unwrap())
+            .unwrap(),
+        Limits::none(),
+    )
+    .unwrap();
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        panic!("expected a v1 envelope");
+    };
+    assert_eq!(envelope.tx.seq_num, SequenceNumber(42));
+    assert_eq!(envelope.tx.fee, 6_100);
+
+    // The relayer signed the transaction
+    let network = network_id(PASSPHRASE);
+    let tx_signature = Signature::from_slice(&envelope.signatures[0].signature.0).unwrap();
+    relayer
+        .verifying_key()
+        .verify(
+            &transaction_hash(&envelope.tx, &network).unwrap(),
+            &tx_signature,
+        )
+        .unwrap();
+
+    // ...and the owner signed the wallet's entry as `__check_auth` expects
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("expected a contract call");
+    };
+    let SorobanCredentials::Address(credentials) = &op.auth[0].credentials else {
+        panic!("expected address credentials");
+    };
+    assert_eq!(credentials.signature_expiration_ledger, 1_060);
+    let ScVal::Vec(Some(parts)) = &credentials.signature else {
+        panic!("expected AuthSignature::Ed25519");
+    };
+    let ScVal::Bytes(signature) = &parts[1] else {
+        panic!("expected signature bytes");
+    };
+    let payload = authorization_payload(
+        &network,
+        credentials.nonce,
+        1_060,
+        &op.auth[0].root_invocation,
+    )
+    .unwrap();
+    owner
+        .verifying_key()
+        .verify(
+            &auth_message(&payload, 0, 3, None),
+            &Signature::from_slice(signature.as_slice()).unwrap(),
+        )
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_auth_for_unknown_address_is_refused() {
+    let env = Env::default();
+    let stranger = ScAddress::from(&Address::generate(&env));
+    let entry = transfer_entry(
+        &stranger,
+        &ScAddress::from(&Address::generate(&env)),
+        &ScAddress::from(&Address::generate(&env)),
+        1,
+    );
+    let rpc = FakeRpc::new(vec![
+        ("getLedgerEntries", account(&key(5), 1)),
+        (
+            "simulateTransaction",
+            simulation(ScVal::Void, &[entry], &[], 100),
+        ),
+    ]);
+    let accesly = Accesly::with_transport(rpc, PASSPHRASE, key(5));
+
+    let wallet = ScAddress::from(&Address::generate(&env)).to_string();
+    let result = accesly
+        .send_payment(&wallet, &key(1), &wallet, &wallet, 1)
+        .await;
+    assert!(
+        matches!(result, Err(SdkError::MissingSigner(address)) if address == stranger.to_string())
+    );
+}
+
+/// A successful-call event from `token`
+fn token_event(token: &ScAddress, topics: Vec<ScVal>, data: ScVal) -> DiagnosticEvent {
+    let ScAddress::Contract(contract_id) = token.clone() else {
+        panic!("expected a contract address");
+    };
+    DiagnosticEvent {
+        in_successful_contract_call: true,
+        event: ContractEvent {
+            ext: ExtensionPoint::V0,
+            contract_id: Some(contract_id),
+            type_: ContractEventType::Contract,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: topics.try_into().unwrap(),
+                data,
+            }),
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_assess_session_summarizes_auth_and_movements() {
+    let env = Env::default();
+    let wallet = ScAddress::from(&Address::generate(&env));
+    let router = ScAddress::from(&Address::generate(&env));
+    let token = ScAddress::from(&Address::generate(&env));
+
+    // router.swap pulls the wallet's tokens with a nested transfer
+    let mut entry = transfer_entry(&wallet, &token, &router, 500);
+    entry.root_invocation = SorobanAuthorizedInvocation {
+        function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+            contract_address: router.clone(),
+            function_name: "swap".try_into().unwrap(),
+            args: Default::default(),
+        }),
+        sub_invocations: [entry.root_invocation].to_vec().try_into().unwrap(),
+    };
+    let transfer = token_event(
+        &token,
+        vec![
+            ScVal::Symbol("transfer".try_into().unwrap()),
+            ScVal::Address(wallet.clone()),
+            ScVal::Address(router.clone()),
+        ],
+        ScVal::I128(soroban_sdk::xdr::Int128Parts { hi: 0, lo: 500 }),
+    );
+    let mut rolled_back = transfer.clone();
+    rolled_back.in_successful_contract_call = false;
+
+    let rpc = FakeRpc::new(vec![
+        (
+            "simulateTransaction",
+            simulation(ScVal::Void, &[entry], &[transfer, rolled_back], 100),
+        ),
+        (
+            "simulateTransaction",
+            json!({ "error": "HostError: Error(Contract, #3)", "latestLedger": 1_000 }),
+        ),
+    ]);
+    let accesly = Accesly::with_transport(rpc, PASSPHRASE, key(5));
+    let probe = |function: &str| Probe {
+        contract: router.to_string(),
+        function: function.into(),
+        args: Vec::new(),
+    };
+
+    let summary = accesly
+        .assess_session(&wallet.to_string(), &[probe("swap"), probe("claim")])
+        .await
+        .unwrap();
+    assert_eq!(summary.calls[0].movements.len(), 1);
+    assert_eq!(summary.calls[0].auth[0].address, Some(wallet.to_string()));
+    assert_eq!(
+        summary.flags,
+        vec![
+            RiskFlag::IndirectWalletAuth {
+                contract: token.to_string(),
+                function: "transfer".into(),
+            },
+            RiskFlag::WalletOutflow {
+                asset: token.to_string(),
+                amount: 500,
+            },
+            RiskFlag::SimulationFailed {
+                contract: router.to_string(),
+                function: "claim".into(),
+            },
+        ]
+    );
+    assert_eq!(summary.level(), RiskLevel::High);
+}
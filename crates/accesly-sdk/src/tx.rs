@@ -0,0 +1,154 @@
+// ---------------------------------------------------------------------------
+// Transaction assembly
+//
+// One `InvokeHostFunction` operation per transaction, sourced and signed by
+// the account paying fees. The envelope signature covers sha256 of the
+// `TransactionSignaturePayload`, i.e. the network id and the transaction.
+// A fee bump wraps a signed envelope so another account pays for it.
+// ---------------------------------------------------------------------------
+
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+use soroban_sdk::xdr::{
+    DecoratedSignature, FeeBumpTransaction, FeeBumpTransactionEnvelope, FeeBumpTransactionExt,
+    FeeBumpTransactionInnerTx, Hash, HostFunction, InvokeContractArgs, InvokeHostFunctionOp,
+    Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions, ScAddress, ScVal,
+    SequenceNumber, Signature, SignatureHint, SorobanAuthorizationEntry, Transaction,
+    TransactionEnvelope, TransactionExt, TransactionSignaturePayload,
+    TransactionSignaturePayloadTaggedTransaction, TransactionV1Envelope, Uint256, WriteXdr,
+};
+
+use crate::SdkError;
+
+/// Inclusion fee bid on top of the simulated resource fee, in stroops
+pub const BASE_FEE: u32 = 100;
+
+/// Parse a G... or C... address
+pub fn address(s: &str) -> Result<ScAddress, SdkError> {
+    s.parse()
+        .map_err(|_| SdkError::InvalidAddress(s.to_string()))
+}
+
+/// Unsigned transaction from `source` calling `contract.function(args)`
+pub fn invoke(
+    source: &[u8; 32],
+    sequence: i64,
+    contract: &ScAddress,
+    function: &str,
+    args: Vec<ScVal>,
+) -> Result<Transaction, SdkError> {
+    let op = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: contract.clone(),
+                function_name: function
+                    .try_into()
+                    .map_err(|_| SdkError::InvalidResponse(format!("bad function {function}")))?,
+                args: args.try_into()?,
+            }),
+            auth: Default::default(),
+        }),
+    };
+
+    Ok(Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(*source)),
+        fee: BASE_FEE,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: [op].to_vec().try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+/// Replace the auth entries of the transaction's operation
+pub fn set_auth(
+    tx: &mut Transaction,
+    auth: Vec<SorobanAuthorizationEntry>,
+) -> Result<(), SdkError> {
+    let mut operations = tx.operations.to_vec();
+    if let Some(Operation {
+        body: OperationBody::InvokeHostFunction(op),
+        ..
+    }) = operations.first_mut()
+    {
+        op.auth = auth.try_into()?;
+    }
+    tx.operations = operations.try_into()?;
+    Ok(())
+}
+
+/// Envelope without signatures, as simulation takes it
+pub fn unsigned(tx: &Transaction) -> TransactionEnvelope {
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: tx.clone(),
+        signatures: Default::default(),
+    })
+}
+
+/// Hash identifying `tx` on the network, and what its source signs
+pub fn transaction_hash(tx: &Transaction, network_id: &[u8; 32]) -> Result<[u8; 32], SdkError> {
+    let payload = TransactionSignaturePayload {
+        network_id: Hash(*network_id),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    Ok(Sha256::digest(payload.to_xdr(Limits::none())?).into())
+}
+
+/// Hash identifying the fee bump `tx`, and what its fee source signs
+pub fn fee_bump_hash(tx: &FeeBumpTransaction, network_id: &[u8; 32]) -> Result<[u8; 32], SdkError> {
+    let payload = TransactionSignaturePayload {
+        network_id: Hash(*network_id),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::TxFeeBump(tx.clone()),
+    };
+    Ok(Sha256::digest(payload.to_xdr(Limits::none())?).into())
+}
+
+/// Envelope signed by `key`, which must be the transaction's source
+pub fn sign(
+    tx: Transaction,
+    network_id: &[u8; 32],
+    key: &SigningKey,
+) -> Result<TransactionEnvelope, SdkError> {
+    let signature = decorated(key, &transaction_hash(&tx, network_id)?)?;
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: [signature].to_vec().try_into()?,
+    }))
+}
+
+/// The signed `inner` envelope wrapped in a fee bump that `key` pays `fee`
+/// stroops for in total and signs
+pub fn fee_bump(
+    inner: TransactionEnvelope,
+    fee: i64,
+    network_id: &[u8; 32],
+    key: &SigningKey,
+) -> Result<TransactionEnvelope, SdkError> {
+    let TransactionEnvelope::Tx(inner) = inner else {
+        return Err(SdkError::InvalidResponse(
+            "only v1 transactions can be fee bumped".into(),
+        ));
+    };
+    let tx = FeeBumpTransaction {
+        fee_source: MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes())),
+        fee,
+        inner_tx: FeeBumpTransactionInnerTx::Tx(inner),
+        ext: FeeBumpTransactionExt::V0,
+    };
+    let signature = decorated(key, &fee_bump_hash(&tx, network_id)?)?;
+    Ok(TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+        tx,
+        signatures: [signature].to_vec().try_into()?,
+    }))
+}
+
+fn decorated(key: &SigningKey, hash: &[u8; 32]) -> Result<DecoratedSignature, SdkError> {
+    let public = key.verifying_key().to_bytes();
+    Ok(DecoratedSignature {
+        // Last four bytes of the signer's key
+        hint: SignatureHint(public[28..].try_into().unwrap()),
+        signature: Signature(key.sign(hash).to_bytes().to_vec().try_into()?),
+    })
+}
@@ -0,0 +1,243 @@
+// ---------------------------------------------------------------------------
+// High-level wallet calls
+//
+// `Accesly` is the network counterpart of `accesly_bindings::wallet::Client`:
+// the same calls, made against a live network. It holds the key of the
+// account that sources and pays for transactions (the relayer, or the
+// factory admin for `create_wallet`); owner keys are passed per call and only
+// used to sign.
+//
+// Every write goes simulate → sign auth → re-simulate → submit → poll. The
+// second simulation runs the signed entries through `__check_auth`, so the
+// resources attached cover the signature checks and a bad signature fails
+// before anything is paid for.
+// ---------------------------------------------------------------------------
+
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::xdr::{
+    Int128Parts, ScAddress, ScBytes, ScVal, SorobanAuthorizationEntry, SorobanCredentials,
+    TransactionExt,
+};
+
+use crate::messages::{network_id, sign_auth_entry, update_owner_message};
+use crate::rpc::{HttpTransport, Rpc, Simulation, Transport};
+use crate::tx::{address, invoke, set_auth, sign, unsigned, BASE_FEE};
+use crate::SdkError;
+
+/// Ledgers an auth entry signed by the SDK stays valid (about five minutes)
+pub const AUTH_VALIDITY_LEDGERS: u32 = 60;
+
+/// Wallet to authorize for and its owner key
+struct WalletSigner<'a> {
+    wallet: ScAddress,
+    owner: &'a SigningKey,
+}
+
+pub struct Accesly<T = HttpTransport> {
+    rpc: Rpc<T>,
+    network_id: [u8; 32],
+    source: SigningKey,
+}
+
+impl Accesly<HttpTransport> {
+    /// Client for the RPC node at `rpc_url`, submitting as `source`
+    pub fn new(rpc_url: &str, network_passphrase: &str, source: SigningKey) -> Self {
+        Self::with_transport(HttpTransport::new(rpc_url), network_passphrase, source)
+    }
+}
+
+impl<T: Transport> Accesly<T> {
+    pub fn with_transport(transport: T, network_passphrase: &str, source: SigningKey) -> Self {
+        Self {
+            rpc: Rpc::new(transport),
+            network_id: network_id(network_passphrase),
+            source,
+        }
+    }
+
+    pub fn rpc(&self) -> &Rpc<T> {
+        &self.rpc
+    }
+
+    /// The wallet's current nonce, which the next owner signature commits to
+    pub async fn get_nonce(&self, wallet: &str) -> Result<u64, SdkError> {
+        match self.simulate(wallet, "get_nonce", Vec::new()).await?.result {
+            ScVal::U64(nonce) => Ok(nonce),
+            other => Err(SdkError::InvalidResponse(format!(
+                "expected a nonce, got {other:?}"
+            ))),
+        }
+    }
+
+    /// Deploy a wallet for `owner` through `factory` and return its address.
+    /// `key` is the lookup key, sha256(pepper || email_hash), the factory
+    /// derives the address from and indexes the wallet under. The source
+    /// account must be the factory admin.
+    pub async fn create_wallet(
+        &self,
+        factory: &str,
+        owner: &[u8; 32],
+        email_hash: &[u8; 32],
+        key: &[u8; 32],
+    ) -> Result<String, SdkError> {
+        let args = vec![bytes(owner), bytes(email_hash), bytes(key)];
+        let (_, result) = self.submit(factory, "deploy_wallet", args, None).await?;
+        match result {
+            ScVal::Address(wallet) => Ok(wallet.to_string()),
+            other => Err(SdkError::InvalidResponse(format!(
+                "expected a wallet address, got {other:?}"
+            ))),
+        }
+    }
+
+    /// Move the wallet to `new_owner` with the current owner's signature,
+    /// returning the transaction hash
+    pub async fn rotate_owner(
+        &self,
+        wallet: &str,
+        owner: &SigningKey,
+        new_owner: &[u8; 32],
+    ) -> Result<String, SdkError> {
+        let nonce = self.get_nonce(wallet).await?;
+        let signature = owner.sign(&update_owner_message(new_owner, nonce));
+        let args = vec![bytes(new_owner), bytes(&signature.to_bytes())];
+        let (hash, _) = self.submit(wallet, "update_owner", args, None).await?;
+        Ok(hash)
+    }
+
+    /// Transfer `amount` of `token` from the wallet to `to`, authorized by
+    /// the owner through `__check_auth`. Returns the transaction hash.
+    pub async fn send_payment(
+        &self,
+        wallet: &str,
+        owner: &SigningKey,
+        token: &str,
+        to: &str,
+        amount: i128,
+    ) -> Result<String, SdkError> {
+        let args = vec![
+            ScVal::Address(address(wallet)?),
+            ScVal::Address(address(to)?),
+            ScVal::I128(Int128Parts {
+                hi: (amount >> 64) as i64,
+                lo: amount as u64,
+            }),
+        ];
+        let signer = WalletSigner {
+            wallet: address(wallet)?,
+            owner,
+        };
+        let (hash, _) = self.submit(token, "transfer", args, Some(signer)).await?;
+        Ok(hash)
+    }
+
+    /// Sign an auth entry for `wallet` with the owner key, against the
+    /// wallet's current nonce and valid for `AUTH_VALIDITY_LEDGERS`
+    pub async fn check_auth_sign(
+        &self,
+        wallet: &str,
+        owner: &SigningKey,
+        entry: &SorobanAuthorizationEntry,
+    ) -> Result<SorobanAuthorizationEntry, SdkError> {
+        let nonce = self.get_nonce(wallet).await?;
+        let expiration = self.rpc.latest_ledger().await? + AUTH_VALIDITY_LEDGERS;
+        sign_auth_entry(entry, &self.network_id, owner, nonce, expiration)
+    }
+
+    fn source_key(&self) -> [u8; 32] {
+        self.source.verifying_key().to_bytes()
+    }
+
+    /// Simulate `contract.function(args)` without submitting
+    pub(crate) async fn simulate(
+        &self,
+        contract: &str,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<Simulation, SdkError> {
+        // Simulation ignores the sequence number
+        let tx = invoke(&self.source_key(), 0, &address(contract)?, function, args)?;
+        self.rpc.simulate(&unsigned(&tx)).await
+    }
+
+    /// Call `contract.function(args)` on chain, returning the transaction
+    /// hash and the simulated return value
+    async fn submit(
+        &self,
+        contract: &str,
+        function: &str,
+        args: Vec<ScVal>,
+        signer: Option<WalletSigner<'_>>,
+    ) -> Result<(String, ScVal), SdkError> {
+        let source = self.source_key();
+        let sequence = self.rpc.sequence(&source).await? + 1;
+        let mut tx = invoke(&source, sequence, &address(contract)?, function, args)?;
+
+        let mut simulation = self.rpc.simulate(&unsigned(&tx)).await?;
+        let (auth, signed) = self.authorize(simulation.auth.clone(), signer).await?;
+        set_auth(&mut tx, auth)?;
+        if signed {
+            simulation = self.rpc.simulate(&unsigned(&tx)).await?;
+        }
+
+        tx.fee =
+            BASE_FEE.saturating_add(simulation.min_resource_fee.try_into().unwrap_or(u32::MAX));
+        tx.ext = TransactionExt::V1(simulation.transaction_data);
+        let hash = self
+            .rpc
+            .send(&sign(tx, &self.network_id, &self.source)?)
+            .await?;
+        self.rpc.wait(&hash).await?;
+        Ok((hash, simulation.result))
+    }
+
+    /// Sign the entries simulation asked of the wallet, one nonce each as
+    /// `__check_auth` consumes them in order. Source-account entries are
+    /// covered by the envelope signature and pass through.
+    async fn authorize(
+        &self,
+        entries: Vec<SorobanAuthorizationEntry>,
+        signer: Option<WalletSigner<'_>>,
+    ) -> Result<(Vec<SorobanAuthorizationEntry>, bool), SdkError> {
+        let mut out = Vec::with_capacity(entries.len());
+        let mut nonce_and_expiration = None;
+
+        for entry in entries {
+            let SorobanCredentials::Address(credentials) = &entry.credentials else {
+                out.push(entry);
+                continue;
+            };
+            let signer = match &signer {
+                Some(signer) if signer.wallet == credentials.address => signer,
+                _ => return Err(SdkError::MissingSigner(credentials.address.to_string())),
+            };
+
+            let (nonce, expiration) = match nonce_and_expiration {
+                Some(current) => current,
+                None => {
+                    let wallet = signer.wallet.to_string();
+                    let nonce = self.get_nonce(&wallet).await?;
+                    (
+                        nonce,
+                        self.rpc.latest_ledger().await? + AUTH_VALIDITY_LEDGERS,
+                    )
+                }
+            };
+            out.push(sign_auth_entry(
+                &entry,
+                &self.network_id,
+                signer.owner,
+                nonce,
+                expiration,
+            )?);
+            nonce_and_expiration = Some((nonce + 1, expiration));
+        }
+
+        let signed = nonce_and_expiration.is_some();
+        Ok((out, signed))
+    }
+}
+
+fn bytes(value: &[u8]) -> ScVal {
+    ScVal::Bytes(ScBytes(value.to_vec().try_into().unwrap()))
+}
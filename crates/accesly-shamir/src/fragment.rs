@@ -0,0 +1,143 @@
+// ---------------------------------------------------------------------------
+// Fragment encoding
+//
+// Serde form, for JSON APIs and DynamoDB items:
+//
+//     { "index": 2, "threshold": 2, "data": "<base64>" }
+//
+// and a compact binary form for encrypting as one blob (`to_bytes`):
+//
+//     version (1) || index (1) || threshold (1) || data
+// ---------------------------------------------------------------------------
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::ShamirError;
+
+/// Version byte of the binary encoding
+pub const FRAGMENT_VERSION: u8 = 1;
+
+/// Where a wallet key fragment is kept
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Slot {
+    /// On the user's device
+    F1,
+    /// `user_fragments`, KMS-encrypted
+    F2,
+    /// `email_fragments`, encrypted under the email key
+    F3,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 3] = [Slot::F1, Slot::F2, Slot::F3];
+
+    /// Index of the slot's fragment (x coordinate)
+    pub fn index(self) -> u8 {
+        self as u8 + 1
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|slot| slot.index() == index)
+    }
+}
+
+/// One share of a secret
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct Fragment {
+    index: u8,
+    threshold: u8,
+    #[serde(with = "base64_data")]
+    data: Vec<u8>,
+}
+
+impl Fragment {
+    pub(crate) fn new(index: u8, threshold: u8, data: Vec<u8>) -> Self {
+        Self {
+            index,
+            threshold,
+            data,
+        }
+    }
+
+    pub(crate) fn push(&mut self, byte: u8) {
+        self.data.push(byte);
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Fragments needed to rebuild the secret
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The wallet slot this fragment belongs in, for a 2-of-3 wallet split
+    pub fn slot(&self) -> Option<Slot> {
+        Slot::from_index(self.index)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Length of the secret the fragment is for
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(Vec::with_capacity(3 + self.data.len()));
+        out.extend_from_slice(&[FRAGMENT_VERSION, self.index, self.threshold]);
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShamirError> {
+        match bytes {
+            [FRAGMENT_VERSION, index, threshold, data @ ..]
+                if *index != 0 && *threshold >= 2 && !data.is_empty() =>
+            {
+                Ok(Self::new(*index, *threshold, data.to_vec()))
+            }
+            _ => Err(ShamirError::InvalidEncoding),
+        }
+    }
+}
+
+// Fragment bytes stay out of logs
+impl core::fmt::Debug for Fragment {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Fragment")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+mod base64_data {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+    use zeroize::Zeroizing;
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let encoded = Zeroizing::new(STANDARD.encode(data));
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = Zeroizing::new(String::deserialize(deserializer)?);
+        STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,34 @@
+// ---------------------------------------------------------------------------
+// GF(2^8) with the AES polynomial x^8 + x^4 + x^3 + x + 1
+//
+// No lookup tables and no branches on operands, so timing doesn't depend on
+// the secret bytes going through. Addition is xor.
+// ---------------------------------------------------------------------------
+
+pub(crate) fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        // All ones when the low bit of b is set
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// a^254, the inverse of any nonzero a. The exponent is fixed, so the
+/// square-and-multiply sequence is the same for every input.
+pub(crate) fn inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
@@ -0,0 +1,157 @@
+#![no_std]
+
+// ---------------------------------------------------------------------------
+// accesly-shamir
+//
+// Shamir secret sharing over GF(256) for the wallet key fragments of the
+// createWallet flow. The key is split 2-of-3:
+//
+//   F1  stays on the user's device
+//   F2  `user_fragments`, encrypted with KMS
+//   F3  `email_fragments`, encrypted under the PBKDF2 email key
+//
+// and any two rebuild it. Each byte of the secret is the constant term of its
+// own random polynomial of degree threshold - 1; fragment i holds the values
+// at x = i. Reconstruction interpolates at x = 0. Field arithmetic is
+// constant-time, and every buffer that holds key material is zeroized when
+// dropped.
+// ---------------------------------------------------------------------------
+
+extern crate alloc;
+
+mod fragment;
+mod gf256;
+
+pub use fragment::{Fragment, Slot, FRAGMENT_VERSION};
+
+use alloc::vec;
+use alloc::vec::Vec;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+/// Fragments the wallet key is split into, and how many rebuild it
+pub const WALLET_FRAGMENTS: u8 = 3;
+pub const WALLET_THRESHOLD: u8 = 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShamirError {
+    EmptySecret,
+    /// Threshold below 2 or above the number of fragments
+    InvalidThreshold,
+    /// Fewer fragments than their threshold
+    NotEnoughFragments,
+    /// A fragment at index 0, or two at the same index
+    InvalidIndex,
+    /// Fragments from different splits: threshold or length differ
+    MismatchedFragments,
+    /// Not an encoded fragment, or one of a version this build can't read
+    InvalidEncoding,
+}
+
+impl core::fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptySecret => write!(f, "secret is empty"),
+            Self::InvalidThreshold => write!(f, "threshold must be 2..=fragment count"),
+            Self::NotEnoughFragments => write!(f, "not enough fragments"),
+            Self::InvalidIndex => write!(f, "invalid or repeated fragment index"),
+            Self::MismatchedFragments => write!(f, "fragments come from different splits"),
+            Self::InvalidEncoding => write!(f, "invalid fragment encoding"),
+        }
+    }
+}
+
+impl core::error::Error for ShamirError {}
+
+/// Split `secret` into `count` fragments, any `threshold` of which rebuild it
+pub fn split<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    threshold: u8,
+    count: u8,
+    rng: &mut R,
+) -> Result<Vec<Fragment>, ShamirError> {
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    if threshold < 2 || threshold > count {
+        return Err(ShamirError::InvalidThreshold);
+    }
+
+    // Allocated at full length up front so pushes never reallocate and
+    // leave copies of fragment bytes behind
+    let mut fragments: Vec<Fragment> = (1..=count)
+        .map(|index| Fragment::new(index, threshold, Vec::with_capacity(secret.len())))
+        .collect();
+    let mut coefficients = Zeroizing::new(vec![0u8; threshold as usize]);
+    for &byte in secret {
+        coefficients[0] = byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+        for fragment in &mut fragments {
+            let y = evaluate(&coefficients, fragment.index());
+            fragment.push(y);
+        }
+    }
+    Ok(fragments)
+}
+
+/// Split a wallet key into F1, F2 and F3, 2-of-3
+pub fn split_wallet_key<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    rng: &mut R,
+) -> Result<[Fragment; 3], ShamirError> {
+    let fragments = split(secret, WALLET_THRESHOLD, WALLET_FRAGMENTS, rng)?;
+    Ok(fragments.try_into().unwrap())
+}
+
+/// Rebuild the secret from the first `threshold` fragments
+pub fn reconstruct(fragments: &[Fragment]) -> Result<Zeroizing<Vec<u8>>, ShamirError> {
+    let first = fragments.first().ok_or(ShamirError::NotEnoughFragments)?;
+    let threshold = first.threshold() as usize;
+    if fragments.len() < threshold {
+        return Err(ShamirError::NotEnoughFragments);
+    }
+
+    let used = &fragments[..threshold];
+    for (i, fragment) in used.iter().enumerate() {
+        if fragment.threshold() != first.threshold() || fragment.len() != first.len() {
+            return Err(ShamirError::MismatchedFragments);
+        }
+        if fragment.index() == 0 || used[..i].iter().any(|o| o.index() == fragment.index()) {
+            return Err(ShamirError::InvalidIndex);
+        }
+    }
+
+    // Lagrange weights at x = 0 only depend on the (public) indices
+    let weights: Vec<u8> = used
+        .iter()
+        .map(|fragment| {
+            let (mut numerator, mut denominator) = (1u8, 1u8);
+            for other in used.iter().filter(|o| o.index() != fragment.index()) {
+                numerator = gf256::mul(numerator, other.index());
+                denominator = gf256::mul(denominator, other.index() ^ fragment.index());
+            }
+            gf256::mul(numerator, gf256::inv(denominator))
+        })
+        .collect();
+
+    let mut secret = Zeroizing::new(Vec::with_capacity(first.len()));
+    for i in 0..first.len() {
+        let mut byte = 0u8;
+        for (fragment, weight) in used.iter().zip(&weights) {
+            byte ^= gf256::mul(fragment.data()[i], *weight);
+        }
+        secret.push(byte);
+    }
+    Ok(secret)
+}
+
+/// The polynomial with these coefficients (constant term first) at `x`
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |y, &coefficient| gf256::mul(y, x) ^ coefficient)
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,93 @@
+// src/test.rs
+
+use super::*;
+use alloc::vec::Vec;
+use rand_core::OsRng;
+
+const KEY: &[u8; 32] = b"wallet secret key, 32 bytes long";
+
+#[test]
+fn test_gf256_inverse() {
+    assert_eq!(gf256::mul(0x57, 0x83), 0xc1);
+    for a in 1..=255u8 {
+        assert_eq!(gf256::mul(a, gf256::inv(a)), 1);
+    }
+}
+
+#[test]
+fn test_any_two_wallet_fragments_rebuild_the_key() {
+    let [f1, f2, f3] = split_wallet_key(KEY, &mut OsRng).unwrap();
+    assert_eq!(f1.slot(), Some(Slot::F1));
+    assert_eq!(f3.slot(), Some(Slot::F3));
+    assert_ne!(f1.data(), &KEY[..]);
+
+    for pair in [[&f1, &f2], [&f1, &f3], [&f3, &f2]] {
+        let pair: Vec<Fragment> = pair.into_iter().cloned().collect();
+        assert_eq!(reconstruct(&pair).unwrap().as_slice(), KEY);
+    }
+    assert_eq!(
+        reconstruct(&[f2]).unwrap_err(),
+        ShamirError::NotEnoughFragments
+    );
+}
+
+#[test]
+fn test_higher_threshold() {
+    let fragments = split(KEY, 3, 5, &mut OsRng).unwrap();
+    assert_eq!(reconstruct(&fragments[2..]).unwrap().as_slice(), KEY);
+    assert_eq!(
+        reconstruct(&fragments[3..]).unwrap_err(),
+        ShamirError::NotEnoughFragments
+    );
+
+    assert_eq!(
+        split(KEY, 1, 3, &mut OsRng).unwrap_err(),
+        ShamirError::InvalidThreshold
+    );
+    assert_eq!(
+        split(KEY, 4, 3, &mut OsRng).unwrap_err(),
+        ShamirError::InvalidThreshold
+    );
+    assert_eq!(
+        split(b"", 2, 3, &mut OsRng).unwrap_err(),
+        ShamirError::EmptySecret
+    );
+}
+
+#[test]
+fn test_rejects_mixed_fragments() {
+    let [f1, _, _] = split_wallet_key(KEY, &mut OsRng).unwrap();
+    assert_eq!(
+        reconstruct(&[f1.clone(), f1.clone()]).unwrap_err(),
+        ShamirError::InvalidIndex
+    );
+
+    let [_, short, _] = split_wallet_key(&KEY[..16], &mut OsRng).unwrap();
+    assert_eq!(
+        reconstruct(&[f1, short]).unwrap_err(),
+        ShamirError::MismatchedFragments
+    );
+}
+
+#[test]
+fn test_fragment_encodings_round_trip() {
+    let [f1, f2, _] = split_wallet_key(KEY, &mut OsRng).unwrap();
+
+    let json = serde_json::to_string(&f1).unwrap();
+    assert!(json.starts_with(r#"{"index":1,"threshold":2,"data":""#));
+    let f1: Fragment = serde_json::from_str(&json).unwrap();
+
+    let bytes = f2.to_bytes();
+    assert_eq!(bytes[..3], [FRAGMENT_VERSION, 2, 2]);
+    let f2 = Fragment::from_bytes(&bytes).unwrap();
+    assert_eq!(reconstruct(&[f1, f2]).unwrap().as_slice(), KEY);
+
+    assert_eq!(
+        Fragment::from_bytes(&[FRAGMENT_VERSION, 0, 2, 1]).unwrap_err(),
+        ShamirError::InvalidEncoding
+    );
+    assert_eq!(
+        Fragment::from_bytes(&[9, 1, 2, 1]).unwrap_err(),
+        ShamirError::InvalidEncoding
+    );
+}
@@ -0,0 +1,291 @@
+// ---------------------------------------------------------------------------
+// accesly-wasm
+//
+// `accesly-shamir` and `accesly-fragment-crypto` for the browser, so
+// `accesly.auth.recover()` splits, rebuilds and decrypts fragments with the
+// same code the backend runs. Build with
+//
+//     wasm-pack build crates/accesly-wasm --release --target web
+//
+// and keep it under budget with `cargo xtask wasm-size`.
+//
+// Fragments cross the boundary in their binary encoding (`Fragment::to_bytes`)
+// as `Uint8Array`s. Secrets handed back to JS are copied straight out of
+// zeroizing buffers, so nothing is left behind in the module's memory; what
+// JS does with them afterwards is up to the caller.
+//
+// Failures throw an `Error` whose `name` is the Rust error type and whose
+// `code` is one of `AcceslyCryptoErrorCode`, so callers switch on `code`
+// rather than on message text.
+// ---------------------------------------------------------------------------
+
+use accesly_fragment_crypto::{DataKey, FragmentCryptoError, Kdf as KdfParams};
+use accesly_shamir::{Fragment, ShamirError};
+use js_sys::{Array, Reflect, Uint8Array};
+use rand_core::OsRng;
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+#[wasm_bindgen(typescript_custom_section)]
+const ERROR_TYPES: &str = r#"
+export type AcceslyCryptoErrorCode =
+  | "EMPTY_SECRET"
+  | "INVALID_THRESHOLD"
+  | "NOT_ENOUGH_FRAGMENTS"
+  | "INVALID_INDEX"
+  | "MISMATCHED_FRAGMENTS"
+  | "INVALID_FRAGMENT"
+  | "INVALID_CONTAINER"
+  | "UNSUPPORTED_VERSION"
+  | "UNSUPPORTED_SCHEME"
+  | "INVALID_PARAMS"
+  | "DECRYPTION_FAILED";
+
+export interface AcceslyCryptoError extends Error {
+  name: "ShamirError" | "FragmentCryptoError";
+  code: AcceslyCryptoErrorCode;
+}
+"#;
+
+/// What `error.code` is set to on the JS side
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorCode {
+    EmptySecret,
+    InvalidThreshold,
+    NotEnoughFragments,
+    InvalidIndex,
+    MismatchedFragments,
+    /// Not an encoded Shamir fragment
+    InvalidFragment,
+    /// Not an encrypted fragment container
+    InvalidContainer,
+    UnsupportedVersion,
+    UnsupportedScheme,
+    InvalidParams,
+    DecryptionFailed,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::EmptySecret => "EMPTY_SECRET",
+            Self::InvalidThreshold => "INVALID_THRESHOLD",
+            Self::NotEnoughFragments => "NOT_ENOUGH_FRAGMENTS",
+            Self::InvalidIndex => "INVALID_INDEX",
+            Self::MismatchedFragments => "MISMATCHED_FRAGMENTS",
+            Self::InvalidFragment => "INVALID_FRAGMENT",
+            Self::InvalidContainer => "INVALID_CONTAINER",
+            Self::UnsupportedVersion => "UNSUPPORTED_VERSION",
+            Self::UnsupportedScheme => "UNSUPPORTED_SCHEME",
+            Self::InvalidParams => "INVALID_PARAMS",
+            Self::DecryptionFailed => "DECRYPTION_FAILED",
+        }
+    }
+}
+
+/// An error as it's thrown to JS
+#[derive(Debug, Eq, PartialEq)]
+pub struct WasmError {
+    pub name: &'static str,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl From<ShamirError> for WasmError {
+    fn from(err: ShamirError) -> Self {
+        let code = match err {
+            ShamirError::EmptySecret => ErrorCode::EmptySecret,
+            ShamirError::InvalidThreshold => ErrorCode::InvalidThreshold,
+            ShamirError::NotEnoughFragments => ErrorCode::NotEnoughFragments,
+            ShamirError::InvalidIndex => ErrorCode::InvalidIndex,
+            ShamirError::MismatchedFragments => ErrorCode::MismatchedFragments,
+            ShamirError::InvalidEncoding => ErrorCode::InvalidFragment,
+        };
+        WasmError {
+            name: "ShamirError",
+            code,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<FragmentCryptoError> for WasmError {
+    fn from(err: FragmentCryptoError) -> Self {
+        let code = match err {
+            FragmentCryptoError::InvalidFormat => ErrorCode::InvalidContainer,
+            FragmentCryptoError::UnsupportedVersion(_) => ErrorCode::UnsupportedVersion,
+            FragmentCryptoError::UnsupportedScheme(_) => ErrorCode::UnsupportedScheme,
+            FragmentCryptoError::InvalidParams => ErrorCode::InvalidParams,
+            FragmentCryptoError::DecryptionFailed => ErrorCode::DecryptionFailed,
+        };
+        WasmError {
+            name: "FragmentCryptoError",
+            code,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<WasmError> for JsValue {
+    fn from(err: WasmError) -> Self {
+        let error = js_sys::Error::new(&err.message);
+        error.set_name(err.name);
+        // Only fails on a frozen object, which a fresh Error isn't
+        let _ = Reflect::set(&error, &"code".into(), &err.code.as_str().into());
+        error.into()
+    }
+}
+
+/// How `encryptF3` derives the key from the email
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Kdf(KdfParams);
+
+#[wasm_bindgen]
+impl Kdf {
+    pub fn pbkdf2(rounds: u32) -> Kdf {
+        Kdf(KdfParams::Pbkdf2Sha256 { rounds })
+    }
+
+    pub fn argon2id(memory_kib: u32, iterations: u32, parallelism: u32) -> Kdf {
+        Kdf(KdfParams::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+        })
+    }
+
+    /// What the backend seals new F3 fragments with
+    #[wasm_bindgen(js_name = defaultArgon2id)]
+    pub fn default_argon2id() -> Kdf {
+        Kdf(KdfParams::DEFAULT_ARGON2ID)
+    }
+
+    #[wasm_bindgen(js_name = defaultPbkdf2)]
+    pub fn default_pbkdf2() -> Kdf {
+        Kdf(KdfParams::DEFAULT_PBKDF2)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shamir
+// ---------------------------------------------------------------------------
+
+/// Split `secret` into `count` encoded fragments, any `threshold` of which
+/// rebuild it
+pub fn split_encoded(
+    secret: &[u8],
+    threshold: u8,
+    count: u8,
+) -> Result<Vec<Zeroizing<Vec<u8>>>, WasmError> {
+    let fragments = accesly_shamir::split(secret, threshold, count, &mut OsRng)?;
+    Ok(fragments.iter().map(Fragment::to_bytes).collect())
+}
+
+/// Rebuild a secret from encoded fragments
+pub fn reconstruct_encoded<B: AsRef<[u8]>>(
+    fragments: &[B],
+) -> Result<Zeroizing<Vec<u8>>, WasmError> {
+    let fragments = fragments
+        .iter()
+        .map(|bytes| Fragment::from_bytes(bytes.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(accesly_shamir::reconstruct(&fragments)?)
+}
+
+#[wasm_bindgen]
+pub fn split(secret: &[u8], threshold: u8, count: u8) -> Result<Array, JsValue> {
+    Ok(to_array(split_encoded(secret, threshold, count)?))
+}
+
+/// Split a wallet key into `[F1, F2, F3]`, 2-of-3
+#[wasm_bindgen(js_name = splitWalletKey)]
+pub fn split_wallet_key(secret: &[u8]) -> Result<Array, JsValue> {
+    Ok(to_array(split_encoded(
+        secret,
+        accesly_shamir::WALLET_THRESHOLD,
+        accesly_shamir::WALLET_FRAGMENTS,
+    )?))
+}
+
+/// Rebuild a secret from an array of encoded fragments
+#[wasm_bindgen]
+pub fn reconstruct(fragments: Array) -> Result<Uint8Array, JsValue> {
+    let fragments = fragments
+        .iter()
+        .map(|value| {
+            value
+                .dyn_into::<Uint8Array>()
+                .map(|bytes| Zeroizing::new(bytes.to_vec()))
+                .map_err(|_| WasmError::from(ShamirError::InvalidEncoding))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(to_js(&reconstruct_encoded(&fragments)?))
+}
+
+// ---------------------------------------------------------------------------
+// Fragment encryption
+// ---------------------------------------------------------------------------
+
+/// A KMS data key from its `Plaintext` and `CiphertextBlob`
+pub fn data_key(plaintext: &[u8], encrypted: Vec<u8>) -> Result<DataKey, WasmError> {
+    Ok(DataKey {
+        plaintext: Zeroizing::new(key_bytes(plaintext)?),
+        encrypted,
+    })
+}
+
+fn key_bytes(bytes: &[u8]) -> Result<[u8; 32], WasmError> {
+    bytes
+        .try_into()
+        .map_err(|_| FragmentCryptoError::InvalidParams.into())
+}
+
+/// Encrypt F2 under a KMS data key
+#[wasm_bindgen(js_name = encryptF2)]
+pub fn encrypt_f2(
+    fragment: &[u8],
+    plaintext_key: &[u8],
+    encrypted_key: Vec<u8>,
+) -> Result<Vec<u8>, JsValue> {
+    let key = data_key(plaintext_key, encrypted_key)?;
+    Ok(accesly_fragment_crypto::seal_f2(fragment, &key).map_err(WasmError::from)?)
+}
+
+/// Decrypt F2 with the plaintext of the data key in its header
+#[wasm_bindgen(js_name = decryptF2)]
+pub fn decrypt_f2(container: &[u8], plaintext_key: &[u8]) -> Result<Uint8Array, JsValue> {
+    let key = Zeroizing::new(key_bytes(plaintext_key)?);
+    let fragment = accesly_fragment_crypto::open_f2(container, &key).map_err(WasmError::from)?;
+    Ok(to_js(&fragment))
+}
+
+/// Encrypt F3 under a key derived from `email`
+#[wasm_bindgen(js_name = encryptF3)]
+pub fn encrypt_f3(fragment: &[u8], email: &str, kdf: &Kdf) -> Result<Vec<u8>, JsValue> {
+    Ok(accesly_fragment_crypto::seal_f3(fragment, email, kdf.0).map_err(WasmError::from)?)
+}
+
+/// Decrypt F3 with the recovery email, with the KDF its header names
+#[wasm_bindgen(js_name = decryptF3)]
+pub fn decrypt_f3(container: &[u8], email: &str) -> Result<Uint8Array, JsValue> {
+    let fragment = accesly_fragment_crypto::open_f3(container, email).map_err(WasmError::from)?;
+    Ok(to_js(&fragment))
+}
+
+/// The form of `email` F3 keys are derived from
+#[wasm_bindgen(js_name = normalizeEmail)]
+pub fn normalize_email(email: &str) -> String {
+    accesly_fragment_crypto::normalize_email(email)
+}
+
+fn to_js(bytes: &[u8]) -> Uint8Array {
+    Uint8Array::from(bytes)
+}
+
+fn to_array(items: Vec<Zeroizing<Vec<u8>>>) -> Array {
+    items.iter().map(|bytes| to_js(bytes)).collect()
+}
+
+#[cfg(test)]
+mod test;
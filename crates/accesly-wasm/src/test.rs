@@ -0,0 +1,84 @@
+// src/test.rs
+
+use super::*;
+
+const KEY: &[u8; 32] = b"wallet secret key, 32 bytes long";
+
+// ============================================================================
+// Shamir
+// ============================================================================
+
+#[test]
+fn test_encoded_fragments_rebuild_the_key() {
+    let fragments = split_encoded(KEY, 2, 3).unwrap();
+    assert_eq!(fragments.len(), 3);
+
+    let pair = [&fragments[2], &fragments[0]];
+    assert_eq!(reconstruct_encoded(&pair).unwrap().as_slice(), KEY);
+
+    // Same bytes the backend's `Fragment::to_bytes` writes
+    let decoded = Fragment::from_bytes(&fragments[1]).unwrap();
+    assert_eq!(decoded.index(), 2);
+    assert_eq!(decoded.threshold(), 2);
+}
+
+#[test]
+fn test_shamir_errors_map_to_codes() {
+    let err = split_encoded(&[], 2, 3).unwrap_err();
+    assert_eq!(err.name, "ShamirError");
+    assert_eq!(err.code, ErrorCode::EmptySecret);
+    assert_eq!(err.message, "secret is empty");
+
+    assert_eq!(
+        split_encoded(KEY, 4, 3).unwrap_err().code,
+        ErrorCode::InvalidThreshold
+    );
+
+    let fragments = split_encoded(KEY, 2, 3).unwrap();
+    assert_eq!(
+        reconstruct_encoded(&fragments[..1]).unwrap_err().code,
+        ErrorCode::NotEnoughFragments
+    );
+    assert_eq!(
+        reconstruct_encoded(&[&fragments[0], &fragments[0]])
+            .unwrap_err()
+            .code,
+        ErrorCode::InvalidIndex
+    );
+    assert_eq!(
+        reconstruct_encoded(&[b"not a fragment".as_slice()])
+            .unwrap_err()
+            .code,
+        ErrorCode::InvalidFragment
+    );
+}
+
+// ============================================================================
+// Fragment encryption
+// ============================================================================
+
+#[test]
+fn test_crypto_errors_map_to_codes() {
+    let err = WasmError::from(FragmentCryptoError::DecryptionFailed);
+    assert_eq!(err.name, "FragmentCryptoError");
+    assert_eq!(err.code, ErrorCode::DecryptionFailed);
+    assert_eq!(err.code.as_str(), "DECRYPTION_FAILED");
+
+    assert_eq!(
+        WasmError::from(FragmentCryptoError::InvalidFormat).code,
+        ErrorCode::InvalidContainer
+    );
+    assert_eq!(
+        WasmError::from(FragmentCryptoError::UnsupportedVersion(9)).code,
+        ErrorCode::UnsupportedVersion
+    );
+}
+
+#[test]
+fn test_data_key_must_be_32_bytes() {
+    let key = data_key(&[7u8; 32], b"kms blob".to_vec()).unwrap();
+    assert_eq!(key.plaintext.as_slice(), &[7u8; 32]);
+
+    let err = data_key(&[7u8; 16], b"kms blob".to_vec()).err().unwrap();
+    assert_eq!(err.code, ErrorCode::InvalidParams);
+}
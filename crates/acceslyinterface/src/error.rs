@@ -0,0 +1,70 @@
+// ---------------------------------------------------------------------------
+// Call errors
+//
+// A contract client's `try_` methods return
+// `Result<Result<T, ConversionError>, Result<E, InvokeError>>`, which callers
+// end up matching on by hand, often by error code. The generated bindings
+// in `accesly-bindings` flatten that into `Result<T, Error<E>>`, where `E`
+// is the contract's own error enum, through `from_try` and `from_try_host`.
+// ---------------------------------------------------------------------------
+
+use soroban_sdk::InvokeError;
+
+/// Failure of a contract call made through the generated bindings
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error<E> {
+    /// One of the errors the contract declares
+    Contract(E),
+    /// Anything else: a trap, missing auth, exhausted budget, or an error
+    /// code the contract doesn't declare
+    Host(InvokeError),
+    /// The call succeeded but its result didn't convert to the expected type
+    Conversion,
+}
+
+impl<E> Error<E> {
+    /// The contract error, if that's what this is
+    pub fn contract(self) -> Option<E> {
+        match self {
+            Self::Contract(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<E> From<InvokeError> for Error<E> {
+    fn from(err: InvokeError) -> Self {
+        Self::Host(err)
+    }
+}
+
+impl<E> From<soroban_sdk::Error> for Error<E> {
+    fn from(err: soroban_sdk::Error) -> Self {
+        Self::Host(err.into())
+    }
+}
+
+/// Flatten the `try_` result of a function returning `Result<T, E>`
+pub fn from_try<T, C, E>(
+    result: Result<Result<T, C>, Result<E, InvokeError>>,
+) -> Result<T, Error<E>> {
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(_)) => Err(Error::Conversion),
+        Err(Ok(err)) => Err(Error::Contract(err)),
+        Err(Err(err)) => Err(err.into()),
+    }
+}
+
+/// Flatten the `try_` result of a function that doesn't return a
+/// `Result`, so can only fail in the host
+pub fn from_try_host<T, C, E>(
+    result: Result<Result<T, C>, Result<soroban_sdk::Error, InvokeError>>,
+) -> Result<T, Error<E>> {
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(_)) => Err(Error::Conversion),
+        Err(Ok(err)) => Err(err.into()),
+        Err(Err(err)) => Err(err.into()),
+    }
+}
@@ -0,0 +1,283 @@
+// ---------------------------------------------------------------------------
+// Event codes
+//
+// The first topic of every event the Accesly contracts publish. Events carry
+// one of these short codes plus structured data, never prose, so indexers
+// and support tools can key on them; `accesly-client::reasons` maps each
+// code to the localized sentence shown to users. A new event needs a code
+// here, in `ALL`, and a translation there.
+// ---------------------------------------------------------------------------
+
+// Wallet
+pub const WALLET_CREATED: &str = "wallet_created";
+pub const AUTH_SUCCESS: &str = "auth_success";
+pub const KEY_ROTATED: &str = "key_rotated";
+pub const ROTATION_DELAY: &str = "rotation_delay";
+pub const ROTATION_PROPOSED: &str = "rotation_proposed";
+pub const ROTATION_CANCELLED: &str = "rotation_cancelled";
+pub const PASSKEY_CHANGED: &str = "passkey_changed";
+pub const SIGNER_CHANGED: &str = "signer_changed";
+pub const THRESHOLD_CHANGED: &str = "threshold_changed";
+pub const GUARDIAN_CHANGED: &str = "guardian_changed";
+pub const GUARDIAN_THRESHOLD: &str = "guardian_threshold";
+pub const GUARDIAN_DELAY: &str = "guardian_delay";
+pub const GUARDIAN_CHANGE_PROPOSED: &str = "guardian_change_proposed";
+pub const GUARDIAN_CHANGE_CANCELLED: &str = "guardian_change_cancelled";
+pub const GUARDIAN_ROTATED: &str = "guardian_rotated";
+pub const RECOVERY_INITIATED: &str = "recovery_initiated";
+pub const RECOVERY_APPROVED: &str = "recovery_approved";
+pub const RECOVERY_VETOED: &str = "recovery_vetoed";
+pub const VERIFIER_SET: &str = "verifier_set";
+pub const EMAIL_RECOVERY: &str = "email_recovery";
+pub const RECOVERY_COOLDOWN: &str = "recovery_cooldown";
+pub const RECOVERY_COOLDOWN_LIFTED: &str = "recovery_cooldown_lifted";
+pub const RECOVERY_COOLDOWN_CONFIG: &str = "recovery_cooldown_config";
+pub const EMAIL_ROTATED: &str = "email_rotated";
+pub const FRAGMENT_RELEASE: &str = "fragment_release";
+pub const SESSION_CREATED: &str = "session_created";
+pub const SESSION_DERIVED: &str = "session_derived";
+pub const SESSION_REVOKED: &str = "session_revoked";
+pub const SESSIONS_REVOKED: &str = "sessions_revoked";
+pub const SESSION_AUTH: &str = "session_auth";
+pub const DEPOSIT_BINDING_SET: &str = "deposit_binding_set";
+pub const DEPOSIT_BINDING_REMOVED: &str = "deposit_binding_removed";
+pub const VIRTUAL_ID_REGISTERED: &str = "virtual_id_registered";
+pub const VIRTUAL_PAYMENT: &str = "virtual_payment";
+pub const SHARES_SET: &str = "shares_set";
+pub const DISTRIBUTION: &str = "distribution";
+pub const QUOTE_ACCEPTED: &str = "quote_accepted";
+pub const CONVERSION: &str = "conversion";
+pub const CONVERSION_RULE: &str = "conversion_rule";
+pub const LENDING: &str = "lending";
+pub const BATCH_EXECUTED: &str = "batch_executed";
+pub const BATCH_OP: &str = "batch_op";
+pub const BATCH_LIMITS: &str = "batch_limits";
+pub const OP_WEIGHT: &str = "op_weight";
+pub const CONTRACT_INVOKED: &str = "contract_invoked";
+pub const ALLOWLIST_MODE: &str = "allowlist_mode";
+pub const CONTRACT_LISTING: &str = "contract_listing";
+/// On chain for a blocked `invoke`; batches and authorizations fail and
+/// roll it back, so there it only shows in simulation
+pub const CONTRACT_BLOCKED: &str = "contract_blocked";
+pub const ASSET_DISPLAY: &str = "asset_display";
+pub const CHANGE_PROPOSED: &str = "change_proposed";
+pub const CHANGE_APPROVED: &str = "change_approved";
+pub const DEVICE_ADDED: &str = "device_added";
+pub const DEVICE_REMOVED: &str = "device_removed";
+pub const DEVICE_REMOVAL_QUEUED: &str = "device_removal_queued";
+pub const DEVICE_REMOVAL_EXECUTED: &str = "device_removal_executed";
+pub const DEVICE_REMOVAL_CANCELLED: &str = "device_removal_cancelled";
+pub const READ_ACCESS_GRANTED: &str = "read_access_granted";
+pub const READ_ACCESS_REVOKED: &str = "read_access_revoked";
+pub const UPGRADE_PROPOSED: &str = "upgrade_proposed";
+pub const UPGRADE_CANCELLED: &str = "upgrade_cancelled";
+pub const WALLET_UPGRADED: &str = "wallet_upgraded";
+pub const STORAGE_MIGRATED: &str = "storage_migrated";
+pub const WALLET_FROZEN: &str = "wallet_frozen";
+pub const WALLET_UNFROZEN: &str = "wallet_unfrozen";
+pub const KEY_COMPROMISED: &str = "key_compromised";
+pub const QUARANTINE_RELEASED: &str = "quarantine_released";
+pub const PREAUTH_GRANTED: &str = "preauth_granted";
+pub const PREAUTH_REVOKED: &str = "preauth_revoked";
+pub const SPONSORSHIP_RECORDED: &str = "sponsorship_recorded";
+pub const SPONSOR_REPAID: &str = "sponsor_repaid";
+pub const RECEIPTS_COMMITTED: &str = "receipts_committed";
+pub const SNAPSHOT_ASSETS: &str = "snapshot_assets";
+pub const BALANCE_SNAPSHOT: &str = "balance_snapshot";
+pub const NOTIFY_FILTER: &str = "notify_filter";
+pub const ATTESTOR_GRANTED: &str = "attestor_granted";
+pub const ATTESTOR_REVOKED: &str = "attestor_revoked";
+pub const ATTESTATION_RECORDED: &str = "attestation_recorded";
+pub const RELAYERS_SET: &str = "relayers_set";
+pub const META_TX_EXECUTED: &str = "meta_tx_executed";
+pub const AUTH_POLICY_SET: &str = "auth_policy_set";
+pub const ACTION_COSIGNED: &str = "action_cosigned";
+pub const ALLOWANCE_APPROVED: &str = "allowance_approved";
+pub const ALLOWANCE_REVOKED: &str = "allowance_revoked";
+pub const ALLOWANCE_PULLED: &str = "allowance_pulled";
+pub const SUBSCRIPTION_CREATED: &str = "subscription_created";
+pub const SUBSCRIPTION_PAUSED: &str = "subscription_paused";
+pub const SUBSCRIPTION_CANCELLED: &str = "subscription_cancelled";
+pub const SUBSCRIPTION_COLLECTED: &str = "subscription_collected";
+pub const SUBSCRIPTION_MISSED: &str = "subscription_missed";
+pub const TX_SCHEDULED: &str = "tx_scheduled";
+pub const SCHEDULED_EXECUTED: &str = "scheduled_executed";
+pub const SCHEDULED_CANCELLED: &str = "scheduled_cancelled";
+pub const LARGE_TRANSFER_THRESHOLD: &str = "large_transfer_threshold";
+pub const LARGE_TRANSFER_QUEUED: &str = "large_transfer_queued";
+pub const LARGE_TRANSFER_EXECUTED: &str = "large_transfer_executed";
+pub const LARGE_TRANSFER_CANCELLED: &str = "large_transfer_cancelled";
+pub const CONTACT_ADDED: &str = "contact_added";
+pub const CONTACT_REMOVED: &str = "contact_removed";
+pub const CONTACTS_ONLY: &str = "contacts_only";
+pub const VELOCITY_LIMIT: &str = "velocity_limit";
+pub const VELOCITY_BYPASS: &str = "velocity_bypass";
+pub const ORACLE_SET: &str = "oracle_set";
+pub const FIAT_LIMIT: &str = "fiat_limit";
+pub const PRESET_APPLIED: &str = "preset_applied";
+pub const OWN_ACCOUNT_ADDED: &str = "own_account_added";
+pub const OWN_ACCOUNT_REMOVED: &str = "own_account_removed";
+pub const INTERNAL_TRANSFER: &str = "internal_transfer";
+pub const DEV_SET_NONCE: &str = "dev_set_nonce";
+pub const DEV_FAST_FORWARD: &str = "dev_fast_forward";
+
+// Wallet factory
+pub const WALLET_DEPLOYED: &str = "wallet_deployed";
+pub const WALLET_WASM_UPDATED: &str = "wallet_wasm_updated";
+pub const APP_REGISTERED: &str = "app_registered";
+pub const MANIFEST_PUBLISHED: &str = "manifest_published";
+pub const WATCH_ADDED: &str = "watch_added";
+pub const WATCH_REMOVED: &str = "watch_removed";
+pub const WALLET_LINKED: &str = "wallet_linked";
+pub const WALLET_UNLINKED: &str = "wallet_unlinked";
+
+// Wallet registry
+pub const WALLET_REGISTERED: &str = "wallet_registered";
+pub const OWNER_SYNCED: &str = "owner_synced";
+pub const EMAIL_SYNCED: &str = "email_synced";
+
+// KYC attestation
+pub const ATTESTOR_ROTATED: &str = "attestor_rotated";
+pub const KYC_STATUS: &str = "kyc_status";
+
+// Paymaster
+pub const PAYMASTER_FUNDED: &str = "paymaster_funded";
+pub const PAYMASTER_WITHDRAWN: &str = "paymaster_withdrawn";
+pub const RELAYER_CHANGED: &str = "relayer_changed";
+pub const BUDGET_SET: &str = "budget_set";
+pub const FEE_SPONSORED: &str = "fee_sponsored";
+
+// Oracle adapter
+pub const ORACLE_FEED_SET: &str = "oracle_feed_set";
+
+/// Every code above
+pub const ALL: &[&str] = &[
+    WALLET_CREATED,
+    AUTH_SUCCESS,
+    KEY_ROTATED,
+    ROTATION_DELAY,
+    ROTATION_PROPOSED,
+    ROTATION_CANCELLED,
+    PASSKEY_CHANGED,
+    SIGNER_CHANGED,
+    THRESHOLD_CHANGED,
+    GUARDIAN_CHANGED,
+    GUARDIAN_THRESHOLD,
+    GUARDIAN_DELAY,
+    GUARDIAN_CHANGE_PROPOSED,
+    GUARDIAN_CHANGE_CANCELLED,
+    GUARDIAN_ROTATED,
+    RECOVERY_INITIATED,
+    RECOVERY_APPROVED,
+    RECOVERY_VETOED,
+    VERIFIER_SET,
+    EMAIL_RECOVERY,
+    RECOVERY_COOLDOWN,
+    RECOVERY_COOLDOWN_LIFTED,
+    RECOVERY_COOLDOWN_CONFIG,
+    EMAIL_ROTATED,
+    FRAGMENT_RELEASE,
+    SESSION_CREATED,
+    SESSION_DERIVED,
+    SESSION_REVOKED,
+    SESSIONS_REVOKED,
+    SESSION_AUTH,
+    DEPOSIT_BINDING_SET,
+    DEPOSIT_BINDING_REMOVED,
+    VIRTUAL_ID_REGISTERED,
+    VIRTUAL_PAYMENT,
+    SHARES_SET,
+    DISTRIBUTION,
+    QUOTE_ACCEPTED,
+    CONVERSION,
+    CONVERSION_RULE,
+    LENDING,
+    BATCH_EXECUTED,
+    BATCH_OP,
+    BATCH_LIMITS,
+    OP_WEIGHT,
+    CONTRACT_INVOKED,
+    ALLOWLIST_MODE,
+    CONTRACT_LISTING,
+    CONTRACT_BLOCKED,
+    ASSET_DISPLAY,
+    CHANGE_PROPOSED,
+    CHANGE_APPROVED,
+    DEVICE_ADDED,
+    DEVICE_REMOVED,
+    DEVICE_REMOVAL_QUEUED,
+    DEVICE_REMOVAL_EXECUTED,
+    DEVICE_REMOVAL_CANCELLED,
+    READ_ACCESS_GRANTED,
+    READ_ACCESS_REVOKED,
+    UPGRADE_PROPOSED,
+    UPGRADE_CANCELLED,
+    WALLET_UPGRADED,
+    STORAGE_MIGRATED,
+    WALLET_FROZEN,
+    WALLET_UNFROZEN,
+    KEY_COMPROMISED,
+    QUARANTINE_RELEASED,
+    PREAUTH_GRANTED,
+    PREAUTH_REVOKED,
+    SPONSORSHIP_RECORDED,
+    SPONSOR_REPAID,
+    RECEIPTS_COMMITTED,
+    SNAPSHOT_ASSETS,
+    BALANCE_SNAPSHOT,
+    NOTIFY_FILTER,
+    ATTESTOR_GRANTED,
+    ATTESTOR_REVOKED,
+    ATTESTATION_RECORDED,
+    RELAYERS_SET,
+    META_TX_EXECUTED,
+    AUTH_POLICY_SET,
+    ACTION_COSIGNED,
+    ALLOWANCE_APPROVED,
+    ALLOWANCE_REVOKED,
+    ALLOWANCE_PULLED,
+    SUBSCRIPTION_CREATED,
+    SUBSCRIPTION_PAUSED,
+    SUBSCRIPTION_CANCELLED,
+    SUBSCRIPTION_COLLECTED,
+    SUBSCRIPTION_MISSED,
+    TX_SCHEDULED,
+    SCHEDULED_EXECUTED,
+    SCHEDULED_CANCELLED,
+    LARGE_TRANSFER_THRESHOLD,
+    LARGE_TRANSFER_QUEUED,
+    LARGE_TRANSFER_EXECUTED,
+    LARGE_TRANSFER_CANCELLED,
+    CONTACT_ADDED,
+    CONTACT_REMOVED,
+    CONTACTS_ONLY,
+    VELOCITY_LIMIT,
+    VELOCITY_BYPASS,
+    ORACLE_SET,
+    FIAT_LIMIT,
+    PRESET_APPLIED,
+    OWN_ACCOUNT_ADDED,
+    OWN_ACCOUNT_REMOVED,
+    INTERNAL_TRANSFER,
+    DEV_SET_NONCE,
+    DEV_FAST_FORWARD,
+    WALLET_DEPLOYED,
+    WALLET_WASM_UPDATED,
+    APP_REGISTERED,
+    MANIFEST_PUBLISHED,
+    WATCH_ADDED,
+    WATCH_REMOVED,
+    WALLET_LINKED,
+    WALLET_UNLINKED,
+    WALLET_REGISTERED,
+    OWNER_SYNCED,
+    EMAIL_SYNCED,
+    ATTESTOR_ROTATED,
+    KYC_STATUS,
+    PAYMASTER_FUNDED,
+    PAYMASTER_WITHDRAWN,
+    RELAYER_CHANGED,
+    BUDGET_SET,
+    FEE_SPONSORED,
+    ORACLE_FEED_SET,
+];
@@ -0,0 +1,24 @@
+#![no_std]
+
+// ---------------------------------------------------------------------------
+// acceslyinterface
+//
+// Types and constants shared by the Accesly contracts and the off-chain code
+// that calls them. `no_std` so contracts can depend on it.
+// ---------------------------------------------------------------------------
+
+pub mod error;
+pub mod events;
+pub mod manifest;
+pub mod paging;
+pub mod presets;
+pub mod versions;
+
+pub use error::{from_try, from_try_host, Error};
+pub use manifest::{Manifest, SignedManifest};
+pub use paging::*;
+pub use presets::{preset, PolicyPreset};
+pub use versions::{Changelog, VersionInfo};
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,65 @@
+// ---------------------------------------------------------------------------
+// Capability manifests
+//
+// What a wallet version supports, so the JS SDK can feature-gate at runtime
+// across a fleet running different wasm: its entry points, the event codes
+// it can publish, its error codes and the policies it enforces. The build
+// pipeline derives one from each release wasm (`cargo xtask manifest`),
+// signs `accesly_messages::manifest_message` over it with the build key and
+// publishes it to the factory, which checks the signature before storing it
+// under the wasm hash. Clients can check it again against the build key
+// they ship with.
+// ---------------------------------------------------------------------------
+
+use soroban_sdk::{contracttype, BytesN, Symbol, Vec};
+
+/// Longest list a manifest may carry
+pub const MAX_MANIFEST_ITEMS: u32 = 256;
+
+/// Policy names a manifest lists, each with the entry point that configures
+/// it: a version enforces the policy when it exports that entry point
+pub const POLICIES: [(&str, &str); 9] = [
+    ("contract_policy", "set_contract_listing"),
+    ("large_transfers", "set_large_transfer_threshold"),
+    ("fiat_limit", "set_fiat_limit"),
+    ("velocity", "set_velocity_limit"),
+    ("contacts_only", "set_contacts_only"),
+    ("auth_policy", "set_auth_policy"),
+    ("batch_limits", "set_batch_limits"),
+    ("reserve", "set_reserve"),
+    ("presets", "init_with_preset"),
+];
+
+/// What a wallet version supports. Fields are in the order the XDR map
+/// sorts them, which off-chain encoders have to follow.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Manifest {
+    pub entrypoints: Vec<Symbol>,
+    /// Codes of the contract's `Error` enum
+    pub errors: Vec<u32>,
+    /// Event codes, from `events`
+    pub events: Vec<Symbol>,
+    /// Names from `POLICIES`
+    pub policies: Vec<Symbol>,
+}
+
+impl Manifest {
+    /// Bounded lists
+    pub fn is_valid(&self) -> bool {
+        self.entrypoints.len() <= MAX_MANIFEST_ITEMS
+            && self.errors.len() <= MAX_MANIFEST_ITEMS
+            && self.events.len() <= MAX_MANIFEST_ITEMS
+            && self.policies.len() <= MAX_MANIFEST_ITEMS
+    }
+}
+
+/// A manifest with the build key's signature over it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedManifest {
+    pub manifest: Manifest,
+    /// ed25519 key it was signed with
+    pub signer: BytesN<32>,
+    pub signature: BytesN<64>,
+}
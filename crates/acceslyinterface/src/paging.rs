@@ -0,0 +1,86 @@
+// ---------------------------------------------------------------------------
+// Pagination
+//
+// Every listing view takes `(cursor, limit)` and returns
+// `(Vec<T>, Option<u32>)`: one page of items plus the cursor of the next
+// page, `None` on the last one. `Page` gives SDK code a named form of that
+// tuple, and the cursor helpers turn the position into the opaque token
+// HTTP APIs hand out.
+// ---------------------------------------------------------------------------
+
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val, Vec};
+
+/// Hard cap on items returned by any listing view. Each item costs two
+/// footprint entries (index slot + entry); 15 keeps a page, plus instance,
+/// code and index length, under the 40 read entries a transaction may touch.
+pub const MAX_PAGE_LIMIT: u32 = 15;
+
+/// Page size used when a caller doesn't ask for one
+pub const DEFAULT_PAGE_LIMIT: u32 = 10;
+
+/// Length of an encoded cursor token
+pub const CURSOR_TOKEN_LEN: usize = 8;
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<u32>,
+}
+
+impl<T> Page<T>
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    pub fn empty(env: &Env) -> Self {
+        Self {
+            items: Vec::new(env),
+            next_cursor: None,
+        }
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.next_cursor.is_none()
+    }
+}
+
+impl<T> From<(Vec<T>, Option<u32>)> for Page<T> {
+    fn from((items, next_cursor): (Vec<T>, Option<u32>)) -> Self {
+        Self { items, next_cursor }
+    }
+}
+
+/// Limit actually applied for a requested `limit`: 0 means the default,
+/// anything above `MAX_PAGE_LIMIT` is capped
+pub fn clamp_limit(limit: u32) -> u32 {
+    match limit {
+        0 => DEFAULT_PAGE_LIMIT,
+        limit => limit.min(MAX_PAGE_LIMIT),
+    }
+}
+
+/// Encode a cursor as a fixed-width lowercase hex token
+pub fn encode_cursor(cursor: u32) -> [u8; CURSOR_TOKEN_LEN] {
+    let mut token = [0u8; CURSOR_TOKEN_LEN];
+    for (i, byte) in cursor.to_be_bytes().iter().enumerate() {
+        token[2 * i] = HEX[(byte >> 4) as usize];
+        token[2 * i + 1] = HEX[(byte & 0x0f) as usize];
+    }
+    token
+}
+
+/// Decode a token from `encode_cursor`. Only the exact canonical form is
+/// accepted, so each cursor has a single token.
+pub fn decode_cursor(token: &[u8]) -> Option<u32> {
+    if token.len() != CURSOR_TOKEN_LEN {
+        return None;
+    }
+    token.iter().try_fold(0u32, |acc, &c| {
+        let digit = match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => return None,
+        };
+        Some((acc << 4) | digit as u32)
+    })
+}
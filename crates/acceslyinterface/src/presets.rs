@@ -0,0 +1,80 @@
+// ---------------------------------------------------------------------------
+// Policy presets
+//
+// Named bundles of wallet policy, so integrators pick "Starter", "Business"
+// or "HighSecurity" instead of tuning each limit themselves. The factory's
+// `deploy_with_preset` takes a preset id and the wallet applies the bundle
+// in `init_with_preset`, in the deployment transaction. Nothing is locked
+// in: the owner changes any of it later through the usual owner-signed
+// setters.
+//
+// Every value stays within the wallet's own ceilings (`MAX_VELOCITY_OPS`,
+// `MAX_ROTATION_DELAY`, `MAX_NEW_KEY_WINDOW`, `MAX_BATCH_OPS`), which
+// `init_with_preset` checks again.
+// ---------------------------------------------------------------------------
+
+use soroban_sdk::contracttype;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+pub const PRESET_STARTER: u32 = 1;
+pub const PRESET_BUSINESS: u32 = 2;
+pub const PRESET_HIGH_SECURITY: u32 = 3;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PolicyPreset {
+    /// Authorizations allowed per `velocity_window`, 0 for no limit
+    pub velocity_max_ops: u32,
+    /// Seconds
+    pub velocity_window: u64,
+    /// Seconds a proposed owner rotation waits before it applies
+    pub rotation_delay: u64,
+    /// Seconds a newly installed owner key waits out for high-risk changes
+    pub new_key_window: u64,
+    /// Most calls in one batch
+    pub max_batch_ops: u32,
+    /// Restrict outgoing transfers to saved contacts
+    pub contacts_only: bool,
+}
+
+/// A personal wallet: room for everyday use, a day to catch a stolen key
+pub const STARTER: PolicyPreset = PolicyPreset {
+    velocity_max_ops: 60,
+    velocity_window: HOUR,
+    rotation_delay: DAY,
+    new_key_window: DAY,
+    max_batch_ops: 10,
+    contacts_only: false,
+};
+
+/// A business wallet paying many parties, with longer delays on key changes
+pub const BUSINESS: PolicyPreset = PolicyPreset {
+    velocity_max_ops: 300,
+    velocity_window: HOUR,
+    rotation_delay: 2 * DAY,
+    new_key_window: 3 * DAY,
+    max_batch_ops: 20,
+    contacts_only: false,
+};
+
+/// Savings: few operations, contacts only and a week on key changes
+pub const HIGH_SECURITY: PolicyPreset = PolicyPreset {
+    velocity_max_ops: 20,
+    velocity_window: HOUR,
+    rotation_delay: 7 * DAY,
+    new_key_window: 7 * DAY,
+    max_batch_ops: 5,
+    contacts_only: true,
+};
+
+/// The preset with id `id`, if there is one
+pub fn preset(id: u32) -> Option<PolicyPreset> {
+    match id {
+        PRESET_STARTER => Some(STARTER),
+        PRESET_BUSINESS => Some(BUSINESS),
+        PRESET_HIGH_SECURITY => Some(HIGH_SECURITY),
+        _ => None,
+    }
+}
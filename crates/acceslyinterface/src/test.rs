@@ -0,0 +1,73 @@
+// src/test.rs
+
+use super::*;
+use soroban_sdk::{vec, Env};
+
+#[test]
+fn test_clamp_limit() {
+    assert_eq!(clamp_limit(0), DEFAULT_PAGE_LIMIT);
+    assert_eq!(clamp_limit(3), 3);
+    assert_eq!(clamp_limit(MAX_PAGE_LIMIT), MAX_PAGE_LIMIT);
+    assert_eq!(clamp_limit(u32::MAX), MAX_PAGE_LIMIT);
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    for cursor in [0, 1, 15, 16, 255, 0xdead_beef, u32::MAX] {
+        assert_eq!(decode_cursor(&encode_cursor(cursor)), Some(cursor));
+    }
+    assert_eq!(&encode_cursor(0x0102_abcd), b"0102abcd");
+}
+
+#[test]
+fn test_decode_cursor_rejects_non_canonical() {
+    assert_eq!(decode_cursor(b""), None);
+    assert_eq!(decode_cursor(b"0000001"), None);
+    assert_eq!(decode_cursor(b"000000001"), None);
+    assert_eq!(decode_cursor(b"0102ABCD"), None);
+    assert_eq!(decode_cursor(b"0000000g"), None);
+}
+
+#[test]
+fn test_page_from_listing_tuple() {
+    let env = Env::default();
+    let page: Page<u32> = (vec![&env, 1, 2], Some(2)).into();
+    assert_eq!(page.items.len(), 2);
+    assert!(!page.is_last());
+    assert!(Page::<u32>::empty(&env).is_last());
+}
+
+#[test]
+fn test_event_codes_are_valid_symbols() {
+    for (i, code) in events::ALL.iter().enumerate() {
+        assert!(!code.is_empty() && code.len() <= 32);
+        assert!(code.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_'));
+        assert!(!events::ALL[..i].contains(code), "duplicate event code");
+    }
+}
+
+#[test]
+fn test_from_try_flattens_client_results() {
+    use soroban_sdk::{ConversionError, InvokeError};
+
+    let ok: Result<Result<u32, ConversionError>, Result<u8, InvokeError>> = Ok(Ok(7));
+    assert_eq!(from_try(ok), Ok(7));
+    let bad_value: Result<Result<u32, ConversionError>, Result<u8, InvokeError>> =
+        Ok(Err(ConversionError));
+    assert_eq!(from_try(bad_value), Err(Error::Conversion));
+    let declared: Result<Result<u32, ConversionError>, Result<u8, InvokeError>> = Err(Ok(3));
+    assert_eq!(from_try(declared), Err(Error::Contract(3)));
+    let declared: Result<Result<u32, ConversionError>, Result<u8, InvokeError>> = Err(Ok(3));
+    assert_eq!(from_try(declared).unwrap_err().contract(), Some(3));
+    let trapped: Result<Result<u32, ConversionError>, Result<u8, InvokeError>> =
+        Err(Err(InvokeError::Abort));
+    assert_eq!(from_try(trapped), Err(Error::Host(InvokeError::Abort)));
+
+    // Functions without a declared error can only fail in the host
+    let undeclared: Result<Result<u32, ConversionError>, Result<soroban_sdk::Error, InvokeError>> =
+        Err(Ok(soroban_sdk::Error::from_contract_error(9)));
+    assert_eq!(
+        from_try_host::<_, _, u8>(undeclared),
+        Err(Error::Host(InvokeError::Contract(9)))
+    );
+}
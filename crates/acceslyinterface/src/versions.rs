@@ -0,0 +1,57 @@
+// ---------------------------------------------------------------------------
+// Wallet versions
+//
+// The factory records a changelog with every wallet wasm it is pointed at,
+// and wallets read it back in `upgrade_to_latest` before scheduling the
+// switch. Releases that break something existing clients or integrations
+// rely on set `breaking` flags; a wallet only takes those with the owner's
+// explicit acknowledgment.
+// ---------------------------------------------------------------------------
+
+use soroban_sdk::{contracttype, Symbol, Vec};
+
+/// Signature formats or auth rules changed, so old clients can't sign
+pub const BREAKING_AUTH: u32 = 1 << 0;
+/// Entry points removed or their arguments changed
+pub const BREAKING_API: u32 = 1 << 1;
+/// Storage rewritten in a form older code can't read, so no going back
+pub const BREAKING_STORAGE: u32 = 1 << 2;
+/// Every flag defined above
+pub const BREAKING_ALL: u32 = BREAKING_AUTH | BREAKING_API | BREAKING_STORAGE;
+
+/// Longest list a changelog may carry
+pub const MAX_CHANGELOG_ITEMS: u32 = 20;
+
+/// What a wallet version changes compared with the one before it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Changelog {
+    /// `BREAKING_*` flags, 0 for a compatible release
+    pub breaking: u32,
+    /// Storage keys `migrate` rewrites on the way to this version
+    pub migrated_keys: Vec<Symbol>,
+    /// Entry points added in this version
+    pub new_entrypoints: Vec<Symbol>,
+}
+
+impl Changelog {
+    pub fn is_breaking(&self) -> bool {
+        self.breaking != 0
+    }
+
+    /// Only known flags and bounded lists
+    pub fn is_valid(&self) -> bool {
+        self.breaking & !BREAKING_ALL == 0
+            && self.migrated_keys.len() <= MAX_CHANGELOG_ITEMS
+            && self.new_entrypoints.len() <= MAX_CHANGELOG_ITEMS
+    }
+}
+
+/// A wallet wasm hash the factory knows, with its changelog
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionInfo {
+    pub changelog: Changelog,
+    /// Ledger sequence the version was registered at
+    pub registered_at: u32,
+}
@@ -0,0 +1,254 @@
+// ---------------------------------------------------------------------------
+// Batch payload encoder drift
+//
+// Builds a random batch twice: as `accesly_sdk::batch::BatchCall`s and as the
+// contract's own `Vec<Invocation>` in a host `Env`. The payload the SDK has
+// the owner sign must hash the same as the one `execute_batch` verifies, and
+// the contract must decode the SDK's bytes back to the same batch. Argument
+// values cover every scalar the host round-trips, nested vectors, bytes and
+// addresses; nested authorizations go as deep as the batch limits allow.
+//
+//     cargo +nightly fuzz run batch_payload
+// ---------------------------------------------------------------------------
+
+#![no_main]
+
+use accesly_sdk::batch::{batch_payload, best_effort_payload, BatchCall, NestedCall};
+use accountAbstraction::{Invocation, MAX_BATCH_DEPTH, MAX_BATCH_OPS};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    testutils::Address as _,
+    xdr::{FromXdr, Int128Parts, ScAddress, ScBytes, ScSymbol, ScVal, ScVec, ToXdr},
+    Address, Bytes, Env, IntoVal, Symbol, Val, Vec,
+};
+
+/// Addresses a batch can reference, generated per run
+const ADDRESSES: usize = 4;
+/// Vectors nest at most this deep inside an argument
+const MAX_ARG_DEPTH: u32 = 3;
+const SYMBOL_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+
+#[derive(Arbitrary, Debug)]
+enum Arg {
+    Void,
+    Bool(bool),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    I128(i128),
+    Symbol(Name),
+    Bytes(std::vec::Vec<u8>),
+    Address(u8),
+    Vec(std::vec::Vec<Arg>),
+}
+
+/// A valid symbol: 1 to 32 of `SYMBOL_CHARS`
+#[derive(Arbitrary, Debug)]
+struct Name(u8, [u8; 32]);
+
+#[derive(Arbitrary, Debug)]
+struct Call {
+    contract: u8,
+    function: Name,
+    args: std::vec::Vec<Arg>,
+    sub_calls: std::vec::Vec<Call>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Batch {
+    ops: std::vec::Vec<Call>,
+    max_failures: u32,
+}
+
+impl Name {
+    fn as_string(&self) -> String {
+        let len = 1 + self.0 as usize % 32;
+        self.1[..len]
+            .iter()
+            .map(|b| SYMBOL_CHARS[*b as usize % SYMBOL_CHARS.len()] as char)
+            .collect()
+    }
+}
+
+struct Ctx {
+    env: Env,
+    addresses: std::vec::Vec<Address>,
+}
+
+impl Ctx {
+    fn address(&self, index: u8) -> &Address {
+        &self.addresses[index as usize % ADDRESSES]
+    }
+
+    // SDK side
+
+    fn sc_val(&self, arg: &Arg, depth: u32) -> ScVal {
+        match arg {
+            Arg::Void => ScVal::Void,
+            Arg::Bool(b) => ScVal::Bool(*b),
+            Arg::U32(n) => ScVal::U32(*n),
+            Arg::I32(n) => ScVal::I32(*n),
+            Arg::U64(n) => ScVal::U64(*n),
+            Arg::I64(n) => ScVal::I64(*n),
+            Arg::I128(n) => ScVal::I128(Int128Parts {
+                hi: (*n >> 64) as i64,
+                lo: *n as u64,
+            }),
+            Arg::Symbol(name) => ScVal::Symbol(ScSymbol(name.as_string().try_into().unwrap())),
+            Arg::Bytes(bytes) => ScVal::Bytes(ScBytes(bytes.clone().try_into().unwrap())),
+            Arg::Address(index) => ScVal::Address(ScAddress::from(self.address(*index))),
+            Arg::Vec(items) if depth < MAX_ARG_DEPTH => {
+                let items: std::vec::Vec<ScVal> = items
+                    .iter()
+                    .map(|item| self.sc_val(item, depth + 1))
+                    .collect();
+                ScVal::Vec(Some(ScVec(items.try_into().unwrap())))
+            }
+            Arg::Vec(_) => ScVal::Void,
+        }
+    }
+
+    fn nested_call(&self, call: &Call, depth: u32) -> NestedCall {
+        NestedCall {
+            contract: ScAddress::from(self.address(call.contract)),
+            function: call.function.as_string(),
+            args: call.args.iter().map(|arg| self.sc_val(arg, 0)).collect(),
+            sub_calls: self
+                .sub_calls(call, depth)
+                .map(|sub| self.nested_call(sub, depth + 1))
+                .collect(),
+        }
+    }
+
+    fn batch_call(&self, call: &Call) -> BatchCall {
+        BatchCall {
+            contract: ScAddress::from(self.address(call.contract)),
+            function: call.function.as_string(),
+            args: call.args.iter().map(|arg| self.sc_val(arg, 0)).collect(),
+            auth: self
+                .sub_calls(call, 1)
+                .map(|sub| self.nested_call(sub, 2))
+                .collect(),
+        }
+    }
+
+    // Contract side
+
+    fn val(&self, arg: &Arg, depth: u32) -> Val {
+        let env = &self.env;
+        match arg {
+            Arg::Void => ().into_val(env),
+            Arg::Bool(b) => b.into_val(env),
+            Arg::U32(n) => n.into_val(env),
+            Arg::I32(n) => n.into_val(env),
+            Arg::U64(n) => n.into_val(env),
+            Arg::I64(n) => n.into_val(env),
+            Arg::I128(n) => n.into_val(env),
+            Arg::Symbol(name) => Symbol::new(env, &name.as_string()).into_val(env),
+            Arg::Bytes(bytes) => Bytes::from_slice(env, bytes).into_val(env),
+            Arg::Address(index) => self.address(*index).into_val(env),
+            Arg::Vec(items) if depth < MAX_ARG_DEPTH => {
+                let mut out = Vec::<Val>::new(env);
+                for item in items {
+                    out.push_back(self.val(item, depth + 1));
+                }
+                out.into_val(env)
+            }
+            Arg::Vec(_) => ().into_val(env),
+        }
+    }
+
+    fn args(&self, call: &Call) -> Vec<Val> {
+        let mut args = Vec::new(&self.env);
+        for arg in &call.args {
+            args.push_back(self.val(arg, 0));
+        }
+        args
+    }
+
+    fn auth_entry(&self, call: &Call, depth: u32) -> InvokerContractAuthEntry {
+        let mut sub_invocations = Vec::new(&self.env);
+        for sub in self.sub_calls(call, depth) {
+            sub_invocations.push_back(self.auth_entry(sub, depth + 1));
+        }
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: self.address(call.contract).clone(),
+                fn_name: Symbol::new(&self.env, &call.function.as_string()),
+                args: self.args(call),
+            },
+            sub_invocations,
+        })
+    }
+
+    fn invocation(&self, call: &Call) -> Invocation {
+        let mut auth = Vec::new(&self.env);
+        for sub in self.sub_calls(call, 1) {
+            auth.push_back(self.auth_entry(sub, 2));
+        }
+        Invocation {
+            contract: self.address(call.contract).clone(),
+            fn_name: Symbol::new(&self.env, &call.function.as_string()),
+            args: self.args(call),
+            auth,
+        }
+    }
+
+    /// A call at `depth` has no nested calls past the batch depth limit
+    fn sub_calls<'a>(&self, call: &'a Call, depth: u32) -> impl Iterator<Item = &'a Call> {
+        let allowed = if depth < MAX_BATCH_DEPTH {
+            call.sub_calls.len()
+        } else {
+            0
+        };
+        call.sub_calls.iter().take(allowed)
+    }
+}
+
+fn bytes(env: &Env, raw: &[u8]) -> Bytes {
+    Bytes::from_slice(env, raw)
+}
+
+fuzz_target!(|batch: Batch| {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    let ctx = Ctx {
+        addresses: (0..ADDRESSES).map(|_| Address::generate(&env)).collect(),
+        env,
+    };
+    let env = &ctx.env;
+    let ops = &batch.ops[..batch.ops.len().min(MAX_BATCH_OPS as usize)];
+
+    let calls: std::vec::Vec<BatchCall> = ops.iter().map(|call| ctx.batch_call(call)).collect();
+    let mut invocations = Vec::<Invocation>::new(env);
+    for call in ops {
+        invocations.push_back(ctx.invocation(call));
+    }
+
+    // execute_batch
+    let sdk = bytes(env, &batch_payload(&calls).unwrap());
+    let contract = invocations.clone().to_xdr(env);
+    assert_eq!(
+        env.crypto().sha256(&sdk).to_array(),
+        env.crypto().sha256(&contract).to_array()
+    );
+    let decoded = Vec::<Invocation>::from_xdr(env, &sdk).expect("contract rejects the SDK payload");
+    assert_eq!(decoded.to_xdr(env), contract);
+
+    // execute_batch_best_effort
+    let sdk = bytes(
+        env,
+        &best_effort_payload(&calls, batch.max_failures).unwrap(),
+    );
+    let contract = (invocations, batch.max_failures).to_xdr(env);
+    assert_eq!(
+        env.crypto().sha256(&sdk).to_array(),
+        env.crypto().sha256(&contract).to_array()
+    );
+    let decoded =
+        <(Vec<Invocation>, u32)>::from_xdr(env, &sdk).expect("contract rejects the SDK payload");
+    assert_eq!(decoded.to_xdr(env), contract);
+});
@@ -27,8 +27,8 @@ const tables = [
   },
   {
     TableName: 'channel_accounts',
-    KeySchema: [{ AttributeName: 'accountId', KeyType: 'HASH' }],
-    AttributeDefinitions: [{ AttributeName: 'accountId', AttributeType: 'S' }],
+    KeySchema: [{ AttributeName: 'channelId', KeyType: 'HASH' }],
+    AttributeDefinitions: [{ AttributeName: 'channelId', AttributeType: 'S' }],
     BillingMode: 'PAY_PER_REQUEST',
   },
   {
@@ -49,6 +49,12 @@ const tables = [
     AttributeDefinitions: [{ AttributeName: 'key', AttributeType: 'S' }],
     BillingMode: 'PAY_PER_REQUEST',
   },
+  {
+    TableName: 'relayer_lane_configs',
+    KeySchema: [{ AttributeName: 'appId', KeyType: 'HASH' }],
+    AttributeDefinitions: [{ AttributeName: 'appId', AttributeType: 'S' }],
+    BillingMode: 'PAY_PER_REQUEST',
+  },
 ];
 
 const { TableNames: existing } = await dynamo.send(new ListTablesCommand({}));
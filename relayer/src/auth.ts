@@ -1,3 +1,4 @@
+import { timingSafeEqual } from 'node:crypto';
 import jwksClient from 'jwks-rsa';
 import jwt from 'jsonwebtoken';
 import type { Request, Response, NextFunction } from 'express';
@@ -69,3 +70,21 @@ export async function requireAuth(
     res.status(401).json({ error: 'Unauthorized' });
   }
 }
+
+// Admin routes authenticate with a shared token, compared in constant time.
+// Without RELAYER_ADMIN_TOKEN they answer 404 as if they didn't exist.
+export function requireAdmin(req: Request, res: Response, next: NextFunction): void {
+  if (!config.admin.token) {
+    res.status(404).json({ error: 'Not found' });
+    return;
+  }
+
+  const given = Buffer.from(String(req.headers['x-admin-token'] ?? ''));
+  const expected = Buffer.from(config.admin.token);
+  if (given.length !== expected.length || !timingSafeEqual(given, expected)) {
+    res.status(401).json({ error: 'Unauthorized' });
+    return;
+  }
+
+  next();
+}
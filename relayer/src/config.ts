@@ -50,6 +50,7 @@ export const config = {
     tableAppConfigs:  optional('DYNAMO_TABLE_APP_CONFIGS',   'app_configs'),
     tableWallets:     optional('DYNAMO_TABLE_WALLETS',       'wallets'),
     tableMonitorState: optional('DYNAMO_TABLE_MONITOR_STATE', 'monitor_state'),
+    tableLaneConfigs: optional('DYNAMO_TABLE_LANE_CONFIGS', 'relayer_lane_configs'),
   },
 
   cors: {
@@ -71,4 +72,36 @@ export const config = {
   replenishment: {
     cron: replenishmentCron,
   },
+
+  lanes: {
+    workers: parseInt(optional('RELAYER_WORKERS', '4')),
+    settings: {
+      critical: {
+        weight: parseInt(optional('LANE_CRITICAL_WEIGHT', '6')),
+        maxWaitMs: parseInt(optional('LANE_CRITICAL_MAX_WAIT_MS', '2000')),
+      },
+      standard: {
+        weight: parseInt(optional('LANE_STANDARD_WEIGHT', '3')),
+        maxWaitMs: parseInt(optional('LANE_STANDARD_MAX_WAIT_MS', '30000')),
+      },
+      bulk: {
+        weight: parseInt(optional('LANE_BULK_WEIGHT', '1')),
+        maxWaitMs: parseInt(optional('LANE_BULK_MAX_WAIT_MS', '120000')),
+      },
+    },
+  },
+
+  sponsors: {
+    // channel_accounts.appId of the relayer's shared sponsor accounts
+    poolId: optional('SPONSOR_POOL_ID', 'relayer'),
+    leaseTtlMs: parseInt(optional('SPONSOR_LEASE_TTL_MS', '60000')),
+    minXlm: parseFloat(optional('SPONSOR_MIN_XLM', '5')),
+    targetXlm: parseFloat(optional('SPONSOR_TARGET_XLM', '20')),
+    maxXlm: parseFloat(optional('SPONSOR_MAX_XLM', '50')),
+  },
+
+  // Admin API is disabled unless a token is set
+  admin: {
+    token: optional('RELAYER_ADMIN_TOKEN', ''),
+  },
 } as const;
@@ -51,6 +51,19 @@ export interface AppConfig {
   plan: 'free' | 'growth' | 'enterprise';
 }
 
+export type Lane = 'critical' | 'standard' | 'bulk';
+
+export interface LaneConfig {
+  appId: string;
+  // Standard + bulk relays per minute; critical relays don't count against it
+  ratePerMinute: number;
+  // Critical relays per minute before further ones queue as standard
+  criticalPerMinute: number;
+  // Target queue wait per lane
+  slaMs: Record<Lane, number>;
+  updatedAt: string;
+}
+
 export interface ChannelAccount {
   channelId: string;
   appId: string;
@@ -58,7 +71,11 @@ export interface ChannelAccount {
   encryptedSecret: string;
   encryptionIv: string;
   encryptionTag: string;
+  // In the pool; set false to retire the account
   isAvailable: boolean;
+  // Instance holding the account's sequence number, until leaseExpiresAt (epoch ms)
+  leaseOwner?: string;
+  leaseExpiresAt?: number;
   lastUsedAt?: string;
   createdAt: string;
 }
@@ -159,6 +176,23 @@ export async function getFundSecret(appConfig: AppConfig): Promise<string> {
   return config.stellar.fundSecret;
 }
 
+// ---------------------------------------------------------------------------
+// relayer_lane_configs — per-app rate and SLA, written through the admin API
+// ---------------------------------------------------------------------------
+
+export async function getLaneConfig(appId: string): Promise<LaneConfig | null> {
+  const result = await dynamo.send(
+    new GetCommand({ TableName: config.dynamo.tableLaneConfigs, Key: { appId } })
+  );
+  return (result.Item as LaneConfig) ?? null;
+}
+
+export async function putLaneConfig(laneConfig: Omit<LaneConfig, 'updatedAt'>): Promise<LaneConfig> {
+  const item: LaneConfig = { ...laneConfig, updatedAt: new Date().toISOString() };
+  await dynamo.send(new PutCommand({ TableName: config.dynamo.tableLaneConfigs, Item: item }));
+  return item;
+}
+
 // ---------------------------------------------------------------------------
 // usage_tracking — H-4: atomic check-and-increment via DynamoDB condition
 // ---------------------------------------------------------------------------
@@ -300,33 +334,81 @@ export async function updateSwapRecord(
 // channel_accounts
 // ---------------------------------------------------------------------------
 
-export async function getAvailableChannel(appId: string): Promise<ChannelAccount | null> {
-  const result = await dynamo.send(new ScanCommand({
-    TableName: config.dynamo.tableChannels,
-    FilterExpression: 'appId = :appId AND isAvailable = :yes',
-    ExpressionAttributeValues: { ':appId': appId, ':yes': true },
-    Limit: 1,
-  }));
-  return (result.Items?.[0] as ChannelAccount) ?? null;
+// M-4: paginated scan — the pool is small, but don't silently drop accounts
+export async function listChannels(appId: string): Promise<ChannelAccount[]> {
+  const channels: ChannelAccount[] = [];
+  let lastKey: Record<string, any> | undefined;
+
+  do {
+    const result = await dynamo.send(new ScanCommand({
+      TableName: config.dynamo.tableChannels,
+      FilterExpression: 'appId = :appId AND isAvailable = :yes',
+      ExpressionAttributeValues: { ':appId': appId, ':yes': true },
+      ExclusiveStartKey: lastKey,
+    }));
+
+    channels.push(...((result.Items ?? []) as ChannelAccount[]));
+    lastKey = result.LastEvaluatedKey;
+  } while (lastKey);
+
+  return channels;
 }
 
-export async function lockChannel(channelId: string): Promise<void> {
-  await dynamo.send(new UpdateCommand({
-    TableName: config.dynamo.tableChannels,
-    Key: { channelId },
-    UpdateExpression: 'SET isAvailable = :no, lastUsedAt = :now',
-    ConditionExpression: 'isAvailable = :yes',
-    ExpressionAttributeValues: { ':no': false, ':yes': true, ':now': new Date().toISOString() },
-  }));
+// Leases are conditional writes, so two instances can never hold the same
+// account; one that crashes just lets its lease run out
+export async function leaseChannel(channelId: string, owner: string, ttlMs: number): Promise<boolean> {
+  const now = Date.now();
+  try {
+    await dynamo.send(new UpdateCommand({
+      TableName: config.dynamo.tableChannels,
+      Key: { channelId },
+      UpdateExpression: 'SET leaseOwner = :owner, leaseExpiresAt = :until, lastUsedAt = :iso',
+      ConditionExpression:
+        'isAvailable = :yes AND (attribute_not_exists(leaseExpiresAt) OR leaseExpiresAt < :now)',
+      ExpressionAttributeValues: {
+        ':owner': owner,
+        ':until': now + ttlMs,
+        ':iso': new Date(now).toISOString(),
+        ':yes': true,
+        ':now': now,
+      },
+    }));
+    return true;
+  } catch (err) {
+    if (err instanceof ConditionalCheckFailedException) return false;
+    throw err;
+  }
 }
 
-export async function releaseChannel(channelId: string): Promise<void> {
-  await dynamo.send(new UpdateCommand({
-    TableName: config.dynamo.tableChannels,
-    Key: { channelId },
-    UpdateExpression: 'SET isAvailable = :yes',
-    ExpressionAttributeValues: { ':yes': true },
-  }));
+export async function renewChannelLease(channelId: string, owner: string, ttlMs: number): Promise<boolean> {
+  try {
+    await dynamo.send(new UpdateCommand({
+      TableName: config.dynamo.tableChannels,
+      Key: { channelId },
+      UpdateExpression: 'SET leaseExpiresAt = :until',
+      ConditionExpression: 'leaseOwner = :owner',
+      ExpressionAttributeValues: { ':owner': owner, ':until': Date.now() + ttlMs },
+    }));
+    return true;
+  } catch (err) {
+    if (err instanceof ConditionalCheckFailedException) return false;
+    throw err;
+  }
+}
+
+export async function releaseChannelLease(channelId: string, owner: string): Promise<void> {
+  try {
+    await dynamo.send(new UpdateCommand({
+      TableName: config.dynamo.tableChannels,
+      Key: { channelId },
+      UpdateExpression: 'REMOVE leaseOwner, leaseExpiresAt',
+      ConditionExpression: 'leaseOwner = :owner',
+      ExpressionAttributeValues: { ':owner': owner },
+    }));
+  } catch (err) {
+    // Lease already expired and was taken over — nothing of ours to release
+    if (!(err instanceof ConditionalCheckFailedException)) throw err;
+  }
 }
 
 // ---------------------------------------------------------------------------
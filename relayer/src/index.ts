@@ -5,15 +5,18 @@ import cors from 'cors';
 import rateLimit from 'express-rate-limit';
 import cron from 'node-cron';
 import { config } from './config.js';
-import { requireAuth } from './auth.js';
+import { requireAuth, requireAdmin } from './auth.js';
 import type { AuthenticatedRequest } from './auth.js';
 import { relay } from './services/relay.js';
+import { scheduler, isLane, LANES } from './services/lanes.js';
 import { checkAndTrack, getUsage } from './services/x402.js';
 import { runReplenishmentCycle } from './services/replenishment.js';
 import { runMonitorCycle } from './services/monitor.js';
-import { getRelayerTx, getAppConfig } from './db/tables.js';
-import { isValidStellarAddress, accountExists, submitXdr } from './stellar/client.js';
-import { Keypair, TransactionBuilder, Operation, BASE_FEE, Account } from '@stellar/stellar-sdk';
+import { getRelayerTx, getAppConfig, putLaneConfig } from './db/tables.js';
+import type { Lane } from './db/tables.js';
+import { submitAsSponsor, runSponsorRebalance } from './services/sponsors.js';
+import { isValidStellarAddress, accountExists } from './stellar/client.js';
+import { TransactionBuilder, Operation, BASE_FEE } from '@stellar/stellar-sdk';
 
 const app = express();
 
@@ -58,15 +61,21 @@ app.get('/health', (_req, res) => {
   res.json({ status: 'ok', network: config.stellar.network });
 });
 
-// POST /relay — SDK sends signed inner XDR, relayer wraps in fee-bump and submits
+// POST /relay — SDK sends signed inner XDR, relayer wraps in fee-bump and submits.
+// The call is queued in its priority lane; `lane: "bulk"` opts into the slow lane.
 app.post('/relay', requireAuth, relayLimiter, async (req: AuthenticatedRequest, res) => {
-  const { inner_xdr, app_id } = req.body as { inner_xdr: string; app_id: string };
+  const { inner_xdr, app_id, lane } = req.body as { inner_xdr: string; app_id: string; lane?: string };
 
   if (!inner_xdr || !app_id) {
     res.status(400).json({ error: 'Missing required fields: inner_xdr, app_id' });
     return;
   }
 
+  if (lane !== undefined && !isLane(lane)) {
+    res.status(400).json({ error: `lane must be one of: ${LANES.join(', ')}` });
+    return;
+  }
+
   // C-3: verify the appId exists (ownership check — TODO: add userId field to app_configs
   // once the other dev's createApp Lambda is deployed, then verify appConfig.userId === req.userId)
   const appConfig = await getAppConfig(app_id);
@@ -77,8 +86,8 @@ app.post('/relay', requireAuth, relayLimiter, async (req: AuthenticatedRequest,
 
   try {
     await checkAndTrack(app_id, 'transactions');
-    const result = await relay({ innerXdr: inner_xdr, appId: app_id, userId: req.userId });
-    res.json({ tx_hash: result.txHash, relayer_tx_id: result.relayerTxId });
+    const result = await relay({ innerXdr: inner_xdr, appId: app_id, userId: req.userId, lane });
+    res.json({ tx_hash: result.txHash, relayer_tx_id: result.relayerTxId, lane: result.lane });
   } catch (err) {
     // H-6: log full error, return generic message
     console.error('[relay] Error:', err instanceof Error ? err.message : err);
@@ -130,22 +139,16 @@ app.post('/wallet/activate', requireAuth, activateLimiter, async (req: Authentic
       return;
     }
 
-    const fundKeypair = Keypair.fromSecret(config.stellar.fundSecret);
-    const accountRes = await fetch(`${config.stellar.horizonUrl}/accounts/${fundKeypair.publicKey()}`);
-    if (!accountRes.ok) throw new Error('Fund account not found');
-    const accountData = await accountRes.json();
-    const account = new Account(fundKeypair.publicKey(), accountData.sequence);
-
-    const tx = new TransactionBuilder(account, {
-      fee: BASE_FEE,
-      networkPassphrase: config.stellar.networkPassphrase,
-    })
-      .addOperation(Operation.createAccount({ destination: stellar_address, startingBalance: '1' }))
-      .setTimeout(30)
-      .build();
-
-    tx.sign(fundKeypair);
-    const txHash = await submitXdr(tx.toXDR());
+    // Sourced by a leased sponsor, so concurrent activations don't race on one sequence
+    const txHash = await submitAsSponsor((account) =>
+      new TransactionBuilder(account, {
+        fee: BASE_FEE,
+        networkPassphrase: config.stellar.networkPassphrase,
+      })
+        .addOperation(Operation.createAccount({ destination: stellar_address, startingBalance: '1' }))
+        .setTimeout(30)
+        .build()
+    );
     res.json({ tx_hash: txHash });
   } catch (err) {
     console.error('[wallet/activate] Error:', err instanceof Error ? err.message : err);
@@ -192,6 +195,70 @@ app.get('/config/:appId', requireAuth, async (req, res) => {
   });
 });
 
+// ---------------------------------------------------------------------------
+// Admin — priority lanes
+// ---------------------------------------------------------------------------
+
+// GET /admin/lanes — queue depth, oldest wait and dispatch counters per lane
+app.get('/admin/lanes', requireAdmin, (_req, res) => {
+  res.json({ workers: config.lanes.workers, lanes: scheduler.stats() });
+});
+
+// GET /admin/apps/:appId/lanes — an app's effective rate/SLA config and how it's doing
+app.get('/admin/apps/:appId/lanes', requireAdmin, async (req, res) => {
+  const appId = req.params.appId as string;
+  try {
+    const laneConfig = await scheduler.appConfig(appId);
+    res.json({ config: laneConfig, stats: scheduler.appStats(appId) });
+  } catch (err) {
+    console.error('[admin/lanes] Error:', err instanceof Error ? err.message : err);
+    res.status(500).json({ error: 'Failed to load lane config' });
+  }
+});
+
+// PUT /admin/apps/:appId/lanes — set an app's rate limits and SLA targets
+app.put('/admin/apps/:appId/lanes', requireAdmin, async (req, res) => {
+  const appId = req.params.appId as string;
+  const { ratePerMinute, criticalPerMinute, slaMs } = req.body as {
+    ratePerMinute?: unknown;
+    criticalPerMinute?: unknown;
+    slaMs?: Partial<Record<Lane, unknown>>;
+  };
+
+  const positive = (value: unknown): value is number =>
+    typeof value === 'number' && Number.isFinite(value) && value > 0;
+  if (
+    !positive(ratePerMinute) ||
+    !positive(criticalPerMinute) ||
+    !slaMs ||
+    !LANES.every((l) => positive(slaMs[l]))
+  ) {
+    res.status(400).json({
+      error: 'ratePerMinute, criticalPerMinute and slaMs.{critical,standard,bulk} must be positive numbers',
+    });
+    return;
+  }
+
+  if (!(await getAppConfig(appId))) {
+    res.status(404).json({ error: 'App config not found' });
+    return;
+  }
+
+  try {
+    const saved = await putLaneConfig({
+      appId,
+      ratePerMinute,
+      criticalPerMinute,
+      slaMs: { critical: slaMs.critical as number, standard: slaMs.standard as number, bulk: slaMs.bulk as number },
+    });
+    scheduler.invalidate(appId);
+    res.json({ config: saved });
+  } catch (err) {
+    console.error('[admin/lanes] Error:', err instanceof Error ? err.message : err);
+    res.status(500).json({ error: 'Failed to save lane config' });
+  }
+});
+
 // ---------------------------------------------------------------------------
 // Background jobs
 // ---------------------------------------------------------------------------
@@ -200,6 +267,10 @@ cron.schedule(config.replenishment.cron, async () => {
   try { await runReplenishmentCycle(); } catch (err) { console.error('[replenishment]', err); }
 });
 
+cron.schedule(config.replenishment.cron, async () => {
+  try { await runSponsorRebalance(); } catch (err) { console.error('[sponsors]', err); }
+});
+
 setInterval(async () => {
   try { await runMonitorCycle(); } catch (err) { console.error('[monitor]', err); }
 }, config.monitor.pollIntervalMs);
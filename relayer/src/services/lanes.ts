@@ -0,0 +1,331 @@
+import { Transaction, xdr } from '@stellar/stellar-sdk';
+import { config } from '../config.js';
+import { getLaneConfig } from '../db/tables.js';
+import type { Lane, LaneConfig } from '../db/tables.js';
+
+// ---------------------------------------------------------------------------
+// Priority lanes
+//
+// Relays no longer go out in arrival order. Each one is queued in a lane:
+//   critical — recoveries, freezes, revocations, owner rotations
+//   standard — everything else
+//   bulk     — batches, and anything the app marks as bulk
+// Workers pick lanes by smooth weighted round-robin, so a burst of payouts
+// can't hold a freeze behind it but still gets its share. A job waiting past
+// its lane's maxWaitMs is taken ahead of the weights (starvation protection).
+//
+// Per app: a token bucket (ratePerMinute) for standard/bulk, a separate small
+// budget for critical so an app can't route its traffic through "freeze" to
+// skip the line, and SLA targets per lane that breaches are counted against.
+// ---------------------------------------------------------------------------
+
+export const LANES: readonly Lane[] = ['critical', 'standard', 'bulk'];
+
+const CRITICAL_FUNCTIONS = new Set([
+  'freeze',
+  'unfreeze',
+  'initiate_recovery',
+  'approve_recovery',
+  'execute_recovery',
+  'veto_recovery',
+  'recover_with_email_proof',
+  'update_owner',
+  'cancel_rotation',
+  'cancel_upgrade',
+  'revoke_session',
+  'revoke_all_sessions',
+  'revoke_preauth',
+  'remove_guardian',
+]);
+
+const BULK_FUNCTIONS = new Set(['execute_batch', 'execute_batch_best_effort']);
+
+export const DEFAULT_LANE_CONFIG: Omit<LaneConfig, 'appId' | 'updatedAt'> = {
+  ratePerMinute: 60,
+  criticalPerMinute: 10,
+  slaMs: { critical: 5_000, standard: 30_000, bulk: 300_000 },
+};
+
+const CONFIG_TTL_MS = 60_000;
+const IDLE_RETRY_MS = 250;
+
+function invokedFunctions(tx: Transaction): string[] {
+  const names: string[] = [];
+  for (const op of tx.operations) {
+    if (op.type !== 'invokeHostFunction') continue;
+    if (op.func.switch() !== xdr.HostFunctionType.hostFunctionTypeInvokeContract()) continue;
+    names.push(op.func.invokeContract().functionName().toString());
+  }
+  return names;
+}
+
+// Clients may ask for the bulk lane, never for a faster one than the call gets
+export function classify(tx: Transaction, requested?: Lane): Lane {
+  const names = invokedFunctions(tx);
+  let lane: Lane = 'standard';
+  if (names.length > 0 && names.every((n) => CRITICAL_FUNCTIONS.has(n))) lane = 'critical';
+  else if (names.some((n) => BULK_FUNCTIONS.has(n))) lane = 'bulk';
+
+  if (requested === 'bulk') return 'bulk';
+  return lane;
+}
+
+export function isLane(value: unknown): value is Lane {
+  return typeof value === 'string' && (LANES as readonly string[]).includes(value);
+}
+
+// ---------------------------------------------------------------------------
+// Scheduler
+// ---------------------------------------------------------------------------
+
+export interface LaneSettings {
+  weight: number;
+  maxWaitMs: number;
+}
+
+interface Job {
+  appId: string;
+  lane: Lane;
+  ratePerMinute: number;
+  slaMs: number;
+  enqueuedAt: number;
+  run: () => Promise<unknown>;
+  resolve: (value: unknown) => void;
+  reject: (err: unknown) => void;
+}
+
+interface Bucket {
+  tokens: number;
+  refilledAt: number;
+}
+
+interface LaneCounters {
+  dispatched: number;
+  promoted: number;
+  demoted: number;
+}
+
+export interface AppLaneStats {
+  depth: number;
+  dispatched: number;
+  slaBreaches: number;
+  maxWaitMs: number;
+}
+
+export interface LaneStats {
+  lane: Lane;
+  weight: number;
+  maxWaitMs: number;
+  depth: number;
+  oldestWaitMs: number;
+  dispatched: number;
+  promoted: number;
+  demoted: number;
+}
+
+export class LaneScheduler {
+  private readonly queues: Record<Lane, Job[]> = { critical: [], standard: [], bulk: [] };
+  private readonly credit: Record<Lane, number> = { critical: 0, standard: 0, bulk: 0 };
+  private readonly counters: Record<Lane, LaneCounters> = {
+    critical: { dispatched: 0, promoted: 0, demoted: 0 },
+    standard: { dispatched: 0, promoted: 0, demoted: 0 },
+    bulk: { dispatched: 0, promoted: 0, demoted: 0 },
+  };
+  private readonly appCounters = new Map<string, Record<Lane, Omit<AppLaneStats, 'depth'>>>();
+  private readonly rateBuckets = new Map<string, Bucket>();
+  private readonly criticalBuckets = new Map<string, Bucket>();
+  private readonly configCache = new Map<string, { config: LaneConfig; loadedAt: number }>();
+  private running = 0;
+  private timer: NodeJS.Timeout | null = null;
+
+  constructor(
+    private readonly workers: number,
+    private readonly settings: Record<Lane, LaneSettings>,
+    private readonly loadConfig: (appId: string) => Promise<LaneConfig | null>,
+  ) {}
+
+  // Queue `run` in `lane` and resolve with its result once a worker ran it
+  async submit<T>(appId: string, lane: Lane, run: () => Promise<T>): Promise<T> {
+    const appConfig = await this.appConfig(appId);
+
+    // Critical calls past the app's critical budget queue as standard
+    if (lane === 'critical') {
+      const bucket = this.refill(this.criticalBuckets, appId, appConfig.criticalPerMinute);
+      if (bucket.tokens >= 1) {
+        bucket.tokens -= 1;
+      } else {
+        this.counters.critical.demoted++;
+        lane = 'standard';
+      }
+    }
+
+    return new Promise<T>((resolve, reject) => {
+      this.queues[lane].push({
+        appId,
+        lane,
+        ratePerMinute: appConfig.ratePerMinute,
+        slaMs: appConfig.slaMs[lane],
+        enqueuedAt: Date.now(),
+        run,
+        resolve: resolve as (value: unknown) => void,
+        reject,
+      });
+      this.pump();
+    });
+  }
+
+  async appConfig(appId: string): Promise<LaneConfig> {
+    const cached = this.configCache.get(appId);
+    if (cached && Date.now() - cached.loadedAt < CONFIG_TTL_MS) return cached.config;
+
+    const stored = await this.loadConfig(appId);
+    const appConfig: LaneConfig = stored ?? {
+      appId,
+      ...DEFAULT_LANE_CONFIG,
+      updatedAt: new Date(0).toISOString(),
+    };
+    this.configCache.set(appId, { config: appConfig, loadedAt: Date.now() });
+    return appConfig;
+  }
+
+  // Drop the cached config so an admin update applies to the next relay
+  invalidate(appId: string): void {
+    this.configCache.delete(appId);
+    this.rateBuckets.delete(appId);
+    this.criticalBuckets.delete(appId);
+  }
+
+  stats(): LaneStats[] {
+    const now = Date.now();
+    return LANES.map((lane) => {
+      const oldest = this.queues[lane][0];
+      return {
+        lane,
+        ...this.settings[lane],
+        depth: this.queues[lane].length,
+        oldestWaitMs: oldest ? now - oldest.enqueuedAt : 0,
+        ...this.counters[lane],
+      };
+    });
+  }
+
+  appStats(appId: string): Record<Lane, AppLaneStats> {
+    const counters = this.appCounters.get(appId);
+    const out = {} as Record<Lane, AppLaneStats>;
+    for (const lane of LANES) {
+      out[lane] = {
+        depth: this.queues[lane].filter((job) => job.appId === appId).length,
+        ...(counters?.[lane] ?? { dispatched: 0, slaBreaches: 0, maxWaitMs: 0 }),
+      };
+    }
+    return out;
+  }
+
+  private pump(): void {
+    while (this.running < this.workers) {
+      const job = this.next();
+      if (!job) break;
+      this.dispatch(job);
+    }
+
+    // Whatever is still queued with a worker free is waiting on its app's rate
+    const queued = LANES.some((lane) => this.queues[lane].length > 0);
+    if (queued && this.running < this.workers && !this.timer) {
+      this.timer = setTimeout(() => {
+        this.timer = null;
+        this.pump();
+      }, IDLE_RETRY_MS);
+    }
+  }
+
+  private next(): Job | undefined {
+    const now = Date.now();
+    const ready = LANES.map((lane) => ({ lane, index: this.eligible(lane) }))
+      .filter((entry) => entry.index >= 0);
+    if (ready.length === 0) return undefined;
+
+    // Starvation protection: the job furthest past its lane's max wait first
+    let pick: { lane: Lane; index: number } | null = null;
+    let worst = 0;
+    for (const entry of ready) {
+      const late = now - this.queues[entry.lane][entry.index]!.enqueuedAt
+        - this.settings[entry.lane].maxWaitMs;
+      if (late > worst) {
+        worst = late;
+        pick = entry;
+      }
+    }
+    if (pick) {
+      this.counters[pick.lane].promoted++;
+    } else {
+      // Smooth weighted round-robin over the lanes with a runnable job
+      let total = 0;
+      for (const entry of ready) {
+        const weight = this.settings[entry.lane].weight;
+        this.credit[entry.lane] += weight;
+        total += weight;
+        if (!pick || this.credit[entry.lane] > this.credit[pick.lane]) pick = entry;
+      }
+      this.credit[pick!.lane] -= total;
+    }
+
+    const job = this.queues[pick!.lane].splice(pick!.index, 1)[0]!;
+    if (job.lane !== 'critical') {
+      this.refill(this.rateBuckets, job.appId, job.ratePerMinute).tokens -= 1;
+    }
+    return job;
+  }
+
+  // First job in `lane` its app's rate allows; critical jobs aren't rate limited
+  private eligible(lane: Lane): number {
+    const queue = this.queues[lane];
+    if (lane === 'critical') return queue.length > 0 ? 0 : -1;
+    return queue.findIndex(
+      (job) => this.refill(this.rateBuckets, job.appId, job.ratePerMinute).tokens >= 1,
+    );
+  }
+
+  private refill(buckets: Map<string, Bucket>, appId: string, perMinute: number): Bucket {
+    const now = Date.now();
+    let bucket = buckets.get(appId);
+    if (!bucket) {
+      bucket = { tokens: perMinute, refilledAt: now };
+      buckets.set(appId, bucket);
+    }
+    bucket.tokens = Math.min(perMinute, bucket.tokens + ((now - bucket.refilledAt) / 60_000) * perMinute);
+    bucket.refilledAt = now;
+    return bucket;
+  }
+
+  private dispatch(job: Job): void {
+    const waitMs = Date.now() - job.enqueuedAt;
+    this.running++;
+    this.counters[job.lane].dispatched++;
+
+    let counters = this.appCounters.get(job.appId);
+    if (!counters) {
+      counters = {
+        critical: { dispatched: 0, slaBreaches: 0, maxWaitMs: 0 },
+        standard: { dispatched: 0, slaBreaches: 0, maxWaitMs: 0 },
+        bulk: { dispatched: 0, slaBreaches: 0, maxWaitMs: 0 },
+      };
+      this.appCounters.set(job.appId, counters);
+    }
+    const app = counters[job.lane];
+    app.dispatched++;
+    app.maxWaitMs = Math.max(app.maxWaitMs, waitMs);
+    if (waitMs > job.slaMs) {
+      app.slaBreaches++;
+      console.warn(`[lanes] ${job.appId} ${job.lane} waited ${waitMs}ms (SLA ${job.slaMs}ms)`);
+    }
+
+    job.run()
+      .then(job.resolve, job.reject)
+      .finally(() => {
+        this.running--;
+        this.pump();
+      });
+  }
+}
+
+export const scheduler = new LaneScheduler(config.lanes.workers, config.lanes.settings, getLaneConfig);
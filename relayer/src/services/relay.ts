@@ -2,7 +2,10 @@ import { Transaction } from '@stellar/stellar-sdk';
 import { buildFeeBump } from '../stellar/feebump.js';
 import { submitXdr, getTxByHash } from '../stellar/client.js';
 import { createRelayerTx, updateRelayerTx, getAppConfig, getFundSecret } from '../db/tables.js';
+import type { Lane } from '../db/tables.js';
 import { config } from '../config.js';
+import { classify, scheduler } from './lanes.js';
+import { feeSponsorSecret } from './sponsors.js';
 
 const MAX_OPS = 10;
 
@@ -10,11 +13,14 @@ export interface RelayRequest {
   innerXdr: string;
   appId: string;
   userId?: string;
+  // Only 'bulk' has an effect — the lane otherwise follows the call
+  lane?: Lane;
 }
 
 export interface RelayResult {
   txHash: string;
   relayerTxId: string;
+  lane: Lane;
 }
 
 // C-4: validate the inner XDR before doing anything with it
@@ -40,10 +46,14 @@ function validateInnerXdr(xdr: string): Transaction {
 export async function relay(req: RelayRequest): Promise<RelayResult> {
   // C-4: validate XDR before touching DynamoDB or Stellar
   const innerTx = validateInnerXdr(req.innerXdr);
+  const lane = classify(innerTx, req.lane);
 
   const appConfig = await getAppConfig(req.appId);
   const feeStrategy = appConfig?.feeStrategy ?? 'developer_pays';
-  const fundSecret = appConfig ? await getFundSecret(appConfig) : config.stellar.fundSecret;
+  // Apps with their own fund account pay from it; the rest spread over the sponsor pool
+  const fundSecret = appConfig?.fundAccountEncryptedSecret
+    ? await getFundSecret(appConfig)
+    : await feeSponsorSecret();
 
   const relayerTxId = await createRelayerTx({
     appId: req.appId,
@@ -52,6 +62,13 @@ export async function relay(req: RelayRequest): Promise<RelayResult> {
     feeStrategy,
   });
 
+  const txHash = await scheduler.submit(req.appId, lane, () =>
+    submitWithRetries(relayerTxId, req.innerXdr, fundSecret)
+  );
+  return { txHash, relayerTxId, lane };
+}
+
+async function submitWithRetries(relayerTxId: string, innerXdr: string, fundSecret: string): Promise<string> {
   const maxAttempts = 3;
   let lastError: Error | null = null;
 
@@ -59,7 +76,7 @@ export async function relay(req: RelayRequest): Promise<RelayResult> {
     try {
       await updateRelayerTx(relayerTxId, { status: 'processing', attempts: attempt });
 
-      const feeBump = buildFeeBump(req.innerXdr, fundSecret);
+      const feeBump = buildFeeBump(innerXdr, fundSecret);
       const feeBumpXdr = feeBump.toXDR();
       const feeBumpHash = feeBump.hash().toString('hex');
 
@@ -70,14 +87,14 @@ export async function relay(req: RelayRequest): Promise<RelayResult> {
         const existing = await getTxByHash(feeBumpHash);
         if (existing?.successful) {
           await updateRelayerTx(relayerTxId, { txHash: feeBumpHash, status: 'confirmed' });
-          return { txHash: feeBumpHash, relayerTxId };
+          return feeBumpHash;
         }
       }
 
       const txHash = await submitXdr(feeBumpXdr);
 
       await updateRelayerTx(relayerTxId, { txHash, status: 'confirmed' });
-      return { txHash, relayerTxId };
+      return txHash;
     } catch (err) {
       lastError = err instanceof Error ? err : new Error(String(err));
       console.error(`[relay] Attempt ${attempt}/${maxAttempts} failed:`, lastError.message);
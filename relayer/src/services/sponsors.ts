@@ -0,0 +1,213 @@
+import { randomUUID } from 'node:crypto';
+import {
+  Account,
+  BASE_FEE,
+  Keypair,
+  Operation,
+  Asset,
+  Transaction,
+  TransactionBuilder,
+} from '@stellar/stellar-sdk';
+import { config } from '../config.js';
+import { decrypt } from '../crypto.js';
+import {
+  listChannels,
+  leaseChannel,
+  renewChannelLease,
+  releaseChannelLease,
+} from '../db/tables.js';
+import type { ChannelAccount } from '../db/tables.js';
+import { fetchAccount, getXLMBalance, submitXdr } from '../stellar/client.js';
+import { notify } from './slack.js';
+
+// ---------------------------------------------------------------------------
+// Sponsor pool
+//
+// Channel accounts in `channel_accounts` under the relayer's pool id share
+// the load that used to sit on the single fund account:
+//
+//   - Fee bumps take the next sponsor round-robin. A fee bump consumes no
+//     sequence number, so they don't need a lease and run fully in parallel;
+//     spreading them just keeps any one balance from draining.
+//   - Transactions the relayer sources itself (wallet activations, sweeps)
+//     lease a sponsor for its sequence number. `withSponsor` holds the lease
+//     exactly as long as its callback runs, renewing it in the background,
+//     and always releases it. A crashed instance's leases expire on their own.
+//   - `runSponsorRebalance` tops low sponsors up from the fund account in one
+//     transaction and sweeps any over the max back.
+//
+// With no channel accounts configured everything falls back to the fund
+// account, with sourced transactions serialized in-process.
+// ---------------------------------------------------------------------------
+
+const INSTANCE_ID = randomUUID();
+const POOL_REFRESH_MS = 60_000;
+const LEASE_ATTEMPTS = 3;
+
+export interface Sponsor {
+  channelId: string | null;
+  keypair: Keypair;
+  // Loaded when the lease is taken; TransactionBuilder bumps it per build
+  account: Account;
+}
+
+interface PooledSponsor {
+  channelId: string;
+  keypair: Keypair;
+}
+
+let pool: PooledSponsor[] = [];
+let poolLoadedAt = 0;
+let nextFeeSponsor = 0;
+let fundAccountQueue: Promise<unknown> = Promise.resolve();
+
+async function loadPool(): Promise<PooledSponsor[]> {
+  if (Date.now() - poolLoadedAt < POOL_REFRESH_MS) return pool;
+
+  const channels = await listChannels(config.sponsors.poolId);
+  pool = channels.map((channel: ChannelAccount) => ({
+    channelId: channel.channelId,
+    keypair: Keypair.fromSecret(
+      decrypt(channel.encryptedSecret, channel.encryptionIv, channel.encryptionTag)
+    ),
+  }));
+  poolLoadedAt = Date.now();
+  return pool;
+}
+
+// Fee source for the next fee bump: the pool round-robin, else the fund account
+export async function feeSponsorSecret(): Promise<string> {
+  const sponsors = await loadPool();
+  if (sponsors.length === 0) return config.stellar.fundSecret;
+  const sponsor = sponsors[nextFeeSponsor++ % sponsors.length]!;
+  return sponsor.keypair.secret();
+}
+
+async function loadSponsor(channelId: string | null, keypair: Keypair): Promise<Sponsor> {
+  const data = await fetchAccount(keypair.publicKey());
+  return { channelId, keypair, account: new Account(keypair.publicKey(), data.sequence) };
+}
+
+// Run `fn` holding a sponsor's sequence number
+export async function withSponsor<T>(fn: (sponsor: Sponsor) => Promise<T>): Promise<T> {
+  const sponsors = await loadPool();
+  if (sponsors.length === 0) return withFundAccount(fn);
+
+  // Start at a random sponsor so instances don't all contend for the first one
+  const offset = Math.floor(Math.random() * sponsors.length);
+  for (let attempt = 0; attempt < LEASE_ATTEMPTS; attempt++) {
+    for (let i = 0; i < sponsors.length; i++) {
+      const candidate = sponsors[(offset + i) % sponsors.length]!;
+      if (await leaseChannel(candidate.channelId, INSTANCE_ID, config.sponsors.leaseTtlMs)) {
+        return runLeased(candidate, fn);
+      }
+    }
+    await new Promise((r) => setTimeout(r, 200 * (attempt + 1)));
+  }
+  throw new Error('No sponsor account available');
+}
+
+async function runLeased<T>(leased: PooledSponsor, fn: (sponsor: Sponsor) => Promise<T>): Promise<T> {
+  const renewal = setInterval(() => {
+    renewChannelLease(leased.channelId, INSTANCE_ID, config.sponsors.leaseTtlMs)
+      .then((held) => {
+        if (!held) console.error(`[sponsors] Lost lease on ${leased.channelId}`);
+      })
+      .catch((err) => console.error('[sponsors] Lease renewal failed:', err instanceof Error ? err.message : err));
+  }, config.sponsors.leaseTtlMs / 3);
+
+  try {
+    return await fn(await loadSponsor(leased.channelId, leased.keypair));
+  } finally {
+    clearInterval(renewal);
+    await releaseChannelLease(leased.channelId, INSTANCE_ID);
+  }
+}
+
+// Without a pool, the fund account's sequence is shared — one at a time
+function withFundAccount<T>(fn: (sponsor: Sponsor) => Promise<T>): Promise<T> {
+  const run = fundAccountQueue.then(async () =>
+    fn(await loadSponsor(null, Keypair.fromSecret(config.stellar.fundSecret)))
+  );
+  fundAccountQueue = run.catch(() => undefined);
+  return run;
+}
+
+// Build, sign and submit a transaction sourced by a leased sponsor. A stale
+// sequence (another writer used the account) reloads it and rebuilds once.
+export async function submitAsSponsor(build: (account: Account) => Transaction): Promise<string> {
+  return withSponsor(async (sponsor) => {
+    for (let attempt = 1; ; attempt++) {
+      const tx = build(sponsor.account);
+      tx.sign(sponsor.keypair);
+      try {
+        return await submitXdr(tx.toXDR());
+      } catch (err) {
+        if (attempt > 1 || !(err instanceof Error) || !err.message.includes('tx_bad_seq')) throw err;
+        sponsor.account = (await loadSponsor(sponsor.channelId, sponsor.keypair)).account;
+      }
+    }
+  });
+}
+
+// ---------------------------------------------------------------------------
+// Rebalancing
+// ---------------------------------------------------------------------------
+
+export async function runSponsorRebalance(): Promise<void> {
+  poolLoadedAt = 0;
+  const sponsors = await loadPool();
+  if (sponsors.length === 0) return;
+
+  const { minXlm, targetXlm, maxXlm } = config.sponsors;
+  const fundKeypair = Keypair.fromSecret(config.stellar.fundSecret);
+  const balances = await Promise.all(
+    sponsors.map(async (s) => ({ sponsor: s, balance: await getXLMBalance(s.keypair.publicKey()) }))
+  );
+
+  // Top-ups go out as one payment per sponsor in a single fund account tx
+  const low = balances.filter((b) => b.balance < minXlm).slice(0, 100);
+  if (low.length > 0) {
+    try {
+      const data = await fetchAccount(fundKeypair.publicKey());
+      const builder = new TransactionBuilder(new Account(fundKeypair.publicKey(), data.sequence), {
+        fee: BASE_FEE,
+        networkPassphrase: config.stellar.networkPassphrase,
+      });
+      for (const { sponsor, balance } of low) {
+        builder.addOperation(Operation.payment({
+          destination: sponsor.keypair.publicKey(),
+          asset: Asset.native(),
+          amount: (targetXlm - balance).toFixed(7),
+        }));
+      }
+      const tx = builder.setTimeout(30).build();
+      tx.sign(fundKeypair);
+      await submitXdr(tx.toXDR());
+      console.log(`[sponsors] Topped up ${low.length} sponsor account(s)`);
+    } catch (err) {
+      console.error('[sponsors] Top-up failed:', err instanceof Error ? err.message : err);
+      await notify('Sponsor pool top-up failed. Check server logs.', 'critical');
+    }
+  }
+
+  // Sweeps use the sponsor's own sequence, so they take its lease
+  for (const { sponsor, balance } of balances.filter((b) => b.balance > maxXlm)) {
+    if (!(await leaseChannel(sponsor.channelId, INSTANCE_ID, config.sponsors.leaseTtlMs))) continue;
+    await runLeased(sponsor, async ({ account, keypair }) => {
+      const tx = new TransactionBuilder(account, {
+        fee: BASE_FEE,
+        networkPassphrase: config.stellar.networkPassphrase,
+      })
+        .addOperation(Operation.payment({
+          destination: fundKeypair.publicKey(),
+          asset: Asset.native(),
+          amount: (balance - targetXlm).toFixed(7),
+        }))
+        .setTimeout(30)
+        .build();
+      tx.sign(keypair);
+      await submitXdr(tx.toXDR());
+    }).catch((err) => console.error('[sponsors] Sweep failed:', err instanceof Error ? err.message : err));
+  }
+}
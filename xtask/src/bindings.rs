@@ -0,0 +1,360 @@
+// ---------------------------------------------------------------------------
+// `cargo xtask bindings`
+//
+// Generates the `accesly-bindings` crate: for every workspace contract, a
+// `Client` over the SDK-generated contract client whose methods call the
+// `try_` variants and return `Result<T, Error>`, where `Error` is
+// `acceslyinterface::Error` over the contract's own error enum. Tests and
+// tooling then match on `Error::Contract(..)` variants instead of raw
+// `Error(Contract, #n)` values.
+//
+// Signatures are read from the contract spec in each built WASM, so the
+// bindings follow exactly what the contract exports. `--check` fails when
+// the checked-in files are out of date. The spec is full XDR, unlike the
+// few fixed fields `onchain` reads, so it is decoded with `soroban-spec`.
+// ---------------------------------------------------------------------------
+
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use clap::Args;
+use stellar_xdr::curr::{ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef};
+
+use crate::verify_build::build;
+
+const OUT_DIR: &str = "crates/accesly-bindings/src";
+
+/// A workspace contract and the module its bindings go in
+pub struct Contract {
+    pub package: &'static str,
+    pub module: &'static str,
+    /// Client type the SDK generates for the contract
+    pub client: &'static str,
+}
+
+pub const CONTRACTS: [Contract; 7] = [
+    Contract {
+        package: "accountAbstraction",
+        module: "wallet",
+        client: "WalletContractClient",
+    },
+    Contract {
+        package: "walletFactory",
+        module: "wallet_factory",
+        client: "WalletFactoryContractClient",
+    },
+    Contract {
+        package: "walletRegistry",
+        module: "wallet_registry",
+        client: "WalletRegistryContractClient",
+    },
+    Contract {
+        package: "kycAttestation",
+        module: "kyc_attestation",
+        client: "KycAttestationContractClient",
+    },
+    Contract {
+        package: "atomicSwap",
+        module: "atomic_swap",
+        client: "AtomicSwapContractClient",
+    },
+    Contract {
+        package: "paymaster",
+        module: "paymaster",
+        client: "PaymasterContractClient",
+    },
+    Contract {
+        package: "oracleAdapter",
+        module: "oracle_adapter",
+        client: "OracleAdapterContractClient",
+    },
+];
+
+#[derive(Args, Debug)]
+pub struct BindingsArgs {
+    /// Fail if the checked-in bindings differ from what would be generated
+    #[arg(long)]
+    pub check: bool,
+    /// Read `<package>.wasm` from this directory instead of building
+    #[arg(long)]
+    pub wasm_dir: Option<PathBuf>,
+    /// Toolchain passed to cargo as `+<toolchain>`
+    #[arg(long)]
+    pub toolchain: Option<String>,
+}
+
+pub fn run(args: BindingsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+
+    let mut stale = Vec::new();
+    for contract in &CONTRACTS {
+        let wasm_path = match &args.wasm_dir {
+            Some(dir) => dir.join(format!("{}.wasm", contract.package)),
+            None => build(&workspace, contract.package, args.toolchain.as_deref())?,
+        };
+        let wasm =
+            std::fs::read(&wasm_path).map_err(|e| format!("{}: {e}", wasm_path.display()))?;
+        let spec = soroban_spec::read::from_wasm(&wasm)
+            .map_err(|e| format!("{}: {e}", wasm_path.display()))?;
+        let source = rustfmt(&render(contract, &spec)?)?;
+
+        let path = workspace
+            .join(OUT_DIR)
+            .join(format!("{}.rs", contract.module));
+        if args.check {
+            if std::fs::read_to_string(&path).ok().as_deref() != Some(source.as_str()) {
+                stale.push(path.display().to_string());
+            }
+        } else {
+            std::fs::write(&path, source)?;
+            println!("wrote {}", path.display());
+        }
+    }
+
+    if !stale.is_empty() {
+        return Err(format!(
+            "out of date, rerun `cargo xtask bindings`: {}",
+            stale.join(", ")
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Names a generated module imports from `soroban_sdk` and the contract
+#[derive(Default)]
+struct Imports {
+    sdk: BTreeSet<String>,
+    contract: BTreeSet<String>,
+}
+
+/// Render the bindings module for `contract` from its spec
+pub fn render(contract: &Contract, spec: &[ScSpecEntry]) -> Result<String, String> {
+    let package = contract.package;
+    let error_enums: Vec<String> = spec
+        .iter()
+        .filter_map(|entry| match entry {
+            ScSpecEntry::UdtErrorEnumV0(e) => Some(e.name.to_utf8_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    let error = match error_enums.as_slice() {
+        [] => None,
+        [name] => Some(name.clone()),
+        _ => return Err(format!("{package}: more than one error enum")),
+    };
+
+    let mut imports = Imports::default();
+    imports
+        .sdk
+        .extend(["Address".to_string(), "Env".to_string()]);
+    imports.contract.insert(contract.client.to_string());
+    let (mut uses_try, mut uses_try_host) = (false, false);
+
+    let mut methods = Vec::new();
+    for entry in spec {
+        let ScSpecEntry::FunctionV0(function) = entry else {
+            continue;
+        };
+        let name = function.name.0.to_utf8_string_lossy();
+        // `__check_auth` and other host hooks aren't callable
+        if name.starts_with("__") {
+            continue;
+        }
+        let (method, declared) = render_method(package, &name, function, &error, &mut imports)?;
+        if declared {
+            uses_try = true;
+        } else {
+            uses_try_host = true;
+        }
+        methods.push((name, method));
+    }
+    // Spec order follows link order; sort so rebuilds don't reshuffle
+    methods.sort();
+
+    let error_type = match &error {
+        Some(name) => format!("{package}::{name}"),
+        None => "core::convert::Infallible".to_string(),
+    };
+    let try_fns: Vec<&str> = [(uses_try, "from_try"), (uses_try_host, "from_try_host")]
+        .into_iter()
+        .filter_map(|(used, name)| used.then_some(name))
+        .collect();
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "// Generated by `cargo xtask bindings` from the {package} contract spec.\n\
+         // Do not edit; rerun the task after changing the contract.\n\n"
+    ));
+    if !try_fns.is_empty() {
+        out.push_str(&use_line(
+            "acceslyinterface",
+            try_fns.iter().map(|s| s.to_string()),
+        ));
+    }
+    out.push_str(&use_line("soroban_sdk", imports.sdk.into_iter()));
+    out.push('\n');
+    out.push_str(&use_line(package, imports.contract.into_iter()));
+    out.push('\n');
+    out.push_str(&format!(
+        "pub type Error = acceslyinterface::Error<{error_type}>;\n\n\
+         /// `{client}` returning `Result<_, Error>`\n\
+         pub struct Client<'a>(pub {client}<'a>);\n\n\
+         #[allow(clippy::too_many_arguments, clippy::type_complexity)]\n\
+         impl Client<'_> {{\n    \
+             pub fn new(env: &Env, address: &Address) -> Self {{\n        \
+                 Self({client}::new(env, address))\n    \
+             }}\n",
+        client = contract.client,
+    ));
+    for (_, method) in methods {
+        out.push('\n');
+        out.push_str(&method);
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// One wrapper method, and whether it returns the contract's declared error
+fn render_method(
+    package: &str,
+    name: &str,
+    function: &ScSpecFunctionV0,
+    error: &Option<String>,
+    imports: &mut Imports,
+) -> Result<(String, bool), String> {
+    let context = |e: String| format!("{package}::{name}: {e}");
+
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for input in function.inputs.iter() {
+        let arg = input.name.to_utf8_string_lossy();
+        let ty = rust_type(&input.type_, imports).map_err(context)?;
+        params.push(format!("{arg}: &{ty}"));
+        args.push(arg);
+    }
+
+    let (ok, declared) = match function.outputs.first() {
+        None => ("()".to_string(), false),
+        Some(ScSpecTypeDef::Result(result)) => {
+            // The spec records an enum named `Error` as the builtin error
+            // type, so that means the contract's enum when it declares one
+            let declared = match result.error_type.as_ref() {
+                ScSpecTypeDef::Udt(udt) if Some(udt.name.to_utf8_string_lossy()) == *error => true,
+                ScSpecTypeDef::Error => error.is_some(),
+                _ => return Err(context("unexpected error type".to_string())),
+            };
+            (
+                rust_type(&result.ok_type, imports).map_err(context)?,
+                declared,
+            )
+        }
+        Some(output) => (rust_type(output, imports).map_err(context)?, false),
+    };
+
+    let mut out = String::new();
+    for line in function.doc.to_utf8_string_lossy().lines() {
+        out.push_str(&format!(
+            "    ///{}{line}\n",
+            if line.is_empty() { "" } else { " " }
+        ));
+    }
+    out.push_str(&format!(
+        "    pub fn {name}(&self{}) -> Result<{ok}, Error> {{\n",
+        params.iter().map(|p| format!(", {p}")).collect::<String>()
+    ));
+    let try_fn = if declared {
+        "from_try"
+    } else {
+        "from_try_host"
+    };
+    out.push_str(&format!(
+        "        {try_fn}(self.0.try_{name}({}))\n    }}\n",
+        args.join(", ")
+    ));
+    Ok((out, declared))
+}
+
+/// The Rust type the SDK client uses for a spec type
+fn rust_type(ty: &ScSpecTypeDef, imports: &mut Imports) -> Result<String, String> {
+    Ok(match ty {
+        ScSpecTypeDef::Val => sdk(imports, "Val"),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Void => "()".to_string(),
+        ScSpecTypeDef::U32 => "u32".to_string(),
+        ScSpecTypeDef::I32 => "i32".to_string(),
+        ScSpecTypeDef::U64 => "u64".to_string(),
+        ScSpecTypeDef::I64 => "i64".to_string(),
+        ScSpecTypeDef::U128 => "u128".to_string(),
+        ScSpecTypeDef::I128 => "i128".to_string(),
+        ScSpecTypeDef::U256 => sdk(imports, "U256"),
+        ScSpecTypeDef::I256 => sdk(imports, "I256"),
+        ScSpecTypeDef::Bytes => sdk(imports, "Bytes"),
+        ScSpecTypeDef::String => sdk(imports, "String"),
+        ScSpecTypeDef::Symbol => sdk(imports, "Symbol"),
+        ScSpecTypeDef::Address => sdk(imports, "Address"),
+        ScSpecTypeDef::BytesN(bytes) => format!("{}<{}>", sdk(imports, "BytesN"), bytes.n),
+        ScSpecTypeDef::Option(option) => {
+            format!("Option<{}>", rust_type(&option.value_type, imports)?)
+        }
+        ScSpecTypeDef::Vec(vec) => {
+            let element = rust_type(&vec.element_type, imports)?;
+            format!("{}<{element}>", sdk(imports, "Vec"))
+        }
+        ScSpecTypeDef::Map(map) => {
+            let key = rust_type(&map.key_type, imports)?;
+            let value = rust_type(&map.value_type, imports)?;
+            format!("{}<{key}, {value}>", sdk(imports, "Map"))
+        }
+        ScSpecTypeDef::Tuple(tuple) => {
+            let types = tuple
+                .value_types
+                .iter()
+                .map(|ty| rust_type(ty, imports))
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("({})", types.join(", "))
+        }
+        ScSpecTypeDef::Udt(udt) => {
+            let name = udt.name.to_utf8_string_lossy();
+            imports.contract.insert(name.clone());
+            name
+        }
+        other => return Err(format!("unsupported spec type {}", other.name())),
+    })
+}
+
+fn sdk(imports: &mut Imports, name: &str) -> String {
+    imports.sdk.insert(name.to_string());
+    name.to_string()
+}
+
+/// `use krate::{a, b};`
+fn use_line(krate: &str, names: impl Iterator<Item = String>) -> String {
+    let names: Vec<String> = names.collect();
+    match names.as_slice() {
+        [name] => format!("use {krate}::{name};\n"),
+        _ => format!("use {krate}::{{{}}};\n", names.join(", ")),
+    }
+}
+
+/// Format generated source the way the rest of the workspace is
+fn rustfmt(source: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("running rustfmt: {e}"))?;
+    child
+        .stdin
+        .take()
+        .ok_or("rustfmt stdin")?
+        .write_all(source.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err("rustfmt failed on the generated bindings".into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
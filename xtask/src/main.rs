@@ -0,0 +1,53 @@
+// ---------------------------------------------------------------------------
+// xtask
+//
+// Repository maintenance tasks, run with `cargo xtask <command>`. Kept out of
+// accesly-cli because these operate on the source tree rather than on
+// deployed wallets.
+// ---------------------------------------------------------------------------
+
+use clap::{Parser, Subcommand};
+
+mod bindings;
+mod manifest;
+mod onchain;
+mod verify_build;
+mod wasm_size;
+
+#[derive(Parser, Debug)]
+#[command(name = "xtask", about = "Accesly repository tasks")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rebuild a contract reproducibly and compare it with deployed instances
+    VerifyBuild(verify_build::VerifyBuildArgs),
+    /// Generate the Result-returning contract bindings in accesly-bindings
+    Bindings(bindings::BindingsArgs),
+    /// Derive a wallet release's capability manifest and sign it
+    Manifest(manifest::ManifestArgs),
+    /// Check the browser build of accesly-wasm against its size budget
+    WasmSize(wasm_size::WasmSizeArgs),
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::VerifyBuild(args) => verify_build::run(args),
+        Command::Bindings(args) => bindings::run(args),
+        Command::Manifest(args) => manifest::run(args),
+        Command::WasmSize(args) => wasm_size::run(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,207 @@
+// ---------------------------------------------------------------------------
+// `cargo xtask manifest`
+//
+// Derives a wallet release's capability manifest (see
+// `acceslyinterface::manifest`) from its WASM and signs it with the build
+// key, for the release pipeline to publish through the factory's
+// `publish_manifest`. Entry points and error codes come from the contract
+// spec. Events are the codes in `events::ALL` whose name the WASM holds, as
+// it does for every code it publishes; a code that only shows up inside a
+// longer string gets listed too. Policies are those in `POLICIES` whose
+// entry point the WASM exports.
+//
+// What's signed is `accesly_messages::manifest_message` over the manifest's
+// XDR: the `ScVal` map the contract type encodes to, built here with
+// `stellar-xdr`, so its layout is pinned by a test.
+// ---------------------------------------------------------------------------
+
+use std::path::{Path, PathBuf};
+
+use accesly_client::recovery_kit::hex;
+use acceslyinterface::events;
+use acceslyinterface::manifest::{MAX_MANIFEST_ITEMS, POLICIES};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Args;
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{Limits, ScMap, ScMapEntry, ScSpecEntry, ScSymbol, ScVal, ScVec, WriteXdr};
+
+use crate::verify_build::build;
+
+#[derive(Args, Debug)]
+pub struct ManifestArgs {
+    /// Contract package to build
+    #[arg(long, default_value = "accountAbstraction")]
+    pub package: String,
+    /// Describe this WASM instead of building
+    #[arg(long)]
+    pub wasm: Option<PathBuf>,
+    /// Toolchain passed to cargo as `+<toolchain>`
+    #[arg(long)]
+    pub toolchain: Option<String>,
+    /// Build key: 32-byte ed25519 seed, hex
+    #[arg(long, env = "ACCESLY_MANIFEST_KEY", hide_env_values = true)]
+    pub signing_key: String,
+    /// Write the signed manifest here instead of stdout
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+/// What a wallet version supports, as `acceslyinterface::Manifest` holds it
+#[derive(Debug, Default, PartialEq)]
+pub struct CapabilityManifest {
+    pub entrypoints: Vec<String>,
+    pub errors: Vec<u32>,
+    pub events: Vec<String>,
+    pub policies: Vec<String>,
+}
+
+pub fn run(args: ManifestArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let wasm_path = match &args.wasm {
+        Some(path) => path.clone(),
+        None => build(&workspace, &args.package, args.toolchain.as_deref())?,
+    };
+    let wasm = std::fs::read(&wasm_path).map_err(|e| format!("{}: {e}", wasm_path.display()))?;
+    let spec = soroban_spec::read::from_wasm(&wasm)
+        .map_err(|e| format!("{}: {e}", wasm_path.display()))?;
+
+    let manifest = derive(&wasm, &spec);
+    let xdr = manifest_xdr(&manifest)?;
+    let wasm_hash: [u8; 32] = Sha256::digest(&wasm).into();
+    let key = signing_key(&args.signing_key)?;
+    let signature = sign(&key, &wasm_hash, &xdr);
+
+    let signed = json!({
+        "wasm_hash": hex(&wasm_hash),
+        "manifest": {
+            "entrypoints": manifest.entrypoints,
+            "errors": manifest.errors,
+            "events": manifest.events,
+            "policies": manifest.policies,
+        },
+        "manifest_xdr": STANDARD.encode(&xdr),
+        "signer": hex(key.verifying_key().as_bytes()),
+        "signature": hex(&signature),
+    });
+    let signed = serde_json::to_string_pretty(&signed)?;
+    match &args.out {
+        Some(path) => {
+            std::fs::write(path, signed)?;
+            println!("wrote {}", path.display());
+        }
+        None => println!("{signed}"),
+    }
+    Ok(())
+}
+
+/// The manifest of `wasm`, whose contract spec is `spec`
+pub fn derive(wasm: &[u8], spec: &[ScSpecEntry]) -> CapabilityManifest {
+    let mut manifest = CapabilityManifest::default();
+    for entry in spec {
+        match entry {
+            ScSpecEntry::FunctionV0(function) => {
+                let name = function.name.to_utf8_string_lossy();
+                if !name.starts_with("__") {
+                    manifest.entrypoints.push(name);
+                }
+            }
+            ScSpecEntry::UdtErrorEnumV0(errors)
+                if errors.name.to_utf8_string_lossy() == "Error" =>
+            {
+                manifest
+                    .errors
+                    .extend(errors.cases.iter().map(|case| case.value));
+            }
+            _ => {}
+        }
+    }
+
+    manifest.events = events::ALL
+        .iter()
+        .filter(|code| contains(wasm, code.as_bytes()))
+        .map(|code| code.to_string())
+        .collect();
+    manifest.policies = POLICIES
+        .iter()
+        .filter(|(_, entrypoint)| manifest.entrypoints.iter().any(|e| e == entrypoint))
+        .map(|(policy, _)| policy.to_string())
+        .collect();
+    manifest
+}
+
+/// XDR of `manifest` as the contract type, the map sorted by field name
+pub fn manifest_xdr(manifest: &CapabilityManifest) -> Result<Vec<u8>, String> {
+    let symbols = |names: &[String]| -> Result<ScVal, String> {
+        let symbols = names
+            .iter()
+            .map(|name| symbol(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        list(symbols)
+    };
+    let errors = list(
+        manifest
+            .errors
+            .iter()
+            .map(|&code| ScVal::U32(code))
+            .collect(),
+    )?;
+
+    let entries = vec![
+        (symbol("entrypoints")?, symbols(&manifest.entrypoints)?),
+        (symbol("errors")?, errors),
+        (symbol("events")?, symbols(&manifest.events)?),
+        (symbol("policies")?, symbols(&manifest.policies)?),
+    ];
+    let map = entries
+        .into_iter()
+        .map(|(key, val)| ScMapEntry { key, val })
+        .collect::<Vec<_>>();
+    let map = ScVal::Map(Some(ScMap(map.try_into().map_err(|_| "manifest map")?)));
+    map.to_xdr(Limits::none()).map_err(|e| e.to_string())
+}
+
+/// `key`'s signature over the manifest message
+pub fn sign(key: &SigningKey, wasm_hash: &[u8; 32], manifest_xdr: &[u8]) -> [u8; 64] {
+    let mut message = Vec::new();
+    accesly_messages::manifest_message(&mut message, wasm_hash, manifest_xdr);
+    key.sign(&message).to_bytes()
+}
+
+fn signing_key(seed: &str) -> Result<SigningKey, String> {
+    let seed = seed.trim();
+    if seed.len() != 64 {
+        return Err("signing key must be 32 bytes of hex".into());
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&seed[2 * i..2 * i + 2], 16)
+            .map_err(|_| "signing key must be 32 bytes of hex")?;
+    }
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+fn symbol(name: &str) -> Result<ScVal, String> {
+    let name = name
+        .try_into()
+        .map_err(|_| format!("{name}: not a symbol"))?;
+    Ok(ScVal::Symbol(ScSymbol(name)))
+}
+
+fn list(items: Vec<ScVal>) -> Result<ScVal, String> {
+    if items.len() > MAX_MANIFEST_ITEMS as usize {
+        return Err(format!(
+            "{} items, more than a manifest holds ({MAX_MANIFEST_ITEMS})",
+            items.len()
+        ));
+    }
+    let items = items.try_into().map_err(|_| "manifest list")?;
+    Ok(ScVal::Vec(Some(ScVec(items))))
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
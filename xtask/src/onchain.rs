@@ -0,0 +1,154 @@
+// ---------------------------------------------------------------------------
+// On-chain contract code lookup
+//
+// Reads a contract instance's executable WASM hash, and the WASM itself,
+// through `getLedgerEntries`. The two ledger keys and the few fields needed
+// from each entry are fixed-layout XDR, so they are encoded and read by hand
+// instead of pulling a full XDR crate into the build tooling.
+// ---------------------------------------------------------------------------
+
+use accesly_client::strkey::parse_contract;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+const LEDGER_ENTRY_CONTRACT_DATA: u32 = 6;
+const LEDGER_ENTRY_CONTRACT_CODE: u32 = 7;
+const SC_ADDRESS_CONTRACT: u32 = 1;
+const SCV_CONTRACT_INSTANCE: u32 = 19;
+const SCV_LEDGER_KEY_CONTRACT_INSTANCE: u32 = 20;
+const DURABILITY_PERSISTENT: u32 = 1;
+const EXECUTABLE_WASM: u32 = 0;
+
+#[derive(Debug)]
+pub struct OnchainError(pub String);
+
+impl std::fmt::Display for OnchainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for OnchainError {}
+
+/// Decode a `C...` contract address into its 32-byte id
+pub fn decode_contract_id(strkey: &str) -> Result<[u8; 32], OnchainError> {
+    parse_contract(strkey).map_err(|e| OnchainError(format!("{strkey}: {e}")))
+}
+
+/// `LedgerKey::ContractData` of a contract's instance entry
+pub fn instance_ledger_key(contract_id: &[u8; 32]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(48);
+    key.extend_from_slice(&LEDGER_ENTRY_CONTRACT_DATA.to_be_bytes());
+    key.extend_from_slice(&SC_ADDRESS_CONTRACT.to_be_bytes());
+    key.extend_from_slice(contract_id);
+    key.extend_from_slice(&SCV_LEDGER_KEY_CONTRACT_INSTANCE.to_be_bytes());
+    key.extend_from_slice(&DURABILITY_PERSISTENT.to_be_bytes());
+    key
+}
+
+/// `LedgerKey::ContractCode` of an uploaded WASM
+pub fn code_ledger_key(wasm_hash: &[u8; 32]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(36);
+    key.extend_from_slice(&LEDGER_ENTRY_CONTRACT_CODE.to_be_bytes());
+    key.extend_from_slice(wasm_hash);
+    key
+}
+
+/// WASM hash out of a contract instance's `LedgerEntryData`
+pub fn wasm_hash_from_instance(entry: &[u8]) -> Result<[u8; 32], OnchainError> {
+    let mut reader = XdrReader::new(entry);
+    reader.expect(LEDGER_ENTRY_CONTRACT_DATA, "contract data entry")?;
+    reader.expect(0, "extension point")?;
+    reader.expect(SC_ADDRESS_CONTRACT, "contract address")?;
+    reader.take(32)?;
+    reader.expect(SCV_LEDGER_KEY_CONTRACT_INSTANCE, "instance key")?;
+    reader.expect(DURABILITY_PERSISTENT, "persistent durability")?;
+    reader.expect(SCV_CONTRACT_INSTANCE, "contract instance")?;
+    reader
+        .expect(EXECUTABLE_WASM, "wasm executable")
+        .map_err(|_| {
+            OnchainError("contract is not backed by wasm (stellar asset contract?)".into())
+        })?;
+    Ok(reader.take(32)?.try_into().unwrap())
+}
+
+/// Code out of a contract code `LedgerEntryData`
+pub fn wasm_from_code_entry(entry: &[u8]) -> Result<Vec<u8>, OnchainError> {
+    let mut reader = XdrReader::new(entry);
+    reader.expect(LEDGER_ENTRY_CONTRACT_CODE, "contract code entry")?;
+    match reader.u32()? {
+        0 => {}
+        // V1 carries an extension point and the code's cost inputs
+        // (extension point plus ten u32 counters)
+        1 => {
+            reader.take(4 + 4 + 10 * 4)?;
+        }
+        v => return Err(OnchainError(format!("unknown contract code extension {v}"))),
+    }
+    reader.take(32)?;
+    let len = reader.u32()? as usize;
+    Ok(reader.take(len)?.to_vec())
+}
+
+struct XdrReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> XdrReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], OnchainError> {
+        if n > self.data.len() {
+            return Err(OnchainError("ledger entry is truncated".into()));
+        }
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, OnchainError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn expect(&mut self, value: u32, what: &str) -> Result<(), OnchainError> {
+        if self.u32()? != value {
+            return Err(OnchainError(format!(
+                "unexpected ledger entry layout: expected {what}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Fetch one ledger entry's `LedgerEntryData`, or None if it doesn't exist
+pub fn get_ledger_entry(rpc_url: &str, key: &[u8]) -> Result<Option<Vec<u8>>, OnchainError> {
+    let rpc_err = |e: String| OnchainError(format!("rpc error: {e}"));
+
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getLedgerEntries",
+        "params": { "keys": [STANDARD.encode(key)] },
+    });
+    let response: Value = ureq::post(rpc_url)
+        .send_json(body)
+        .map_err(|e| rpc_err(e.to_string()))?
+        .into_json()
+        .map_err(|e| rpc_err(e.to_string()))?;
+    if let Some(err) = response.get("error") {
+        return Err(rpc_err(err.to_string()));
+    }
+
+    let entry = response
+        .pointer("/result/entries/0/xdr")
+        .and_then(Value::as_str);
+    match entry {
+        None => Ok(None),
+        Some(xdr) => STANDARD
+            .decode(xdr)
+            .map(Some)
+            .map_err(|e| rpc_err(e.to_string())),
+    }
+}
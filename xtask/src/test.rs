@@ -0,0 +1,280 @@
+// src/test.rs
+
+use ed25519_dalek::{Signature, SigningKey, Verifier};
+use stellar_xdr::curr::{
+    ScSpecEntry, ScSpecFunctionInputV0, ScSpecFunctionV0, ScSpecTypeBytesN, ScSpecTypeDef,
+    ScSpecTypeResult, ScSpecUdtErrorEnumCaseV0, ScSpecUdtErrorEnumV0,
+};
+
+use crate::bindings::{render, CONTRACTS};
+use crate::manifest::{derive, manifest_xdr, sign, CapabilityManifest};
+use crate::onchain::{
+    decode_contract_id, instance_ledger_key, wasm_from_code_entry, wasm_hash_from_instance,
+};
+use crate::verify_build::describe_drift;
+use crate::wasm_size::{check_budget, gzip_len};
+
+const CONTRACT: &str = "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+fn meta(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+    entries
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn be(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+// ============================================================================
+// CONTRACT ID TESTS
+// ============================================================================
+
+#[test]
+fn test_decode_contract_id() {
+    let id = decode_contract_id(CONTRACT).unwrap();
+    assert_ne!(id, [0u8; 32]);
+
+    // Checksum catches a changed character
+    let tampered = CONTRACT.replacen('D', "E", 1);
+    assert!(decode_contract_id(&tampered).is_err());
+    // Account keys are not contracts
+    assert!(
+        decode_contract_id("GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN").is_err()
+    );
+    assert!(decode_contract_id("not a key").is_err());
+}
+
+// ============================================================================
+// LEDGER ENTRY TESTS
+// ============================================================================
+
+#[test]
+fn test_wasm_hash_from_instance_entry() {
+    let id = [3u8; 32];
+    let key = instance_ledger_key(&id);
+    assert_eq!(key.len(), 48);
+
+    // Entry data: type, extension, then the key fields, then the value
+    let mut entry = be(&[6, 0]);
+    entry.extend_from_slice(&key[4..]);
+    entry.extend_from_slice(&be(&[19, 0]));
+    entry.extend_from_slice(&[9u8; 32]);
+    entry.extend_from_slice(&be(&[0])); // no storage
+    assert_eq!(wasm_hash_from_instance(&entry).unwrap(), [9u8; 32]);
+
+    // Stellar asset contracts have no wasm
+    let mut sac = entry.clone();
+    sac[56..60].copy_from_slice(&1u32.to_be_bytes());
+    assert!(wasm_hash_from_instance(&sac).is_err());
+    assert!(wasm_hash_from_instance(&entry[..70]).is_err());
+}
+
+#[test]
+fn test_wasm_from_code_entry() {
+    let code = b"\0asm\x01\0\0\0x".to_vec();
+    for ext in [be(&[0]), [be(&[1, 0, 0]), vec![0u8; 40]].concat()] {
+        let mut entry = be(&[7]);
+        entry.extend_from_slice(&ext);
+        entry.extend_from_slice(&[5u8; 32]);
+        entry.extend_from_slice(&be(&[code.len() as u32]));
+        entry.extend_from_slice(&code);
+        entry.extend_from_slice(&[0u8; 3]);
+        assert_eq!(wasm_from_code_entry(&entry).unwrap(), code);
+    }
+}
+
+// ============================================================================
+// DRIFT REPORT TESTS
+// ============================================================================
+
+#[test]
+fn test_describe_drift() {
+    let local = meta(&[("rsver", "1.84.0"), ("rssdkver", "22.0.0#abc")]);
+    let deployed = meta(&[
+        ("rsver", "1.81.0"),
+        ("rssdkver", "22.0.0#abc"),
+        ("extra", "x"),
+    ]);
+
+    assert_eq!(
+        describe_drift(&local, &deployed),
+        vec![
+            "extra: local - / deployed x".to_string(),
+            "rsver: local 1.84.0 / deployed 1.81.0".to_string(),
+        ]
+    );
+    assert_eq!(
+        describe_drift(&local, &local),
+        vec!["same metadata; source or dependencies differ".to_string()]
+    );
+}
+
+// ============================================================================
+// BINDINGS TESTS
+// ============================================================================
+
+fn spec_fn(name: &str, inputs: &[(&str, ScSpecTypeDef)], output: ScSpecTypeDef) -> ScSpecEntry {
+    ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+        doc: "".try_into().unwrap(),
+        name: name.try_into().unwrap(),
+        inputs: inputs
+            .iter()
+            .map(|(arg, ty)| ScSpecFunctionInputV0 {
+                doc: "".try_into().unwrap(),
+                name: (*arg).try_into().unwrap(),
+                type_: ty.clone(),
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap(),
+        outputs: vec![output].try_into().unwrap(),
+    })
+}
+
+#[test]
+fn test_render_bindings() {
+    let spec = [
+        spec_fn(
+            "remove_device",
+            &[
+                (
+                    "public_key",
+                    ScSpecTypeDef::BytesN(ScSpecTypeBytesN { n: 32 }),
+                ),
+                (
+                    "signature",
+                    ScSpecTypeDef::BytesN(ScSpecTypeBytesN { n: 64 }),
+                ),
+            ],
+            ScSpecTypeDef::Result(Box::new(ScSpecTypeResult {
+                ok_type: Box::new(ScSpecTypeDef::Void),
+                error_type: Box::new(ScSpecTypeDef::Error),
+            })),
+        ),
+        spec_fn("get_nonce", &[], ScSpecTypeDef::U64),
+        spec_fn("__check_auth", &[], ScSpecTypeDef::Void),
+        ScSpecEntry::UdtErrorEnumV0(ScSpecUdtErrorEnumV0 {
+            doc: "".try_into().unwrap(),
+            lib: "".try_into().unwrap(),
+            name: "Error".try_into().unwrap(),
+            cases: vec![].try_into().unwrap(),
+        }),
+    ];
+    let source = render(&CONTRACTS[0], &spec).unwrap();
+
+    assert!(source.contains("pub type Error = acceslyinterface::Error<accountAbstraction::Error>;"));
+    assert!(source.contains("use acceslyinterface::{from_try, from_try_host};"));
+    assert!(source.contains("use soroban_sdk::{Address, BytesN, Env};"));
+    // A declared error goes through `from_try`, a plain return can only
+    // fail in the host
+    assert!(source.contains(
+        "pub fn remove_device(&self, public_key: &BytesN<32>, signature: &BytesN<64>) \
+         -> Result<(), Error> {\n        from_try(self.0.try_remove_device(public_key, signature))"
+    ));
+    assert!(source.contains(
+        "pub fn get_nonce(&self) -> Result<u64, Error> {\n        from_try_host(self.0.try_get_nonce())"
+    ));
+    assert!(!source.contains("check_auth"));
+    // Sorted by name, not spec order
+    assert!(source.find("get_nonce") < source.find("remove_device"));
+}
+
+#[test]
+fn test_render_bindings_without_error_enum() {
+    let spec = [spec_fn(
+        "ping",
+        &[],
+        ScSpecTypeDef::Result(Box::new(ScSpecTypeResult {
+            ok_type: Box::new(ScSpecTypeDef::U32),
+            error_type: Box::new(ScSpecTypeDef::Error),
+        })),
+    )];
+    let source = render(&CONTRACTS[4], &spec).unwrap();
+
+    assert!(source.contains("pub type Error = acceslyinterface::Error<core::convert::Infallible>;"));
+    assert!(source.contains("from_try_host(self.0.try_ping())"));
+    assert!(!source.contains("from_try("));
+}
+
+// ============================================================================
+// MANIFEST TESTS
+// ============================================================================
+
+#[test]
+fn test_derive_manifest() {
+    let spec = [
+        spec_fn("init", &[], ScSpecTypeDef::Void),
+        spec_fn("set_fiat_limit", &[], ScSpecTypeDef::Void),
+        spec_fn("__check_auth", &[], ScSpecTypeDef::Void),
+        ScSpecEntry::UdtErrorEnumV0(ScSpecUdtErrorEnumV0 {
+            doc: "".try_into().unwrap(),
+            lib: "".try_into().unwrap(),
+            name: "Error".try_into().unwrap(),
+            cases: vec![ScSpecUdtErrorEnumCaseV0 {
+                doc: "".try_into().unwrap(),
+                name: "NotInitialized".try_into().unwrap(),
+                value: 2,
+            }]
+            .try_into()
+            .unwrap(),
+        }),
+    ];
+    let wasm = b"\0asm..key_rotated..fiat_limit..";
+
+    let manifest = derive(wasm, &spec);
+    assert_eq!(manifest.entrypoints, ["init", "set_fiat_limit"]);
+    assert_eq!(manifest.errors, [2]);
+    assert!(manifest.events.contains(&"key_rotated".to_string()));
+    assert!(!manifest.events.contains(&"wallet_deployed".to_string()));
+    assert_eq!(manifest.policies, ["fiat_limit"]);
+}
+
+#[test]
+fn test_manifest_xdr_matches_the_contract_type() {
+    let manifest = CapabilityManifest {
+        entrypoints: vec!["init".into()],
+        errors: vec![1],
+        events: vec!["key_rotated".into()],
+        policies: vec!["fiat_limit".into()],
+    };
+    // `Manifest::to_xdr` of the same values, from the SDK
+    let expected = concat!(
+        "0000001100000001000000040000000f0000000b656e747279706f696e74730000000010",
+        "00000001000000010000000f00000004696e69740000000f000000066572726f72730000",
+        "00000010000000010000000100000003000000010000000f000000066576656e74730000",
+        "0000001000000001000000010000000f0000000b6b65795f726f7461746564000000000f",
+        "00000008706f6c69636965730000001000000001000000010000000f0000000a66696174",
+        "5f6c696d69740000",
+    );
+    let xdr = manifest_xdr(&manifest).unwrap();
+    assert_eq!(accesly_client::recovery_kit::hex(&xdr), expected);
+
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let wasm_hash = [1u8; 32];
+    let signature = Signature::from_bytes(&sign(&key, &wasm_hash, &xdr));
+    let mut message = b"accesly:manifest:".to_vec();
+    message.extend_from_slice(&wasm_hash);
+    message.extend_from_slice(&xdr);
+    assert!(key.verifying_key().verify(&message, &signature).is_ok());
+}
+
+// ============================================================================
+// WASM SIZE TESTS
+// ============================================================================
+
+#[test]
+fn test_wasm_size_budget() {
+    let repetitive = vec![0x61u8; 64 * 1024];
+    let gzipped = gzip_len(&repetitive).unwrap();
+    assert!(gzipped > 0 && gzipped < 1024);
+
+    assert!(check_budget(96 * 1024, 96).is_ok());
+    let err = check_budget(96 * 1024 + 512, 96).unwrap_err();
+    assert!(err.contains("0.5 KiB over the 96 KiB budget"), "{err}");
+}
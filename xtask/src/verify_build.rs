@@ -0,0 +1,195 @@
+// ---------------------------------------------------------------------------
+// `cargo xtask verify-build`
+//
+// Rebuilds a contract's WASM and checks that deployed instances run exactly
+// that code. The build is made path-independent (workspace and cargo home
+// are remapped, output goes to its own target dir) and uses the lockfile
+// as-is, so two machines on the same toolchain and lockfile get the same
+// bytes. The toolchain that produced a WASM is recorded by the SDK in the
+// `contractmetav0` section; on drift, the deployed code is downloaded and its
+// metadata compared with the local build to point at the cause.
+//
+// There is no factory registry to enumerate deployments from, so the
+// contracts to check are passed explicitly.
+// ---------------------------------------------------------------------------
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use accesly_client::wasm_meta::{meta_value, read_contract_meta, BuildInfo};
+use clap::Args;
+use sha2::{Digest, Sha256};
+
+use crate::onchain::{
+    code_ledger_key, decode_contract_id, get_ledger_entry, instance_ledger_key,
+    wasm_from_code_entry, wasm_hash_from_instance,
+};
+
+const WASM_TARGET: &str = "wasm32v1-none";
+const TARGET_DIR: &str = "target/reproducible";
+
+#[derive(Args, Debug)]
+pub struct VerifyBuildArgs {
+    /// Contract package to build
+    #[arg(long, default_value = "accountAbstraction")]
+    pub package: String,
+    /// Deployed contract (C...) expected to run this build; repeatable
+    #[arg(long = "contract")]
+    pub contracts: Vec<String>,
+    /// Soroban RPC endpoint, needed when contracts are given
+    #[arg(long, env = "ACCESLY_RPC_URL")]
+    pub rpc_url: Option<String>,
+    /// Toolchain passed to cargo as `+<toolchain>`
+    #[arg(long)]
+    pub toolchain: Option<String>,
+    /// Check this WASM instead of building
+    #[arg(long)]
+    pub wasm: Option<PathBuf>,
+}
+
+pub fn run(args: VerifyBuildArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let wasm_path = match &args.wasm {
+        Some(path) => path.clone(),
+        None => build(&workspace, &args.package, args.toolchain.as_deref())?,
+    };
+
+    let wasm = std::fs::read(&wasm_path)?;
+    let hash: [u8; 32] = Sha256::digest(&wasm).into();
+    let meta = read_contract_meta(&wasm)?;
+
+    println!("wasm      {}", wasm_path.display());
+    println!("sha256    {}", hex(&hash));
+    for key in ["version", "commit", "features", "rsver", "rssdkver"] {
+        println!("{key:<9} {}", meta_value(&meta, key).unwrap_or("-"));
+    }
+    if BuildInfo::from_meta(&meta).is_dirty() {
+        println!("warning: built from uncommitted changes; deployments can't match");
+    }
+    match std::fs::read(workspace.join("Cargo.lock")) {
+        Ok(lock) => println!("lockfile  {}", hex(&Sha256::digest(lock))),
+        Err(_) => println!("lockfile  none (dependency versions are not pinned)"),
+    }
+
+    if args.contracts.is_empty() {
+        return Ok(());
+    }
+    let rpc_url = args
+        .rpc_url
+        .as_deref()
+        .ok_or("--rpc-url (or ACCESLY_RPC_URL) is required to check contracts")?;
+
+    let mut drifted = 0;
+    println!();
+    for contract in &args.contracts {
+        let id = decode_contract_id(contract)?;
+        let instance = get_ledger_entry(rpc_url, &instance_ledger_key(&id))?
+            .ok_or_else(|| format!("{contract}: contract not found"))?;
+        let deployed = wasm_hash_from_instance(&instance)?;
+
+        if deployed == hash {
+            println!("{contract}  ok");
+            continue;
+        }
+
+        drifted += 1;
+        println!("{contract}  DRIFT  deployed {}", hex(&deployed));
+        match get_ledger_entry(rpc_url, &code_ledger_key(&deployed))? {
+            None => println!("  deployed code is archived; metadata unavailable"),
+            Some(entry) => {
+                let deployed_meta = read_contract_meta(&wasm_from_code_entry(&entry)?)?;
+                for line in describe_drift(&meta, &deployed_meta) {
+                    println!("  {line}");
+                }
+            }
+        }
+    }
+
+    if drifted > 0 {
+        return Err(format!("{drifted} of {} contracts drifted", args.contracts.len()).into());
+    }
+    Ok(())
+}
+
+/// Run the reproducible build and return the produced WASM
+pub fn build(
+    workspace: &Path,
+    package: &str,
+    toolchain: Option<&str>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let workspace = workspace.canonicalize()?;
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")))
+        .ok_or("cannot locate cargo home")?;
+
+    // Absolute paths otherwise end up in panic locations inside the WASM
+    let rustflags = [
+        format!("--remap-path-prefix={}=/build", workspace.display()),
+        format!("--remap-path-prefix={}=/cargo", cargo_home.display()),
+    ]
+    .join("\x1f");
+
+    let mut cargo = Command::new("cargo");
+    if let Some(toolchain) = toolchain {
+        cargo.arg(format!("+{toolchain}"));
+    }
+    cargo
+        .args([
+            "build",
+            "--release",
+            "--target",
+            WASM_TARGET,
+            "--package",
+            package,
+        ])
+        .args(["--target-dir", TARGET_DIR])
+        .current_dir(&workspace)
+        .env("CARGO_ENCODED_RUSTFLAGS", rustflags);
+    if workspace.join("Cargo.lock").exists() {
+        cargo.arg("--locked");
+    }
+
+    if !cargo.status()?.success() {
+        return Err("contract build failed".into());
+    }
+    Ok(workspace
+        .join(TARGET_DIR)
+        .join(WASM_TARGET)
+        .join("release")
+        .join(format!("{}.wasm", package.replace('-', "_"))))
+}
+
+/// Metadata entries whose values differ between the local and deployed WASM
+pub fn describe_drift(local: &[(String, String)], deployed: &[(String, String)]) -> Vec<String> {
+    let mut keys: Vec<&str> = local
+        .iter()
+        .chain(deployed)
+        .map(|(k, _)| k.as_str())
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    let lines: Vec<String> = keys
+        .into_iter()
+        .filter_map(|key| {
+            let (l, d) = (meta_value(local, key), meta_value(deployed, key));
+            (l != d).then(|| {
+                format!(
+                    "{key}: local {} / deployed {}",
+                    l.unwrap_or("-"),
+                    d.unwrap_or("-")
+                )
+            })
+        })
+        .collect();
+    if lines.is_empty() {
+        vec!["same metadata; source or dependencies differ".to_string()]
+    } else {
+        lines
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,94 @@
+// ---------------------------------------------------------------------------
+// `cargo xtask wasm-size`
+//
+// Size budget for the browser build of accesly-wasm. Builds it for
+// wasm32-unknown-unknown with the workspace release profile and fails when
+// the module, gzipped the way a CDN would serve it, is over budget. The
+// module is measured before wasm-bindgen strips its custom sections, so the
+// shipped file only ever comes in under what's reported here.
+// ---------------------------------------------------------------------------
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::Args;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const PACKAGE: &str = "accesly-wasm";
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+/// Gzipped KiB the recovery flow can add to an app's bundle
+pub const DEFAULT_BUDGET_KIB: u64 = 96;
+
+#[derive(Args, Debug)]
+pub struct WasmSizeArgs {
+    /// Largest allowed gzipped size, in KiB
+    #[arg(long, default_value_t = DEFAULT_BUDGET_KIB)]
+    pub budget_kib: u64,
+    /// Measure this WASM instead of building
+    #[arg(long)]
+    pub wasm: Option<PathBuf>,
+}
+
+pub fn run(args: WasmSizeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let wasm_path = match &args.wasm {
+        Some(path) => path.clone(),
+        None => build(&Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))?,
+    };
+
+    let wasm = std::fs::read(&wasm_path)?;
+    let gzipped = gzip_len(&wasm)?;
+    println!("wasm      {}", wasm_path.display());
+    println!("raw       {}", kib(wasm.len() as u64));
+    println!("gzip      {}", kib(gzipped));
+    println!("budget    {} KiB gzipped", args.budget_kib);
+
+    check_budget(gzipped, args.budget_kib)?;
+    Ok(())
+}
+
+fn build(workspace: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let status = Command::new("cargo")
+        .args([
+            "build",
+            "--release",
+            "--target",
+            WASM_TARGET,
+            "--package",
+            PACKAGE,
+        ])
+        .current_dir(workspace)
+        .status()?;
+    if !status.success() {
+        return Err(format!("{PACKAGE} build failed").into());
+    }
+    Ok(workspace
+        .join("target")
+        .join(WASM_TARGET)
+        .join("release")
+        .join(format!("{}.wasm", PACKAGE.replace('-', "_"))))
+}
+
+/// Size of `bytes` at the best gzip level
+pub fn gzip_len(bytes: &[u8]) -> std::io::Result<u64> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?.len() as u64)
+}
+
+pub fn check_budget(gzipped: u64, budget_kib: u64) -> Result<(), String> {
+    let budget = budget_kib * 1024;
+    if gzipped > budget {
+        return Err(format!(
+            "{PACKAGE} is {} gzipped, {} over the {budget_kib} KiB budget",
+            kib(gzipped),
+            kib(gzipped - budget)
+        ));
+    }
+    Ok(())
+}
+
+fn kib(bytes: u64) -> String {
+    format!("{:.1} KiB", bytes as f64 / 1024.0)
+}